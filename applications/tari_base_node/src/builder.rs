@@ -76,10 +76,7 @@ use tari_core::{
     },
     mining::Miner,
     tari_utilities::{hex::Hex, message_format::MessageFormat},
-    transactions::{
-        crypto::keys::SecretKey as SK,
-        types::{CryptoFactories, HashDigest, PrivateKey, PublicKey},
-    },
+    transactions::types::{CryptoFactories, HashDigest, PublicKey},
     validation::{
         accum_difficulty_validators::AccumDifficultyValidator,
         block_validators::{FullConsensusValidator, StatelessBlockValidator},
@@ -330,6 +327,7 @@ pub fn load_identity(path: &Path) -> Result<NodeIdentity, String> {
 /// `path` - Reference to path to save the file
 /// `public_addr` - Network address of the base node
 /// `peer_features` - The features enabled for the base node
+/// `pow_difficulty` - The DHT proof-of-work difficulty the identity must meet
 ///
 /// ## Returns
 /// Result containing the node identity, string will indicate reason on error
@@ -337,10 +335,10 @@ pub fn create_new_base_node_identity<P: AsRef<Path>>(
     path: P,
     public_addr: Multiaddr,
     features: PeerFeatures,
+    pow_difficulty: u32,
 ) -> Result<NodeIdentity, String>
 {
-    let private_key = PrivateKey::random(&mut OsRng);
-    let node_identity = NodeIdentity::new(private_key, public_addr, features)
+    let node_identity = NodeIdentity::random_with_proof_of_work(&mut OsRng, public_addr, features, pow_difficulty)
         .map_err(|e| format!("We were unable to construct a node identity. {}", e.to_string()))?;
     save_as_json(path, &node_identity)?;
     Ok(node_identity)
@@ -475,8 +473,10 @@ where
         pruning_horizon: config.pruning_horizon,
     };
     let db = BlockchainDatabase::new(backend, &rules, validators, db_config).map_err(|e| e.to_string())?;
-    let mempool_validator =
-        MempoolValidators::new(FullTxValidator::new(factories.clone()), TxInputAndMaturityValidator {});
+    let mempool_validator = MempoolValidators::new(
+        FullTxValidator::new(factories.clone(), &rules.consensus_constants()),
+        TxInputAndMaturityValidator {},
+    );
     let mempool = Mempool::new(db.clone(), MempoolConfig::default(), mempool_validator);
     let handle = runtime::Handle::current();
 
@@ -964,6 +964,7 @@ async fn setup_base_node_comms(
         outbound_buffer_size: 100,
         // TODO - make this configurable
         dht: DhtConfig {
+            pow_difficulty: config.dht_pow_difficulty,
             database_url: DbConnectionUrl::File(config.data_dir.join("dht.db")),
             ..Default::default()
         },
@@ -1015,6 +1016,7 @@ async fn setup_wallet_comms(
         outbound_buffer_size: 100,
         // TODO - make this configurable
         dht: DhtConfig {
+            pow_difficulty: config.dht_pow_difficulty,
             database_url: DbConnectionUrl::File(config.data_dir.join("dht-wallet.db")),
             ..Default::default()
         },
@@ -1104,11 +1106,13 @@ where
             mempool.clone(),
             consensus_manager,
             node_config,
+            comms.peer_manager(),
         ))
         .add_initializer(MempoolServiceInitializer::new(
             subscription_factory.clone(),
             mempool,
             mempool_config,
+            comms.peer_manager(),
         ))
         .add_initializer(LivenessInitializer::new(
             LivenessConfig {
@@ -1165,6 +1169,7 @@ async fn register_wallet_services(
             subscription_factory.clone(),
             OutputManagerSqliteDatabase::new(wallet_db_conn.clone()),
             factories.clone(),
+            wallet_comms.node_identity(),
         ))
         .add_initializer(TransactionServiceInitializer::new(
             TransactionServiceConfig::default(),
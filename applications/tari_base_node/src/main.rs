@@ -83,6 +83,8 @@ mod builder;
 mod cli;
 /// Application-specific constants
 mod grpc;
+/// Prometheus metrics HTTP endpoint
+mod metrics;
 /// Miner lib Todo hide behind feature flag
 mod miner;
 /// Parser module used to control user commands
@@ -92,6 +94,7 @@ mod utils;
 use crate::builder::{create_new_base_node_identity, load_identity};
 use log::*;
 use parser::Parser;
+use rand::{rngs::OsRng, RngCore};
 use rustyline::{config::OutputStreamType, error::ReadlineError, CompletionType, Config, EditMode, Editor};
 use std::{net::SocketAddr, path::PathBuf, sync::Arc};
 use structopt::StructOpt;
@@ -160,12 +163,14 @@ fn main_inner() -> Result<(), ExitCodes> {
             // If the base node identity exists, we want to be sure that the wallet identity exists
             node_config.identity_file.exists(),
         PeerFeatures::COMMUNICATION_CLIENT,
+        node_config.dht_pow_difficulty,
     )?;
     let node_identity = setup_node_identity(
         &node_config.identity_file,
         &node_config.public_address,
         bootstrap.create_id,
         PeerFeatures::COMMUNICATION_NODE,
+        node_config.dht_pow_difficulty,
     )?;
 
     // Build, node, build!
@@ -206,6 +211,14 @@ fn main_inner() -> Result<(), ExitCodes> {
 
         rt.spawn(run_grpc(grpc, node_config.grpc_address));
     }
+    if node_config.metrics_enabled {
+        let metrics_address = node_config.metrics_address;
+        rt.spawn(async move {
+            if let Err(err) = metrics::run_metrics_server(metrics_address).await {
+                error!(target: LOG_TARGET, "{}", err);
+            }
+        });
+    }
     let base_node_handle = rt.spawn(ctx.run(rt.handle().clone()));
 
     info!(
@@ -318,6 +331,7 @@ fn cli_loop(parser: Parser, mut shutdown: Shutdown) {
 /// `public_address` - Network address of the base node
 /// `create_id` - Whether an identity needs to be created or not
 /// `peer_features` - Enables features of the base node
+/// `pow_difficulty` - The DHT proof-of-work difficulty the identity must meet
 ///
 /// # Return
 /// A NodeIdentity wrapped in an atomic reference counter on success, the exit code indicating the reason on failure
@@ -326,10 +340,28 @@ fn setup_node_identity(
     public_address: &Multiaddr,
     create_id: bool,
     peer_features: PeerFeatures,
+    pow_difficulty: u32,
 ) -> Result<Arc<NodeIdentity>, ExitCodes>
 {
     match load_identity(identity_file) {
-        Ok(id) => Ok(Arc::new(id)),
+        Ok(mut id) => {
+            // The identity is saved again once comms has been initialized
+            if !id.meets_proof_of_work_difficulty(pow_difficulty) {
+                info!(
+                    target: LOG_TARGET,
+                    "Generating a proof-of-work with difficulty {} for the node identity", pow_difficulty
+                );
+                id.generate_proof_of_work(OsRng.next_u64(), pow_difficulty)
+                    .map_err(|e| {
+                        error!(
+                            target: LOG_TARGET,
+                            "Could not generate a node id proof-of-work. {:?}.", e
+                        );
+                        ExitCodes::ConfigError
+                    })?;
+            }
+            Ok(Arc::new(id))
+        },
         Err(e) => {
             if !create_id {
                 error!(
@@ -343,7 +375,7 @@ fn setup_node_identity(
 
             debug!(target: LOG_TARGET, "Node id not found. {}. Creating new ID", e);
 
-            match create_new_base_node_identity(identity_file, public_address.clone(), peer_features) {
+            match create_new_base_node_identity(identity_file, public_address.clone(), peer_features, pow_difficulty) {
                 Ok(id) => {
                     info!(
                         target: LOG_TARGET,
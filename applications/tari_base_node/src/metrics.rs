@@ -0,0 +1,63 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use futures::future;
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body,
+    Method,
+    Request,
+    Response,
+    Server,
+    StatusCode,
+};
+use log::*;
+use std::{convert::Infallible, net::SocketAddr};
+
+const LOG_TARGET: &str = "base_node::app::metrics";
+
+/// Runs an HTTP server which serves the node metrics in the Prometheus text format on `/metrics`
+pub async fn run_metrics_server(address: SocketAddr) -> Result<(), String> {
+    info!(target: LOG_TARGET, "Starting metrics server on {}", address);
+
+    let make_service = make_service_fn(|_| future::ok::<_, Infallible>(service_fn(handle_request)));
+    Server::try_bind(&address)
+        .map_err(|e| format!("Failed to bind metrics server to {}: {}", address, e))?
+        .serve(make_service)
+        .await
+        .map_err(|e| format!("Metrics server returned error: {}", e))?;
+
+    info!(target: LOG_TARGET, "Stopping metrics server");
+    Ok(())
+}
+
+async fn handle_request(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let response = match (req.method(), req.uri().path()) {
+        (&Method::GET, "/metrics") => Response::builder()
+            .header(CONTENT_TYPE, tari_metrics::TEXT_CONTENT_TYPE)
+            .body(Body::from(tari_metrics::encode_text())),
+        _ => Response::builder().status(StatusCode::NOT_FOUND).body(Body::empty()),
+    };
+
+    Ok(response.expect("Response builder only fails if given invalid headers"))
+}
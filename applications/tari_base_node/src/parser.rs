@@ -133,6 +133,8 @@ pub struct Parser {
     miner_thread_count: u64,
 }
 
+const MANUAL_BAN_REASON: &str = "Banned by the node operator";
+
 const MAKE_IT_RAIN_USAGE: &str = "\nmake-it-rain [Txs/s] [duration (s)] [start amount (uT)] [increment (uT)/Tx] \
                                   [\"start time (UTC)\" / 'now' for immediate start] [public key or emoji id to send \
                                   to] [message]\n       or\nmake-it-rain [Txs/s] [duration (s)] [start amount (uT)] \
@@ -371,7 +373,10 @@ impl Parser {
                 println!("Checks the blockchain database for missing blocks and headers");
             },
             ListConnections => {
-                println!("Lists the peer connections currently held by this node");
+                println!(
+                    "Lists the peer connections currently held by this node, with traffic totals per connection and \
+                     protocol"
+                );
             },
             ListHeaders => {
                 println!("List the amount of headers, can be called in the following two ways: ");
@@ -794,7 +799,15 @@ impl Parser {
                             }
 
                             if let Some(dt) = peer.banned_until() {
-                                s.push(format!("BANNED until {}", format_naive_datetime(dt)));
+                                if peer.banned_reason.is_empty() {
+                                    s.push(format!("BANNED until {}", format_naive_datetime(dt)));
+                                } else {
+                                    s.push(format!(
+                                        "BANNED until {} ({})",
+                                        format_naive_datetime(dt),
+                                        peer.banned_reason
+                                    ));
+                                }
                             }
                             s.join(", ")
                         };
@@ -861,7 +874,10 @@ impl Parser {
 
         self.executor.spawn(async move {
             if must_ban {
-                match peer_manager.ban_for(&public_key, duration).await {
+                match peer_manager
+                    .ban_for(&public_key, duration, MANUAL_BAN_REASON.to_string())
+                    .await
+                {
                     Ok(node_id) => match connection_manager.disconnect_peer(node_id).await {
                         Ok(_) => {
                             println!("Peer was banned in base node.");
@@ -882,7 +898,10 @@ impl Parser {
                     },
                 }
 
-                match wallet_peer_manager.ban_for(&public_key, duration).await {
+                match wallet_peer_manager
+                    .ban_for(&public_key, duration, MANUAL_BAN_REASON.to_string())
+                    .await
+                {
                     Ok(node_id) => match connection_manager.disconnect_peer(node_id).await {
                         Ok(_) => {
                             println!("Peer was banned in wallet.");
@@ -946,12 +965,32 @@ impl Parser {
                     println!();
                     let num_connections = conns.len();
                     let mut table = Table::new();
-                    table.set_titles(vec!["NodeId", "Public Key", "Address", "Direction", "Uptime", "Role"]);
+                    table.set_titles(vec![
+                        "NodeId",
+                        "Public Key",
+                        "Address",
+                        "Direction",
+                        "Uptime",
+                        "Role",
+                        "Bytes In",
+                        "Bytes Out",
+                    ]);
+                    let mut protocol_table = Table::new();
+                    protocol_table.set_titles(vec![
+                        "NodeId",
+                        "Protocol",
+                        "Substreams",
+                        "Msgs In",
+                        "Msgs Out",
+                        "Bytes In",
+                        "Bytes Out",
+                    ]);
                     for conn in conns {
                         let peer = peer_manager
                             .find_by_node_id(conn.peer_node_id())
                             .await
                             .expect("Unexpected peer database error or peer not found");
+                        let connection_traffic = conn.traffic().connection().snapshot();
 
                         table.add_row(row![
                             peer.node_id.short_str(),
@@ -966,10 +1005,26 @@ impl Parser {
                                     "Base node"
                                 }
                             },
+                            connection_traffic.bytes_in,
+                            connection_traffic.bytes_out,
                         ]);
+
+                        for (protocol, stats) in conn.traffic().protocol_stats() {
+                            protocol_table.add_row(row![
+                                peer.node_id.short_str(),
+                                String::from_utf8_lossy(&protocol),
+                                stats.substreams,
+                                stats.messages_in,
+                                stats.messages_out,
+                                stats.bytes_in,
+                                stats.bytes_out,
+                            ]);
+                        }
                     }
 
                     table.print_std();
+                    println!();
+                    protocol_table.print_std();
 
                     println!("{} active connection(s)", num_connections);
                 },
@@ -0,0 +1,473 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::LOG_TARGET;
+use log::*;
+use rand::rngs::OsRng;
+use std::{fs, path::Path, sync::Arc};
+use tari_common::{CommsTransport, GlobalConfig, SocksAuthentication, TorControlAuthentication};
+use tari_comms::{
+    multiaddr::{Multiaddr, Protocol},
+    peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerFlags},
+    socks,
+    tor,
+    tor::TorIdentity,
+    transports::SocksConfig,
+    types::CommsPublicKey,
+    utils::multiaddr::multiaddr_to_socketaddr,
+};
+use tari_comms_dht::{DbConnectionUrl, DhtConfig};
+use tari_core::{
+    tari_utilities::{hex::Hex, message_format::MessageFormat},
+    transactions::types::CryptoFactories,
+};
+use tari_p2p::{
+    initialization::CommsConfig,
+    transport::{TorConfig, TransportType},
+};
+use tari_wallet::{
+    contacts_service::storage::sqlite_db::ContactsServiceSqliteDatabase,
+    output_manager_service::{config::OutputManagerServiceConfig, storage::sqlite_db::OutputManagerSqliteDatabase},
+    storage::{connection_manager::run_migration_and_create_sqlite_connection, sqlite_db::WalletSqliteDatabase},
+    transaction_service::storage::sqlite_db::TransactionServiceSqliteDatabase,
+    wallet::{Wallet, WalletConfig},
+};
+use tokio::runtime::Runtime;
+
+/// The wallet type used by the console wallet, backed by a single SQLite database
+pub type WalletSqlite = Wallet<
+    WalletSqliteDatabase,
+    TransactionServiceSqliteDatabase,
+    OutputManagerSqliteDatabase,
+    ContactsServiceSqliteDatabase,
+>;
+
+/// Loads the node identity from a json file at the given path
+/// ## Parameters
+/// `path` - Reference to a path
+///
+/// ## Returns
+/// Result containing a NodeIdentity on success, string indicates the reason on failure
+pub fn load_identity(path: &Path) -> Result<NodeIdentity, String> {
+    if !path.exists() {
+        return Err(format!("Identity file, {}, does not exist.", path.to_str().unwrap()));
+    }
+
+    let id_str = fs::read_to_string(path).map_err(|e| {
+        format!(
+            "The node identity file, {}, could not be read. {}",
+            path.to_str().unwrap_or("?"),
+            e.to_string()
+        )
+    })?;
+    let id = NodeIdentity::from_json(&id_str).map_err(|e| {
+        format!(
+            "The node identity file, {}, has an error. {}",
+            path.to_str().unwrap_or("?"),
+            e.to_string()
+        )
+    })?;
+    info!(
+        target: LOG_TARGET,
+        "Wallet node ID loaded with public key {} and Node id {}",
+        id.public_key().to_hex(),
+        id.node_id().to_hex()
+    );
+    Ok(id)
+}
+
+/// Create a new wallet node identity and save it to disk
+/// ## Parameters
+/// `path` - Reference to path to save the file
+/// `public_addr` - Network address of the wallet
+/// `pow_difficulty` - The DHT proof-of-work difficulty the identity must meet
+///
+/// ## Returns
+/// Result containing the node identity, string will indicate reason on error
+pub fn create_new_wallet_identity<P: AsRef<Path>>(
+    path: P,
+    public_addr: Multiaddr,
+    pow_difficulty: u32,
+) -> Result<NodeIdentity, String>
+{
+    let node_identity = NodeIdentity::random_with_proof_of_work(
+        &mut OsRng,
+        public_addr,
+        PeerFeatures::COMMUNICATION_CLIENT,
+        pow_difficulty,
+    )
+    .map_err(|e| format!("We were unable to construct a node identity. {}", e.to_string()))?;
+    save_as_json(path, &node_identity)?;
+    Ok(node_identity)
+}
+
+/// Loads an object from json at the given path
+/// ## Parameters
+/// `path` - Path to file from which to load the object
+///
+/// ## Returns
+/// Result containing an object on success, string will indicate reason on error
+pub fn load_from_json<P: AsRef<Path>, T: MessageFormat>(path: P) -> Result<T, String> {
+    if !path.as_ref().exists() {
+        return Err(format!("File, {}, does not exist.", path.as_ref().to_str().unwrap()));
+    }
+
+    let contents = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let object = T::from_json(&contents).map_err(|err| err.to_string())?;
+    Ok(object)
+}
+
+/// Saves an object as json at a given path, creating the parent directory if it does not already exist
+/// ## Parameters
+/// `path` - Path to save the file
+/// `object` - Data to be saved
+///
+/// ## Returns
+/// Result to check if successful or not, string will indicate reason on error
+pub fn save_as_json<P: AsRef<Path>, T: MessageFormat>(path: P, object: &T) -> Result<(), String> {
+    let json = object.to_json().unwrap();
+    if let Some(p) = path.as_ref().parent() {
+        if !p.exists() {
+            fs::create_dir_all(p).map_err(|e| format!("Could not save json to data folder. {}", e.to_string()))?;
+        }
+    }
+    fs::write(path.as_ref(), json.as_bytes()).map_err(|e| {
+        format!(
+            "Error writing json file, {}. {}",
+            path.as_ref().to_str().unwrap_or("<invalid UTF-8>"),
+            e.to_string()
+        )
+    })?;
+
+    Ok(())
+}
+
+/// Parses a peer in the `<public key>::<address>` format used for base nodes and peer seeds
+/// ## Parameters
+/// `peer` - The delimited peer string
+///
+/// ## Returns
+/// The public key and address of the base node, string will indicate the reason on error
+pub fn parse_base_node_peer(peer: &str) -> Result<(CommsPublicKey, Multiaddr), String> {
+    let parts: Vec<&str> = peer.split("::").map(|s| s.trim()).collect();
+    if parts.len() != 2 {
+        return Err(format!(
+            "Invalid base node peer '{}'. Expected the format <public key>::<address>",
+            peer
+        ));
+    }
+    let public_key = CommsPublicKey::from_hex(parts[0]).map_err(|e| {
+        format!(
+            "{} is not a valid base node peer. The public key is incorrect. {}",
+            peer, e
+        )
+    })?;
+    let address = parts[1].parse::<Multiaddr>().map_err(|e| {
+        format!(
+            "{} is not a valid base node peer. The address is incorrect. {}",
+            peer, e
+        )
+    })?;
+    Ok((public_key, address))
+}
+
+/// Sets up the tokio runtime based on the configuration
+/// ## Parameters
+/// `config` - The global configuration
+///
+/// ## Returns
+/// A result containing the runtime on success, string indicating the error on failure
+pub fn setup_runtime(config: &GlobalConfig) -> Result<Runtime, String> {
+    debug!(
+        target: LOG_TARGET,
+        "Configuring the wallet to run on {} core threads and {} blocking worker threads.",
+        config.core_threads,
+        config.blocking_threads
+    );
+    tokio::runtime::Builder::new()
+        .threaded_scheduler()
+        .enable_all()
+        .max_threads(config.core_threads + config.blocking_threads)
+        .core_threads(config.core_threads)
+        .build()
+        .map_err(|e| {
+            format!(
+                "There was an error while building the wallet runtime. {}",
+                e.to_string()
+            )
+        })
+}
+
+/// Creates the wallet, starting its comms stack and services, and adds the given base nodes to it. The configured peer
+/// seeds are added to the peer database for DHT discovery only and are not used as base nodes. The console wallet
+/// uses its own database, peer database and identity files, so that it can run alongside a base node with an embedded
+/// wallet using the same configuration.
+/// ## Parameters
+/// `config` - The configuration in which to set up the wallet, see [GlobalConfig]
+/// `node_identity` - The node identity of the wallet
+/// `runtime` - The runtime the wallet services will run on
+/// `base_node_peers` - The base nodes the wallet will use, in order of preference
+/// `watch_only` - Whether the wallet only tracks watched outputs and refuses spending operations
+///
+/// ## Returns
+/// A Result containing the wallet on success, string will indicate the reason on error
+pub fn create_wallet(
+    config: &GlobalConfig,
+    node_identity: Arc<NodeIdentity>,
+    runtime: Runtime,
+    base_node_peers: Vec<(CommsPublicKey, Multiaddr)>,
+    watch_only: bool,
+) -> Result<WalletSqlite, String>
+{
+    create_folder(&config.console_wallet_peer_db_path)?;
+    create_folder(
+        &config
+            .console_wallet_db_file
+            .parent()
+            .expect("console_wallet_file cannot be set to a root directory"),
+    )?;
+
+    let connection = run_migration_and_create_sqlite_connection(&config.console_wallet_db_file)
+        .map_err(|e| format!("Could not open the wallet database: {:?}", e))?;
+
+    let comms_config = CommsConfig {
+        node_identity,
+        transport_type: setup_transport_type(&config),
+        datastore_path: config.console_wallet_peer_db_path.clone(),
+        peer_database_name: "peers".to_string(),
+        max_concurrent_inbound_tasks: 100,
+        outbound_buffer_size: 100,
+        // TODO - make this configurable
+        dht: DhtConfig {
+            pow_difficulty: config.dht_pow_difficulty,
+            database_url: DbConnectionUrl::File(config.data_dir.join("dht-console-wallet.db")),
+            ..Default::default()
+        },
+        // TODO: This should be false unless testing locally - make this configurable
+        allow_test_addresses: true,
+        listener_liveness_whitelist_cidrs: Vec::new(),
+        listener_liveness_max_sessions: 0,
+    };
+    let wallet_config = WalletConfig {
+        comms_config,
+        factories: CryptoFactories::default(),
+        transaction_service_config: None,
+        output_manager_service_config: Some(OutputManagerServiceConfig {
+            watch_only,
+            ..Default::default()
+        }),
+        base_node_service_config: None,
+    };
+
+    let mut wallet = Wallet::new(
+        wallet_config,
+        runtime,
+        WalletSqliteDatabase::new(connection.clone()),
+        TransactionServiceSqliteDatabase::new(connection.clone()),
+        OutputManagerSqliteDatabase::new(connection.clone()),
+        ContactsServiceSqliteDatabase::new(connection),
+    )
+    .map_err(|e| format!("Could not create the wallet: {:?}", e))?;
+
+    // Save final node identity after comms has initialized. This is required because the public_address can be changed
+    // by comms during initialization when using tor.
+    save_as_json(&config.console_wallet_identity_file, &*wallet.comms.node_identity())
+        .map_err(|e| format!("Failed to save node identity: {:?}", e))?;
+    if let Some(hs) = wallet.comms.hidden_service() {
+        save_as_json(&config.console_wallet_tor_identity_file, hs.tor_identity())
+            .map_err(|e| format!("Failed to save tor identity: {:?}", e))?;
+    }
+
+    for seed in &config.peer_seeds {
+        match parse_base_node_peer(seed) {
+            Ok((public_key, address)) => add_seed_peer(&mut wallet, public_key, address)?,
+            Err(err) => warn!(target: LOG_TARGET, "{}", err),
+        }
+    }
+
+    for (public_key, address) in base_node_peers {
+        info!(target: LOG_TARGET, "Adding base node peer {}::{}", public_key, address);
+        wallet
+            .add_base_node_peer(public_key, address.to_string())
+            .map_err(|e| format!("Could not add base node peer: {:?}", e))?;
+    }
+
+    Ok(wallet)
+}
+
+/// Adds a peer seed to the wallet's peer database so that it can be used to join the DHT
+fn add_seed_peer(wallet: &mut WalletSqlite, public_key: CommsPublicKey, address: Multiaddr) -> Result<(), String> {
+    if &public_key == wallet.comms.node_identity().public_key() {
+        info!(
+            target: LOG_TARGET,
+            "Attempting to add yourself [{}] as a seed peer, ignoring request", public_key
+        );
+        return Ok(());
+    }
+    let node_id = NodeId::from_key(&public_key).map_err(|e| format!("Invalid seed peer {}: {}", public_key, e))?;
+    info!(target: LOG_TARGET, "Adding seed peer {}::{}", public_key, address);
+    let peer = Peer::new(
+        public_key,
+        node_id,
+        address.into(),
+        PeerFlags::default(),
+        PeerFeatures::COMMUNICATION_NODE,
+        &[],
+    );
+    let peer_manager = wallet.comms.peer_manager();
+    wallet
+        .runtime
+        .block_on(peer_manager.add_peer(peer))
+        .map_err(|e| format!("Could not add seed peer: {}", e))?;
+    Ok(())
+}
+
+/// Creates a transport type for the wallet using the provided configuration. The console wallet listens two ports
+/// above the configured base node port, leaving the port above it to the base node's embedded wallet, so that it can
+/// run alongside a base node using the same configuration file.
+/// ## Paramters
+/// `config` - The reference to the configuration in which to set up the comms stack, see [GlobalConfig]
+///
+/// ##Returns
+/// TransportType based on the configuration
+fn setup_transport_type(config: &GlobalConfig) -> TransportType {
+    debug!(
+        target: LOG_TARGET,
+        "Wallet transport is set to '{:?}'", config.comms_transport
+    );
+
+    let add_to_port = |addr: Multiaddr, n| -> Multiaddr {
+        addr.iter()
+            .map(|p| match p {
+                Protocol::Tcp(port) => Protocol::Tcp(port + n),
+                p => p,
+            })
+            .collect()
+    };
+
+    match config.comms_transport.clone() {
+        CommsTransport::Tcp {
+            listener_address,
+            tor_socks_address,
+            tor_socks_auth,
+        } => TransportType::Tcp {
+            listener_address: add_to_port(listener_address, 2),
+            tor_socks_config: tor_socks_address.map(|proxy_address| SocksConfig {
+                proxy_address,
+                authentication: tor_socks_auth.map(into_socks_authentication).unwrap_or_default(),
+            }),
+        },
+        CommsTransport::TorHiddenService {
+            control_server_address,
+            socks_address_override,
+            forward_address,
+            auth,
+            onion_port,
+        } => {
+            let tor_identity_path = Path::new(&config.console_wallet_tor_identity_file);
+            let identity = if tor_identity_path.exists() {
+                // If this fails, we can just use another address
+                load_from_json::<_, TorIdentity>(&tor_identity_path).ok()
+            } else {
+                None
+            };
+            info!(
+                target: LOG_TARGET,
+                "Wallet tor identity at path '{}' {:?}",
+                tor_identity_path.to_string_lossy(),
+                identity
+                    .as_ref()
+                    .map(|ident| format!("loaded for address '{}.onion'", ident.service_id))
+                    .or_else(|| Some("not found".to_string()))
+                    .unwrap()
+            );
+
+            let mut forward_addr = multiaddr_to_socketaddr(&forward_address).expect("Invalid tor forward address");
+            forward_addr.set_port(forward_addr.port() + 2);
+            TransportType::Tor(TorConfig {
+                control_server_addr: control_server_address,
+                control_server_auth: {
+                    match auth {
+                        TorControlAuthentication::None => tor::Authentication::None,
+                        TorControlAuthentication::Password(password) => tor::Authentication::HashedPassword(password),
+                    }
+                },
+                identity: identity.map(Box::new),
+                port_mapping: (onion_port.get() + 2, forward_addr).into(),
+                // TODO: make configurable
+                socks_address_override,
+                socks_auth: socks::Authentication::None,
+            })
+        },
+        CommsTransport::Socks5 {
+            proxy_address,
+            listener_address,
+            auth,
+        } => TransportType::Socks {
+            socks_config: SocksConfig {
+                proxy_address,
+                authentication: into_socks_authentication(auth),
+            },
+            listener_address: add_to_port(listener_address, 2),
+        },
+    }
+}
+
+/// Converts one socks authentication struct into another
+fn into_socks_authentication(auth: SocksAuthentication) -> socks::Authentication {
+    match auth {
+        SocksAuthentication::None => socks::Authentication::None,
+        SocksAuthentication::UsernamePassword(username, password) => {
+            socks::Authentication::Password(username, password)
+        },
+    }
+}
+
+/// Creates the given directory if it does not already exist
+fn create_folder<P: AsRef<Path>>(path: P) -> Result<(), String> {
+    let path = path.as_ref();
+    match fs::create_dir_all(path) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(()),
+        Err(e) => Err(format!("Could not create directory {}: {}", path.display(), e)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tari_crypto::keys::PublicKey;
+
+    #[test]
+    fn parses_base_node_peers() {
+        let (_, public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        let peer = format!("{}::/ip4/127.0.0.1/tcp/18189", public_key.to_hex());
+
+        let (parsed_key, address) = parse_base_node_peer(&peer).unwrap();
+        assert_eq!(parsed_key, public_key);
+        assert_eq!(address, "/ip4/127.0.0.1/tcp/18189".parse::<Multiaddr>().unwrap());
+
+        assert!(parse_base_node_peer(&public_key.to_hex()).is_err());
+        assert!(parse_base_node_peer("not a key::/ip4/127.0.0.1/tcp/18189").is_err());
+        assert!(parse_base_node_peer(&format!("{}::not an address", public_key.to_hex())).is_err());
+    }
+}
@@ -0,0 +1,72 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::net::SocketAddr;
+use structopt::StructOpt;
+use tari_common::ConfigBootstrap;
+
+/// The console wallet command line arguments. The common bootstrap arguments (base path, config file, log config,
+/// --init and --create-id) are shared with the other Tari applications.
+#[derive(StructOpt, Debug)]
+#[structopt(
+    name = "tari_console_wallet",
+    about = "A standalone console wallet for the Tari network"
+)]
+pub struct Arguments {
+    #[structopt(flatten)]
+    pub bootstrap: ConfigBootstrap,
+    /// A base node for the wallet to use, in the format <public key>::<address>. May be given more than once, in order
+    /// of preference. If omitted, the base nodes in the wallet base_nodes setting and any previously added base nodes
+    /// are used.
+    #[structopt(long = "base-node", alias("base_node"))]
+    pub base_nodes: Vec<String>,
+    /// Run a single command, e.g. --command "get-balance", and exit instead of starting the interactive console
+    #[structopt(long)]
+    pub command: Option<String>,
+    /// Start the wallet gRPC server on the given address, e.g. 127.0.0.1:18143. The server is unauthenticated, so it
+    /// should only be bound to a trusted interface
+    #[structopt(long = "grpc-address", alias("grpc_address"))]
+    pub grpc_address: Option<SocketAddr>,
+    /// Run without the interactive console until Ctrl+C is received. Typically used with --grpc-address
+    #[structopt(long)]
+    pub daemon: bool,
+    /// Run as a watch-only wallet that tracks outputs added with watch-output without holding any spending keys.
+    /// Spending commands are refused. Use a separate wallet database from any wallet holding keys.
+    #[structopt(long = "watch-only", alias("watch_only"))]
+    pub watch_only: bool,
+}
+
+/// Prints the banner and the list of available commands
+pub fn print_banner(commands: Vec<String>, chunk_size: usize) {
+    println!("Tari Console Wallet {}", env!("CARGO_PKG_VERSION"));
+    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~");
+    println!("Commands:");
+    let width = commands.iter().map(|c| c.chars().count()).max().unwrap_or(0) + 4;
+    for chunk in commands.chunks(chunk_size) {
+        let line = chunk
+            .iter()
+            .map(|c| format!("{:width$}", c, width = width))
+            .collect::<String>();
+        println!("    {}", line.trim_end());
+    }
+    println!();
+}
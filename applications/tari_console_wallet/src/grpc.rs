@@ -0,0 +1,554 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{builder::WalletSqlite, utils::parse_emoji_id_or_public_key};
+use chrono::NaiveDateTime;
+use digest::Digest;
+use futures::StreamExt;
+use log::*;
+use prost_types::Timestamp;
+use rand::rngs::OsRng;
+use std::sync::Arc;
+use tari_comms::{types::CommsPublicKey, NodeIdentity};
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction::{OutputFeatures, UnblindedOutput},
+    types::{CryptoFactories, PrivateKey},
+};
+use tari_crypto::{
+    common::Blake256,
+    keys::PublicKey,
+    ristretto::{RistrettoPublicKey, RistrettoSchnorr},
+    signatures::SchnorrSignatureError,
+    tari_utilities::{hex::Hex, ByteArray},
+};
+use tari_wallet::{
+    contacts_service::handle::ContactsServiceHandle,
+    output_manager_service::handle::OutputManagerHandle,
+    transaction_service::{
+        error::TransactionServiceError,
+        handle::{TransactionEvent, TransactionServiceHandle},
+        storage::database::{CompletedTransaction, InboundTransaction, OutboundTransaction, TransactionStatus},
+    },
+    util::emoji::EmojiId,
+};
+use tokio::{runtime, sync::mpsc};
+use tonic::{Request, Response, Status};
+use wallet_grpc::*;
+
+const LOG_TARGET: &str = "wallet::console::grpc";
+/// The fee per gram used when a request does not specify one
+const DEFAULT_FEE_PER_GRAM: MicroTari = MicroTari(25);
+/// The number of transaction events that are buffered for a slow client before the stream waits for it
+const TRANSACTION_EVENT_BUFFER_SIZE: usize = 100;
+/// Domain separation tag for the message signature challenge
+const MESSAGE_SIGNATURE_DOMAIN: &[u8] = b"com.tari.wallet.message_signature";
+
+pub(crate) mod wallet_grpc {
+    tonic::include_proto!("tari.wallet");
+}
+
+pub struct WalletGrpcServer {
+    executor: runtime::Handle,
+    node_identity: Arc<NodeIdentity>,
+    factories: CryptoFactories,
+    output_manager_service: OutputManagerHandle,
+    transaction_service: TransactionServiceHandle,
+    contacts_service: ContactsServiceHandle,
+}
+
+impl WalletGrpcServer {
+    pub fn new(wallet: &WalletSqlite) -> Self {
+        Self {
+            executor: wallet.runtime.handle().clone(),
+            node_identity: wallet.comms.node_identity(),
+            factories: wallet.factories.clone(),
+            output_manager_service: wallet.output_manager_service.clone(),
+            transaction_service: wallet.transaction_service.clone(),
+            contacts_service: wallet.contacts_service.clone(),
+        }
+    }
+
+    /// Returns all pending and completed transactions known to the wallet
+    async fn all_transactions(&self) -> Result<Vec<TransactionInfo>, Status> {
+        let mut transaction_service = self.transaction_service.clone();
+        let inbound = transaction_service
+            .get_pending_inbound_transactions()
+            .await
+            .map_err(to_status)?;
+        let outbound = transaction_service
+            .get_pending_outbound_transactions()
+            .await
+            .map_err(to_status)?;
+        let completed = transaction_service
+            .get_completed_transactions()
+            .await
+            .map_err(to_status)?;
+
+        let public_key = self.node_identity.public_key();
+        let mut transactions = inbound
+            .into_iter()
+            .map(|(_, tx)| convert_inbound(tx, public_key))
+            .chain(outbound.into_iter().map(|(_, tx)| convert_outbound(tx, public_key)))
+            .chain(completed.into_iter().map(|(_, tx)| convert_completed(tx, public_key)))
+            .collect::<Vec<_>>();
+        transactions.sort_by_key(|tx| tx.tx_id);
+        Ok(transactions)
+    }
+}
+
+#[tonic::async_trait]
+impl wallet_grpc::wallet_server::Wallet for WalletGrpcServer {
+    type StreamTransactionEventsStream = mpsc::Receiver<Result<wallet_grpc::TransactionEvent, Status>>;
+
+    async fn get_identity(&self, _: Request<GetIdentityRequest>) -> Result<Response<GetIdentityResponse>, Status> {
+        Ok(Response::new(GetIdentityResponse {
+            public_key: self.node_identity.public_key().to_vec(),
+            node_id: self.node_identity.node_id().to_vec(),
+            emoji_id: EmojiId::from_pubkey(self.node_identity.public_key()).to_string(),
+            public_address: self.node_identity.public_address().to_string(),
+        }))
+    }
+
+    async fn get_balance(&self, _: Request<GetBalanceRequest>) -> Result<Response<GetBalanceResponse>, Status> {
+        debug!(target: LOG_TARGET, "Incoming GRPC request for GetBalance");
+        let mut output_manager_service = self.output_manager_service.clone();
+        let balance = output_manager_service.get_balance().await.map_err(to_status)?;
+        Ok(Response::new(GetBalanceResponse {
+            available_balance: balance.available_balance.0,
+            pending_incoming_balance: balance.pending_incoming_balance.0,
+            pending_outgoing_balance: balance.pending_outgoing_balance.0,
+            time_locked_balance: balance.time_locked_balance.unwrap_or_default().0,
+        }))
+    }
+
+    async fn get_transactions(
+        &self,
+        _: Request<GetTransactionsRequest>,
+    ) -> Result<Response<GetTransactionsResponse>, Status>
+    {
+        debug!(target: LOG_TARGET, "Incoming GRPC request for GetTransactions");
+        Ok(Response::new(GetTransactionsResponse {
+            transactions: self.all_transactions().await?,
+        }))
+    }
+
+    async fn get_transaction_info(
+        &self,
+        request: Request<GetTransactionInfoRequest>,
+    ) -> Result<Response<GetTransactionInfoResponse>, Status>
+    {
+        let request = request.into_inner();
+        debug!(
+            target: LOG_TARGET,
+            "Incoming GRPC request for GetTransactionInfo: {:?}", request.transaction_ids
+        );
+        let transactions = self
+            .all_transactions()
+            .await?
+            .into_iter()
+            .filter(|tx| request.transaction_ids.contains(&tx.tx_id))
+            .collect();
+        Ok(Response::new(GetTransactionInfoResponse { transactions }))
+    }
+
+    async fn send_tari(&self, request: Request<SendTariRequest>) -> Result<Response<SendTariResponse>, Status> {
+        let request = request.into_inner();
+        debug!(
+            target: LOG_TARGET,
+            "Incoming GRPC request for SendTari: {} uT to {}", request.amount, request.recipient
+        );
+        let recipient = parse_emoji_id_or_public_key(&request.recipient)
+            .ok_or_else(|| Status::invalid_argument("recipient is not a valid public key or emoji id"))?;
+        let fee_per_gram = match request.fee_per_gram {
+            0 => DEFAULT_FEE_PER_GRAM,
+            fee => MicroTari::from(fee),
+        };
+
+        let mut transaction_service = self.transaction_service.clone();
+        match transaction_service
+            .send_transaction(recipient, request.amount.into(), fee_per_gram, request.message)
+            .await
+        {
+            Ok(tx_id) => Ok(Response::new(SendTariResponse {
+                tx_id,
+                discovery_in_progress: false,
+            })),
+            Err(TransactionServiceError::OutboundSendDiscoveryInProgress(tx_id)) => {
+                Ok(Response::new(SendTariResponse {
+                    tx_id,
+                    discovery_in_progress: true,
+                }))
+            },
+            Err(err) => Err(to_status(err)),
+        }
+    }
+
+    async fn cancel_transaction(
+        &self,
+        request: Request<CancelTransactionRequest>,
+    ) -> Result<Response<CancelTransactionResponse>, Status>
+    {
+        let tx_id = request.into_inner().tx_id;
+        debug!(
+            target: LOG_TARGET,
+            "Incoming GRPC request for CancelTransaction: {}", tx_id
+        );
+        let mut transaction_service = self.transaction_service.clone();
+        transaction_service.cancel_transaction(tx_id).await.map_err(to_status)?;
+        Ok(Response::new(CancelTransactionResponse {}))
+    }
+
+    async fn import_utxo(&self, request: Request<ImportUtxoRequest>) -> Result<Response<ImportUtxoResponse>, Status> {
+        let request = request.into_inner();
+        debug!(
+            target: LOG_TARGET,
+            "Incoming GRPC request for ImportUtxo: {} uT", request.amount
+        );
+        let spending_key = PrivateKey::from_bytes(&request.spending_key)
+            .map_err(|_| Status::invalid_argument("spending_key is not a valid private key"))?;
+        let source_public_key = CommsPublicKey::from_bytes(&request.source_public_key)
+            .map_err(|_| Status::invalid_argument("source_public_key is not a valid public key"))?;
+        let amount = MicroTari::from(request.amount);
+
+        let unblinded_output = UnblindedOutput::new(amount, spending_key, None);
+        let mut output_manager_service = self.output_manager_service.clone();
+        output_manager_service
+            .add_output(unblinded_output.clone())
+            .await
+            .map_err(to_status)?;
+        let mut transaction_service = self.transaction_service.clone();
+        let tx_id = transaction_service
+            .import_utxo(amount, source_public_key, request.message)
+            .await
+            .map_err(to_status)?;
+        info!(
+            target: LOG_TARGET,
+            "UTXO (Commitment: {}) imported into wallet",
+            unblinded_output
+                .as_transaction_input(&self.factories.commitment, OutputFeatures::default())
+                .commitment
+                .to_hex()
+        );
+        Ok(Response::new(ImportUtxoResponse { tx_id }))
+    }
+
+    async fn coin_split(&self, request: Request<CoinSplitRequest>) -> Result<Response<CoinSplitResponse>, Status> {
+        let request = request.into_inner();
+        debug!(
+            target: LOG_TARGET,
+            "Incoming GRPC request for CoinSplit: {} x {} uT", request.split_count, request.amount_per_split
+        );
+        let fee_per_gram = match request.fee_per_gram {
+            0 => DEFAULT_FEE_PER_GRAM,
+            fee => MicroTari::from(fee),
+        };
+        let lock_height = match request.lock_height {
+            0 => None,
+            height => Some(height),
+        };
+        let message = match request.message.as_str() {
+            "" => "Coin split".to_string(),
+            _ => request.message,
+        };
+
+        let mut output_manager_service = self.output_manager_service.clone();
+        let (tx_id, tx, fee, amount) = output_manager_service
+            .create_coin_split(
+                request.amount_per_split.into(),
+                request.split_count as usize,
+                fee_per_gram,
+                lock_height,
+            )
+            .await
+            .map_err(to_status)?;
+        let mut transaction_service = self.transaction_service.clone();
+        transaction_service
+            .submit_transaction(tx_id, tx, fee, amount, message)
+            .await
+            .map_err(to_status)?;
+        Ok(Response::new(CoinSplitResponse { tx_id }))
+    }
+
+    async fn get_contacts(&self, _: Request<GetContactsRequest>) -> Result<Response<GetContactsResponse>, Status> {
+        debug!(target: LOG_TARGET, "Incoming GRPC request for GetContacts");
+        let mut contacts_service = self.contacts_service.clone();
+        let contacts = contacts_service
+            .get_contacts()
+            .await
+            .map_err(to_status)?
+            .into_iter()
+            .map(|contact| Contact {
+                emoji_id: EmojiId::from_pubkey(&contact.public_key).to_string(),
+                public_key: contact.public_key.to_vec(),
+                alias: contact.alias,
+            })
+            .collect();
+        Ok(Response::new(GetContactsResponse { contacts }))
+    }
+
+    async fn sign_message(
+        &self,
+        request: Request<SignMessageRequest>,
+    ) -> Result<Response<SignMessageResponse>, Status>
+    {
+        let message = request.into_inner().message;
+        let signature = sign_message(
+            self.node_identity.secret_key(),
+            self.node_identity.public_key(),
+            &message,
+        )
+        .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(SignMessageResponse {
+            public_key: self.node_identity.public_key().to_vec(),
+            public_nonce: signature.get_public_nonce().to_vec(),
+            signature: signature.get_signature().to_vec(),
+        }))
+    }
+
+    async fn verify_message_signature(
+        &self,
+        request: Request<VerifyMessageSignatureRequest>,
+    ) -> Result<Response<VerifyMessageSignatureResponse>, Status>
+    {
+        let request = request.into_inner();
+        let public_key = RistrettoPublicKey::from_bytes(&request.public_key)
+            .map_err(|_| Status::invalid_argument("public_key is not a valid public key"))?;
+        let public_nonce = RistrettoPublicKey::from_bytes(&request.public_nonce)
+            .map_err(|_| Status::invalid_argument("public_nonce is not a valid public key"))?;
+        let signature = PrivateKey::from_bytes(&request.signature)
+            .map_err(|_| Status::invalid_argument("signature is not valid"))?;
+        let signature = RistrettoSchnorr::new(public_nonce, signature);
+        Ok(Response::new(VerifyMessageSignatureResponse {
+            is_valid: verify_message_signature(&signature, &public_key, &request.message),
+        }))
+    }
+
+    async fn stream_transaction_events(
+        &self,
+        _: Request<StreamTransactionEventsRequest>,
+    ) -> Result<Response<Self::StreamTransactionEventsStream>, Status>
+    {
+        debug!(target: LOG_TARGET, "Incoming GRPC request for StreamTransactionEvents");
+        let mut event_stream = self.transaction_service.get_event_stream_fused();
+        let (mut tx, rx) = mpsc::channel(TRANSACTION_EVENT_BUFFER_SIZE);
+        self.executor.spawn(async move {
+            while let Some(event) = event_stream.next().await {
+                let event = match event {
+                    Ok(event) => Ok(convert_event(&event)),
+                    Err(err) => {
+                        warn!(
+                            target: LOG_TARGET,
+                            "Error reading from transaction event stream: {:?}", err
+                        );
+                        Err(Status::internal(format!("Error reading transaction events: {:?}", err)))
+                    },
+                };
+                if tx.send(event).await.is_err() {
+                    debug!(target: LOG_TARGET, "Transaction event stream client disconnected");
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(rx))
+    }
+}
+
+/// Calculates the challenge for a message signature. The challenge commits to the public nonce and the public key of
+/// the signer so that a signature cannot be constructed for a key without knowing its secret key.
+fn build_message_challenge(
+    public_nonce: &RistrettoPublicKey,
+    public_key: &RistrettoPublicKey,
+    message: &str,
+) -> Vec<u8>
+{
+    Blake256::new()
+        .chain(MESSAGE_SIGNATURE_DOMAIN)
+        .chain(public_nonce.as_bytes())
+        .chain(public_key.as_bytes())
+        .chain(message.as_bytes())
+        .result()
+        .to_vec()
+}
+
+/// Signs the message with the given secret key
+fn sign_message(
+    secret_key: &PrivateKey,
+    public_key: &RistrettoPublicKey,
+    message: &str,
+) -> Result<RistrettoSchnorr, SchnorrSignatureError>
+{
+    let (nonce, public_nonce) = RistrettoPublicKey::random_keypair(&mut OsRng);
+    let challenge = build_message_challenge(&public_nonce, public_key, message);
+    RistrettoSchnorr::sign(secret_key.clone(), nonce, &challenge)
+}
+
+/// Returns true if the signature of the message is valid for the given public key
+fn verify_message_signature(signature: &RistrettoSchnorr, public_key: &RistrettoPublicKey, message: &str) -> bool {
+    let challenge = build_message_challenge(signature.get_public_nonce(), public_key, message);
+    signature.verify_challenge(public_key, &challenge)
+}
+
+/// Converts a wallet service error into a gRPC status
+fn to_status<E: std::fmt::Debug>(err: E) -> Status {
+    warn!(target: LOG_TARGET, "Error communicating with wallet: {:?}", err);
+    Status::internal(format!("{:?}", err))
+}
+
+/// Utility function that converts a `chrono::NaiveDateTime` to a `prost::Timestamp`
+fn naive_datetime_to_timestamp(datetime: NaiveDateTime) -> Timestamp {
+    Timestamp {
+        seconds: datetime.timestamp(),
+        nanos: datetime.timestamp_subsec_nanos() as i32,
+    }
+}
+
+fn convert_status(status: TransactionStatus) -> wallet_grpc::TransactionStatus {
+    match status {
+        TransactionStatus::Pending => wallet_grpc::TransactionStatus::Pending,
+        TransactionStatus::Completed => wallet_grpc::TransactionStatus::Completed,
+        TransactionStatus::Broadcast => wallet_grpc::TransactionStatus::Broadcast,
+        TransactionStatus::Mined => wallet_grpc::TransactionStatus::Mined,
+        TransactionStatus::Imported => wallet_grpc::TransactionStatus::Imported,
+        TransactionStatus::Cancelled => wallet_grpc::TransactionStatus::Cancelled,
+    }
+}
+
+fn convert_inbound(tx: InboundTransaction, wallet_public_key: &CommsPublicKey) -> TransactionInfo {
+    TransactionInfo {
+        tx_id: tx.tx_id,
+        source_public_key: tx.source_public_key.to_vec(),
+        destination_public_key: wallet_public_key.to_vec(),
+        status: convert_status(tx.status) as i32,
+        direction: TransactionDirection::Inbound as i32,
+        amount: tx.amount.0,
+        fee: 0,
+        timestamp: Some(naive_datetime_to_timestamp(tx.timestamp)),
+        message: tx.message,
+        confirmations: 0,
+    }
+}
+
+fn convert_outbound(tx: OutboundTransaction, wallet_public_key: &CommsPublicKey) -> TransactionInfo {
+    TransactionInfo {
+        tx_id: tx.tx_id,
+        source_public_key: wallet_public_key.to_vec(),
+        destination_public_key: tx.destination_public_key.to_vec(),
+        status: convert_status(tx.status) as i32,
+        direction: TransactionDirection::Outbound as i32,
+        amount: tx.amount.0,
+        fee: tx.fee.0,
+        timestamp: Some(naive_datetime_to_timestamp(tx.timestamp)),
+        message: tx.message,
+        confirmations: 0,
+    }
+}
+
+fn convert_completed(tx: CompletedTransaction, wallet_public_key: &CommsPublicKey) -> TransactionInfo {
+    let direction = if &tx.source_public_key == wallet_public_key {
+        TransactionDirection::Outbound
+    } else {
+        TransactionDirection::Inbound
+    };
+    TransactionInfo {
+        tx_id: tx.tx_id,
+        source_public_key: tx.source_public_key.to_vec(),
+        destination_public_key: tx.destination_public_key.to_vec(),
+        status: convert_status(tx.status) as i32,
+        direction: direction as i32,
+        amount: tx.amount.0,
+        fee: tx.fee.0,
+        timestamp: Some(naive_datetime_to_timestamp(tx.timestamp)),
+        message: tx.message,
+        confirmations: tx.confirmations.unwrap_or(0),
+    }
+}
+
+fn convert_event(event: &TransactionEvent) -> wallet_grpc::TransactionEvent {
+    use TransactionEventType::*;
+    let (event_type, tx_id, is_success) = match event {
+        TransactionEvent::ReceivedTransaction(tx_id) => (ReceivedTransaction, *tx_id, false),
+        TransactionEvent::ReceivedTransactionReply(tx_id) => (ReceivedTransactionReply, *tx_id, false),
+        TransactionEvent::ReceivedFinalizedTransaction(tx_id) => (ReceivedFinalizedTransaction, *tx_id, false),
+        TransactionEvent::TransactionDirectSendResult(tx_id, result) => (DirectSendResult, *tx_id, *result),
+        TransactionEvent::TransactionStoreForwardSendResult(tx_id, result) => (StoreForwardSendResult, *tx_id, *result),
+        TransactionEvent::TransactionCancelled(tx_id) => (Cancelled, *tx_id, false),
+        TransactionEvent::TransactionBroadcast(tx_id) => (Broadcast, *tx_id, false),
+        TransactionEvent::MempoolBroadcastTimedOut(tx_id) => (MempoolBroadcastTimedOut, *tx_id, false),
+        TransactionEvent::TransactionMined(tx_id) => (Mined, *tx_id, false),
+        TransactionEvent::TransactionMinedRequestTimedOut(tx_id) => (MinedRequestTimedOut, *tx_id, false),
+        TransactionEvent::TransactionConfirmed(tx_id) => (Confirmed, *tx_id, false),
+        TransactionEvent::TransactionReorged(tx_id) => (Reorged, *tx_id, false),
+        TransactionEvent::ReceivedOneSidedTransaction(tx_id) => (ReceivedOneSidedTransaction, *tx_id, false),
+        TransactionEvent::Error(err) => {
+            return wallet_grpc::TransactionEvent {
+                event_type: Error as i32,
+                tx_id: 0,
+                is_success: false,
+                error: err.clone(),
+            }
+        },
+    };
+    wallet_grpc::TransactionEvent {
+        event_type: event_type as i32,
+        tx_id,
+        is_success,
+        error: String::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sign_and_verify_message() {
+        let (secret_key, public_key) = RistrettoPublicKey::random_keypair(&mut OsRng);
+        let signature = sign_message(&secret_key, &public_key, "Hello Tari").unwrap();
+        assert!(verify_message_signature(&signature, &public_key, "Hello Tari"));
+    }
+
+    #[test]
+    fn verify_rejects_tampered_signature() {
+        let (secret_key, public_key) = RistrettoPublicKey::random_keypair(&mut OsRng);
+        let signature = sign_message(&secret_key, &public_key, "Hello Tari").unwrap();
+        assert!(!verify_message_signature(&signature, &public_key, "Hello Tari!"));
+
+        let (_, other_public_key) = RistrettoPublicKey::random_keypair(&mut OsRng);
+        assert!(!verify_message_signature(&signature, &other_public_key, "Hello Tari"));
+
+        let (_, other_nonce) = RistrettoPublicKey::random_keypair(&mut OsRng);
+        let tampered = RistrettoSchnorr::new(other_nonce, signature.get_signature().clone());
+        assert!(!verify_message_signature(&tampered, &public_key, "Hello Tari"));
+    }
+
+    #[test]
+    fn verify_rejects_challenge_without_nonce_and_key() {
+        // A signature over a challenge that only commits to the message must not verify
+        let (secret_key, public_key) = RistrettoPublicKey::random_keypair(&mut OsRng);
+        let (nonce, _) = RistrettoPublicKey::random_keypair(&mut OsRng);
+        let challenge = Blake256::digest(b"Hello Tari");
+        let signature = RistrettoSchnorr::sign(secret_key, nonce, challenge.as_slice()).unwrap();
+        assert!(!verify_message_signature(&signature, &public_key, "Hello Tari"));
+    }
+}
@@ -0,0 +1,382 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+/// # Tari Console Wallet
+///
+/// A standalone wallet for the Tari network that uses one or more remote base nodes, so that a wallet can be used
+/// without running a full node.
+///
+/// ## Running the Tari Console Wallet
+///
+/// For the first run
+/// ```cargo run --bin tari_console_wallet -- --create-id --base-node <public key>::<address>```
+/// 
+/// Base nodes can also be configured with the `base_nodes` setting in the `[wallet]` section. The configured peer seeds
+/// are only used to join the network. The console wallet keeps its own database and identity files
+/// (`console_wallet_file`, `console_wallet_identity_file`), separate from the base node's embedded wallet.
+///
+/// Subsequent runs (previously added base nodes are remembered)
+/// ```cargo run --bin tari_console_wallet```
+///
+/// To run a single command and exit, e.g. from a script
+/// ```cargo run --bin tari_console_wallet -- --command "send-tari 1000 <public key or emoji id> Thanks"```
+/// 
+/// To drive the wallet from another application over gRPC (see `proto/wallet.proto`), without the interactive console
+/// ```cargo run --bin tari_console_wallet -- --grpc-address 127.0.0.1:18143 --daemon```
+///
+/// To monitor the outputs of a cold storage wallet without its spending keys, using a separate wallet database
+/// ```cargo run --bin tari_console_wallet -- --watch-only```
+/// 
+/// ## Commands
+///
+/// `help` - Displays a list of commands
+/// `get-balance` - Displays the balance of the wallet (available, pending incoming, pending outgoing and time-locked)
+/// `send-tari` - Sends Tari, the amount needs to be specified, followed by the destination (public key or emoji id) and
+/// an optional message
+/// `send-tari-from-utxos` - Same as send-tari but spends exactly the UTXOs given as a comma separated list of
+/// commitments
+/// `send-tari-time-locked` - Same as send-tari but takes a lock height for the transaction and a maturity height for
+/// the recipient's output before the message
+/// `send-tari-from-account` - Same as send-tari but takes the name of the account to fund the transaction from first
+/// `list-transactions` - Lists pending inbound and outbound transactions
+/// `list-completed-transactions` - Lists completed transactions
+/// `cancel-transaction` - Cancels a pending transaction
+/// `list-contacts` - Lists your contacts
+/// `add-contact` - Adds or updates a contact
+/// `remove-contact` - Removes a contact
+/// `get-seed-words` - Displays the seed words that can be used to recover the wallet
+/// `list-utxos` - Lists your unspent outputs along with their labels and whether they are frozen
+/// `freeze-utxo` - Excludes an unspent output from automatic selection when funding transactions
+/// `unfreeze-utxo` - Makes a frozen unspent output available for automatic selection again
+/// `label-utxo` - Attaches a label to an unspent output
+/// `clear-utxo-annotation` - Removes the label of an unspent output and unfreezes it
+/// `coin-split` - Splits your funds into a number of outputs of the same value
+/// `consolidate-utxos` - Merges your smallest outputs into a single output
+/// `create-account` - Creates a named account with its own keys, outputs and balance
+/// `list-accounts` - Lists your accounts with their balances and marks the receiving account
+/// `set-receiving-account` - Sets the account that incoming transactions are paid into
+/// `list-account-transactions` - Lists the completed transactions of an account
+/// `watch-output` - Tracks an unspent output of another wallet, e.g. cold storage, without its spending key
+/// `unwatch-output` - Stops tracking a watched output
+/// `list-watched-outputs` - Lists the watched outputs and their status on the blockchain
+/// `create-offline-send` - Writes an unsigned send request to a file, e.g. from a watch-only wallet
+/// `sign-offline-send` - Signs an unsigned send request in the wallet holding the spending keys
+/// `reply-offline-send` - Replies to a signed send request delivered as a file
+/// `finalize-offline-send` - Finalizes a signed send with the recipient's reply
+/// `import-offline-send` - Imports a finalized transaction and broadcasts it
+/// `list-base-nodes` - Lists the base nodes the wallet uses and their health
+/// `whoami` - Displays identity information about this wallet
+/// `quit` - Exits the wallet
+/// `exit` - Same as quit
+
+/// Used to display tabulated data
+#[macro_use]
+mod table;
+
+/// Utilities and helpers for building the wallet instance
+mod builder;
+/// The command line interface definition and configuration
+mod cli;
+/// The wallet gRPC server
+mod grpc;
+/// Parser module used to control user commands
+mod parser;
+mod utils;
+
+use crate::{
+    builder::{create_new_wallet_identity, load_identity, parse_base_node_peer},
+    cli::Arguments,
+};
+use log::*;
+use parser::Parser;
+use rand::{rngs::OsRng, RngCore};
+use rustyline::{config::OutputStreamType, error::ReadlineError, CompletionType, Config, EditMode, Editor};
+use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
+use structopt::StructOpt;
+use tari_common::GlobalConfig;
+use tari_comms::{multiaddr::Multiaddr, NodeIdentity};
+use tari_shutdown::Shutdown;
+use tonic::transport::Server;
+
+pub const LOG_TARGET: &str = "wallet::console::app";
+/// How long a single --command waits for a sent transaction to be delivered before the wallet shuts down
+const COMMAND_SEND_RESULT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Enum to show failure information
+enum ExitCodes {
+    ConfigError = 101,
+    UnknownError = 102,
+    CommandError = 103,
+}
+
+impl From<tari_common::ConfigError> for ExitCodes {
+    fn from(err: tari_common::ConfigError) -> Self {
+        error!(target: LOG_TARGET, "{}", err);
+        Self::ConfigError
+    }
+}
+
+/// Application entry point
+fn main() {
+    match main_inner() {
+        Ok(_) => std::process::exit(0),
+        Err(exit_code) => std::process::exit(exit_code as i32),
+    }
+}
+
+/// Sets up the wallet and runs either the requested command or the cli_loop
+fn main_inner() -> Result<(), ExitCodes> {
+    // Parse and validate command-line arguments
+    let mut arguments = Arguments::from_args();
+
+    // Check and initialize configuration files
+    arguments.bootstrap.init_dirs()?;
+
+    // Load and apply configuration file
+    let cfg = arguments.bootstrap.load_configuration()?;
+
+    // Initialise the logger
+    arguments.bootstrap.initialize_logging()?;
+
+    // Populate the configuration struct
+    let config = GlobalConfig::convert_from(cfg).map_err(|err| {
+        error!(target: LOG_TARGET, "The configuration file has an error. {}", err);
+        ExitCodes::ConfigError
+    })?;
+
+    trace!(target: LOG_TARGET, "Using configuration: {:?}", config);
+
+    if arguments.bootstrap.init {
+        info!(target: LOG_TARGET, "Default configuration created. Done.");
+        return Ok(());
+    }
+
+    // Base nodes given on the command line take precedence over the configured base nodes
+    let base_node_peers = if arguments.base_nodes.is_empty() {
+        &config.console_wallet_base_nodes
+    } else {
+        &arguments.base_nodes
+    };
+    let base_node_peers = base_node_peers
+        .iter()
+        .map(|peer| parse_base_node_peer(peer))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| {
+            error!(target: LOG_TARGET, "{}", err);
+            ExitCodes::ConfigError
+        })?;
+
+    let node_identity = setup_node_identity(
+        &config.console_wallet_identity_file,
+        &config.public_address,
+        arguments.bootstrap.create_id,
+        config.dht_pow_difficulty,
+    )?;
+    if arguments.bootstrap.create_id {
+        info!(
+            target: LOG_TARGET,
+            "Wallet ID created at '{}'. Done.",
+            config.console_wallet_identity_file.to_string_lossy()
+        );
+        return Ok(());
+    }
+
+    let runtime = builder::setup_runtime(&config).map_err(|err| {
+        error!(target: LOG_TARGET, "{}", err);
+        ExitCodes::UnknownError
+    })?;
+    let mut wallet = builder::create_wallet(&config, node_identity, runtime, base_node_peers, arguments.watch_only)
+        .map_err(|err| {
+            error!(target: LOG_TARGET, "{}", err);
+            ExitCodes::UnknownError
+        })?;
+
+    if let Some(grpc_address) = arguments.grpc_address {
+        let grpc = grpc::WalletGrpcServer::new(&wallet);
+        wallet.runtime.spawn(run_grpc(grpc, grpc_address));
+    }
+
+    if arguments.daemon {
+        info!(
+            target: LOG_TARGET,
+            "Wallet has been successfully configured and initialized. Running until Ctrl+C is received."
+        );
+        if let Err(err) = wallet.runtime.block_on(tokio::signal::ctrl_c()) {
+            error!(target: LOG_TARGET, "Could not listen for the Ctrl+C signal: {}", err);
+        }
+        info!(target: LOG_TARGET, "Wallet shutting down.");
+        wallet.shutdown();
+        return Ok(());
+    }
+
+    let mut parser = Parser::new(wallet, config.network);
+    let mut shutdown = Shutdown::new();
+
+    match arguments.command {
+        Some(command) => {
+            info!(target: LOG_TARGET, "Running command '{}'", command);
+            // The wallet is shut down as soon as the command returns, so sends must wait until they are delivered
+            parser.wait_for_send_results(COMMAND_SEND_RESULT_TIMEOUT);
+            let result = parser.handle_command(&command, &mut shutdown);
+            parser.into_wallet().shutdown();
+            result.map_err(|_| ExitCodes::CommandError)
+        },
+        None => {
+            cli::print_banner(parser.get_commands(), 3);
+            info!(
+                target: LOG_TARGET,
+                "Wallet has been successfully configured and initialized. Starting CLI loop."
+            );
+            cli_loop(parser, shutdown);
+            println!("Goodbye!");
+            Ok(())
+        },
+    }
+}
+
+/// Runs the wallet gRPC server
+async fn run_grpc(grpc: grpc::WalletGrpcServer, grpc_address: SocketAddr) -> Result<(), String> {
+    info!(target: LOG_TARGET, "Starting GRPC on {}", grpc_address);
+
+    Server::builder()
+        .add_service(grpc::wallet_grpc::wallet_server::WalletServer::new(grpc))
+        .serve(grpc_address)
+        .await
+        .map_err(|e| format!("GRPC server returned error:{}", e))?;
+    info!(target: LOG_TARGET, "Stopping GRPC");
+    Ok(())
+}
+
+/// Runs the interactive wallet console. The wallet is shut down when the parser, which owns it, is dropped at the end
+/// of the loop.
+/// ## Parameters
+/// `parser` - The parser to process input commands
+/// `shutdown` - The trigger for shutting down
+fn cli_loop(parser: Parser, mut shutdown: Shutdown) {
+    let cli_config = Config::builder()
+        .history_ignore_space(true)
+        .completion_type(CompletionType::List)
+        .edit_mode(EditMode::Emacs)
+        .output_stream(OutputStreamType::Stdout)
+        .build();
+    let mut rustyline = Editor::with_config(cli_config);
+    rustyline.set_helper(Some(parser));
+    loop {
+        let readline = rustyline.readline(">> ");
+        match readline {
+            Ok(line) => {
+                rustyline.add_history_entry(line.as_str());
+                if let Some(p) = rustyline.helper_mut().as_deref_mut() {
+                    // Errors have already been reported to the user by the parser
+                    let _ = p.handle_command(&line, &mut shutdown);
+                }
+            },
+            Err(ReadlineError::Interrupted) => {
+                println!("The wallet is shutting down because Ctrl+C was received...");
+                info!(
+                    target: LOG_TARGET,
+                    "Termination signal received from user. Shutting wallet down."
+                );
+                if shutdown.trigger().is_err() {
+                    error!(target: LOG_TARGET, "Shutdown signal failed to trigger");
+                };
+                break;
+            },
+            Err(err) => {
+                println!("Error: {:?}", err);
+                break;
+            },
+        }
+        if shutdown.is_triggered() {
+            break;
+        };
+    }
+}
+
+/// Loads the wallet node identity, or creates a new one if the --create-id flag was specified
+/// ## Parameters
+/// `identity_file` - Reference to file path
+/// `public_address` - Network address of the wallet
+/// `create_id` - Whether an identity needs to be created or not
+/// `pow_difficulty` - The DHT proof-of-work difficulty the identity must meet
+///
+/// # Return
+/// A NodeIdentity wrapped in an atomic reference counter on success, the exit code indicating the reason on failure
+fn setup_node_identity(
+    identity_file: &PathBuf,
+    public_address: &Multiaddr,
+    create_id: bool,
+    pow_difficulty: u32,
+) -> Result<Arc<NodeIdentity>, ExitCodes>
+{
+    match load_identity(identity_file) {
+        Ok(mut id) => {
+            // The identity is saved again once comms has been initialized
+            if !id.meets_proof_of_work_difficulty(pow_difficulty) {
+                info!(
+                    target: LOG_TARGET,
+                    "Generating a proof-of-work with difficulty {} for the wallet identity", pow_difficulty
+                );
+                id.generate_proof_of_work(OsRng.next_u64(), pow_difficulty)
+                    .map_err(|e| {
+                        error!(
+                            target: LOG_TARGET,
+                            "Could not generate a wallet id proof-of-work. {:?}.", e
+                        );
+                        ExitCodes::ConfigError
+                    })?;
+            }
+            Ok(Arc::new(id))
+        },
+        Err(e) => {
+            if !create_id {
+                error!(
+                    target: LOG_TARGET,
+                    "Wallet identity information not found. {}. You can update the configuration file to point to a \
+                     valid node identity file, or re-run the wallet with the --create-id flag to create a new \
+                     identity.",
+                    e
+                );
+                return Err(ExitCodes::ConfigError);
+            }
+
+            debug!(target: LOG_TARGET, "Wallet id not found. {}. Creating new ID", e);
+
+            match create_new_wallet_identity(identity_file, public_address.clone(), pow_difficulty) {
+                Ok(id) => {
+                    info!(
+                        target: LOG_TARGET,
+                        "New wallet identity [{}] with public key {} has been created at {}.",
+                        id.node_id(),
+                        id.public_key(),
+                        identity_file.to_string_lossy(),
+                    );
+                    Ok(Arc::new(id))
+                },
+                Err(e) => {
+                    error!(target: LOG_TARGET, "Could not create new wallet id. {:?}.", e);
+                    Err(ExitCodes::ConfigError)
+                },
+            }
+        },
+    }
+}
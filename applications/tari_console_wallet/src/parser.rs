@@ -0,0 +1,1575 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    builder::WalletSqlite,
+    table::Table,
+    utils,
+    utils::{format_naive_datetime, parse_emoji_id_or_public_key},
+    LOG_TARGET,
+};
+use log::*;
+use qrcode::{render::unicode, QrCode};
+use regex::Regex;
+use rustyline::{
+    completion::Completer,
+    error::ReadlineError,
+    hint::{Hinter, HistoryHinter},
+    line_buffer::LineBuffer,
+    Context,
+};
+use rustyline_derive::{Helper, Highlighter, Validator};
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    string::ToString,
+    time::{Duration, Instant},
+};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter, EnumString};
+use tari_common::Network;
+use tari_comms::types::CommsPublicKey;
+use tari_core::{
+    tari_utilities::hex::Hex,
+    transactions::{tari_amount::MicroTari, transaction::OutputFeatures, types::Commitment},
+};
+use tari_shutdown::Shutdown;
+use tari_wallet::{
+    contacts_service::storage::database::Contact,
+    output_manager_service::{error::OutputManagerError, storage::database::DEFAULT_ACCOUNT},
+    transaction_service::{
+        error::TransactionServiceError,
+        offline::{
+            OfflineFinalizedTransaction,
+            OfflineRecipientReply,
+            OfflineSenderMessage,
+            OfflineTransactionFile,
+            UnsignedTransactionRequest,
+        },
+        storage::database::CompletedTransaction,
+    },
+    util::emoji::EmojiId,
+};
+use tokio::time;
+
+/// The fee per gram used for transactions created by the console wallet
+// TODO: make this configurable
+const FEE_PER_GRAM: MicroTari = MicroTari(25);
+
+/// Shown when a command that needs spending keys is used in a wallet started with --watch-only
+const WATCH_ONLY_ERROR: &str = "This is a watch-only wallet, it holds no spending keys and cannot spend funds.";
+
+/// Enum representing commands used by the console wallet
+#[derive(Clone, PartialEq, Debug, Display, EnumIter, EnumString)]
+#[strum(serialize_all = "kebab_case")]
+pub enum WalletCommand {
+    Help,
+    GetBalance,
+    SendTari,
+    SendTariFromUtxos,
+    SendTariTimeLocked,
+    SendTariFromAccount,
+    ListTransactions,
+    ListCompletedTransactions,
+    CancelTransaction,
+    ListContacts,
+    AddContact,
+    RemoveContact,
+    GetSeedWords,
+    ListUtxos,
+    FreezeUtxo,
+    UnfreezeUtxo,
+    LabelUtxo,
+    ClearUtxoAnnotation,
+    CoinSplit,
+    ConsolidateUtxos,
+    CreateAccount,
+    ListAccounts,
+    SetReceivingAccount,
+    ListAccountTransactions,
+    WatchOutput,
+    UnwatchOutput,
+    ListWatchedOutputs,
+    CreateOfflineSend,
+    SignOfflineSend,
+    ReplyOfflineSend,
+    FinalizeOfflineSend,
+    ImportOfflineSend,
+    ListBaseNodes,
+    Whoami,
+    Quit,
+    Exit,
+}
+
+/// This is used to parse commands from the user and execute them against the wallet. Commands are run to completion
+/// before returning so that the same parser can be used interactively and for scripting.
+#[derive(Helper, Validator, Highlighter)]
+pub struct Parser {
+    wallet: WalletSqlite,
+    network: Network,
+    commands: Vec<String>,
+    hinter: HistoryHinter,
+    send_result_timeout: Option<Duration>,
+}
+
+/// This will go through all instructions and look for potential matches
+impl Completer for Parser {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Result<(usize, Vec<String>), ReadlineError> {
+        let completions = self
+            .commands
+            .iter()
+            .filter(|cmd| cmd.starts_with(line))
+            .cloned()
+            .collect();
+
+        Ok((pos, completions))
+    }
+
+    fn update(&self, line: &mut LineBuffer, _: usize, elected: &str) {
+        line.update(elected, elected.len());
+    }
+}
+
+/// This allows us to make hints based on historic inputs
+impl Hinter for Parser {
+    fn hint(&self, line: &str, pos: usize, ctx: &rustyline::Context<'_>) -> Option<String> {
+        self.hinter.hint(line, pos, ctx)
+    }
+}
+
+impl Parser {
+    /// creates a new parser struct
+    pub fn new(wallet: WalletSqlite, network: Network) -> Self {
+        Parser {
+            wallet,
+            network,
+            commands: WalletCommand::iter().map(|x| x.to_string()).collect(),
+            hinter: HistoryHinter {},
+            send_result_timeout: None,
+        }
+    }
+
+    /// Makes the send commands wait up to `timeout` for a transaction to be delivered to the recipient, directly or via
+    /// store and forward, before returning. Used when running a single command so that the wallet is not shut down
+    /// before the transaction has been sent.
+    pub fn wait_for_send_results(&mut self, timeout: Duration) {
+        self.send_result_timeout = Some(timeout);
+    }
+
+    /// This will return the list of commands from the parser
+    pub fn get_commands(&self) -> Vec<String> {
+        self.commands.clone()
+    }
+
+    /// Consumes the parser and returns the wallet so that it can be shut down
+    pub fn into_wallet(self) -> WalletSqlite {
+        self.wallet
+    }
+
+    /// This will parse the provided command and execute it. The returned result indicates whether the command
+    /// succeeded, any error has already been printed to the console.
+    pub fn handle_command(&mut self, command_str: &str, shutdown: &mut Shutdown) -> Result<(), String> {
+        if command_str.trim().is_empty() {
+            return Ok(());
+        }
+
+        // Delimit arguments using spaces and pairs of quotation marks, which may include spaces
+        let re = Regex::new(r#"[^\s"]+|"(?:\\"|[^"])+""#).unwrap();
+        // Remove quotation marks left behind by `Regex` - it does not support look ahead and look behind
+        let mut args = re
+            .find_iter(command_str.trim())
+            .map(|mat| str::replace(mat.as_str(), "\"", ""))
+            .collect::<Vec<_>>()
+            .into_iter();
+
+        let command_arg = args.next().unwrap_or_else(|| "help".to_string());
+        let command = match WalletCommand::from_str(&command_arg) {
+            Ok(command) => command,
+            Err(_) => {
+                println!("{} is not a valid command, please enter a valid command", command_str);
+                println!("Enter help or press tab for available commands");
+                return Err(format!("Invalid command '{}'", command_arg));
+            },
+        };
+        let args = args.collect::<Vec<_>>();
+        let result = self.process_command(command, &args, shutdown);
+        if let Err(err) = &result {
+            println!("{}", err);
+        }
+        result
+    }
+
+    /// Function to process commands
+    fn process_command(
+        &mut self,
+        command: WalletCommand,
+        args: &[String],
+        shutdown: &mut Shutdown,
+    ) -> Result<(), String>
+    {
+        use WalletCommand::*;
+        match command {
+            Help => {
+                self.print_help(args);
+                Ok(())
+            },
+            GetBalance => self.process_get_balance(),
+            SendTari => self.process_send_tari(args),
+            SendTariFromUtxos => self.process_send_tari_from_utxos(args),
+            SendTariTimeLocked => self.process_send_tari_time_locked(args),
+            SendTariFromAccount => self.process_send_tari_from_account(args),
+            ListTransactions => self.process_list_transactions(),
+            ListCompletedTransactions => self.process_list_completed_transactions(args),
+            CancelTransaction => self.process_cancel_transaction(args),
+            ListContacts => self.process_list_contacts(),
+            AddContact => self.process_add_contact(args),
+            RemoveContact => self.process_remove_contact(args),
+            GetSeedWords => self.process_get_seed_words(),
+            ListUtxos => self.process_list_unspent_outputs(),
+            FreezeUtxo => self.process_set_utxo_frozen(args, true),
+            UnfreezeUtxo => self.process_set_utxo_frozen(args, false),
+            LabelUtxo => self.process_label_utxo(args),
+            ClearUtxoAnnotation => self.process_clear_utxo_annotation(args),
+            CoinSplit => self.process_coin_split(args),
+            ConsolidateUtxos => self.process_consolidate_utxos(args),
+            CreateAccount => self.process_create_account(args),
+            ListAccounts => self.process_list_accounts(),
+            SetReceivingAccount => self.process_set_receiving_account(args),
+            ListAccountTransactions => self.process_list_account_transactions(args),
+            WatchOutput => self.process_watch_output(args),
+            UnwatchOutput => self.process_unwatch_output(args),
+            ListWatchedOutputs => self.process_list_watched_outputs(),
+            CreateOfflineSend => self.process_create_offline_send(args),
+            SignOfflineSend => self.process_sign_offline_send(args),
+            ReplyOfflineSend => self.process_reply_offline_send(args),
+            FinalizeOfflineSend => self.process_finalize_offline_send(args),
+            ImportOfflineSend => self.process_import_offline_send(args),
+            ListBaseNodes => self.process_list_base_nodes(),
+            Whoami => {
+                self.process_whoami();
+                Ok(())
+            },
+            Exit | Quit => {
+                println!("Shutting down...");
+                info!(
+                    target: LOG_TARGET,
+                    "Termination signal received from user. Shutting wallet down."
+                );
+                let _ = shutdown.trigger();
+                Ok(())
+            },
+        }
+    }
+
+    /// Displays the commands or context specific help for a given command
+    fn print_help(&self, args: &[String]) {
+        let help_for = args
+            .first()
+            .and_then(|arg| WalletCommand::from_str(arg).ok())
+            .unwrap_or(WalletCommand::Help);
+        use WalletCommand::*;
+        match help_for {
+            Help => {
+                println!("Available commands are: ");
+                let joined = self.commands.join(", ");
+                println!("{}", joined);
+            },
+            GetBalance => {
+                println!("Gets your balance");
+            },
+            SendTari => {
+                println!("Sends an amount of Tari to a address call this command via:");
+                println!("send-tari [amount of tari to send] [destination public key or emoji id] [optional: msg]");
+            },
+            SendTariFromUtxos => {
+                println!("Sends Tari to an address spending exactly the listed UTXOs, call this command via:");
+                println!(
+                    "send-tari-from-utxos [amount of tari to send] [destination public key or emoji id] [comma \
+                     separated UTXO commitments] [optional: msg]"
+                );
+            },
+            SendTariTimeLocked => {
+                println!(
+                    "Sends Tari in a transaction that cannot be mined before a block height and whose output the \
+                     recipient can only spend from a later block height, call this command via:"
+                );
+                println!(
+                    "send-tari-time-locked [amount of tari to send] [destination public key or emoji id] [lock \
+                     height] [maturity height] [optional: msg]"
+                );
+            },
+            SendTariFromAccount => {
+                println!("Sends Tari to an address spending only the UTXOs of one account, call this command via:");
+                println!(
+                    "send-tari-from-account [account] [amount of tari to send] [destination public key or emoji id] \
+                     [optional: msg]"
+                );
+            },
+            ListTransactions => {
+                println!("Print a list of pending inbound and outbound transactions");
+            },
+            ListCompletedTransactions => {
+                println!("Print a list of completed transactions.");
+                println!("USAGE: list-completed-transactions [last n] or list-completed-transactions [n] [m]");
+            },
+            CancelTransaction => {
+                println!("Cancel a transaction");
+                println!("USAGE: cancel-transaction [transaction ID]");
+            },
+            ListContacts => {
+                println!("Lists your contacts");
+            },
+            AddContact => {
+                println!("Adds a contact, or updates the alias of an existing contact");
+                println!("USAGE: add-contact [alias] [public key or emoji id]");
+            },
+            RemoveContact => {
+                println!("Removes a contact");
+                println!("USAGE: remove-contact [public key or emoji id]");
+            },
+            GetSeedWords => {
+                println!("Displays the seed words that can be used to recover this wallet. Keep them secret!");
+            },
+            ListUtxos => {
+                println!("List your UTXOs along with their labels and whether they are frozen");
+            },
+            FreezeUtxo => {
+                println!("Freezes a UTXO so that it is never selected automatically to fund a transaction");
+                println!("USAGE: freeze-utxo [UTXO commitment]");
+            },
+            UnfreezeUtxo => {
+                println!("Unfreezes a UTXO so that it can be selected automatically again");
+                println!("USAGE: unfreeze-utxo [UTXO commitment]");
+            },
+            LabelUtxo => {
+                println!("Attaches a label to a UTXO, or clears it if no label is given");
+                println!("USAGE: label-utxo [UTXO commitment] [optional: label]");
+            },
+            ClearUtxoAnnotation => {
+                println!("Removes the label of a UTXO and unfreezes it");
+                println!("USAGE: clear-utxo-annotation [UTXO commitment]");
+            },
+            CoinSplit => {
+                println!("Constructs a transaction to split a small set of UTXOs into a large set of UTXOs");
+                println!("USAGE: coin-split [amount of tari to allocated to each UTXO] [number of UTXOs to create]");
+            },
+            ConsolidateUtxos => {
+                println!("Constructs a transaction that merges your smallest UTXOs into a single UTXO");
+                println!("USAGE: consolidate-utxos [maximum number of UTXOs to merge]");
+            },
+            CreateAccount => {
+                println!("Creates a new account with its own keys, UTXOs and balance");
+                println!("USAGE: create-account [account name]");
+            },
+            ListAccounts => {
+                println!("Lists your accounts and their balances, the receiving account is marked");
+            },
+            SetReceivingAccount => {
+                println!("Sets the account that incoming transactions are paid into");
+                println!("USAGE: set-receiving-account [account name]");
+            },
+            ListAccountTransactions => {
+                println!("Print a list of the completed transactions of an account.");
+                println!("USAGE: list-account-transactions [account name] [optional: last n]");
+            },
+            WatchOutput => {
+                println!(
+                    "Tracks a UTXO of another wallet, e.g. cold storage, without its spending key. The value cannot \
+                     be read from the commitment and has to be given"
+                );
+                println!("USAGE: watch-output [UTXO commitment] [value] [optional: maturity] [optional: label]");
+            },
+            UnwatchOutput => {
+                println!("Stops tracking a watched UTXO");
+                println!("USAGE: unwatch-output [UTXO commitment]");
+            },
+            ListWatchedOutputs => {
+                println!("Lists the watched UTXOs along with their status as last reported by the base node");
+            },
+            CreateOfflineSend => {
+                println!(
+                    "Writes an unsigned request to send Tari to a file, to be signed by the wallet holding the \
+                     spending keys with sign-offline-send"
+                );
+                println!(
+                    "USAGE: create-offline-send [amount of tari to send] [destination public key or emoji id] [output \
+                     file] [optional: msg]"
+                );
+            },
+            SignOfflineSend => {
+                println!(
+                    "Signs an unsigned send request and writes the sender message, to be given to the recipient, to a \
+                     file"
+                );
+                println!("USAGE: sign-offline-send [request file] [output file]");
+            },
+            ReplyOfflineSend => {
+                println!("Accepts a sender message and writes the reply, to be given back to the sender, to a file");
+                println!("USAGE: reply-offline-send [sender message file] [output file]");
+            },
+            FinalizeOfflineSend => {
+                println!(
+                    "Finalizes a signed send with the recipient's reply and writes the transaction, to be imported by \
+                     an online wallet, to a file"
+                );
+                println!("USAGE: finalize-offline-send [reply file] [output file]");
+            },
+            ImportOfflineSend => {
+                println!("Imports a finalized transaction and broadcasts it to the base node");
+                println!("USAGE: import-offline-send [transaction file]");
+            },
+            ListBaseNodes => {
+                println!("Lists the base nodes this wallet uses and their health, the current base node is marked");
+            },
+            Whoami => {
+                println!("Display identity information about this wallet, including: public key, node ID and emoji ID");
+            },
+            Exit | Quit => {
+                println!("Exits the wallet");
+            },
+        }
+    }
+
+    /// Function to process the get-balance command
+    fn process_get_balance(&mut self) -> Result<(), String> {
+        let balance = self
+            .wallet
+            .runtime
+            .block_on(self.wallet.output_manager_service.get_balance())
+            .map_err(|e| format!("Failed to retrieve the balance: {:?}", e))?;
+        println!("Balances:\n{}", balance);
+        Ok(())
+    }
+
+    /// Function to process the send-tari command
+    fn process_send_tari(&mut self, args: &[String]) -> Result<(), String> {
+        let usage = "Command entered incorrectly, please use the following format:\nsend-tari [amount of tari to \
+                     send] [public key or emoji id to send to] [optional: msg]";
+        let amount: MicroTari = args
+            .get(0)
+            .and_then(|v| v.parse::<u64>().ok())
+            .ok_or_else(|| "Please enter a valid amount of tari".to_string())?
+            .into();
+        let dest_pubkey = args.get(1).ok_or_else(|| usage.to_string()).and_then(|key| {
+            parse_emoji_id_or_public_key(key)
+                .ok_or_else(|| "Please enter a valid destination public key or emoji id".to_string())
+        })?;
+        // Use the rest of the command line as the message
+        let msg = args.iter().skip(2).cloned().collect::<Vec<_>>().join(" ");
+
+        self.send_tari(dest_pubkey, amount, msg, None, None, None)
+    }
+
+    /// Function to process the send-tari-from-utxos command
+    fn process_send_tari_from_utxos(&mut self, args: &[String]) -> Result<(), String> {
+        let usage = "Command entered incorrectly, please use the following format:\nsend-tari-from-utxos [amount of \
+                     tari to send] [public key or emoji id to send to] [comma separated UTXO commitments] [optional: \
+                     msg]";
+        let amount: MicroTari = args
+            .get(0)
+            .and_then(|v| v.parse::<u64>().ok())
+            .ok_or_else(|| "Please enter a valid amount of tari".to_string())?
+            .into();
+        let dest_pubkey = args.get(1).ok_or_else(|| usage.to_string()).and_then(|key| {
+            parse_emoji_id_or_public_key(key)
+                .ok_or_else(|| "Please enter a valid destination public key or emoji id".to_string())
+        })?;
+        let inputs = args
+            .get(2)
+            .ok_or_else(|| usage.to_string())?
+            .split(',')
+            .map(parse_commitment)
+            .collect::<Result<Vec<_>, _>>()?;
+        // Use the rest of the command line as the message
+        let msg = args.iter().skip(3).cloned().collect::<Vec<_>>().join(" ");
+
+        self.send_tari(dest_pubkey, amount, msg, Some(inputs), None, None)
+    }
+
+    /// Function to process the send-tari-time-locked command
+    fn process_send_tari_time_locked(&mut self, args: &[String]) -> Result<(), String> {
+        let usage = "Command entered incorrectly, please use the following format:\nsend-tari-time-locked [amount of \
+                     tari to send] [public key or emoji id to send to] [lock height] [maturity height] [optional: msg]";
+        let amount: MicroTari = args
+            .get(0)
+            .and_then(|v| v.parse::<u64>().ok())
+            .ok_or_else(|| "Please enter a valid amount of tari".to_string())?
+            .into();
+        let dest_pubkey = args.get(1).ok_or_else(|| usage.to_string()).and_then(|key| {
+            parse_emoji_id_or_public_key(key)
+                .ok_or_else(|| "Please enter a valid destination public key or emoji id".to_string())
+        })?;
+        let lock_height = args
+            .get(2)
+            .ok_or_else(|| usage.to_string())?
+            .parse::<u64>()
+            .map_err(|_| "Please enter a valid lock height".to_string())?;
+        let maturity = args
+            .get(3)
+            .ok_or_else(|| usage.to_string())?
+            .parse::<u64>()
+            .map_err(|_| "Please enter a valid maturity height".to_string())?;
+        // Use the rest of the command line as the message
+        let msg = args.iter().skip(4).cloned().collect::<Vec<_>>().join(" ");
+
+        self.send_tari(dest_pubkey, amount, msg, None, Some((lock_height, maturity)), None)
+    }
+
+    /// Function to process the send-tari-from-account command
+    fn process_send_tari_from_account(&mut self, args: &[String]) -> Result<(), String> {
+        let usage = "Command entered incorrectly, please use the following format:\nsend-tari-from-account [account] \
+                     [amount of tari to send] [public key or emoji id to send to] [optional: msg]";
+        let account = args.get(0).ok_or_else(|| usage.to_string())?.clone();
+        let amount: MicroTari = args
+            .get(1)
+            .and_then(|v| v.parse::<u64>().ok())
+            .ok_or_else(|| "Please enter a valid amount of tari".to_string())?
+            .into();
+        let dest_pubkey = args.get(2).ok_or_else(|| usage.to_string()).and_then(|key| {
+            parse_emoji_id_or_public_key(key)
+                .ok_or_else(|| "Please enter a valid destination public key or emoji id".to_string())
+        })?;
+        // Use the rest of the command line as the message
+        let msg = args.iter().skip(3).cloned().collect::<Vec<_>>().join(" ");
+
+        self.send_tari(dest_pubkey, amount, msg, None, None, Some(account))
+    }
+
+    /// Send a transaction, waiting for discovery of the destination if required. If `inputs` is provided exactly those
+    /// UTXOs are spent, otherwise a `time_lock` of (lock height, maturity height) can be placed on the transaction or
+    /// the transaction can be funded from a single `account`.
+    fn send_tari(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        msg: String,
+        inputs: Option<Vec<Commitment>>,
+        time_lock: Option<(u64, u64)>,
+        account: Option<String>,
+    ) -> Result<(), String>
+    {
+        let mut txn_service = self.wallet.transaction_service.clone();
+        let send_result_timeout = self.send_result_timeout;
+        self.wallet.runtime.block_on(async move {
+            let event_stream = txn_service.get_event_stream_fused();
+            let result = match (inputs, time_lock, account) {
+                (None, None, Some(account)) => {
+                    txn_service
+                        .send_transaction_from_account(account, dest_pubkey.clone(), amount, FEE_PER_GRAM, msg)
+                        .await
+                },
+                (Some(inputs), _, _) => {
+                    txn_service
+                        .send_transaction_with_inputs(dest_pubkey.clone(), amount, FEE_PER_GRAM, msg, inputs)
+                        .await
+                },
+                (None, Some((lock_height, maturity)), _) => {
+                    txn_service
+                        .send_time_locked_transaction(
+                            dest_pubkey.clone(),
+                            amount,
+                            FEE_PER_GRAM,
+                            Some(lock_height),
+                            maturity,
+                            msg,
+                        )
+                        .await
+                },
+                (None, None, None) => {
+                    txn_service
+                        .send_transaction(dest_pubkey.clone(), amount, FEE_PER_GRAM, msg)
+                        .await
+                },
+            };
+            match result {
+                Ok(tx_id) => {
+                    println!("Sending {} Tari to {} (tx_id: {})", amount, dest_pubkey, tx_id);
+                    let timeout = match send_result_timeout {
+                        Some(timeout) => timeout,
+                        None => return Ok(()),
+                    };
+                    match time::timeout(timeout, utils::wait_for_send_result_event(event_stream, tx_id)).await {
+                        Ok(true) => {
+                            println!("Transaction {} sent to {}", tx_id, dest_pubkey);
+                            Ok(())
+                        },
+                        Ok(false) => Err(format!(
+                            "Transaction {} could not be sent to {}. The peer may be offline. The wallet will keep \
+                             trying to send it the next time it is started.",
+                            tx_id, dest_pubkey
+                        )),
+                        Err(_) => Err(format!(
+                            "Timed out waiting for transaction {} to be sent to {}. The wallet will keep trying to \
+                             send it the next time it is started.",
+                            tx_id, dest_pubkey
+                        )),
+                    }
+                },
+                Err(TransactionServiceError::OutboundSendDiscoveryInProgress(tx_id)) => {
+                    println!(
+                        "No peer found matching that public key. Attempting to discover the peer on the network. 🌎"
+                    );
+                    let start = Instant::now();
+                    match time::timeout(
+                        Duration::from_secs(120),
+                        utils::wait_for_discovery_transaction_event(event_stream, tx_id),
+                    )
+                    .await
+                    {
+                        Ok(true) => {
+                            debug!(
+                                target: LOG_TARGET,
+                                "Discovery succeeded for peer {} after {}ms",
+                                dest_pubkey,
+                                start.elapsed().as_millis()
+                            );
+                            println!("Sending {} Tari to {} (tx_id: {})", amount, dest_pubkey, tx_id);
+                            Ok(())
+                        },
+                        Ok(false) => Err(format!(
+                            "Discovery failed for peer {} after {}ms. The peer may be offline. Please try again later.",
+                            dest_pubkey,
+                            start.elapsed().as_millis()
+                        )),
+                        Err(_) => Err(
+                            "Discovery timed out before the node was discovered. The peer may be offline. Please try \
+                             again later."
+                                .to_string(),
+                        ),
+                    }
+                },
+                Err(TransactionServiceError::OutputManagerError(OutputManagerError::NotEnoughFunds)) => {
+                    Err("Not enough funds to fulfill the transaction.".to_string())
+                },
+                Err(TransactionServiceError::OutputManagerError(OutputManagerError::UnspentOutputNotFound)) => {
+                    Err("One of the selected UTXOs could not be found in your unspent outputs.".to_string())
+                },
+                Err(TransactionServiceError::OutputManagerError(OutputManagerError::AccountNotFound)) => {
+                    Err("No account with that name exists.".to_string())
+                },
+                Err(TransactionServiceError::OutputManagerError(OutputManagerError::WatchOnlyWallet)) => {
+                    Err(WATCH_ONLY_ERROR.to_string())
+                },
+                Err(e) => {
+                    warn!(target: LOG_TARGET, "Error communicating with wallet: {:?}", e);
+                    Err(format!("Something went wrong sending funds: {:?}", e))
+                },
+            }
+        })
+    }
+
+    /// Function to process the list-transactions command
+    fn process_list_transactions(&mut self) -> Result<(), String> {
+        let inbound = self
+            .wallet
+            .runtime
+            .block_on(self.wallet.transaction_service.get_pending_inbound_transactions())
+            .map_err(|e| format!("Failed to retrieve inbound transactions: {:?}", e))?;
+        let outbound = self
+            .wallet
+            .runtime
+            .block_on(self.wallet.transaction_service.get_pending_outbound_transactions())
+            .map_err(|e| format!("Failed to retrieve outbound transactions: {:?}", e))?;
+
+        println!("Inbound Transactions");
+        if inbound.is_empty() {
+            println!("No pending inbound transactions found.");
+        } else {
+            let mut table = Table::new();
+            table.set_titles(vec![
+                "Transaction ID",
+                "Source Public Key",
+                "Amount",
+                "Status",
+                "Receiver State",
+                "Timestamp",
+                "Message",
+            ]);
+            for (tx_id, txn) in inbound {
+                table.add_row(row![
+                    tx_id,
+                    txn.source_public_key,
+                    txn.amount,
+                    txn.status,
+                    txn.receiver_protocol.state,
+                    format_naive_datetime(&txn.timestamp),
+                    txn.message
+                ]);
+            }
+            table.print_std();
+        }
+
+        println!();
+        println!("Outbound Transactions");
+        if outbound.is_empty() {
+            println!("No pending outbound transactions found.");
+        } else {
+            let mut table = Table::new();
+            table.set_titles(vec![
+                "Transaction ID",
+                "Dest Public Key",
+                "Amount",
+                "Fee",
+                "Status",
+                "Sender State",
+                "Timestamp",
+                "Message",
+            ]);
+            for (tx_id, txn) in outbound {
+                table.add_row(row![
+                    tx_id,
+                    txn.destination_public_key,
+                    txn.amount,
+                    txn.fee,
+                    txn.status,
+                    txn.sender_protocol,
+                    format_naive_datetime(&txn.timestamp),
+                    txn.message
+                ]);
+            }
+            table.print_std();
+        }
+        Ok(())
+    }
+
+    /// Function to process the list-completed-transactions command
+    fn process_list_completed_transactions(&mut self, args: &[String]) -> Result<(), String> {
+        let n = args.get(0).and_then(|s| s.parse::<usize>().ok()).unwrap_or(10);
+        let m = args.get(1).and_then(|s| s.parse::<usize>().ok());
+
+        let transactions = self
+            .wallet
+            .runtime
+            .block_on(self.wallet.transaction_service.get_completed_transactions())
+            .map_err(|e| format!("Failed to retrieve completed transactions: {:?}", e))?;
+        print_completed_transactions(transactions, n, m);
+        Ok(())
+    }
+
+    /// Function to process the list-account-transactions command
+    fn process_list_account_transactions(&mut self, args: &[String]) -> Result<(), String> {
+        let account = args.get(0).ok_or_else(|| {
+            "Command entered incorrectly, please use the following format:\nlist-account-transactions [account name] \
+             [optional: last n]"
+                .to_string()
+        })?;
+        let n = args.get(1).and_then(|s| s.parse::<usize>().ok()).unwrap_or(10);
+
+        let transactions = self
+            .wallet
+            .runtime
+            .block_on(
+                self.wallet
+                    .transaction_service
+                    .get_completed_transactions_for_account(account.clone()),
+            )
+            .map_err(|e| format!("Failed to retrieve completed transactions: {:?}", e))?;
+        print_completed_transactions(transactions, n, None);
+        Ok(())
+    }
+
+    /// Function to process the create-account command
+    fn process_create_account(&mut self, args: &[String]) -> Result<(), String> {
+        let name = args.get(0).ok_or_else(|| {
+            "Command entered incorrectly, please use the following format:\ncreate-account [account name]".to_string()
+        })?;
+        self.wallet
+            .runtime
+            .block_on(self.wallet.output_manager_service.create_account(name.clone()))
+            .map_err(|e| match e {
+                OutputManagerError::AccountAlreadyExists => format!("An account named '{}' already exists", name),
+                OutputManagerError::InvalidAccountName => format!("'{}' is not a valid account name", name),
+                e => format!("Failed to create account: {:?}", e),
+            })?;
+        println!("Account '{}' created", name);
+        Ok(())
+    }
+
+    /// Function to process the list-accounts command
+    fn process_list_accounts(&mut self) -> Result<(), String> {
+        let mut oms = self.wallet.output_manager_service.clone();
+        let rows = self
+            .wallet
+            .runtime
+            .block_on(async move {
+                let accounts = oms.get_accounts().await?;
+                let receiving = accounts.iter().find(|a| a.receiving).map(|a| a.name.clone());
+                let mut names = vec![DEFAULT_ACCOUNT.to_string()];
+                names.extend(accounts.into_iter().map(|a| a.name));
+                let mut rows = Vec::with_capacity(names.len());
+                for name in names {
+                    let balance = oms.get_account_balance(name.clone()).await?;
+                    let is_receiving = receiving
+                        .as_ref()
+                        .map(|r| r == &name)
+                        .unwrap_or(name == DEFAULT_ACCOUNT);
+                    rows.push((name, balance, is_receiving));
+                }
+                Ok::<_, OutputManagerError>(rows)
+            })
+            .map_err(|e| format!("Failed to retrieve accounts: {:?}", e))?;
+
+        let mut table = Table::new();
+        table.set_titles(vec![
+            "Account",
+            "Available",
+            "Pending Incoming",
+            "Pending Outgoing",
+            "Receiving",
+        ]);
+        for (name, balance, is_receiving) in rows {
+            table.add_row(row![
+                name,
+                balance.available_balance,
+                balance.pending_incoming_balance,
+                balance.pending_outgoing_balance,
+                if is_receiving { "*" } else { "" }
+            ]);
+        }
+        table.print_std();
+        Ok(())
+    }
+
+    /// Function to process the set-receiving-account command
+    fn process_set_receiving_account(&mut self, args: &[String]) -> Result<(), String> {
+        let name = args.get(0).ok_or_else(|| {
+            "Command entered incorrectly, please use the following format:\nset-receiving-account [account name]"
+                .to_string()
+        })?;
+        self.wallet
+            .runtime
+            .block_on(self.wallet.output_manager_service.set_receiving_account(name.clone()))
+            .map_err(|e| match e {
+                OutputManagerError::AccountNotFound => format!("No account named '{}' exists", name),
+                e => format!("Failed to set the receiving account: {:?}", e),
+            })?;
+        println!("Incoming transactions will be paid into account '{}'", name);
+        Ok(())
+    }
+
+    /// Function to process the cancel-transaction command
+    fn process_cancel_transaction(&mut self, args: &[String]) -> Result<(), String> {
+        let tx_id = args.get(0).and_then(|s| s.parse::<u64>().ok()).ok_or_else(|| {
+            "Please enter a valid transaction ID\nUSAGE: cancel-transaction [transaction id]".to_string()
+        })?;
+
+        self.wallet
+            .runtime
+            .block_on(self.wallet.transaction_service.cancel_transaction(tx_id))
+            .map_err(|e| format!("Failed to cancel transaction: {:?}", e))?;
+        println!("Transaction {} successfully cancelled", tx_id);
+        Ok(())
+    }
+
+    /// Function to process the list-contacts command
+    fn process_list_contacts(&mut self) -> Result<(), String> {
+        let contacts = self
+            .wallet
+            .runtime
+            .block_on(self.wallet.contacts_service.get_contacts())
+            .map_err(|e| format!("Failed to retrieve contacts: {:?}", e))?;
+        if contacts.is_empty() {
+            println!("No contacts found.");
+            return Ok(());
+        }
+
+        let mut table = Table::new();
+        table.set_titles(vec!["Alias", "Public Key", "Emoji ID"]);
+        for contact in contacts {
+            let emoji_id = EmojiId::from_pubkey(&contact.public_key);
+            table.add_row(row![contact.alias, contact.public_key, emoji_id]);
+        }
+        table.print_std();
+        Ok(())
+    }
+
+    /// Function to process the add-contact command
+    fn process_add_contact(&mut self, args: &[String]) -> Result<(), String> {
+        let usage = "Command entered incorrectly, please use the following format:\nadd-contact [alias] [public key \
+                     or emoji id]";
+        let alias = args.get(0).ok_or_else(|| usage.to_string())?.clone();
+        let public_key = args.get(1).ok_or_else(|| usage.to_string()).and_then(|key| {
+            parse_emoji_id_or_public_key(key).ok_or_else(|| "Please enter a valid public key or emoji id".to_string())
+        })?;
+
+        self.wallet
+            .runtime
+            .block_on(self.wallet.contacts_service.upsert_contact(Contact {
+                alias: alias.clone(),
+                public_key,
+            }))
+            .map_err(|e| format!("Failed to add contact: {:?}", e))?;
+        println!("Contact '{}' saved", alias);
+        Ok(())
+    }
+
+    /// Function to process the remove-contact command
+    fn process_remove_contact(&mut self, args: &[String]) -> Result<(), String> {
+        let public_key = args
+            .get(0)
+            .ok_or_else(|| {
+                "Command entered incorrectly, please use the following format:\nremove-contact [public key or emoji id]"
+                    .to_string()
+            })
+            .and_then(|key| {
+                parse_emoji_id_or_public_key(key)
+                    .ok_or_else(|| "Please enter a valid public key or emoji id".to_string())
+            })?;
+
+        let contact = self
+            .wallet
+            .runtime
+            .block_on(self.wallet.contacts_service.remove_contact(public_key))
+            .map_err(|e| format!("Failed to remove contact: {:?}", e))?;
+        println!("Contact '{}' removed", contact.alias);
+        Ok(())
+    }
+
+    /// Function to process the get-seed-words command
+    fn process_get_seed_words(&mut self) -> Result<(), String> {
+        let seed_words = self
+            .wallet
+            .runtime
+            .block_on(self.wallet.output_manager_service.get_seed_words())
+            .map_err(|e| match e {
+                OutputManagerError::WatchOnlyWallet => WATCH_ONLY_ERROR.to_string(),
+                e => format!("Failed to retrieve seed words: {:?}", e),
+            })?;
+        println!("Seed words (keep these secret, they can be used to recover your funds):");
+        println!("{}", seed_words.join(" "));
+        Ok(())
+    }
+
+    /// Function to process the list-utxos command. The maturity is calculated against the chain height reported by the
+    /// current base node, if it is known.
+    fn process_list_unspent_outputs(&mut self) -> Result<(), String> {
+        let current_height = self.current_chain_height();
+        let unspent_outputs = self
+            .wallet
+            .runtime
+            .block_on(self.wallet.output_manager_service.get_annotated_unspent_outputs())
+            .map_err(|e| format!("Failed to retrieve unspent outputs: {:?}", e))?;
+        if unspent_outputs.is_empty() {
+            println!("\nNo valid UTXOs found at this time\n");
+            return Ok(());
+        }
+
+        println!(
+            "\nYou have {} UTXOs: (value, commitment, mature in ? blocks, flags, frozen, account, label)",
+            unspent_outputs.len()
+        );
+        for uo in unspent_outputs.iter() {
+            let mature_in = match current_height {
+                Some(height) => uo.output.features.maturity.saturating_sub(height).to_string(),
+                None => "?".to_string(),
+            };
+            println!(
+                "   {}, {}, {:>3}, {:?}, {}, {}, {}",
+                uo.output.value,
+                uo.commitment.to_hex(),
+                mature_in,
+                uo.output.features.flags,
+                if uo.frozen { "frozen" } else { "-" },
+                uo.account,
+                uo.label.as_ref().map(String::as_str).unwrap_or("-")
+            );
+        }
+        println!();
+        Ok(())
+    }
+
+    /// Function to process the freeze-utxo and unfreeze-utxo commands
+    fn process_set_utxo_frozen(&mut self, args: &[String], frozen: bool) -> Result<(), String> {
+        let command = if frozen { "freeze-utxo" } else { "unfreeze-utxo" };
+        let commitment = args
+            .get(0)
+            .ok_or_else(|| {
+                format!(
+                    "Command entered incorrectly, please use the following format:\n{} [UTXO commitment]",
+                    command
+                )
+            })
+            .and_then(|c| parse_commitment(c))?;
+
+        let mut output_manager = self.wallet.output_manager_service.clone();
+        self.wallet
+            .runtime
+            .block_on(async move {
+                if frozen {
+                    output_manager.freeze_output(commitment).await
+                } else {
+                    output_manager.unfreeze_output(commitment).await
+                }
+            })
+            .map_err(|e| format!("Failed to update the UTXO: {:?}", e))?;
+        println!("UTXO {}", if frozen { "frozen" } else { "unfrozen" });
+        Ok(())
+    }
+
+    /// Function to process the label-utxo command
+    fn process_label_utxo(&mut self, args: &[String]) -> Result<(), String> {
+        let commitment = args
+            .get(0)
+            .ok_or_else(|| {
+                "Command entered incorrectly, please use the following format:\nlabel-utxo [UTXO commitment] \
+                 [optional: label]"
+                    .to_string()
+            })
+            .and_then(|c| parse_commitment(c))?;
+        // Use the rest of the command line as the label
+        let label = args.iter().skip(1).cloned().collect::<Vec<_>>().join(" ");
+        let label = if label.is_empty() { None } else { Some(label) };
+
+        self.wallet
+            .runtime
+            .block_on(
+                self.wallet
+                    .output_manager_service
+                    .set_output_label(commitment, label.clone()),
+            )
+            .map_err(|e| format!("Failed to label the UTXO: {:?}", e))?;
+        match label {
+            Some(label) => println!("UTXO labelled '{}'", label),
+            None => println!("UTXO label cleared"),
+        }
+        Ok(())
+    }
+
+    /// Function to process the clear-utxo-annotation command
+    fn process_clear_utxo_annotation(&mut self, args: &[String]) -> Result<(), String> {
+        let commitment = args
+            .get(0)
+            .ok_or_else(|| {
+                "Command entered incorrectly, please use the following format:
+clear-utxo-annotation [UTXO commitment]"
+                    .to_string()
+            })
+            .and_then(|c| parse_commitment(c))?;
+
+        self.wallet
+            .runtime
+            .block_on(self.wallet.output_manager_service.clear_output_annotation(commitment))
+            .map_err(|e| format!("Failed to clear the UTXO annotation: {:?}", e))?;
+        println!("UTXO label cleared and UTXO unfrozen");
+        Ok(())
+    }
+
+    /// Function to process the coin-split command
+    fn process_coin_split(&mut self, args: &[String]) -> Result<(), String> {
+        let amount_per_split = args.get(0).and_then(|v| v.parse::<u64>().ok());
+        let split_count = args.get(1).and_then(|v| v.parse::<usize>().ok());
+        let (amount_per_split, split_count) = match (amount_per_split, split_count) {
+            (Some(amount), Some(count)) => (MicroTari::from(amount), count),
+            _ => {
+                return Err(
+                    "Command entered incorrectly, please use the following format:\ncoin-split [amount of tari to \
+                     allocated to each UTXO] [number of UTXOs to create]"
+                        .to_string(),
+                )
+            },
+        };
+
+        // Use output manager service to get utxo and create the coin split transaction
+        let mut output_manager = self.wallet.output_manager_service.clone();
+        let mut txn_service = self.wallet.transaction_service.clone();
+        self.wallet.runtime.block_on(async move {
+            let (tx_id, tx, fee, amount) = output_manager
+                .create_coin_split(amount_per_split, split_count, FEE_PER_GRAM, None)
+                .await
+                .map_err(|e| format!("Something went wrong creating a coin split transaction: {:?}", e))?;
+            txn_service
+                .submit_transaction(tx_id, tx, fee, amount, "Coin split".into())
+                .await
+                .map_err(|e| format!("Something went wrong submitting the coin split transaction: {:?}", e))?;
+            println!("Coin split transaction created with tx_id:\n{}", tx_id);
+            Ok(())
+        })
+    }
+
+    /// Function to process the consolidate-utxos command
+    fn process_consolidate_utxos(&mut self, args: &[String]) -> Result<(), String> {
+        let max_inputs = match args.get(0).and_then(|v| v.parse::<usize>().ok()) {
+            Some(max_inputs) => max_inputs,
+            None => {
+                return Err("Command entered incorrectly, please use the following format:
+consolidate-utxos [maximum number of UTXOs to merge]"
+                    .to_string())
+            },
+        };
+
+        let mut output_manager = self.wallet.output_manager_service.clone();
+        let mut txn_service = self.wallet.transaction_service.clone();
+        self.wallet.runtime.block_on(async move {
+            let (tx_id, tx, fee, amount) = output_manager
+                .create_coin_consolidation(max_inputs, FEE_PER_GRAM, None)
+                .await
+                .map_err(|e| format!("Something went wrong creating a consolidation transaction: {:?}", e))?;
+            let input_count = tx.body.inputs().len();
+            txn_service
+                .submit_transaction(tx_id, tx, fee, amount, "UTXO consolidation".into())
+                .await
+                .map_err(|e| format!("Something went wrong submitting the consolidation transaction: {:?}", e))?;
+            println!(
+                "Consolidation transaction merging {} UTXOs created with tx_id:\n{}",
+                input_count, tx_id
+            );
+            Ok(())
+        })
+    }
+
+    /// Function to process the watch-output command
+    fn process_watch_output(&mut self, args: &[String]) -> Result<(), String> {
+        let usage = "Command entered incorrectly, please use the following format:\nwatch-output [UTXO commitment] \
+                     [value] [optional: maturity] [optional: label]";
+        let commitment = args
+            .get(0)
+            .ok_or_else(|| usage.to_string())
+            .and_then(|c| parse_commitment(c))?;
+        let value: MicroTari = args
+            .get(1)
+            .and_then(|v| v.parse::<u64>().ok())
+            .ok_or_else(|| usage.to_string())?
+            .into();
+        let maturity = match args.get(2) {
+            Some(m) => m
+                .parse::<u64>()
+                .map_err(|_| "Please enter a valid maturity height".to_string())?,
+            None => 0,
+        };
+        // Use the rest of the command line as the label
+        let label = args.iter().skip(3).cloned().collect::<Vec<_>>().join(" ");
+        let label = if label.is_empty() { None } else { Some(label) };
+
+        self.wallet
+            .runtime
+            .block_on(self.wallet.output_manager_service.add_watched_output(
+                commitment,
+                OutputFeatures::with_maturity(maturity),
+                value,
+                label,
+            ))
+            .map_err(|e| match e {
+                OutputManagerError::DuplicateOutput => "That UTXO is already being watched".to_string(),
+                e => format!("Failed to watch the UTXO: {:?}", e),
+            })?;
+        println!("Watching UTXO, its status will be updated on the next base node sync");
+        Ok(())
+    }
+
+    /// Function to process the unwatch-output command
+    fn process_unwatch_output(&mut self, args: &[String]) -> Result<(), String> {
+        let commitment = args
+            .get(0)
+            .ok_or_else(|| {
+                "Command entered incorrectly, please use the following format:\nunwatch-output [UTXO commitment]"
+                    .to_string()
+            })
+            .and_then(|c| parse_commitment(c))?;
+
+        self.wallet
+            .runtime
+            .block_on(self.wallet.output_manager_service.remove_watched_output(commitment))
+            .map_err(|e| match e {
+                OutputManagerError::WatchedOutputNotFound => "That UTXO is not being watched".to_string(),
+                e => format!("Failed to unwatch the UTXO: {:?}", e),
+            })?;
+        println!("UTXO is no longer being watched");
+        Ok(())
+    }
+
+    /// Function to process the list-watched-outputs command
+    fn process_list_watched_outputs(&mut self) -> Result<(), String> {
+        let watched_outputs = self
+            .wallet
+            .runtime
+            .block_on(self.wallet.output_manager_service.get_watched_outputs())
+            .map_err(|e| format!("Failed to retrieve watched outputs: {:?}", e))?;
+        if watched_outputs.is_empty() {
+            println!("No watched UTXOs found.");
+            return Ok(());
+        }
+
+        let mut table = Table::new();
+        table.set_titles(vec!["Value", "Commitment", "Maturity", "Status", "Label"]);
+        for wo in watched_outputs {
+            table.add_row(row![
+                wo.value,
+                wo.commitment.to_hex(),
+                wo.features.maturity,
+                format!("{:?}", wo.status),
+                wo.label.unwrap_or_else(|| "-".to_string())
+            ]);
+        }
+        table.print_std();
+        Ok(())
+    }
+
+    /// Function to process the create-offline-send command
+    fn process_create_offline_send(&mut self, args: &[String]) -> Result<(), String> {
+        let usage = "Command entered incorrectly, please use the following format:\ncreate-offline-send [amount of \
+                     tari to send] [public key or emoji id to send to] [output file] [optional: msg]";
+        let amount: MicroTari = args
+            .get(0)
+            .and_then(|v| v.parse::<u64>().ok())
+            .ok_or_else(|| "Please enter a valid amount of tari".to_string())?
+            .into();
+        let dest_pubkey = args.get(1).ok_or_else(|| usage.to_string()).and_then(|key| {
+            parse_emoji_id_or_public_key(key)
+                .ok_or_else(|| "Please enter a valid destination public key or emoji id".to_string())
+        })?;
+        let output_file = args.get(2).ok_or_else(|| usage.to_string())?;
+        // Use the rest of the command line as the message
+        let msg = args.iter().skip(3).cloned().collect::<Vec<_>>().join(" ");
+
+        UnsignedTransactionRequest::new(dest_pubkey, amount, FEE_PER_GRAM, msg)
+            .to_file(output_file)
+            .map_err(|e| format!("Failed to write the send request: {:?}", e))?;
+        println!(
+            "Send request written to {}, sign it with sign-offline-send in the wallet holding the spending keys",
+            output_file
+        );
+        Ok(())
+    }
+
+    /// Function to process the sign-offline-send command
+    fn process_sign_offline_send(&mut self, args: &[String]) -> Result<(), String> {
+        let (input_file, output_file) = match (args.get(0), args.get(1)) {
+            (Some(i), Some(o)) => (i, o),
+            _ => {
+                return Err(
+                    "Command entered incorrectly, please use the following format:\nsign-offline-send [request file] \
+                     [output file]"
+                        .to_string(),
+                )
+            },
+        };
+        let request = UnsignedTransactionRequest::from_file(input_file)
+            .map_err(|e| format!("Failed to read the send request: {:?}", e))?;
+
+        let sender_message = self
+            .wallet
+            .runtime
+            .block_on(self.wallet.transaction_service.sign_offline_transaction(request))
+            .map_err(|e| match e {
+                TransactionServiceError::OutputManagerError(OutputManagerError::NotEnoughFunds) => {
+                    "Not enough funds to fulfill the transaction.".to_string()
+                },
+                TransactionServiceError::OutputManagerError(OutputManagerError::WatchOnlyWallet) => {
+                    WATCH_ONLY_ERROR.to_string()
+                },
+                e => format!("Failed to sign the send request: {:?}", e),
+            })?;
+        sender_message
+            .to_file(output_file)
+            .map_err(|e| format!("Failed to write the sender message: {:?}", e))?;
+        println!(
+            "Sender message written to {}, give it to the recipient to reply to with reply-offline-send",
+            output_file
+        );
+        Ok(())
+    }
+
+    /// Function to process the reply-offline-send command
+    fn process_reply_offline_send(&mut self, args: &[String]) -> Result<(), String> {
+        let (input_file, output_file) = match (args.get(0), args.get(1)) {
+            (Some(i), Some(o)) => (i, o),
+            _ => {
+                return Err(
+                    "Command entered incorrectly, please use the following format:\nreply-offline-send [sender \
+                     message file] [output file]"
+                        .to_string(),
+                )
+            },
+        };
+        let sender_message = OfflineSenderMessage::from_file(input_file)
+            .map_err(|e| format!("Failed to read the sender message: {:?}", e))?;
+
+        let reply = self
+            .wallet
+            .runtime
+            .block_on(
+                self.wallet
+                    .transaction_service
+                    .reply_to_offline_transaction(sender_message),
+            )
+            .map_err(|e| match e {
+                TransactionServiceError::RepeatedMessageError => {
+                    "That transaction has already been replied to".to_string()
+                },
+                TransactionServiceError::OutputManagerError(OutputManagerError::WatchOnlyWallet) => {
+                    WATCH_ONLY_ERROR.to_string()
+                },
+                e => format!("Failed to reply to the sender message: {:?}", e),
+            })?;
+        reply
+            .to_file(output_file)
+            .map_err(|e| format!("Failed to write the reply: {:?}", e))?;
+        println!(
+            "Reply written to {}, give it back to the sender to finalize with finalize-offline-send",
+            output_file
+        );
+        Ok(())
+    }
+
+    /// Function to process the finalize-offline-send command
+    fn process_finalize_offline_send(&mut self, args: &[String]) -> Result<(), String> {
+        let (input_file, output_file) = match (args.get(0), args.get(1)) {
+            (Some(i), Some(o)) => (i, o),
+            _ => {
+                return Err(
+                    "Command entered incorrectly, please use the following format:\nfinalize-offline-send [reply \
+                     file] [output file]"
+                        .to_string(),
+                )
+            },
+        };
+        let reply =
+            OfflineRecipientReply::from_file(input_file).map_err(|e| format!("Failed to read the reply: {:?}", e))?;
+
+        let finalized_transaction = self
+            .wallet
+            .runtime
+            .block_on(self.wallet.transaction_service.finalize_offline_transaction(reply))
+            .map_err(|e| match e {
+                TransactionServiceError::TransactionDoesNotExistError => {
+                    "No pending send matches that reply".to_string()
+                },
+                TransactionServiceError::InvalidSourcePublicKey => {
+                    "That reply does not come from the recipient of the send".to_string()
+                },
+                e => format!("Failed to finalize the transaction: {:?}", e),
+            })?;
+        finalized_transaction
+            .to_file(output_file)
+            .map_err(|e| format!("Failed to write the transaction: {:?}", e))?;
+        println!(
+            "Transaction written to {}, broadcast it from an online wallet with import-offline-send",
+            output_file
+        );
+        Ok(())
+    }
+
+    /// Function to process the import-offline-send command
+    fn process_import_offline_send(&mut self, args: &[String]) -> Result<(), String> {
+        let input_file = args.get(0).ok_or_else(|| {
+            "Command entered incorrectly, please use the following format:\nimport-offline-send [transaction file]"
+                .to_string()
+        })?;
+        let finalized_transaction = OfflineFinalizedTransaction::from_file(input_file)
+            .map_err(|e| format!("Failed to read the transaction: {:?}", e))?;
+
+        let tx_id = self
+            .wallet
+            .runtime
+            .block_on(
+                self.wallet
+                    .transaction_service
+                    .import_offline_transaction(finalized_transaction),
+            )
+            .map_err(|e| format!("Failed to import the transaction: {:?}", e))?;
+        println!(
+            "Transaction imported and submitted for broadcast with tx_id:\n{}",
+            tx_id
+        );
+        Ok(())
+    }
+
+    /// Function to process the list-base-nodes command
+    fn process_list_base_nodes(&mut self) -> Result<(), String> {
+        let base_nodes = self
+            .wallet
+            .runtime
+            .block_on(self.wallet.base_node_service.get_base_nodes())
+            .map_err(|e| format!("Failed to retrieve base nodes: {:?}", e))?;
+        let current = self
+            .wallet
+            .runtime
+            .block_on(self.wallet.base_node_service.get_current_base_node())
+            .map_err(|e| format!("Failed to retrieve the current base node: {:?}", e))?;
+        if base_nodes.is_empty() {
+            println!("No base nodes have been set. Use the --base-node argument or the wallet base_nodes setting.");
+            return Ok(());
+        }
+
+        let mut table = Table::new();
+        table.set_titles(vec!["", "Public Key", "Latency (ms)", "Chain Height", "Missed Pings"]);
+        for node in base_nodes {
+            let marker = if current.as_ref() == Some(&node.public_key) {
+                "*"
+            } else {
+                ""
+            };
+            table.add_row(row![
+                marker,
+                node.public_key,
+                node.latency.map(|l| l.to_string()).unwrap_or_else(|| "-".to_string()),
+                node.chain_height
+                    .map(|h| h.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                node.missed_pings
+            ]);
+        }
+        table.print_std();
+        Ok(())
+    }
+
+    /// Function to process the whoami command
+    fn process_whoami(&self) {
+        let node_identity = self.wallet.comms.node_identity();
+        println!("{}", node_identity);
+        let emoji_id = EmojiId::from_pubkey(node_identity.public_key());
+        println!("Emoji ID: {}", emoji_id);
+        println!();
+        let qr_link = format!("tari://{}/pubkey/{}", self.network, node_identity.public_key().to_hex());
+        let code = QrCode::new(qr_link).unwrap();
+        let image = code
+            .render::<unicode::Dense1x2>()
+            .dark_color(unicode::Dense1x2::Dark)
+            .light_color(unicode::Dense1x2::Light)
+            .build();
+        println!("{}", image);
+    }
+
+    /// Returns the chain height reported by the current base node, if one is known
+    fn current_chain_height(&mut self) -> Option<u64> {
+        let current = self
+            .wallet
+            .runtime
+            .block_on(self.wallet.base_node_service.get_current_base_node())
+            .ok()??;
+        self.wallet
+            .runtime
+            .block_on(self.wallet.base_node_service.get_base_nodes())
+            .ok()?
+            .into_iter()
+            .find(|node| node.public_key == current)
+            .and_then(|node| node.chain_height)
+    }
+}
+
+/// Parse a hex encoded UTXO commitment as displayed by the list-utxos command
+fn parse_commitment(commitment: &str) -> Result<Commitment, String> {
+    Commitment::from_hex(commitment.trim()).map_err(|_| format!("'{}' is not a valid UTXO commitment", commitment))
+}
+
+/// Print the `n` most recent completed transactions, or the `m` transactions after the `n` most recent ones if `m` is
+/// given
+fn print_completed_transactions(transactions: HashMap<u64, CompletedTransaction>, n: usize, m: Option<usize>) {
+    if transactions.is_empty() {
+        println!("No completed transactions found.");
+        return;
+    }
+
+    let mut transactions = transactions.into_iter().map(|(_, txn)| txn).collect::<Vec<_>>();
+    transactions.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    let transactions = match m {
+        Some(m) => transactions.into_iter().skip(n).take(m).collect::<Vec<_>>(),
+        None => transactions.into_iter().take(n).collect::<Vec<_>>(),
+    };
+
+    let mut table = Table::new();
+    table.set_titles(vec![
+        "Transaction ID",
+        "Sender",
+        "Receiver",
+        "Amount",
+        "Fee",
+        "Status",
+        "Confirmations",
+        "Timestamp",
+        "Message",
+    ]);
+    for txn in transactions {
+        table.add_row(row![
+            txn.tx_id,
+            txn.source_public_key,
+            txn.destination_public_key,
+            txn.amount,
+            txn.fee,
+            txn.status,
+            txn.confirmations
+                .map(|c| c.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            format_naive_datetime(&txn.timestamp),
+            txn.message
+        ]);
+    }
+    table.print_std();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::OsRng;
+    use tari_core::transactions::types::{CommitmentFactory, PrivateKey};
+    use tari_crypto::{commitment::HomomorphicCommitmentFactory, keys::SecretKey};
+
+    #[test]
+    fn command_names() {
+        assert_eq!(WalletCommand::SendTari.to_string(), "send-tari");
+        assert_eq!(
+            WalletCommand::from_str("list-completed-transactions").unwrap(),
+            WalletCommand::ListCompletedTransactions
+        );
+        assert!(WalletCommand::from_str("make-it-rain").is_err());
+        assert_eq!(
+            WalletCommand::from_str("clear-utxo-annotation").unwrap(),
+            WalletCommand::ClearUtxoAnnotation
+        );
+        assert_eq!(
+            WalletCommand::from_str("send-tari-from-utxos").unwrap(),
+            WalletCommand::SendTariFromUtxos
+        );
+        assert_eq!(
+            WalletCommand::from_str("send-tari-time-locked").unwrap(),
+            WalletCommand::SendTariTimeLocked
+        );
+        assert_eq!(
+            WalletCommand::from_str("send-tari-from-account").unwrap(),
+            WalletCommand::SendTariFromAccount
+        );
+        assert_eq!(
+            WalletCommand::from_str("set-receiving-account").unwrap(),
+            WalletCommand::SetReceivingAccount
+        );
+        assert_eq!(
+            WalletCommand::from_str("list-watched-outputs").unwrap(),
+            WalletCommand::ListWatchedOutputs
+        );
+        assert_eq!(
+            WalletCommand::from_str("finalize-offline-send").unwrap(),
+            WalletCommand::FinalizeOfflineSend
+        );
+    }
+
+    #[test]
+    fn parses_commitments() {
+        let factory = CommitmentFactory::default();
+        let commitment = factory.commit_value(&PrivateKey::random(&mut OsRng), 100);
+
+        assert_eq!(parse_commitment(&format!(" {} ", commitment.to_hex())), Ok(commitment));
+        assert!(parse_commitment("not a commitment").is_err());
+        assert!(parse_commitment("").is_err());
+    }
+}
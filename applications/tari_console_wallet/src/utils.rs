@@ -0,0 +1,154 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use chrono::NaiveDateTime;
+use futures::{Stream, StreamExt};
+use std::sync::Arc;
+use tari_comms::types::CommsPublicKey;
+use tari_core::tari_utilities::hex::Hex;
+use tari_wallet::{transaction_service::handle::TransactionEvent, util::emoji::EmojiId};
+use tokio::sync::broadcast::RecvError;
+
+pub const LOG_TARGET: &str = "wallet::console::utils";
+
+/// Asynchronously processes the event stream checking to see if the given tx_id is present or not
+/// ## Parameters
+/// `event_stream` - The stream of events to search
+/// `expected_tx_id` - The transaction id to be searched for
+///
+/// ## Returns
+/// True if found, false otherwise
+pub async fn wait_for_discovery_transaction_event<S>(mut event_stream: S, expected_tx_id: u64) -> bool
+where S: Stream<Item = Result<Arc<TransactionEvent>, RecvError>> + Unpin {
+    loop {
+        match event_stream.next().await {
+            Some(event_result) => match event_result {
+                Ok(event) => {
+                    if let TransactionEvent::TransactionDirectSendResult(tx_id, is_success) = &*event {
+                        if *tx_id == expected_tx_id {
+                            break *is_success;
+                        }
+                    }
+                },
+                Err(e) => {
+                    log::error!(target: LOG_TARGET, "Error reading from event broadcast channel {:?}", e);
+                    break false;
+                },
+            },
+            None => {
+                break false;
+            },
+        }
+    }
+}
+
+/// Waits for the result of sending a transaction directly and via store and forward
+/// ## Parameters
+/// `event_stream` - The stream of events to search
+/// `expected_tx_id` - The transaction id to wait for
+///
+/// ## Returns
+/// True as soon as either send succeeds, false if both fail or the stream ends
+pub async fn wait_for_send_result_event<S>(mut event_stream: S, expected_tx_id: u64) -> bool
+where S: Stream<Item = Result<Arc<TransactionEvent>, RecvError>> + Unpin {
+    let mut direct_failed = false;
+    let mut saf_failed = false;
+    while let Some(event_result) = event_stream.next().await {
+        match event_result {
+            Ok(event) => match &*event {
+                TransactionEvent::TransactionDirectSendResult(tx_id, is_success) if *tx_id == expected_tx_id => {
+                    if *is_success {
+                        return true;
+                    }
+                    direct_failed = true;
+                },
+                TransactionEvent::TransactionStoreForwardSendResult(tx_id, is_success) if *tx_id == expected_tx_id => {
+                    if *is_success {
+                        return true;
+                    }
+                    saf_failed = true;
+                },
+                _ => {},
+            },
+            Err(e) => {
+                log::error!(target: LOG_TARGET, "Error reading from event broadcast channel {:?}", e);
+                return false;
+            },
+        }
+        if direct_failed && saf_failed {
+            return false;
+        }
+    }
+    false
+}
+
+/// Returns a CommsPublicKey from either a emoji id or a public key
+pub fn parse_emoji_id_or_public_key(key: &str) -> Option<CommsPublicKey> {
+    EmojiId::str_to_pubkey(&key.trim().replace('|', ""))
+        .or_else(|_| CommsPublicKey::from_hex(key))
+        .ok()
+}
+
+/// Standard formatting helper function for a NaiveDateTime
+pub fn format_naive_datetime(dt: &NaiveDateTime) -> String {
+    dt.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tari_core::transactions::types::PublicKey;
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+    #[test]
+    fn parses_public_key_and_emoji_id() {
+        let (_, public_key) = PublicKey::random_keypair(&mut rand::rngs::OsRng);
+        let emoji_id = EmojiId::from_pubkey(&public_key);
+
+        assert_eq!(
+            parse_emoji_id_or_public_key(&public_key.to_hex()),
+            Some(public_key.clone())
+        );
+        assert_eq!(parse_emoji_id_or_public_key(&emoji_id.to_string()), Some(public_key));
+        assert_eq!(parse_emoji_id_or_public_key("not a key"), None);
+    }
+
+    #[tokio_macros::test_basic]
+    async fn waits_for_a_successful_send_result() {
+        let events = vec![
+            Ok(Arc::new(TransactionEvent::TransactionDirectSendResult(2, true))),
+            Ok(Arc::new(TransactionEvent::TransactionDirectSendResult(1, false))),
+            Ok(Arc::new(TransactionEvent::TransactionStoreForwardSendResult(1, true))),
+        ];
+        assert!(wait_for_send_result_event(futures::stream::iter(events), 1).await);
+
+        let events = vec![
+            Ok(Arc::new(TransactionEvent::TransactionStoreForwardSendResult(1, false))),
+            Ok(Arc::new(TransactionEvent::TransactionDirectSendResult(1, false))),
+            Ok(Arc::new(TransactionEvent::TransactionDirectSendResult(1, true))),
+        ];
+        assert!(!wait_for_send_result_event(futures::stream::iter(events), 1).await);
+
+        let events = vec![Ok(Arc::new(TransactionEvent::TransactionDirectSendResult(1, false)))];
+        assert!(!wait_for_send_result_event(futures::stream::iter(events), 1).await);
+    }
+}
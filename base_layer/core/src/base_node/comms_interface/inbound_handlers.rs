@@ -44,7 +44,10 @@ use log::*;
 use std::sync::Arc;
 use strum_macros::Display;
 use tari_broadcast_channel::Publisher;
-use tari_comms::types::CommsPublicKey;
+use tari_comms::{
+    peer_manager::{Offence, PeerManager},
+    types::CommsPublicKey,
+};
 use tari_crypto::tari_utilities::{hash::Hashable, hex::Hex};
 use tokio::sync::RwLock;
 
@@ -67,6 +70,7 @@ where T: BlockchainBackend + 'static
     mempool: Mempool<T>,
     consensus_manager: ConsensusManager,
     outbound_nci: OutboundNodeCommsInterface,
+    peer_manager: Arc<PeerManager>,
 }
 
 impl<T> InboundNodeCommsHandlers<T>
@@ -79,6 +83,7 @@ where T: BlockchainBackend + 'static
         mempool: Mempool<T>,
         consensus_manager: ConsensusManager,
         outbound_nci: OutboundNodeCommsInterface,
+        peer_manager: Arc<PeerManager>,
     ) -> Self
     {
         Self {
@@ -87,6 +92,7 @@ where T: BlockchainBackend + 'static
             mempool,
             consensus_manager,
             outbound_nci,
+            peer_manager,
         }
     }
 
@@ -270,6 +276,9 @@ where T: BlockchainBackend + 'static
             },
             Err(e) => {
                 error!(target: LOG_TARGET, "Block validation failed: {:?}", e);
+                if let Some(source_peer) = source_peer.as_ref().filter(|_| is_invalid_block_error(&e)) {
+                    self.report_invalid_block(source_peer).await;
+                }
                 BlockEvent::Invalid((Box::new(block.clone()), e))
             },
         };
@@ -300,6 +309,24 @@ where T: BlockchainBackend + 'static
         Ok(())
     }
 
+    async fn report_invalid_block(&self, source_peer: &CommsPublicKey) {
+        match self
+            .peer_manager
+            .report_offence(source_peer, Offence::InvalidBlock)
+            .await
+        {
+            Ok(true) => warn!(
+                target: LOG_TARGET,
+                "Peer {} was banned for repeatedly sending invalid blocks", source_peer
+            ),
+            Ok(false) => {},
+            Err(err) => debug!(
+                target: LOG_TARGET,
+                "Failed to report invalid block from peer {}: {:?}", source_peer, err
+            ),
+        }
+    }
+
     async fn get_target_difficulty(&self, pow_algo: PowAlgorithm) -> Result<Difficulty, CommsInterfaceError> {
         let height_of_longest_chain = async_db::get_metadata(self.blockchain_db.clone())
             .await?
@@ -327,6 +354,17 @@ where T: BlockchainBackend + 'static
     }
 }
 
+/// Returns true if the error indicates that the block itself is invalid, as opposed to a local error
+fn is_invalid_block_error(err: &ChainStorageError) -> bool {
+    match err {
+        ChainStorageError::ValidationError { .. } |
+        ChainStorageError::MismatchedMmrRoot(_) |
+        ChainStorageError::InvalidBlock |
+        ChainStorageError::UnspendableInput => true,
+        _ => false,
+    }
+}
+
 impl<T> Clone for InboundNodeCommsHandlers<T>
 where T: BlockchainBackend + 'static
 {
@@ -338,6 +376,7 @@ where T: BlockchainBackend + 'static
             mempool: self.mempool.clone(),
             consensus_manager: self.consensus_manager.clone(),
             outbound_nci: self.outbound_nci.clone(),
+            peer_manager: self.peer_manager.clone(),
         }
     }
 }
@@ -36,6 +36,7 @@ use futures::{channel::mpsc::unbounded as futures_mpsc_channel_unbounded, future
 use log::*;
 use std::{convert::TryFrom, sync::Arc};
 use tari_broadcast_channel::bounded;
+use tari_comms::peer_manager::PeerManager;
 use tari_comms_dht::outbound::OutboundMessageRequester;
 use tari_p2p::{
     comms_connector::PeerMessage,
@@ -64,6 +65,7 @@ where T: BlockchainBackend
     mempool: Mempool<T>,
     consensus_manager: ConsensusManager,
     config: BaseNodeServiceConfig,
+    peer_manager: Arc<PeerManager>,
 }
 
 impl<T> BaseNodeServiceInitializer<T>
@@ -76,6 +78,7 @@ where T: BlockchainBackend
         mempool: Mempool<T>,
         consensus_manager: ConsensusManager,
         config: BaseNodeServiceConfig,
+        peer_manager: Arc<PeerManager>,
     ) -> Self
     {
         Self {
@@ -84,6 +87,7 @@ where T: BlockchainBackend
             mempool,
             consensus_manager,
             config,
+            peer_manager,
         }
     }
 
@@ -178,6 +182,7 @@ where T: BlockchainBackend + 'static
             self.mempool.clone(),
             self.consensus_manager.clone(),
             outbound_nci.clone(),
+            self.peer_manager.clone(),
         );
         let config = self.config;
 
@@ -28,6 +28,7 @@ use crate::{
         states::{BaseNodeState, BlockSyncConfig, StateEvent},
     },
     chain_storage::{BlockchainBackend, BlockchainDatabase},
+    metrics,
 };
 use futures::{future, future::Either, SinkExt};
 use log::*;
@@ -132,6 +133,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeStateMachine<B> {
     pub async fn run(mut self) {
         use crate::base_node::states::BaseNodeState::*;
         let mut state = Starting(states::Starting);
+        metrics::set_base_node_state(&state);
         loop {
             if let Shutdown(reason) = &state {
                 debug!(
@@ -153,6 +155,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeStateMachine<B> {
                 "=== Base Node event in State [{}]:  {}", state, next_event
             );
             state = self.transition(state, next_event);
+            metrics::set_base_node_state(&state);
         }
     }
 
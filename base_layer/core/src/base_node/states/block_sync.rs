@@ -333,7 +333,13 @@ async fn find_chain_split_height<B: BlockchainBackend + 'static>(
                         target: LOG_TARGET,
                         "Banning peer {} from local node, because they supplied invalid chain link", sync_peer
                     );
-                    ban_sync_peer(shared, sync_peers, sync_peer.clone()).await?;
+                    ban_sync_peer(
+                        shared,
+                        sync_peers,
+                        sync_peer.clone(),
+                        "Invalid chain link supplied during block sync",
+                    )
+                    .await?;
                     return Err(BlockSyncError::InvalidChainLink);
                 }
             }
@@ -343,7 +349,7 @@ async fn find_chain_split_height<B: BlockchainBackend + 'static>(
         target: LOG_TARGET,
         "Banning all peers from local node, because they could not provide a valid chain link",
     );
-    ban_all_sync_peers(shared, sync_peers).await?;
+    ban_all_sync_peers(shared, sync_peers, "Could not provide a valid chain link").await?;
     Err(BlockSyncError::ForkChainNotLinked)
 }
 
@@ -380,7 +386,13 @@ async fn request_and_add_blocks<B: BlockchainBackend + 'static>(
                         target: LOG_TARGET,
                         "Banning peer {} from local node, because they supplied invalid block", sync_peer
                     );
-                    ban_sync_peer(shared, sync_peers, sync_peer.clone()).await?;
+                    ban_sync_peer(
+                        shared,
+                        sync_peers,
+                        sync_peer.clone(),
+                        "Invalid block supplied during block sync",
+                    )
+                    .await?;
                     break;
                 },
                 Err(ChainStorageError::ValidationError { source }) => {
@@ -394,7 +406,13 @@ async fn request_and_add_blocks<B: BlockchainBackend + 'static>(
                         target: LOG_TARGET,
                         "Banning peer {} from local node, because they supplied invalid block", sync_peer
                     );
-                    ban_sync_peer(shared, sync_peers, sync_peer.clone()).await?;
+                    ban_sync_peer(
+                        shared,
+                        sync_peers,
+                        sync_peer.clone(),
+                        "Invalid block supplied during block sync",
+                    )
+                    .await?;
                     break;
                 },
                 Err(e) => return Err(BlockSyncError::ChainStorageError(e)),
@@ -444,7 +462,13 @@ async fn request_blocks<B: BlockchainBackend + 'static>(
                             target: LOG_TARGET,
                             "Banning peer {} from local node, because they supplied the incorrect blocks", sync_peer
                         );
-                        ban_sync_peer(shared, sync_peers, sync_peer.clone()).await?;
+                        ban_sync_peer(
+                            shared,
+                            sync_peers,
+                            sync_peer.clone(),
+                            "Incorrect blocks supplied during block sync",
+                        )
+                        .await?;
                     }
                 } else {
                     debug!(
@@ -458,12 +482,24 @@ async fn request_blocks<B: BlockchainBackend + 'static>(
                         "Banning peer {} from local node, because they supplied the incorrect number of blocks",
                         sync_peer
                     );
-                    ban_sync_peer(shared, sync_peers, sync_peer.clone()).await?;
+                    ban_sync_peer(
+                        shared,
+                        sync_peers,
+                        sync_peer.clone(),
+                        "Incorrect number of blocks supplied during block sync",
+                    )
+                    .await?;
                 }
             },
             Err(CommsInterfaceError::UnexpectedApiResponse) => {
                 debug!(target: LOG_TARGET, "Remote node provided an unexpected api response.",);
-                ban_sync_peer(shared, sync_peers, sync_peer.clone()).await?;
+                ban_sync_peer(
+                    shared,
+                    sync_peers,
+                    sync_peer.clone(),
+                    "Unexpected API response during block sync",
+                )
+                .await?;
             },
             Err(CommsInterfaceError::RequestTimedOut) => {
                 warn!(
@@ -520,7 +556,13 @@ async fn request_headers<B: BlockchainBackend + 'static>(
                             target: LOG_TARGET,
                             "Banning peer {} from local node, because they supplied the incorrect headers", sync_peer
                         );
-                        ban_sync_peer(shared, sync_peers, sync_peer.clone()).await?;
+                        ban_sync_peer(
+                            shared,
+                            sync_peers,
+                            sync_peer.clone(),
+                            "Incorrect headers supplied during block sync",
+                        )
+                        .await?;
                     }
                 } else {
                     debug!(
@@ -534,7 +576,13 @@ async fn request_headers<B: BlockchainBackend + 'static>(
                         "Banning peer {} from local node, because they supplied the incorrect number of headers",
                         sync_peer
                     );
-                    ban_sync_peer(shared, sync_peers, sync_peer.clone()).await?;
+                    ban_sync_peer(
+                        shared,
+                        sync_peers,
+                        sync_peer.clone(),
+                        "Incorrect number of headers supplied during block sync",
+                    )
+                    .await?;
                 }
             },
             Err(CommsInterfaceError::UnexpectedApiResponse) => {
@@ -543,7 +591,13 @@ async fn request_headers<B: BlockchainBackend + 'static>(
                     target: LOG_TARGET,
                     "Banning peer {} from local node, because they provided an unexpected api response", sync_peer
                 );
-                ban_sync_peer(shared, sync_peers, sync_peer.clone()).await?;
+                ban_sync_peer(
+                    shared,
+                    sync_peers,
+                    sync_peer.clone(),
+                    "Unexpected API response during block sync",
+                )
+                .await?;
             },
             Err(CommsInterfaceError::RequestTimedOut) => {
                 warn!(
@@ -608,13 +662,18 @@ async fn ban_sync_peer<B: BlockchainBackend + 'static>(
     shared: &mut BaseNodeStateMachine<B>,
     sync_peers: &mut Vec<NodeId>,
     sync_peer: NodeId,
+    reason: &str,
 ) -> Result<(), BlockSyncError>
 {
     sync_peers.retain(|p| *p != sync_peer);
     let peer = shared.peer_manager.find_by_node_id(&sync_peer).await?;
     shared
         .peer_manager
-        .ban_for(&peer.public_key, shared.config.block_sync_config.peer_ban_duration)
+        .ban_for(
+            &peer.public_key,
+            shared.config.block_sync_config.peer_ban_duration,
+            reason.to_string(),
+        )
         .await?;
     shared.connection_manager.disconnect_peer(sync_peer).await??;
     if sync_peers.is_empty() {
@@ -627,11 +686,12 @@ async fn ban_sync_peer<B: BlockchainBackend + 'static>(
 async fn ban_all_sync_peers<B: BlockchainBackend + 'static>(
     shared: &mut BaseNodeStateMachine<B>,
     sync_peers: &mut Vec<NodeId>,
+    reason: &str,
 ) -> Result<(), BlockSyncError>
 {
     while !sync_peers.is_empty() {
         warn!(target: LOG_TARGET, "Banning peer {} from local node.", sync_peers[0]);
-        ban_sync_peer(shared, sync_peers, sync_peers[0].clone()).await?;
+        ban_sync_peer(shared, sync_peers, sync_peers[0].clone(), reason).await?;
     }
     Ok(())
 }
@@ -30,6 +30,7 @@ use crate::{
         HistoricalBlock,
     },
     consensus::ConsensusManager,
+    metrics,
     proof_of_work::{Difficulty, PowAlgorithm, ProofOfWork},
     transactions::{
         transaction::{TransactionInput, TransactionKernel, TransactionOutput},
@@ -447,7 +448,10 @@ where T: BlockchainBackend
     /// If an error does occur while writing the new block parts, all changes are reverted before returning.
     pub fn add_block(&self, block: Block) -> Result<BlockAddResult, ChainStorageError> {
         // Perform orphan block validation.
-        if let Err(e) = self.validators.orphan.validate(&block) {
+        let timer = metrics::start_block_validation_timer();
+        let orphan_validation_result = self.validators.orphan.validate(&block);
+        timer.observe_duration();
+        if let Err(e) = orphan_validation_result {
             warn!(
                 target: LOG_TARGET,
                 "Block #{} ({}) failed validation - {}",
@@ -680,6 +684,7 @@ fn store_new_block<T: BlockchainBackend>(db: &mut RwLockWriteGuard<T>, block: Bl
     kernels.iter().for_each(|k| txn.insert_kernel(k.clone(), true));
     txn.commit_block();
     commit(db, txn)?;
+    metrics::set_chain_height(height);
     Ok(())
 }
 
@@ -867,6 +872,7 @@ fn rewind_to_height<T: BlockchainBackend>(
         MetadataValue::AccumulatedWork(Some(accumulated_work)),
     ));
     commit(db, txn)?;
+    metrics::set_chain_height(last_header.height);
 
     Ok(removed_blocks)
 }
@@ -1042,7 +1048,9 @@ fn reorganize_chain<T: BlockchainBackend>(
     for block in chain {
         let block_hash = block.hash();
         orphan_hashes.push(block_hash.clone());
+        let timer = metrics::start_block_validation_timer();
         validation_result = block_validator.validate(&block, db);
+        timer.observe_duration();
         if validation_result.is_err() {
             warn!(
                 target: LOG_TARGET,
@@ -23,7 +23,10 @@
 use crate::{
     consensus::network::Network,
     proof_of_work::{Difficulty, PowAlgorithm},
-    transactions::tari_amount::{uT, MicroTari, T},
+    transactions::{
+        tari_amount::{uT, MicroTari, T},
+        transaction::{KERNEL_SIGNATURE_VERSION_LEGACY, KERNEL_SIGNATURE_VERSION_METADATA},
+    },
 };
 use chrono::{DateTime, Duration, Utc};
 use std::ops::Add;
@@ -59,6 +62,9 @@ pub struct ConsensusConstants {
     pub(in crate::consensus) emission_tail: MicroTari,
     /// This is the initial min difficulty for the difficulty adjustment
     min_pow_difficulty: (Difficulty, Difficulty),
+    /// The version of the kernel signature challenge. Kernels carrying `meta_info` or a `linked_kernel` are only
+    /// valid from `KERNEL_SIGNATURE_VERSION_METADATA`, changing it is a hard fork
+    kernel_signature_version: u16,
 }
 // The target time used by the difficulty adjustment algorithms, their target time is the target block interval * PoW
 // algorithm count
@@ -78,6 +84,11 @@ impl ConsensusConstants {
         self.blockchain_version
     }
 
+    /// The version of the kernel signature challenge.
+    pub fn kernel_signature_version(&self) -> u16 {
+        self.kernel_signature_version
+    }
+
     /// This returns the FTL(Future Time Limit) for blocks
     /// Any block with a timestamp greater than this is rejected.
     pub fn ftl(&self) -> EpochTime {
@@ -157,6 +168,7 @@ impl ConsensusConstants {
             emission_decay: 0.999_999_560_409_038_5,
             emission_tail: 1 * T,
             min_pow_difficulty: (1.into(), 60_000_000.into()),
+            kernel_signature_version: KERNEL_SIGNATURE_VERSION_LEGACY,
         }
     }
 
@@ -177,6 +189,7 @@ impl ConsensusConstants {
             emission_decay: 0.999,
             emission_tail: 100.into(),
             min_pow_difficulty: (1.into(), 1.into()),
+            kernel_signature_version: KERNEL_SIGNATURE_VERSION_METADATA,
         }
     }
 
@@ -198,6 +211,7 @@ impl ConsensusConstants {
             emission_decay: 0.999,
             emission_tail: 100.into(),
             min_pow_difficulty: (1.into(), 500_000_000.into()),
+            kernel_signature_version: KERNEL_SIGNATURE_VERSION_METADATA,
         }
     }
 }
@@ -232,6 +246,11 @@ impl ConsensusConstantsBuilder {
         self
     }
 
+    pub fn with_kernel_signature_version(mut self, version: u16) -> ConsensusConstantsBuilder {
+        self.consensus.kernel_signature_version = version;
+        self
+    }
+
     pub fn build(self) -> ConsensusConstants {
         self.consensus
     }
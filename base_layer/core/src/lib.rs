@@ -46,6 +46,9 @@ pub mod proof_of_work;
 #[cfg(feature = "base_node")]
 pub mod validation;
 
+#[cfg(feature = "base_node")]
+mod metrics;
+
 #[cfg(any(feature = "base_node", feature = "base_node_proto"))]
 pub mod base_node;
 #[cfg(any(feature = "base_node", feature = "base_node_proto"))]
@@ -35,6 +35,7 @@ use crate::{
         StatsResponse,
         TxStorageResponse,
     },
+    metrics,
     transactions::{transaction::Transaction, types::Signature},
     validation::{ValidationError, Validator},
 };
@@ -85,25 +86,28 @@ where T: BlockchainBackend
         // The transaction is already internally consistent
         let db = self.blockchain_db.db_read_access()?;
 
-        match self.validator.validate(&tx, &db) {
+        let response = match self.validator.validate(&tx, &db) {
             Ok(()) => {
                 self.unconfirmed_pool.insert(tx)?;
-                Ok(TxStorageResponse::UnconfirmedPool)
+                TxStorageResponse::UnconfirmedPool
             },
             Err(ValidationError::UnknownInputs) => {
                 self.orphan_pool.insert(tx)?;
-                Ok(TxStorageResponse::OrphanPool)
+                TxStorageResponse::OrphanPool
             },
             Err(ValidationError::ContainsSTxO) => {
                 self.reorg_pool.insert(tx)?;
-                Ok(TxStorageResponse::ReorgPool)
+                TxStorageResponse::ReorgPool
             },
             Err(ValidationError::MaturityError) => {
                 self.pending_pool.insert(tx)?;
-                Ok(TxStorageResponse::PendingPool)
+                TxStorageResponse::PendingPool
             },
-            _ => Ok(TxStorageResponse::NotStored),
-        }
+            _ => TxStorageResponse::NotStored,
+        };
+        drop(db);
+        self.update_metrics()?;
+        Ok(response)
     }
 
     // Insert a set of new transactions into the UTxPool.
@@ -135,6 +139,7 @@ where T: BlockchainBackend
         self.unconfirmed_pool.insert_txs(txs)?;
         // Move Time-locked txs that have input UTXOs that have recently become valid to PendingPool.
         self.pending_pool.insert_txs(time_locked_txs)?;
+        self.update_metrics()?;
 
         Ok(())
     }
@@ -195,6 +200,7 @@ where T: BlockchainBackend
             self.pending_pool
                 .insert_txs(self.unconfirmed_pool.remove_timelocked(new_tip_height))?;
         }
+        self.update_metrics()?;
 
         Ok(())
     }
@@ -253,6 +259,14 @@ where T: BlockchainBackend
         })
     }
 
+    fn update_metrics(&self) -> Result<(), MempoolError> {
+        metrics::set_mempool_size("unconfirmed", self.unconfirmed_pool.len());
+        metrics::set_mempool_size("orphan", self.orphan_pool.len()?);
+        metrics::set_mempool_size("pending", self.pending_pool.len());
+        metrics::set_mempool_size("reorg", self.reorg_pool.len()?);
+        Ok(())
+    }
+
     /// Gathers and returns a breakdown of all the transaction in the Mempool.
     pub fn state(&self) -> Result<StateResponse, MempoolError> {
         let unconfirmed_pool = self
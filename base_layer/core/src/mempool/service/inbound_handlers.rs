@@ -33,7 +33,10 @@ use crate::{
 };
 use log::*;
 use std::sync::Arc;
-use tari_comms::types::CommsPublicKey;
+use tari_comms::{
+    peer_manager::{Offence, PeerManager},
+    types::CommsPublicKey,
+};
 use tari_crypto::tari_utilities::hex::Hex;
 
 pub const LOG_TARGET: &str = "c::mp::service::inbound_handlers";
@@ -45,14 +48,24 @@ where T: BlockchainBackend + 'static
 {
     mempool: Mempool<T>,
     outbound_nmi: OutboundMempoolServiceInterface,
+    peer_manager: Arc<PeerManager>,
 }
 
 impl<T> MempoolInboundHandlers<T>
 where T: BlockchainBackend + 'static
 {
     /// Construct the MempoolInboundHandlers.
-    pub fn new(mempool: Mempool<T>, outbound_nmi: OutboundMempoolServiceInterface) -> Self {
-        Self { mempool, outbound_nmi }
+    pub fn new(
+        mempool: Mempool<T>,
+        outbound_nmi: OutboundMempoolServiceInterface,
+        peer_manager: Arc<PeerManager>,
+    ) -> Self
+    {
+        Self {
+            mempool,
+            outbound_nmi,
+            peer_manager,
+        }
     }
 
     /// Handle inbound Mempool service requests from remote nodes and local services.
@@ -95,8 +108,27 @@ where T: BlockchainBackend + 'static
                 .map(|p| format!("remote peer: {}", p))
                 .unwrap_or_else(|| "local services".to_string())
         );
-        let exclude_peers = source_peer.into_iter().collect();
-        self.submit_transaction(tx, exclude_peers).await.map(|_| ())
+        let exclude_peers = source_peer.iter().cloned().collect();
+        let tx_storage = self.submit_transaction(tx, exclude_peers).await?;
+        // The transaction was rejected by the mempool validators
+        if let (TxStorageResponse::NotStored, Some(source_peer)) = (tx_storage, source_peer) {
+            match self
+                .peer_manager
+                .report_offence(&source_peer, Offence::InvalidTransaction)
+                .await
+            {
+                Ok(true) => warn!(
+                    target: LOG_TARGET,
+                    "Peer {} was banned for repeatedly sending invalid transactions", source_peer
+                ),
+                Ok(false) => {},
+                Err(err) => debug!(
+                    target: LOG_TARGET,
+                    "Failed to report invalid transaction from peer {}: {:?}", source_peer, err
+                ),
+            }
+        }
+        Ok(())
     }
 
     // Submits a transaction to the mempool and propagate valid transactions.
@@ -173,6 +205,7 @@ where T: BlockchainBackend + 'static
         Self {
             mempool: self.mempool.clone(),
             outbound_nmi: self.outbound_nmi.clone(),
+            peer_manager: self.peer_manager.clone(),
         }
     }
 }
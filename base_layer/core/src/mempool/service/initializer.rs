@@ -39,6 +39,7 @@ use crate::{
 use futures::{channel::mpsc::unbounded as futures_mpsc_channel_unbounded, future, Future, Stream, StreamExt};
 use log::*;
 use std::{convert::TryFrom, sync::Arc};
+use tari_comms::peer_manager::PeerManager;
 use tari_comms_dht::outbound::OutboundMessageRequester;
 use tari_p2p::{
     comms_connector::PeerMessage,
@@ -65,6 +66,7 @@ where T: BlockchainBackend
     inbound_message_subscription_factory: Arc<TopicSubscriptionFactory<TariMessageType, Arc<PeerMessage>>>,
     mempool: Mempool<T>,
     config: MempoolServiceConfig,
+    peer_manager: Arc<PeerManager>,
 }
 
 impl<T> MempoolServiceInitializer<T>
@@ -75,12 +77,14 @@ where T: BlockchainBackend
         inbound_message_subscription_factory: Arc<TopicSubscriptionFactory<TariMessageType, Arc<PeerMessage>>>,
         mempool: Mempool<T>,
         config: MempoolServiceConfig,
+        peer_manager: Arc<PeerManager>,
     ) -> Self
     {
         Self {
             inbound_message_subscription_factory,
             mempool,
             config,
+            peer_manager,
         }
     }
 
@@ -164,7 +168,8 @@ where T: BlockchainBackend + 'static
         let local_mp_interface = LocalMempoolService::new(local_request_sender_service);
         let config = self.config;
         let mempool = self.mempool.clone();
-        let inbound_handlers = MempoolInboundHandlers::new(mempool, outbound_mp_interface.clone());
+        let inbound_handlers =
+            MempoolInboundHandlers::new(mempool, outbound_mp_interface.clone(), self.peer_manager.clone());
 
         // Register handle to OutboundMempoolServiceInterface before waiting for handles to be ready
         handles_fut.register(outbound_mp_interface);
@@ -0,0 +1,76 @@
+// Copyright 2020, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::base_node::states::BaseNodeState;
+use lazy_static::lazy_static;
+use tari_metrics::{Histogram, HistogramTimer, IntGauge, IntGaugeVec};
+
+const BASE_NODE_STATES: &[&str] = &["starting", "block_sync", "listening", "waiting", "shutdown"];
+
+lazy_static! {
+    static ref CHAIN_HEIGHT: IntGauge =
+        tari_metrics::register_int_gauge("base_node_chain_height", "The height of the longest chain");
+    static ref BLOCK_VALIDATION_TIME: Histogram = tari_metrics::register_histogram(
+        "base_node_block_validation_seconds",
+        "Time taken to validate a block",
+        tari_metrics::DURATION_BUCKETS
+    );
+    static ref MEMPOOL_TRANSACTIONS: IntGaugeVec = tari_metrics::register_int_gauge_vec(
+        "mempool_transactions",
+        "Number of transactions in each mempool pool",
+        &["pool"]
+    );
+    static ref BASE_NODE_STATE: IntGaugeVec = tari_metrics::register_int_gauge_vec(
+        "base_node_state",
+        "Set to 1 for the current state of the base node state machine, otherwise 0",
+        &["state"]
+    );
+}
+
+pub fn set_chain_height(height: u64) {
+    CHAIN_HEIGHT.set(height as i64);
+}
+
+/// Start timing a block validation. The duration is recorded when the returned timer is dropped.
+pub fn start_block_validation_timer() -> HistogramTimer {
+    BLOCK_VALIDATION_TIME.start_timer()
+}
+
+pub fn set_mempool_size(pool: &str, num_txs: usize) {
+    MEMPOOL_TRANSACTIONS.with_label_values(&[pool]).set(num_txs as i64);
+}
+
+pub fn set_base_node_state(state: &BaseNodeState) {
+    use BaseNodeState::*;
+    let current = match state {
+        Starting(_) => "starting",
+        BlockSync(_, _, _) => "block_sync",
+        Listening(_) => "listening",
+        Waiting(_) => "waiting",
+        Shutdown(_) => "shutdown",
+    };
+    for state in BASE_NODE_STATES {
+        BASE_NODE_STATE
+            .with_label_values(&[state])
+            .set(if *state == current { 1 } else { 0 });
+    }
+}
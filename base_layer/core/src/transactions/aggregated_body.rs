@@ -185,6 +185,21 @@ impl AggregateBody {
         Ok(())
     }
 
+    /// Check that every kernel in this aggregate body only carries fields that are committed to by the given kernel
+    /// signature version
+    pub fn check_kernel_signature_version(&self, kernel_signature_version: u16) -> Result<(), TransactionError> {
+        for kernel in self.kernels.iter() {
+            kernel.check_signature_version(kernel_signature_version).or_else(|e| {
+                warn!(
+                    target: LOG_TARGET,
+                    "Kernel ({}) is not supported by kernel signature version {}.", kernel, kernel_signature_version
+                );
+                Err(e)
+            })?;
+        }
+        Ok(())
+    }
+
     pub fn get_total_fee(&self) -> MicroTari {
         let mut fee = MicroTari::from(0);
         for kernel in &self.kernels {
@@ -61,6 +61,10 @@ pub const MAX_TRANSACTION_INPUTS: usize = 500;
 pub const MAX_TRANSACTION_OUTPUTS: usize = 100;
 pub const MAX_TRANSACTION_RECIPIENTS: usize = 15;
 pub const MINIMUM_TRANSACTION_FEE: MicroTari = MicroTari(100);
+/// Kernel signatures only commit to the fee and lock height, so kernels may not carry `meta_info` or a `linked_kernel`
+pub const KERNEL_SIGNATURE_VERSION_LEGACY: u16 = 1;
+/// Kernel signatures also commit to the `meta_info` and `linked_kernel` of the kernel
+pub const KERNEL_SIGNATURE_VERSION_METADATA: u16 = 2;
 
 //--------------------------------------        Output features   --------------------------------------------------//
 
@@ -165,6 +169,8 @@ pub enum TransactionError {
     NoSignatureError,
     // A range proof construction or verification has produced an error
     RangeProofError(RangeProofError),
+    // The kernel carries fields that are not committed to by the kernel signature version in use
+    UnsupportedKernelSignatureVersion,
 }
 
 //-----------------------------------------     UnblindedOutput   ----------------------------------------------------//
@@ -512,14 +518,17 @@ impl Default for KernelBuilder {
 }
 
 impl TransactionKernel {
+    /// Verify the excess signature against a challenge that commits to the kernel's fee, lock height, `meta_info` and
+    /// `linked_kernel`. For kernels without `meta_info` or a `linked_kernel` this is the same challenge as
+    /// `KERNEL_SIGNATURE_VERSION_LEGACY`; use `check_signature_version` to enforce the version of the network.
     pub fn verify_signature(&self) -> Result<(), TransactionError> {
         let excess = self.excess.as_public_key();
         let r = self.excess_sig.get_public_nonce();
         let m = TransactionMetadata {
             lock_height: self.lock_height,
             fee: self.fee,
-            meta_info: None,
-            linked_kernel: None,
+            meta_info: self.meta_info.clone(),
+            linked_kernel: self.linked_kernel.clone(),
         };
         let c = build_challenge(r, &m);
         if self.excess_sig.verify_challenge(excess, &c) {
@@ -528,6 +537,18 @@ impl TransactionKernel {
             Err(TransactionError::InvalidSignatureError)
         }
     }
+
+    /// Check that the kernel only carries fields that are committed to by the given kernel signature version. This is
+    /// a consensus rule: nodes using `KERNEL_SIGNATURE_VERSION_LEGACY` reject kernels with `meta_info` or a
+    /// `linked_kernel`.
+    pub fn check_signature_version(&self, kernel_signature_version: u16) -> Result<(), TransactionError> {
+        if kernel_signature_version < KERNEL_SIGNATURE_VERSION_METADATA &&
+            (self.meta_info.is_some() || self.linked_kernel.is_some())
+        {
+            return Err(TransactionError::UnsupportedKernelSignatureVersion);
+        }
+        Ok(())
+    }
 }
 
 impl Hashable for TransactionKernel {
@@ -797,7 +818,10 @@ mod test {
         txn_schema,
     };
     use rand::{self, rngs::OsRng};
-    use tari_crypto::{keys::SecretKey as SecretKeyTrait, ristretto::pedersen::PedersenCommitmentFactory};
+    use tari_crypto::{
+        keys::{PublicKey as PublicKeyTrait, SecretKey as SecretKeyTrait},
+        ristretto::pedersen::PedersenCommitmentFactory,
+    };
 
     #[test]
     fn unblinded_input() {
@@ -887,6 +911,47 @@ mod test {
         )
     }
 
+    #[test]
+    fn kernel_signature_with_meta_info() {
+        let (k, excess) = PublicKey::random_keypair(&mut OsRng);
+        let (r, public_nonce) = PublicKey::random_keypair(&mut OsRng);
+        let meta_info = vec![7u8; 32];
+        let tx_meta = TransactionMetadata {
+            fee: 100.into(),
+            lock_height: 0,
+            meta_info: Some(meta_info.clone()),
+            linked_kernel: None,
+        };
+        let e = build_challenge(&public_nonce, &tx_meta);
+        let mut kernel = KernelBuilder::new()
+            .with_fee(100.into())
+            .with_meta_info(meta_info)
+            .with_excess(&Commitment::from_public_key(&excess))
+            .with_signature(&Signature::sign(k, r, &e).unwrap())
+            .build()
+            .unwrap();
+
+        // The signature commits to the meta_info, which is only allowed from the metadata kernel signature version
+        assert!(kernel.verify_signature().is_ok());
+        assert!(kernel
+            .check_signature_version(KERNEL_SIGNATURE_VERSION_METADATA)
+            .is_ok());
+        assert!(kernel.check_signature_version(KERNEL_SIGNATURE_VERSION_LEGACY).is_err());
+
+        kernel.meta_info = Some(vec![8u8; 32]);
+        assert!(kernel.verify_signature().is_err());
+        kernel.meta_info = None;
+        assert!(kernel.verify_signature().is_err());
+
+        // Kernels without meta_info are valid under either version
+        let kernel = create_test_kernel(100.into(), 0);
+        assert!(kernel.verify_signature().is_ok());
+        assert!(kernel.check_signature_version(KERNEL_SIGNATURE_VERSION_LEGACY).is_ok());
+        assert!(kernel
+            .check_signature_version(KERNEL_SIGNATURE_VERSION_METADATA)
+            .is_ok());
+    }
+
     #[test]
     fn check_timelocks() {
         let factories = CryptoFactories::new(32);
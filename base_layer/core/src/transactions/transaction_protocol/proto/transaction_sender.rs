@@ -104,6 +104,7 @@ impl TryFrom<proto::SingleRoundSenderData> for SingleRoundSenderData {
             public_nonce,
             metadata,
             message,
+            maturity: data.maturity,
         })
     }
 }
@@ -119,6 +120,7 @@ impl From<SingleRoundSenderData> for proto::SingleRoundSenderData {
             public_nonce: sender_data.public_nonce.to_vec(),
             metadata: Some(sender_data.metadata.into()),
             message: sender_data.message,
+            maturity: sender_data.maturity,
         }
     }
 }
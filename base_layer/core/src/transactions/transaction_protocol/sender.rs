@@ -76,6 +76,9 @@ pub(super) struct RawTransactionInfo {
     pub recipient_info: RecipientInfo,
     pub signatures: Vec<Signature>,
     pub message: String,
+    // The maturity the recipient is asked to place on their output
+    #[serde(default)]
+    pub recipient_maturity: u64,
 }
 
 impl RawTransactionInfo {
@@ -99,6 +102,9 @@ pub struct SingleRoundSenderData {
     pub metadata: TransactionMetadata,
     /// Plain text message to receiver
     pub message: String,
+    /// The minimum block height at which the recipient's output may be spent
+    #[serde(default)]
+    pub maturity: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -276,6 +282,7 @@ impl SenderTransactionProtocol {
                     public_excess: info.public_excess.clone(),
                     metadata: info.metadata.clone(),
                     message: info.message.clone(),
+                    maturity: info.recipient_maturity,
                 })
             },
             _ => Err(TPE::InvalidStateError),
@@ -328,13 +335,19 @@ impl SenderTransactionProtocol {
         let mut s_agg = info.signatures[0].clone();
         info.signatures.iter().skip(1).for_each(|s| s_agg = &s_agg + s);
         let excess = PedersenCommitment::from_public_key(&info.public_excess);
-        let kernel = KernelBuilder::new()
+        let mut kernel_builder = KernelBuilder::new()
             .with_fee(info.metadata.fee)
             .with_features(features)
             .with_lock_height(info.metadata.lock_height)
             .with_excess(&excess)
-            .with_signature(&s_agg)
-            .build()?;
+            .with_signature(&s_agg);
+        if let Some(meta_info) = info.metadata.meta_info.clone() {
+            kernel_builder = kernel_builder.with_meta_info(meta_info);
+        }
+        if let Some(linked_kernel) = info.metadata.linked_kernel.clone() {
+            kernel_builder = kernel_builder.with_linked_kernel(linked_kernel);
+        }
+        let kernel = kernel_builder.build()?;
         tx_builder.with_kernel(kernel);
         tx_builder.build(factories).map_err(TPE::from)
     }
@@ -604,6 +617,33 @@ mod test {
         assert_eq!(tx.offset, p.offset);
     }
 
+    #[test]
+    fn zero_recipients_with_meta_info() {
+        let factories = CryptoFactories::default();
+        let p = TestParams::new();
+        let (utxo, input) = make_input(&mut OsRng, MicroTari(1200), &factories.commitment);
+        let meta_info = vec![7u8; 40];
+        let mut builder = SenderTransactionProtocol::builder(0);
+        builder
+            .with_lock_height(0)
+            .with_fee_per_gram(MicroTari(10))
+            .with_offset(p.offset.clone())
+            .with_private_nonce(p.nonce.clone())
+            .with_change_secret(p.change_key.clone())
+            .with_input(utxo, input)
+            .with_output(UnblindedOutput::new(MicroTari(500), p.spend_key.clone(), None))
+            .with_meta_info(meta_info.clone());
+        let mut sender = builder.build::<Blake256>(&factories).unwrap();
+        match sender.finalize(KernelFeatures::empty(), &factories) {
+            Ok(true) => (),
+            Ok(false) => panic!("{:?}", sender.failure_reason()),
+            Err(e) => panic!("{:?}", e),
+        }
+        let tx = sender.get_transaction().unwrap();
+        assert_eq!(tx.body.kernels()[0].meta_info, Some(meta_info));
+        assert!(tx.body.kernels()[0].verify_signature().is_ok());
+    }
+
     #[test]
     fn single_recipient_no_change() {
         let factories = CryptoFactories::default();
@@ -736,6 +776,56 @@ mod test {
         assert!(tx.clone().validate_internal_consistency(&factories, None).is_ok());
     }
 
+    #[test]
+    fn single_recipient_time_locked() {
+        let factories = CryptoFactories::default();
+        // Alice's parameters
+        let a = TestParams::new();
+        // Bob's parameters
+        let b = TestParams::new();
+        let (utxo, input) = make_input(&mut OsRng, MicroTari(2500), &factories.commitment);
+        let mut builder = SenderTransactionProtocol::builder(1);
+        builder
+            .with_lock_height(15)
+            .with_recipient_maturity(120)
+            .with_fee_per_gram(MicroTari(20))
+            .with_offset(a.offset.clone())
+            .with_private_nonce(a.nonce.clone())
+            .with_change_secret(a.change_key.clone())
+            .with_input(utxo, input)
+            .with_amount(0, MicroTari(1000));
+        let mut alice = builder.build::<Blake256>(&factories).unwrap();
+        let msg = alice.build_single_round_message().unwrap();
+        assert_eq!(msg.metadata.lock_height, 15);
+        assert_eq!(msg.maturity, 120);
+
+        // The requested maturity survives saving and reloading the pending transaction
+        let ser = alice.save_pending_transaction_to_be_sent().unwrap();
+        let mut alice = SenderTransactionProtocol::load_pending_transaction_to_be_sent(ser).unwrap();
+        assert_eq!(alice.get_single_round_message().unwrap().maturity, 120);
+
+        let bob_info = SingleReceiverTransactionProtocol::create(
+            &msg,
+            b.nonce,
+            b.spend_key,
+            OutputFeatures::with_maturity(msg.maturity),
+            &factories,
+        )
+        .unwrap();
+        alice
+            .add_single_recipient_info(bob_info.clone(), &factories.range_proof)
+            .unwrap();
+        match alice.finalize(KernelFeatures::empty(), &factories) {
+            Ok(true) => (),
+            Ok(false) => panic!("{:?}", alice.failure_reason()),
+            Err(e) => panic!("{:?}", e),
+        };
+        let tx = alice.get_transaction().unwrap();
+        assert_eq!(tx.body.kernels()[0].lock_height, 15);
+        assert!(tx.body.outputs().contains(&bob_info.output));
+        assert_eq!(bob_info.output.features.maturity, 120);
+    }
+
     #[test]
     fn single_recipient_range_proof_fail() {
         let factories = CryptoFactories::new(32);
@@ -159,6 +159,7 @@ mod test {
             public_nonce: pub_rs.clone(),
             metadata: m.clone(),
             message: "".to_string(),
+            maturity: 0,
         };
         let prot = SingleReceiverTransactionProtocol::create(&info, r, k.clone(), of, &factories).unwrap();
         assert_eq!(prot.tx_id, 500, "tx_id is incorrect");
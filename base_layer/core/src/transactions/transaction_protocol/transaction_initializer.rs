@@ -35,7 +35,7 @@ use crate::transactions::{
         sender::{calculate_tx_id, RawTransactionInfo, SenderState, SenderTransactionProtocol},
         TransactionMetadata,
     },
-    types::{BlindingFactor, CryptoFactories, PrivateKey, PublicKey},
+    types::{BlindingFactor, CryptoFactories, HashOutput, PrivateKey, PublicKey},
 };
 use digest::Digest;
 use std::{
@@ -67,6 +67,8 @@ pub struct SenderTransactionInitializer {
     excess_blinding_factor: BlindingFactor,
     private_nonce: Option<PrivateKey>,
     message: Option<String>,
+    meta_info: Option<HashOutput>,
+    recipient_maturity: u64,
 }
 
 pub struct BuildError {
@@ -95,6 +97,8 @@ impl SenderTransactionInitializer {
             private_nonce: None,
             excess_blinding_factor: BlindingFactor::default(),
             message: None,
+            meta_info: None,
+            recipient_maturity: 0,
         }
     }
 
@@ -117,6 +121,13 @@ impl SenderTransactionInitializer {
         self
     }
 
+    /// Sets the minimum block height at which the recipient's output may be spent. The value is passed to the
+    /// recipient in the single round sender message; it defaults to zero (spendable immediately).
+    pub fn with_recipient_maturity(&mut self, maturity: u64) -> &mut Self {
+        self.recipient_maturity = maturity;
+        self
+    }
+
     /// Manually sets the offset value. If this is not called, a random offset will be used when `build()` is called.
     pub fn with_offset(&mut self, offset: BlindingFactor) -> &mut Self {
         self.offset = Some(offset);
@@ -158,6 +169,13 @@ impl SenderTransactionInitializer {
         self
     }
 
+    /// Provide additional metadata that will be committed to by the kernel signature and published in the kernel's
+    /// `meta_info` field
+    pub fn with_meta_info(&mut self, meta_info: HashOutput) -> &mut Self {
+        self.meta_info = Some(meta_info);
+        self
+    }
+
     /// Tries to make a change output with the given transaction parameters and add it to the set of outputs. The total
     /// fee, including the additional change output (if any) is returned along with the amount of change.
     /// The change output **always has default output features**.
@@ -286,7 +304,7 @@ impl SenderTransactionInitializer {
             metadata: TransactionMetadata {
                 fee: total_fee,
                 lock_height: self.lock_height.unwrap(),
-                meta_info: None,
+                meta_info: self.meta_info,
                 linked_kernel: None,
             },
             inputs: self.inputs,
@@ -300,6 +318,7 @@ impl SenderTransactionInitializer {
             recipient_info,
             signatures: Vec::new(),
             message: self.message.unwrap_or_else(|| "".to_string()),
+            recipient_maturity: self.recipient_maturity,
         };
         let state = SenderState::Initializing(Box::new(sender_info));
         let state = state
@@ -61,6 +61,7 @@ impl StatelessValidation<Block> for StatelessBlockValidator {
     /// 1. Is there precisely one Coinbase output and is it correctly defined?
     /// 1. Is the accounting correct?
     /// 1. Are all inputs allowed to be spent (Are the feature flags satisfied)
+    /// 1. Are the kernels supported by the kernel signature version of the network?
     fn validate(&self, block: &Block) -> Result<(), ValidationError> {
         let block_id = format!("block #{} ({})", block.header.height, block.hash().to_hex());
         check_coinbase_output(block, &self.consensus_constants)?;
@@ -72,6 +73,8 @@ impl StatelessValidation<Block> for StatelessBlockValidator {
         trace!(target: LOG_TARGET, "SV - Output constraints are ok for {} ", &block_id);
         check_cut_through(block)?;
         trace!(target: LOG_TARGET, "SV - Cut-through is ok for {} ", &block_id);
+        check_kernel_signature_version(block, &self.consensus_constants)?;
+        trace!(target: LOG_TARGET, "SV - Kernel versions are ok for {} ", &block_id);
         info!(
             target: LOG_TARGET,
             "{} has PASSED stateless VALIDATION check.", &block_id
@@ -110,6 +113,8 @@ impl<B: BlockchainBackend> Validation<Block, B> for FullConsensusValidator {
         trace!(target: LOG_TARGET, "FCV - Block weight ok for {}", &block_id);
         check_cut_through(block)?;
         trace!(target: LOG_TARGET, "FCV - Cut-though correct for {}", &block_id);
+        check_kernel_signature_version(block, &self.rules.consensus_constants())?;
+        trace!(target: LOG_TARGET, "FCV - Kernel versions ok for {}", &block_id);
         block.check_stxo_rules().map_err(BlockValidationError::from)?;
         trace!(target: LOG_TARGET, "FCV - Cut-though correct for {}", &block_id);
         check_accounting_balance(block, self.rules.clone(), &self.factories)?;
@@ -135,6 +140,17 @@ impl<B: BlockchainBackend> Validation<Block, B> for FullConsensusValidator {
 }
 
 //-------------------------------------     Block validator helper functions     -------------------------------------//
+fn check_kernel_signature_version(
+    block: &Block,
+    consensus_constants: &ConsensusConstants,
+) -> Result<(), ValidationError>
+{
+    block
+        .body
+        .check_kernel_signature_version(consensus_constants.kernel_signature_version())
+        .map_err(ValidationError::TransactionError)
+}
+
 fn check_accounting_balance(
     block: &Block,
     rules: ConsensusManager,
@@ -22,6 +22,7 @@
 
 use crate::{
     chain_storage::{is_stxo, is_utxo, BlockchainBackend},
+    consensus::ConsensusConstants,
     transactions::{transaction::Transaction, types::CryptoFactories},
     validation::{StatelessValidation, Validation, ValidationError},
 };
@@ -33,17 +34,21 @@ pub const LOG_TARGET: &str = "c::val::transaction_validators";
 /// This validator will only check that a transaction is internally consistent. It requires no state information.
 pub struct StatelessTxValidator {
     factories: CryptoFactories,
+    consensus_constants: ConsensusConstants,
 }
 
 impl StatelessTxValidator {
-    pub fn new(factories: CryptoFactories) -> Self {
-        Self { factories }
+    pub fn new(factories: CryptoFactories, consensus_constants: &ConsensusConstants) -> Self {
+        Self {
+            factories,
+            consensus_constants: consensus_constants.clone(),
+        }
     }
 }
 
 impl StatelessValidation<Transaction> for StatelessTxValidator {
     fn validate(&self, tx: &Transaction) -> Result<(), ValidationError> {
-        verify_tx(tx, &self.factories)?;
+        verify_tx(tx, &self.factories, &self.consensus_constants)?;
         Ok(())
     }
 }
@@ -53,17 +58,21 @@ impl StatelessValidation<Transaction> for StatelessTxValidator {
 /// have passed
 pub struct FullTxValidator {
     factories: CryptoFactories,
+    consensus_constants: ConsensusConstants,
 }
 
 impl FullTxValidator {
-    pub fn new(factories: CryptoFactories) -> Self {
-        Self { factories }
+    pub fn new(factories: CryptoFactories, consensus_constants: &ConsensusConstants) -> Self {
+        Self {
+            factories,
+            consensus_constants: consensus_constants.clone(),
+        }
     }
 }
 
 impl<B: BlockchainBackend> Validation<Transaction, B> for FullTxValidator {
     fn validate(&self, tx: &Transaction, db: &B) -> Result<(), ValidationError> {
-        verify_tx(tx, &self.factories)?;
+        verify_tx(tx, &self.factories, &self.consensus_constants)?;
         verify_inputs(tx, db)?;
         let tip_height = db
             .fetch_metadata()
@@ -117,9 +126,17 @@ impl<B: BlockchainBackend> Validation<Transaction, B> for TimeLockTxValidator {
     }
 }
 
-// This function verifies that the provided transaction is internally sound and that no funds were created in the
-// transaction.
-fn verify_tx(tx: &Transaction, factories: &CryptoFactories) -> Result<(), ValidationError> {
+// This function verifies that the provided transaction is internally sound, that no funds were created in the
+// transaction and that its kernels are supported by the kernel signature version of the network.
+fn verify_tx(
+    tx: &Transaction,
+    factories: &CryptoFactories,
+    consensus_constants: &ConsensusConstants,
+) -> Result<(), ValidationError>
+{
+    tx.body
+        .check_kernel_signature_version(consensus_constants.kernel_signature_version())
+        .map_err(ValidationError::TransactionError)?;
     tx.validate_internal_consistency(factories, None)
         .map_err(ValidationError::TransactionError)
 }
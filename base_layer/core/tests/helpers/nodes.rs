@@ -24,7 +24,7 @@ use futures::Sink;
 use rand::{distributions::Alphanumeric, rngs::OsRng, Rng};
 use std::{error::Error, iter, sync::Arc, time::Duration};
 use tari_comms::{
-    peer_manager::{NodeIdentity, PeerFeatures},
+    peer_manager::{NodeIdentity, PeerFeatures, PeerManager},
     transports::MemoryTransport,
     CommsNode,
 };
@@ -67,7 +67,8 @@ use tari_p2p::{
     },
 };
 use tari_service_framework::StackBuilder;
-use tari_test_utils::async_assert_eventually;
+use tari_storage::{lmdb_store::LMDBBuilder, LMDBWrapper};
+use tari_test_utils::{async_assert_eventually, paths::create_temporary_data_path, random};
 use tokio::runtime::Runtime;
 
 /// The NodeInterfaces is used as a container for providing access to all the services and interfaces of a single node.
@@ -486,6 +487,21 @@ where
     (comms, dht)
 }
 
+/// Create a peer manager backed by an LMDB database in a temporary directory
+pub fn create_peer_manager() -> Arc<PeerManager> {
+    let database_name = random::string(8);
+    let datastore = LMDBBuilder::new()
+        .set_path(create_temporary_data_path().to_str().unwrap())
+        .set_environment_size(50)
+        .set_max_number_of_databases(1)
+        .add_database(&database_name, lmdb_zero::db::CREATE)
+        .build()
+        .unwrap();
+    let peer_database = datastore.get_handle(&database_name).unwrap();
+    let peer_database = LMDBWrapper::new(Arc::new(peer_database));
+    Arc::new(PeerManager::new(peer_database).unwrap())
+}
+
 // Helper function for starting the services of the Base node.
 fn setup_base_node_services(
     runtime: &mut Runtime,
@@ -526,11 +542,13 @@ fn setup_base_node_services(
             mempool.clone(),
             consensus_manager,
             base_node_service_config,
+            comms.peer_manager(),
         ))
         .add_initializer(MempoolServiceInitializer::new(
             subscription_factory,
             mempool,
             mempool_service_config,
+            comms.peer_manager(),
         ))
         .add_initializer(ChainMetadataServiceInitializer)
         .finish();
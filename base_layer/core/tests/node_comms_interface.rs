@@ -24,6 +24,7 @@
 mod helpers;
 
 use futures::{channel::mpsc::unbounded as futures_mpsc_channel_unbounded, executor::block_on, StreamExt};
+use helpers::nodes::create_peer_manager;
 use tari_broadcast_channel::bounded;
 use tari_comms::peer_manager::NodeId;
 use tari_core::{
@@ -101,6 +102,7 @@ fn inbound_get_metadata() {
         mempool,
         consensus_manager,
         outbound_nci,
+        create_peer_manager(),
     );
     let block = store.fetch_block(0).unwrap().block().clone();
 
@@ -154,6 +156,7 @@ fn inbound_fetch_kernels() {
         mempool,
         consensus_manager,
         outbound_nci,
+        create_peer_manager(),
     );
 
     let kernel = create_test_kernel(5.into(), 0);
@@ -215,6 +218,7 @@ fn inbound_fetch_headers() {
         mempool,
         consensus_manager,
         outbound_nci,
+        create_peer_manager(),
     );
     let header = store.fetch_block(0).unwrap().block().header.clone();
 
@@ -273,6 +277,7 @@ fn inbound_fetch_utxos() {
         mempool,
         consensus_manager,
         outbound_nci,
+        create_peer_manager(),
     );
 
     let (utxo, _) = create_utxo(MicroTari(10_000), &factories, None);
@@ -335,6 +340,7 @@ fn inbound_fetch_blocks() {
         mempool,
         consensus_manager,
         outbound_nci,
+        create_peer_manager(),
     );
     let block = store.fetch_block(0).unwrap().block().clone();
 
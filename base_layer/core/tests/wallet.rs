@@ -136,8 +136,11 @@ fn wallet_base_node_integration_test() {
         transaction_service_config: Some(TransactionServiceConfig {
             mempool_broadcast_timeout: Duration::from_secs(10),
             base_node_mined_timeout: Duration::from_secs(1),
+            num_confirmations_required: 0,
             ..Default::default()
         }),
+        output_manager_service_config: None,
+        base_node_service_config: None,
     };
     let alice_runtime = create_runtime();
     let mut alice_wallet = Wallet::new(
@@ -185,6 +188,8 @@ fn wallet_base_node_integration_test() {
         comms_config: bob_comms_config,
         factories: factories.clone(),
         transaction_service_config: None,
+        output_manager_service_config: None,
+        base_node_service_config: None,
     };
     let bob_runtime = create_runtime();
     let mut bob_wallet = Wallet::new(
@@ -118,9 +118,19 @@ where
         }
     }
 
-    /// Derive a new private key from master key: derived_key=SHA256(master_key||branch_seed||index)
+    /// Derive a new private key from master key: derived_key=SHA256(master_key||branch_seed||":"||index). The empty
+    /// branch omits the separator so that keys derived before branches were supported are unchanged.
     pub fn derive_key(&self, key_index: usize) -> Result<DerivedKey<K>, ByteArrayError> {
-        let concatenated = format!("{}{}", self.master_key.to_hex(), key_index.to_string());
+        let concatenated = if self.branch_seed.is_empty() {
+            format!("{}{}", self.master_key.to_hex(), key_index.to_string())
+        } else {
+            format!(
+                "{}{}:{}",
+                self.master_key.to_hex(),
+                self.branch_seed,
+                key_index.to_string()
+            )
+        };
         match K::from_bytes(D::digest(&concatenated.into_bytes()).as_slice()) {
             Ok(k) => Ok(DerivedKey { k, key_index }),
             Err(e) => Err(e),
@@ -140,7 +150,7 @@ mod test {
     use rand::rngs::OsRng;
     use sha2::Sha256;
     use std::fs::remove_file;
-    use tari_crypto::ristretto::RistrettoSecretKey;
+    use tari_crypto::{ristretto::RistrettoSecretKey, tari_utilities::ByteArray};
 
     #[test]
     fn test_new_keymanager() {
@@ -218,6 +228,24 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_branches_derive_distinct_keys() {
+        let master_key = RistrettoSecretKey::random(&mut OsRng);
+        let default_km = KeyManager::<RistrettoSecretKey, Sha256>::from(master_key.clone(), "".to_string(), 0);
+        let hot_km = KeyManager::<RistrettoSecretKey, Sha256>::from(master_key.clone(), "hot".to_string(), 0);
+        let fees_km = KeyManager::<RistrettoSecretKey, Sha256>::from(master_key.clone(), "fees".to_string(), 0);
+
+        let default_key = default_km.derive_key(1).unwrap().k;
+        assert_ne!(default_key, hot_km.derive_key(1).unwrap().k);
+        assert_ne!(hot_km.derive_key(1).unwrap().k, fees_km.derive_key(1).unwrap().k);
+        assert_eq!(hot_km.derive_key(1).unwrap().k, hot_km.derive_key(1).unwrap().k);
+
+        // The default branch derives keys the same way as before branches were used
+        let concatenated = format!("{}{}", master_key.to_hex(), 1);
+        let expected = RistrettoSecretKey::from_bytes(Sha256::digest(&concatenated.into_bytes()).as_slice()).unwrap();
+        assert_eq!(default_key, expected);
+    }
+
     #[test]
     fn test_to_file_and_from_file() {
         let desired_km = KeyManager::<RistrettoSecretKey, Sha256>::new(&mut OsRng);
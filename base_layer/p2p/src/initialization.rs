@@ -31,7 +31,7 @@ use rand::{distributions::Alphanumeric, thread_rng, Rng};
 use std::{error::Error, iter, path::PathBuf, sync::Arc, time::Duration};
 use tari_comms::{
     backoff::ConstantBackoff,
-    peer_manager::NodeIdentity,
+    peer_manager::{NodeIdentity, PeerManager, PeerManagerError},
     pipeline,
     pipeline::SinkService,
     tor,
@@ -52,6 +52,7 @@ pub enum CommsInitializationError {
     CommsBuilderError(CommsBuilderError),
     DhtInitializationError(DhtInitializationError),
     HiddenServiceBuilderError(tor::HiddenServiceBuilderError),
+    PeerManagerError(PeerManagerError),
     #[error(non_std, no_from, msg_embedded)]
     InvalidLivenessCidrs(String),
 }
@@ -276,6 +277,7 @@ where
         .build()
         .unwrap();
     let peer_database = datastore.get_handle(&config.peer_database_name).unwrap();
+    PeerManager::migrate_lmdb(&peer_database)?;
     let peer_database = LMDBWrapper::new(Arc::new(peer_database));
 
     let listener_liveness_whitelist_cidrs = parse_cidrs(&config.listener_liveness_whitelist_cidrs)
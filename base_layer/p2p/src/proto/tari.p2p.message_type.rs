@@ -17,6 +17,7 @@ pub enum TariMessageType {
     MempoolResponse = 72,
     /// -- DAN Messages --
     TransactionFinalized = 73,
+    TransactionCancelled = 74,
     // -- Extended --
     Text = 225,
     TextAck = 226,
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS one_sided_payment_scan_state;
\ No newline at end of file
@@ -0,0 +1,4 @@
+CREATE TABLE one_sided_payment_scan_state (
+    id INTEGER PRIMARY KEY NOT NULL,
+    last_scanned_block_height BIGINT NOT NULL
+);
\ No newline at end of file
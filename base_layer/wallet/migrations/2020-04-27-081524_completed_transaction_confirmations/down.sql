@@ -0,0 +1,18 @@
+PRAGMA foreign_keys=off;
+ALTER TABLE completed_transactions RENAME TO completed_transactions_old;
+CREATE TABLE completed_transactions (
+    tx_id INTEGER PRIMARY KEY NOT NULL,
+    source_public_key BLOB NOT NULL,
+    destination_public_key BLOB NOT NULL,
+    amount INTEGER NOT NULL,
+    fee INTEGER NOT NULL,
+    transaction_protocol TEXT NOT NULL,
+    status INTEGER NOT NULL,
+    message TEXT NOT NULL,
+    timestamp DATETIME NOT NULL
+);
+INSERT INTO completed_transactions (tx_id, source_public_key, destination_public_key, amount, fee, transaction_protocol, status, message, timestamp)
+SELECT tx_id, source_public_key, destination_public_key, amount, fee, transaction_protocol, status, message, timestamp
+FROM completed_transactions_old;
+DROP TABLE completed_transactions_old;
+PRAGMA foreign_keys=on;
@@ -0,0 +1,3 @@
+ALTER TABLE completed_transactions ADD COLUMN mined_height BIGINT NULL;
+ALTER TABLE completed_transactions ADD COLUMN mined_block_hash BLOB NULL;
+ALTER TABLE completed_transactions ADD COLUMN confirmations BIGINT NULL;
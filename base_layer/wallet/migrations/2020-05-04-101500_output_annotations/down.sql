@@ -0,0 +1 @@
+DROP TABLE IF EXISTS output_annotations;
\ No newline at end of file
@@ -0,0 +1,5 @@
+CREATE TABLE output_annotations (
+    spending_key BLOB PRIMARY KEY NOT NULL,
+    label TEXT NULL,
+    frozen INTEGER NOT NULL DEFAULT 0
+);
@@ -0,0 +1,11 @@
+DROP TABLE IF EXISTS accounts;
+DROP TABLE IF EXISTS transaction_accounts;
+
+CREATE TABLE output_annotations_old (
+    spending_key BLOB PRIMARY KEY NOT NULL,
+    label TEXT NULL,
+    frozen INTEGER NOT NULL DEFAULT 0
+);
+INSERT INTO output_annotations_old (spending_key, label, frozen) SELECT spending_key, label, frozen FROM output_annotations;
+DROP TABLE output_annotations;
+ALTER TABLE output_annotations_old RENAME TO output_annotations;
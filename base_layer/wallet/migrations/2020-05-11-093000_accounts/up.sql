@@ -0,0 +1,13 @@
+CREATE TABLE accounts (
+    name TEXT PRIMARY KEY NOT NULL,
+    branch_seed TEXT NOT NULL,
+    primary_key_index INTEGER NOT NULL,
+    receiving INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE transaction_accounts (
+    tx_id INTEGER PRIMARY KEY NOT NULL,
+    account TEXT NOT NULL
+);
+
+ALTER TABLE output_annotations ADD COLUMN account TEXT NULL;
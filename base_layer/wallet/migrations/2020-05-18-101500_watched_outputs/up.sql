@@ -0,0 +1,8 @@
+CREATE TABLE watched_outputs (
+    commitment BLOB PRIMARY KEY NOT NULL,
+    value INTEGER NOT NULL,
+    flags INTEGER NOT NULL,
+    maturity INTEGER NOT NULL,
+    label TEXT NULL,
+    status INTEGER NOT NULL DEFAULT 0
+);
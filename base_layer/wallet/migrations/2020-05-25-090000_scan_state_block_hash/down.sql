@@ -0,0 +1,7 @@
+CREATE TABLE one_sided_payment_scan_state_old (
+    id INTEGER PRIMARY KEY NOT NULL,
+    last_scanned_block_height BIGINT NOT NULL
+);
+INSERT INTO one_sided_payment_scan_state_old (id, last_scanned_block_height) SELECT id, last_scanned_block_height FROM one_sided_payment_scan_state;
+DROP TABLE one_sided_payment_scan_state;
+ALTER TABLE one_sided_payment_scan_state_old RENAME TO one_sided_payment_scan_state;
@@ -0,0 +1 @@
+ALTER TABLE one_sided_payment_scan_state ADD COLUMN last_scanned_block_hash BLOB NULL;
@@ -0,0 +1 @@
+UPDATE completed_transactions SET confirmations = NULL WHERE confirmations = 9223372036854775807;
@@ -0,0 +1,3 @@
+-- Transactions that were Mined before confirmations were tracked had their outputs confirmed when they were mined, so
+-- they are recorded as final rather than being monitored again
+UPDATE completed_transactions SET confirmations = 9223372036854775807 WHERE status = 2 AND confirmations IS NULL;
@@ -0,0 +1,7 @@
+CREATE TABLE peers_old (
+    public_key BLOB PRIMARY KEY NOT NULL UNIQUE,
+    peer TEXT NOT NULL
+);
+INSERT INTO peers_old (public_key, peer) SELECT public_key, peer FROM peers;
+DROP TABLE peers;
+ALTER TABLE peers_old RENAME TO peers;
@@ -0,0 +1,4 @@
+-- The order of the peers is the order of preference of the wallet's base nodes, existing peers keep the order they
+-- were added in
+ALTER TABLE peers ADD COLUMN rank INTEGER NOT NULL DEFAULT 0;
+UPDATE peers SET rank = rowid;
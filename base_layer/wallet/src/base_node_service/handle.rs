@@ -0,0 +1,157 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::base_node_service::error::BaseNodeServiceError;
+use futures::{stream::Fuse, StreamExt};
+use std::{fmt, sync::Arc};
+use tari_comms::types::CommsPublicKey;
+use tari_service_framework::reply_channel::SenderService;
+use tokio::sync::broadcast;
+use tower::Service;
+
+/// API Request enum
+#[derive(Debug)]
+pub enum BaseNodeServiceRequest {
+    AddBaseNode(CommsPublicKey),
+    RemoveBaseNode(CommsPublicKey),
+    GetBaseNodes,
+    GetCurrentBaseNode,
+}
+
+impl fmt::Display for BaseNodeServiceRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AddBaseNode(k) => f.write_str(&format!("AddBaseNode ({})", k)),
+            Self::RemoveBaseNode(k) => f.write_str(&format!("RemoveBaseNode ({})", k)),
+            Self::GetBaseNodes => f.write_str("GetBaseNodes"),
+            Self::GetCurrentBaseNode => f.write_str("GetCurrentBaseNode"),
+        }
+    }
+}
+
+/// API Response enum
+#[derive(Debug)]
+pub enum BaseNodeServiceResponse {
+    BaseNodeAdded,
+    BaseNodeRemoved,
+    BaseNodes(Vec<BaseNodeState>),
+    CurrentBaseNode(Option<CommsPublicKey>),
+}
+
+/// The health information the service keeps for each of the wallet's base nodes, in order of preference
+#[derive(Clone, Debug, PartialEq)]
+pub struct BaseNodeState {
+    pub public_key: CommsPublicKey,
+    /// The latency of the last pong received from this base node
+    pub latency: Option<u32>,
+    /// The height of the longest chain reported by this base node in its last pong
+    pub chain_height: Option<u64>,
+    /// The number of consecutive pings this base node has not answered
+    pub missed_pings: u32,
+}
+
+impl BaseNodeState {
+    pub fn new(public_key: CommsPublicKey) -> Self {
+        Self {
+            public_key,
+            latency: None,
+            chain_height: None,
+            missed_pings: 0,
+        }
+    }
+}
+
+/// Events that can be published on the Base Node Service Event Stream
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BaseNodeEvent {
+    /// The wallet switched to a different base node
+    BaseNodeChanged(CommsPublicKey),
+    /// None of the wallet's base nodes are currently considered healthy
+    NoHealthyBaseNodes,
+    /// The last of the wallet's base nodes was removed so there is no current base node
+    NoBaseNodes,
+}
+
+pub type BaseNodeEventSender = broadcast::Sender<Arc<BaseNodeEvent>>;
+pub type BaseNodeEventReceiver = broadcast::Receiver<Arc<BaseNodeEvent>>;
+
+/// The Base Node Service Handle is a struct that contains the interfaces used to communicate with a running
+/// Base Node Service
+#[derive(Clone)]
+pub struct BaseNodeServiceHandle {
+    handle: SenderService<BaseNodeServiceRequest, Result<BaseNodeServiceResponse, BaseNodeServiceError>>,
+    event_stream_sender: BaseNodeEventSender,
+}
+
+impl BaseNodeServiceHandle {
+    pub fn new(
+        handle: SenderService<BaseNodeServiceRequest, Result<BaseNodeServiceResponse, BaseNodeServiceError>>,
+        event_stream_sender: BaseNodeEventSender,
+    ) -> Self
+    {
+        Self {
+            handle,
+            event_stream_sender,
+        }
+    }
+
+    pub fn get_event_stream_fused(&self) -> Fuse<BaseNodeEventReceiver> {
+        self.event_stream_sender.subscribe().fuse()
+    }
+
+    /// Add a base node to the end of the list of base nodes the wallet can use
+    pub async fn add_base_node(&mut self, public_key: CommsPublicKey) -> Result<(), BaseNodeServiceError> {
+        match self
+            .handle
+            .call(BaseNodeServiceRequest::AddBaseNode(public_key))
+            .await??
+        {
+            BaseNodeServiceResponse::BaseNodeAdded => Ok(()),
+            _ => Err(BaseNodeServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn remove_base_node(&mut self, public_key: CommsPublicKey) -> Result<(), BaseNodeServiceError> {
+        match self
+            .handle
+            .call(BaseNodeServiceRequest::RemoveBaseNode(public_key))
+            .await??
+        {
+            BaseNodeServiceResponse::BaseNodeRemoved => Ok(()),
+            _ => Err(BaseNodeServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_base_nodes(&mut self) -> Result<Vec<BaseNodeState>, BaseNodeServiceError> {
+        match self.handle.call(BaseNodeServiceRequest::GetBaseNodes).await?? {
+            BaseNodeServiceResponse::BaseNodes(nodes) => Ok(nodes),
+            _ => Err(BaseNodeServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_current_base_node(&mut self) -> Result<Option<CommsPublicKey>, BaseNodeServiceError> {
+        match self.handle.call(BaseNodeServiceRequest::GetCurrentBaseNode).await?? {
+            BaseNodeServiceResponse::CurrentBaseNode(public_key) => Ok(public_key),
+            _ => Err(BaseNodeServiceError::UnexpectedApiResponse),
+        }
+    }
+}
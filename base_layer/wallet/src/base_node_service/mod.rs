@@ -0,0 +1,107 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+pub mod config;
+pub mod error;
+pub mod handle;
+pub mod service;
+
+use crate::{
+    base_node_service::{config::BaseNodeServiceConfig, handle::BaseNodeServiceHandle, service::BaseNodeService},
+    output_manager_service::handle::OutputManagerHandle,
+    transaction_service::handle::TransactionServiceHandle,
+};
+use futures::{future, Future};
+use log::*;
+use tari_p2p::services::liveness::LivenessHandle;
+use tari_service_framework::{
+    handles::ServiceHandlesFuture,
+    reply_channel,
+    ServiceInitializationError,
+    ServiceInitializer,
+};
+use tari_shutdown::ShutdownSignal;
+use tokio::{runtime, sync::broadcast};
+
+const LOG_TARGET: &str = "wallet::base_node_service::initializer";
+
+pub struct BaseNodeServiceInitializer {
+    config: BaseNodeServiceConfig,
+}
+
+impl BaseNodeServiceInitializer {
+    pub fn new(config: BaseNodeServiceConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl ServiceInitializer for BaseNodeServiceInitializer {
+    type Future = impl Future<Output = Result<(), ServiceInitializationError>>;
+
+    fn initialize(
+        &mut self,
+        executor: runtime::Handle,
+        handles_fut: ServiceHandlesFuture,
+        shutdown: ShutdownSignal,
+    ) -> Self::Future
+    {
+        let (sender, receiver) = reply_channel::unbounded();
+        let (publisher, _) = broadcast::channel(200);
+
+        let base_node_handle = BaseNodeServiceHandle::new(sender, publisher.clone());
+
+        // Register handle before waiting for handles to be ready
+        handles_fut.register(base_node_handle);
+
+        let config = self.config.clone();
+
+        executor.spawn(async move {
+            let handles = handles_fut.await;
+
+            let liveness_service = handles
+                .get_handle::<LivenessHandle>()
+                .expect("LivenessHandle handle required for Base Node Service");
+            let transaction_service = handles
+                .get_handle::<TransactionServiceHandle>()
+                .expect("Transaction Service handle required for Base Node Service");
+            let output_manager_service = handles
+                .get_handle::<OutputManagerHandle>()
+                .expect("Output Manager Service handle required for Base Node Service");
+
+            let service = BaseNodeService::new(
+                config,
+                receiver,
+                liveness_service,
+                transaction_service,
+                output_manager_service,
+                publisher,
+            )
+            .start();
+
+            futures::pin_mut!(service);
+            future::select(service, shutdown).await;
+            info!(target: LOG_TARGET, "Base Node Service shutdown");
+        });
+
+        future::ready(Ok(()))
+    }
+}
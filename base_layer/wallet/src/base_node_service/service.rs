@@ -0,0 +1,368 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    base_node_service::{
+        config::BaseNodeServiceConfig,
+        error::BaseNodeServiceError,
+        handle::{BaseNodeEvent, BaseNodeEventSender, BaseNodeServiceRequest, BaseNodeServiceResponse, BaseNodeState},
+    },
+    output_manager_service::handle::OutputManagerHandle,
+    transaction_service::handle::TransactionServiceHandle,
+};
+use futures::{pin_mut, StreamExt};
+use log::*;
+use prost::Message;
+use std::sync::Arc;
+use tari_comms::{peer_manager::NodeId, types::CommsPublicKey};
+use tari_core::base_node::proto::base_node as BaseNodeProto;
+use tari_p2p::services::liveness::{LivenessEvent, LivenessHandle, MetadataKey, PingPongEvent};
+use tari_service_framework::reply_channel;
+
+const LOG_TARGET: &str = "wallet::base_node_service::service";
+
+/// A base node the service is monitoring along with the Node Id that the Liveness service reports it by
+struct MonitoredBaseNode {
+    node_id: NodeId,
+    state: BaseNodeState,
+}
+
+/// The Base Node Service keeps a ranked list of the base nodes the wallet can use and monitors their health using the
+/// pings sent by the Liveness service and the chain metadata returned in the pongs. The first healthy base node in
+/// the list is used by the Transaction and Output Manager services and if it becomes unhealthy the services are
+/// failed over to the next healthy base node.
+pub struct BaseNodeService {
+    config: BaseNodeServiceConfig,
+    request_stream:
+        Option<reply_channel::Receiver<BaseNodeServiceRequest, Result<BaseNodeServiceResponse, BaseNodeServiceError>>>,
+    liveness_service: LivenessHandle,
+    transaction_service: TransactionServiceHandle,
+    output_manager_service: OutputManagerHandle,
+    event_publisher: BaseNodeEventSender,
+    base_nodes: Vec<MonitoredBaseNode>,
+    current_base_node: Option<CommsPublicKey>,
+    secondary_base_node: Option<CommsPublicKey>,
+    chain_tip_height: Option<u64>,
+    no_healthy_base_nodes: bool,
+}
+
+impl BaseNodeService {
+    pub fn new(
+        config: BaseNodeServiceConfig,
+        request_stream: reply_channel::Receiver<
+            BaseNodeServiceRequest,
+            Result<BaseNodeServiceResponse, BaseNodeServiceError>,
+        >,
+        liveness_service: LivenessHandle,
+        transaction_service: TransactionServiceHandle,
+        output_manager_service: OutputManagerHandle,
+        event_publisher: BaseNodeEventSender,
+    ) -> Self
+    {
+        Self {
+            config,
+            request_stream: Some(request_stream),
+            liveness_service,
+            transaction_service,
+            output_manager_service,
+            event_publisher,
+            base_nodes: Vec::new(),
+            current_base_node: None,
+            secondary_base_node: None,
+            chain_tip_height: None,
+            no_healthy_base_nodes: false,
+        }
+    }
+
+    pub async fn start(mut self) -> Result<(), BaseNodeServiceError> {
+        let request_stream = self
+            .request_stream
+            .take()
+            .expect("Base Node Service initialized without request_stream")
+            .fuse();
+        pin_mut!(request_stream);
+        let liveness_event_stream = self.liveness_service.get_event_stream_fused();
+        pin_mut!(liveness_event_stream);
+
+        info!(target: LOG_TARGET, "Base Node Service started");
+        loop {
+            futures::select! {
+                request_context = request_stream.select_next_some() => {
+                    let (request, reply_tx) = request_context.split();
+                    let _ = reply_tx.send(self.handle_request(request).await.or_else(|resp| {
+                        error!(target: LOG_TARGET, "Error handling request: {:?}", resp);
+                        Err(resp)
+                    })).or_else(|resp| {
+                        error!(target: LOG_TARGET, "Failed to send reply");
+                        Err(resp)
+                    });
+                },
+                liveness_event = liveness_event_stream.select_next_some() => {
+                    match liveness_event {
+                        Ok(event) => {
+                            let _ = self.handle_liveness_event(&*event).await.or_else(|resp| {
+                                error!(target: LOG_TARGET, "Error handling liveness event: {:?}", resp);
+                                Err(resp)
+                            });
+                        },
+                        Err(e) => warn!(target: LOG_TARGET, "Error reading liveness event stream: {:?}", e),
+                    }
+                },
+                complete => {
+                    info!(target: LOG_TARGET, "Base Node service shutting down");
+                    break;
+                }
+            }
+        }
+        info!(target: LOG_TARGET, "Base Node Service ended");
+        Ok(())
+    }
+
+    async fn handle_request(
+        &mut self,
+        request: BaseNodeServiceRequest,
+    ) -> Result<BaseNodeServiceResponse, BaseNodeServiceError>
+    {
+        match request {
+            BaseNodeServiceRequest::AddBaseNode(pk) => {
+                self.add_base_node(pk).await?;
+                Ok(BaseNodeServiceResponse::BaseNodeAdded)
+            },
+            BaseNodeServiceRequest::RemoveBaseNode(pk) => {
+                self.remove_base_node(pk).await?;
+                Ok(BaseNodeServiceResponse::BaseNodeRemoved)
+            },
+            BaseNodeServiceRequest::GetBaseNodes => Ok(BaseNodeServiceResponse::BaseNodes(
+                self.base_nodes.iter().map(|n| n.state.clone()).collect(),
+            )),
+            BaseNodeServiceRequest::GetCurrentBaseNode => {
+                Ok(BaseNodeServiceResponse::CurrentBaseNode(self.current_base_node.clone()))
+            },
+        }
+    }
+
+    /// Add a base node to the end of the ranked list and start monitoring it with the Liveness service
+    async fn add_base_node(&mut self, public_key: CommsPublicKey) -> Result<(), BaseNodeServiceError> {
+        if self.base_nodes.iter().any(|n| n.state.public_key == public_key) {
+            return Err(BaseNodeServiceError::DuplicateBaseNode);
+        }
+        let node_id = NodeId::from_key(&public_key)?;
+        self.liveness_service.add_node_id(node_id.clone()).await?;
+        info!(target: LOG_TARGET, "Base node {} added", public_key);
+        self.base_nodes.push(MonitoredBaseNode {
+            node_id,
+            state: BaseNodeState::new(public_key),
+        });
+        self.update_current_base_node().await
+    }
+
+    /// Remove a base node from the ranked list and stop monitoring it
+    async fn remove_base_node(&mut self, public_key: CommsPublicKey) -> Result<(), BaseNodeServiceError> {
+        let index = self
+            .base_nodes
+            .iter()
+            .position(|n| n.state.public_key == public_key)
+            .ok_or_else(|| BaseNodeServiceError::BaseNodeNotFound)?;
+        let removed = self.base_nodes.remove(index);
+        self.liveness_service.remove_node_id(removed.node_id).await?;
+        info!(target: LOG_TARGET, "Base node {} removed", public_key);
+        self.update_current_base_node().await
+    }
+
+    async fn handle_liveness_event(&mut self, event: &LivenessEvent) -> Result<(), BaseNodeServiceError> {
+        match event {
+            // A new round of pings has gone out so every base node owes us a pong
+            LivenessEvent::BroadcastedMonitoredNodeIdPings(_) => {
+                for base_node in self.base_nodes.iter_mut() {
+                    base_node.state.missed_pings = base_node.state.missed_pings.saturating_add(1);
+                }
+            },
+            LivenessEvent::ReceivedPong(event) => {
+                if !self.handle_pong(event) {
+                    return Ok(());
+                }
+            },
+            _ => return Ok(()),
+        }
+        self.update_current_base_node().await
+    }
+
+    /// Update the health information of the base node that sent this pong. Returns false if the pong was not from one
+    /// of the wallet's base nodes.
+    fn handle_pong(&mut self, event: &PingPongEvent) -> bool {
+        let base_node = match self.base_nodes.iter_mut().find(|n| n.node_id == event.node_id) {
+            None => return false,
+            Some(n) => n,
+        };
+        base_node.state.missed_pings = 0;
+        base_node.state.latency = event.latency;
+        if let Some(bytes) = event.metadata.get(MetadataKey::ChainMetadata) {
+            match BaseNodeProto::ChainMetadata::decode(bytes.as_slice()) {
+                Ok(metadata) => {
+                    if metadata.height_of_longest_chain.is_some() {
+                        base_node.state.chain_height = metadata.height_of_longest_chain;
+                    }
+                },
+                Err(e) => warn!(
+                    target: LOG_TARGET,
+                    "Could not decode chain metadata from base node {}: {:?}", base_node.state.public_key, e
+                ),
+            }
+        }
+        trace!(
+            target: LOG_TARGET,
+            "Pong from base node {} (latency: {:?}, chain height: {:?})",
+            base_node.state.public_key,
+            base_node.state.latency,
+            base_node.state.chain_height
+        );
+        true
+    }
+
+    fn is_responsive(&self, state: &BaseNodeState) -> bool {
+        state.missed_pings <= self.config.max_missed_pings
+    }
+
+    /// A base node is healthy if it has answered a recent ping and is not too far behind the best chain reported by
+    /// the other responsive base nodes. Base nodes that have not yet reported their chain height are given the benefit
+    /// of the doubt.
+    fn is_healthy(&self, state: &BaseNodeState, best_chain_height: Option<u64>) -> bool {
+        if !self.is_responsive(state) {
+            return false;
+        }
+        match (best_chain_height, state.chain_height) {
+            (Some(best), Some(height)) => best.saturating_sub(height) <= self.config.max_block_lag,
+            _ => true,
+        }
+    }
+
+    /// The public keys of the healthy base nodes in order of preference
+    fn healthy_base_nodes(&self) -> Vec<CommsPublicKey> {
+        let best_chain_height = self
+            .base_nodes
+            .iter()
+            .filter(|n| self.is_responsive(&n.state))
+            .filter_map(|n| n.state.chain_height)
+            .max();
+        self.base_nodes
+            .iter()
+            .filter(|n| self.is_healthy(&n.state, best_chain_height))
+            .map(|n| n.state.public_key.clone())
+            .collect()
+    }
+
+    /// Select the most preferred healthy base node and, if it has changed, switch the Transaction and Output Manager
+    /// services over to it.
+    async fn update_current_base_node(&mut self) -> Result<(), BaseNodeServiceError> {
+        let healthy_base_nodes = self.healthy_base_nodes();
+
+        let selected = match healthy_base_nodes.first() {
+            None if self.base_nodes.is_empty() => return self.clear_current_base_node().await,
+            None => {
+                // Keep using the current base node as there is nothing better to switch to
+                if !self.no_healthy_base_nodes {
+                    warn!(target: LOG_TARGET, "None of the wallet's base nodes are healthy");
+                    self.no_healthy_base_nodes = true;
+                    self.publish_event(BaseNodeEvent::NoHealthyBaseNodes);
+                }
+                return Ok(());
+            },
+            Some(pk) => pk.clone(),
+        };
+        self.no_healthy_base_nodes = false;
+
+        if self.current_base_node.as_ref() != Some(&selected) {
+            match self.current_base_node.as_ref() {
+                None => info!(target: LOG_TARGET, "Using base node {}", selected),
+                Some(previous) => info!(
+                    target: LOG_TARGET,
+                    "Failing over from base node {} to base node {}", previous, selected
+                ),
+            }
+            self.transaction_service
+                .set_base_node_public_key(selected.clone())
+                .await?;
+            self.output_manager_service
+                .set_base_node_public_key(selected.clone())
+                .await?;
+            self.current_base_node = Some(selected.clone());
+            self.publish_event(BaseNodeEvent::BaseNodeChanged(selected));
+        }
+
+        let secondary = if self.config.cross_check_utxo_queries {
+            healthy_base_nodes.get(1).cloned()
+        } else {
+            None
+        };
+        if secondary != self.secondary_base_node {
+            self.output_manager_service
+                .set_secondary_base_node_public_key(secondary.clone())
+                .await?;
+            self.secondary_base_node = secondary;
+        }
+
+        // Keep the Output Manager informed of the chain tip so that it does not select immature outputs
+        let chain_tip_height = self
+            .base_nodes
+            .iter()
+            .find(|n| n.state.public_key == selected)
+            .and_then(|n| n.state.chain_height);
+        if let Some(height) = chain_tip_height {
+            if self.chain_tip_height != Some(height) {
+                self.output_manager_service.set_chain_tip_height(height).await?;
+                self.chain_tip_height = Some(height);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Once the last base node has been removed there is no current base node to report or to cross-check UTXO queries
+    /// with. The Transaction and Output Manager services keep the last base node they were given until a new one is
+    /// added.
+    async fn clear_current_base_node(&mut self) -> Result<(), BaseNodeServiceError> {
+        self.no_healthy_base_nodes = false;
+        if self.secondary_base_node.is_some() {
+            self.output_manager_service
+                .set_secondary_base_node_public_key(None)
+                .await?;
+            self.secondary_base_node = None;
+        }
+        if let Some(previous) = self.current_base_node.take() {
+            info!(target: LOG_TARGET, "Last base node {} removed", previous);
+            self.chain_tip_height = None;
+            self.publish_event(BaseNodeEvent::NoBaseNodes);
+        }
+        Ok(())
+    }
+
+    fn publish_event(&self, event: BaseNodeEvent) {
+        let _ = self.event_publisher.send(Arc::new(event)).map_err(|e| {
+            trace!(
+                target: LOG_TARGET,
+                "Error sending event, usually because there are no subscribers: {:?}",
+                e
+            );
+            e
+        });
+    }
+}
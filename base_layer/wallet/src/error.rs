@@ -21,6 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
+    base_node_service::error::BaseNodeServiceError,
     contacts_service::error::ContactsServiceError,
     output_manager_service::error::OutputManagerError,
     storage::database::DbKey,
@@ -46,6 +47,7 @@ pub enum WalletError {
     ContactsServiceError(ContactsServiceError),
     LivenessServiceError(LivenessError),
     StoreAndForwardError(StoreAndForwardError),
+    BaseNodeServiceError(BaseNodeServiceError),
 }
 
 #[derive(Debug, Error)]
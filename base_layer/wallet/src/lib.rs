@@ -4,8 +4,10 @@
 
 #[macro_use]
 mod macros;
+pub mod base_node_service;
 pub mod contacts_service;
 pub mod error;
+mod metrics;
 pub mod output_manager_service;
 pub mod storage;
 pub mod transaction_service;
@@ -0,0 +1,84 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    output_manager_service::service::Balance,
+    transaction_service::handle::{TransactionEvent, TransactionEventReceiver},
+};
+use tari_metrics::{IntCounterVec, IntGaugeVec};
+use tokio::sync::broadcast::RecvError;
+
+lazy_static! {
+    static ref BALANCE: IntGaugeVec = tari_metrics::register_int_gauge_vec(
+        "wallet_balance_micro_tari",
+        "The last calculated wallet balance in MicroTari",
+        &["kind"]
+    );
+    static ref TRANSACTION_EVENTS: IntCounterVec = tari_metrics::register_int_counter_vec(
+        "wallet_transaction_events_total",
+        "Number of transaction service events by event type",
+        &["event"]
+    );
+}
+
+pub fn set_balance(balance: &Balance) {
+    BALANCE
+        .with_label_values(&["available"])
+        .set(u64::from(balance.available_balance) as i64);
+    BALANCE
+        .with_label_values(&["pending_incoming"])
+        .set(u64::from(balance.pending_incoming_balance) as i64);
+    BALANCE
+        .with_label_values(&["pending_outgoing"])
+        .set(u64::from(balance.pending_outgoing_balance) as i64);
+}
+
+/// Count transaction service events until the event publisher is dropped
+pub async fn record_transaction_events(mut events: TransactionEventReceiver) {
+    loop {
+        match events.recv().await {
+            Ok(event) => TRANSACTION_EVENTS.with_label_values(&[event_label(&event)]).inc(),
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+fn event_label(event: &TransactionEvent) -> &'static str {
+    use TransactionEvent::*;
+    match event {
+        MempoolBroadcastTimedOut(_) => "mempool_broadcast_timed_out",
+        ReceivedTransaction(_) => "received_transaction",
+        ReceivedTransactionReply(_) => "received_transaction_reply",
+        ReceivedFinalizedTransaction(_) => "received_finalized_transaction",
+        TransactionDirectSendResult(_, _) => "direct_send_result",
+        TransactionStoreForwardSendResult(_, _) => "store_forward_send_result",
+        TransactionCancelled(_) => "cancelled",
+        TransactionBroadcast(_) => "broadcast",
+        TransactionMined(_) => "mined",
+        TransactionMinedRequestTimedOut(_) => "mined_request_timed_out",
+        TransactionConfirmed(_) => "confirmed",
+        TransactionReorged(_) => "reorged",
+        ReceivedOneSidedTransaction(_) => "received_one_sided_transaction",
+        Error(_) => "error",
+    }
+}
@@ -21,16 +21,28 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::time::Duration;
+use tari_core::transactions::tari_amount::MicroTari;
 
 #[derive(Clone)]
 pub struct OutputManagerServiceConfig {
     pub base_node_query_timeout: Duration,
+    /// Change below this value is not worth creating an output for. UTXO selection avoids producing such change and
+    /// a change-free input set may overshoot the amount by up to this value, with the excess going to the fee.
+    pub dust_threshold: MicroTari,
+    /// A watch-only wallet holds no spending keys. It tracks outputs added from public data only and refuses every
+    /// request that would need a private key.
+    pub watch_only: bool,
+    /// The fee per gram paid when a received one-sided payment is swept to a key only this wallet knows
+    pub one_sided_payment_sweep_fee_per_gram: MicroTari,
 }
 
 impl Default for OutputManagerServiceConfig {
     fn default() -> Self {
         Self {
             base_node_query_timeout: Duration::from_secs(30),
+            dust_threshold: MicroTari::from(100),
+            watch_only: false,
+            one_sided_payment_sweep_fee_per_gram: MicroTari::from(25),
         }
     }
 }
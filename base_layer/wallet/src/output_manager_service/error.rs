@@ -49,8 +49,22 @@ pub enum OutputManagerError {
     IncompleteTransaction,
     /// Not enough funds to fulfil transaction
     NotEnoughFunds,
+    /// No unspent output with the specified commitment exists
+    UnspentOutputNotFound,
+    /// There are fewer than two spendable outputs to consolidate
+    NothingToConsolidate,
     /// Output already exists
     DuplicateOutput,
+    /// No account with the specified name exists
+    AccountNotFound,
+    /// An account with the specified name already exists
+    AccountAlreadyExists,
+    /// Account names must be non-empty, printable and must not be the default account name
+    InvalidAccountName,
+    /// This operation requires spending keys which a watch-only wallet does not hold
+    WatchOnlyWallet,
+    /// No watched output with the specified commitment exists
+    WatchedOutputNotFound,
     /// Error sending a message to the public API
     ApiSendFailed,
     /// Error receiving a message from the public API
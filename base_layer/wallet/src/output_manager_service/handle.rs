@@ -22,8 +22,9 @@
 
 use crate::output_manager_service::{
     error::OutputManagerError,
-    service::Balance,
-    storage::database::PendingTransactionOutputs,
+    service::{AnnotatedOutput, Balance},
+    storage::database::{Account, PendingTransactionOutputs, WatchedOutput, DEFAULT_ACCOUNT},
+    TxId,
 };
 use futures::{stream::Fuse, StreamExt};
 use std::{collections::HashMap, fmt, time::Duration};
@@ -31,10 +32,11 @@ use tari_broadcast_channel::Subscriber;
 use tari_comms::types::CommsPublicKey;
 use tari_core::transactions::{
     tari_amount::MicroTari,
-    transaction::{Transaction, TransactionInput, TransactionOutput, UnblindedOutput},
-    types::PrivateKey,
+    transaction::{OutputFeatures, Transaction, TransactionInput, TransactionOutput, UnblindedOutput},
+    types::{Commitment, PrivateKey},
     SenderTransactionProtocol,
 };
+use tari_crypto::tari_utilities::hex::Hex;
 use tari_service_framework::reply_channel::SenderService;
 use tower::Service;
 
@@ -43,11 +45,21 @@ use tower::Service;
 pub enum OutputManagerRequest {
     GetBalance,
     AddOutput(UnblindedOutput),
-    GetRecipientKey((u64, MicroTari)),
+    GetRecipientKey((u64, MicroTari, u64)),
     GetCoinbaseKey((u64, MicroTari, u64)),
     ConfirmPendingTransaction(u64),
     ConfirmTransaction((u64, Vec<TransactionInput>, Vec<TransactionOutput>)),
-    PrepareToSendTransaction((MicroTari, MicroTari, Option<u64>, String)),
+    PrepareToSendTransaction(
+        (
+            MicroTari,
+            MicroTari,
+            Option<u64>,
+            u64,
+            String,
+            Option<Vec<Commitment>>,
+            String,
+        ),
+    ),
     CancelTransaction(u64),
     TimeoutTransactions(Duration),
     GetPendingTransactions,
@@ -56,8 +68,46 @@ pub enum OutputManagerRequest {
     GetInvalidOutputs,
     GetSeedWords,
     SetBaseNodePublicKey(CommsPublicKey),
+    SetSecondaryBaseNodePublicKey(Option<CommsPublicKey>),
+    SetChainTipHeight(u64),
     SyncWithBaseNode,
     CreateCoinSplit((MicroTari, usize, MicroTari, Option<u64>)),
+    GetConsolidationCandidates(MicroTari),
+    CreateCoinConsolidation((usize, MicroTari, Option<u64>)),
+    CreateOneSidedTransaction((CommsPublicKey, MicroTari, MicroTari, Option<u64>, String)),
+    ScanForOneSidedPayments,
+    SetOutputFrozen((Commitment, bool)),
+    SetOutputLabel((Commitment, Option<String>)),
+    ClearOutputAnnotation(Commitment),
+    GetAnnotatedUnspentOutputs,
+    CreateAccount(String),
+    GetAccounts,
+    GetAccountBalance(String),
+    SetReceivingAccount(String),
+    GetTransactionAccounts,
+    AddWatchedOutput((Commitment, OutputFeatures, MicroTari, Option<String>)),
+    RemoveWatchedOutput(Commitment),
+    GetWatchedOutputs,
+}
+
+impl OutputManagerRequest {
+    /// Whether servicing this request needs the wallet's spending keys, in which case a watch-only wallet refuses it
+    pub fn requires_spending_keys(&self) -> bool {
+        match self {
+            Self::AddOutput(_) |
+            Self::GetRecipientKey(_) |
+            Self::GetCoinbaseKey(_) |
+            Self::PrepareToSendTransaction(_) |
+            Self::CreateCoinSplit(_) |
+            Self::CreateCoinConsolidation(_) |
+            Self::CreateOneSidedTransaction(_) |
+            Self::ScanForOneSidedPayments |
+            Self::GetSeedWords |
+            Self::CreateAccount(_) |
+            Self::SetReceivingAccount(_) => true,
+            _ => false,
+        }
+    }
 }
 
 impl fmt::Display for OutputManagerRequest {
@@ -69,8 +119,8 @@ impl fmt::Display for OutputManagerRequest {
             Self::GetCoinbaseKey(v) => f.write_str(&format!("GetCoinbaseKey ({})", v.0)),
             Self::ConfirmTransaction(v) => f.write_str(&format!("ConfirmTransaction ({})", v.0)),
             Self::ConfirmPendingTransaction(v) => f.write_str(&format!("ConfirmPendingTransaction ({})", v)),
-            Self::PrepareToSendTransaction((_, _, _, msg)) => {
-                f.write_str(&format!("PrepareToSendTransaction ({})", msg))
+            Self::PrepareToSendTransaction((_, _, _, _, msg, _, account)) => {
+                f.write_str(&format!("PrepareToSendTransaction ({}, from {})", msg, account))
             },
             Self::CancelTransaction(v) => f.write_str(&format!("CancelTransaction ({})", v)),
             Self::TimeoutTransactions(d) => f.write_str(&format!("TimeoutTransactions ({}s)", d.as_secs())),
@@ -80,8 +130,31 @@ impl fmt::Display for OutputManagerRequest {
             Self::GetInvalidOutputs => f.write_str("GetInvalidOutputs"),
             Self::GetSeedWords => f.write_str("GetSeedWords"),
             Self::SetBaseNodePublicKey(k) => f.write_str(&format!("SetBaseNodePublicKey ({})", k)),
+            Self::SetSecondaryBaseNodePublicKey(k) => match k {
+                Some(k) => f.write_str(&format!("SetSecondaryBaseNodePublicKey ({})", k)),
+                None => f.write_str("SetSecondaryBaseNodePublicKey (None)"),
+            },
+            Self::SetChainTipHeight(h) => f.write_str(&format!("SetChainTipHeight ({})", h)),
             Self::SyncWithBaseNode => f.write_str("SyncWithBaseNode"),
             Self::CreateCoinSplit(v) => f.write_str(&format!("CreateCoinSplit ({})", v.0)),
+            Self::GetConsolidationCandidates(v) => f.write_str(&format!("GetConsolidationCandidates ({})", v)),
+            Self::CreateCoinConsolidation(v) => f.write_str(&format!("CreateCoinConsolidation ({})", v.0)),
+            Self::CreateOneSidedTransaction((k, v, _, _, msg)) => {
+                f.write_str(&format!("CreateOneSidedTransaction (to {}, {}, {})", k, v, msg))
+            },
+            Self::ScanForOneSidedPayments => f.write_str("ScanForOneSidedPayments"),
+            Self::SetOutputFrozen((c, frozen)) => f.write_str(&format!("SetOutputFrozen ({}, {})", c.to_hex(), frozen)),
+            Self::SetOutputLabel((c, _)) => f.write_str(&format!("SetOutputLabel ({})", c.to_hex())),
+            Self::ClearOutputAnnotation(c) => f.write_str(&format!("ClearOutputAnnotation ({})", c.to_hex())),
+            Self::GetAnnotatedUnspentOutputs => f.write_str("GetAnnotatedUnspentOutputs"),
+            Self::CreateAccount(name) => f.write_str(&format!("CreateAccount ({})", name)),
+            Self::GetAccounts => f.write_str("GetAccounts"),
+            Self::GetAccountBalance(name) => f.write_str(&format!("GetAccountBalance ({})", name)),
+            Self::SetReceivingAccount(name) => f.write_str(&format!("SetReceivingAccount ({})", name)),
+            Self::GetTransactionAccounts => f.write_str("GetTransactionAccounts"),
+            Self::AddWatchedOutput((c, _, v, _)) => f.write_str(&format!("AddWatchedOutput ({}, {})", c.to_hex(), v)),
+            Self::RemoveWatchedOutput(c) => f.write_str(&format!("RemoveWatchedOutput ({})", c.to_hex())),
+            Self::GetWatchedOutputs => f.write_str("GetWatchedOutputs"),
         }
     }
 }
@@ -103,15 +176,34 @@ pub enum OutputManagerResponse {
     InvalidOutputs(Vec<UnblindedOutput>),
     SeedWords(Vec<String>),
     BaseNodePublicKeySet,
+    SecondaryBaseNodePublicKeySet,
+    ChainTipHeightSet,
     StartedBaseNodeSync(u64),
     Transaction((u64, Transaction, MicroTari, MicroTari)),
+    ConsolidationCandidates(Vec<UnblindedOutput>),
+    StartedOneSidedPaymentScan(u64),
+    OutputFrozenSet,
+    OutputLabelSet,
+    OutputAnnotationCleared,
+    AnnotatedUnspentOutputs(Vec<AnnotatedOutput>),
+    AccountCreated,
+    Accounts(Vec<Account>),
+    ReceivingAccountSet,
+    TransactionAccounts(HashMap<TxId, String>),
+    WatchedOutputAdded,
+    WatchedOutputRemoved,
+    WatchedOutputs(Vec<WatchedOutput>),
 }
 
 /// Events that can be published on the Text Message Service Event Stream
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum OutputManagerEvent {
     BaseNodeSyncRequestTimedOut(u64),
     ReceiveBaseNodeResponse(u64),
+    /// A one-sided payment was found and is being swept to a fresh key: the sweep's TxId, the transaction, its fee and
+    /// the value of the payment received
+    ReceivedOneSidedPayment((TxId, Transaction, MicroTari, MicroTari)),
+    OneSidedPaymentScanRequestTimedOut(u64),
     Error(String),
 }
 
@@ -156,7 +248,26 @@ impl OutputManagerHandle {
     {
         match self
             .handle
-            .call(OutputManagerRequest::GetRecipientKey((tx_id, amount)))
+            .call(OutputManagerRequest::GetRecipientKey((tx_id, amount, 0)))
+            .await??
+        {
+            OutputManagerResponse::RecipientKeyGenerated(k) => Ok(k),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Request a spending key for an incoming transaction whose sender asked for the output to only become spendable
+    /// at the `maturity` block height.
+    pub async fn get_recipient_spending_key_with_maturity(
+        &mut self,
+        tx_id: u64,
+        amount: MicroTari,
+        maturity: u64,
+    ) -> Result<PrivateKey, OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetRecipientKey((tx_id, amount, maturity)))
             .await??
         {
             OutputManagerResponse::RecipientKeyGenerated(k) => Ok(k),
@@ -195,7 +306,102 @@ impl OutputManagerHandle {
                 amount,
                 fee_per_gram,
                 lock_height,
+                0,
+                message,
+                None,
+                DEFAULT_ACCOUNT.to_string(),
+            )))
+            .await??
+        {
+            OutputManagerResponse::TransactionToSend(stp) => Ok(stp),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Prepare a transaction that spends exactly the unspent outputs with the given commitments, including frozen
+    /// outputs, instead of letting the service select the inputs.
+    pub async fn prepare_transaction_to_send_with_inputs(
+        &mut self,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+        inputs: Vec<Commitment>,
+    ) -> Result<SenderTransactionProtocol, OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::PrepareToSendTransaction((
+                amount,
+                fee_per_gram,
+                lock_height,
+                0,
+                message,
+                Some(inputs),
+                DEFAULT_ACCOUNT.to_string(),
+            )))
+            .await??
+        {
+            OutputManagerResponse::TransactionToSend(stp) => Ok(stp),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Prepare a transaction that cannot be mined before `lock_height` and whose recipient output only becomes
+    /// spendable at the `recipient_maturity` block height. If `inputs` is provided exactly those unspent outputs are
+    /// spent.
+    pub async fn prepare_time_locked_transaction_to_send(
+        &mut self,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        recipient_maturity: u64,
+        message: String,
+        inputs: Option<Vec<Commitment>>,
+    ) -> Result<SenderTransactionProtocol, OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::PrepareToSendTransaction((
+                amount,
+                fee_per_gram,
+                lock_height,
+                recipient_maturity,
                 message,
+                inputs,
+                DEFAULT_ACCOUNT.to_string(),
+            )))
+            .await??
+        {
+            OutputManagerResponse::TransactionToSend(stp) => Ok(stp),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Prepare a transaction that is funded only from the outputs of the named account, returning any change to the
+    /// same account. The lock height, recipient maturity and inputs are treated as in
+    /// `prepare_time_locked_transaction_to_send`, with explicit inputs required to belong to the account.
+    pub async fn prepare_transaction_to_send_from_account(
+        &mut self,
+        account: String,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        recipient_maturity: u64,
+        message: String,
+        inputs: Option<Vec<Commitment>>,
+    ) -> Result<SenderTransactionProtocol, OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::PrepareToSendTransaction((
+                amount,
+                fee_per_gram,
+                lock_height,
+                recipient_maturity,
+                message,
+                inputs,
+                account,
             )))
             .await??
         {
@@ -306,6 +512,35 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Set a second base node that UTXO queries are also sent to. Outputs are only invalidated if neither base node
+    /// returns them. Setting `None` stops the cross-checking.
+    pub async fn set_secondary_base_node_public_key(
+        &mut self,
+        public_key: Option<CommsPublicKey>,
+    ) -> Result<(), OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::SetSecondaryBaseNodePublicKey(public_key))
+            .await??
+        {
+            OutputManagerResponse::SecondaryBaseNodePublicKeySet => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Let the service know the current height of the chain tip so that immature outputs are not selected as inputs
+    pub async fn set_chain_tip_height(&mut self, height: u64) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::SetChainTipHeight(height))
+            .await??
+        {
+            OutputManagerResponse::ChainTipHeightSet => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn sync_with_base_node(&mut self) -> Result<u64, OutputManagerError> {
         match self.handle.call(OutputManagerRequest::SyncWithBaseNode).await?? {
             OutputManagerResponse::StartedBaseNodeSync(request_key) => Ok(request_key),
@@ -335,4 +570,234 @@ impl OutputManagerHandle {
             _ => Err(OutputManagerError::UnexpectedApiResponse),
         }
     }
+
+    /// Fetch the outputs a coin consolidation at `fee_per_gram` would merge, smallest first
+    pub async fn get_consolidation_candidates(
+        &mut self,
+        fee_per_gram: MicroTari,
+    ) -> Result<Vec<UnblindedOutput>, OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetConsolidationCandidates(fee_per_gram))
+            .await??
+        {
+            OutputManagerResponse::ConsolidationCandidates(o) => Ok(o),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Create a transaction that merges up to `max_inputs` of the wallet's smallest outputs into a single output
+    pub async fn create_coin_consolidation(
+        &mut self,
+        max_inputs: usize,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+    ) -> Result<(u64, Transaction, MicroTari, MicroTari), OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateCoinConsolidation((
+                max_inputs,
+                fee_per_gram,
+                lock_height,
+            )))
+            .await??
+        {
+            OutputManagerResponse::Transaction(ct) => Ok(ct),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Create a transaction that pays `amount` to `dest_pubkey` without any interaction with the recipient. The
+    /// returned transaction is complete and ready to be broadcast.
+    pub async fn create_one_sided_transaction(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+    ) -> Result<(u64, Transaction, MicroTari, MicroTari), OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateOneSidedTransaction((
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                lock_height,
+                message,
+            )))
+            .await??
+        {
+            OutputManagerResponse::Transaction(ct) => Ok(ct),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Start scanning the blocks mined since the last scan for one-sided payments made to this wallet
+    pub async fn scan_for_one_sided_payments(&mut self) -> Result<u64, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::ScanForOneSidedPayments)
+            .await??
+        {
+            OutputManagerResponse::StartedOneSidedPaymentScan(request_key) => Ok(request_key),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Freeze the unspent output with the given commitment so that it is never selected automatically
+    pub async fn freeze_output(&mut self, commitment: Commitment) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::SetOutputFrozen((commitment, true)))
+            .await??
+        {
+            OutputManagerResponse::OutputFrozenSet => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn unfreeze_output(&mut self, commitment: Commitment) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::SetOutputFrozen((commitment, false)))
+            .await??
+        {
+            OutputManagerResponse::OutputFrozenSet => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Attach a label to the unspent output with the given commitment. A `None` label clears it.
+    pub async fn set_output_label(
+        &mut self,
+        commitment: Commitment,
+        label: Option<String>,
+    ) -> Result<(), OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::SetOutputLabel((commitment, label)))
+            .await??
+        {
+            OutputManagerResponse::OutputLabelSet => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Remove the label of the unspent output with the given commitment and unfreeze it
+    pub async fn clear_output_annotation(&mut self, commitment: Commitment) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::ClearOutputAnnotation(commitment))
+            .await??
+        {
+            OutputManagerResponse::OutputAnnotationCleared => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Fetch the unspent outputs along with their commitments, labels and frozen state
+    pub async fn get_annotated_unspent_outputs(&mut self) -> Result<Vec<AnnotatedOutput>, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetAnnotatedUnspentOutputs)
+            .await??
+        {
+            OutputManagerResponse::AnnotatedUnspentOutputs(o) => Ok(o),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Create a new account whose keys are derived from a separate branch of the wallet's master seed
+    pub async fn create_account(&mut self, name: String) -> Result<(), OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::CreateAccount(name)).await?? {
+            OutputManagerResponse::AccountCreated => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Fetch the wallet's named accounts. The default account is always present and is not included.
+    pub async fn get_accounts(&mut self) -> Result<Vec<Account>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetAccounts).await?? {
+            OutputManagerResponse::Accounts(a) => Ok(a),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_account_balance(&mut self, name: String) -> Result<Balance, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetAccountBalance(name))
+            .await??
+        {
+            OutputManagerResponse::Balance(b) => Ok(b),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Set the account that the outputs of incoming transactions are assigned to
+    pub async fn set_receiving_account(&mut self, name: String) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::SetReceivingAccount(name))
+            .await??
+        {
+            OutputManagerResponse::ReceivingAccountSet => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Fetch the account that each transaction belongs to. Transactions that are not present belong to the default
+    /// account.
+    pub async fn get_transaction_accounts(&mut self) -> Result<HashMap<TxId, String>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetTransactionAccounts).await?? {
+            OutputManagerResponse::TransactionAccounts(a) => Ok(a),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Start tracking an output of a cold wallet given only its public data. The value cannot be recovered from the
+    /// commitment so it has to be supplied by the caller.
+    pub async fn add_watched_output(
+        &mut self,
+        commitment: Commitment,
+        features: OutputFeatures,
+        value: MicroTari,
+        label: Option<String>,
+    ) -> Result<(), OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::AddWatchedOutput((
+                commitment, features, value, label,
+            )))
+            .await??
+        {
+            OutputManagerResponse::WatchedOutputAdded => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn remove_watched_output(&mut self, commitment: Commitment) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::RemoveWatchedOutput(commitment))
+            .await??
+        {
+            OutputManagerResponse::WatchedOutputRemoved => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Fetch the watched outputs along with their status as last reported by the base node
+    pub async fn get_watched_outputs(&mut self) -> Result<Vec<WatchedOutput>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetWatchedOutputs).await?? {
+            OutputManagerResponse::WatchedOutputs(o) => Ok(o),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
 }
@@ -30,6 +30,7 @@ use futures::{future, Future, Stream, StreamExt};
 use log::*;
 use std::sync::Arc;
 use tari_broadcast_channel::bounded;
+use tari_comms::peer_manager::NodeIdentity;
 use tari_comms_dht::outbound::OutboundMessageRequester;
 use tari_core::{base_node::proto::base_node as BaseNodeProto, transactions::types::CryptoFactories};
 use tari_p2p::{
@@ -51,9 +52,11 @@ use tokio::runtime;
 pub mod config;
 pub mod error;
 pub mod handle;
+pub mod one_sided_payment;
 #[allow(unused_assignments)]
 pub mod service;
 pub mod storage;
+pub mod utxo_selection;
 
 const LOG_TARGET: &str = "wallet::output_manager_service::initializer";
 
@@ -66,6 +69,7 @@ where T: OutputManagerBackend
     subscription_factory: Arc<TopicSubscriptionFactory<TariMessageType, Arc<PeerMessage>>>,
     backend: Option<T>,
     factories: CryptoFactories,
+    node_identity: Arc<NodeIdentity>,
 }
 
 impl<T> OutputManagerServiceInitializer<T>
@@ -76,6 +80,7 @@ where T: OutputManagerBackend
         subscription_factory: Arc<TopicSubscriptionFactory<TariMessageType, Arc<PeerMessage>>>,
         backend: T,
         factories: CryptoFactories,
+        node_identity: Arc<NodeIdentity>,
     ) -> Self
     {
         Self {
@@ -83,6 +88,7 @@ where T: OutputManagerBackend
             subscription_factory,
             backend: Some(backend),
             factories,
+            node_identity,
         }
     }
 
@@ -122,6 +128,7 @@ where T: OutputManagerBackend + 'static
             .expect("Cannot start Output Manager Service without setting a storage backend");
         let factories = self.factories.clone();
         let config = self.config.clone();
+        let node_identity = self.node_identity.clone();
 
         executor.spawn(async move {
             let handles = handles_fut.await;
@@ -138,6 +145,7 @@ where T: OutputManagerBackend + 'static
                 OutputManagerDatabase::new(backend),
                 publisher,
                 factories,
+                node_identity,
             )
             .await
             .expect("Could not initialize Output Manager Service")
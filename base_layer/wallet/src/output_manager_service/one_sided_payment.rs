@@ -0,0 +1,175 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! One-sided payments allow a sender to pay a recipient, identified only by their public key, without any interaction
+//! between the two parties. The sender chooses a random nonce `r` and derives a Diffie-Hellman shared secret `r·P`
+//! with the recipient's public key `P`. The spending key of the payment output is derived from the shared secret and
+//! the public nonce `R = r·G` is published in the `meta_info` field of the transaction kernel, along with the output
+//! value masked by the shared secret. The recipient scans mined blocks for kernels carrying this data, computes the
+//! same shared secret `p·R` and recovers the spending key and value of any output that matches.
+//!
+//! The sender knows the spending key as well, as it must to build the output's range proof, so a recovered output is
+//! immediately swept to a fresh key from the recipient's key manager. Until the sweep is mined the sender could still
+//! spend the payment back to themselves.
+
+use crate::{output_manager_service::error::OutputManagerError, types::HashDigest};
+use digest::Digest;
+use rand::rngs::OsRng;
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction::{TransactionKernel, TransactionOutput, UnblindedOutput},
+    types::{CryptoFactories, HashOutput, PrivateKey, PublicKey},
+};
+use tari_crypto::{
+    commitment::HomomorphicCommitmentFactory,
+    keys::{DiffieHellmanSharedSecret, PublicKey as PublicKeyTrait, SecretKey},
+    tari_utilities::ByteArray,
+};
+
+/// The size in bytes of the kernel `meta_info` used for a one-sided payment: a 32 byte public nonce followed by the
+/// 8 byte masked output value
+pub const ONE_SIDED_PAYMENT_META_INFO_SIZE: usize = 40;
+
+const SPENDING_KEY_DOMAIN: &[u8] = b"one_sided_payment.spending_key";
+const VALUE_MASK_DOMAIN: &[u8] = b"one_sided_payment.value_mask";
+
+/// Derive the spending key and the value mask for a payment output from a Diffie-Hellman shared secret
+fn derive_from_shared_secret(shared_secret: &PublicKey) -> Result<(PrivateKey, [u8; 8]), OutputManagerError> {
+    let spending_key = PrivateKey::from_bytes(
+        HashDigest::new()
+            .chain(SPENDING_KEY_DOMAIN)
+            .chain(shared_secret.as_bytes())
+            .result()
+            .as_slice(),
+    )?;
+    let mask_hash = HashDigest::new()
+        .chain(VALUE_MASK_DOMAIN)
+        .chain(shared_secret.as_bytes())
+        .result();
+    let mut value_mask = [0u8; 8];
+    value_mask.copy_from_slice(&mask_hash.as_slice()[..8]);
+    Ok((spending_key, value_mask))
+}
+
+fn mask_value(value: u64, value_mask: &[u8; 8]) -> [u8; 8] {
+    let mut masked = value.to_le_bytes();
+    masked.iter_mut().zip(value_mask.iter()).for_each(|(v, m)| *v ^= m);
+    masked
+}
+
+/// Create the spending key and kernel `meta_info` for a one-sided payment of `amount` to `recipient`. The spending key
+/// must be used as the blinding factor of the payment output and the `meta_info` must be included in the kernel of the
+/// transaction that contains it.
+pub fn create_one_sided_payment(
+    recipient: &PublicKey,
+    amount: MicroTari,
+) -> Result<(PrivateKey, HashOutput), OutputManagerError>
+{
+    let nonce = PrivateKey::random(&mut OsRng);
+    let public_nonce = PublicKey::from_secret_key(&nonce);
+    let shared_secret = PublicKey::shared_secret(&nonce, recipient);
+    let (spending_key, value_mask) = derive_from_shared_secret(&shared_secret)?;
+
+    let mut meta_info = Vec::with_capacity(ONE_SIDED_PAYMENT_META_INFO_SIZE);
+    meta_info.extend_from_slice(public_nonce.as_bytes());
+    meta_info.extend_from_slice(&mask_value(u64::from(amount), &value_mask));
+
+    Ok((spending_key, meta_info))
+}
+
+/// Check whether the provided kernel carries a one-sided payment addressed to the owner of `secret_key`. If it does,
+/// and the matching output can be found in `outputs`, the recovered output is returned.
+pub fn scan_kernel_for_one_sided_payment(
+    secret_key: &PrivateKey,
+    kernel: &TransactionKernel,
+    outputs: &[TransactionOutput],
+    factories: &CryptoFactories,
+) -> Option<UnblindedOutput>
+{
+    let meta_info = match kernel.meta_info.as_ref() {
+        Some(m) if m.len() == ONE_SIDED_PAYMENT_META_INFO_SIZE => m,
+        _ => return None,
+    };
+    let public_nonce = PublicKey::from_bytes(&meta_info[..32]).ok()?;
+    let shared_secret = PublicKey::shared_secret(secret_key, &public_nonce);
+    let (spending_key, value_mask) = derive_from_shared_secret(&shared_secret).ok()?;
+
+    let mut masked_value = [0u8; 8];
+    masked_value.copy_from_slice(&meta_info[32..]);
+    let value = MicroTari::from(u64::from_le_bytes(mask_value(
+        u64::from_le_bytes(masked_value),
+        &value_mask,
+    )));
+
+    let commitment = factories.commitment.commit_value(&spending_key, u64::from(value));
+    outputs
+        .iter()
+        .find(|o| o.commitment == commitment)
+        .map(|o| UnblindedOutput::new(value, spending_key, Some(o.features.clone())))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::output_manager_service::one_sided_payment::{
+        create_one_sided_payment,
+        scan_kernel_for_one_sided_payment,
+    };
+    use rand::rngs::OsRng;
+    use tari_core::transactions::{
+        tari_amount::MicroTari,
+        transaction::{KernelBuilder, UnblindedOutput},
+        types::{CryptoFactories, PrivateKey, PublicKey, Signature},
+    };
+    use tari_crypto::{
+        commitment::HomomorphicCommitmentFactory,
+        keys::{PublicKey as PublicKeyTrait, SecretKey},
+    };
+
+    #[test]
+    fn test_one_sided_payment_recovery() {
+        let factories = CryptoFactories::default();
+        let recipient_secret = PrivateKey::random(&mut OsRng);
+        let recipient_public = PublicKey::from_secret_key(&recipient_secret);
+        let amount = MicroTari::from(12_345);
+
+        let (spending_key, meta_info) = create_one_sided_payment(&recipient_public, amount).unwrap();
+        let output = UnblindedOutput::new(amount, spending_key.clone(), None)
+            .as_transaction_output(&factories)
+            .unwrap();
+
+        let excess_key = PrivateKey::random(&mut OsRng);
+        let kernel = KernelBuilder::new()
+            .with_excess(&factories.commitment.commit_value(&excess_key, 0))
+            .with_signature(&Signature::default())
+            .with_meta_info(meta_info)
+            .build()
+            .unwrap();
+
+        let recovered =
+            scan_kernel_for_one_sided_payment(&recipient_secret, &kernel, &[output.clone()], &factories).unwrap();
+        assert_eq!(recovered.value, amount);
+        assert_eq!(recovered.spending_key, spending_key);
+
+        let other_secret = PrivateKey::random(&mut OsRng);
+        assert!(scan_kernel_for_one_sided_payment(&other_secret, &kernel, &[output], &factories).is_none());
+    }
+}
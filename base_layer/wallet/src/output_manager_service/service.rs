@@ -21,11 +21,25 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
+    metrics,
     output_manager_service::{
         config::OutputManagerServiceConfig,
-        error::OutputManagerError,
+        error::{OutputManagerError, OutputManagerStorageError},
         handle::{OutputManagerEvent, OutputManagerRequest, OutputManagerResponse},
-        storage::database::{KeyManagerState, OutputManagerBackend, OutputManagerDatabase, PendingTransactionOutputs},
+        one_sided_payment::{create_one_sided_payment, scan_kernel_for_one_sided_payment},
+        storage::database::{
+            Account,
+            KeyManagerState,
+            OutputAnnotation,
+            OutputManagerBackend,
+            OutputManagerDatabase,
+            PendingTransactionOutputs,
+            ScannedBlock,
+            WatchedOutput,
+            WatchedOutputStatus,
+            DEFAULT_ACCOUNT,
+        },
+        utxo_selection::{self, SelectionTarget},
         TxId,
     },
     types::{HashDigest, KeyDigest},
@@ -34,9 +48,16 @@ use crate::{
 use futures::{future::BoxFuture, pin_mut, stream::FuturesUnordered, FutureExt, SinkExt, Stream, StreamExt};
 use log::*;
 use rand::{rngs::OsRng, RngCore};
-use std::{cmp::Ordering, collections::HashMap, convert::TryFrom, fmt, sync::Mutex, time::Duration};
+use std::{
+    cmp::{min, Ordering},
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    fmt,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 use tari_broadcast_channel::Publisher;
-use tari_comms::types::CommsPublicKey;
+use tari_comms::{peer_manager::NodeIdentity, types::CommsPublicKey};
 use tari_comms_dht::{
     domain_message::OutboundDomainMessage,
     outbound::{OutboundEncryption, OutboundMessageRequester},
@@ -49,6 +70,8 @@ use tari_core::{
             base_node_service_response::Response as BaseNodeResponseProto,
         },
     },
+    blocks::Block,
+    proto::core::HistoricalBlock,
     transactions::{
         fee::Fee,
         tari_amount::MicroTari,
@@ -59,12 +82,16 @@ use tari_core::{
             TransactionInput,
             TransactionOutput,
             UnblindedOutput,
+            MAX_TRANSACTION_INPUTS,
         },
-        types::{CryptoFactories, PrivateKey},
+        types::{Commitment, CryptoFactories, PrivateKey},
         SenderTransactionProtocol,
     },
 };
-use tari_crypto::{keys::SecretKey as SecretKeyTrait, tari_utilities::hash::Hashable};
+use tari_crypto::{
+    keys::SecretKey as SecretKeyTrait,
+    tari_utilities::{hash::Hashable, hex::Hex, ByteArray},
+};
 use tari_key_manager::{
     key_manager::KeyManager,
     mnemonic::{from_secret_key, MnemonicLanguage},
@@ -74,6 +101,11 @@ use tari_service_framework::reply_channel;
 
 const LOG_TARGET: &str = "wallet::output_manager_service";
 
+/// The maximum number of blocks that will be requested from the base node in a single one-sided payment scan query
+const ONE_SIDED_PAYMENT_SCAN_BATCH_SIZE: u64 = 100;
+/// The number of already scanned blocks that are scanned again when a reorg of the scanned chain is detected
+const ONE_SIDED_PAYMENT_REORG_RESCAN_DEPTH: u64 = 10;
+
 /// This service will manage a wallet's available outputs and the key manager that produces the keys for these outputs.
 /// The service will assemble transactions to be sent from the wallets available outputs and provide keys to receive
 /// outputs. When the outputs are detected on the blockchain the Transaction service will call this Service to confirm
@@ -83,6 +115,7 @@ where TBackend: OutputManagerBackend + 'static
 {
     config: OutputManagerServiceConfig,
     key_manager: Mutex<KeyManager<PrivateKey, KeyDigest>>,
+    accounts: Vec<Account>,
     db: OutputManagerDatabase<TBackend>,
     outbound_message_service: OutboundMessageRequester,
     request_stream:
@@ -90,8 +123,14 @@ where TBackend: OutputManagerBackend + 'static
     base_node_response_stream: Option<BNResponseStream>,
     factories: CryptoFactories,
     base_node_public_key: Option<CommsPublicKey>,
+    secondary_base_node_public_key: Option<CommsPublicKey>,
     pending_utxo_query_keys: HashMap<u64, Vec<Vec<u8>>>,
+    paired_utxo_query_keys: HashMap<u64, u64>,
+    cross_check_responses: HashMap<u64, Vec<Vec<u8>>>,
+    pending_scan_query_keys: HashMap<u64, ScanQuery>,
+    chain_tip_height: Option<u64>,
     event_publisher: Publisher<OutputManagerEvent>,
+    node_identity: Arc<NodeIdentity>,
 }
 
 impl<TBackend, BNResponseStream> OutputManagerService<TBackend, BNResponseStream>
@@ -110,9 +149,11 @@ where
         db: OutputManagerDatabase<TBackend>,
         event_publisher: Publisher<OutputManagerEvent>,
         factories: CryptoFactories,
+        node_identity: Arc<NodeIdentity>,
     ) -> Result<OutputManagerService<TBackend, BNResponseStream>, OutputManagerError>
     {
-        // Check to see if there is any persisted state, otherwise start fresh
+        // Check to see if there is any persisted state, otherwise start fresh. A watch-only wallet never uses its key
+        // manager so a throwaway seed is not persisted for it.
         let key_manager_state = match db.get_key_manager_state().await? {
             None => {
                 let starting_state = KeyManagerState {
@@ -120,7 +161,9 @@ where
                     branch_seed: "".to_string(),
                     primary_key_index: 0,
                 };
-                db.set_key_manager_state(starting_state.clone()).await?;
+                if !config.watch_only {
+                    db.set_key_manager_state(starting_state.clone()).await?;
+                }
                 starting_state
             },
             Some(km) => km,
@@ -130,6 +173,8 @@ where
         // Pending Transactions.
         db.clear_short_term_encumberances().await?;
 
+        let accounts = db.get_accounts().await?;
+
         Ok(OutputManagerService {
             config,
             outbound_message_service,
@@ -138,13 +183,20 @@ where
                 key_manager_state.branch_seed,
                 key_manager_state.primary_key_index,
             )),
+            accounts,
             db,
             request_stream: Some(request_stream),
             base_node_response_stream: Some(base_node_response_stream),
             factories,
             base_node_public_key: None,
+            secondary_base_node_public_key: None,
             pending_utxo_query_keys: HashMap::new(),
+            paired_utxo_query_keys: HashMap::new(),
+            cross_check_responses: HashMap::new(),
+            pending_scan_query_keys: HashMap::new(),
+            chain_tip_height: None,
             event_publisher,
+            node_identity,
         })
     }
 
@@ -183,7 +235,7 @@ where
                 msg = base_node_response_stream.select_next_some() => {
                     trace!(target: LOG_TARGET, "Handling Base Node Response");
                     let (origin_public_key, inner_msg) = msg.into_origin_and_inner();
-                    let result = self.handle_base_node_response(inner_msg).await.or_else(|resp| {
+                    let result = self.handle_base_node_response(inner_msg, &mut utxo_query_timeout_futures).await.or_else(|resp| {
                         error!(target: LOG_TARGET, "Error handling base node service response from {}: {:?}", origin_public_key, resp);
                         Err(resp)
                     });
@@ -222,17 +274,36 @@ where
     ) -> Result<OutputManagerResponse, OutputManagerError>
     {
         trace!(target: LOG_TARGET, "Handling Service Request: {}", request);
+        if self.config.watch_only && request.requires_spending_keys() {
+            return Err(OutputManagerError::WatchOnlyWallet);
+        }
         match request {
             OutputManagerRequest::AddOutput(uo) => {
                 self.add_output(uo).await.map(|_| OutputManagerResponse::OutputAdded)
             },
             OutputManagerRequest::GetBalance => self.get_balance().await.map(OutputManagerResponse::Balance),
-            OutputManagerRequest::GetRecipientKey((tx_id, amount)) => self
-                .get_recipient_spending_key(tx_id, amount)
+            OutputManagerRequest::GetRecipientKey((tx_id, amount, maturity)) => self
+                .get_recipient_spending_key(tx_id, amount, maturity)
                 .await
                 .map(OutputManagerResponse::RecipientKeyGenerated),
-            OutputManagerRequest::PrepareToSendTransaction((amount, fee_per_gram, lock_height, message)) => self
-                .prepare_transaction_to_send(amount, fee_per_gram, lock_height, message)
+            OutputManagerRequest::PrepareToSendTransaction((
+                amount,
+                fee_per_gram,
+                lock_height,
+                recipient_maturity,
+                message,
+                selected_inputs,
+                account,
+            )) => self
+                .prepare_transaction_to_send(
+                    amount,
+                    fee_per_gram,
+                    lock_height,
+                    recipient_maturity,
+                    message,
+                    selected_inputs,
+                    account,
+                )
                 .await
                 .map(OutputManagerResponse::TransactionToSend),
             OutputManagerRequest::ConfirmPendingTransaction(tx_id) => self
@@ -272,6 +343,14 @@ where
                 .set_base_node_public_key(pk, utxo_query_timeout_futures)
                 .await
                 .map(|_| OutputManagerResponse::BaseNodePublicKeySet),
+            OutputManagerRequest::SetSecondaryBaseNodePublicKey(pk) => {
+                self.secondary_base_node_public_key = pk;
+                Ok(OutputManagerResponse::SecondaryBaseNodePublicKeySet)
+            },
+            OutputManagerRequest::SetChainTipHeight(height) => {
+                self.chain_tip_height = Some(height);
+                Ok(OutputManagerResponse::ChainTipHeightSet)
+            },
             OutputManagerRequest::SyncWithBaseNode => self
                 .query_unspent_outputs_status(utxo_query_timeout_futures)
                 .await
@@ -284,6 +363,76 @@ where
                 .create_coin_split(amount_per_split, split_count, fee_per_gram, lock_height)
                 .await
                 .map(OutputManagerResponse::Transaction),
+            OutputManagerRequest::GetConsolidationCandidates(fee_per_gram) => self
+                .fetch_consolidation_candidates(fee_per_gram)
+                .await
+                .map(OutputManagerResponse::ConsolidationCandidates),
+            OutputManagerRequest::CreateCoinConsolidation((max_inputs, fee_per_gram, lock_height)) => self
+                .create_coin_consolidation(max_inputs, fee_per_gram, lock_height)
+                .await
+                .map(OutputManagerResponse::Transaction),
+            OutputManagerRequest::CreateOneSidedTransaction((
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                lock_height,
+                message,
+            )) => self
+                .create_one_sided_transaction(dest_pubkey, amount, fee_per_gram, lock_height, message)
+                .await
+                .map(OutputManagerResponse::Transaction),
+            OutputManagerRequest::ScanForOneSidedPayments => self
+                .scan_for_one_sided_payments(utxo_query_timeout_futures)
+                .await
+                .map(OutputManagerResponse::StartedOneSidedPaymentScan),
+            OutputManagerRequest::SetOutputFrozen((commitment, frozen)) => self
+                .set_output_frozen(commitment, frozen)
+                .await
+                .map(|_| OutputManagerResponse::OutputFrozenSet),
+            OutputManagerRequest::SetOutputLabel((commitment, label)) => self
+                .set_output_label(commitment, label)
+                .await
+                .map(|_| OutputManagerResponse::OutputLabelSet),
+            OutputManagerRequest::ClearOutputAnnotation(commitment) => self
+                .clear_output_annotation(commitment)
+                .await
+                .map(|_| OutputManagerResponse::OutputAnnotationCleared),
+            OutputManagerRequest::GetAnnotatedUnspentOutputs => self
+                .fetch_annotated_unspent_outputs()
+                .await
+                .map(OutputManagerResponse::AnnotatedUnspentOutputs),
+            OutputManagerRequest::CreateAccount(name) => self
+                .create_account(name)
+                .await
+                .map(|_| OutputManagerResponse::AccountCreated),
+            OutputManagerRequest::GetAccounts => Ok(OutputManagerResponse::Accounts(self.accounts.clone())),
+            OutputManagerRequest::GetAccountBalance(name) => {
+                self.get_account_balance(name).await.map(OutputManagerResponse::Balance)
+            },
+            OutputManagerRequest::SetReceivingAccount(name) => self
+                .set_receiving_account(name)
+                .await
+                .map(|_| OutputManagerResponse::ReceivingAccountSet),
+            OutputManagerRequest::GetTransactionAccounts => self
+                .db
+                .get_transaction_accounts()
+                .await
+                .map(OutputManagerResponse::TransactionAccounts)
+                .map_err(OutputManagerError::from),
+            OutputManagerRequest::AddWatchedOutput((commitment, features, value, label)) => self
+                .add_watched_output(WatchedOutput::new(commitment, features, value, label))
+                .await
+                .map(|_| OutputManagerResponse::WatchedOutputAdded),
+            OutputManagerRequest::RemoveWatchedOutput(commitment) => self
+                .remove_watched_output(commitment)
+                .await
+                .map(|_| OutputManagerResponse::WatchedOutputRemoved),
+            OutputManagerRequest::GetWatchedOutputs => self
+                .db
+                .get_watched_outputs()
+                .await
+                .map(OutputManagerResponse::WatchedOutputs)
+                .map_err(OutputManagerError::from),
         }
     }
 
@@ -291,17 +440,38 @@ where
     pub async fn handle_base_node_response(
         &mut self,
         response: BaseNodeProto::BaseNodeServiceResponse,
+        utxo_query_timeout_futures: &mut FuturesUnordered<BoxFuture<'static, u64>>,
     ) -> Result<(), OutputManagerError>
     {
         let request_key = response.request_key;
 
-        let response: Vec<tari_core::transactions::proto::types::TransactionOutput> = match response.response {
-            Some(BaseNodeResponseProto::TransactionOutputs(outputs)) => outputs.outputs,
-            _ => {
-                return Ok(());
+        match response.response {
+            Some(BaseNodeResponseProto::TransactionOutputs(outputs)) => {
+                self.handle_utxo_query_response(request_key, outputs.outputs).await
             },
-        };
+            Some(BaseNodeResponseProto::ChainMetadata(metadata)) => {
+                self.handle_scan_chain_metadata_response(
+                    request_key,
+                    metadata.height_of_longest_chain,
+                    utxo_query_timeout_futures,
+                )
+                .await
+            },
+            Some(BaseNodeResponseProto::HistoricalBlocks(blocks)) => {
+                self.handle_scan_blocks_response(request_key, blocks.blocks, utxo_query_timeout_futures)
+                    .await
+            },
+            _ => Ok(()),
+        }
+    }
 
+    /// Handle the response to a UTXO query, invalidating any unspent outputs that the base node no longer reports
+    async fn handle_utxo_query_response(
+        &mut self,
+        request_key: u64,
+        response: Vec<tari_core::transactions::proto::types::TransactionOutput>,
+    ) -> Result<(), OutputManagerError>
+    {
         // Only process requests with a request_key that we are expecting.
         let queried_hashes: Vec<Vec<u8>> = match self.pending_utxo_query_keys.remove(&request_key) {
             None => {
@@ -320,6 +490,33 @@ where
             "Handling a Base Node Response meant for this service"
         );
 
+        let mut returned_hashes = Vec::new();
+        for output in response.iter() {
+            returned_hashes.push(
+                TransactionOutput::try_from(output.clone())
+                    .map_err(OutputManagerError::ConversionError)?
+                    .hash(),
+            );
+        }
+
+        // If this query was cross-checked with a second base node wait for both responses and only consider outputs
+        // that neither base node returned
+        if let Some(paired_key) = self.paired_utxo_query_keys.remove(&request_key) {
+            match self.cross_check_responses.remove(&request_key) {
+                None => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Waiting for the response to cross-check query {} before handling query {}",
+                        paired_key,
+                        request_key
+                    );
+                    self.cross_check_responses.insert(paired_key, returned_hashes);
+                    return Ok(());
+                },
+                Some(paired_hashes) => returned_hashes.extend(paired_hashes),
+            }
+        }
+
         // Construct a HashMap of all the unspent outputs
         let unspent_outputs: Vec<UnblindedOutput> = self.db.get_unspent_outputs().await?;
 
@@ -332,12 +529,8 @@ where
         }
 
         // Go through all the returned UTXOs and if they are in the hashmap remove them
-        for output in response.iter() {
-            let response_hash = TransactionOutput::try_from(output.clone())
-                .map_err(OutputManagerError::ConversionError)?
-                .hash();
-
-            let _ = output_hashes.remove(&response_hash);
+        for response_hash in returned_hashes.iter() {
+            let _ = output_hashes.remove(response_hash);
         }
 
         // If there are any remaining Unspent Outputs we will move them to the invalid collection
@@ -349,6 +542,9 @@ where
             self.db.invalidate_output(v).await?;
         }
 
+        self.update_watched_output_statuses(&queried_hashes, &returned_hashes)
+            .await?;
+
         debug!(
             target: LOG_TARGET,
             "Handled Base Node response for Query {}", request_key
@@ -377,8 +573,35 @@ where
         utxo_query_timeout_futures: &mut FuturesUnordered<BoxFuture<'static, u64>>,
     ) -> Result<(), OutputManagerError>
     {
+        if self.pending_scan_query_keys.remove(&query_key).is_some() {
+            warn!(
+                target: LOG_TARGET,
+                "One-sided payment scan query {} timed out", query_key
+            );
+            let _ = self
+                .event_publisher
+                .send(OutputManagerEvent::OneSidedPaymentScanRequestTimedOut(query_key))
+                .await
+                .map_err(|e| {
+                    trace!(
+                        target: LOG_TARGET,
+                        "Error sending event, usually because there are no subscribers: {:?}",
+                        e
+                    );
+                    e
+                });
+            return Ok(());
+        }
+
         if self.pending_utxo_query_keys.remove(&query_key).is_some() {
             error!(target: LOG_TARGET, "UTXO Query {} timed out", query_key);
+            // A cross-checked query is repeated as a whole so drop what is known about the other half of the pair
+            if let Some(paired_key) = self.paired_utxo_query_keys.remove(&query_key) {
+                let _ = self.paired_utxo_query_keys.remove(&paired_key);
+                let _ = self.pending_utxo_query_keys.remove(&paired_key);
+                let _ = self.cross_check_responses.remove(&paired_key);
+            }
+            let _ = self.cross_check_responses.remove(&query_key);
             self.query_unspent_outputs_status(utxo_query_timeout_futures).await?;
             // TODO Remove this once this bug is fixed
             trace!(target: LOG_TARGET, "Finished queueing new Base Node query timeout");
@@ -414,6 +637,12 @@ where
                     let hash = uo.as_transaction_output(&self.factories)?.hash();
                     output_hashes.push(hash.clone());
                 }
+                // Watched outputs that have not been seen to be spent are queried along with our own
+                for wo in self.db.get_watched_outputs().await?.iter() {
+                    if wo.status != WatchedOutputStatus::Spent {
+                        output_hashes.push(wo.hash());
+                    }
+                }
 
                 let request_key = OsRng.next_u64();
 
@@ -435,9 +664,37 @@ where
                     .await?;
                 // TODO Remove this once this bug is fixed
                 trace!(target: LOG_TARGET, "Query sent to Base Node");
-                self.pending_utxo_query_keys.insert(request_key, output_hashes);
+                self.pending_utxo_query_keys.insert(request_key, output_hashes.clone());
                 let state_timeout = StateDelay::new(self.config.base_node_query_timeout, request_key);
                 utxo_query_timeout_futures.push(state_timeout.delay().boxed());
+
+                if let Some(secondary_pk) = self.secondary_base_node_public_key.clone() {
+                    let cross_check_key = OsRng.next_u64();
+                    let service_request = BaseNodeProto::BaseNodeServiceRequest {
+                        request_key: cross_check_key,
+                        request: Some(BaseNodeRequestProto::FetchUtxos(BaseNodeProto::HashOutputs {
+                            outputs: output_hashes.clone(),
+                        })),
+                    };
+                    self.outbound_message_service
+                        .send_direct(
+                            secondary_pk,
+                            OutboundEncryption::None,
+                            OutboundDomainMessage::new(TariMessageType::BaseNodeRequest, service_request),
+                        )
+                        .await?;
+                    self.pending_utxo_query_keys.insert(cross_check_key, output_hashes);
+                    self.paired_utxo_query_keys.insert(request_key, cross_check_key);
+                    self.paired_utxo_query_keys.insert(cross_check_key, request_key);
+                    let state_timeout = StateDelay::new(self.config.base_node_query_timeout, cross_check_key);
+                    utxo_query_timeout_futures.push(state_timeout.delay().boxed());
+                    debug!(
+                        target: LOG_TARGET,
+                        "Output Manager Sync query ({}) cross-checked with secondary Base Node query ({})",
+                        request_key,
+                        cross_check_key
+                    );
+                }
                 debug!(
                     target: LOG_TARGET,
                     "Output Manager Sync query ({}) sent to Base Node", request_key
@@ -447,34 +704,343 @@ where
         }
     }
 
+    /// Start a scan of the blocks mined since the last scan for one-sided payments made to this wallet. The scan starts
+    /// by requesting the current chain metadata from the base node so that the range of blocks to fetch is known.
+    pub async fn scan_for_one_sided_payments(
+        &mut self,
+        utxo_query_timeout_futures: &mut FuturesUnordered<BoxFuture<'static, u64>>,
+    ) -> Result<u64, OutputManagerError>
+    {
+        let request_key = OsRng.next_u64();
+        self.send_scan_query(
+            request_key,
+            BaseNodeRequestProto::GetChainMetadata(true),
+            ScanQuery::ChainMetadata,
+            utxo_query_timeout_futures,
+        )
+        .await?;
+        debug!(
+            target: LOG_TARGET,
+            "One-sided payment scan ({}) started with Base Node", request_key
+        );
+        Ok(request_key)
+    }
+
+    /// Send a query that forms part of a one-sided payment scan to the base node and start its timeout
+    async fn send_scan_query(
+        &mut self,
+        request_key: u64,
+        request: BaseNodeRequestProto,
+        query: ScanQuery,
+        utxo_query_timeout_futures: &mut FuturesUnordered<BoxFuture<'static, u64>>,
+    ) -> Result<(), OutputManagerError>
+    {
+        let pk = self
+            .base_node_public_key
+            .clone()
+            .ok_or(OutputManagerError::NoBaseNodeKeysProvided)?;
+        let service_request = BaseNodeProto::BaseNodeServiceRequest {
+            request_key,
+            request: Some(request),
+        };
+        self.outbound_message_service
+            .send_direct(
+                pk,
+                OutboundEncryption::None,
+                OutboundDomainMessage::new(TariMessageType::BaseNodeRequest, service_request),
+            )
+            .await?;
+        self.pending_scan_query_keys.insert(request_key, query);
+        let state_timeout = StateDelay::new(self.config.base_node_query_timeout, request_key);
+        utxo_query_timeout_futures.push(state_timeout.delay().boxed());
+        Ok(())
+    }
+
+    /// Request the next batch of blocks to be scanned, if the scan has not yet reached the chain tip
+    async fn request_next_scan_blocks(
+        &mut self,
+        tip_height: u64,
+        utxo_query_timeout_futures: &mut FuturesUnordered<BoxFuture<'static, u64>>,
+    ) -> Result<(), OutputManagerError>
+    {
+        let start_height = match self.db.get_last_scanned_block().await? {
+            None => 0,
+            Some(b) => b.height + 1,
+        };
+        if start_height > tip_height {
+            trace!(
+                target: LOG_TARGET,
+                "One-sided payment scan is up to date at height {}",
+                tip_height
+            );
+            return Ok(());
+        }
+        let end_height = min(start_height + ONE_SIDED_PAYMENT_SCAN_BATCH_SIZE - 1, tip_height);
+        let heights: Vec<u64> = (start_height..=end_height).collect();
+        let request_key = OsRng.next_u64();
+        self.send_scan_query(
+            request_key,
+            BaseNodeRequestProto::FetchBlocks(BaseNodeProto::BlockHeights { heights }),
+            ScanQuery::Blocks { tip_height },
+            utxo_query_timeout_futures,
+        )
+        .await?;
+        debug!(
+            target: LOG_TARGET,
+            "Requested blocks {} to {} from Base Node for one-sided payment scan ({})",
+            start_height,
+            end_height,
+            request_key
+        );
+        Ok(())
+    }
+
+    /// Handle the chain metadata returned at the start of a one-sided payment scan
+    async fn handle_scan_chain_metadata_response(
+        &mut self,
+        request_key: u64,
+        tip_height: Option<u64>,
+        utxo_query_timeout_futures: &mut FuturesUnordered<BoxFuture<'static, u64>>,
+    ) -> Result<(), OutputManagerError>
+    {
+        match self.pending_scan_query_keys.remove(&request_key) {
+            Some(ScanQuery::ChainMetadata) => {},
+            Some(query) => {
+                self.pending_scan_query_keys.insert(request_key, query);
+                return Ok(());
+            },
+            None => return Ok(()),
+        }
+
+        match tip_height {
+            None => Ok(()),
+            Some(tip_height) => {
+                self.chain_tip_height = Some(tip_height);
+                self.request_next_scan_blocks(tip_height, utxo_query_timeout_futures)
+                    .await
+            },
+        }
+    }
+
+    /// Scan the blocks returned by the base node for one-sided payments to this wallet. Every payment output that is
+    /// found is swept to a fresh key and a `ReceivedOneSidedPayment` event carrying the sweep transaction is published
+    /// so that the Transaction Service can broadcast it. If the first block does not build on the last scanned block
+    /// the scanned chain was reorged, so the scan is rewound and the recent blocks are scanned again.
+    async fn handle_scan_blocks_response(
+        &mut self,
+        request_key: u64,
+        blocks: Vec<HistoricalBlock>,
+        utxo_query_timeout_futures: &mut FuturesUnordered<BoxFuture<'static, u64>>,
+    ) -> Result<(), OutputManagerError>
+    {
+        let tip_height = match self.pending_scan_query_keys.remove(&request_key) {
+            Some(ScanQuery::Blocks { tip_height }) => tip_height,
+            Some(query) => {
+                self.pending_scan_query_keys.insert(request_key, query);
+                return Ok(());
+            },
+            None => return Ok(()),
+        };
+
+        let mut blocks = blocks
+            .into_iter()
+            .filter_map(|b| b.block)
+            .map(Block::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(OutputManagerError::ConversionError)?;
+        blocks.sort_by_key(|b| b.header.height);
+
+        let scan_start = self.db.get_last_scanned_block().await?;
+        let mut last_scanned = scan_start.clone();
+        for block in blocks {
+            let expected_height = last_scanned.as_ref().map(|b| b.height + 1).unwrap_or(0);
+            if block.header.height != expected_height {
+                debug!(
+                    target: LOG_TARGET,
+                    "Base Node returned block {} when block {} was expected, stopping this scan batch",
+                    block.header.height,
+                    expected_height
+                );
+                break;
+            }
+            if let Some(ScannedBlock {
+                height,
+                hash: Some(hash),
+            }) = last_scanned.as_ref()
+            {
+                if block.header.prev_hash != *hash {
+                    let rewind_height = height.saturating_sub(ONE_SIDED_PAYMENT_REORG_RESCAN_DEPTH);
+                    warn!(
+                        target: LOG_TARGET,
+                        "Block {} does not build on the last scanned block, rescanning one-sided payments from height \
+                         {}",
+                        block.header.height,
+                        rewind_height + 1
+                    );
+                    last_scanned = Some(ScannedBlock {
+                        height: rewind_height,
+                        hash: None,
+                    });
+                    break;
+                }
+            }
+
+            for kernel in block.body.kernels().iter() {
+                let output = match scan_kernel_for_one_sided_payment(
+                    self.node_identity.secret_key(),
+                    kernel,
+                    block.body.outputs(),
+                    &self.factories,
+                ) {
+                    None => continue,
+                    Some(o) => o,
+                };
+                match self.db.add_unspent_output(output.clone()).await {
+                    Ok(_) => {},
+                    Err(OutputManagerStorageError::DuplicateOutput) => continue,
+                    Err(e) => return Err(e.into()),
+                }
+                let value = output.value;
+                let (tx_id, tx, fee) = match self.sweep_one_sided_payment(output).await {
+                    Ok(Some(sweep)) => sweep,
+                    Ok(None) => {
+                        warn!(
+                            target: LOG_TARGET,
+                            "Received a one-sided payment of {} which is too small to sweep, it remains spendable by \
+                             its sender",
+                            value
+                        );
+                        continue;
+                    },
+                    Err(e) => {
+                        error!(
+                            target: LOG_TARGET,
+                            "Could not sweep a received one-sided payment of {}: {:?}", value, e
+                        );
+                        continue;
+                    },
+                };
+                info!(
+                    target: LOG_TARGET,
+                    "Received one-sided payment of {}, sweeping it with transaction (TxId: {})", value, tx_id
+                );
+                let _ = self
+                    .event_publisher
+                    .send(OutputManagerEvent::ReceivedOneSidedPayment((tx_id, tx, fee, value)))
+                    .await
+                    .map_err(|e| {
+                        trace!(
+                            target: LOG_TARGET,
+                            "Error sending event, usually because there are no subscribers: {:?}",
+                            e
+                        );
+                        e
+                    });
+            }
+
+            last_scanned = Some(ScannedBlock {
+                height: block.header.height,
+                hash: Some(block.hash()),
+            });
+        }
+
+        if last_scanned == scan_start {
+            debug!(
+                target: LOG_TARGET,
+                "One-sided payment scan ({}) made no progress, waiting for the next scan", request_key
+            );
+            return Ok(());
+        }
+        if let Some(block) = last_scanned {
+            self.db.set_last_scanned_block(block).await?;
+        }
+        self.request_next_scan_blocks(tip_height, utxo_query_timeout_futures)
+            .await
+    }
+
+    /// Spend a received one-sided payment output to a fresh key from the key manager. The sender derived the payment's
+    /// spending key and can spend it too, so the payment only belongs to this wallet once the sweep is mined. Returns
+    /// `None` if the output's value does not cover the sweep fee.
+    async fn sweep_one_sided_payment(
+        &mut self,
+        output: UnblindedOutput,
+    ) -> Result<Option<(TxId, Transaction, MicroTari)>, OutputManagerError>
+    {
+        let fee_per_gram = self.config.one_sided_payment_sweep_fee_per_gram;
+        let fee = Fee::calculate(fee_per_gram, 1, 1, 1);
+        let output_value = match output.value.checked_sub(fee).filter(|v| *v > MicroTari::from(0)) {
+            None => return Ok(None),
+            Some(v) => v,
+        };
+
+        let offset = PrivateKey::random(&mut OsRng);
+        let nonce = PrivateKey::random(&mut OsRng);
+        let mut builder = SenderTransactionProtocol::builder(0);
+        builder
+            .with_lock_height(0)
+            .with_fee_per_gram(fee_per_gram)
+            .with_offset(offset)
+            .with_private_nonce(nonce)
+            .with_input(
+                output.as_transaction_input(&self.factories.commitment, output.clone().features),
+                output.clone(),
+            );
+
+        let mut spend_key = PrivateKey::default();
+        {
+            let mut km = acquire_lock!(self.key_manager);
+            spend_key = km.next_key()?.k;
+        }
+        self.db.increment_key_index().await?;
+        let utxo = UnblindedOutput::new(output_value, spend_key, None);
+        builder.with_output(utxo.clone());
+
+        let mut stp = builder
+            .build::<HashDigest>(&self.factories)
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+        let tx_id = stp.get_tx_id()?;
+        self.db.encumber_outputs(tx_id, vec![output], vec![utxo]).await?;
+        self.confirm_encumberance(tx_id).await?;
+        stp.finalize(KernelFeatures::empty(), &self.factories)?;
+        let tx = stp.take_transaction()?;
+        Ok(Some((tx_id, tx, fee)))
+    }
+
     /// Add an unblinded output to the unspent outputs list
     pub async fn add_output(&mut self, output: UnblindedOutput) -> Result<(), OutputManagerError> {
         Ok(self.db.add_unspent_output(output).await?)
     }
 
     pub async fn get_balance(&self) -> Result<Balance, OutputManagerError> {
-        let balance = self.db.get_balance().await?;
+        let balance = if self.config.watch_only {
+            self.db.get_watched_balance(self.chain_tip_height).await?
+        } else {
+            self.db.get_balance(self.chain_tip_height).await?
+        };
         trace!(target: LOG_TARGET, "Balance: {:?}", balance);
+        metrics::set_balance(&balance);
         Ok(balance)
     }
 
-    /// Request a spending key to be used to accept a transaction from a sender.
+    /// Request a spending key to be used to accept a transaction from a sender. The received output will only be
+    /// spendable from the `maturity` block height that the sender asked for. The key is derived from the current
+    /// receiving account so the output and the transaction are assigned to that account.
     pub async fn get_recipient_spending_key(
         &mut self,
         tx_id: TxId,
         amount: MicroTari,
+        maturity: u64,
     ) -> Result<PrivateKey, OutputManagerError>
     {
-        let mut key = PrivateKey::default();
-        {
-            let mut km = acquire_lock!(self.key_manager);
-            key = km.next_key()?.k;
-        }
+        let account = self.receiving_account();
+        let key = self.next_account_key(&account).await?;
 
-        self.db.increment_key_index().await?;
         self.db
-            .accept_incoming_pending_transaction(tx_id, amount, key.clone(), OutputFeatures::default())
+            .accept_incoming_pending_transaction(tx_id, amount, key.clone(), OutputFeatures::with_maturity(maturity))
             .await?;
+        if account != DEFAULT_ACCOUNT {
+            self.db.set_transaction_account(tx_id, account).await?;
+        }
 
         self.confirm_encumberance(tx_id).await?;
         Ok(key)
@@ -540,17 +1106,35 @@ where
     }
 
     /// Prepare a Sender Transaction Protocol for the amount and fee_per_gram specified. If required a change output
-    /// will be produced.
+    /// will be produced. If `selected_inputs` is provided exactly those unspent outputs will be spent, otherwise the
+    /// inputs are chosen from the outputs that are not frozen. The transaction cannot be mined before `lock_height`
+    /// and the recipient is asked to make their output spendable only from the `recipient_maturity` block height.
+    /// Only outputs belonging to `account` are spent and the change is returned to it.
     pub async fn prepare_transaction_to_send(
         &mut self,
         amount: MicroTari,
         fee_per_gram: MicroTari,
         lock_height: Option<u64>,
+        recipient_maturity: u64,
         message: String,
+        selected_inputs: Option<Vec<Commitment>>,
+        account: String,
     ) -> Result<SenderTransactionProtocol, OutputManagerError>
     {
-        let (outputs, _) = self.select_utxos(amount, fee_per_gram, 1, None).await?;
-        let total = outputs.iter().fold(MicroTari::from(0), |acc, x| acc + x.value);
+        self.check_account_exists(&account)?;
+        let (outputs, require_change_output) = match selected_inputs {
+            Some(commitments) => {
+                let selection = self
+                    .select_specified_utxos(amount, fee_per_gram, 1, commitments)
+                    .await?;
+                let owners = self.db.get_output_accounts().await?;
+                if selection.0.iter().any(|o| output_account(&owners, o) != account) {
+                    return Err(OutputManagerError::UnspentOutputNotFound);
+                }
+                selection
+            },
+            None => self.select_utxos(amount, fee_per_gram, 1, None, &account).await?,
+        };
 
         let offset = PrivateKey::random(&mut OsRng);
         let nonce = PrivateKey::random(&mut OsRng);
@@ -558,6 +1142,7 @@ where
         let mut builder = SenderTransactionProtocol::builder(1);
         builder
             .with_lock_height(lock_height.unwrap_or(0))
+            .with_recipient_maturity(recipient_maturity)
             .with_fee_per_gram(fee_per_gram)
             .with_offset(offset.clone())
             .with_private_nonce(nonce.clone())
@@ -571,17 +1156,11 @@ where
             );
         }
 
-        let fee_without_change = Fee::calculate(fee_per_gram, 1, outputs.len(), 1);
         let mut change_key: Option<PrivateKey> = None;
-        // If the input values > the amount to be sent + fees_without_change then we will need to include a change
-        // output
-        if total > amount + fee_without_change {
-            let mut key = PrivateKey::default();
-            {
-                let mut km = acquire_lock!(self.key_manager);
-                key = km.next_key()?.k;
-            }
-            self.db.increment_key_index().await?;
+        // If the selected inputs pay for more than the amount to be sent and the fees then we will need to include a
+        // change output. A change-free selection may overshoot slightly, in which case the excess goes to the fee.
+        if require_change_output {
+            let key = self.next_account_key(&account).await?;
             change_key = Some(key.clone());
             builder.with_change_secret(key);
         }
@@ -602,9 +1181,11 @@ where
 
         // The Transaction Protocol built successfully so we will pull the unspent outputs out of the unspent list and
         // store them until the transaction times out OR is confirmed
-        self.db
-            .encumber_outputs(stp.get_tx_id()?, outputs, change_output)
-            .await?;
+        let tx_id = stp.get_tx_id()?;
+        self.db.encumber_outputs(tx_id, outputs, change_output).await?;
+        if account != DEFAULT_ACCOUNT {
+            self.db.set_transaction_account(tx_id, account).await?;
+        }
 
         Ok(stp)
     }
@@ -677,41 +1258,25 @@ where
     }
 
     /// Select which unspent transaction outputs to use to send a transaction of the specified amount. Use the specified
-    /// selection strategy to choose the outputs. It also determines if a change output is required.
+    /// selection strategy to choose the outputs, or branch-and-bound if none is specified. Only outputs of `account`
+    /// are considered and frozen outputs and outputs that have not matured at the current chain tip are never
+    /// selected. It also determines if a change output is required.
     async fn select_utxos(
         &mut self,
         amount: MicroTari,
         fee_per_gram: MicroTari,
         output_count: usize,
         strategy: Option<UTXOSelectionStrategy>,
+        account: &str,
     ) -> Result<(Vec<UnblindedOutput>, bool), OutputManagerError>
     {
-        let mut utxos = Vec::new();
-        let mut total = MicroTari::from(0);
-        let mut fee_without_change = MicroTari::from(0);
-        let mut fee_with_change = MicroTari::from(0);
-
-        let uo = self.db.fetch_sorted_unspent_outputs().await?;
-
-        // Heuristic for selecting strategy: Default to MaturityThenSmallest, but if amount >
-        // alpha * largest UTXO, use Largest
-        let strategy = match (strategy, uo.is_empty()) {
-            (Some(s), _) => s,
-            (None, true) => UTXOSelectionStrategy::Smallest,
-            (None, false) => {
-                let largest_utxo = &uo[uo.len() - 1];
-                if amount > largest_utxo.value {
-                    UTXOSelectionStrategy::Largest
-                } else {
-                    UTXOSelectionStrategy::MaturityThenSmallest
-                }
+        let uo = self.fetch_spendable_outputs(account).await?;
+        let target = SelectionTarget::new(amount, fee_per_gram, output_count);
+        let selection = match strategy.unwrap_or(UTXOSelectionStrategy::BranchAndBound) {
+            UTXOSelectionStrategy::BranchAndBound => {
+                utxo_selection::select_utxos(&uo, &target, self.config.dust_threshold)
             },
-        };
-
-        let uo = match strategy {
-            UTXOSelectionStrategy::Smallest => uo,
-            // TODO: We should pass in the current height and group
-            // all funds less than the current height as maturity 0
+            UTXOSelectionStrategy::Smallest => utxo_selection::select_in_order(&uo, &target),
             UTXOSelectionStrategy::MaturityThenSmallest => {
                 let mut new_uo = uo;
                 new_uo.sort_by(|a, b| match a.features.maturity.cmp(&b.features.maturity) {
@@ -719,34 +1284,132 @@ where
                     Ordering::Less => Ordering::Less,
                     Ordering::Greater => Ordering::Greater,
                 });
-                new_uo
+                utxo_selection::select_in_order(&new_uo, &target)
+            },
+            UTXOSelectionStrategy::Largest => {
+                let new_uo = uo.into_iter().rev().collect::<Vec<_>>();
+                utxo_selection::select_in_order(&new_uo, &target)
             },
-            UTXOSelectionStrategy::Largest => uo.into_iter().rev().collect(),
         };
 
-        let mut require_change_output = false;
-        for o in uo.iter() {
-            utxos.push(o.clone());
-            total += o.value;
-            // I am assuming that the only output will be the payment output and change if required
-            fee_without_change = Fee::calculate(fee_per_gram, 1, utxos.len(), output_count);
-            if total == amount + fee_without_change {
-                break;
-            }
-            fee_with_change = Fee::calculate(fee_per_gram, 1, utxos.len(), output_count + 1);
-            if total >= amount + fee_with_change {
-                require_change_output = true;
-                break;
+        selection
+            .map(|s| (s.inputs, s.requires_change))
+            .ok_or(OutputManagerError::NotEnoughFunds)
+    }
+
+    /// Fetch the unspent outputs of `account` that may be selected automatically, sorted by value. Frozen outputs and
+    /// outputs that have not matured at the current chain tip are excluded.
+    async fn fetch_spendable_outputs(&self, account: &str) -> Result<Vec<UnblindedOutput>, OutputManagerError> {
+        let annotations = self.db.get_output_annotations().await?;
+        let frozen_keys = annotations
+            .iter()
+            .filter(|a| a.frozen)
+            .map(|a| a.spending_key.to_vec())
+            .collect::<HashSet<_>>();
+        let owners = annotations
+            .into_iter()
+            .filter_map(|a| a.account.map(|name| (a.spending_key.to_vec(), name)))
+            .collect::<HashMap<_, _>>();
+        let uo = self
+            .db
+            .fetch_sorted_unspent_outputs()
+            .await?
+            .into_iter()
+            .filter(|o| !frozen_keys.contains(&o.spending_key.to_vec()) && output_account(&owners, o) == account)
+            .collect::<Vec<_>>();
+        Ok(utxo_selection::filter_mature(uo, self.chain_tip_height))
+    }
+
+    /// Use exactly the unspent outputs with the specified commitments to send a transaction of the specified amount.
+    /// Frozen outputs may be selected this way. It also determines if a change output is required.
+    async fn select_specified_utxos(
+        &mut self,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        output_count: usize,
+        commitments: Vec<Commitment>,
+    ) -> Result<(Vec<UnblindedOutput>, bool), OutputManagerError>
+    {
+        let mut utxos: Vec<UnblindedOutput> = Vec::with_capacity(commitments.len());
+        for commitment in commitments.iter() {
+            let output = self.find_unspent_output(commitment).await?;
+            if !utxos.contains(&output) {
+                utxos.push(output);
             }
         }
 
-        if (total != amount + fee_without_change) && (total < amount + fee_with_change) {
+        let total = utxos.iter().fold(MicroTari::from(0), |acc, x| acc + x.value);
+        let fee_without_change = Fee::calculate(fee_per_gram, 1, utxos.len(), output_count);
+        if total < amount + fee_without_change {
             return Err(OutputManagerError::NotEnoughFunds);
         }
+        // If the excess cannot pay for a change output it is added to the fee instead, as the transaction builder does
+        let fee_with_change = Fee::calculate(fee_per_gram, 1, utxos.len(), output_count + 1);
+        let require_change_output = total > amount + fee_with_change;
 
         Ok((utxos, require_change_output))
     }
 
+    /// Find the unspent output with the specified commitment
+    async fn find_unspent_output(&self, commitment: &Commitment) -> Result<UnblindedOutput, OutputManagerError> {
+        self.db
+            .fetch_sorted_unspent_outputs()
+            .await?
+            .into_iter()
+            .find(|o| {
+                &o.as_transaction_input(&self.factories.commitment, o.features.clone())
+                    .commitment ==
+                    commitment
+            })
+            .ok_or(OutputManagerError::UnspentOutputNotFound)
+    }
+
+    /// Freeze or unfreeze the unspent output with the specified commitment. Frozen outputs are excluded from automatic
+    /// UTXO selection.
+    pub async fn set_output_frozen(&mut self, commitment: Commitment, frozen: bool) -> Result<(), OutputManagerError> {
+        let mut annotation = self.fetch_output_annotation(&commitment).await?;
+        annotation.frozen = frozen;
+        Ok(self.db.set_output_annotation(annotation).await?)
+    }
+
+    /// Attach a label to the unspent output with the specified commitment, or clear it if `label` is `None`
+    pub async fn set_output_label(
+        &mut self,
+        commitment: Commitment,
+        label: Option<String>,
+    ) -> Result<(), OutputManagerError>
+    {
+        let mut annotation = self.fetch_output_annotation(&commitment).await?;
+        annotation.label = label;
+        Ok(self.db.set_output_annotation(annotation).await?)
+    }
+
+    /// Remove the label of the unspent output with the specified commitment and unfreeze it. The annotation is deleted
+    /// unless it records the account that owns the output.
+    pub async fn clear_output_annotation(&mut self, commitment: Commitment) -> Result<(), OutputManagerError> {
+        let mut annotation = self.fetch_output_annotation(&commitment).await?;
+        if annotation.account.is_some() {
+            annotation.label = None;
+            annotation.frozen = false;
+            return Ok(self.db.set_output_annotation(annotation).await?);
+        }
+        match self.db.remove_output_annotation(annotation.spending_key).await {
+            Ok(_) | Err(OutputManagerStorageError::ValueNotFound(_)) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn fetch_output_annotation(&self, commitment: &Commitment) -> Result<OutputAnnotation, OutputManagerError> {
+        let output = self.find_unspent_output(commitment).await?;
+        Ok(self
+            .db
+            .get_output_annotations()
+            .await?
+            .into_iter()
+            .find(|a| a.spending_key == output.spending_key)
+            .unwrap_or_else(|| OutputAnnotation::new(output.spending_key)))
+    }
+
     /// Set the base node public key to the list that will be used to check the status of UTXO's on the base chain. If
     /// this is the first time the base node public key is set do the UTXO queries.
     async fn set_base_node_public_key(
@@ -779,6 +1442,30 @@ where
         Ok(self.db.fetch_sorted_unspent_outputs().await?)
     }
 
+    pub async fn fetch_annotated_unspent_outputs(&self) -> Result<Vec<AnnotatedOutput>, OutputManagerError> {
+        let annotations = self.db.get_output_annotations().await?;
+        Ok(self
+            .db
+            .fetch_sorted_unspent_outputs()
+            .await?
+            .into_iter()
+            .map(|output| {
+                let annotation = annotations.iter().find(|a| a.spending_key == output.spending_key);
+                AnnotatedOutput {
+                    commitment: output
+                        .as_transaction_input(&self.factories.commitment, output.features.clone())
+                        .commitment,
+                    label: annotation.and_then(|a| a.label.clone()),
+                    frozen: annotation.map(|a| a.frozen).unwrap_or(false),
+                    account: annotation
+                        .and_then(|a| a.account.clone())
+                        .unwrap_or_else(|| DEFAULT_ACCOUNT.to_string()),
+                    output,
+                }
+            })
+            .collect())
+    }
+
     pub async fn fetch_invalid_outputs(&self) -> Result<Vec<UnblindedOutput>, OutputManagerError> {
         Ok(self.db.get_invalid_outputs().await?)
     }
@@ -803,6 +1490,7 @@ where
                 fee_per_gram,
                 output_count,
                 Some(UTXOSelectionStrategy::Largest),
+                DEFAULT_ACCOUNT,
             )
             .await?;
         let utxo_total = inputs.iter().fold(MicroTari::from(0), |acc, x| acc + x.value);
@@ -873,6 +1561,295 @@ where
         Ok((tx_id, tx, fee, utxo_total))
     }
 
+    /// Fetch the spendable outputs of the default account that are worth consolidating at `fee_per_gram`, smallest
+    /// first. Outputs worth no more than the fee of spending them as an input would only reduce the consolidated value
+    /// and are left out.
+    pub async fn fetch_consolidation_candidates(
+        &self,
+        fee_per_gram: MicroTari,
+    ) -> Result<Vec<UnblindedOutput>, OutputManagerError>
+    {
+        let input_fee = Fee::calculate(fee_per_gram, 0, 1, 0);
+        Ok(self
+            .fetch_spendable_outputs(DEFAULT_ACCOUNT)
+            .await?
+            .into_iter()
+            .filter(|o| o.value > input_fee)
+            .collect())
+    }
+
+    /// Merge up to `max_inputs` of the smallest spendable outputs into a single output paid back to this wallet. This
+    /// sweeps dust out of the wallet so that later transactions need fewer inputs and pay lower fees. Outputs worth
+    /// less than the fee of spending them are not merged.
+    pub async fn create_coin_consolidation(
+        &mut self,
+        max_inputs: usize,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+    ) -> Result<(u64, Transaction, MicroTari, MicroTari), OutputManagerError>
+    {
+        let mut inputs = self.fetch_consolidation_candidates(fee_per_gram).await?;
+        inputs.truncate(min(max_inputs, MAX_TRANSACTION_INPUTS));
+        if inputs.len() < 2 {
+            return Err(OutputManagerError::NothingToConsolidate);
+        }
+        let utxo_total = inputs.iter().fold(MicroTari::from(0), |acc, x| acc + x.value);
+        let fee = Fee::calculate(fee_per_gram, 1, inputs.len(), 1);
+        let output_value = utxo_total
+            .checked_sub(fee)
+            .filter(|v| *v > MicroTari::from(0))
+            .ok_or(OutputManagerError::NotEnoughFunds)?;
+
+        trace!(
+            target: LOG_TARGET,
+            "Construct coin consolidation transaction spending {} outputs.",
+            inputs.len()
+        );
+        let offset = PrivateKey::random(&mut OsRng);
+        let nonce = PrivateKey::random(&mut OsRng);
+        let mut builder = SenderTransactionProtocol::builder(0);
+        builder
+            .with_lock_height(lock_height.unwrap_or(0))
+            .with_fee_per_gram(fee_per_gram)
+            .with_offset(offset)
+            .with_private_nonce(nonce);
+        for uo in inputs.iter() {
+            builder.with_input(
+                uo.as_transaction_input(&self.factories.commitment, uo.clone().features),
+                uo.clone(),
+            );
+        }
+
+        let mut spend_key = PrivateKey::default();
+        {
+            let mut km = acquire_lock!(self.key_manager);
+            spend_key = km.next_key()?.k;
+        }
+        self.db.increment_key_index().await?;
+        let utxo = UnblindedOutput::new(output_value, spend_key, None);
+        builder.with_output(utxo.clone());
+
+        let mut stp = builder
+            .build::<HashDigest>(&self.factories)
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+        let tx_id = stp.get_tx_id()?;
+        self.db.encumber_outputs(tx_id, inputs, vec![utxo]).await?;
+        self.confirm_encumberance(tx_id).await?;
+        stp.finalize(KernelFeatures::empty(), &self.factories)?;
+        let tx = stp.take_transaction()?;
+        Ok((tx_id, tx, fee, utxo_total))
+    }
+
+    /// Create a complete transaction paying `amount` to `dest_pubkey` without the recipient's participation. The
+    /// payment output's spending key is derived from a Diffie-Hellman exchange with the recipient's public key and the
+    /// data the recipient needs to recover it is carried in the kernel's `meta_info`.
+    pub async fn create_one_sided_transaction(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+    ) -> Result<(u64, Transaction, MicroTari, MicroTari), OutputManagerError>
+    {
+        let (inputs, require_change_output) = self
+            .select_utxos(amount, fee_per_gram, 1, None, DEFAULT_ACCOUNT)
+            .await?;
+
+        let (spending_key, meta_info) = create_one_sided_payment(&dest_pubkey, amount)?;
+
+        let offset = PrivateKey::random(&mut OsRng);
+        let nonce = PrivateKey::random(&mut OsRng);
+        let mut builder = SenderTransactionProtocol::builder(0);
+        builder
+            .with_lock_height(lock_height.unwrap_or(0))
+            .with_fee_per_gram(fee_per_gram)
+            .with_offset(offset)
+            .with_private_nonce(nonce)
+            .with_message(message)
+            .with_meta_info(meta_info)
+            .with_output(UnblindedOutput::new(amount, spending_key, None));
+
+        for uo in inputs.iter() {
+            builder.with_input(
+                uo.as_transaction_input(&self.factories.commitment, uo.clone().features),
+                uo.clone(),
+            );
+        }
+
+        let mut change_key: Option<PrivateKey> = None;
+        if require_change_output {
+            let mut key = PrivateKey::default();
+            {
+                let mut km = acquire_lock!(self.key_manager);
+                key = km.next_key()?.k;
+            }
+            self.db.increment_key_index().await?;
+            change_key = Some(key.clone());
+            builder.with_change_secret(key);
+        }
+
+        let mut stp = builder
+            .build::<HashDigest>(&self.factories)
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+
+        let mut change_output = Vec::<UnblindedOutput>::new();
+        if let Some(key) = change_key {
+            let change = stp.get_change_amount()?;
+            if change > MicroTari::from(0) {
+                change_output.push(UnblindedOutput::new(change, key, None));
+            }
+        }
+
+        let tx_id = stp.get_tx_id()?;
+        let fee = stp.get_fee_amount()?;
+        self.db.encumber_outputs(tx_id, inputs, change_output).await?;
+        self.confirm_encumberance(tx_id).await?;
+        stp.finalize(KernelFeatures::empty(), &self.factories)?;
+        let tx = stp.take_transaction()?;
+        Ok((tx_id, tx, fee, amount))
+    }
+
+    /// Create a new account whose keys are derived from a branch of the master key named after the account
+    pub async fn create_account(&mut self, name: String) -> Result<(), OutputManagerError> {
+        if name.is_empty() || name == DEFAULT_ACCOUNT || name.chars().any(char::is_control) {
+            return Err(OutputManagerError::InvalidAccountName);
+        }
+        if self.accounts.iter().any(|a| a.name == name) {
+            return Err(OutputManagerError::AccountAlreadyExists);
+        }
+        let account = Account::new(name);
+        self.db.set_account(account.clone()).await?;
+        self.accounts.push(account);
+        Ok(())
+    }
+
+    pub async fn get_account_balance(&self, name: String) -> Result<Balance, OutputManagerError> {
+        self.check_account_exists(&name)?;
+        Ok(self.db.get_account_balance(&name, self.chain_tip_height).await?)
+    }
+
+    /// Assign the outputs of all subsequent incoming transactions to the named account
+    pub async fn set_receiving_account(&mut self, name: String) -> Result<(), OutputManagerError> {
+        self.check_account_exists(&name)?;
+        for account in self.accounts.iter_mut() {
+            let receiving = account.name == name;
+            if account.receiving != receiving {
+                account.receiving = receiving;
+                self.db.set_account(account.clone()).await?;
+            }
+        }
+        Ok(())
+    }
+
+    fn receiving_account(&self) -> String {
+        self.accounts
+            .iter()
+            .find(|a| a.receiving)
+            .map(|a| a.name.clone())
+            .unwrap_or_else(|| DEFAULT_ACCOUNT.to_string())
+    }
+
+    fn check_account_exists(&self, name: &str) -> Result<(), OutputManagerError> {
+        if name == DEFAULT_ACCOUNT || self.accounts.iter().any(|a| a.name == name) {
+            Ok(())
+        } else {
+            Err(OutputManagerError::AccountNotFound)
+        }
+    }
+
+    /// Derive the next spending key of the named account. Keys of accounts other than the default account are
+    /// annotated with the account so that the outputs they unlock are attributed to it.
+    async fn next_account_key(&mut self, account: &str) -> Result<PrivateKey, OutputManagerError> {
+        if account == DEFAULT_ACCOUNT {
+            let key = {
+                let mut km = acquire_lock!(self.key_manager);
+                km.next_key()?.k
+            };
+            self.db.increment_key_index().await?;
+            return Ok(key);
+        }
+
+        let master_key = acquire_lock!(self.key_manager).master_key.clone();
+        let entry = self
+            .accounts
+            .iter_mut()
+            .find(|a| a.name == account)
+            .ok_or(OutputManagerError::AccountNotFound)?;
+        let mut km =
+            KeyManager::<PrivateKey, KeyDigest>::from(master_key, entry.branch_seed.clone(), entry.primary_key_index);
+        let key = km.next_key()?.k;
+        entry.primary_key_index = km.primary_key_index;
+        let updated = entry.clone();
+
+        self.db.set_account(updated).await?;
+        let mut annotation = OutputAnnotation::new(key.clone());
+        annotation.account = Some(account.to_string());
+        self.db.set_output_annotation(annotation).await?;
+        Ok(key)
+    }
+
+    /// Start tracking an output given only its public data. Its status is unknown until the next base node sync.
+    pub async fn add_watched_output(&mut self, output: WatchedOutput) -> Result<(), OutputManagerError> {
+        if self
+            .db
+            .get_watched_outputs()
+            .await?
+            .iter()
+            .any(|o| o.commitment == output.commitment)
+        {
+            return Err(OutputManagerError::DuplicateOutput);
+        }
+        Ok(self.db.set_watched_output(output).await?)
+    }
+
+    pub async fn remove_watched_output(&mut self, commitment: Commitment) -> Result<(), OutputManagerError> {
+        if !self
+            .db
+            .get_watched_outputs()
+            .await?
+            .iter()
+            .any(|o| o.commitment == commitment)
+        {
+            return Err(OutputManagerError::WatchedOutputNotFound);
+        }
+        Ok(self.db.remove_watched_output(commitment).await?)
+    }
+
+    /// Update the status of the watched outputs that were part of a UTXO query. Outputs the base node returned are
+    /// unspent, while outputs that were previously unspent and are no longer returned have been spent.
+    async fn update_watched_output_statuses(
+        &mut self,
+        queried_hashes: &[Vec<u8>],
+        returned_hashes: &[Vec<u8>],
+    ) -> Result<(), OutputManagerError>
+    {
+        for mut wo in self.db.get_watched_outputs().await? {
+            let hash = wo.hash();
+            if !queried_hashes.contains(&hash) {
+                continue;
+            }
+            let status = if returned_hashes.contains(&hash) {
+                WatchedOutputStatus::Unspent
+            } else if wo.status == WatchedOutputStatus::Unspent {
+                WatchedOutputStatus::Spent
+            } else {
+                wo.status
+            };
+            if status != wo.status {
+                debug!(
+                    target: LOG_TARGET,
+                    "Watched output {} is now {:?}",
+                    wo.commitment.to_hex(),
+                    status
+                );
+                wo.status = status;
+                self.db.set_watched_output(wo).await?;
+            }
+        }
+        Ok(())
+    }
+
     /// Return the Seed words for the current Master Key set in the Key Manager
     pub fn get_seed_words(&self) -> Result<Vec<String>, OutputManagerError> {
         Ok(from_secret_key(
@@ -882,9 +1859,17 @@ where
     }
 }
 
+/// The queries that make up a one-sided payment scan that are awaiting a response from the base node
+enum ScanQuery {
+    ChainMetadata,
+    Blocks { tip_height: u64 },
+}
+
 /// Different UTXO selection strategies for choosing which UTXO's are used to fulfill a transaction
-/// TODO Investigate and implement more optimal strategies
 pub enum UTXOSelectionStrategy {
+    // Search for a set of UTXOs that needs no change output, otherwise select the UTXOs that waste the least on fees
+    // without creating dust change. See the `utxo_selection` module.
+    BranchAndBound,
     // Start from the smallest UTXOs and work your way up until the amount is covered. Main benefit
     // is removing small UTXOs from the blockchain, con is that it costs more in fees
     Smallest,
@@ -894,6 +1879,24 @@ pub enum UTXOSelectionStrategy {
     Largest,
 }
 
+/// An unspent output together with its commitment and the coin control data the user has attached to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnotatedOutput {
+    pub output: UnblindedOutput,
+    pub commitment: Commitment,
+    pub label: Option<String>,
+    pub frozen: bool,
+    pub account: String,
+}
+
+/// The account that owns `output` according to the map of annotated output spending keys to account names
+fn output_account<'a>(owners: &'a HashMap<Vec<u8>, String>, output: &UnblindedOutput) -> &'a str {
+    owners
+        .get(&output.spending_key.to_vec())
+        .map(String::as_str)
+        .unwrap_or(DEFAULT_ACCOUNT)
+}
+
 /// This struct holds the detailed balance of the Output Manager Service.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Balance {
@@ -903,6 +1906,9 @@ pub struct Balance {
     pub pending_incoming_balance: MicroTari,
     /// The current balance of funds encumbered in pending outbound transactions that have not been confirmed
     pub pending_outgoing_balance: MicroTari,
+    /// The part of the available balance held in outputs that only mature after the current chain tip. This is `None`
+    /// while the chain tip height is unknown.
+    pub time_locked_balance: Option<MicroTari>,
 }
 
 impl fmt::Display for Balance {
@@ -910,6 +1916,9 @@ impl fmt::Display for Balance {
         writeln!(f, "Available balance: {}", self.available_balance)?;
         writeln!(f, "Pending incoming balance: {}", self.pending_incoming_balance)?;
         write!(f, "Pending outgoing balance: {}", self.pending_outgoing_balance)?;
+        if let Some(time_locked_balance) = self.time_locked_balance {
+            write!(f, "\nTime locked balance: {}", time_locked_balance)?;
+        }
         Ok(())
     }
 }
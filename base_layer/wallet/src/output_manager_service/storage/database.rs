@@ -29,14 +29,22 @@ use std::{
     sync::Arc,
     time::Duration,
 };
-use tari_core::transactions::{
-    tari_amount::MicroTari,
-    transaction::{OutputFeatures, UnblindedOutput},
-    types::{BlindingFactor, PrivateKey},
+use tari_core::{
+    blocks::BlockHash,
+    transactions::{
+        tari_amount::MicroTari,
+        transaction::{OutputFeatures, TransactionOutput, UnblindedOutput},
+        types::{BlindingFactor, Commitment, PrivateKey, RangeProof},
+    },
 };
+use tari_crypto::tari_utilities::{hash::Hashable, ByteArray};
 
 const LOG_TARGET: &str = "wallet::output_manager_service::database";
 
+/// The name of the account that owns every output not explicitly assigned to another account. Its keys are derived
+/// from the branch held in the `KeyManagerState`.
+pub const DEFAULT_ACCOUNT: &str = "default";
+
 /// This trait defines the required behaviour that a storage backend must provide for the Output Manager service.
 /// Data is passed to and from the backend via the [DbKey], [DbValue], and [DbValueKey] enums. If new data types are
 /// required to be supported by the backends then these enums can be updated to reflect this requirement and the trait
@@ -89,6 +97,99 @@ pub struct PendingTransactionOutputs {
     pub timestamp: NaiveDateTime,
 }
 
+/// Coin control data attached to an output by the user. Frozen outputs are never chosen by the automatic UTXO
+/// selection but can still be spent by selecting them explicitly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputAnnotation {
+    pub spending_key: BlindingFactor,
+    pub label: Option<String>,
+    pub frozen: bool,
+    /// The account that owns this output, `None` meaning the default account
+    pub account: Option<String>,
+}
+
+impl OutputAnnotation {
+    pub fn new(spending_key: BlindingFactor) -> Self {
+        Self {
+            spending_key,
+            label: None,
+            frozen: false,
+            account: None,
+        }
+    }
+}
+
+/// A named sub-account of the wallet. Each account derives its keys from its own branch of the wallet's master seed
+/// and so has its own set of outputs, balance and transaction history.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Account {
+    pub name: String,
+    pub branch_seed: String,
+    pub primary_key_index: usize,
+    /// Whether incoming transactions are currently assigned to this account
+    pub receiving: bool,
+}
+
+impl Account {
+    pub fn new(name: String) -> Self {
+        Self {
+            branch_seed: name.clone(),
+            name,
+            primary_key_index: 0,
+            receiving: false,
+        }
+    }
+}
+
+/// The status of a watched output as last reported by the base node
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WatchedOutputStatus {
+    /// The output has not yet been seen in the UTXO set
+    Unconfirmed,
+    Unspent,
+    /// The output was seen in the UTXO set and has since been removed from it
+    Spent,
+}
+
+/// The last block that was scanned for one-sided payments. The hash is compared with the `prev_hash` of the next block
+/// to be scanned so that a reorg of already scanned blocks can be detected.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScannedBlock {
+    pub height: u64,
+    /// `None` if the block was scanned before block hashes were recorded
+    pub hash: Option<BlockHash>,
+}
+
+/// An output that a watch-only wallet tracks using public data only. The commitment and features are enough to find
+/// the output in the base node's UTXO set; the value is supplied by the user as it cannot be recovered from the
+/// commitment.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WatchedOutput {
+    pub commitment: Commitment,
+    pub features: OutputFeatures,
+    pub value: MicroTari,
+    pub label: Option<String>,
+    pub status: WatchedOutputStatus,
+}
+
+impl WatchedOutput {
+    pub fn new(commitment: Commitment, features: OutputFeatures, value: MicroTari, label: Option<String>) -> Self {
+        Self {
+            commitment,
+            features,
+            value,
+            label,
+            status: WatchedOutputStatus::Unconfirmed,
+        }
+    }
+
+    /// The hash the base node indexes this output by. It does not cover the range proof so it can be computed without
+    /// one.
+    pub fn hash(&self) -> Vec<u8> {
+        TransactionOutput::new(self.features.clone(), self.commitment.clone(), RangeProof::default()).hash()
+    }
+}
+
 /// Holds the state of the KeyManager being used by the Output Manager Service
 #[derive(Clone, Debug, PartialEq)]
 pub struct KeyManagerState {
@@ -107,6 +208,13 @@ pub enum DbKey {
     AllPendingTransactionOutputs,
     KeyManagerState,
     InvalidOutputs,
+    LastScannedBlock,
+    OutputAnnotation(BlindingFactor),
+    OutputAnnotations,
+    Accounts,
+    TransactionAccounts,
+    WatchedOutput(Commitment),
+    WatchedOutputs,
 }
 
 #[derive(Debug)]
@@ -119,6 +227,13 @@ pub enum DbValue {
     InvalidOutputs(Vec<UnblindedOutput>),
     AllPendingTransactionOutputs(HashMap<TxId, PendingTransactionOutputs>),
     KeyManagerState(KeyManagerState),
+    LastScannedBlock(ScannedBlock),
+    OutputAnnotation(Box<OutputAnnotation>),
+    OutputAnnotations(Vec<OutputAnnotation>),
+    Accounts(Vec<Account>),
+    TransactionAccounts(HashMap<TxId, String>),
+    WatchedOutput(Box<WatchedOutput>),
+    WatchedOutputs(Vec<WatchedOutput>),
 }
 
 pub enum DbKeyValuePair {
@@ -126,6 +241,11 @@ pub enum DbKeyValuePair {
     UnspentOutput(BlindingFactor, Box<UnblindedOutput>),
     PendingTransactionOutputs(TxId, Box<PendingTransactionOutputs>),
     KeyManagerState(KeyManagerState),
+    LastScannedBlock(ScannedBlock),
+    OutputAnnotation(BlindingFactor, Box<OutputAnnotation>),
+    Account(String, Box<Account>),
+    TransactionAccount(TxId, String),
+    WatchedOutput(Commitment, Box<WatchedOutput>),
 }
 
 pub enum WriteOperation {
@@ -185,6 +305,183 @@ where T: OutputManagerBackend + 'static
         Ok(())
     }
 
+    /// Retrieve the last block that was scanned for one-sided payments, if any
+    pub async fn get_last_scanned_block(&self) -> Result<Option<ScannedBlock>, OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::LastScannedBlock) {
+            Ok(None) => Ok(None),
+            Ok(Some(DbValue::LastScannedBlock(b))) => Ok(Some(b)),
+            Ok(Some(other)) => unexpected_result(DbKey::LastScannedBlock, other),
+            Err(e) => log_error(DbKey::LastScannedBlock, e),
+        })
+        .await
+        .or_else(|err| Err(OutputManagerStorageError::BlockingTaskSpawnError(err.to_string())))
+        .and_then(|inner_result| inner_result)
+    }
+
+    pub async fn set_last_scanned_block(&self, block: ScannedBlock) -> Result<(), OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            db_clone.write(WriteOperation::Insert(DbKeyValuePair::LastScannedBlock(block)))
+        })
+        .await
+        .or_else(|err| Err(OutputManagerStorageError::BlockingTaskSpawnError(err.to_string())))??;
+
+        Ok(())
+    }
+
+    /// Retrieve the coin control data for all outputs that have any
+    pub async fn get_output_annotations(&self) -> Result<Vec<OutputAnnotation>, OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::OutputAnnotations) {
+            Ok(None) => log_error(
+                DbKey::OutputAnnotations,
+                OutputManagerStorageError::UnexpectedResult("Could not retrieve output annotations".to_string()),
+            ),
+            Ok(Some(DbValue::OutputAnnotations(a))) => Ok(a),
+            Ok(Some(other)) => unexpected_result(DbKey::OutputAnnotations, other),
+            Err(e) => log_error(DbKey::OutputAnnotations, e),
+        })
+        .await
+        .or_else(|err| Err(OutputManagerStorageError::BlockingTaskSpawnError(err.to_string())))
+        .and_then(|inner_result| inner_result)
+    }
+
+    /// Insert or replace the coin control data for the output with the annotation's spending key
+    pub async fn set_output_annotation(&self, annotation: OutputAnnotation) -> Result<(), OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            db_clone.write(WriteOperation::Insert(DbKeyValuePair::OutputAnnotation(
+                annotation.spending_key.clone(),
+                Box::new(annotation),
+            )))
+        })
+        .await
+        .or_else(|err| Err(OutputManagerStorageError::BlockingTaskSpawnError(err.to_string())))??;
+
+        Ok(())
+    }
+
+    /// Remove the coin control data for the output with the given spending key
+    pub async fn remove_output_annotation(
+        &self,
+        spending_key: BlindingFactor,
+    ) -> Result<(), OutputManagerStorageError>
+    {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            db_clone.write(WriteOperation::Remove(DbKey::OutputAnnotation(spending_key)))
+        })
+        .await
+        .or_else(|err| Err(OutputManagerStorageError::BlockingTaskSpawnError(err.to_string())))??;
+
+        Ok(())
+    }
+
+    /// Retrieve all the named accounts, excluding the default account
+    pub async fn get_accounts(&self) -> Result<Vec<Account>, OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::Accounts) {
+            Ok(None) => log_error(
+                DbKey::Accounts,
+                OutputManagerStorageError::UnexpectedResult("Could not retrieve accounts".to_string()),
+            ),
+            Ok(Some(DbValue::Accounts(a))) => Ok(a),
+            Ok(Some(other)) => unexpected_result(DbKey::Accounts, other),
+            Err(e) => log_error(DbKey::Accounts, e),
+        })
+        .await
+        .or_else(|err| Err(OutputManagerStorageError::BlockingTaskSpawnError(err.to_string())))
+        .and_then(|inner_result| inner_result)
+    }
+
+    /// Insert or replace the account with the given name
+    pub async fn set_account(&self, account: Account) -> Result<(), OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            db_clone.write(WriteOperation::Insert(DbKeyValuePair::Account(
+                account.name.clone(),
+                Box::new(account),
+            )))
+        })
+        .await
+        .or_else(|err| Err(OutputManagerStorageError::BlockingTaskSpawnError(err.to_string())))??;
+
+        Ok(())
+    }
+
+    /// Retrieve the account each transaction was sent from or received into. Transactions that do not appear belong
+    /// to the default account.
+    pub async fn get_transaction_accounts(&self) -> Result<HashMap<TxId, String>, OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::TransactionAccounts) {
+            Ok(None) => log_error(
+                DbKey::TransactionAccounts,
+                OutputManagerStorageError::UnexpectedResult("Could not retrieve transaction accounts".to_string()),
+            ),
+            Ok(Some(DbValue::TransactionAccounts(a))) => Ok(a),
+            Ok(Some(other)) => unexpected_result(DbKey::TransactionAccounts, other),
+            Err(e) => log_error(DbKey::TransactionAccounts, e),
+        })
+        .await
+        .or_else(|err| Err(OutputManagerStorageError::BlockingTaskSpawnError(err.to_string())))
+        .and_then(|inner_result| inner_result)
+    }
+
+    pub async fn set_transaction_account(&self, tx_id: TxId, account: String) -> Result<(), OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            db_clone.write(WriteOperation::Insert(DbKeyValuePair::TransactionAccount(
+                tx_id, account,
+            )))
+        })
+        .await
+        .or_else(|err| Err(OutputManagerStorageError::BlockingTaskSpawnError(err.to_string())))??;
+
+        Ok(())
+    }
+
+    /// Retrieve all the outputs tracked by a watch-only wallet
+    pub async fn get_watched_outputs(&self) -> Result<Vec<WatchedOutput>, OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::WatchedOutputs) {
+            Ok(None) => log_error(
+                DbKey::WatchedOutputs,
+                OutputManagerStorageError::UnexpectedResult("Could not retrieve watched outputs".to_string()),
+            ),
+            Ok(Some(DbValue::WatchedOutputs(o))) => Ok(o),
+            Ok(Some(other)) => unexpected_result(DbKey::WatchedOutputs, other),
+            Err(e) => log_error(DbKey::WatchedOutputs, e),
+        })
+        .await
+        .or_else(|err| Err(OutputManagerStorageError::BlockingTaskSpawnError(err.to_string())))
+        .and_then(|inner_result| inner_result)
+    }
+
+    /// Insert or replace the watched output with the given commitment
+    pub async fn set_watched_output(&self, output: WatchedOutput) -> Result<(), OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            db_clone.write(WriteOperation::Insert(DbKeyValuePair::WatchedOutput(
+                output.commitment.clone(),
+                Box::new(output),
+            )))
+        })
+        .await
+        .or_else(|err| Err(OutputManagerStorageError::BlockingTaskSpawnError(err.to_string())))??;
+
+        Ok(())
+    }
+
+    pub async fn remove_watched_output(&self, commitment: Commitment) -> Result<(), OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.write(WriteOperation::Remove(DbKey::WatchedOutput(commitment))))
+            .await
+            .or_else(|err| Err(OutputManagerStorageError::BlockingTaskSpawnError(err.to_string())))??;
+
+        Ok(())
+    }
+
     pub async fn increment_key_index(&self) -> Result<(), OutputManagerStorageError> {
         let db_clone = self.db.clone();
         tokio::task::spawn_blocking(move || db_clone.increment_key_index())
@@ -207,7 +504,72 @@ where T: OutputManagerBackend + 'static
         Ok(())
     }
 
-    pub async fn get_balance(&self) -> Result<Balance, OutputManagerStorageError> {
+    /// Calculate the balance of the wallet. If the current chain tip height is known the unspent outputs that only
+    /// mature after it are reported as the time-locked part of the available balance.
+    pub async fn get_balance(&self, chain_tip_height: Option<u64>) -> Result<Balance, OutputManagerStorageError> {
+        self.get_filtered_balance(chain_tip_height, |_| true).await
+    }
+
+    /// Calculate the balance of a watch-only wallet from its watched outputs. Outputs that have not yet been seen in
+    /// the UTXO set are reported as pending incoming.
+    pub async fn get_watched_balance(
+        &self,
+        chain_tip_height: Option<u64>,
+    ) -> Result<Balance, OutputManagerStorageError>
+    {
+        let watched_outputs = self.get_watched_outputs().await?;
+        let sum = |status: WatchedOutputStatus, min_maturity: u64| {
+            watched_outputs
+                .iter()
+                .filter(|o| o.status == status && o.features.maturity >= min_maturity)
+                .fold(MicroTari::from(0), |acc, o| acc + o.value)
+        };
+
+        Ok(Balance {
+            available_balance: sum(WatchedOutputStatus::Unspent, 0),
+            pending_incoming_balance: sum(WatchedOutputStatus::Unconfirmed, 0),
+            pending_outgoing_balance: MicroTari::from(0),
+            time_locked_balance: chain_tip_height.map(|tip| sum(WatchedOutputStatus::Unspent, tip + 1)),
+        })
+    }
+
+    /// Calculate the balance of a single account. Outputs that are not annotated with an account belong to the default
+    /// account.
+    pub async fn get_account_balance(
+        &self,
+        account: &str,
+        chain_tip_height: Option<u64>,
+    ) -> Result<Balance, OutputManagerStorageError>
+    {
+        let owners = self.get_output_accounts().await?;
+        self.get_filtered_balance(chain_tip_height, |o| {
+            owners
+                .get(&o.spending_key.to_vec())
+                .map(String::as_str)
+                .unwrap_or(DEFAULT_ACCOUNT) ==
+                account
+        })
+        .await
+    }
+
+    /// Retrieve the account that owns each output annotated with one, keyed by the output's spending key bytes
+    pub async fn get_output_accounts(&self) -> Result<HashMap<Vec<u8>, String>, OutputManagerStorageError> {
+        Ok(self
+            .get_output_annotations()
+            .await?
+            .into_iter()
+            .filter_map(|a| a.account.map(|account| (a.spending_key.to_vec(), account)))
+            .collect())
+    }
+
+    async fn get_filtered_balance<F>(
+        &self,
+        chain_tip_height: Option<u64>,
+        include: F,
+    ) -> Result<Balance, OutputManagerStorageError>
+    where
+        F: Fn(&UnblindedOutput) -> bool,
+    {
         let db_clone = self.db.clone();
         let db_clone2 = self.db.clone();
 
@@ -230,7 +592,13 @@ where T: OutputManagerBackend + 'static
         .or_else(|err| Err(OutputManagerStorageError::BlockingTaskSpawnError(err.to_string())))??;
         if let DbValue::UnspentOutputs(uo) = unspent_outputs {
             if let DbValue::AllPendingTransactionOutputs(pto) = pending_txs {
+                let uo = uo.into_iter().filter(|x| include(x)).collect::<Vec<_>>();
                 let available_balance = uo.iter().fold(MicroTari::from(0), |acc, x| acc + x.value);
+                let time_locked_balance = chain_tip_height.map(|tip| {
+                    uo.iter()
+                        .filter(|x| x.features.maturity > tip)
+                        .fold(MicroTari::from(0), |acc, x| acc + x.value)
+                });
                 let mut pending_incoming = MicroTari::from(0);
                 let mut pending_outgoing = MicroTari::from(0);
 
@@ -238,10 +606,12 @@ where T: OutputManagerBackend + 'static
                     pending_incoming += v
                         .outputs_to_be_received
                         .iter()
+                        .filter(|x| include(x))
                         .fold(MicroTari::from(0), |acc, x| acc + x.value);
                     pending_outgoing += v
                         .outputs_to_be_spent
                         .iter()
+                        .filter(|x| include(x))
                         .fold(MicroTari::from(0), |acc, x| acc + x.value);
                 }
 
@@ -249,6 +619,7 @@ where T: OutputManagerBackend + 'static
                     available_balance,
                     pending_incoming_balance: pending_incoming,
                     pending_outgoing_balance: pending_outgoing,
+                    time_locked_balance,
                 });
             }
         }
@@ -507,6 +878,13 @@ impl Display for DbKey {
             DbKey::AllPendingTransactionOutputs => f.write_str(&"All Pending Transaction Outputs".to_string()),
             DbKey::KeyManagerState => f.write_str(&"Key Manager State".to_string()),
             DbKey::InvalidOutputs => f.write_str(&"Invalid Outputs Key"),
+            DbKey::LastScannedBlock => f.write_str(&"Last Scanned Block"),
+            DbKey::OutputAnnotation(_) => f.write_str(&"Output Annotation"),
+            DbKey::OutputAnnotations => f.write_str(&"Output Annotations"),
+            DbKey::Accounts => f.write_str(&"Accounts"),
+            DbKey::TransactionAccounts => f.write_str(&"Transaction Accounts"),
+            DbKey::WatchedOutput(_) => f.write_str(&"Watched Output"),
+            DbKey::WatchedOutputs => f.write_str(&"Watched Outputs"),
         }
     }
 }
@@ -522,6 +900,13 @@ impl Display for DbValue {
             DbValue::AllPendingTransactionOutputs(_) => f.write_str("All Pending Transaction Outputs"),
             DbValue::KeyManagerState(_) => f.write_str("Key Manager State"),
             DbValue::InvalidOutputs(_) => f.write_str("Invalid Outputs"),
+            DbValue::LastScannedBlock(_) => f.write_str("Last Scanned Block"),
+            DbValue::OutputAnnotation(_) => f.write_str("Output Annotation"),
+            DbValue::OutputAnnotations(_) => f.write_str("Output Annotations"),
+            DbValue::Accounts(_) => f.write_str("Accounts"),
+            DbValue::TransactionAccounts(_) => f.write_str("Transaction Accounts"),
+            DbValue::WatchedOutput(_) => f.write_str("Watched Output"),
+            DbValue::WatchedOutputs(_) => f.write_str("Watched Outputs"),
         }
     }
 }
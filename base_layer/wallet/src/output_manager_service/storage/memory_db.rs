@@ -23,12 +23,16 @@
 use crate::output_manager_service::{
     error::OutputManagerStorageError,
     storage::database::{
+        Account,
         DbKey,
         DbKeyValuePair,
         DbValue,
         KeyManagerState,
+        OutputAnnotation,
         OutputManagerBackend,
         PendingTransactionOutputs,
+        ScannedBlock,
+        WatchedOutput,
         WriteOperation,
     },
     TxId,
@@ -51,6 +55,11 @@ pub struct InnerDatabase {
     pending_transactions: HashMap<TxId, PendingTransactionOutputs>,
     short_term_pending_transactions: HashMap<TxId, PendingTransactionOutputs>,
     key_manager_state: Option<KeyManagerState>,
+    last_scanned_block: Option<ScannedBlock>,
+    output_annotations: Vec<OutputAnnotation>,
+    accounts: Vec<Account>,
+    transaction_accounts: HashMap<TxId, String>,
+    watched_outputs: Vec<WatchedOutput>,
 }
 
 impl InnerDatabase {
@@ -62,6 +71,11 @@ impl InnerDatabase {
             pending_transactions: HashMap::new(),
             short_term_pending_transactions: Default::default(),
             key_manager_state: None,
+            last_scanned_block: None,
+            output_annotations: Vec::new(),
+            accounts: Vec::new(),
+            transaction_accounts: HashMap::new(),
+            watched_outputs: Vec::new(),
         }
     }
 }
@@ -114,6 +128,21 @@ impl OutputManagerBackend for OutputManagerMemoryDatabase {
                 .as_ref()
                 .map(|km| DbValue::KeyManagerState(km.clone())),
             DbKey::InvalidOutputs => Some(DbValue::InvalidOutputs(db.invalid_outputs.clone())),
+            DbKey::LastScannedBlock => db.last_scanned_block.clone().map(DbValue::LastScannedBlock),
+            DbKey::OutputAnnotation(k) => db
+                .output_annotations
+                .iter()
+                .find(|v| &v.spending_key == k)
+                .map(|v| DbValue::OutputAnnotation(Box::new(v.clone()))),
+            DbKey::OutputAnnotations => Some(DbValue::OutputAnnotations(db.output_annotations.clone())),
+            DbKey::Accounts => Some(DbValue::Accounts(db.accounts.clone())),
+            DbKey::TransactionAccounts => Some(DbValue::TransactionAccounts(db.transaction_accounts.clone())),
+            DbKey::WatchedOutput(c) => db
+                .watched_outputs
+                .iter()
+                .find(|v| &v.commitment == c)
+                .map(|v| DbValue::WatchedOutput(Box::new(v.clone()))),
+            DbKey::WatchedOutputs => Some(DbValue::WatchedOutputs(db.watched_outputs.clone())),
         };
 
         Ok(result)
@@ -143,6 +172,22 @@ impl OutputManagerBackend for OutputManagerMemoryDatabase {
                     db.short_term_pending_transactions.insert(t, *p);
                 },
                 DbKeyValuePair::KeyManagerState(km) => db.key_manager_state = Some(km),
+                DbKeyValuePair::LastScannedBlock(b) => db.last_scanned_block = Some(b),
+                DbKeyValuePair::OutputAnnotation(k, a) => {
+                    db.output_annotations.retain(|v| v.spending_key != k);
+                    db.output_annotations.push(*a);
+                },
+                DbKeyValuePair::Account(name, a) => {
+                    db.accounts.retain(|v| v.name != name);
+                    db.accounts.push(*a);
+                },
+                DbKeyValuePair::TransactionAccount(tx_id, a) => {
+                    db.transaction_accounts.insert(tx_id, a);
+                },
+                DbKeyValuePair::WatchedOutput(c, o) => {
+                    db.watched_outputs.retain(|v| v.commitment != c);
+                    db.watched_outputs.push(*o);
+                },
             },
             WriteOperation::Remove(k) => match k {
                 DbKey::SpentOutput(k) => match db.spent_outputs.iter().position(|v| v.spending_key == k) {
@@ -171,6 +216,25 @@ impl OutputManagerBackend for OutputManagerMemoryDatabase {
                 DbKey::AllPendingTransactionOutputs => return Err(OutputManagerStorageError::OperationNotSupported),
                 DbKey::KeyManagerState => return Err(OutputManagerStorageError::OperationNotSupported),
                 DbKey::InvalidOutputs => return Err(OutputManagerStorageError::OperationNotSupported),
+                DbKey::LastScannedBlock => return Err(OutputManagerStorageError::OperationNotSupported),
+                DbKey::OutputAnnotation(k) => match db.output_annotations.iter().position(|v| v.spending_key == k) {
+                    None => return Err(OutputManagerStorageError::ValueNotFound(DbKey::OutputAnnotation(k))),
+                    Some(pos) => {
+                        return Ok(Some(DbValue::OutputAnnotation(Box::new(
+                            db.output_annotations.remove(pos),
+                        ))));
+                    },
+                },
+                DbKey::OutputAnnotations => return Err(OutputManagerStorageError::OperationNotSupported),
+                DbKey::Accounts => return Err(OutputManagerStorageError::OperationNotSupported),
+                DbKey::TransactionAccounts => return Err(OutputManagerStorageError::OperationNotSupported),
+                DbKey::WatchedOutput(c) => match db.watched_outputs.iter().position(|v| v.commitment == c) {
+                    None => return Err(OutputManagerStorageError::ValueNotFound(DbKey::WatchedOutput(c))),
+                    Some(pos) => {
+                        return Ok(Some(DbValue::WatchedOutput(Box::new(db.watched_outputs.remove(pos)))));
+                    },
+                },
+                DbKey::WatchedOutputs => return Err(OutputManagerStorageError::OperationNotSupported),
             },
         }
         Ok(None)
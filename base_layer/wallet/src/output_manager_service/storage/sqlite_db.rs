@@ -24,17 +24,31 @@ use crate::{
     output_manager_service::{
         error::OutputManagerStorageError,
         storage::database::{
+            Account,
             DbKey,
             DbKeyValuePair,
             DbValue,
             KeyManagerState,
+            OutputAnnotation,
             OutputManagerBackend,
             PendingTransactionOutputs,
+            ScannedBlock,
+            WatchedOutput,
+            WatchedOutputStatus,
             WriteOperation,
         },
         TxId,
     },
-    schema::{key_manager_states, outputs, pending_transaction_outputs},
+    schema::{
+        accounts,
+        key_manager_states,
+        one_sided_payment_scan_state,
+        output_annotations,
+        outputs,
+        pending_transaction_outputs,
+        transaction_accounts,
+        watched_outputs,
+    },
 };
 use chrono::{Duration as ChronoDuration, NaiveDateTime, Utc};
 #[cfg(test)]
@@ -49,7 +63,7 @@ use std::{
 use tari_core::transactions::{
     tari_amount::MicroTari,
     transaction::{OutputFeatures, OutputFlags, UnblindedOutput},
-    types::PrivateKey,
+    types::{Commitment, PrivateKey},
 };
 use tari_crypto::tari_utilities::ByteArray;
 
@@ -137,6 +151,48 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                     .map(|o| UnblindedOutput::try_from(o.clone()))
                     .collect::<Result<Vec<_>, _>>()?,
             )),
+            DbKey::LastScannedBlock => ScanStateSql::get(&(*conn))?.map(|s| DbValue::LastScannedBlock(s.into())),
+            DbKey::OutputAnnotation(k) => match OutputAnnotationSql::find(&k.to_vec(), &(*conn)) {
+                Ok(a) => Some(DbValue::OutputAnnotation(Box::new(OutputAnnotation::try_from(a)?))),
+                Err(e) => {
+                    match e {
+                        OutputManagerStorageError::DieselError(DieselError::NotFound) => (),
+                        e => return Err(e),
+                    };
+                    None
+                },
+            },
+            DbKey::OutputAnnotations => Some(DbValue::OutputAnnotations(
+                OutputAnnotationSql::index(&(*conn))?
+                    .into_iter()
+                    .map(OutputAnnotation::try_from)
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            DbKey::Accounts => Some(DbValue::Accounts(
+                AccountSql::index(&(*conn))?.into_iter().map(Account::from).collect(),
+            )),
+            DbKey::TransactionAccounts => Some(DbValue::TransactionAccounts(
+                TransactionAccountSql::index(&(*conn))?
+                    .into_iter()
+                    .map(|t| (t.tx_id as u64, t.account))
+                    .collect(),
+            )),
+            DbKey::WatchedOutput(c) => match WatchedOutputSql::find(&c.to_vec(), &(*conn)) {
+                Ok(o) => Some(DbValue::WatchedOutput(Box::new(WatchedOutput::try_from(o)?))),
+                Err(e) => {
+                    match e {
+                        OutputManagerStorageError::DieselError(DieselError::NotFound) => (),
+                        e => return Err(e),
+                    };
+                    None
+                },
+            },
+            DbKey::WatchedOutputs => Some(DbValue::WatchedOutputs(
+                WatchedOutputSql::index(&(*conn))?
+                    .into_iter()
+                    .map(WatchedOutput::try_from)
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
         };
 
         Ok(result)
@@ -173,6 +229,15 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                     }
                 },
                 DbKeyValuePair::KeyManagerState(km) => KeyManagerStateSql::set_state(km, &(*conn))?,
+                DbKeyValuePair::LastScannedBlock(b) => ScanStateSql::from(b).set(&(*conn))?,
+                DbKeyValuePair::OutputAnnotation(_, a) => OutputAnnotationSql::from(*a).set(&(*conn))?,
+                DbKeyValuePair::Account(_, a) => AccountSql::from(*a).set(&(*conn))?,
+                DbKeyValuePair::TransactionAccount(tx_id, account) => TransactionAccountSql {
+                    tx_id: tx_id as i64,
+                    account,
+                }
+                .set(&(*conn))?,
+                DbKeyValuePair::WatchedOutput(_, o) => WatchedOutputSql::from(*o).set(&(*conn))?,
             },
             WriteOperation::Remove(k) => match k {
                 DbKey::SpentOutput(s) => match OutputSql::find_status(&s.to_vec(), OutputStatus::Spent, &(*conn)) {
@@ -219,6 +284,37 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                 DbKey::AllPendingTransactionOutputs => return Err(OutputManagerStorageError::OperationNotSupported),
                 DbKey::KeyManagerState => return Err(OutputManagerStorageError::OperationNotSupported),
                 DbKey::InvalidOutputs => {},
+                DbKey::LastScannedBlock => return Err(OutputManagerStorageError::OperationNotSupported),
+                DbKey::OutputAnnotation(k) => match OutputAnnotationSql::find(&k.to_vec(), &(*conn)) {
+                    Ok(a) => {
+                        a.delete(&(*conn))?;
+                        return Ok(Some(DbValue::OutputAnnotation(Box::new(OutputAnnotation::try_from(
+                            a,
+                        )?))));
+                    },
+                    Err(e) => {
+                        match e {
+                            OutputManagerStorageError::DieselError(DieselError::NotFound) => (),
+                            e => return Err(e),
+                        };
+                    },
+                },
+                DbKey::OutputAnnotations => return Err(OutputManagerStorageError::OperationNotSupported),
+                DbKey::Accounts => return Err(OutputManagerStorageError::OperationNotSupported),
+                DbKey::TransactionAccounts => return Err(OutputManagerStorageError::OperationNotSupported),
+                DbKey::WatchedOutput(c) => match WatchedOutputSql::find(&c.to_vec(), &(*conn)) {
+                    Ok(o) => {
+                        o.delete(&(*conn))?;
+                        return Ok(Some(DbValue::WatchedOutput(Box::new(WatchedOutput::try_from(o)?))));
+                    },
+                    Err(e) => {
+                        match e {
+                            OutputManagerStorageError::DieselError(DieselError::NotFound) => (),
+                            e => return Err(e),
+                        };
+                    },
+                },
+                DbKey::WatchedOutputs => return Err(OutputManagerStorageError::OperationNotSupported),
             },
         }
 
@@ -883,11 +979,281 @@ impl From<KeyManagerStateUpdate> for KeyManagerStateUpdateSql {
     }
 }
 
+/// The one-sided payment scan state is stored as a single row
+const SCAN_STATE_ID: i32 = 0;
+
+#[derive(Clone, Debug, Queryable, Insertable)]
+#[table_name = "one_sided_payment_scan_state"]
+struct ScanStateSql {
+    id: i32,
+    last_scanned_block_height: i64,
+    last_scanned_block_hash: Option<Vec<u8>>,
+}
+
+impl ScanStateSql {
+    pub fn get(conn: &SqliteConnection) -> Result<Option<ScanStateSql>, OutputManagerStorageError> {
+        Ok(one_sided_payment_scan_state::table
+            .filter(one_sided_payment_scan_state::id.eq(SCAN_STATE_ID))
+            .first::<ScanStateSql>(conn)
+            .optional()?)
+    }
+
+    pub fn set(&self, conn: &SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        diesel::replace_into(one_sided_payment_scan_state::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+}
+
+impl From<ScannedBlock> for ScanStateSql {
+    fn from(b: ScannedBlock) -> Self {
+        Self {
+            id: SCAN_STATE_ID,
+            last_scanned_block_height: b.height as i64,
+            last_scanned_block_hash: b.hash,
+        }
+    }
+}
+
+impl From<ScanStateSql> for ScannedBlock {
+    fn from(s: ScanStateSql) -> Self {
+        Self {
+            height: s.last_scanned_block_height as u64,
+            hash: s.last_scanned_block_hash,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "output_annotations"]
+struct OutputAnnotationSql {
+    spending_key: Vec<u8>,
+    label: Option<String>,
+    frozen: i32,
+    account: Option<String>,
+}
+
+impl OutputAnnotationSql {
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<OutputAnnotationSql>, OutputManagerStorageError> {
+        Ok(output_annotations::table.load::<OutputAnnotationSql>(conn)?)
+    }
+
+    pub fn find(
+        spending_key: &[u8],
+        conn: &SqliteConnection,
+    ) -> Result<OutputAnnotationSql, OutputManagerStorageError>
+    {
+        Ok(output_annotations::table
+            .filter(output_annotations::spending_key.eq(spending_key))
+            .first::<OutputAnnotationSql>(conn)?)
+    }
+
+    pub fn set(&self, conn: &SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        diesel::replace_into(output_annotations::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn delete(&self, conn: &SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        let num_deleted =
+            diesel::delete(output_annotations::table.filter(output_annotations::spending_key.eq(&self.spending_key)))
+                .execute(conn)?;
+
+        if num_deleted == 0 {
+            return Err(OutputManagerStorageError::ValuesNotFound);
+        }
+
+        Ok(())
+    }
+}
+
+impl From<OutputAnnotation> for OutputAnnotationSql {
+    fn from(a: OutputAnnotation) -> Self {
+        Self {
+            spending_key: a.spending_key.to_vec(),
+            label: a.label,
+            frozen: a.frozen as i32,
+            account: a.account,
+        }
+    }
+}
+
+impl TryFrom<OutputAnnotationSql> for OutputAnnotation {
+    type Error = OutputManagerStorageError;
+
+    fn try_from(a: OutputAnnotationSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            spending_key: PrivateKey::from_vec(&a.spending_key)
+                .map_err(|_| OutputManagerStorageError::ConversionError)?,
+            label: a.label,
+            frozen: a.frozen != 0,
+            account: a.account,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "accounts"]
+struct AccountSql {
+    name: String,
+    branch_seed: String,
+    primary_key_index: i64,
+    receiving: i32,
+}
+
+impl AccountSql {
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<AccountSql>, OutputManagerStorageError> {
+        Ok(accounts::table.load::<AccountSql>(conn)?)
+    }
+
+    pub fn set(&self, conn: &SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        diesel::replace_into(accounts::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+}
+
+impl From<Account> for AccountSql {
+    fn from(a: Account) -> Self {
+        Self {
+            name: a.name,
+            branch_seed: a.branch_seed,
+            primary_key_index: a.primary_key_index as i64,
+            receiving: a.receiving as i32,
+        }
+    }
+}
+
+impl From<AccountSql> for Account {
+    fn from(a: AccountSql) -> Self {
+        Self {
+            name: a.name,
+            branch_seed: a.branch_seed,
+            primary_key_index: a.primary_key_index as usize,
+            receiving: a.receiving != 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "transaction_accounts"]
+struct TransactionAccountSql {
+    tx_id: i64,
+    account: String,
+}
+
+impl TransactionAccountSql {
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<TransactionAccountSql>, OutputManagerStorageError> {
+        Ok(transaction_accounts::table.load::<TransactionAccountSql>(conn)?)
+    }
+
+    pub fn set(&self, conn: &SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        diesel::replace_into(transaction_accounts::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "watched_outputs"]
+struct WatchedOutputSql {
+    commitment: Vec<u8>,
+    value: i64,
+    flags: i32,
+    maturity: i64,
+    label: Option<String>,
+    status: i32,
+}
+
+impl WatchedOutputSql {
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<WatchedOutputSql>, OutputManagerStorageError> {
+        Ok(watched_outputs::table.load::<WatchedOutputSql>(conn)?)
+    }
+
+    pub fn find(commitment: &[u8], conn: &SqliteConnection) -> Result<WatchedOutputSql, OutputManagerStorageError> {
+        Ok(watched_outputs::table
+            .filter(watched_outputs::commitment.eq(commitment))
+            .first::<WatchedOutputSql>(conn)?)
+    }
+
+    pub fn set(&self, conn: &SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        diesel::replace_into(watched_outputs::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn delete(&self, conn: &SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        let num_deleted =
+            diesel::delete(watched_outputs::table.filter(watched_outputs::commitment.eq(&self.commitment)))
+                .execute(conn)?;
+
+        if num_deleted == 0 {
+            return Err(OutputManagerStorageError::ValuesNotFound);
+        }
+
+        Ok(())
+    }
+}
+
+impl From<WatchedOutput> for WatchedOutputSql {
+    fn from(o: WatchedOutput) -> Self {
+        Self {
+            commitment: o.commitment.to_vec(),
+            value: u64::from(o.value) as i64,
+            flags: o.features.flags.bits() as i32,
+            maturity: o.features.maturity as i64,
+            label: o.label,
+            status: match o.status {
+                WatchedOutputStatus::Unconfirmed => 0,
+                WatchedOutputStatus::Unspent => 1,
+                WatchedOutputStatus::Spent => 2,
+            },
+        }
+    }
+}
+
+impl TryFrom<WatchedOutputSql> for WatchedOutput {
+    type Error = OutputManagerStorageError;
+
+    fn try_from(o: WatchedOutputSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            commitment: Commitment::from_bytes(&o.commitment)
+                .map_err(|_| OutputManagerStorageError::ConversionError)?,
+            features: OutputFeatures {
+                flags: OutputFlags::from_bits(o.flags as u8)
+                    .ok_or_else(|| OutputManagerStorageError::ConversionError)?,
+                maturity: o.maturity as u64,
+            },
+            value: MicroTari::from(o.value as u64),
+            label: o.label,
+            status: match o.status {
+                0 => WatchedOutputStatus::Unconfirmed,
+                1 => WatchedOutputStatus::Unspent,
+                2 => WatchedOutputStatus::Spent,
+                _ => return Err(OutputManagerStorageError::ConversionError),
+            },
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::output_manager_service::storage::{
-        database::KeyManagerState,
-        sqlite_db::{KeyManagerStateSql, OutputSql, OutputStatus, PendingTransactionOutputSql, UpdateOutput},
+        database::{KeyManagerState, OutputAnnotation, ScannedBlock},
+        sqlite_db::{
+            KeyManagerStateSql,
+            OutputAnnotationSql,
+            OutputSql,
+            OutputStatus,
+            PendingTransactionOutputSql,
+            ScanStateSql,
+            UpdateOutput,
+        },
     };
     use chrono::{Duration as ChronoDuration, Utc};
     use diesel::{Connection, SqliteConnection};
@@ -1085,4 +1451,84 @@ mod test {
 
         assert_eq!(state3_read.primary_key_index, 2);
     }
+
+    #[test]
+    fn test_scan_state_crud() {
+        let db_name = format!("{}.sqlite3", random_string(8).as_str());
+        let temp_dir = TempDir::new(random_string(8).as_str()).unwrap();
+        let db_folder = temp_dir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}{}", db_folder, db_name);
+
+        embed_migrations!("./migrations");
+        let conn = SqliteConnection::establish(&db_path).unwrap_or_else(|_| panic!("Error connecting to {}", db_path));
+
+        embedded_migrations::run_with_output(&conn, &mut std::io::stdout()).expect("Migration failed");
+
+        assert!(ScanStateSql::get(&conn).unwrap().is_none());
+
+        let block = ScannedBlock { height: 10, hash: None };
+        ScanStateSql::from(block.clone()).set(&conn).unwrap();
+        assert_eq!(ScannedBlock::from(ScanStateSql::get(&conn).unwrap().unwrap()), block);
+
+        let block = ScannedBlock {
+            height: 25,
+            hash: Some(vec![1u8; 32]),
+        };
+        ScanStateSql::from(block.clone()).set(&conn).unwrap();
+        assert_eq!(ScannedBlock::from(ScanStateSql::get(&conn).unwrap().unwrap()), block);
+    }
+
+    #[test]
+    fn test_output_annotation_crud() {
+        let db_name = format!("{}.sqlite3", random_string(8).as_str());
+        let temp_dir = TempDir::new(random_string(8).as_str()).unwrap();
+        let db_folder = temp_dir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}{}", db_folder, db_name);
+
+        embed_migrations!("./migrations");
+        let conn = SqliteConnection::establish(&db_path).unwrap_or_else(|_| panic!("Error connecting to {}", db_path));
+
+        embedded_migrations::run_with_output(&conn, &mut std::io::stdout()).expect("Migration failed");
+
+        assert!(OutputAnnotationSql::index(&conn).unwrap().is_empty());
+
+        let mut annotation1 = OutputAnnotation::new(PrivateKey::random(&mut OsRng));
+        annotation1.label = Some("Treasury".to_string());
+        let mut annotation2 = OutputAnnotation::new(PrivateKey::random(&mut OsRng));
+        annotation2.frozen = true;
+        annotation2.account = Some("hot".to_string());
+
+        OutputAnnotationSql::from(annotation1.clone()).set(&conn).unwrap();
+        OutputAnnotationSql::from(annotation2.clone()).set(&conn).unwrap();
+
+        let annotations = OutputAnnotationSql::index(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|a| OutputAnnotation::try_from(a).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(annotations.len(), 2);
+        assert!(annotations.contains(&annotation1));
+        assert!(annotations.contains(&annotation2));
+
+        annotation1.frozen = true;
+        annotation1.label = None;
+        OutputAnnotationSql::from(annotation1.clone()).set(&conn).unwrap();
+
+        let annotations = OutputAnnotationSql::index(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|a| OutputAnnotation::try_from(a).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(annotations.len(), 2);
+        assert!(annotations.contains(&annotation1));
+
+        let found = OutputAnnotationSql::find(&annotation2.spending_key.to_vec(), &conn).unwrap();
+        assert_eq!(OutputAnnotation::try_from(found.clone()).unwrap(), annotation2);
+        found.delete(&conn).unwrap();
+        assert!(found.delete(&conn).is_err());
+
+        let annotations = OutputAnnotationSql::index(&conn).unwrap();
+        assert_eq!(annotations.len(), 1);
+        assert!(OutputAnnotationSql::find(&annotation2.spending_key.to_vec(), &conn).is_err());
+    }
 }
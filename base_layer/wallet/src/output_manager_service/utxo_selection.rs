@@ -0,0 +1,547 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! UTXO selection for outgoing transactions. The preferred approach is a branch-and-bound search for a set of inputs
+//! that pays for the transaction without needing a change output: a change-free transaction is cheaper and does not
+//! link a new output of the sender to the payment. The search works on the effective value of each output, i.e. its
+//! value less the fee needed to spend it, so outputs that cost more to spend than they are worth are never selected.
+//! If no change-free set exists the candidate that wastes the least value on fees, and then uses the fewest inputs,
+//! is chosen from a few simple orderings, avoiding change outputs below the dust threshold where possible.
+
+use std::cmp::{min, Ordering};
+use tari_core::transactions::{fee::Fee, tari_amount::MicroTari, transaction::UnblindedOutput};
+
+/// The maximum number of nodes the branch-and-bound search will visit before settling for the best set found so far
+pub const BRANCH_AND_BOUND_MAX_TRIES: usize = 100_000;
+
+/// The amount a selection of inputs has to pay for and the shape of the transaction it has to pay the fee of
+#[derive(Clone, Debug, PartialEq)]
+pub struct SelectionTarget {
+    pub amount: MicroTari,
+    pub fee_per_gram: MicroTari,
+    /// The number of outputs in the transaction, excluding a possible change output
+    pub output_count: usize,
+}
+
+/// How a set of inputs funds a `SelectionTarget`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Funding {
+    Insufficient,
+    /// The inputs pay for the transaction without a change output. Any excess is added to the fee.
+    ChangeFree(MicroTari),
+    /// The inputs pay for the transaction and a change output of this value
+    Change(MicroTari),
+}
+
+/// The inputs chosen to fund a transaction and whether a change output must be added to it
+#[derive(Clone, Debug, PartialEq)]
+pub struct UtxoSelection {
+    pub inputs: Vec<UnblindedOutput>,
+    pub requires_change: bool,
+}
+
+impl SelectionTarget {
+    pub fn new(amount: MicroTari, fee_per_gram: MicroTari, output_count: usize) -> Self {
+        Self {
+            amount,
+            fee_per_gram,
+            output_count,
+        }
+    }
+
+    pub fn fee_without_change(&self, num_inputs: usize) -> MicroTari {
+        Fee::calculate(self.fee_per_gram, 1, num_inputs, self.output_count)
+    }
+
+    pub fn fee_with_change(&self, num_inputs: usize) -> MicroTari {
+        Fee::calculate(self.fee_per_gram, 1, num_inputs, self.output_count + 1)
+    }
+
+    /// The fee needed to include a single input in the transaction
+    pub fn fee_per_input(&self) -> MicroTari {
+        Fee::calculate(self.fee_per_gram, 0, 1, 0)
+    }
+
+    /// The most by which a change-free set of inputs may exceed the amount and fee. The excess is given up to the fee,
+    /// so it is capped by the dust threshold, and it has to be less than the fee for a change output as the
+    /// transaction builder will otherwise insist on creating one.
+    pub fn change_free_tolerance(&self, dust_threshold: MicroTari) -> MicroTari {
+        let cost_of_change = u64::from(self.fee_with_change(0)) - u64::from(self.fee_without_change(0));
+        MicroTari::from(min(u64::from(dust_threshold), cost_of_change.saturating_sub(1)))
+    }
+
+    /// Determine how inputs with the given total value fund this target
+    pub fn funding(&self, total: MicroTari, num_inputs: usize, tolerance: MicroTari) -> Funding {
+        let total = u64::from(total);
+        let required = u64::from(self.amount) + u64::from(self.fee_without_change(num_inputs));
+        let required_with_change = u64::from(self.amount) + u64::from(self.fee_with_change(num_inputs));
+        if total >= required_with_change {
+            Funding::Change(MicroTari::from(total - required_with_change))
+        } else if total >= required && total - required <= u64::from(tolerance) {
+            Funding::ChangeFree(MicroTari::from(total - required))
+        } else {
+            Funding::Insufficient
+        }
+    }
+}
+
+impl UtxoSelection {
+    pub fn total(&self) -> MicroTari {
+        self.inputs.iter().map(|o| o.value).sum()
+    }
+
+    /// The value of the inputs that is paid to neither the recipients nor back to the sender as change
+    pub fn waste(&self, target: &SelectionTarget) -> MicroTari {
+        if self.requires_change {
+            target.fee_with_change(self.inputs.len())
+        } else {
+            // The whole excess is added to the fee
+            self.total() - target.amount
+        }
+    }
+}
+
+/// Remove the outputs that cannot be spent yet because their maturity is beyond the current tip of the chain. If the
+/// tip height is not known the outputs are returned unchanged.
+pub fn filter_mature(outputs: Vec<UnblindedOutput>, tip_height: Option<u64>) -> Vec<UnblindedOutput> {
+    match tip_height {
+        None => outputs,
+        Some(height) => outputs.into_iter().filter(|o| o.features.maturity <= height).collect(),
+    }
+}
+
+/// Select outputs in the order given until they pay for the target, either exactly or with a change output. This is
+/// the selection used by the ordering based `UTXOSelectionStrategy`s.
+pub fn select_in_order(outputs: &[UnblindedOutput], target: &SelectionTarget) -> Option<UtxoSelection> {
+    select_prefix(outputs, target, MicroTari::from(0), MicroTari::from(0))
+}
+
+/// Select outputs in the order given until they pay for the target without change, within the tolerance, or with a
+/// change output of at least `min_change`
+fn select_prefix(
+    outputs: &[UnblindedOutput],
+    target: &SelectionTarget,
+    tolerance: MicroTari,
+    min_change: MicroTari,
+) -> Option<UtxoSelection>
+{
+    let mut total = MicroTari::from(0);
+    for (i, o) in outputs.iter().enumerate() {
+        total += o.value;
+        let requires_change = match target.funding(total, i + 1, tolerance) {
+            Funding::Insufficient => continue,
+            Funding::ChangeFree(_) => false,
+            Funding::Change(change) if change >= min_change => true,
+            Funding::Change(_) => continue,
+        };
+        return Some(UtxoSelection {
+            inputs: outputs[..=i].to_vec(),
+            requires_change,
+        });
+    }
+    None
+}
+
+/// Search for a set of outputs that pays for the target without a change output, overshooting the amount and fee by
+/// no more than the change-free tolerance. Of the sets found the one with the smallest excess, and then the fewest
+/// inputs, is returned.
+pub fn select_branch_and_bound(
+    outputs: &[UnblindedOutput],
+    target: &SelectionTarget,
+    dust_threshold: MicroTari,
+) -> Option<UtxoSelection>
+{
+    let fee_per_input = u64::from(target.fee_per_input());
+    let mut candidates = outputs
+        .iter()
+        .filter(|o| u64::from(o.value) > fee_per_input)
+        .collect::<Vec<_>>();
+    candidates.sort_by(|a, b| b.value.cmp(&a.value));
+    let effective_values = candidates
+        .iter()
+        .map(|o| u64::from(o.value) - fee_per_input)
+        .collect::<Vec<_>>();
+
+    let lower = u64::from(target.amount) + u64::from(target.fee_without_change(0));
+    let mut search = BranchAndBound {
+        values: &effective_values,
+        lower,
+        upper: lower + u64::from(target.change_free_tolerance(dust_threshold)),
+        tries: 0,
+        selected: Vec::new(),
+        best: None,
+    };
+    let available = effective_values.iter().sum();
+    search.run(available);
+
+    search.best.map(|(_, indices)| UtxoSelection {
+        inputs: indices.into_iter().map(|i| candidates[i].clone()).collect(),
+        requires_change: false,
+    })
+}
+
+/// Depth first search over the inclusion or exclusion of each value, sorted from largest to smallest
+struct BranchAndBound<'a> {
+    values: &'a [u64],
+    lower: u64,
+    upper: u64,
+    tries: usize,
+    selected: Vec<usize>,
+    best: Option<(u64, Vec<usize>)>,
+}
+
+/// A step of the branch-and-bound search, given the sum of the values selected so far and the sum of the values that
+/// have not been considered yet
+enum SearchStep {
+    /// Consider including the value at the index
+    Include { index: usize, sum: u64, remaining: u64 },
+    /// Backtrack from including the value at the index and consider excluding it instead
+    Exclude { index: usize, sum: u64, remaining: u64 },
+}
+
+impl<'a> BranchAndBound<'a> {
+    /// The search keeps its own stack instead of recursing so that its depth, which can be as large as the number of
+    /// values, is not limited by the size of the thread's stack
+    fn run(&mut self, available: u64) {
+        let mut stack = vec![SearchStep::Include {
+            index: 0,
+            sum: 0,
+            remaining: available,
+        }];
+        while let Some(step) = stack.pop() {
+            match step {
+                SearchStep::Include { index, sum, remaining } => {
+                    if self.tries >= BRANCH_AND_BOUND_MAX_TRIES ||
+                        self.best.as_ref().map(|(e, _)| *e == 0).unwrap_or(false)
+                    {
+                        return;
+                    }
+                    self.tries += 1;
+
+                    if sum > self.upper {
+                        continue;
+                    }
+                    if sum >= self.lower {
+                        let excess = sum - self.lower;
+                        let is_better = match self.best.as_ref() {
+                            None => true,
+                            Some((best_excess, best)) => match excess.cmp(best_excess) {
+                                Ordering::Less => true,
+                                Ordering::Equal => self.selected.len() < best.len(),
+                                Ordering::Greater => false,
+                            },
+                        };
+                        if is_better {
+                            self.best = Some((excess, self.selected.clone()));
+                        }
+                        // Adding more values can only overshoot further
+                        continue;
+                    }
+                    if index >= self.values.len() || sum + remaining < self.lower {
+                        continue;
+                    }
+
+                    let value = self.values[index];
+                    self.selected.push(index);
+                    stack.push(SearchStep::Exclude { index, sum, remaining });
+                    stack.push(SearchStep::Include {
+                        index: index + 1,
+                        sum: sum + value,
+                        remaining: remaining - value,
+                    });
+                },
+                SearchStep::Exclude { index, sum, remaining } => {
+                    self.selected.pop();
+
+                    // Excluding this value and including an equal one further on leads to the same sums, so skip past
+                    // them all
+                    let value = self.values[index];
+                    let mut next = index + 1;
+                    let mut skipped = value;
+                    while next < self.values.len() && self.values[next] == value {
+                        skipped += value;
+                        next += 1;
+                    }
+                    stack.push(SearchStep::Include {
+                        index: next,
+                        sum,
+                        remaining: remaining - skipped,
+                    });
+                },
+            }
+        }
+    }
+}
+
+/// Select the outputs that waste the least value on fees, and then use the fewest inputs, from a few candidate
+/// selections: the largest outputs first, the smallest outputs first and the smallest single output that covers the
+/// target. A candidate may not create change below the dust threshold unless no candidate can avoid it.
+pub fn select_least_waste(
+    outputs: &[UnblindedOutput],
+    target: &SelectionTarget,
+    dust_threshold: MicroTari,
+) -> Option<UtxoSelection>
+{
+    let tolerance = target.change_free_tolerance(dust_threshold);
+    let mut smallest_first = outputs.to_vec();
+    smallest_first.sort_by(|a, b| a.value.cmp(&b.value));
+    let largest_first = smallest_first.iter().rev().cloned().collect::<Vec<_>>();
+
+    let mut candidates = Vec::new();
+    candidates.extend(select_prefix(&largest_first, target, tolerance, dust_threshold));
+    candidates.extend(select_prefix(&smallest_first, target, tolerance, dust_threshold));
+    candidates.extend(
+        smallest_first
+            .iter()
+            .find_map(|o| select_prefix(&[o.clone()], target, tolerance, dust_threshold)),
+    );
+
+    let best = candidates.into_iter().min_by(|a, b| {
+        a.waste(target)
+            .cmp(&b.waste(target))
+            .then(a.inputs.len().cmp(&b.inputs.len()))
+    });
+    if best.is_some() {
+        return best;
+    }
+
+    // Dust change cannot be avoided, fall back to the ordering based selections
+    select_in_order(&largest_first, target).or_else(|| select_in_order(&smallest_first, target))
+}
+
+/// Select the outputs to fund the target: a change-free set found by branch-and-bound if there is one, otherwise the
+/// selection that wastes the least
+pub fn select_utxos(
+    outputs: &[UnblindedOutput],
+    target: &SelectionTarget,
+    dust_threshold: MicroTari,
+) -> Option<UtxoSelection>
+{
+    select_branch_and_bound(outputs, target, dust_threshold)
+        .or_else(|| select_least_waste(outputs, target, dust_threshold))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::output_manager_service::utxo_selection::{
+        filter_mature,
+        select_branch_and_bound,
+        select_in_order,
+        select_least_waste,
+        select_utxos,
+        Funding,
+        SelectionTarget,
+        UtxoSelection,
+    };
+    use rand::{
+        rngs::{OsRng, StdRng},
+        Rng,
+        SeedableRng,
+    };
+    use tari_core::transactions::{
+        tari_amount::MicroTari,
+        transaction::{OutputFeatures, UnblindedOutput},
+        types::PrivateKey,
+    };
+    use tari_crypto::keys::SecretKey;
+
+    /// The number of random cases each property is checked against
+    const CASES: u64 = 300;
+
+    struct Case {
+        outputs: Vec<UnblindedOutput>,
+        target: SelectionTarget,
+        dust_threshold: MicroTari,
+    }
+
+    impl Case {
+        fn random(seed: u64, min_value: u64) -> Self {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let outputs = (0..rng.gen_range(1, 11))
+                .map(|_| {
+                    UnblindedOutput::new(
+                        MicroTari::from(rng.gen_range(min_value, 5_000)),
+                        PrivateKey::random(&mut OsRng),
+                        Some(OutputFeatures::with_maturity(rng.gen_range(0, 20))),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let available = outputs.iter().map(|o| u64::from(o.value)).sum::<u64>();
+            Self {
+                outputs,
+                target: SelectionTarget::new(
+                    MicroTari::from(rng.gen_range(1, available + 1)),
+                    MicroTari::from(rng.gen_range(0, 30)),
+                    rng.gen_range(1, 4),
+                ),
+                dust_threshold: MicroTari::from(rng.gen_range(0, 300)),
+            }
+        }
+
+        fn sorted(&self) -> Vec<UnblindedOutput> {
+            let mut outputs = self.outputs.clone();
+            outputs.sort_by(|a, b| a.value.cmp(&b.value));
+            outputs
+        }
+
+        /// The selections made by the existing ordering based strategies, smallest and largest first
+        fn existing_strategies(&self) -> Vec<UtxoSelection> {
+            let smallest_first = self.sorted();
+            let largest_first = smallest_first.iter().rev().cloned().collect::<Vec<_>>();
+            select_in_order(&smallest_first, &self.target)
+                .into_iter()
+                .chain(select_in_order(&largest_first, &self.target))
+                .collect()
+        }
+
+        fn funding(&self, selection: &UtxoSelection) -> Funding {
+            self.target.funding(
+                selection.total(),
+                selection.inputs.len(),
+                self.target.change_free_tolerance(self.dust_threshold),
+            )
+        }
+
+        fn assert_valid(&self, selection: &UtxoSelection) {
+            for (i, input) in selection.inputs.iter().enumerate() {
+                assert!(self.outputs.contains(input));
+                assert!(!selection.inputs[i + 1..].contains(input));
+            }
+            match self.funding(selection) {
+                Funding::Insufficient => panic!("Selection does not fund the target"),
+                Funding::ChangeFree(_) => assert!(!selection.requires_change),
+                Funding::Change(_) => assert!(selection.requires_change),
+            }
+        }
+    }
+
+    #[test]
+    fn selections_fund_the_target() {
+        for seed in 0..CASES {
+            let case = Case::random(seed, 1);
+            for selection in select_utxos(&case.outputs, &case.target, case.dust_threshold)
+                .into_iter()
+                .chain(select_branch_and_bound(
+                    &case.outputs,
+                    &case.target,
+                    case.dust_threshold,
+                ))
+                .chain(select_least_waste(&case.outputs, &case.target, case.dust_threshold))
+                .chain(case.existing_strategies())
+            {
+                case.assert_valid(&selection);
+            }
+        }
+    }
+
+    #[test]
+    fn never_fails_when_existing_strategies_succeed() {
+        for seed in 0..CASES {
+            let case = Case::random(seed, 1);
+            if !case.existing_strategies().is_empty() {
+                assert!(select_utxos(&case.outputs, &case.target, case.dust_threshold).is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn branch_and_bound_finds_the_best_change_free_set() {
+        for seed in 0..CASES {
+            let case = Case::random(seed, 100);
+            // Exhaustively search for the change-free set with the smallest excess
+            let tolerance = case.target.change_free_tolerance(case.dust_threshold);
+            let mut best_excess = None;
+            for mask in 1u32..(1 << case.outputs.len()) {
+                let subset = case
+                    .outputs
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| mask & (1 << i) != 0)
+                    .map(|(_, o)| o.value)
+                    .collect::<Vec<_>>();
+                let total = subset.iter().sum();
+                if let Funding::ChangeFree(excess) = case.target.funding(total, subset.len(), tolerance) {
+                    best_excess = Some(best_excess.map_or(excess, |e: MicroTari| e.min(excess)));
+                }
+            }
+
+            let selection = select_branch_and_bound(&case.outputs, &case.target, case.dust_threshold);
+            match (best_excess, selection) {
+                (None, None) => {},
+                (Some(excess), Some(selection)) => {
+                    assert!(!selection.requires_change);
+                    assert_eq!(case.funding(&selection), Funding::ChangeFree(excess));
+                },
+                (expected, selection) => panic!("Expected excess {:?} but selected {:?}", expected, selection),
+            }
+        }
+    }
+
+    #[test]
+    fn avoids_dust_change() {
+        for seed in 0..CASES {
+            let case = Case::random(seed, 1);
+            let available = case.outputs.iter().map(|o| o.value).sum::<MicroTari>();
+            let required = case.target.amount + case.target.fee_with_change(case.outputs.len()) + case.dust_threshold;
+            if available < required {
+                continue;
+            }
+            let selection = select_utxos(&case.outputs, &case.target, case.dust_threshold).unwrap();
+            if let Funding::Change(change) = case.funding(&selection) {
+                assert!(change >= case.dust_threshold);
+            }
+        }
+    }
+
+    #[test]
+    fn least_waste_wastes_no_more_than_existing_strategies() {
+        for seed in 0..CASES {
+            let case = Case::random(seed, 1);
+            for existing in case.existing_strategies() {
+                match case.funding(&existing) {
+                    Funding::Change(change) if change >= case.dust_threshold => {},
+                    _ => continue,
+                }
+                let selection = select_least_waste(&case.outputs, &case.target, case.dust_threshold).unwrap();
+                assert!(selection.waste(&case.target) <= existing.waste(&case.target));
+            }
+        }
+    }
+
+    #[test]
+    fn immature_outputs_are_not_selected() {
+        for seed in 0..CASES {
+            let case = Case::random(seed, 1);
+            let tip_height = seed % 20;
+            let mature = filter_mature(case.outputs.clone(), Some(tip_height));
+            assert!(case
+                .outputs
+                .iter()
+                .all(|o| mature.contains(o) == (o.features.maturity <= tip_height)));
+            if let Some(selection) = select_utxos(&mature, &case.target, case.dust_threshold) {
+                assert!(selection.inputs.iter().all(|o| o.features.maturity <= tip_height));
+            }
+        }
+        let case = Case::random(0, 1);
+        assert_eq!(filter_mature(case.outputs.clone(), None), case.outputs);
+    }
+}
@@ -1,3 +1,12 @@
+table! {
+    accounts (name) {
+        name -> Text,
+        branch_seed -> Text,
+        primary_key_index -> BigInt,
+        receiving -> Integer,
+    }
+}
+
 table! {
     coinbase_transactions (tx_id) {
         tx_id -> BigInt,
@@ -18,6 +27,9 @@ table! {
         status -> Integer,
         message -> Text,
         timestamp -> Timestamp,
+        mined_height -> Nullable<BigInt>,
+        mined_block_hash -> Nullable<Binary>,
+        confirmations -> Nullable<BigInt>,
     }
 }
 
@@ -49,6 +61,14 @@ table! {
     }
 }
 
+table! {
+    one_sided_payment_scan_state (id) {
+        id -> Integer,
+        last_scanned_block_height -> BigInt,
+        last_scanned_block_hash -> Nullable<Binary>,
+    }
+}
+
 table! {
     outbound_transactions (tx_id) {
         tx_id -> BigInt,
@@ -61,6 +81,15 @@ table! {
     }
 }
 
+table! {
+    output_annotations (spending_key) {
+        spending_key -> Binary,
+        label -> Nullable<Text>,
+        frozen -> Integer,
+        account -> Nullable<Text>,
+    }
+}
+
 table! {
     outputs (spending_key) {
         spending_key -> Binary,
@@ -76,6 +105,7 @@ table! {
     peers (public_key) {
         public_key -> Binary,
         peer -> Text,
+        rank -> BigInt,
     }
 }
 
@@ -87,14 +117,37 @@ table! {
     }
 }
 
+table! {
+    transaction_accounts (tx_id) {
+        tx_id -> BigInt,
+        account -> Text,
+    }
+}
+
+table! {
+    watched_outputs (commitment) {
+        commitment -> Binary,
+        value -> BigInt,
+        flags -> Integer,
+        maturity -> BigInt,
+        label -> Nullable<Text>,
+        status -> Integer,
+    }
+}
+
 allow_tables_to_appear_in_same_query!(
+    accounts,
     coinbase_transactions,
     completed_transactions,
     contacts,
     inbound_transactions,
     key_manager_states,
+    one_sided_payment_scan_state,
     outbound_transactions,
+    output_annotations,
     outputs,
     peers,
     pending_transaction_outputs,
+    transaction_accounts,
+    watched_outputs,
 );
@@ -247,7 +247,14 @@ mod test {
         }
 
         let _ = runtime.block_on(db.remove_peer(peers[0].public_key.clone())).unwrap();
-        peers.remove(0);
+        let removed_peer = peers.remove(0);
+        let got_peers = runtime.block_on(db.get_peers()).unwrap();
+
+        assert_eq!(peers, got_peers);
+
+        // A peer that is saved again goes to the back of the list
+        runtime.block_on(db.save_peer(removed_peer.clone())).unwrap();
+        peers.push(removed_peer);
         let got_peers = runtime.block_on(db.get_peers()).unwrap();
 
         assert_eq!(peers, got_peers);
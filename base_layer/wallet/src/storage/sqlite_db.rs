@@ -73,7 +73,7 @@ impl WalletBackend for WalletSqliteDatabase {
                     if PeerSql::find(&k.to_vec(), &(*conn)).is_ok() {
                         return Err(WalletStorageError::DuplicateContact);
                     }
-                    PeerSql::try_from(p)?.commit(&conn)?;
+                    PeerSql::new(p, PeerSql::next_rank(&conn)?)?.commit(&conn)?;
                 },
             },
             WriteOperation::Remove(k) => match k {
@@ -99,18 +99,34 @@ impl WalletBackend for WalletSqliteDatabase {
 struct PeerSql {
     public_key: Vec<u8>,
     peer: String,
+    rank: i64,
 }
 
 impl PeerSql {
+    /// Create the Sql form of a Peer at the given position in the wallet's order of preference
+    pub fn new(peer: Peer, rank: i64) -> Result<Self, WalletStorageError> {
+        Ok(Self {
+            public_key: peer.public_key.to_vec(),
+            peer: serde_json::to_string(&peer)?,
+            rank,
+        })
+    }
+
     /// Write this struct to the database
     pub fn commit(&self, conn: &SqliteConnection) -> Result<(), WalletStorageError> {
         diesel::insert_into(peers::table).values(self.clone()).execute(conn)?;
         Ok(())
     }
 
-    /// Return all peers
+    /// Return all peers in the order they were added
     pub fn index(conn: &SqliteConnection) -> Result<Vec<PeerSql>, WalletStorageError> {
-        Ok(peers::table.load::<PeerSql>(conn)?)
+        Ok(peers::table.order(peers::rank.asc()).load::<PeerSql>(conn)?)
+    }
+
+    /// The rank that places a new peer after all of the existing peers
+    pub fn next_rank(conn: &SqliteConnection) -> Result<i64, WalletStorageError> {
+        let max_rank: Option<i64> = peers::table.select(diesel::dsl::max(peers::rank)).first(conn)?;
+        Ok(max_rank.map(|r| r + 1).unwrap_or(0))
     }
 
     /// Find a particular Peer, if it exists
@@ -139,15 +155,3 @@ impl TryFrom<PeerSql> for Peer {
         Ok(serde_json::from_str(&p.peer)?)
     }
 }
-
-/// Conversion from an Contact to the Sql datatype form
-impl TryFrom<Peer> for PeerSql {
-    type Error = WalletStorageError;
-
-    fn try_from(p: Peer) -> Result<Self, Self::Error> {
-        Ok(Self {
-            public_key: p.public_key.to_vec(),
-            peer: serde_json::to_string(&p)?,
-        })
-    }
-}
@@ -146,6 +146,8 @@ pub fn create_wallet(
         comms_config,
         factories,
         transaction_service_config: None,
+        output_manager_service_config: None,
+        base_node_service_config: None,
     };
 
     Wallet::new(
@@ -713,6 +715,9 @@ pub fn complete_sent_transaction<
                 message: p.message.clone(),
                 status: TransactionStatus::Completed,
                 timestamp: Utc::now().naive_utc(),
+                mined_height: None,
+                mined_block_hash: None,
+                confirmations: None,
             };
             wallet.runtime.block_on(
                 wallet
@@ -21,6 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::time::Duration;
+use tari_core::transactions::tari_amount::MicroTari;
 
 #[derive(Clone)]
 pub struct TransactionServiceConfig {
@@ -30,6 +31,18 @@ pub struct TransactionServiceConfig {
     pub mempool_broadcast_timeout: Duration,
     pub initial_base_node_mined_timeout: Duration,
     pub base_node_mined_timeout: Duration,
+    // The number of blocks that must be built on top of a mined transaction before its outputs are spendable. A value
+    // of 0 makes the outputs spendable as soon as the transaction is detected as mined.
+    pub num_confirmations_required: u64,
+    // If set, the wallet's unspent outputs are periodically consolidated according to this policy
+    pub consolidation_policy: Option<ConsolidationPolicy>,
+    // How long to wait for the recipient's reply before the sender message is resent. The wait doubles after every
+    // resend up to `max_transaction_resend_period`.
+    pub transaction_resend_period: Duration,
+    pub max_transaction_resend_period: Duration,
+    // Pending outbound transactions that have not been replied to this long after they were created are cancelled,
+    // their outputs released and the recipient notified
+    pub pending_transaction_cancellation_timeout: Duration,
 }
 
 impl Default for TransactionServiceConfig {
@@ -39,6 +52,44 @@ impl Default for TransactionServiceConfig {
             mempool_broadcast_timeout: Duration::from_secs(30),
             initial_base_node_mined_timeout: Duration::from_secs(5),
             base_node_mined_timeout: Duration::from_secs(30),
+            num_confirmations_required: 3,
+            consolidation_policy: None,
+            transaction_resend_period: Duration::from_secs(10 * 60),
+            max_transaction_resend_period: Duration::from_secs(4 * 60 * 60),
+            pending_transaction_cancellation_timeout: Duration::from_secs(3 * 24 * 60 * 60),
+        }
+    }
+}
+
+/// When and how the Transaction Service consolidates the wallet's unspent outputs in the background
+#[derive(Clone, Debug)]
+pub struct ConsolidationPolicy {
+    /// How often the number of unspent outputs is checked
+    /// Default: 1 hour
+    pub check_interval: Duration,
+    /// Outputs are consolidated once the wallet holds more unspent outputs than this
+    /// Default: 200
+    pub utxo_count_threshold: usize,
+    /// The maximum number of outputs merged by a single consolidation transaction
+    /// Default: 100
+    pub max_inputs: usize,
+    /// The fee per gram paid by consolidation transactions
+    /// Default: 25 µT
+    pub fee_per_gram: MicroTari,
+    /// Outputs are only consolidated while the base node's mempool holds no more than this total transaction weight,
+    /// i.e. while there is little competition for block space and fees are low
+    /// Default: 10000
+    pub max_mempool_weight: u64,
+}
+
+impl Default for ConsolidationPolicy {
+    fn default() -> Self {
+        Self {
+            check_interval: Duration::from_secs(60 * 60),
+            utxo_count_threshold: 200,
+            max_inputs: 100,
+            fee_per_gram: MicroTari::from(25),
+            max_mempool_weight: 10_000,
         }
     }
 }
@@ -86,6 +86,8 @@ pub enum TransactionServiceError {
     UnexpectedBaseNodeResponse,
     /// The current transaction has been cancelled
     TransactionCancelled,
+    /// The recipient did not reply to the pending transaction before it expired
+    TransactionExpired,
     DhtOutboundError(DhtOutboundError),
     OutputManagerError(OutputManagerError),
     TransportChannelError(TransportChannelError),
@@ -98,6 +100,15 @@ pub enum TransactionServiceError {
     TransactionError(TransactionError),
     #[error(msg_embedded, no_from, non_std)]
     ConversionError(String),
+    /// An offline transaction file could not be read or written
+    #[error(msg_embedded, no_from, non_std)]
+    OfflineTransactionFileError(String),
+    /// An imported offline transaction was rejected
+    #[error(msg_embedded, no_from, non_std)]
+    InvalidOfflineTransaction(String),
+    /// The transaction history could not be exported
+    #[error(msg_embedded, no_from, non_std)]
+    TransactionHistoryExportError(String),
     NodeIdError(NodeIdError),
     BroadcastRecvError(RecvError),
     OneshotCancelled(Canceled),
@@ -24,6 +24,13 @@ use crate::{
     output_manager_service::TxId,
     transaction_service::{
         error::TransactionServiceError,
+        history::{TransactionHistoryFilter, TransactionHistoryRecord},
+        offline::{
+            OfflineFinalizedTransaction,
+            OfflineRecipientReply,
+            OfflineSenderMessage,
+            UnsignedTransactionRequest,
+        },
         service::PendingCoinbaseSpendingKey,
         storage::database::{CompletedTransaction, InboundTransaction, OutboundTransaction},
     },
@@ -31,7 +38,7 @@ use crate::{
 use futures::{stream::Fuse, StreamExt};
 use std::{collections::HashMap, fmt, sync::Arc};
 use tari_comms::types::CommsPublicKey;
-use tari_core::transactions::{tari_amount::MicroTari, transaction::Transaction};
+use tari_core::transactions::{tari_amount::MicroTari, transaction::Transaction, types::Commitment};
 use tari_service_framework::reply_channel::SenderService;
 use tokio::sync::broadcast;
 use tower::Service;
@@ -41,14 +48,24 @@ pub enum TransactionServiceRequest {
     GetPendingInboundTransactions,
     GetPendingOutboundTransactions,
     GetCompletedTransactions,
+    GetCompletedTransactionsForAccount(String),
     SetBaseNodePublicKey(CommsPublicKey),
     SendTransaction((CommsPublicKey, MicroTari, MicroTari, String)),
+    SendTransactionWithInputs((CommsPublicKey, MicroTari, MicroTari, String, Vec<Commitment>)),
+    SendTimeLockedTransaction((CommsPublicKey, MicroTari, MicroTari, Option<u64>, u64, String)),
+    SendTransactionFromAccount((String, CommsPublicKey, MicroTari, MicroTari, String)),
     CancelTransaction(TxId),
     RequestCoinbaseSpendingKey((MicroTari, u64)),
     CompleteCoinbaseTransaction((TxId, Transaction)),
     CancelPendingCoinbaseTransaction(TxId),
     ImportUtxo(MicroTari, CommsPublicKey, String),
     SubmitTransaction((TxId, Transaction, MicroTari, MicroTari, String)),
+    SendOneSidedTransaction((CommsPublicKey, MicroTari, MicroTari, String)),
+    SignOfflineTransaction(UnsignedTransactionRequest),
+    ReplyToOfflineTransaction(OfflineSenderMessage),
+    FinalizeOfflineTransaction(OfflineRecipientReply),
+    ImportOfflineTransaction(OfflineFinalizedTransaction),
+    GetTransactionHistory(TransactionHistoryFilter),
     #[cfg(feature = "test_harness")]
     CompletePendingOutboundTransaction(CompletedTransaction),
     #[cfg(feature = "test_harness")]
@@ -67,10 +84,32 @@ impl fmt::Display for TransactionServiceRequest {
             Self::GetPendingInboundTransactions => f.write_str("GetPendingInboundTransactions"),
             Self::GetPendingOutboundTransactions => f.write_str("GetPendingOutboundTransactions"),
             Self::GetCompletedTransactions => f.write_str("GetCompletedTransactions"),
+            Self::GetCompletedTransactionsForAccount(a) => {
+                f.write_str(&format!("GetCompletedTransactionsForAccount ({})", a))
+            },
             Self::SetBaseNodePublicKey(k) => f.write_str(&format!("SetBaseNodePublicKey ({})", k)),
             Self::SendTransaction((k, v, _, msg)) => {
                 f.write_str(&format!("SendTransaction (to {}, {}, {})", k, v, msg))
             },
+            Self::SendTransactionWithInputs((k, v, _, msg, inputs)) => f.write_str(&format!(
+                "SendTransactionWithInputs (to {}, {}, {}, {} inputs)",
+                k,
+                v,
+                msg,
+                inputs.len()
+            )),
+            Self::SendTimeLockedTransaction((k, v, _, lock_height, maturity, msg)) => f.write_str(&format!(
+                "SendTimeLockedTransaction (to {}, {}, lock_height={}, maturity={}, {})",
+                k,
+                v,
+                lock_height.unwrap_or(0),
+                maturity,
+                msg
+            )),
+            Self::SendTransactionFromAccount((a, k, v, _, msg)) => f.write_str(&format!(
+                "SendTransactionFromAccount (from {}, to {}, {}, {})",
+                a, k, v, msg
+            )),
             Self::CancelTransaction(t) => f.write_str(&format!("CancelTransaction ({})", t)),
             Self::RequestCoinbaseSpendingKey((v, h)) => {
                 f.write_str(&format!("RequestCoinbaseSpendingKey ({}, maturity={})", v, h))
@@ -81,6 +120,21 @@ impl fmt::Display for TransactionServiceRequest {
             },
             Self::ImportUtxo(v, k, msg) => f.write_str(&format!("ImportUtxo (from {}, {}, {})", k, v, msg)),
             Self::SubmitTransaction((id, _, _, _, _)) => f.write_str(&format!("SubmitTransaction ({})", id)),
+            Self::SendOneSidedTransaction((k, v, _, msg)) => {
+                f.write_str(&format!("SendOneSidedTransaction (to {}, {}, {})", k, v, msg))
+            },
+            Self::SignOfflineTransaction(r) => f.write_str(&format!(
+                "SignOfflineTransaction (to {}, {}, {})",
+                r.dest_pubkey, r.amount, r.message
+            )),
+            Self::ReplyToOfflineTransaction(m) => {
+                f.write_str(&format!("ReplyToOfflineTransaction (from {})", m.source_pubkey))
+            },
+            Self::FinalizeOfflineTransaction(r) => {
+                f.write_str(&format!("FinalizeOfflineTransaction ({})", r.reply.tx_id))
+            },
+            Self::ImportOfflineTransaction(t) => f.write_str(&format!("ImportOfflineTransaction ({})", t.tx_id)),
+            Self::GetTransactionHistory(_) => f.write_str("GetTransactionHistory"),
             #[cfg(feature = "test_harness")]
             Self::CompletePendingOutboundTransaction(tx) => {
                 f.write_str(&format!("CompletePendingOutboundTransaction ({})", tx.tx_id))
@@ -113,6 +167,11 @@ pub enum TransactionServiceResponse {
     BaseNodePublicKeySet,
     UtxoImported(TxId),
     TransactionSubmitted,
+    OfflineTransactionSigned(Box<OfflineSenderMessage>),
+    OfflineTransactionReply(Box<OfflineRecipientReply>),
+    OfflineTransactionFinalized(Box<OfflineFinalizedTransaction>),
+    OfflineTransactionImported(TxId),
+    TransactionHistory(Vec<TransactionHistoryRecord>),
     #[cfg(feature = "test_harness")]
     CompletedPendingTransaction,
     #[cfg(feature = "test_harness")]
@@ -138,6 +197,9 @@ pub enum TransactionEvent {
     TransactionBroadcast(TxId),
     TransactionMined(TxId),
     TransactionMinedRequestTimedOut(TxId),
+    TransactionConfirmed(TxId),
+    TransactionReorged(TxId),
+    ReceivedOneSidedTransaction(TxId),
     Error(String),
 }
 
@@ -190,6 +252,110 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Send a transaction that spends exactly the unspent outputs with the given commitments
+    pub async fn send_transaction_with_inputs(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        inputs: Vec<Commitment>,
+    ) -> Result<TxId, TransactionServiceError>
+    {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendTransactionWithInputs((
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                message,
+                inputs,
+            )))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Send a transaction that cannot be mined before `lock_height` and whose output can only be spent by the
+    /// recipient from the `maturity` block height
+    pub async fn send_time_locked_transaction(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        maturity: u64,
+        message: String,
+    ) -> Result<TxId, TransactionServiceError>
+    {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendTimeLockedTransaction((
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                lock_height,
+                maturity,
+                message,
+            )))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Send a transaction funded only from the outputs of the named account
+    pub async fn send_transaction_from_account(
+        &mut self,
+        account: String,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<TxId, TransactionServiceError>
+    {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendTransactionFromAccount((
+                account,
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                message,
+            )))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn send_one_sided_transaction(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<TxId, TransactionServiceError>
+    {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendOneSidedTransaction((
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                message,
+            )))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn cancel_transaction(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
         match self
             .handle
@@ -240,6 +406,22 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Fetch the completed transactions that were sent from or received into the named account
+    pub async fn get_completed_transactions_for_account(
+        &mut self,
+        account: String,
+    ) -> Result<HashMap<u64, CompletedTransaction>, TransactionServiceError>
+    {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetCompletedTransactionsForAccount(account))
+            .await??
+        {
+            TransactionServiceResponse::CompletedTransactions(c) => Ok(c),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn request_coinbase_key(
         &mut self,
         amount: MicroTari,
@@ -346,6 +528,89 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Sign an unsigned send request prepared by another wallet, reserving the inputs in this wallet. The resulting
+    /// sender message must be carried to the recipient.
+    pub async fn sign_offline_transaction(
+        &mut self,
+        request: UnsignedTransactionRequest,
+    ) -> Result<OfflineSenderMessage, TransactionServiceError>
+    {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SignOfflineTransaction(request))
+            .await??
+        {
+            TransactionServiceResponse::OfflineTransactionSigned(m) => Ok(*m),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Accept a sender message that was delivered out of band and produce the reply to carry back to the sender
+    pub async fn reply_to_offline_transaction(
+        &mut self,
+        sender_message: OfflineSenderMessage,
+    ) -> Result<OfflineRecipientReply, TransactionServiceError>
+    {
+        match self
+            .handle
+            .call(TransactionServiceRequest::ReplyToOfflineTransaction(sender_message))
+            .await??
+        {
+            TransactionServiceResponse::OfflineTransactionReply(r) => Ok(*r),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Apply a recipient reply to a transaction signed by `sign_offline_transaction` and produce the finalized
+    /// transaction, without broadcasting it
+    pub async fn finalize_offline_transaction(
+        &mut self,
+        reply: OfflineRecipientReply,
+    ) -> Result<OfflineFinalizedTransaction, TransactionServiceError>
+    {
+        match self
+            .handle
+            .call(TransactionServiceRequest::FinalizeOfflineTransaction(reply))
+            .await??
+        {
+            TransactionServiceResponse::OfflineTransactionFinalized(t) => Ok(*t),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Import a finalized transaction into this wallet's history and broadcast it to the base node
+    pub async fn import_offline_transaction(
+        &mut self,
+        transaction: OfflineFinalizedTransaction,
+    ) -> Result<TxId, TransactionServiceError>
+    {
+        match self
+            .handle
+            .call(TransactionServiceRequest::ImportOfflineTransaction(transaction))
+            .await??
+        {
+            TransactionServiceResponse::OfflineTransactionImported(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Fetch the pending, completed and cancelled transactions selected by the filter, oldest first. The counterparty
+    /// aliases are not set as the Transaction Service does not have access to the contacts.
+    pub async fn get_transaction_history(
+        &mut self,
+        filter: TransactionHistoryFilter,
+    ) -> Result<Vec<TransactionHistoryRecord>, TransactionServiceError>
+    {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetTransactionHistory(filter))
+            .await??
+        {
+            TransactionServiceResponse::TransactionHistory(records) => Ok(records),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     #[cfg(feature = "test_harness")]
     pub async fn test_complete_pending_transaction(
         &mut self,
@@ -0,0 +1,561 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Transaction history records for reconciliation of wallet activity. The records can be filtered by date and status,
+//! exported as CSV or JSON and summarised into totals per accounting period.
+
+use crate::{
+    contacts_service::storage::database::Contact,
+    output_manager_service::TxId,
+    transaction_service::{
+        error::TransactionServiceError,
+        storage::database::{CompletedTransaction, InboundTransaction, OutboundTransaction, TransactionStatus},
+    },
+    util::emoji::EmojiId,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+use tari_comms::types::CommsPublicKey;
+use tari_core::transactions::tari_amount::MicroTari;
+use tari_crypto::tari_utilities::hex::Hex;
+
+const CSV_HEADER: &str = "tx_id,direction,amount,fee,counterparty_public_key,counterparty_emoji_id,counterparty_alias,\
+                          message,status,mined_height,timestamp";
+const REPORT_CSV_HEADER: &str = "period,total_in,total_out,total_fees,transaction_count";
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TransactionDirection {
+    Inbound,
+    Outbound,
+    /// A transaction from this wallet to itself, such as a coin split or consolidation. Only its fee changes the
+    /// wallet's balance.
+    SelfTransfer,
+}
+
+impl fmt::Display for TransactionDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// A single entry in the wallet's transaction history, as seen from this wallet
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransactionHistoryRecord {
+    pub tx_id: TxId,
+    pub direction: TransactionDirection,
+    pub amount: MicroTari,
+    /// The fee is only known to the sender of a transaction until it has been completed
+    pub fee: MicroTari,
+    pub counterparty_public_key: CommsPublicKey,
+    pub counterparty_emoji_id: String,
+    /// The alias of the counterparty if they are one of the wallet's contacts
+    pub counterparty_alias: Option<String>,
+    pub message: String,
+    pub status: TransactionStatus,
+    pub mined_height: Option<u64>,
+    pub timestamp: NaiveDateTime,
+}
+
+impl TransactionHistoryRecord {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        tx_id: TxId,
+        direction: TransactionDirection,
+        amount: MicroTari,
+        fee: MicroTari,
+        counterparty_public_key: CommsPublicKey,
+        message: String,
+        status: TransactionStatus,
+        mined_height: Option<u64>,
+        timestamp: NaiveDateTime,
+    ) -> Self
+    {
+        Self {
+            tx_id,
+            direction,
+            amount,
+            fee,
+            counterparty_emoji_id: EmojiId::from_pubkey(&counterparty_public_key).to_string(),
+            counterparty_public_key,
+            counterparty_alias: None,
+            message,
+            status,
+            mined_height,
+            timestamp,
+        }
+    }
+
+    pub fn from_inbound(tx: &InboundTransaction) -> Self {
+        Self::new(
+            tx.tx_id,
+            TransactionDirection::Inbound,
+            tx.amount,
+            MicroTari::from(0),
+            tx.source_public_key.clone(),
+            tx.message.clone(),
+            tx.status.clone(),
+            None,
+            tx.timestamp,
+        )
+    }
+
+    pub fn from_outbound(tx: &OutboundTransaction) -> Self {
+        Self::new(
+            tx.tx_id,
+            TransactionDirection::Outbound,
+            tx.amount,
+            tx.fee,
+            tx.destination_public_key.clone(),
+            tx.message.clone(),
+            tx.status.clone(),
+            None,
+            tx.timestamp,
+        )
+    }
+
+    /// The direction of a completed transaction is determined by whether this wallet, identified by `own_public_key`,
+    /// was its source, its destination or both. Coinbase transactions also have the wallet as both source and
+    /// destination but, as they spend no inputs, they are inbound.
+    pub fn from_completed(tx: &CompletedTransaction, own_public_key: &CommsPublicKey) -> Self {
+        let is_destination = &tx.destination_public_key == own_public_key;
+        let (direction, counterparty) = if is_destination && &tx.source_public_key == own_public_key {
+            if tx.transaction.body.inputs().is_empty() {
+                (TransactionDirection::Inbound, tx.source_public_key.clone())
+            } else {
+                (TransactionDirection::SelfTransfer, tx.source_public_key.clone())
+            }
+        } else if is_destination {
+            (TransactionDirection::Inbound, tx.source_public_key.clone())
+        } else {
+            (TransactionDirection::Outbound, tx.destination_public_key.clone())
+        };
+        Self::new(
+            tx.tx_id,
+            direction,
+            tx.amount,
+            tx.fee,
+            counterparty,
+            tx.message.clone(),
+            tx.status.clone(),
+            tx.mined_height,
+            tx.timestamp,
+        )
+    }
+
+    fn to_csv_row(&self) -> String {
+        [
+            self.tx_id.to_string(),
+            self.direction.to_string(),
+            u64::from(self.amount).to_string(),
+            u64::from(self.fee).to_string(),
+            self.counterparty_public_key.to_hex(),
+            self.counterparty_emoji_id.clone(),
+            self.counterparty_alias.clone().unwrap_or_default(),
+            self.message.clone(),
+            self.status.to_string(),
+            self.mined_height.map(|h| h.to_string()).unwrap_or_default(),
+            self.timestamp.format(TIMESTAMP_FORMAT).to_string(),
+        ]
+        .iter()
+        .map(|field| escape_csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",")
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+fn escape_csv_field(field: &str) -> String {
+    if field.contains(|c: char| c == ',' || c == '"' || c == '\n' || c == '\r') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Fill in the counterparty aliases of the records from the wallet's contacts
+pub fn apply_contact_aliases(records: &mut [TransactionHistoryRecord], contacts: &[Contact]) {
+    for record in records.iter_mut() {
+        record.counterparty_alias = contacts
+            .iter()
+            .find(|c| c.public_key == record.counterparty_public_key)
+            .map(|c| c.alias.clone());
+    }
+}
+
+/// Selects the transaction history records to include in an export. Unset bounds and an empty status list do not
+/// restrict the selection.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TransactionHistoryFilter {
+    /// Only include transactions at or after this time
+    pub from: Option<NaiveDateTime>,
+    /// Only include transactions before this time
+    pub to: Option<NaiveDateTime>,
+    /// Only include transactions with one of these statuses
+    pub statuses: Vec<TransactionStatus>,
+}
+
+impl TransactionHistoryFilter {
+    pub fn matches(&self, record: &TransactionHistoryRecord) -> bool {
+        self.from.map(|from| record.timestamp >= from).unwrap_or(true) &&
+            self.to.map(|to| record.timestamp < to).unwrap_or(true) &&
+            (self.statuses.is_empty() || self.statuses.contains(&record.status))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransactionHistoryFormat {
+    Csv,
+    Json,
+}
+
+/// Render the records as a CSV document, with a header row, or as a JSON array
+pub fn export_transaction_history(
+    records: &[TransactionHistoryRecord],
+    format: TransactionHistoryFormat,
+) -> Result<String, TransactionServiceError>
+{
+    match format {
+        TransactionHistoryFormat::Csv => {
+            let mut csv = String::from(CSV_HEADER);
+            csv.push('\n');
+            for record in records {
+                csv.push_str(&record.to_csv_row());
+                csv.push('\n');
+            }
+            Ok(csv)
+        },
+        TransactionHistoryFormat::Json => serde_json::to_string_pretty(records)
+            .map_err(|e| TransactionServiceError::TransactionHistoryExportError(e.to_string())),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AccountingPeriod {
+    Day,
+    Month,
+    Year,
+}
+
+impl AccountingPeriod {
+    fn label(self, timestamp: &NaiveDateTime) -> String {
+        let format = match self {
+            AccountingPeriod::Day => "%Y-%m-%d",
+            AccountingPeriod::Month => "%Y-%m",
+            AccountingPeriod::Year => "%Y",
+        };
+        timestamp.format(format).to_string()
+    }
+}
+
+/// The totals of the wallet's settled transactions in one accounting period
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TransactionHistorySummary {
+    /// The period formatted as `YYYY-MM-DD`, `YYYY-MM` or `YYYY`
+    pub period: String,
+    pub total_in: MicroTari,
+    pub total_out: MicroTari,
+    /// Fees paid by this wallet on outbound transactions and self-transfers
+    pub total_fees: MicroTari,
+    pub transaction_count: usize,
+}
+
+/// Summarise the records into totals per accounting period, in chronological order. Pending and cancelled
+/// transactions have not moved any funds and are left out of the totals. Self-transfers only count towards the fees.
+pub fn summarize_transaction_history(
+    records: &[TransactionHistoryRecord],
+    period: AccountingPeriod,
+) -> Vec<TransactionHistorySummary>
+{
+    let mut summaries: BTreeMap<String, TransactionHistorySummary> = BTreeMap::new();
+    for record in records
+        .iter()
+        .filter(|r| r.status != TransactionStatus::Pending && r.status != TransactionStatus::Cancelled)
+    {
+        let label = period.label(&record.timestamp);
+        let summary = summaries
+            .entry(label.clone())
+            .or_insert_with(|| TransactionHistorySummary {
+                period: label,
+                ..Default::default()
+            });
+        match record.direction {
+            TransactionDirection::Inbound => summary.total_in = summary.total_in + record.amount,
+            TransactionDirection::Outbound => {
+                summary.total_out = summary.total_out + record.amount;
+                summary.total_fees = summary.total_fees + record.fee;
+            },
+            TransactionDirection::SelfTransfer => summary.total_fees = summary.total_fees + record.fee,
+        }
+        summary.transaction_count += 1;
+    }
+    summaries.into_iter().map(|(_, s)| s).collect()
+}
+
+/// Render the period summaries as a CSV document, with a header row, or as a JSON array
+pub fn export_transaction_history_report(
+    summaries: &[TransactionHistorySummary],
+    format: TransactionHistoryFormat,
+) -> Result<String, TransactionServiceError>
+{
+    match format {
+        TransactionHistoryFormat::Csv => {
+            let mut csv = String::from(REPORT_CSV_HEADER);
+            csv.push('\n');
+            for s in summaries {
+                csv.push_str(&format!(
+                    "{},{},{},{},{}\n",
+                    s.period,
+                    u64::from(s.total_in),
+                    u64::from(s.total_out),
+                    u64::from(s.total_fees),
+                    s.transaction_count
+                ));
+            }
+            Ok(csv)
+        },
+        TransactionHistoryFormat::Json => serde_json::to_string_pretty(summaries)
+            .map_err(|e| TransactionServiceError::TransactionHistoryExportError(e.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::NaiveDate;
+    use rand::rngs::OsRng;
+    use tari_core::transactions::{
+        helpers::create_test_input,
+        transaction::Transaction,
+        types::{BlindingFactor, CommitmentFactory},
+    };
+    use tari_crypto::keys::PublicKey;
+
+    fn record(
+        direction: TransactionDirection,
+        amount: u64,
+        fee: u64,
+        status: TransactionStatus,
+        timestamp: NaiveDateTime,
+    ) -> TransactionHistoryRecord
+    {
+        let (_, public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        TransactionHistoryRecord::new(
+            1,
+            direction,
+            MicroTari::from(amount),
+            MicroTari::from(fee),
+            public_key,
+            "Payment, \"March\"".to_string(),
+            status,
+            None,
+            timestamp,
+        )
+    }
+
+    fn completed_transaction(
+        source_public_key: CommsPublicKey,
+        destination_public_key: CommsPublicKey,
+        num_inputs: usize,
+    ) -> CompletedTransaction
+    {
+        let factory = CommitmentFactory::default();
+        let inputs = (0..num_inputs)
+            .map(|_| create_test_input(MicroTari::from(1000), 0, &factory).0)
+            .collect();
+        CompletedTransaction {
+            tx_id: 1,
+            source_public_key,
+            destination_public_key,
+            amount: MicroTari::from(900),
+            fee: MicroTari::from(100),
+            transaction: Transaction::new(inputs, Vec::new(), Vec::new(), BlindingFactor::default()),
+            status: TransactionStatus::Mined,
+            message: "".to_string(),
+            timestamp: NaiveDate::from_ymd(2020, 3, 15).and_hms(12, 0, 0),
+            mined_height: Some(10),
+            mined_block_hash: None,
+            confirmations: None,
+        }
+    }
+
+    #[test]
+    fn direction_of_completed_transactions() {
+        let (_, own_public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        let (_, other_public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+
+        let tx = completed_transaction(other_public_key.clone(), own_public_key.clone(), 1);
+        let record = TransactionHistoryRecord::from_completed(&tx, &own_public_key);
+        assert_eq!(record.direction, TransactionDirection::Inbound);
+        assert_eq!(record.counterparty_public_key, other_public_key);
+
+        let tx = completed_transaction(own_public_key.clone(), other_public_key.clone(), 1);
+        let record = TransactionHistoryRecord::from_completed(&tx, &own_public_key);
+        assert_eq!(record.direction, TransactionDirection::Outbound);
+        assert_eq!(record.counterparty_public_key, other_public_key);
+
+        // Coin splits and consolidations spend the wallet's own outputs
+        let tx = completed_transaction(own_public_key.clone(), own_public_key.clone(), 2);
+        let record = TransactionHistoryRecord::from_completed(&tx, &own_public_key);
+        assert_eq!(record.direction, TransactionDirection::SelfTransfer);
+
+        // Coinbase transactions have no inputs
+        let tx = completed_transaction(own_public_key.clone(), own_public_key.clone(), 0);
+        let record = TransactionHistoryRecord::from_completed(&tx, &own_public_key);
+        assert_eq!(record.direction, TransactionDirection::Inbound);
+    }
+
+    #[test]
+    fn filter_by_date_and_status() {
+        let march = NaiveDate::from_ymd(2020, 3, 15).and_hms(12, 0, 0);
+        let april = NaiveDate::from_ymd(2020, 4, 15).and_hms(12, 0, 0);
+        let mined = record(TransactionDirection::Inbound, 100, 0, TransactionStatus::Mined, march);
+        let cancelled = record(
+            TransactionDirection::Outbound,
+            50,
+            5,
+            TransactionStatus::Cancelled,
+            april,
+        );
+
+        let filter = TransactionHistoryFilter {
+            from: Some(NaiveDate::from_ymd(2020, 3, 1).and_hms(0, 0, 0)),
+            to: Some(NaiveDate::from_ymd(2020, 4, 1).and_hms(0, 0, 0)),
+            statuses: Vec::new(),
+        };
+        assert!(filter.matches(&mined));
+        assert!(!filter.matches(&cancelled));
+
+        let filter = TransactionHistoryFilter {
+            statuses: vec![TransactionStatus::Cancelled],
+            ..Default::default()
+        };
+        assert!(!filter.matches(&mined));
+        assert!(filter.matches(&cancelled));
+        assert!(TransactionHistoryFilter::default().matches(&mined));
+    }
+
+    #[test]
+    fn export_csv_and_json() {
+        let timestamp = NaiveDate::from_ymd(2020, 3, 15).and_hms(12, 0, 0);
+        let mut records = vec![record(
+            TransactionDirection::Outbound,
+            1000,
+            20,
+            TransactionStatus::Mined,
+            timestamp,
+        )];
+        records[0].mined_height = Some(42);
+        apply_contact_aliases(&mut records, &[Contact {
+            alias: "Alice".to_string(),
+            public_key: records[0].counterparty_public_key.clone(),
+        }]);
+
+        let csv = export_transaction_history(&records, TransactionHistoryFormat::Csv).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(
+            lines[1],
+            format!(
+                "1,Outbound,1000,20,{},{},Alice,\"Payment, \"\"March\"\"\",Mined,42,2020-03-15T12:00:00",
+                records[0].counterparty_public_key.to_hex(),
+                records[0].counterparty_emoji_id
+            )
+        );
+
+        let json = export_transaction_history(&records, TransactionHistoryFormat::Json).unwrap();
+        let parsed: Vec<TransactionHistoryRecord> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, records);
+    }
+
+    #[test]
+    fn summarize_per_period() {
+        let records = vec![
+            record(
+                TransactionDirection::Inbound,
+                1000,
+                0,
+                TransactionStatus::Mined,
+                NaiveDate::from_ymd(2020, 3, 1).and_hms(9, 0, 0),
+            ),
+            record(
+                TransactionDirection::Outbound,
+                300,
+                10,
+                TransactionStatus::Broadcast,
+                NaiveDate::from_ymd(2020, 3, 20).and_hms(9, 0, 0),
+            ),
+            record(
+                TransactionDirection::Outbound,
+                500,
+                10,
+                TransactionStatus::Cancelled,
+                NaiveDate::from_ymd(2020, 3, 21).and_hms(9, 0, 0),
+            ),
+            record(
+                TransactionDirection::Outbound,
+                200,
+                5,
+                TransactionStatus::Mined,
+                NaiveDate::from_ymd(2020, 4, 2).and_hms(9, 0, 0),
+            ),
+            record(
+                TransactionDirection::SelfTransfer,
+                5000,
+                15,
+                TransactionStatus::Mined,
+                NaiveDate::from_ymd(2020, 4, 3).and_hms(9, 0, 0),
+            ),
+        ];
+
+        let summaries = summarize_transaction_history(&records, AccountingPeriod::Month);
+        assert_eq!(summaries, vec![
+            TransactionHistorySummary {
+                period: "2020-03".to_string(),
+                total_in: MicroTari::from(1000),
+                total_out: MicroTari::from(300),
+                total_fees: MicroTari::from(10),
+                transaction_count: 2,
+            },
+            TransactionHistorySummary {
+                period: "2020-04".to_string(),
+                total_in: MicroTari::from(0),
+                total_out: MicroTari::from(200),
+                total_fees: MicroTari::from(20),
+                transaction_count: 2,
+            },
+        ]);
+
+        let csv = export_transaction_history_report(&summaries, TransactionHistoryFormat::Csv).unwrap();
+        assert_eq!(
+            csv,
+            format!("{}\n2020-03,1000,300,10,2\n2020-04,0,200,20,2\n", REPORT_CSV_HEADER)
+        );
+
+        let summaries = summarize_transaction_history(&records, AccountingPeriod::Year);
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].transaction_count, 4);
+        assert_eq!(summaries[0].total_fees, MicroTari::from(30));
+    }
+}
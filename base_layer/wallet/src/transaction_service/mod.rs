@@ -23,11 +23,14 @@
 pub mod config;
 pub mod error;
 pub mod handle;
+pub mod history;
+pub mod offline;
 pub mod protocols;
 pub mod service;
 pub mod storage;
 
 use crate::{
+    metrics,
     output_manager_service::handle::OutputManagerHandle,
     transaction_service::{
         config::TransactionServiceConfig,
@@ -119,6 +122,13 @@ where T: TransactionBackend
             .filter_map(ok_or_skip_result)
     }
 
+    fn transaction_cancelled_stream(&self) -> impl Stream<Item = DomainMessage<proto::TransactionCancelledMessage>> {
+        self.subscription_factory
+            .get_subscription(TariMessageType::TransactionCancelled)
+            .map(map_decode::<proto::TransactionCancelledMessage>)
+            .filter_map(ok_or_skip_result)
+    }
+
     fn mempool_response_stream(&self) -> impl Stream<Item = DomainMessage<MempoolProto::MempoolServiceResponse>> {
         self.subscription_factory
             .get_subscription(TariMessageType::MempoolResponse)
@@ -150,12 +160,14 @@ where T: TransactionBackend + Clone + 'static
         let transaction_stream = self.transaction_stream();
         let transaction_reply_stream = self.transaction_reply_stream();
         let transaction_finalized_stream = self.transaction_finalized_stream();
+        let transaction_cancelled_stream = self.transaction_cancelled_stream();
         let mempool_response_stream = self.mempool_response_stream();
         let base_node_response_stream = self.base_node_response_stream();
 
         let (publisher, _) = broadcast::channel(200);
 
         let transaction_handle = TransactionServiceHandle::new(sender, publisher.clone());
+        executor.spawn(metrics::record_transaction_events(publisher.subscribe()));
 
         // Register handle before waiting for handles to be ready
         handles_fut.register(transaction_handle);
@@ -189,6 +201,7 @@ where T: TransactionBackend + Clone + 'static
                 transaction_stream,
                 transaction_reply_stream,
                 transaction_finalized_stream,
+                transaction_cancelled_stream,
                 mempool_response_stream,
                 base_node_response_stream,
                 output_manager_service,
@@ -0,0 +1,118 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! File formats used to complete a transaction between wallets that cannot reach each other, or the network, over
+//! comms. A send proceeds in four steps, each of which produces a file that is carried to the next wallet:
+//! 1. The online (possibly watch-only) sending wallet creates an `UnsignedTransactionRequest`.
+//! 2. The offline sending wallet, which holds the spending keys, signs the request into an `OfflineSenderMessage`.
+//! 3. The recipient wallet replies with an `OfflineRecipientReply`.
+//! 4. The offline sending wallet finalizes the reply into an `OfflineFinalizedTransaction`, which either of the online
+//!    wallets imports and broadcasts.
+//!
+//! None of these files contain private keys.
+
+use crate::{output_manager_service::TxId, transaction_service::error::TransactionServiceError};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{fs, path::Path};
+use tari_comms::types::CommsPublicKey;
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction::Transaction,
+    transaction_protocol::{recipient::RecipientSignedMessage, sender::TransactionSenderMessage},
+    types::Commitment,
+};
+
+/// Reading and writing of the offline transaction files as JSON
+pub trait OfflineTransactionFile: Serialize + DeserializeOwned {
+    fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), TransactionServiceError> {
+        let data = serde_json::to_string_pretty(self)
+            .map_err(|e| TransactionServiceError::OfflineTransactionFileError(e.to_string()))?;
+        fs::write(path, data).map_err(|e| TransactionServiceError::OfflineTransactionFileError(e.to_string()))
+    }
+
+    fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, TransactionServiceError> {
+        let data = fs::read_to_string(path)
+            .map_err(|e| TransactionServiceError::OfflineTransactionFileError(e.to_string()))?;
+        serde_json::from_str(&data).map_err(|e| TransactionServiceError::OfflineTransactionFileError(e.to_string()))
+    }
+}
+
+/// The parameters of a send, prepared by a wallet that does not hold the spending keys
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct UnsignedTransactionRequest {
+    pub dest_pubkey: CommsPublicKey,
+    pub amount: MicroTari,
+    pub fee_per_gram: MicroTari,
+    pub lock_height: Option<u64>,
+    pub recipient_maturity: u64,
+    pub message: String,
+    /// The commitments of the outputs to spend, if the signing wallet should not select the inputs itself
+    pub inputs: Option<Vec<Commitment>>,
+}
+
+impl UnsignedTransactionRequest {
+    pub fn new(dest_pubkey: CommsPublicKey, amount: MicroTari, fee_per_gram: MicroTari, message: String) -> Self {
+        Self {
+            dest_pubkey,
+            amount,
+            fee_per_gram,
+            lock_height: None,
+            recipient_maturity: 0,
+            message,
+            inputs: None,
+        }
+    }
+}
+
+impl OfflineTransactionFile for UnsignedTransactionRequest {}
+
+/// The signed sender half of a transaction, to be carried to the recipient
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OfflineSenderMessage {
+    pub source_pubkey: CommsPublicKey,
+    pub sender_message: TransactionSenderMessage,
+}
+
+impl OfflineTransactionFile for OfflineSenderMessage {}
+
+/// The recipient's reply to an `OfflineSenderMessage`, to be carried back to the sender
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct OfflineRecipientReply {
+    pub recipient_pubkey: CommsPublicKey,
+    pub reply: RecipientSignedMessage,
+}
+
+impl OfflineTransactionFile for OfflineRecipientReply {}
+
+/// A completed transaction, ready to be imported by an online wallet and broadcast
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct OfflineFinalizedTransaction {
+    pub tx_id: TxId,
+    pub source_pubkey: CommsPublicKey,
+    pub destination_pubkey: CommsPublicKey,
+    pub amount: MicroTari,
+    pub fee: MicroTari,
+    pub message: String,
+    pub transaction: Transaction,
+}
+
+impl OfflineTransactionFile for OfflineFinalizedTransaction {}
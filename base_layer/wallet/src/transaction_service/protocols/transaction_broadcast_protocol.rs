@@ -107,6 +107,8 @@ where TBackend: TransactionBackend + Clone + 'static
         //      b) A Basenode response for this Id is received showing it is mined > Update Tx status and end protocol
         //      c) Timeout is reached > Start again
         loop {
+            self.update_base_node_public_key();
+
             let completed_tx = match self.resources.db.get_completed_transaction(self.id).await {
                 Ok(tx) => tx,
                 Err(e) => {
@@ -417,15 +419,22 @@ where TBackend: TransactionBackend + Clone + 'static
             }
             // If all outputs are present then mark this transaction as mined.
             if check && !response.is_empty() {
-                self.resources
-                    .output_manager_service
-                    .confirm_transaction(
-                        self.id,
-                        completed_tx.transaction.body.inputs().clone(),
-                        completed_tx.transaction.body.outputs().clone(),
-                    )
-                    .await
-                    .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+                // If confirmations are required the outputs will only be confirmed by the Chain Monitoring protocol
+                // once enough blocks have been built on top of this transaction. A transaction this wallet is not a
+                // party to has no outputs to confirm.
+                if self.resources.config.num_confirmations_required == 0 &&
+                    completed_tx.is_party(self.resources.node_identity.public_key())
+                {
+                    self.resources
+                        .output_manager_service
+                        .confirm_transaction(
+                            self.id,
+                            completed_tx.transaction.body.inputs().clone(),
+                            completed_tx.transaction.body.outputs().clone(),
+                        )
+                        .await
+                        .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+                }
 
                 self.resources
                     .db
@@ -457,4 +466,18 @@ where TBackend: TransactionBackend + Clone + 'static
 
         Ok(false)
     }
+
+    /// Pick up any change to the wallet's current base node so that the next round of requests is sent to it
+    fn update_base_node_public_key(&mut self) {
+        let current_base_node = self.resources.base_node_public_key_watch.borrow().clone();
+        if let Some(public_key) = current_base_node {
+            if public_key != self.base_node_public_key {
+                info!(
+                    target: LOG_TARGET,
+                    "Base node changed to {} for TxId: {}", public_key, self.id
+                );
+                self.base_node_public_key = public_key;
+            }
+        }
+    }
 }
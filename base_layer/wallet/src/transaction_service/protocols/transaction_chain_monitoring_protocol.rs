@@ -26,7 +26,7 @@ use crate::{
         error::{TransactionServiceError, TransactionServiceProtocolError},
         handle::TransactionEvent,
         service::TransactionServiceResources,
-        storage::database::{TransactionBackend, TransactionStatus},
+        storage::database::{CompletedTransaction, TransactionBackend, TransactionStatus},
     },
 };
 use futures::{channel::mpsc::Receiver, FutureExt, StreamExt};
@@ -47,16 +47,21 @@ use tari_core::{
         service::{MempoolResponse, MempoolServiceResponse},
         TxStorageResponse,
     },
-    transactions::transaction::TransactionOutput,
+    proto::core::HistoricalBlock,
+    transactions::{aggregated_body::AggregateBody, transaction::TransactionOutput},
 };
 use tari_crypto::tari_utilities::{hex::Hex, Hashable};
 use tari_p2p::tari_message::TariMessageType;
 use tokio::time::delay_for;
 
 const LOG_TARGET: &str = "wallet::transaction_service::protocols::chain_monitoring_protocol";
+/// The number of blocks that are searched in each round for the block a transaction was mined in
+const MINED_BLOCK_SEARCH_DEPTH: u64 = 10;
 
 /// This protocol defines the process of monitoring a mempool and base node to detect when a Broadcast transaction is
-/// Mined or leaves the mempool in which case it should be cancelled
+/// Mined or leaves the mempool in which case it should be cancelled. Once Mined the transaction is monitored until it
+/// has the configured number of confirmations, if the block it was mined in is reorged out of the chain before that the
+/// transaction is reverted to the Broadcast state and resubmitted to the mempool.
 pub struct TransactionChainMonitoringProtocol<TBackend>
 where TBackend: TransactionBackend + Clone + 'static
 {
@@ -67,6 +72,9 @@ where TBackend: TransactionBackend + Clone + 'static
     base_node_public_key: CommsPublicKey,
     mempool_response_receiver: Option<Receiver<MempoolServiceResponse>>,
     base_node_response_receiver: Option<Receiver<BaseNodeProto::BaseNodeServiceResponse>>,
+    /// The highest block of the next search window for the block a Mined transaction was mined in. Each round that
+    /// does not find the transaction moves the window further back from the tip.
+    mined_block_search_end: Option<u64>,
 }
 
 impl<TBackend> TransactionChainMonitoringProtocol<TBackend>
@@ -90,6 +98,7 @@ where TBackend: TransactionBackend + Clone + 'static
             base_node_public_key,
             mempool_response_receiver: Some(mempool_response_receiver),
             base_node_response_receiver: Some(base_node_response_receiver),
+            mined_block_search_end: None,
         }
     }
 
@@ -113,7 +122,8 @@ where TBackend: TransactionBackend + Clone + 'static
         );
 
         // This is the main loop of the protocol and following the following steps
-        // 1) Check transaction being monitored is still in the Broadcast state and needs to be monitored
+        // 1) Check transaction being monitored is still in the Broadcast state and needs to be monitored, if it is
+        //    Mined but does not have enough confirmations yet check its confirmations instead
         // 2) Send a MempoolRequest::GetTxStateWithExcessSig to Mempool and a Mined? Request to base node
         // 3) Wait for both a Mempool response and Base Node response for the correct Id OR a Timeout
         //      a) If the Tx is not in the mempool AND is not mined the protocol ends and Tx should be cancelled
@@ -121,6 +131,8 @@ where TBackend: TransactionBackend + Clone + 'static
         //      c) If the Tx is in the mempool AND mined then update the status of the Tx and end the protocol
         //      c) Timeout is reached > Start again
         loop {
+            self.update_base_node_public_key();
+
             let completed_tx = match self.resources.db.get_completed_transaction(self.tx_id).await {
                 Ok(tx) => tx,
                 Err(e) => {
@@ -138,6 +150,18 @@ where TBackend: TransactionBackend + Clone + 'static
                 },
             };
 
+            if completed_tx.status == TransactionStatus::Mined &&
+                completed_tx.confirmations.unwrap_or(0) < self.resources.config.num_confirmations_required
+            {
+                if self
+                    .check_confirmations(completed_tx, &mut base_node_response_receiver)
+                    .await?
+                {
+                    return Ok(self.id);
+                }
+                continue;
+            }
+
             if completed_tx.status != TransactionStatus::Broadcast {
                 debug!(
                     target: LOG_TARGET,
@@ -203,6 +227,7 @@ where TBackend: TransactionBackend + Clone + 'static
             let mut received_mempool_response = None;
             let mut mempool_response_received = false;
             let mut base_node_response_received = false;
+            let mut mined = false;
             // Loop until both a Mempool response AND a Base node response is received OR the Timeout expires.
             loop {
                 futures::select! {
@@ -217,8 +242,12 @@ where TBackend: TransactionBackend + Clone + 'static
                         .handle_base_node_response(completed_tx.tx_id, base_node_response)
                         .await?
                         {
-                            // Tx is mined!
-                            return Ok(self.id);
+                            // Tx is mined! If confirmations are required carry on monitoring it until it has them
+                            if self.resources.config.num_confirmations_required == 0 {
+                                return Ok(self.id);
+                            }
+                            mined = true;
+                            break;
                         }
                         base_node_response_received = true;
                     },
@@ -246,6 +275,10 @@ where TBackend: TransactionBackend + Clone + 'static
                 }
             }
 
+            if mined {
+                continue;
+            }
+
             if mempool_response_received && base_node_response_received {
                 info!(
                     target: LOG_TARGET,
@@ -255,25 +288,7 @@ where TBackend: TransactionBackend + Clone + 'static
                 delay.await;
             }
 
-            info!(
-                target: LOG_TARGET,
-                "Chain monitoring process timed out for Transaction TX_ID: {}", completed_tx.tx_id
-            );
-
-            let _ = self
-                .resources
-                .event_publisher
-                .send(Arc::new(TransactionEvent::TransactionMinedRequestTimedOut(
-                    completed_tx.tx_id,
-                )))
-                .map_err(|e| {
-                    trace!(
-                        target: LOG_TARGET,
-                        "Error sending event, usually because there are no subscribers: {:?}",
-                        e
-                    );
-                    e
-                });
+            self.publish_mined_request_timed_out(completed_tx.tx_id);
         }
     }
 
@@ -436,15 +451,9 @@ where TBackend: TransactionBackend + Clone + 'static
             }
             // If all outputs are present then mark this transaction as mined.
             if check && !response.is_empty() {
-                self.resources
-                    .output_manager_service
-                    .confirm_transaction(
-                        completed_tx.tx_id,
-                        completed_tx.transaction.body.inputs().clone(),
-                        completed_tx.transaction.body.outputs().clone(),
-                    )
-                    .await
-                    .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+                if self.resources.config.num_confirmations_required == 0 {
+                    self.confirm_outputs(&completed_tx).await?;
+                }
 
                 self.resources
                     .db
@@ -476,4 +485,383 @@ where TBackend: TransactionBackend + Clone + 'static
 
         Ok(false)
     }
+
+    /// Confirm the inputs and outputs of a mined transaction with the Output Manager so that they become spendable. A
+    /// transaction this wallet is not a party to has nothing to confirm.
+    async fn confirm_outputs(
+        &mut self,
+        completed_tx: &CompletedTransaction,
+    ) -> Result<(), TransactionServiceProtocolError>
+    {
+        if !completed_tx.is_party(self.resources.node_identity.public_key()) {
+            debug!(
+                target: LOG_TARGET,
+                "Transaction (TxId: {}) does not involve this wallet, no outputs to confirm", completed_tx.tx_id
+            );
+            return Ok(());
+        }
+        self.resources
+            .output_manager_service
+            .confirm_transaction(
+                completed_tx.tx_id,
+                completed_tx.transaction.body.inputs().clone(),
+                completed_tx.transaction.body.outputs().clone(),
+            )
+            .await
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))
+    }
+
+    /// Perform a single round of confirmation monitoring for a Mined transaction. The chain tip is requested from the
+    /// Base Node followed by the blocks that should contain the transaction kernel. If the transaction is found the
+    /// block it was mined in and its confirmation count are recorded. If the block it was previously recorded as mined
+    /// in is no longer part of the chain the transaction has been reorged out and is reverted and resubmitted.
+    /// Returns true when the transaction has reached the required number of confirmations.
+    async fn check_confirmations(
+        &mut self,
+        completed_tx: CompletedTransaction,
+        base_node_response_receiver: &mut Receiver<BaseNodeProto::BaseNodeServiceResponse>,
+    ) -> Result<bool, TransactionServiceProtocolError>
+    {
+        let metadata = match self
+            .query_base_node(
+                BaseNodeRequestProto::GetChainMetadata(true),
+                base_node_response_receiver,
+                |r| match r {
+                    BaseNodeResponseProto::ChainMetadata(_) => true,
+                    _ => false,
+                },
+            )
+            .await?
+        {
+            Some(BaseNodeResponseProto::ChainMetadata(metadata)) => metadata,
+            _ => {
+                self.publish_mined_request_timed_out(completed_tx.tx_id);
+                return Ok(false);
+            },
+        };
+
+        let tip_height = match metadata.height_of_longest_chain {
+            None => {
+                delay_for(self.timeout).await;
+                return Ok(false);
+            },
+            Some(h) => h,
+        };
+
+        let heights: Vec<u64> = match completed_tx.mined_height {
+            Some(mined_height) if mined_height > tip_height => {
+                // A base node that is behind, or a different base node after a failover, has not seen the block yet.
+                // A reorg onto a shorter chain is detected once the chain has grown past the mined height again.
+                debug!(
+                    target: LOG_TARGET,
+                    "Base Node tip {} is below the height {} that Transaction (TxId: {}) was mined at",
+                    tip_height,
+                    mined_height,
+                    completed_tx.tx_id
+                );
+                delay_for(self.timeout).await;
+                return Ok(false);
+            },
+            Some(mined_height) => (mined_height..=tip_height.min(mined_height + 1)).collect(),
+            None => {
+                let search_end = self.mined_block_search_end.unwrap_or(tip_height).min(tip_height);
+                (search_end.saturating_sub(MINED_BLOCK_SEARCH_DEPTH - 1)..=search_end).collect()
+            },
+        };
+        let search_start = heights.first().cloned().unwrap_or(0);
+        let search_end = heights.last().cloned().unwrap_or(0);
+
+        let blocks = match self
+            .query_base_node(
+                BaseNodeRequestProto::FetchBlocks(BaseNodeProto::BlockHeights { heights }),
+                base_node_response_receiver,
+                |r| match r {
+                    BaseNodeResponseProto::HistoricalBlocks(_) => true,
+                    _ => false,
+                },
+            )
+            .await?
+        {
+            Some(BaseNodeResponseProto::HistoricalBlocks(blocks)) => blocks.blocks,
+            _ => {
+                self.publish_mined_request_timed_out(completed_tx.tx_id);
+                return Ok(false);
+            },
+        };
+
+        let found = self.find_mined_block(&completed_tx, tip_height, metadata.best_block, blocks)?;
+
+        let mined_height = match (completed_tx.mined_height, completed_tx.mined_block_hash.clone(), found) {
+            (_, _, Some((height, None))) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Hash of block {} containing Mined Transaction (TxId: {}) not known yet",
+                    height,
+                    completed_tx.tx_id
+                );
+                delay_for(self.timeout).await;
+                return Ok(false);
+            },
+            (None, _, Some((height, Some(hash)))) => {
+                self.mined_block_search_end = None;
+                self.resources
+                    .db
+                    .set_completed_transaction_mined_height(completed_tx.tx_id, height, hash)
+                    .await
+                    .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+                height
+            },
+            (None, _, None) if search_start == 0 => {
+                // The whole chain has been searched so the block the transaction was mined in has been reorged out
+                self.mined_block_search_end = None;
+                self.handle_reorg(&completed_tx).await?;
+                return Ok(false);
+            },
+            (None, _, None) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Block containing Mined Transaction (TxId: {}) not found at heights {} to {}, searching further \
+                     back",
+                    completed_tx.tx_id,
+                    search_start,
+                    search_end
+                );
+                self.mined_block_search_end = Some(search_start - 1);
+                return Ok(false);
+            },
+            (Some(height), Some(hash), Some((found_height, Some(found_hash))))
+                if height == found_height && hash == found_hash =>
+            {
+                height
+            },
+            _ => {
+                self.handle_reorg(&completed_tx).await?;
+                return Ok(false);
+            },
+        };
+
+        let confirmations = tip_height - mined_height + 1;
+        self.resources
+            .db
+            .set_completed_transaction_confirmations(completed_tx.tx_id, confirmations)
+            .await
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+        debug!(
+            target: LOG_TARGET,
+            "Mined Transaction (TxId: {}) has {} of {} required confirmations",
+            completed_tx.tx_id,
+            confirmations,
+            self.resources.config.num_confirmations_required
+        );
+
+        if confirmations < self.resources.config.num_confirmations_required {
+            delay_for(self.timeout).await;
+            return Ok(false);
+        }
+
+        self.confirm_outputs(&completed_tx).await?;
+
+        let _ = self
+            .resources
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionConfirmed(completed_tx.tx_id)))
+            .map_err(|e| {
+                trace!(
+                    target: LOG_TARGET,
+                    "Error sending event, usually because there are no subscribers: {:?}",
+                    e
+                );
+                e
+            });
+
+        info!(
+            target: LOG_TARGET,
+            "Transaction (TxId: {}) has been confirmed with {} confirmations", completed_tx.tx_id, confirmations
+        );
+
+        Ok(true)
+    }
+
+    /// Search the provided blocks for the kernel of the transaction and return the height and hash of the block that
+    /// contains it. The hash of a block is taken from the `prev_hash` of the block that follows it, or from the chain
+    /// metadata if the block is the current tip, and is `None` if neither is available.
+    fn find_mined_block(
+        &self,
+        completed_tx: &CompletedTransaction,
+        tip_height: u64,
+        best_block: Option<Vec<u8>>,
+        blocks: Vec<HistoricalBlock>,
+    ) -> Result<Option<(u64, Option<Vec<u8>>)>, TransactionServiceProtocolError>
+    {
+        let excess_sig = &completed_tx.transaction.body.kernels()[0].excess_sig;
+        let mut mined_height = None;
+        let mut prev_hashes = Vec::new();
+
+        for historical_block in blocks {
+            let block = match historical_block.block {
+                None => continue,
+                Some(b) => b,
+            };
+            let header = match block.header {
+                None => continue,
+                Some(h) => h,
+            };
+            prev_hashes.push((header.height, header.prev_hash));
+
+            let body = match block.body {
+                None => continue,
+                Some(body) => AggregateBody::try_from(body).map_err(|e| {
+                    TransactionServiceProtocolError::new(self.id, TransactionServiceError::ConversionError(e))
+                })?,
+            };
+            if body.kernels().iter().any(|k| &k.excess_sig == excess_sig) {
+                mined_height = Some(header.height);
+            }
+        }
+
+        let mined_height = match mined_height {
+            None => return Ok(None),
+            Some(h) => h,
+        };
+
+        let mined_block_hash = if mined_height == tip_height {
+            best_block
+        } else {
+            prev_hashes
+                .into_iter()
+                .find(|(height, _)| *height == mined_height + 1)
+                .map(|(_, prev_hash)| prev_hash)
+        };
+
+        Ok(Some((mined_height, mined_block_hash)))
+    }
+
+    /// Send a request to the Base Node and wait for a response that satisfies `is_expected` or for the timeout to
+    /// expire, in which case `None` is returned
+    async fn query_base_node<F>(
+        &mut self,
+        request: BaseNodeRequestProto,
+        base_node_response_receiver: &mut Receiver<BaseNodeProto::BaseNodeServiceResponse>,
+        is_expected: F,
+    ) -> Result<Option<BaseNodeResponseProto>, TransactionServiceProtocolError>
+    where
+        F: Fn(&BaseNodeResponseProto) -> bool,
+    {
+        let service_request = BaseNodeProto::BaseNodeServiceRequest {
+            request_key: self.id,
+            request: Some(request),
+        };
+        self.resources
+            .outbound_message_service
+            .send_direct(
+                self.base_node_public_key.clone(),
+                OutboundEncryption::None,
+                OutboundDomainMessage::new(TariMessageType::BaseNodeRequest, service_request),
+            )
+            .await
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+
+        let mut delay = delay_for(self.timeout).fuse();
+        loop {
+            futures::select! {
+                base_node_response = base_node_response_receiver.select_next_some() => {
+                    // Responses to requests from earlier rounds share this protocol's request key so are skipped
+                    if let Some(response) = base_node_response.response {
+                        if is_expected(&response) {
+                            return Ok(Some(response));
+                        }
+                    }
+                },
+                () = delay => {
+                    return Ok(None);
+                },
+            }
+        }
+    }
+
+    /// The block this transaction was mined in is no longer part of the chain so revert the transaction to the
+    /// Broadcast state and resubmit it to the mempool so that it can be mined again.
+    async fn handle_reorg(
+        &mut self,
+        completed_tx: &CompletedTransaction,
+    ) -> Result<(), TransactionServiceProtocolError>
+    {
+        warn!(
+            target: LOG_TARGET,
+            "Block containing Mined Transaction (TxId: {}) has been reorged out of the chain, resubmitting to the \
+             mempool",
+            completed_tx.tx_id
+        );
+
+        self.resources
+            .db
+            .revert_mined_completed_transaction(completed_tx.tx_id)
+            .await
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+
+        let mempool_request = MempoolProto::MempoolServiceRequest {
+            request_key: self.id,
+            request: Some(MempoolProto::mempool_service_request::Request::SubmitTransaction(
+                completed_tx.transaction.clone().into(),
+            )),
+        };
+        self.resources
+            .outbound_message_service
+            .send_direct(
+                self.base_node_public_key.clone(),
+                OutboundEncryption::None,
+                OutboundDomainMessage::new(TariMessageType::MempoolRequest, mempool_request),
+            )
+            .await
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+
+        let _ = self
+            .resources
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionReorged(completed_tx.tx_id)))
+            .map_err(|e| {
+                trace!(
+                    target: LOG_TARGET,
+                    "Error sending event, usually because there are no subscribers: {:?}",
+                    e
+                );
+                e
+            });
+
+        Ok(())
+    }
+
+    fn publish_mined_request_timed_out(&self, tx_id: TxId) {
+        info!(
+            target: LOG_TARGET,
+            "Chain monitoring process timed out for Transaction TX_ID: {}", tx_id
+        );
+
+        let _ = self
+            .resources
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionMinedRequestTimedOut(tx_id)))
+            .map_err(|e| {
+                trace!(
+                    target: LOG_TARGET,
+                    "Error sending event, usually because there are no subscribers: {:?}",
+                    e
+                );
+                e
+            });
+    }
+
+    /// Pick up any change to the wallet's current base node so that the next round of requests is sent to it
+    fn update_base_node_public_key(&mut self) {
+        let current_base_node = self.resources.base_node_public_key_watch.borrow().clone();
+        if let Some(public_key) = current_base_node {
+            if public_key != self.base_node_public_key {
+                info!(
+                    target: LOG_TARGET,
+                    "Base node changed to {} for TxId: {}", public_key, self.tx_id
+                );
+                self.base_node_public_key = public_key;
+            }
+        }
+    }
 }
@@ -42,6 +42,7 @@ use tari_core::transactions::{
     SenderTransactionProtocol,
 };
 use tari_p2p::{services::liveness::LivenessEvent, tari_message::TariMessageType};
+use tokio::time::delay_for;
 
 const LOG_TARGET: &str = "wallet::transaction_service::protocols::send_protocol";
 
@@ -198,6 +199,24 @@ where TBackend: TransactionBackend + Clone + 'static
             .await
             .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
 
+        // The sender message is resent on a backoff schedule until a reply is received or the transaction expires. The
+        // expiry is measured from when the transaction was created so that it survives restarts of the protocol.
+        let mut resend_period = self.resources.config.transaction_resend_period;
+        let mut resend_delay = delay_for(resend_period).fuse();
+        let elapsed = Utc::now()
+            .naive_utc()
+            .signed_duration_since(outbound_tx.timestamp)
+            .to_std()
+            .unwrap_or_default();
+        let mut expiry_delay = delay_for(
+            self.resources
+                .config
+                .pending_transaction_cancellation_timeout
+                .checked_sub(elapsed)
+                .unwrap_or_default(),
+        )
+        .fuse();
+
         #[allow(unused_assignments)]
         let mut reply = None;
         loop {
@@ -228,6 +247,20 @@ where TBackend: TransactionBackend + Clone + 'static
                         }
                     }
                 },
+                () = resend_delay => {
+                    debug!(target: LOG_TARGET, "No Transaction Reply received for TxId: {} after {:?}, resending transaction.", self.id, resend_period);
+                    let msg = self.sender_protocol.get_single_round_message().map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+                    self.send_transaction(msg, false).await?;
+                    resend_period = std::cmp::min(resend_period * 2, self.resources.config.max_transaction_resend_period);
+                    resend_delay = delay_for(resend_period).fuse();
+                },
+                () = expiry_delay => {
+                    info!(target: LOG_TARGET, "No Transaction Reply received for TxId: {} before it expired", self.id);
+                    return Err(TransactionServiceProtocolError::new(
+                        self.id,
+                        TransactionServiceError::TransactionExpired,
+                    ));
+                },
                 _ = cancellation_receiver => {
                     info!(target: LOG_TARGET, "Cancelling Transaction Send Protocol for TxId: {}", self.id);
                     return Err(TransactionServiceProtocolError::new(
@@ -277,6 +310,9 @@ where TBackend: TransactionBackend + Clone + 'static
             status: TransactionStatus::Completed,
             message: outbound_tx.message.clone(),
             timestamp: Utc::now().naive_utc(),
+            mined_height: None,
+            mined_block_hash: None,
+            confirmations: None,
         };
 
         self.resources
@@ -376,11 +412,13 @@ where TBackend: TransactionBackend + Clone + 'static
         Ok(self.id)
     }
 
-    /// Contains all the logic to initially send the transaction to the recipient directly and via SAF
+    /// Contains all the logic to send the transaction to the recipient directly and via SAF. Send result events are
+    /// only published for the `initial_send`. If the initial send fails both directly and via SAF the pending
+    /// transaction is cancelled, a failed resend is only logged and will be retried.
     async fn send_transaction(
         &mut self,
         msg: SingleRoundSenderData,
-        send_events: bool,
+        initial_send: bool,
     ) -> Result<(), TransactionServiceProtocolError>
     {
         let proto_message = proto::TransactionSenderMessage::single(msg.into());
@@ -424,7 +462,7 @@ where TBackend: TransactionBackend + Clone + 'static
                                     target: LOG_TARGET,
                                     "Direct Send process for TX_ID: {} was unsuccessful and no message was sent", tx_id
                                 );
-                                if send_events {
+                                if initial_send {
                                     let _ = event_publisher
                                         .send(Arc::new(TransactionEvent::TransactionDirectSendResult(tx_id, false)));
                                 }
@@ -433,7 +471,7 @@ where TBackend: TransactionBackend + Clone + 'static
                     });
                 },
                 _ => {
-                    if send_events {
+                    if initial_send {
                         let _ = self
                             .resources
                             .event_publisher
@@ -447,7 +485,7 @@ where TBackend: TransactionBackend + Clone + 'static
             },
             Err(e) => {
                 error!(target: LOG_TARGET, "Direct Transaction Send failed: {:?}", e);
-                if send_events {
+                if initial_send {
                     let _ = self
                         .resources
                         .event_publisher
@@ -506,6 +544,13 @@ where TBackend: TransactionBackend + Clone + 'static
         };
 
         if !direct_send_success && !store_and_forward_send_success {
+            if !initial_send {
+                warn!(
+                    target: LOG_TARGET,
+                    "Failed to resend Transaction (TxId: {}) both Directly or via Store and Forward", self.id
+                );
+                return Ok(());
+            }
             error!(
                 target: LOG_TARGET,
                 "Failed to Send Transaction (TxId: {}) both Directly or via Store and Forward. Pending Transaction \
@@ -518,7 +563,7 @@ where TBackend: TransactionBackend + Clone + 'static
                     "Failed to Cancel TX_ID: {} after failed sending attempt with error {:?}", self.id, e
                 );
             };
-            if send_events {
+            if initial_send {
                 let _ =
                     self.resources
                         .event_publisher
@@ -531,7 +576,7 @@ where TBackend: TransactionBackend + Clone + 'static
                 TransactionServiceError::OutboundSendFailure,
             ));
         }
-        if send_events {
+        if initial_send {
             let _ = self
                 .resources
                 .event_publisher
@@ -21,11 +21,22 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    output_manager_service::{handle::OutputManagerHandle, TxId},
+    output_manager_service::{
+        handle::{OutputManagerEvent, OutputManagerHandle},
+        storage::database::DEFAULT_ACCOUNT,
+        TxId,
+    },
     transaction_service::{
         config::TransactionServiceConfig,
         error::{TransactionServiceError, TransactionServiceProtocolError},
         handle::{TransactionEvent, TransactionEventSender, TransactionServiceRequest, TransactionServiceResponse},
+        history::{TransactionHistoryFilter, TransactionHistoryRecord},
+        offline::{
+            OfflineFinalizedTransaction,
+            OfflineRecipientReply,
+            OfflineSenderMessage,
+            UnsignedTransactionRequest,
+        },
         protocols::{
             transaction_broadcast_protocol::TransactionBroadcastProtocol,
             transaction_chain_monitoring_protocol::TransactionChainMonitoringProtocol,
@@ -45,6 +56,7 @@ use crate::{
 use chrono::Utc;
 use futures::{
     channel::{mpsc, mpsc::Sender, oneshot},
+    future::Either,
     pin_mut,
     stream::FuturesUnordered,
     SinkExt,
@@ -71,23 +83,26 @@ use tari_comms_dht::{
 use tari_core::transactions::{tari_amount::uT, types::BlindingFactor};
 use tari_core::{
     base_node::proto::base_node as BaseNodeProto,
-    mempool::{proto::mempool as MempoolProto, service::MempoolServiceResponse},
+    mempool::{
+        proto::mempool as MempoolProto,
+        service::{MempoolResponse, MempoolServiceResponse},
+    },
     transactions::{
         tari_amount::MicroTari,
-        transaction::{KernelFeatures, OutputFeatures, OutputFlags, Transaction},
+        transaction::{KernelFeatures, OutputFeatures, OutputFlags, Transaction, TransactionError},
         transaction_protocol::{
             proto,
             recipient::{RecipientSignedMessage, RecipientState},
             sender::TransactionSenderMessage,
         },
-        types::{CryptoFactories, PrivateKey},
+        types::{Commitment, CryptoFactories, PrivateKey},
         ReceiverTransactionProtocol,
     },
 };
 use tari_crypto::{commitment::HomomorphicCommitmentFactory, keys::SecretKey};
 use tari_p2p::{domain_message::DomainMessage, services::liveness::LivenessHandle, tari_message::TariMessageType};
 use tari_service_framework::{reply_channel, reply_channel::Receiver};
-use tokio::task::JoinHandle;
+use tokio::{sync::watch, task::JoinHandle, time};
 
 const LOG_TARGET: &str = "wallet::transaction_service::service";
 
@@ -114,8 +129,15 @@ pub struct PendingCoinbaseSpendingKey {
 /// `pending_inbound_transactions` - List of transaction protocols that have been received and responded to.
 /// `completed_transaction` - List of sent transactions that have been responded to and are completed.
 
-pub struct TransactionService<TTxStream, TTxReplyStream, TTxFinalizedStream, MReplyStream, BNResponseStream, TBackend>
-where TBackend: TransactionBackend + Clone + 'static
+pub struct TransactionService<
+    TTxStream,
+    TTxReplyStream,
+    TTxFinalizedStream,
+    TTxCancelledStream,
+    MReplyStream,
+    BNResponseStream,
+    TBackend,
+> where TBackend: TransactionBackend + Clone + 'static
 {
     config: TransactionServiceConfig,
     db: TransactionDatabase<TBackend>,
@@ -125,6 +147,7 @@ where TBackend: TransactionBackend + Clone + 'static
     transaction_stream: Option<TTxStream>,
     transaction_reply_stream: Option<TTxReplyStream>,
     transaction_finalized_stream: Option<TTxFinalizedStream>,
+    transaction_cancelled_stream: Option<TTxCancelledStream>,
     mempool_response_stream: Option<MReplyStream>,
     base_node_response_stream: Option<BNResponseStream>,
     request_stream: Option<
@@ -134,20 +157,31 @@ where TBackend: TransactionBackend + Clone + 'static
     node_identity: Arc<NodeIdentity>,
     factories: CryptoFactories,
     base_node_public_key: Option<CommsPublicKey>,
+    base_node_public_key_publisher: watch::Sender<Option<CommsPublicKey>>,
     service_resources: TransactionServiceResources<TBackend>,
     pending_transaction_reply_senders: HashMap<TxId, Sender<(CommsPublicKey, RecipientSignedMessage)>>,
     mempool_response_senders: HashMap<u64, Sender<MempoolServiceResponse>>,
     base_node_response_senders: HashMap<u64, Sender<BaseNodeProto::BaseNodeServiceResponse>>,
     send_transaction_cancellation_senders: HashMap<u64, oneshot::Sender<()>>,
+    pending_consolidation_stats_key: Option<u64>,
 }
 
 #[allow(clippy::too_many_arguments)]
-impl<TTxStream, TTxReplyStream, TTxFinalizedStream, MReplyStream, BNResponseStream, TBackend>
-    TransactionService<TTxStream, TTxReplyStream, TTxFinalizedStream, MReplyStream, BNResponseStream, TBackend>
+impl<TTxStream, TTxReplyStream, TTxFinalizedStream, TTxCancelledStream, MReplyStream, BNResponseStream, TBackend>
+    TransactionService<
+        TTxStream,
+        TTxReplyStream,
+        TTxFinalizedStream,
+        TTxCancelledStream,
+        MReplyStream,
+        BNResponseStream,
+        TBackend,
+    >
 where
     TTxStream: Stream<Item = DomainMessage<proto::TransactionSenderMessage>>,
     TTxReplyStream: Stream<Item = DomainMessage<proto::RecipientSignedMessage>>,
     TTxFinalizedStream: Stream<Item = DomainMessage<proto::TransactionFinalizedMessage>>,
+    TTxCancelledStream: Stream<Item = DomainMessage<proto::TransactionCancelledMessage>>,
     MReplyStream: Stream<Item = DomainMessage<MempoolProto::MempoolServiceResponse>>,
     BNResponseStream: Stream<Item = DomainMessage<BaseNodeProto::BaseNodeServiceResponse>>,
     TBackend: TransactionBackend + Clone + 'static,
@@ -162,6 +196,7 @@ where
         transaction_stream: TTxStream,
         transaction_reply_stream: TTxReplyStream,
         transaction_finalized_stream: TTxFinalizedStream,
+        transaction_cancelled_stream: TTxCancelledStream,
         mempool_response_stream: MReplyStream,
         base_node_response_stream: BNResponseStream,
         output_manager_service: OutputManagerHandle,
@@ -172,9 +207,11 @@ where
         factories: CryptoFactories,
     ) -> Self
     {
+        let (base_node_public_key_publisher, base_node_public_key_watch) = watch::channel(None);
         // Collect the resources that all protocols will need so that they can be neatly cloned as the protocols are
         // spawned.
         let service_resources = TransactionServiceResources {
+            config: config.clone(),
             db: db.clone(),
             output_manager_service: output_manager_service.clone(),
             outbound_message_service: outbound_message_service.clone(),
@@ -182,6 +219,7 @@ where
             event_publisher: event_publisher.clone(),
             node_identity: node_identity.clone(),
             factories: factories.clone(),
+            base_node_public_key_watch,
         };
         TransactionService {
             config,
@@ -192,6 +230,7 @@ where
             transaction_stream: Some(transaction_stream),
             transaction_reply_stream: Some(transaction_reply_stream),
             transaction_finalized_stream: Some(transaction_finalized_stream),
+            transaction_cancelled_stream: Some(transaction_cancelled_stream),
             mempool_response_stream: Some(mempool_response_stream),
             base_node_response_stream: Some(base_node_response_stream),
             request_stream: Some(request_stream),
@@ -199,11 +238,13 @@ where
             node_identity,
             factories,
             base_node_public_key: None,
+            base_node_public_key_publisher,
             service_resources,
             pending_transaction_reply_senders: HashMap::new(),
             mempool_response_senders: HashMap::new(),
             base_node_response_senders: HashMap::new(),
             send_transaction_cancellation_senders: HashMap::new(),
+            pending_consolidation_stats_key: None,
         }
     }
 
@@ -233,6 +274,12 @@ where
             .expect("Transaction Service initialized without transaction_finalized_stream")
             .fuse();
         pin_mut!(transaction_finalized_stream);
+        let transaction_cancelled_stream = self
+            .transaction_cancelled_stream
+            .take()
+            .expect("Transaction Service initialized without transaction_cancelled_stream")
+            .fuse();
+        pin_mut!(transaction_cancelled_stream);
         let mempool_response_stream = self
             .mempool_response_stream
             .take()
@@ -245,6 +292,16 @@ where
             .expect("Transaction Service initialized without base_node_response_stream")
             .fuse();
         pin_mut!(base_node_response_stream);
+        let mut output_manager_event_stream = self.output_manager_service.get_event_stream_fused();
+
+        let mut consolidation_tick = match self.config.consolidation_policy.as_ref() {
+            Some(policy) => Either::Left(time::interval_at(
+                time::Instant::now() + policy.check_interval,
+                policy.check_interval,
+            )),
+            None => Either::Right(futures::stream::iter(Vec::new())),
+        }
+        .fuse();
 
         let mut send_transaction_protocol_handles: FuturesUnordered<
             JoinHandle<Result<u64, TransactionServiceProtocolError>>,
@@ -321,10 +378,19 @@ where
                     }
                 },
                 // Incoming messages from the Comms layer
+                msg = transaction_cancelled_stream.select_next_some() => {
+                    trace!(target: LOG_TARGET, "Handling Transaction Cancelled Message");
+                    let (origin_public_key, inner_msg) = msg.into_origin_and_inner();
+                    let _ = self.accept_transaction_cancellation(origin_public_key, inner_msg).await.or_else(|err| {
+                        error!(target: LOG_TARGET, "Failed to handle incoming Transaction Cancelled message: {:?} for NodeID: {}", err , self.node_identity.node_id().short_str());
+                        Err(err)
+                    });
+                },
+                // Incoming messages from the Comms layer
                 msg = mempool_response_stream.select_next_some() => {
                     trace!(target: LOG_TARGET, "Handling Mempool Response");
                     let (origin_public_key, inner_msg) = msg.into_origin_and_inner();
-                    let _ = self.handle_mempool_response(inner_msg).await.or_else(|resp| {
+                    let _ = self.handle_mempool_response(inner_msg, &mut transaction_broadcast_protocol_handles).await.or_else(|resp| {
                         error!(target: LOG_TARGET, "Error handling mempool service response: {:?}", resp);
                         Err(resp)
                     });
@@ -338,6 +404,22 @@ where
                         Err(resp)
                     });
                 }
+                // Events from the Output Manager Service
+                event = output_manager_event_stream.select_next_some() => {
+                    if let OutputManagerEvent::ReceivedOneSidedPayment((tx_id, tx, fee, amount)) = (*event).clone() {
+                        let _ = self.add_one_sided_payment_transaction(&mut transaction_broadcast_protocol_handles, tx_id, tx, fee, amount).await.or_else(|resp| {
+                            error!(target: LOG_TARGET, "Error recording received one-sided payment: {:?}", resp);
+                            Err(resp)
+                        });
+                    }
+                }
+                // Periodic check of the UTXO consolidation policy
+                _ = consolidation_tick.select_next_some() => {
+                    let _ = self.check_consolidation_policy().await.or_else(|resp| {
+                        error!(target: LOG_TARGET, "Error checking the UTXO consolidation policy: {:?}", resp);
+                        Err(resp)
+                    });
+                }
                 join_result = send_transaction_protocol_handles.select_next_some() => {
                     trace!(target: LOG_TARGET, "Send Protocol for Transaction has ended with result {:?}", join_result);
                     match join_result {
@@ -386,7 +468,72 @@ where
                     dest_pubkey,
                     amount,
                     fee_per_gram,
+                    None,
+                    0,
                     message,
+                    None,
+                    DEFAULT_ACCOUNT.to_string(),
+                    send_transaction_join_handles,
+                )
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::SendTransactionWithInputs((
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                message,
+                inputs,
+            )) => self
+                .send_transaction(
+                    dest_pubkey,
+                    amount,
+                    fee_per_gram,
+                    None,
+                    0,
+                    message,
+                    Some(inputs),
+                    DEFAULT_ACCOUNT.to_string(),
+                    send_transaction_join_handles,
+                )
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::SendTimeLockedTransaction((
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                lock_height,
+                maturity,
+                message,
+            )) => self
+                .send_transaction(
+                    dest_pubkey,
+                    amount,
+                    fee_per_gram,
+                    lock_height,
+                    maturity,
+                    message,
+                    None,
+                    DEFAULT_ACCOUNT.to_string(),
+                    send_transaction_join_handles,
+                )
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::SendTransactionFromAccount((
+                account,
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                message,
+            )) => self
+                .send_transaction(
+                    dest_pubkey,
+                    amount,
+                    fee_per_gram,
+                    None,
+                    0,
+                    message,
+                    None,
+                    account,
                     send_transaction_join_handles,
                 )
                 .await
@@ -406,6 +553,11 @@ where
             TransactionServiceRequest::GetCompletedTransactions => Ok(
                 TransactionServiceResponse::CompletedTransactions(self.get_completed_transactions().await?),
             ),
+            TransactionServiceRequest::GetCompletedTransactionsForAccount(account) => {
+                Ok(TransactionServiceResponse::CompletedTransactions(
+                    self.get_completed_transactions_for_account(account).await?,
+                ))
+            },
             TransactionServiceRequest::RequestCoinbaseSpendingKey((amount, maturity_height)) => Ok(
                 TransactionServiceResponse::CoinbaseKey(self.request_coinbase_key(amount, maturity_height).await?),
             ),
@@ -435,6 +587,36 @@ where
                 .submit_transaction(transaction_broadcast_join_handles, tx_id, tx, fee, amount, message)
                 .await
                 .map(|_| TransactionServiceResponse::TransactionSubmitted),
+            TransactionServiceRequest::SendOneSidedTransaction((dest_pubkey, amount, fee_per_gram, message)) => self
+                .send_one_sided_transaction(
+                    dest_pubkey,
+                    amount,
+                    fee_per_gram,
+                    message,
+                    transaction_broadcast_join_handles,
+                )
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::SignOfflineTransaction(request) => self
+                .sign_offline_transaction(request)
+                .await
+                .map(|m| TransactionServiceResponse::OfflineTransactionSigned(Box::new(m))),
+            TransactionServiceRequest::ReplyToOfflineTransaction(sender_message) => self
+                .reply_to_offline_transaction(sender_message)
+                .await
+                .map(|r| TransactionServiceResponse::OfflineTransactionReply(Box::new(r))),
+            TransactionServiceRequest::FinalizeOfflineTransaction(reply) => self
+                .finalize_offline_transaction(reply)
+                .await
+                .map(|t| TransactionServiceResponse::OfflineTransactionFinalized(Box::new(t))),
+            TransactionServiceRequest::ImportOfflineTransaction(transaction) => self
+                .import_offline_transaction(transaction, transaction_broadcast_join_handles)
+                .await
+                .map(TransactionServiceResponse::OfflineTransactionImported),
+            TransactionServiceRequest::GetTransactionHistory(filter) => self
+                .get_transaction_history(filter)
+                .await
+                .map(TransactionServiceResponse::TransactionHistory),
             #[cfg(feature = "test_harness")]
             TransactionServiceRequest::CompletePendingOutboundTransaction(completed_transaction) => {
                 self.complete_pending_outbound_transaction(completed_transaction)
@@ -469,18 +651,35 @@ where
     /// 'dest_pubkey': The Comms pubkey of the recipient node
     /// 'amount': The amount of Tari to send to the recipient
     /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
+    /// 'lock_height': The block height before which the transaction cannot be mined
+    /// 'recipient_maturity': The block height from which the recipient's output can be spent
+    /// 'selected_inputs': The commitments of the unspent outputs to spend, if the inputs should not be selected
+    /// automatically
+    /// 'account': The wallet account whose outputs fund the transaction
     pub async fn send_transaction(
         &mut self,
         dest_pubkey: CommsPublicKey,
         amount: MicroTari,
         fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        recipient_maturity: u64,
         message: String,
+        selected_inputs: Option<Vec<Commitment>>,
+        account: String,
         join_handles: &mut FuturesUnordered<JoinHandle<Result<u64, TransactionServiceProtocolError>>>,
     ) -> Result<TxId, TransactionServiceError>
     {
         let sender_protocol = self
             .output_manager_service
-            .prepare_transaction_to_send(amount, fee_per_gram, None, message.clone())
+            .prepare_transaction_to_send_from_account(
+                account,
+                amount,
+                fee_per_gram,
+                lock_height,
+                recipient_maturity,
+                message.clone(),
+                selected_inputs,
+            )
             .await?;
 
         let tx_id = sender_protocol.get_tx_id()?;
@@ -567,6 +766,19 @@ where
                     id
                 );
             },
+            Err(TransactionServiceProtocolError {
+                id,
+                error: TransactionServiceError::TransactionExpired,
+            }) => {
+                tx_id = id;
+                let _ = self.pending_transaction_reply_senders.remove(&id);
+                let _ = self.send_transaction_cancellation_senders.remove(&id);
+                info!(
+                    target: LOG_TARGET,
+                    "Pending Outbound Transaction (TxId: {}) expired without a reply and will be cancelled", id
+                );
+                let _ = self.cancel_transaction(id).await;
+            },
             Err(TransactionServiceProtocolError { id, error }) => {
                 tx_id = id;
                 let _ = self.pending_transaction_reply_senders.remove(&id);
@@ -613,8 +825,15 @@ where
         let _ = self.liveness_service.remove_node_id(node_id).await;
     }
 
-    /// Cancel a pending outbound transaction
+    /// Cancel a pending transaction. If it is an outbound transaction the recipient is notified of the cancellation.
     async fn cancel_transaction(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
+        let recipient_pubkey = self
+            .db
+            .get_pending_outbound_transaction(tx_id)
+            .await
+            .ok()
+            .map(|tx| tx.destination_public_key);
+
         self.db.cancel_pending_transaction(tx_id).await.map_err(|e| {
             error!(
                 target: LOG_TARGET,
@@ -644,6 +863,102 @@ where
 
         info!(target: LOG_TARGET, "Pending Transaction (TxId: {}) cancelled", tx_id);
 
+        if let Some(recipient_pubkey) = recipient_pubkey {
+            let _ = self
+                .send_transaction_cancelled_message(tx_id, recipient_pubkey)
+                .await
+                .map_err(|e| {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Error notifying the recipient of the cancellation of Transaction (TxId: {}): {:?}", tx_id, e
+                    );
+                    e
+                });
+        }
+
+        Ok(())
+    }
+
+    /// Notify the recipient of a pending outbound transaction, directly and via SAF, that it has been cancelled
+    async fn send_transaction_cancelled_message(
+        &mut self,
+        tx_id: TxId,
+        recipient_pubkey: CommsPublicKey,
+    ) -> Result<(), TransactionServiceError>
+    {
+        let proto_message = proto::TransactionCancelledMessage { tx_id };
+        self.outbound_message_service
+            .send_direct(
+                recipient_pubkey.clone(),
+                OutboundEncryption::None,
+                OutboundDomainMessage::new(TariMessageType::TransactionCancelled, proto_message.clone()),
+            )
+            .await?;
+
+        self.outbound_message_service
+            .propagate(
+                NodeDestination::NodeId(Box::new(NodeId::from_key(&recipient_pubkey)?)),
+                OutboundEncryption::EncryptFor(Box::new(recipient_pubkey)),
+                vec![],
+                OutboundDomainMessage::new(TariMessageType::TransactionCancelled, proto_message),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Handle the notification from a sender that a transaction it sent has been cancelled. The pending inbound
+    /// transaction is cancelled and the output it would have paid to is released.
+    pub async fn accept_transaction_cancellation(
+        &mut self,
+        source_pubkey: CommsPublicKey,
+        transaction_cancelled: proto::TransactionCancelledMessage,
+    ) -> Result<(), TransactionServiceError>
+    {
+        let tx_id = transaction_cancelled.tx_id;
+        let inbound_tx = match self.db.get_pending_inbound_transaction(tx_id).await {
+            Ok(tx) => tx,
+            Err(_e) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "TxId for received Transaction Cancellation does not exist in Pending Inbound Transactions, could \
+                     be a repeat Store and Forward message"
+                );
+                return Ok(());
+            },
+        };
+
+        if inbound_tx.source_public_key != source_pubkey {
+            error!(
+                target: LOG_TARGET,
+                "Transaction Cancellation Source Public Key does not correspond to stored value"
+            );
+            return Err(TransactionServiceError::InvalidSourcePublicKey);
+        }
+        if inbound_tx.status == TransactionStatus::Cancelled {
+            return Ok(());
+        }
+
+        self.db.cancel_pending_transaction(tx_id).await?;
+        self.output_manager_service.cancel_transaction(tx_id).await?;
+
+        info!(
+            target: LOG_TARGET,
+            "Pending Inbound Transaction (TxId: {}) cancelled by the sender {}", tx_id, source_pubkey
+        );
+
+        let _ = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionCancelled(tx_id)))
+            .map_err(|e| {
+                trace!(
+                    target: LOG_TARGET,
+                    "Error sending event, usually because there are no subscribers: {:?}",
+                    e
+                );
+                e
+            });
+
         Ok(())
     }
 
@@ -698,43 +1013,9 @@ where
             .try_into()
             .map_err(TransactionServiceError::InvalidMessageError)?;
 
-        // Currently we will only reply to a Single sender transaction protocol
-        if let TransactionSenderMessage::Single(data) = sender_message.clone() {
-            trace!(
-                target: LOG_TARGET,
-                "Transaction (TxId: {}) received from {}",
-                data.tx_id,
-                source_pubkey
-            );
-            // Check this is not a repeat message i.e. tx_id doesn't already exist in our pending or completed
-            // transactions
-            if self.db.transaction_exists(data.tx_id).await? {
-                trace!(
-                    target: LOG_TARGET,
-                    "Transaction (TxId: {}) already present in database.",
-                    data.tx_id
-                );
-                return Err(TransactionServiceError::RepeatedMessageError);
-            }
-
-            let amount = data.amount;
-
-            let spending_key = self
-                .output_manager_service
-                .get_recipient_spending_key(data.tx_id, data.amount)
-                .await?;
-            let nonce = PrivateKey::random(&mut OsRng);
-
-            let rtp = ReceiverTransactionProtocol::new(
-                sender_message,
-                nonce,
-                spending_key,
-                OutputFeatures::default(),
-                &self.factories,
-            );
-            let recipient_reply = rtp.get_signed_data()?.clone();
-
-            let tx_id = recipient_reply.tx_id;
+        if let Some((recipient_reply, inbound_transaction)) =
+            self.create_recipient_reply(&source_pubkey, sender_message).await?
+        {
             let proto_message: proto::RecipientSignedMessage = recipient_reply.into();
             self.outbound_message_service
                 .send_direct(
@@ -753,41 +1034,104 @@ where
                 )
                 .await?;
 
-            // Otherwise add it to our pending transaction list and return reply
-            let inbound_transaction = InboundTransaction {
-                tx_id,
-                source_public_key: source_pubkey.clone(),
-                amount,
-                receiver_protocol: rtp.clone(),
-                status: TransactionStatus::Pending,
-                message: data.message.clone(),
-                timestamp: Utc::now().naive_utc(),
-            };
-            self.db
-                .add_pending_inbound_transaction(tx_id, inbound_transaction.clone())
-                .await?;
-
             info!(
                 target: LOG_TARGET,
-                "Transaction with TX_ID = {} received from {}. Reply Sent", tx_id, source_pubkey,
+                "Transaction with TX_ID = {} received from {}. Reply Sent", inbound_transaction.tx_id, source_pubkey,
             );
-            info!(
+
+            self.add_received_transaction(inbound_transaction).await?;
+        }
+        Ok(())
+    }
+
+    /// Generate the reply to a sender message without sending it. The pending inbound transaction that should be
+    /// stored once the reply has been delivered is returned alongside the reply. Only Single round sender messages are
+    /// replied to, for any other message `None` is returned.
+    async fn create_recipient_reply(
+        &mut self,
+        source_pubkey: &CommsPublicKey,
+        sender_message: TransactionSenderMessage,
+    ) -> Result<Option<(RecipientSignedMessage, InboundTransaction)>, TransactionServiceError>
+    {
+        // Currently we will only reply to a Single sender transaction protocol
+        let data = match sender_message.clone() {
+            TransactionSenderMessage::Single(data) => data,
+            _ => return Ok(None),
+        };
+        trace!(
+            target: LOG_TARGET,
+            "Transaction (TxId: {}) received from {}",
+            data.tx_id,
+            source_pubkey
+        );
+        // Check this is not a repeat message i.e. tx_id doesn't already exist in our pending or completed
+        // transactions
+        if self.db.transaction_exists(data.tx_id).await? {
+            trace!(
                 target: LOG_TARGET,
-                "Transaction (TX_ID: {}) - Amount: {} - Message: {}", tx_id, amount, data.message
+                "Transaction (TxId: {}) already present in database.",
+                data.tx_id
             );
+            return Err(TransactionServiceError::RepeatedMessageError);
+        }
 
-            let _ = self
-                .event_publisher
-                .send(Arc::new(TransactionEvent::ReceivedTransaction(tx_id)))
-                .map_err(|e| {
-                    trace!(
-                        target: LOG_TARGET,
-                        "Error sending event, usually because there are no subscribers: {:?}",
-                        e
-                    );
+        let spending_key = self
+            .output_manager_service
+            .get_recipient_spending_key_with_maturity(data.tx_id, data.amount, data.maturity)
+            .await?;
+        let nonce = PrivateKey::random(&mut OsRng);
+
+        let rtp = ReceiverTransactionProtocol::new(
+            sender_message,
+            nonce,
+            spending_key,
+            OutputFeatures::with_maturity(data.maturity),
+            &self.factories,
+        );
+        let recipient_reply = rtp.get_signed_data()?.clone();
+
+        let inbound_transaction = InboundTransaction {
+            tx_id: recipient_reply.tx_id,
+            source_public_key: source_pubkey.clone(),
+            amount: data.amount,
+            receiver_protocol: rtp,
+            status: TransactionStatus::Pending,
+            message: data.message.clone(),
+            timestamp: Utc::now().naive_utc(),
+        };
+
+        Ok(Some((recipient_reply, inbound_transaction)))
+    }
+
+    /// Add a transaction that has been replied to to the pending inbound transactions
+    async fn add_received_transaction(
+        &mut self,
+        inbound_transaction: InboundTransaction,
+    ) -> Result<(), TransactionServiceError>
+    {
+        let tx_id = inbound_transaction.tx_id;
+        info!(
+            target: LOG_TARGET,
+            "Transaction (TX_ID: {}) - Amount: {} - Message: {}",
+            tx_id,
+            inbound_transaction.amount,
+            inbound_transaction.message
+        );
+        self.db
+            .add_pending_inbound_transaction(tx_id, inbound_transaction)
+            .await?;
+
+        let _ = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::ReceivedTransaction(tx_id)))
+            .map_err(|e| {
+                trace!(
+                    target: LOG_TARGET,
+                    "Error sending event, usually because there are no subscribers: {:?}",
                     e
-                });
-        }
+                );
+                e
+            });
         Ok(())
     }
 
@@ -871,6 +1215,9 @@ where
             status: TransactionStatus::Completed,
             message: inbound_tx.message.clone(),
             timestamp: inbound_tx.timestamp,
+            mined_height: None,
+            mined_block_hash: None,
+            confirmations: None,
         };
 
         self.db
@@ -987,6 +1334,9 @@ where
                 status: TransactionStatus::Completed,
                 message: "Coinbase Transaction".to_string(),
                 timestamp: Utc::now().naive_utc(),
+                mined_height: None,
+                mined_block_hash: None,
+                confirmations: None,
             })
             .await?;
 
@@ -1028,9 +1378,67 @@ where
         Ok(self.db.get_completed_transactions().await?)
     }
 
-    /// Add a base node public key to the list that will be used to broadcast transactions and monitor the base chain
-    /// for the presence of spendable outputs. If this is the first time the base node public key is set do the initial
-    /// mempool broadcast
+    /// Fetch the completed transactions that belong to the named account. Transactions that the Output Manager has not
+    /// assigned to an account belong to the default account.
+    pub async fn get_completed_transactions_for_account(
+        &mut self,
+        account: String,
+    ) -> Result<HashMap<u64, CompletedTransaction>, TransactionServiceError>
+    {
+        let transaction_accounts = self.output_manager_service.get_transaction_accounts().await?;
+        Ok(self
+            .db
+            .get_completed_transactions()
+            .await?
+            .into_iter()
+            .filter(|(tx_id, _)| {
+                transaction_accounts
+                    .get(tx_id)
+                    .map(String::as_str)
+                    .unwrap_or(DEFAULT_ACCOUNT) ==
+                    account
+            })
+            .collect())
+    }
+
+    /// Gather the pending inbound, pending outbound and completed transactions into history records, sorted by
+    /// timestamp
+    pub async fn get_transaction_history(
+        &self,
+        filter: TransactionHistoryFilter,
+    ) -> Result<Vec<TransactionHistoryRecord>, TransactionServiceError>
+    {
+        let own_public_key = self.node_identity.public_key();
+        let mut records = self
+            .db
+            .get_pending_inbound_transactions()
+            .await?
+            .values()
+            .map(TransactionHistoryRecord::from_inbound)
+            .chain(
+                self.db
+                    .get_pending_outbound_transactions()
+                    .await?
+                    .values()
+                    .map(TransactionHistoryRecord::from_outbound),
+            )
+            .chain(
+                self.db
+                    .get_completed_transactions()
+                    .await?
+                    .values()
+                    .map(|tx| TransactionHistoryRecord::from_completed(tx, own_public_key)),
+            )
+            .filter(|r| filter.matches(r))
+            .collect::<Vec<_>>();
+        records.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.tx_id.cmp(&b.tx_id)));
+        Ok(records)
+    }
+
+    /// Set the base node public key that will be used to broadcast transactions and monitor the base chain for the
+    /// presence of spendable outputs. If this is the first time the base node public key is set do the initial mempool
+    /// broadcast. If the base node changes the running protocols will switch to the new base node on their next round
+    /// and any Completed transactions are broadcast to the new base node.
     async fn set_base_node_public_key(
         &mut self,
         base_node_public_key: CommsPublicKey,
@@ -1041,7 +1449,17 @@ where
     {
         let startup_broadcast = self.base_node_public_key.is_none();
 
-        self.base_node_public_key = Some(base_node_public_key);
+        self.base_node_public_key = Some(base_node_public_key.clone());
+        if self
+            .base_node_public_key_publisher
+            .broadcast(Some(base_node_public_key))
+            .is_err()
+        {
+            error!(
+                target: LOG_TARGET,
+                "Could not publish the new base node public key to the protocols"
+            );
+        }
 
         if startup_broadcast {
             let _ = self
@@ -1076,6 +1494,17 @@ where
                     );
                     Err(resp)
                 });
+        } else {
+            let _ = self
+                .broadcast_all_completed_transactions_to_mempool(broadcast_join_handles)
+                .await
+                .or_else(|resp| {
+                    error!(
+                        target: LOG_TARGET,
+                        "Error broadcasting all completed transactions to new base node: {:?}", resp
+                    );
+                    Err(resp)
+                });
         }
         Ok(())
     }
@@ -1142,11 +1571,24 @@ where
     pub async fn handle_mempool_response(
         &mut self,
         response: MempoolProto::MempoolServiceResponse,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
     ) -> Result<(), TransactionServiceError>
     {
         let response = MempoolServiceResponse::try_from(response).unwrap();
         trace!(target: LOG_TARGET, "Received Mempool Response: {:?}", response);
 
+        if self.pending_consolidation_stats_key == Some(response.request_key) {
+            self.pending_consolidation_stats_key = None;
+            if let MempoolResponse::Stats(stats) = response.response {
+                return self
+                    .consolidate_if_fees_are_low(stats.total_weight, transaction_broadcast_join_handles)
+                    .await;
+            }
+            return Ok(());
+        }
+
         let tx_id = response.request_key;
 
         let sender = match self.mempool_response_senders.get_mut(&tx_id) {
@@ -1195,7 +1637,7 @@ where
                             TransactionServiceError::InvalidCompletedTransaction => trace!(
                                 target: LOG_TARGET,
                                 "Not starting Chain monitoring protocol as transaction cannot be found, either \
-                                 cancelled or already mined."
+                                 cancelled or already confirmed."
                             ),
                             _ => error!(
                                 target: LOG_TARGET,
@@ -1230,7 +1672,7 @@ where
     {
         let completed_tx = self.db.get_completed_transaction(tx_id).await?;
 
-        if completed_tx.status != TransactionStatus::Broadcast || completed_tx.transaction.body.kernels().is_empty() {
+        if !self.requires_chain_monitoring(&completed_tx) || completed_tx.transaction.body.kernels().is_empty() {
             return Err(TransactionServiceError::InvalidCompletedTransaction);
         }
 
@@ -1318,7 +1760,7 @@ where
     }
 
     /// Go through all completed transactions that have been broadcast and start querying the base_node to see if they
-    /// have been mined
+    /// have been mined or, if already mined, whether they have enough confirmations
     async fn start_chain_monitoring_for_all_broadcast_transactions(
         &mut self,
         join_handles: &mut FuturesUnordered<JoinHandle<Result<u64, TransactionServiceProtocolError>>>,
@@ -1330,15 +1772,35 @@ where
         );
         let completed_txs = self.db.get_completed_transactions().await?;
         for completed_tx in completed_txs.values() {
-            if completed_tx.status == TransactionStatus::Broadcast {
-                self.start_transaction_chain_monitoring_protocol(completed_tx.tx_id, join_handles)
-                    .await?;
+            if self.requires_chain_monitoring(completed_tx) {
+                let _ = self
+                    .start_transaction_chain_monitoring_protocol(completed_tx.tx_id, join_handles)
+                    .await
+                    .map_err(|e| {
+                        error!(
+                            target: LOG_TARGET,
+                            "Could not start chain monitoring for transaction (TxId: {}): {:?}", completed_tx.tx_id, e
+                        );
+                        e
+                    });
             }
         }
 
         Ok(())
     }
 
+    /// A transaction needs to be monitored on the chain while it is Broadcast, or once Mined until it has the required
+    /// number of confirmations
+    fn requires_chain_monitoring(&self, completed_tx: &CompletedTransaction) -> bool {
+        match completed_tx.status {
+            TransactionStatus::Broadcast => true,
+            TransactionStatus::Mined => {
+                completed_tx.confirmations.unwrap_or(0) < self.config.num_confirmations_required
+            },
+            _ => false,
+        }
+    }
+
     /// Add a completed transaction to the Transaction Manager to record directly importing a spendable UTXO.
     pub async fn add_utxo_import_transaction(
         &mut self,
@@ -1385,6 +1847,9 @@ where
                 status: TransactionStatus::Completed,
                 message,
                 timestamp: Utc::now().naive_utc(),
+                mined_height: None,
+                mined_block_hash: None,
+                confirmations: None,
             })
             .await?;
         trace!(
@@ -1397,6 +1862,426 @@ where
         Ok(())
     }
 
+    /// Sign a send that was prepared by a wallet without spending keys. The inputs are selected, or the requested
+    /// inputs used, and reserved exactly as for a send over comms but the sender message is returned to be carried to
+    /// the recipient out of band rather than being sent.
+    pub async fn sign_offline_transaction(
+        &mut self,
+        request: UnsignedTransactionRequest,
+    ) -> Result<OfflineSenderMessage, TransactionServiceError>
+    {
+        let mut sender_protocol = self
+            .output_manager_service
+            .prepare_transaction_to_send_from_account(
+                DEFAULT_ACCOUNT.to_string(),
+                request.amount,
+                request.fee_per_gram,
+                request.lock_height,
+                request.recipient_maturity,
+                request.message.clone(),
+                request.inputs,
+            )
+            .await?;
+
+        if !sender_protocol.is_single_round_message_ready() {
+            error!(target: LOG_TARGET, "Sender Transaction Protocol is in an invalid state");
+            return Err(TransactionServiceError::InvalidStateError);
+        }
+        let msg = sender_protocol.build_single_round_message()?;
+        let tx_id = msg.tx_id;
+
+        self.output_manager_service.confirm_pending_transaction(tx_id).await?;
+
+        let outbound_tx = OutboundTransaction {
+            tx_id,
+            destination_public_key: request.dest_pubkey,
+            amount: request.amount,
+            fee: sender_protocol.get_fee_amount()?,
+            sender_protocol,
+            status: TransactionStatus::Pending,
+            message: request.message,
+            timestamp: Utc::now().naive_utc(),
+        };
+        self.db.add_pending_outbound_transaction(tx_id, outbound_tx).await?;
+
+        info!(
+            target: LOG_TARGET,
+            "Offline Transaction (TxId: {}) signed. Waiting for the Recipient Reply to be imported", tx_id
+        );
+
+        Ok(OfflineSenderMessage {
+            source_pubkey: self.node_identity.public_key().clone(),
+            sender_message: TransactionSenderMessage::Single(Box::new(msg)),
+        })
+    }
+
+    /// Reply to a sender message that was delivered out of band. The transaction is added to the pending inbound
+    /// transactions and the reply returned to be carried back to the sender.
+    pub async fn reply_to_offline_transaction(
+        &mut self,
+        sender_message: OfflineSenderMessage,
+    ) -> Result<OfflineRecipientReply, TransactionServiceError>
+    {
+        let (reply, inbound_transaction) = self
+            .create_recipient_reply(&sender_message.source_pubkey, sender_message.sender_message)
+            .await?
+            .ok_or_else(|| {
+                TransactionServiceError::InvalidMessageError("Sender message is not Single round".to_string())
+            })?;
+
+        self.add_received_transaction(inbound_transaction).await?;
+
+        Ok(OfflineRecipientReply {
+            recipient_pubkey: self.node_identity.public_key().clone(),
+            reply,
+        })
+    }
+
+    /// Apply a recipient reply that was delivered out of band to a pending outbound transaction and finalize it. The
+    /// transaction is moved to the completed transactions but is not broadcast, that is left to whichever wallet
+    /// imports the returned finalized transaction.
+    pub async fn finalize_offline_transaction(
+        &mut self,
+        recipient_reply: OfflineRecipientReply,
+    ) -> Result<OfflineFinalizedTransaction, TransactionServiceError>
+    {
+        let OfflineRecipientReply {
+            recipient_pubkey,
+            reply,
+        } = recipient_reply;
+        let tx_id = reply.tx_id;
+
+        let mut outbound_tx = self
+            .db
+            .get_pending_outbound_transaction(tx_id)
+            .await
+            .map_err(|_| TransactionServiceError::TransactionDoesNotExistError)?;
+
+        if outbound_tx.destination_public_key != recipient_pubkey {
+            error!(
+                target: LOG_TARGET,
+                "Transaction Reply did not come from the expected Public Key"
+            );
+            return Err(TransactionServiceError::InvalidSourcePublicKey);
+        }
+        if !outbound_tx.sender_protocol.is_collecting_single_signature() {
+            error!(target: LOG_TARGET, "Pending Transaction not in correct state");
+            return Err(TransactionServiceError::InvalidStateError);
+        }
+
+        outbound_tx
+            .sender_protocol
+            .add_single_recipient_info(reply, &self.factories.range_proof)?;
+        if !outbound_tx
+            .sender_protocol
+            .finalize(KernelFeatures::empty(), &self.factories)?
+        {
+            return Err(TransactionServiceError::TransactionError(
+                TransactionError::ValidationError("Transaction could not be finalized".to_string()),
+            ));
+        }
+        let tx = outbound_tx.sender_protocol.get_transaction()?.clone();
+
+        let completed_transaction = CompletedTransaction {
+            tx_id,
+            source_public_key: self.node_identity.public_key().clone(),
+            destination_public_key: outbound_tx.destination_public_key.clone(),
+            amount: outbound_tx.amount,
+            fee: outbound_tx.fee,
+            transaction: tx.clone(),
+            status: TransactionStatus::Completed,
+            message: outbound_tx.message.clone(),
+            timestamp: Utc::now().naive_utc(),
+            mined_height: None,
+            mined_block_hash: None,
+            confirmations: None,
+        };
+        self.db
+            .complete_outbound_transaction(tx_id, completed_transaction)
+            .await?;
+
+        info!(target: LOG_TARGET, "Offline Transaction (TxId: {}) finalized", tx_id);
+
+        let _ = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::ReceivedTransactionReply(tx_id)))
+            .map_err(|e| {
+                trace!(
+                    target: LOG_TARGET,
+                    "Error sending event, usually because there are no subscribers: {:?}",
+                    e
+                );
+                e
+            });
+
+        Ok(OfflineFinalizedTransaction {
+            tx_id,
+            source_pubkey: self.node_identity.public_key().clone(),
+            destination_pubkey: outbound_tx.destination_public_key,
+            amount: outbound_tx.amount,
+            fee: outbound_tx.fee,
+            message: outbound_tx.message,
+            transaction: tx,
+        })
+    }
+
+    /// Import a transaction that was finalized out of band and broadcast it. If this wallet is the recipient the
+    /// transaction completes the matching pending inbound transaction, otherwise it is added to the completed
+    /// transactions as is. A transaction between two other wallets is only monitored on the chain, as this wallet has
+    /// no outputs in it to confirm. If no base node has been set yet the broadcast happens once one is.
+    pub async fn import_offline_transaction(
+        &mut self,
+        finalized_transaction: OfflineFinalizedTransaction,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError>
+    {
+        let tx_id = finalized_transaction.tx_id;
+
+        if self.db.get_pending_inbound_transaction(tx_id).await.is_ok() {
+            let finalized_transaction_message = proto::TransactionFinalizedMessage {
+                tx_id,
+                transaction: Some(finalized_transaction.transaction.into()),
+            };
+            self.accept_finalized_transaction(
+                finalized_transaction.source_pubkey,
+                finalized_transaction_message,
+                transaction_broadcast_join_handles,
+            )
+            .await?;
+            return Ok(tx_id);
+        }
+
+        // Nothing in this wallet vouches for a transaction that does not finalize one of its pending inbound
+        // transactions, so check it before it is stored and broadcast
+        if self.db.get_pending_outbound_transaction(tx_id).await.is_ok() {
+            return Err(TransactionServiceError::InvalidOfflineTransaction(format!(
+                "TxId {} is already used by a pending outbound transaction",
+                tx_id
+            )));
+        }
+        finalized_transaction
+            .transaction
+            .validate_internal_consistency(&self.factories, None)?;
+        let kernel_fee = finalized_transaction.transaction.body.get_total_fee();
+        if finalized_transaction.fee != kernel_fee {
+            return Err(TransactionServiceError::InvalidOfflineTransaction(format!(
+                "The fee of {} does not match the kernel fees of {}",
+                finalized_transaction.fee, kernel_fee
+            )));
+        }
+
+        if self.db.get_completed_transaction(tx_id).await.is_err() {
+            self.db
+                .insert_completed_transaction(tx_id, CompletedTransaction {
+                    tx_id,
+                    source_public_key: finalized_transaction.source_pubkey,
+                    destination_public_key: finalized_transaction.destination_pubkey,
+                    amount: finalized_transaction.amount,
+                    fee: finalized_transaction.fee,
+                    transaction: finalized_transaction.transaction,
+                    status: TransactionStatus::Completed,
+                    message: finalized_transaction.message,
+                    timestamp: Utc::now().naive_utc(),
+                    mined_height: None,
+                    mined_block_hash: None,
+                    confirmations: None,
+                })
+                .await?;
+        }
+
+        info!(target: LOG_TARGET, "Offline Transaction (TxId: {}) imported", tx_id);
+
+        let _ = self
+            .broadcast_completed_transaction_to_mempool(tx_id, transaction_broadcast_join_handles)
+            .await
+            .map_err(|e| {
+                error!(
+                    target: LOG_TARGET,
+                    "Error broadcasting imported offline transaction to mempool: {:?}", e
+                );
+                e
+            });
+
+        Ok(tx_id)
+    }
+
+    /// Sends a one-sided transaction to a recipient. The transaction is completed without any interaction with the
+    /// recipient and is broadcast immediately; the recipient discovers the payment when it scans mined blocks.
+    /// # Arguments
+    /// 'dest_pubkey': The Comms pubkey of the recipient node
+    /// 'amount': The amount of Tari to send to the recipient
+    /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
+    pub async fn send_one_sided_transaction(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError>
+    {
+        let (tx_id, tx, fee, amount) = self
+            .output_manager_service
+            .create_one_sided_transaction(dest_pubkey.clone(), amount, fee_per_gram, None, message.clone())
+            .await?;
+        self.db
+            .insert_completed_transaction(tx_id, CompletedTransaction {
+                tx_id,
+                source_public_key: self.node_identity.public_key().clone(),
+                destination_public_key: dest_pubkey,
+                amount,
+                fee,
+                transaction: tx,
+                status: TransactionStatus::Completed,
+                message,
+                timestamp: Utc::now().naive_utc(),
+                mined_height: None,
+                mined_block_hash: None,
+                confirmations: None,
+            })
+            .await?;
+        info!(
+            target: LOG_TARGET,
+            "One-sided transaction ({}) created, launching broadcast protocol", tx_id
+        );
+        self.complete_send_transaction_protocol(Ok(tx_id), transaction_broadcast_join_handles)
+            .await;
+        Ok(tx_id)
+    }
+
+    /// Check whether the wallet holds more outputs that could be consolidated than the consolidation policy allows.
+    /// Frozen, immature and dust outputs are not counted as a consolidation would not merge them. If it does, ask the
+    /// base node for its mempool stats to judge whether fees are low enough to consolidate them now.
+    async fn check_consolidation_policy(&mut self) -> Result<(), TransactionServiceError> {
+        let policy = match self.config.consolidation_policy.as_ref() {
+            None => return Ok(()),
+            Some(p) => p.clone(),
+        };
+        let base_node_public_key = match self.base_node_public_key.as_ref() {
+            None => return Ok(()),
+            Some(pk) => pk.clone(),
+        };
+        let utxo_count = self
+            .output_manager_service
+            .get_consolidation_candidates(policy.fee_per_gram)
+            .await?
+            .len();
+        if utxo_count <= policy.utxo_count_threshold {
+            return Ok(());
+        }
+
+        debug!(
+            target: LOG_TARGET,
+            "Wallet holds {} unspent outputs, checking mempool before consolidating", utxo_count
+        );
+        let request_key = OsRng.next_u64();
+        let mempool_request = MempoolProto::MempoolServiceRequest {
+            request_key,
+            request: Some(MempoolProto::mempool_service_request::Request::GetStats(true)),
+        };
+        self.outbound_message_service
+            .send_direct(
+                base_node_public_key,
+                OutboundEncryption::None,
+                OutboundDomainMessage::new(TariMessageType::MempoolRequest, mempool_request),
+            )
+            .await?;
+        self.pending_consolidation_stats_key = Some(request_key);
+        Ok(())
+    }
+
+    /// Consolidate the wallet's smallest unspent outputs if the base node's mempool is quiet enough
+    async fn consolidate_if_fees_are_low(
+        &mut self,
+        mempool_weight: u64,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+    ) -> Result<(), TransactionServiceError>
+    {
+        let policy = match self.config.consolidation_policy.as_ref() {
+            None => return Ok(()),
+            Some(p) => p.clone(),
+        };
+        if mempool_weight > policy.max_mempool_weight {
+            debug!(
+                target: LOG_TARGET,
+                "Mempool weight of {} is too high, postponing UTXO consolidation", mempool_weight
+            );
+            return Ok(());
+        }
+
+        let (tx_id, tx, fee, amount) = self
+            .output_manager_service
+            .create_coin_consolidation(policy.max_inputs, policy.fee_per_gram, None)
+            .await?;
+        info!(
+            target: LOG_TARGET,
+            "Created UTXO consolidation transaction (TxId: {}) merging {} outputs",
+            tx_id,
+            tx.body.inputs().len()
+        );
+        self.submit_transaction(
+            transaction_broadcast_join_handles,
+            tx_id,
+            tx,
+            fee,
+            amount,
+            "UTXO consolidation".to_string(),
+        )
+        .await
+    }
+
+    /// Record a one-sided payment that the Output Manager Service found while scanning mined blocks and broadcast the
+    /// transaction that sweeps it to a key only this wallet knows. The sender of a one-sided payment is not known so
+    /// the source public key is left as the default.
+    async fn add_one_sided_payment_transaction(
+        &mut self,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+        tx_id: TxId,
+        tx: Transaction,
+        fee: MicroTari,
+        amount: MicroTari,
+    ) -> Result<(), TransactionServiceError>
+    {
+        self.db
+            .insert_completed_transaction(tx_id, CompletedTransaction {
+                tx_id,
+                source_public_key: CommsPublicKey::default(),
+                destination_public_key: self.node_identity.public_key().clone(),
+                amount,
+                fee,
+                transaction: tx,
+                status: TransactionStatus::Completed,
+                message: "One-sided payment".to_string(),
+                timestamp: Utc::now().naive_utc(),
+                mined_height: None,
+                mined_block_hash: None,
+                confirmations: None,
+            })
+            .await?;
+        let _ = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::ReceivedOneSidedTransaction(tx_id)))
+            .map_err(|e| {
+                trace!(
+                    target: LOG_TARGET,
+                    "Error sending event, usually because there are no subscribers: {:?}",
+                    e
+                );
+                e
+            });
+        self.complete_send_transaction_protocol(Ok(tx_id), transaction_broadcast_join_handles)
+            .await;
+        Ok(())
+    }
+
     /// This function is only available for testing by the client of LibWallet. It simulates a receiver accepting and
     /// replying to a Pending Outbound Transaction. This results in that transaction being "completed" and it's status
     /// set to `Broadcast` which indicated it is in a base_layer mempool.
@@ -1601,6 +2486,9 @@ where
             status: TransactionStatus::Completed,
             message: found_tx.message.clone(),
             timestamp: found_tx.timestamp,
+            mined_height: None,
+            mined_block_hash: None,
+            confirmations: None,
         };
 
         self.db
@@ -1626,6 +2514,7 @@ where
 pub struct TransactionServiceResources<TBackend>
 where TBackend: TransactionBackend + Clone + 'static
 {
+    pub config: TransactionServiceConfig,
     pub db: TransactionDatabase<TBackend>,
     pub output_manager_service: OutputManagerHandle,
     pub outbound_message_service: OutboundMessageRequester,
@@ -1633,4 +2522,5 @@ where TBackend: TransactionBackend + Clone + 'static
     pub event_publisher: TransactionEventSender,
     pub node_identity: Arc<NodeIdentity>,
     pub factories: CryptoFactories,
+    pub base_node_public_key_watch: watch::Receiver<Option<CommsPublicKey>>,
 }
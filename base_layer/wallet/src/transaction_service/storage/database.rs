@@ -79,6 +79,22 @@ pub trait TransactionBackend: Send + Sync {
     fn broadcast_completed_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
     /// Indicated that a completed transaction has been detected as mined on the base layer
     fn mine_completed_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
+    /// Record the height and hash of the block in which a mined completed transaction was found
+    fn set_completed_transaction_mined_height(
+        &self,
+        tx_id: TxId,
+        mined_height: u64,
+        mined_block_hash: Vec<u8>,
+    ) -> Result<(), TransactionStorageError>;
+    /// Update the number of confirmations a mined completed transaction has on the base layer
+    fn set_completed_transaction_confirmations(
+        &self,
+        tx_id: TxId,
+        confirmations: u64,
+    ) -> Result<(), TransactionStorageError>;
+    /// Revert a mined completed transaction whose block was reorged out of the chain back to the Broadcast status and
+    /// clear its mined height, block hash and confirmations
+    fn revert_mined_completed_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
     /// Cancel Completed transaction, this will update the transaction status
     fn cancel_completed_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
     /// Cancel Completed transaction, this will update the transaction status
@@ -185,6 +201,12 @@ pub struct CompletedTransaction {
     pub status: TransactionStatus,
     pub message: String,
     pub timestamp: NaiveDateTime,
+    /// The height of the block this transaction was mined in, if known
+    pub mined_height: Option<u64>,
+    /// The hash of the block this transaction was mined in, if known
+    pub mined_block_hash: Option<Vec<u8>>,
+    /// The number of blocks, including the block it was mined in, that have been built on top of this transaction
+    pub confirmations: Option<u64>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -223,6 +245,14 @@ pub enum WriteOperation {
     Remove(DbKey),
 }
 
+impl CompletedTransaction {
+    /// Whether the wallet with the given public key sent or received this transaction. A transaction between two other
+    /// parties, e.g. an offline transaction imported only to be broadcast, has no outputs in the wallet to confirm.
+    pub fn is_party(&self, public_key: &CommsPublicKey) -> bool {
+        &self.source_public_key == public_key || &self.destination_public_key == public_key
+    }
+}
+
 impl From<CompletedTransaction> for InboundTransaction {
     fn from(ct: CompletedTransaction) -> Self {
         Self {
@@ -615,6 +645,49 @@ where T: TransactionBackend + 'static
             .and_then(|inner_result| inner_result)
     }
 
+    /// Record the height and hash of the block in which the specified completed transaction was mined
+    pub async fn set_completed_transaction_mined_height(
+        &mut self,
+        tx_id: TxId,
+        mined_height: u64,
+        mined_block_hash: Vec<u8>,
+    ) -> Result<(), TransactionStorageError>
+    {
+        let db_clone = self.db.clone();
+
+        tokio::task::spawn_blocking(move || {
+            db_clone.set_completed_transaction_mined_height(tx_id, mined_height, mined_block_hash)
+        })
+        .await
+        .or_else(|err| Err(TransactionStorageError::BlockingTaskSpawnError(err.to_string())))
+        .and_then(|inner_result| inner_result)
+    }
+
+    /// Update the number of confirmations of the specified mined completed transaction
+    pub async fn set_completed_transaction_confirmations(
+        &mut self,
+        tx_id: TxId,
+        confirmations: u64,
+    ) -> Result<(), TransactionStorageError>
+    {
+        let db_clone = self.db.clone();
+
+        tokio::task::spawn_blocking(move || db_clone.set_completed_transaction_confirmations(tx_id, confirmations))
+            .await
+            .or_else(|err| Err(TransactionStorageError::BlockingTaskSpawnError(err.to_string())))
+            .and_then(|inner_result| inner_result)
+    }
+
+    /// Indicated that the block the specified completed transaction was mined in has been reorged out of the chain
+    pub async fn revert_mined_completed_transaction(&mut self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        let db_clone = self.db.clone();
+
+        tokio::task::spawn_blocking(move || db_clone.revert_mined_completed_transaction(tx_id))
+            .await
+            .or_else(|err| Err(TransactionStorageError::BlockingTaskSpawnError(err.to_string())))
+            .and_then(|inner_result| inner_result)
+    }
+
     #[allow(clippy::erasing_op)] // this is for 0 * uT
     pub async fn add_utxo_import_transaction(
         &mut self,
@@ -635,6 +708,9 @@ where T: TransactionBackend + 'static
             status: TransactionStatus::Imported,
             message,
             timestamp: Utc::now().naive_utc(),
+            mined_height: None,
+            mined_block_hash: None,
+            confirmations: None,
         };
 
         let db_clone = self.db.clone();
@@ -364,6 +364,62 @@ impl TransactionBackend for TransactionMemoryDatabase {
         Ok(())
     }
 
+    fn set_completed_transaction_mined_height(
+        &self,
+        tx_id: TxId,
+        mined_height: u64,
+        mined_block_hash: Vec<u8>,
+    ) -> Result<(), TransactionStorageError>
+    {
+        let mut db = acquire_write_lock!(self.db);
+
+        let mut completed_tx = db
+            .completed_transactions
+            .get_mut(&tx_id)
+            .ok_or_else(|| TransactionStorageError::ValueNotFound(DbKey::CompletedTransaction(tx_id)))?;
+
+        completed_tx.mined_height = Some(mined_height);
+        completed_tx.mined_block_hash = Some(mined_block_hash);
+
+        Ok(())
+    }
+
+    fn set_completed_transaction_confirmations(
+        &self,
+        tx_id: TxId,
+        confirmations: u64,
+    ) -> Result<(), TransactionStorageError>
+    {
+        let mut db = acquire_write_lock!(self.db);
+
+        let mut completed_tx = db
+            .completed_transactions
+            .get_mut(&tx_id)
+            .ok_or_else(|| TransactionStorageError::ValueNotFound(DbKey::CompletedTransaction(tx_id)))?;
+
+        completed_tx.confirmations = Some(confirmations);
+
+        Ok(())
+    }
+
+    fn revert_mined_completed_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        let mut db = acquire_write_lock!(self.db);
+
+        let mut completed_tx = db
+            .completed_transactions
+            .get_mut(&tx_id)
+            .ok_or_else(|| TransactionStorageError::ValueNotFound(DbKey::CompletedTransaction(tx_id)))?;
+
+        if completed_tx.status == TransactionStatus::Mined {
+            completed_tx.status = TransactionStatus::Broadcast;
+            completed_tx.mined_height = None;
+            completed_tx.mined_block_hash = None;
+            completed_tx.confirmations = None;
+        }
+
+        Ok(())
+    }
+
     fn cancel_completed_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
         let mut db = acquire_write_lock!(self.db);
 
@@ -378,6 +378,9 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
                         UpdateCompletedTransaction {
                             status: Some(TransactionStatus::Broadcast),
                             timestamp: None,
+                            mined_height: None,
+                            mined_block_hash: None,
+                            confirmations: None,
                         },
                         &(*conn),
                     )?;
@@ -402,6 +405,9 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
                     UpdateCompletedTransaction {
                         status: Some(TransactionStatus::Mined),
                         timestamp: None,
+                        mined_height: None,
+                        mined_block_hash: None,
+                        confirmations: None,
                     },
                     &(*conn),
                 )?;
@@ -416,6 +422,97 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         Ok(())
     }
 
+    fn set_completed_transaction_mined_height(
+        &self,
+        tx_id: u64,
+        mined_height: u64,
+        mined_block_hash: Vec<u8>,
+    ) -> Result<(), TransactionStorageError>
+    {
+        let conn = acquire_lock!(self.database_connection);
+
+        match CompletedTransactionSql::find(tx_id, &(*conn)) {
+            Ok(v) => {
+                let _ = v.update(
+                    UpdateCompletedTransaction {
+                        status: None,
+                        timestamp: None,
+                        mined_height: Some(Some(mined_height)),
+                        mined_block_hash: Some(Some(mined_block_hash)),
+                        confirmations: None,
+                    },
+                    &(*conn),
+                )?;
+            },
+            Err(TransactionStorageError::DieselError(DieselError::NotFound)) => {
+                return Err(TransactionStorageError::ValueNotFound(DbKey::CompletedTransaction(
+                    tx_id,
+                )))
+            },
+            Err(e) => return Err(e),
+        };
+        Ok(())
+    }
+
+    fn set_completed_transaction_confirmations(
+        &self,
+        tx_id: u64,
+        confirmations: u64,
+    ) -> Result<(), TransactionStorageError>
+    {
+        let conn = acquire_lock!(self.database_connection);
+
+        match CompletedTransactionSql::find(tx_id, &(*conn)) {
+            Ok(v) => {
+                let _ = v.update(
+                    UpdateCompletedTransaction {
+                        status: None,
+                        timestamp: None,
+                        mined_height: None,
+                        mined_block_hash: None,
+                        confirmations: Some(Some(confirmations)),
+                    },
+                    &(*conn),
+                )?;
+            },
+            Err(TransactionStorageError::DieselError(DieselError::NotFound)) => {
+                return Err(TransactionStorageError::ValueNotFound(DbKey::CompletedTransaction(
+                    tx_id,
+                )))
+            },
+            Err(e) => return Err(e),
+        };
+        Ok(())
+    }
+
+    fn revert_mined_completed_transaction(&self, tx_id: u64) -> Result<(), TransactionStorageError> {
+        let conn = acquire_lock!(self.database_connection);
+
+        match CompletedTransactionSql::find(tx_id, &(*conn)) {
+            Ok(v) => {
+                if TransactionStatus::try_from(v.status)? == TransactionStatus::Mined {
+                    let _ = v.update(
+                        UpdateCompletedTransaction {
+                            status: Some(TransactionStatus::Broadcast),
+                            timestamp: None,
+                            mined_height: Some(None),
+                            mined_block_hash: Some(None),
+                            confirmations: Some(None),
+                        },
+                        &(*conn),
+                    )?;
+                }
+            },
+            Err(TransactionStorageError::DieselError(DieselError::NotFound)) => {
+                return Err(TransactionStorageError::ValueNotFound(DbKey::CompletedTransaction(
+                    tx_id,
+                )))
+            },
+            Err(e) => return Err(e),
+        };
+        Ok(())
+    }
+
     fn cancel_completed_transaction(&self, tx_id: u64) -> Result<(), TransactionStorageError> {
         let conn = acquire_lock!(self.database_connection);
         match CompletedTransactionSql::find(tx_id, &(*conn)) {
@@ -467,6 +564,9 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
                 UpdateCompletedTransaction {
                     status: None,
                     timestamp: Some(timestamp),
+                    mined_height: None,
+                    mined_block_hash: None,
+                    confirmations: None,
                 },
                 &(*conn),
             );
@@ -719,6 +819,9 @@ struct CompletedTransactionSql {
     status: i32,
     message: String,
     timestamp: NaiveDateTime,
+    mined_height: Option<i64>,
+    mined_block_hash: Option<Vec<u8>>,
+    confirmations: Option<i64>,
 }
 
 impl CompletedTransactionSql {
@@ -780,6 +883,9 @@ impl CompletedTransactionSql {
                 .set(UpdateCompletedTransactionSql {
                     status: Some(TransactionStatus::Cancelled as i32),
                     timestamp: None,
+                    mined_height: None,
+                    mined_block_hash: None,
+                    confirmations: None,
                 })
                 .execute(conn)?;
 
@@ -807,6 +913,9 @@ impl TryFrom<CompletedTransaction> for CompletedTransactionSql {
             status: c.status as i32,
             message: c.message,
             timestamp: c.timestamp,
+            mined_height: c.mined_height.map(|h| h as i64),
+            mined_block_hash: c.mined_block_hash,
+            confirmations: c.confirmations.map(|c| c as i64),
         })
     }
 }
@@ -827,6 +936,9 @@ impl TryFrom<CompletedTransactionSql> for CompletedTransaction {
             status: TransactionStatus::try_from(c.status)?,
             message: c.message,
             timestamp: c.timestamp,
+            mined_height: c.mined_height.map(|h| h as u64),
+            mined_block_hash: c.mined_block_hash,
+            confirmations: c.confirmations.map(|c| c as u64),
         })
     }
 }
@@ -835,6 +947,9 @@ impl TryFrom<CompletedTransactionSql> for CompletedTransaction {
 pub struct UpdateCompletedTransaction {
     status: Option<TransactionStatus>,
     timestamp: Option<NaiveDateTime>,
+    mined_height: Option<Option<u64>>,
+    mined_block_hash: Option<Option<Vec<u8>>>,
+    confirmations: Option<Option<u64>>,
 }
 
 #[derive(AsChangeset)]
@@ -842,6 +957,9 @@ pub struct UpdateCompletedTransaction {
 pub struct UpdateCompletedTransactionSql {
     status: Option<i32>,
     timestamp: Option<NaiveDateTime>,
+    mined_height: Option<Option<i64>>,
+    mined_block_hash: Option<Option<Vec<u8>>>,
+    confirmations: Option<Option<i64>>,
 }
 
 /// Map a Rust friendly UpdateCompletedTransaction to the Sql data type form
@@ -850,6 +968,9 @@ impl From<UpdateCompletedTransaction> for UpdateCompletedTransactionSql {
         Self {
             status: u.status.map(|s| s as i32),
             timestamp: u.timestamp,
+            mined_height: u.mined_height.map(|h| h.map(|h| h as i64)),
+            mined_block_hash: u.mined_block_hash,
+            confirmations: u.confirmations.map(|c| c.map(|c| c as i64)),
         }
     }
 }
@@ -1024,6 +1145,9 @@ mod test {
             status: TransactionStatus::Mined,
             message: "Yo!".to_string(),
             timestamp: Utc::now().naive_utc(),
+            mined_height: None,
+            mined_block_hash: None,
+            confirmations: None,
         };
         let completed_tx2 = CompletedTransaction {
             tx_id: 3,
@@ -1035,6 +1159,9 @@ mod test {
             status: TransactionStatus::Broadcast,
             message: "Hey!".to_string(),
             timestamp: Utc::now().naive_utc(),
+            mined_height: None,
+            mined_block_hash: None,
+            confirmations: None,
         };
 
         CompletedTransactionSql::try_from(completed_tx1.clone())
@@ -1122,6 +1249,9 @@ mod test {
                 UpdateCompletedTransaction {
                     status: Some(TransactionStatus::Mined),
                     timestamp: None,
+                    mined_height: None,
+                    mined_block_hash: None,
+                    confirmations: None,
                 },
                 &conn,
             )
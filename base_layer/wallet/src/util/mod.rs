@@ -23,3 +23,4 @@
 pub mod emoji;
 pub mod futures;
 pub mod luhn;
+pub mod payment_request;
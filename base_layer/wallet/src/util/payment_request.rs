@@ -0,0 +1,424 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Payment requests let a recipient ask to be paid a given amount, for instance by showing a QR code. They are encoded
+//! as a `tari:` URI of the form
+//!
+//! ```text
+//! tari:<emoji id or public key>?amount=<µT>&message=<text>&expiry=<unix time>&base_node=<key>::<address>&checksum=<xx>
+//! ```
+//!
+//! Only the destination, the amount and the checksum are required. Parameter values are percent-encoded. The
+//! `checksum` parameter must come last and holds a Luhn mod 256 checksum of the bytes of the URI that precede it, so
+//! that a mistyped or truncated request is rejected rather than paid.
+
+use crate::util::{
+    emoji::EmojiId,
+    luhn::{checksum, is_valid},
+};
+use chrono::{NaiveDateTime, Utc};
+use derive_error::Error;
+use std::{fmt, str::FromStr};
+use tari_comms::multiaddr::Multiaddr;
+use tari_core::transactions::{tari_amount::MicroTari, types::PublicKey};
+use tari_crypto::tari_utilities::hex::Hex;
+
+/// The URI scheme used for payment requests
+pub const PAYMENT_REQUEST_SCHEME: &str = "tari";
+
+const CHECKSUM_PARAMETER: &str = "checksum=";
+
+#[derive(Debug, Error, PartialEq)]
+pub enum PaymentRequestError {
+    /// The URI does not use the `tari:` scheme
+    InvalidScheme,
+    /// The destination is neither a valid emoji ID nor a hex encoded public key
+    InvalidDestination,
+    /// The amount is missing or is not a whole number of microtari
+    InvalidAmount,
+    /// The expiry is not a unix timestamp in the range of representable dates
+    InvalidExpiry,
+    /// The base node hint is not of the form `<public key>::<address>`
+    InvalidBaseNodeHint,
+    /// A parameter is not a `name=value` pair, its value is not correctly percent-encoded or the message contains a
+    /// NUL character
+    InvalidEncoding,
+    /// A parameter appears more than once
+    #[error(msg_embedded, non_std, no_from)]
+    DuplicateParameter(String),
+    /// The URI does not end with a checksum parameter
+    MissingChecksum,
+    /// The checksum does not match the rest of the URI
+    InvalidChecksum,
+    /// The payment request has expired
+    Expired,
+}
+
+/// A request to pay `amount` to `destination`, optionally with a message for the payer, an expiry time after which
+/// the request should no longer be paid and a hint of a base node the recipient is known to use.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaymentRequest {
+    pub destination: PublicKey,
+    pub amount: MicroTari,
+    pub message: String,
+    pub expiry: Option<NaiveDateTime>,
+    pub base_node_hint: Option<(PublicKey, Multiaddr)>,
+}
+
+impl PaymentRequest {
+    /// Start building a request to pay `amount` to `destination`
+    pub fn new(destination: PublicKey, amount: MicroTari) -> Self {
+        Self {
+            destination,
+            amount,
+            message: String::new(),
+            expiry: None,
+            base_node_hint: None,
+        }
+    }
+
+    /// Set the message that the payer should attach to the transaction
+    pub fn with_message<T: Into<String>>(mut self, message: T) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    /// Set the time after which the request should no longer be paid
+    pub fn with_expiry(mut self, expiry: NaiveDateTime) -> Self {
+        self.expiry = Some(expiry);
+        self
+    }
+
+    /// Set a base node that the recipient is known to use
+    pub fn with_base_node_hint(mut self, public_key: PublicKey, address: Multiaddr) -> Self {
+        self.base_node_hint = Some((public_key, address));
+        self
+    }
+
+    /// Returns true if the request has an expiry time and it has passed
+    pub fn is_expired(&self) -> bool {
+        self.is_expired_at(Utc::now().naive_utc())
+    }
+
+    /// Returns true if the request has an expiry time and it is before `now`
+    pub fn is_expired_at(&self, now: NaiveDateTime) -> bool {
+        self.expiry.map(|expiry| expiry < now).unwrap_or(false)
+    }
+
+    /// Encode the request as a `tari:` URI. The destination is written as an emoji ID.
+    pub fn to_uri(&self) -> String {
+        let mut uri = format!(
+            "{}:{}?amount={}",
+            PAYMENT_REQUEST_SCHEME,
+            EmojiId::from_pubkey(&self.destination),
+            u64::from(self.amount)
+        );
+        if !self.message.is_empty() {
+            uri.push_str(&format!("&message={}", percent_encode(&self.message)));
+        }
+        if let Some(expiry) = self.expiry {
+            uri.push_str(&format!("&expiry={}", expiry.timestamp()));
+        }
+        if let Some((public_key, address)) = &self.base_node_hint {
+            let hint = format!("{}::{}", public_key.to_hex(), address);
+            uri.push_str(&format!("&base_node={}", percent_encode(&hint)));
+        }
+        let cs = checksum(&uri.bytes().map(usize::from).collect::<Vec<_>>(), 256);
+        uri.push_str(&format!("&{}{:02x}", CHECKSUM_PARAMETER, cs));
+        uri
+    }
+
+    /// Parse and validate a `tari:` URI. Parameters that are not known to this version of the wallet are ignored.
+    pub fn from_uri(uri: &str) -> Result<Self, PaymentRequestError> {
+        let uri = uri.trim();
+        let payload = verify_checksum(uri)?;
+
+        let scheme_len = PAYMENT_REQUEST_SCHEME.len();
+        if payload.len() <= scheme_len ||
+            !payload.is_char_boundary(scheme_len) ||
+            !payload[..scheme_len].eq_ignore_ascii_case(PAYMENT_REQUEST_SCHEME) ||
+            !payload[scheme_len..].starts_with(':')
+        {
+            return Err(PaymentRequestError::InvalidScheme);
+        }
+        let payload = &payload[scheme_len + 1..];
+        let (destination, query) = match payload.find('?') {
+            Some(i) => (&payload[..i], &payload[i + 1..]),
+            None => (payload, ""),
+        };
+        let destination = parse_destination(&percent_decode(destination)?)?;
+
+        let mut amount = None;
+        let mut message = None;
+        let mut expiry = None;
+        let mut base_node_hint = None;
+        for param in query.split('&').filter(|p| !p.is_empty()) {
+            let mut parts = param.splitn(2, '=');
+            let name = parts.next().unwrap_or("");
+            let value = percent_decode(parts.next().ok_or(PaymentRequestError::InvalidEncoding)?)?;
+            let is_duplicate = match name {
+                "amount" => amount
+                    .replace(
+                        value
+                            .parse::<u64>()
+                            .map(MicroTari::from)
+                            .map_err(|_| PaymentRequestError::InvalidAmount)?,
+                    )
+                    .is_some(),
+                "message" => {
+                    if value.contains('\0') {
+                        return Err(PaymentRequestError::InvalidEncoding);
+                    }
+                    message.replace(value).is_some()
+                },
+                "expiry" => expiry
+                    .replace(
+                        value
+                            .parse::<i64>()
+                            .ok()
+                            .and_then(|t| NaiveDateTime::from_timestamp_opt(t, 0))
+                            .ok_or(PaymentRequestError::InvalidExpiry)?,
+                    )
+                    .is_some(),
+                "base_node" => base_node_hint.replace(parse_base_node_hint(&value)?).is_some(),
+                _ => false,
+            };
+            if is_duplicate {
+                return Err(PaymentRequestError::DuplicateParameter(name.to_string()));
+            }
+        }
+
+        Ok(Self {
+            destination,
+            amount: amount.ok_or(PaymentRequestError::InvalidAmount)?,
+            message: message.unwrap_or_default(),
+            expiry,
+            base_node_hint,
+        })
+    }
+}
+
+impl FromStr for PaymentRequest {
+    type Err = PaymentRequestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_uri(s)
+    }
+}
+
+impl fmt::Display for PaymentRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_uri())
+    }
+}
+
+/// Checks the trailing checksum parameter and returns the part of the URI that it covers
+fn verify_checksum(uri: &str) -> Result<&str, PaymentRequestError> {
+    let i = uri
+        .rfind(CHECKSUM_PARAMETER)
+        .ok_or(PaymentRequestError::MissingChecksum)?;
+    if i == 0 || !(uri[..i].ends_with('&') || uri[..i].ends_with('?')) {
+        return Err(PaymentRequestError::MissingChecksum);
+    }
+    let cs = &uri[i + CHECKSUM_PARAMETER.len()..];
+    if cs.len() != 2 || !cs.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(PaymentRequestError::InvalidChecksum);
+    }
+    let cs = u8::from_str_radix(cs, 16).map_err(|_| PaymentRequestError::InvalidChecksum)?;
+    let payload = &uri[..i - 1];
+    let mut digits = payload.bytes().map(usize::from).collect::<Vec<_>>();
+    digits.push(usize::from(cs));
+    if !is_valid(&digits, 256) {
+        return Err(PaymentRequestError::InvalidChecksum);
+    }
+    Ok(payload)
+}
+
+fn parse_destination(destination: &str) -> Result<PublicKey, PaymentRequestError> {
+    EmojiId::str_to_pubkey(destination)
+        .or_else(|_| PublicKey::from_hex(destination).map_err(|_| ()))
+        .map_err(|_| PaymentRequestError::InvalidDestination)
+}
+
+fn parse_base_node_hint(hint: &str) -> Result<(PublicKey, Multiaddr), PaymentRequestError> {
+    let mut parts = hint.splitn(2, "::");
+    let public_key = parts
+        .next()
+        .and_then(|k| PublicKey::from_hex(k).ok())
+        .ok_or(PaymentRequestError::InvalidBaseNodeHint)?;
+    let address = parts
+        .next()
+        .and_then(|a| a.parse::<Multiaddr>().ok())
+        .ok_or(PaymentRequestError::InvalidBaseNodeHint)?;
+    Ok((public_key, address))
+}
+
+/// Percent-encode every byte of `s` except the RFC 3986 unreserved characters
+fn percent_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(b as char),
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+fn percent_decode(s: &str) -> Result<String, PaymentRequestError> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s
+                .get(i + 1..i + 3)
+                .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                .ok_or(PaymentRequestError::InvalidEncoding)?;
+            decoded.push(u8::from_str_radix(hex, 16).map_err(|_| PaymentRequestError::InvalidEncoding)?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| PaymentRequestError::InvalidEncoding)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+    fn random_public_key() -> PublicKey {
+        let (_, public_key) = PublicKey::random_keypair(&mut rand::rngs::OsRng);
+        public_key
+    }
+
+    #[test]
+    fn round_trip() {
+        let destination = random_public_key();
+        let base_node = random_public_key();
+        let request = PaymentRequest::new(destination.clone(), MicroTari::from(12_345))
+            .with_message("Coffee & cake, 100% worth it")
+            .with_expiry(NaiveDateTime::from_timestamp(1_600_000_000, 0))
+            .with_base_node_hint(base_node.clone(), "/ip4/127.0.0.1/tcp/18189".parse().unwrap());
+        let uri = request.to_uri();
+        assert!(uri.starts_with(&format!("tari:{}?amount=12345&", EmojiId::from_pubkey(&destination))));
+
+        let parsed = PaymentRequest::from_uri(&uri).unwrap();
+        assert_eq!(parsed, request);
+        assert_eq!(parsed.base_node_hint.unwrap().0, base_node);
+        assert_eq!(uri.parse::<PaymentRequest>().unwrap().to_string(), uri);
+    }
+
+    #[test]
+    fn minimal_request() {
+        let request = PaymentRequest::new(random_public_key(), MicroTari::from(1));
+        let parsed = PaymentRequest::from_uri(&request.to_uri()).unwrap();
+        assert_eq!(parsed, request);
+        assert!(parsed.message.is_empty());
+        assert!(!parsed.is_expired());
+    }
+
+    #[test]
+    fn accepts_hex_destinations() {
+        let destination = random_public_key();
+        let payload = format!("TARI:{}?amount=500&unknown=ignored", destination.to_hex());
+        let cs = checksum(&payload.bytes().map(usize::from).collect::<Vec<_>>(), 256);
+        let parsed = PaymentRequest::from_uri(&format!("{}&checksum={:02x}", payload, cs)).unwrap();
+        assert_eq!(parsed.destination, destination);
+        assert_eq!(parsed.amount, MicroTari::from(500));
+    }
+
+    #[test]
+    fn rejects_corrupted_requests() {
+        let uri = PaymentRequest::new(random_public_key(), MicroTari::from(1000))
+            .with_message("rent")
+            .to_uri();
+
+        let tampered = uri.replace("amount=1000", "amount=9000");
+        assert_eq!(
+            PaymentRequest::from_uri(&tampered),
+            Err(PaymentRequestError::InvalidChecksum)
+        );
+        let truncated = &uri[..uri.rfind('&').unwrap()];
+        assert_eq!(
+            PaymentRequest::from_uri(truncated),
+            Err(PaymentRequestError::MissingChecksum)
+        );
+        assert_eq!(
+            PaymentRequest::from_uri(&uri.replacen("tari:", "btc::", 1)),
+            Err(PaymentRequestError::InvalidChecksum)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        let with_checksum = |payload: &str| {
+            let cs = checksum(&payload.bytes().map(usize::from).collect::<Vec<_>>(), 256);
+            format!("{}&checksum={:02x}", payload, cs)
+        };
+        let destination = EmojiId::from_pubkey(&random_public_key()).to_string();
+
+        let uri = with_checksum(&format!("bitcoin:{}?amount=1", destination));
+        assert_eq!(PaymentRequest::from_uri(&uri), Err(PaymentRequestError::InvalidScheme));
+        let truncated = destination.chars().skip(1).collect::<String>();
+        let uri = with_checksum(&format!("tari:{}?amount=1", truncated));
+        assert_eq!(
+            PaymentRequest::from_uri(&uri),
+            Err(PaymentRequestError::InvalidDestination)
+        );
+        let uri = with_checksum(&format!("tari:{}?message=hi", destination));
+        assert_eq!(PaymentRequest::from_uri(&uri), Err(PaymentRequestError::InvalidAmount));
+        let uri = with_checksum(&format!("tari:{}?amount=1&amount=2", destination));
+        assert_eq!(
+            PaymentRequest::from_uri(&uri),
+            Err(PaymentRequestError::DuplicateParameter("amount".to_string()))
+        );
+        let uri = with_checksum(&format!("tari:{}?amount=1&message=%E2%8", destination));
+        assert_eq!(
+            PaymentRequest::from_uri(&uri),
+            Err(PaymentRequestError::InvalidEncoding)
+        );
+        let uri = with_checksum(&format!("tari:{}?amount=1&message=a%00b", destination));
+        assert_eq!(
+            PaymentRequest::from_uri(&uri),
+            Err(PaymentRequestError::InvalidEncoding)
+        );
+        let uri = with_checksum(&format!("tari:{}?amount=1&expiry={}", destination, i64::MAX));
+        assert_eq!(PaymentRequest::from_uri(&uri), Err(PaymentRequestError::InvalidExpiry));
+        let uri = with_checksum(&format!("tari:{}?amount=1&base_node=abc", destination));
+        assert_eq!(
+            PaymentRequest::from_uri(&uri),
+            Err(PaymentRequestError::InvalidBaseNodeHint)
+        );
+    }
+
+    #[test]
+    fn expiry() {
+        let request = PaymentRequest::new(random_public_key(), MicroTari::from(1))
+            .with_expiry(NaiveDateTime::from_timestamp(1_000, 0));
+        assert!(request.is_expired());
+        assert!(!request.is_expired_at(NaiveDateTime::from_timestamp(999, 0)));
+    }
+}
@@ -21,8 +21,14 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
+    base_node_service::{
+        config::BaseNodeServiceConfig,
+        error::BaseNodeServiceError,
+        handle::BaseNodeServiceHandle,
+        BaseNodeServiceInitializer,
+    },
     contacts_service::{handle::ContactsServiceHandle, storage::database::ContactsBackend, ContactsServiceInitializer},
-    error::WalletError,
+    error::{WalletError, WalletStorageError},
     output_manager_service::{
         config::OutputManagerServiceConfig,
         handle::OutputManagerHandle,
@@ -34,6 +40,17 @@ use crate::{
     transaction_service::{
         config::TransactionServiceConfig,
         handle::TransactionServiceHandle,
+        history::{
+            apply_contact_aliases,
+            export_transaction_history,
+            export_transaction_history_report,
+            summarize_transaction_history,
+            AccountingPeriod,
+            TransactionHistoryFilter,
+            TransactionHistoryFormat,
+            TransactionHistoryRecord,
+            TransactionHistorySummary,
+        },
         storage::database::TransactionBackend,
         TransactionServiceInitializer,
     },
@@ -77,6 +94,8 @@ pub struct WalletConfig {
     pub comms_config: CommsConfig,
     pub factories: CryptoFactories,
     pub transaction_service_config: Option<TransactionServiceConfig>,
+    pub output_manager_service_config: Option<OutputManagerServiceConfig>,
+    pub base_node_service_config: Option<BaseNodeServiceConfig>,
 }
 
 /// A structure containing the config and services that a Wallet application will require. This struct will start up all
@@ -95,6 +114,7 @@ where
     pub output_manager_service: OutputManagerHandle,
     pub transaction_service: TransactionServiceHandle,
     pub contacts_service: ContactsServiceHandle,
+    pub base_node_service: BaseNodeServiceHandle,
     pub db: WalletDatabase<T>,
     pub runtime: Runtime,
     pub factories: CryptoFactories,
@@ -149,10 +169,11 @@ where
                 comms.connection_manager(),
             ))
             .add_initializer(OutputManagerServiceInitializer::new(
-                OutputManagerServiceConfig::default(),
+                config.output_manager_service_config.unwrap_or_default(),
                 subscription_factory.clone(),
                 output_manager_backend,
                 factories.clone(),
+                comms.node_identity(),
             ))
             .add_initializer(TransactionServiceInitializer::new(
                 config.transaction_service_config.unwrap_or_default(),
@@ -162,14 +183,17 @@ where
                 factories.clone(),
             ))
             .add_initializer(ContactsServiceInitializer::new(contacts_backend))
+            .add_initializer(BaseNodeServiceInitializer::new(
+                config.base_node_service_config.unwrap_or_default(),
+            ))
             .finish();
 
         let handles = runtime.block_on(fut).expect("Service initialization failed");
 
-        let mut output_manager_handle = handles
+        let output_manager_handle = handles
             .get_handle::<OutputManagerHandle>()
             .expect("Could not get Output Manager Service Handle");
-        let mut transaction_service_handle = handles
+        let transaction_service_handle = handles
             .get_handle::<TransactionServiceHandle>()
             .expect("Could not get Transaction Service Handle");
         let liveness_handle = handles
@@ -178,10 +202,12 @@ where
         let contacts_handle = handles
             .get_handle::<ContactsServiceHandle>()
             .expect("Could not get Contacts Service Handle");
+        let mut base_node_handle = handles
+            .get_handle::<BaseNodeServiceHandle>()
+            .expect("Could not get Base Node Service Handle");
 
         for p in base_node_peers {
-            runtime.block_on(transaction_service_handle.set_base_node_public_key(p.public_key.clone()))?;
-            runtime.block_on(output_manager_handle.set_base_node_public_key(p.public_key.clone()))?;
+            runtime.block_on(base_node_handle.add_base_node(p.public_key.clone()))?;
         }
 
         let store_and_forward_requester = dht.store_and_forward_requester();
@@ -194,6 +220,7 @@ where
             output_manager_service: output_manager_handle,
             transaction_service: transaction_service_handle,
             contacts_service: contacts_handle,
+            base_node_service: base_node_handle,
             db,
             runtime,
             factories,
@@ -212,8 +239,20 @@ where
     }
 
     /// This function will set the base_node that the wallet uses to broadcast transactions and monitor the blockchain
-    /// state
+    /// state, replacing any base nodes that were previously added
     pub fn set_base_node_peer(&mut self, public_key: CommsPublicKey, net_address: String) -> Result<(), WalletError> {
+        let existing_peers = self.runtime.block_on(self.db.get_peers())?;
+        for p in existing_peers {
+            self.remove_base_node_peer(p.public_key)?;
+        }
+        self.add_base_node_peer(public_key, net_address)
+    }
+
+    /// Add a base node to the end of the list of base nodes the wallet can use. The wallet uses the first healthy base
+    /// node in the list and fails over to the next one if it stops responding or falls behind the others. The order of
+    /// the list is persisted. Adding a base node that is already in the list keeps its place and only updates its
+    /// address in the peer manager.
+    pub fn add_base_node_peer(&mut self, public_key: CommsPublicKey, net_address: String) -> Result<(), WalletError> {
         let address = net_address.parse::<Multiaddr>()?;
         let peer = Peer::new(
             public_key.clone(),
@@ -224,22 +263,26 @@ where
             &[],
         );
 
-        let existing_peers = self.runtime.block_on(self.db.get_peers())?;
-        // Remove any peers in db to only persist a single peer at a time.
-        for p in existing_peers {
-            let _ = self.runtime.block_on(self.db.remove_peer(p.public_key.clone()))?;
+        match self.runtime.block_on(self.db.save_peer(peer.clone())) {
+            Err(WalletStorageError::DuplicateContact) => (),
+            result => result?,
         }
-        self.runtime.block_on(self.db.save_peer(peer.clone()))?;
-
         self.runtime
             .block_on(self.comms.peer_manager().add_peer(peer.clone()))?;
-        self.runtime.block_on(
-            self.transaction_service
-                .set_base_node_public_key(peer.public_key.clone()),
-        )?;
-        self.runtime
-            .block_on(self.output_manager_service.set_base_node_public_key(peer.public_key))?;
+        match self
+            .runtime
+            .block_on(self.base_node_service.add_base_node(peer.public_key))
+        {
+            Err(BaseNodeServiceError::DuplicateBaseNode) => Ok(()),
+            result => result.map_err(WalletError::from),
+        }
+    }
 
+    /// Remove a base node from the list of base nodes the wallet can use
+    pub fn remove_base_node_peer(&mut self, public_key: CommsPublicKey) -> Result<(), WalletError> {
+        self.runtime
+            .block_on(self.base_node_service.remove_base_node(public_key.clone()))?;
+        let _ = self.runtime.block_on(self.db.remove_peer(public_key))?;
         Ok(())
     }
 
@@ -301,6 +344,54 @@ where
         signature.verify_challenge(&public_key, challenge.clone().as_slice())
     }
 
+    /// Fetch the transaction history selected by the filter, with the counterparties labelled by their contact aliases
+    pub fn get_transaction_history(
+        &mut self,
+        filter: TransactionHistoryFilter,
+    ) -> Result<Vec<TransactionHistoryRecord>, WalletError>
+    {
+        let mut records = self
+            .runtime
+            .block_on(self.transaction_service.get_transaction_history(filter))?;
+        let contacts = self.runtime.block_on(self.contacts_service.get_contacts())?;
+        apply_contact_aliases(&mut records, &contacts);
+        Ok(records)
+    }
+
+    /// Export the transaction history selected by the filter as a CSV or JSON document
+    pub fn export_transaction_history(
+        &mut self,
+        filter: TransactionHistoryFilter,
+        format: TransactionHistoryFormat,
+    ) -> Result<String, WalletError>
+    {
+        let records = self.get_transaction_history(filter)?;
+        Ok(export_transaction_history(&records, format)?)
+    }
+
+    /// Report the totals received, sent and paid in fees for each accounting period covered by the filter
+    pub fn get_transaction_history_report(
+        &mut self,
+        filter: TransactionHistoryFilter,
+        period: AccountingPeriod,
+    ) -> Result<Vec<TransactionHistorySummary>, WalletError>
+    {
+        let records = self.get_transaction_history(filter)?;
+        Ok(summarize_transaction_history(&records, period))
+    }
+
+    /// Export the per period report of the transaction history selected by the filter as a CSV or JSON document
+    pub fn export_transaction_history_report(
+        &mut self,
+        filter: TransactionHistoryFilter,
+        period: AccountingPeriod,
+        format: TransactionHistoryFormat,
+    ) -> Result<String, WalletError>
+    {
+        let summaries = self.get_transaction_history_report(filter, period)?;
+        Ok(export_transaction_history_report(&summaries, format)?)
+    }
+
     /// Have all the wallet components that need to start a sync process with the set base node to confirm the wallets
     /// state is accurately reflected on the blockchain
     pub fn sync_with_base_node(&mut self) -> Result<u64, WalletError> {
@@ -310,6 +401,8 @@ where
         let request_key = self
             .runtime
             .block_on(self.output_manager_service.sync_with_base_node())?;
+        self.runtime
+            .block_on(self.output_manager_service.scan_for_one_sided_payments())?;
         Ok(request_key)
     }
 }
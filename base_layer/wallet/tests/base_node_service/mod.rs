@@ -0,0 +1,286 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use futures::StreamExt;
+use prost::Message;
+use rand::rngs::OsRng;
+use std::{sync::mpsc, time::Duration};
+use tari_broadcast_channel::bounded;
+use tari_comms::{peer_manager::NodeId, types::CommsPublicKey};
+use tari_core::{base_node::proto::base_node as BaseNodeProto, transactions::types::PublicKey};
+use tari_crypto::keys::PublicKey as PublicKeyTrait;
+use tari_p2p::services::liveness::{
+    mock::{create_p2p_liveness_mock, LivenessMockState},
+    LivenessEvent,
+    Metadata,
+    MetadataKey,
+    PingPongEvent,
+};
+use tari_service_framework::reply_channel;
+use tari_shutdown::Shutdown;
+use tari_wallet::{
+    base_node_service::{
+        config::BaseNodeServiceConfig,
+        handle::{BaseNodeEvent, BaseNodeServiceHandle},
+        service::BaseNodeService,
+    },
+    output_manager_service::{
+        error::OutputManagerError,
+        handle::{OutputManagerHandle, OutputManagerRequest, OutputManagerResponse},
+    },
+    transaction_service::{
+        error::TransactionServiceError,
+        handle::{TransactionServiceHandle, TransactionServiceRequest, TransactionServiceResponse},
+    },
+};
+use tokio::{runtime::Runtime, sync::broadcast};
+
+/// Answer the Transaction Service requests made by the Base Node Service and pass on the base nodes it sets
+async fn mock_transaction_service(
+    mut receiver: reply_channel::Receiver<
+        TransactionServiceRequest,
+        Result<TransactionServiceResponse, TransactionServiceError>,
+    >,
+    base_nodes: mpsc::Sender<CommsPublicKey>,
+)
+{
+    while let Some(request_context) = receiver.next().await {
+        let (request, reply_tx) = request_context.split();
+        if let TransactionServiceRequest::SetBaseNodePublicKey(pk) = request {
+            base_nodes.send(pk).unwrap();
+        }
+        let _ = reply_tx.send(Ok(TransactionServiceResponse::BaseNodePublicKeySet));
+    }
+}
+
+/// Answer the Output Manager Service requests made by the Base Node Service and pass them on
+async fn mock_output_manager_service(
+    mut receiver: reply_channel::Receiver<OutputManagerRequest, Result<OutputManagerResponse, OutputManagerError>>,
+    requests: mpsc::Sender<OutputManagerRequest>,
+)
+{
+    while let Some(request_context) = receiver.next().await {
+        let (request, reply_tx) = request_context.split();
+        let response = match request {
+            OutputManagerRequest::SetSecondaryBaseNodePublicKey(_) => {
+                OutputManagerResponse::SecondaryBaseNodePublicKeySet
+            },
+            OutputManagerRequest::SetChainTipHeight(_) => {
+                let _ = reply_tx.send(Ok(OutputManagerResponse::ChainTipHeightSet));
+                continue;
+            },
+            _ => OutputManagerResponse::BaseNodePublicKeySet,
+        };
+        requests.send(request).unwrap();
+        let _ = reply_tx.send(Ok(response));
+    }
+}
+
+fn setup_base_node_service(
+    runtime: &mut Runtime,
+    config: BaseNodeServiceConfig,
+) -> (
+    BaseNodeServiceHandle,
+    LivenessMockState,
+    mpsc::Receiver<CommsPublicKey>,
+    mpsc::Receiver<OutputManagerRequest>,
+    Shutdown,
+)
+{
+    let (liveness_handle, liveness_mock, _) = create_p2p_liveness_mock(100);
+    let liveness_mock_state = liveness_mock.get_mock_state();
+    runtime.spawn(liveness_mock.run());
+
+    let (ts_sender, ts_receiver) = reply_channel::unbounded();
+    let (ts_publisher, _) = broadcast::channel(100);
+    let (ts_requests_sender, ts_requests) = mpsc::channel();
+    runtime.spawn(mock_transaction_service(ts_receiver, ts_requests_sender));
+
+    let (oms_sender, oms_receiver) = reply_channel::unbounded();
+    let (_oms_publisher, oms_subscriber) = bounded(100);
+    let (oms_requests_sender, oms_requests) = mpsc::channel();
+    runtime.spawn(mock_output_manager_service(oms_receiver, oms_requests_sender));
+
+    let (sender, receiver) = reply_channel::unbounded();
+    let (publisher, _) = broadcast::channel(100);
+    let handle = BaseNodeServiceHandle::new(sender, publisher.clone());
+    let service = BaseNodeService::new(
+        config,
+        receiver,
+        liveness_handle,
+        TransactionServiceHandle::new(ts_sender, ts_publisher),
+        OutputManagerHandle::new(oms_sender, oms_subscriber),
+        publisher,
+    )
+    .start();
+    let shutdown = Shutdown::new();
+    let shutdown_signal = shutdown.to_signal();
+    runtime.spawn(async move {
+        futures::pin_mut!(service);
+        futures::future::select(service, shutdown_signal).await;
+    });
+
+    (handle, liveness_mock_state, ts_requests, oms_requests, shutdown)
+}
+
+fn pong(public_key: &CommsPublicKey, chain_height: u64) -> LivenessEvent {
+    let mut bytes = Vec::new();
+    BaseNodeProto::ChainMetadata {
+        height_of_longest_chain: Some(chain_height),
+        best_block: None,
+        pruning_horizon: 0,
+        accumulated_difficulty: None,
+    }
+    .encode(&mut bytes)
+    .unwrap();
+    let mut metadata = Metadata::new();
+    metadata.insert(MetadataKey::ChainMetadata, bytes);
+    LivenessEvent::ReceivedPong(Box::new(PingPongEvent::new(
+        NodeId::from_key(public_key).unwrap(),
+        Some(50),
+        metadata,
+        false,
+        true,
+    )))
+}
+
+#[test]
+fn base_node_failover() {
+    let mut runtime = Runtime::new().unwrap();
+    let (mut base_node_service, liveness_mock_state, ts_requests, oms_requests, _shutdown) =
+        setup_base_node_service(&mut runtime, BaseNodeServiceConfig {
+            max_missed_pings: 2,
+            max_block_lag: 5,
+            cross_check_utxo_queries: true,
+        });
+    let mut event_stream = base_node_service.get_event_stream_fused();
+    let timeout = Duration::from_secs(10);
+
+    let (_, node_a) = PublicKey::random_keypair(&mut OsRng);
+    let (_, node_b) = PublicKey::random_keypair(&mut OsRng);
+    let (_, node_c) = PublicKey::random_keypair(&mut OsRng);
+
+    runtime
+        .block_on(base_node_service.add_base_node(node_a.clone()))
+        .unwrap();
+    runtime
+        .block_on(base_node_service.add_base_node(node_b.clone()))
+        .unwrap();
+    runtime
+        .block_on(base_node_service.add_base_node(node_c.clone()))
+        .unwrap();
+    assert!(runtime
+        .block_on(base_node_service.add_base_node(node_c.clone()))
+        .is_err());
+    assert_eq!(liveness_mock_state.take_calls().len(), 3);
+
+    // The first base node added is used by both services and the second is used to cross-check UTXO queries
+    assert_eq!(ts_requests.recv_timeout(timeout).unwrap(), node_a);
+    match oms_requests.recv_timeout(timeout).unwrap() {
+        OutputManagerRequest::SetBaseNodePublicKey(pk) => assert_eq!(pk, node_a),
+        _ => panic!("Unexpected Output Manager request"),
+    }
+    match oms_requests.recv_timeout(timeout).unwrap() {
+        OutputManagerRequest::SetSecondaryBaseNodePublicKey(pk) => assert_eq!(pk, Some(node_b.clone())),
+        _ => panic!("Unexpected Output Manager request"),
+    }
+    assert_eq!(
+        runtime.block_on(base_node_service.get_current_base_node()).unwrap(),
+        Some(node_a.clone())
+    );
+
+    // Node A stops answering pings while nodes B and C keep up
+    runtime.block_on(async {
+        liveness_mock_state.publish_event(pong(&node_a, 100)).await.unwrap();
+        for _ in 0..3 {
+            liveness_mock_state
+                .publish_event(LivenessEvent::BroadcastedMonitoredNodeIdPings(3))
+                .await
+                .unwrap();
+            liveness_mock_state.publish_event(pong(&node_b, 100)).await.unwrap();
+            liveness_mock_state.publish_event(pong(&node_c, 100)).await.unwrap();
+        }
+    });
+    assert_eq!(ts_requests.recv_timeout(timeout).unwrap(), node_b);
+    match oms_requests.recv_timeout(timeout).unwrap() {
+        OutputManagerRequest::SetBaseNodePublicKey(pk) => assert_eq!(pk, node_b),
+        _ => panic!("Unexpected Output Manager request"),
+    }
+    match oms_requests.recv_timeout(timeout).unwrap() {
+        OutputManagerRequest::SetSecondaryBaseNodePublicKey(pk) => assert_eq!(pk, Some(node_c.clone())),
+        _ => panic!("Unexpected Output Manager request"),
+    }
+
+    // Node B falls too far behind the chain reported by node C
+    runtime.block_on(async {
+        liveness_mock_state.publish_event(pong(&node_c, 110)).await.unwrap();
+    });
+    assert_eq!(ts_requests.recv_timeout(timeout).unwrap(), node_c);
+
+    // Once node C is removed node B is the most up to date base node left that is still responding
+    runtime
+        .block_on(base_node_service.remove_base_node(node_c.clone()))
+        .unwrap();
+    assert_eq!(ts_requests.recv_timeout(timeout).unwrap(), node_b);
+    assert!(runtime
+        .block_on(base_node_service.remove_base_node(node_c.clone()))
+        .is_err());
+
+    let base_nodes = runtime.block_on(base_node_service.get_base_nodes()).unwrap();
+    assert_eq!(base_nodes.len(), 2);
+    assert_eq!(base_nodes[0].public_key, node_a);
+    assert_eq!(base_nodes[0].missed_pings, 3);
+    assert_eq!(base_nodes[1].chain_height, Some(100));
+
+    // Once every base node is removed there is no current base node
+    runtime
+        .block_on(base_node_service.remove_base_node(node_b.clone()))
+        .unwrap();
+    runtime
+        .block_on(base_node_service.remove_base_node(node_a.clone()))
+        .unwrap();
+    assert_eq!(
+        runtime.block_on(base_node_service.get_current_base_node()).unwrap(),
+        None
+    );
+
+    runtime.block_on(async {
+        let mut changed = Vec::new();
+        while let Some(Ok(event)) = event_stream.next().await {
+            match (*event).clone() {
+                BaseNodeEvent::BaseNodeChanged(pk) => changed.push(pk),
+                BaseNodeEvent::NoBaseNodes => break,
+                BaseNodeEvent::NoHealthyBaseNodes => (),
+            }
+        }
+        assert_eq!(changed, vec![node_a.clone(), node_b.clone(), node_c, node_b]);
+    });
+
+    // Adding a base node again makes it the current base node
+    runtime
+        .block_on(base_node_service.add_base_node(node_a.clone()))
+        .unwrap();
+    assert_eq!(
+        runtime.block_on(base_node_service.get_current_base_node()).unwrap(),
+        Some(node_a)
+    );
+}
@@ -22,6 +22,7 @@
 
 #![feature(type_alias_impl_trait)]
 
+pub mod base_node_service;
 pub mod output_manager_service;
 pub mod support;
 // pub mod text_message_service;
@@ -32,7 +32,7 @@ use futures::{
 };
 use prost::Message;
 use rand::{rngs::OsRng, RngCore};
-use std::{thread, time::Duration};
+use std::{sync::Arc, thread, time::Duration};
 use tari_broadcast_channel::bounded;
 use tari_comms::{
     message::EnvelopeBody,
@@ -42,9 +42,15 @@ use tari_comms_dht::outbound::mock::{create_outbound_service_mock, OutboundServi
 use tari_core::{
     base_node::proto::{
         base_node as BaseNodeProto,
-        base_node::base_node_service_response::Response as BaseNodeResponseProto,
+        base_node::{
+            base_node_service_request::Request as BaseNodeRequestProto,
+            base_node_service_response::Response as BaseNodeResponseProto,
+        },
     },
+    blocks::{Block, BlockHeader},
+    proto::core as CoreProto,
     transactions::{
+        aggregated_body::AggregateBody,
         fee::Fee,
         tari_amount::{uT, MicroTari},
         transaction::{KernelFeatures, OutputFeatures, Transaction, TransactionOutput, UnblindedOutput},
@@ -70,7 +76,14 @@ use tari_wallet::{
         handle::{OutputManagerEvent, OutputManagerHandle},
         service::OutputManagerService,
         storage::{
-            database::{DbKey, DbValue, OutputManagerBackend, OutputManagerDatabase},
+            database::{
+                DbKey,
+                DbValue,
+                OutputManagerBackend,
+                OutputManagerDatabase,
+                WatchedOutputStatus,
+                DEFAULT_ACCOUNT,
+            },
             memory_db::OutputManagerMemoryDatabase,
             sqlite_db::OutputManagerSqliteDatabase,
         },
@@ -89,6 +102,48 @@ pub fn setup_output_manager_service<T: OutputManagerBackend + 'static>(
     Shutdown,
     Sender<DomainMessage<BaseNodeProto::BaseNodeServiceResponse>>,
 )
+{
+    let node_identity = Arc::new(
+        NodeIdentity::random(
+            &mut OsRng,
+            "/ip4/127.0.0.1/tcp/58218".parse().unwrap(),
+            PeerFeatures::COMMUNICATION_NODE,
+        )
+        .unwrap(),
+    );
+    setup_output_manager_service_with_node_identity(runtime, backend, node_identity)
+}
+
+pub fn setup_output_manager_service_with_node_identity<T: OutputManagerBackend + 'static>(
+    runtime: &mut Runtime,
+    backend: T,
+    node_identity: Arc<NodeIdentity>,
+) -> (
+    OutputManagerHandle,
+    OutboundServiceMockState,
+    Shutdown,
+    Sender<DomainMessage<BaseNodeProto::BaseNodeServiceResponse>>,
+)
+{
+    setup_output_manager_service_with_config(runtime, backend, node_identity, OutputManagerServiceConfig {
+        base_node_query_timeout: Duration::from_secs(3),
+        dust_threshold: MicroTari::from(0),
+        watch_only: false,
+        ..Default::default()
+    })
+}
+
+pub fn setup_output_manager_service_with_config<T: OutputManagerBackend + 'static>(
+    runtime: &mut Runtime,
+    backend: T,
+    node_identity: Arc<NodeIdentity>,
+    config: OutputManagerServiceConfig,
+) -> (
+    OutputManagerHandle,
+    OutboundServiceMockState,
+    Shutdown,
+    Sender<DomainMessage<BaseNodeProto::BaseNodeServiceResponse>>,
+)
 {
     let shutdown = Shutdown::new();
     let factories = CryptoFactories::default();
@@ -100,15 +155,14 @@ pub fn setup_output_manager_service<T: OutputManagerBackend + 'static>(
 
     let output_manager_service = runtime
         .block_on(OutputManagerService::new(
-            OutputManagerServiceConfig {
-                base_node_query_timeout: Duration::from_secs(3),
-            },
+            config,
             outbound_message_requester.clone(),
             oms_request_receiver,
             base_node_response_receiver,
             OutputManagerDatabase::new(backend),
             oms_event_publisher,
             factories.clone(),
+            node_identity,
         ))
         .unwrap();
     let output_manager_service_handle = OutputManagerHandle::new(oms_request_sender, oms_event_subscriber);
@@ -199,8 +253,9 @@ fn sending_transaction_and_confirmation<T: Clone + OutputManagerBackend + 'stati
         num_outputs + 1 - runtime.block_on(oms.get_spent_outputs()).unwrap().len() + tx.body.outputs().len() - 1
     );
 
+    // A key is only used if a change output was needed
     if let DbValue::KeyManagerState(km) = backend.fetch(&DbKey::KeyManagerState).unwrap().unwrap() {
-        assert_eq!(km.primary_key_index, 1);
+        assert_eq!(km.primary_key_index, tx.body.outputs().len() - 1);
     } else {
         assert!(false, "No Key Manager set");
     }
@@ -966,3 +1021,877 @@ fn coin_split_no_change_sqlite_db() {
 
     coin_split_no_change(OutputManagerSqliteDatabase::new(connection));
 }
+
+fn one_sided_payment_send_and_scan<T: OutputManagerBackend + 'static>(sender_backend: T, receiver_backend: T) {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+    let (mut sender_oms, _, _shutdown, _) = setup_output_manager_service(&mut runtime, sender_backend);
+
+    let receiver_identity = Arc::new(
+        NodeIdentity::random(
+            &mut OsRng,
+            "/ip4/127.0.0.1/tcp/58219".parse().unwrap(),
+            PeerFeatures::COMMUNICATION_NODE,
+        )
+        .unwrap(),
+    );
+    let (mut receiver_oms, outbound_service, _shutdown2, mut base_node_response_sender) =
+        setup_output_manager_service_with_node_identity(&mut runtime, receiver_backend, receiver_identity.clone());
+
+    let (_ti, uo) = make_input(&mut OsRng.clone(), MicroTari::from(10_000), &factories.commitment);
+    runtime.block_on(sender_oms.add_output(uo)).unwrap();
+
+    let amount = MicroTari::from(4_000);
+    let fee_per_gram = MicroTari::from(20);
+    let (_tx_id, tx, fee, sent_amount) = runtime
+        .block_on(sender_oms.create_one_sided_transaction(
+            receiver_identity.public_key().clone(),
+            amount,
+            fee_per_gram,
+            None,
+            "Yo!".to_string(),
+        ))
+        .unwrap();
+    assert_eq!(sent_amount, amount);
+    assert_eq!(tx.body.outputs().len(), 2);
+    assert_eq!(fee, Fee::calculate(fee_per_gram, 1, 1, 2));
+    assert!(tx.body.kernels()[0].verify_signature().is_ok());
+
+    let balance = runtime.block_on(sender_oms.get_balance()).unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(0));
+    assert_eq!(balance.pending_incoming_balance, MicroTari::from(10_000) - amount - fee);
+
+    let base_node_identity = NodeIdentity::random(
+        &mut OsRng,
+        "/ip4/127.0.0.1/tcp/58220".parse().unwrap(),
+        PeerFeatures::COMMUNICATION_NODE,
+    )
+    .unwrap();
+    runtime
+        .block_on(receiver_oms.set_base_node_public_key(base_node_identity.public_key().clone()))
+        .unwrap();
+    outbound_service.wait_call_count(1, Duration::from_secs(10)).unwrap();
+    let _ = outbound_service.take_calls();
+
+    let request_key = runtime.block_on(receiver_oms.scan_for_one_sided_payments()).unwrap();
+    outbound_service.wait_call_count(1, Duration::from_secs(10)).unwrap();
+    let _ = outbound_service.take_calls();
+
+    let base_node_response = BaseNodeProto::BaseNodeServiceResponse {
+        request_key,
+        response: Some(BaseNodeResponseProto::ChainMetadata(BaseNodeProto::ChainMetadata {
+            height_of_longest_chain: Some(0),
+            best_block: None,
+            pruning_horizon: 0,
+            accumulated_difficulty: None,
+        })),
+    };
+    runtime
+        .block_on(base_node_response_sender.send(create_dummy_message(
+            base_node_response,
+            base_node_identity.public_key(),
+        )))
+        .unwrap();
+
+    let (_, body) = outbound_service.wait_pop_call(Duration::from_secs(10)).unwrap();
+    let envelope_body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
+    let bn_request: BaseNodeProto::BaseNodeServiceRequest = envelope_body
+        .decode_part::<BaseNodeProto::BaseNodeServiceRequest>(1)
+        .unwrap()
+        .unwrap();
+    match bn_request.request {
+        Some(BaseNodeRequestProto::FetchBlocks(heights)) => assert_eq!(heights.heights, vec![0]),
+        _ => panic!("Expected a FetchBlocks request"),
+    }
+
+    let base_node_response = BaseNodeProto::BaseNodeServiceResponse {
+        request_key: bn_request.request_key,
+        response: Some(BaseNodeResponseProto::HistoricalBlocks(
+            BaseNodeProto::HistoricalBlocks {
+                blocks: vec![CoreProto::HistoricalBlock {
+                    confirmations: 1,
+                    spent_commitments: vec![],
+                    block: Some(
+                        Block {
+                            header: BlockHeader::new(0),
+                            body: tx.body.clone(),
+                        }
+                        .into(),
+                    ),
+                }],
+            },
+        )),
+    };
+    runtime
+        .block_on(base_node_response_sender.send(create_dummy_message(
+            base_node_response,
+            base_node_identity.public_key(),
+        )))
+        .unwrap();
+
+    let result_stream = runtime.block_on(async {
+        collect_stream!(
+            receiver_oms.get_event_stream_fused().map(|i| (*i).clone()),
+            take = 1,
+            timeout = Duration::from_secs(30)
+        )
+    });
+    let sweep_fee = Fee::calculate(
+        OutputManagerServiceConfig::default().one_sided_payment_sweep_fee_per_gram,
+        1,
+        1,
+        1,
+    );
+    let sweep_tx = result_stream
+        .iter()
+        .filter_map(|e| {
+            if let OutputManagerEvent::ReceivedOneSidedPayment((_, tx, fee, v)) = e {
+                assert_eq!(*fee, sweep_fee);
+                assert_eq!(*v, amount);
+                Some(tx.clone())
+            } else {
+                None
+            }
+        })
+        .next()
+        .expect("Expected a ReceivedOneSidedPayment event");
+    // The payment is swept to a fresh key so the sender, who also knows the payment's spending key, cannot take it back
+    assert_eq!(sweep_tx.body.inputs().len(), 1);
+    assert_eq!(sweep_tx.body.outputs().len(), 1);
+    assert!(sweep_tx.body.kernels()[0].verify_signature().is_ok());
+
+    let balance = runtime.block_on(receiver_oms.get_balance()).unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(0));
+    assert_eq!(balance.pending_incoming_balance, amount - sweep_fee);
+
+    // A block that does not build on the last scanned block is a reorg and must not advance the scan
+    let request_key = runtime.block_on(receiver_oms.scan_for_one_sided_payments()).unwrap();
+    outbound_service.wait_call_count(1, Duration::from_secs(10)).unwrap();
+    let _ = outbound_service.take_calls();
+    let base_node_response = BaseNodeProto::BaseNodeServiceResponse {
+        request_key,
+        response: Some(BaseNodeResponseProto::ChainMetadata(BaseNodeProto::ChainMetadata {
+            height_of_longest_chain: Some(1),
+            best_block: None,
+            pruning_horizon: 0,
+            accumulated_difficulty: None,
+        })),
+    };
+    runtime
+        .block_on(base_node_response_sender.send(create_dummy_message(
+            base_node_response,
+            base_node_identity.public_key(),
+        )))
+        .unwrap();
+
+    let (_, body) = outbound_service.wait_pop_call(Duration::from_secs(10)).unwrap();
+    let envelope_body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
+    let bn_request: BaseNodeProto::BaseNodeServiceRequest = envelope_body
+        .decode_part::<BaseNodeProto::BaseNodeServiceRequest>(1)
+        .unwrap()
+        .unwrap();
+    match bn_request.request {
+        Some(BaseNodeRequestProto::FetchBlocks(heights)) => assert_eq!(heights.heights, vec![1]),
+        _ => panic!("Expected a FetchBlocks request"),
+    }
+
+    let mut orphan_header = BlockHeader::new(0);
+    orphan_header.height = 1;
+    orphan_header.prev_hash = vec![1u8; 32];
+    let base_node_response = BaseNodeProto::BaseNodeServiceResponse {
+        request_key: bn_request.request_key,
+        response: Some(BaseNodeResponseProto::HistoricalBlocks(
+            BaseNodeProto::HistoricalBlocks {
+                blocks: vec![CoreProto::HistoricalBlock {
+                    confirmations: 1,
+                    spent_commitments: vec![],
+                    block: Some(
+                        Block {
+                            header: orphan_header,
+                            body: AggregateBody::empty(),
+                        }
+                        .into(),
+                    ),
+                }],
+            },
+        )),
+    };
+    runtime
+        .block_on(base_node_response_sender.send(create_dummy_message(
+            base_node_response,
+            base_node_identity.public_key(),
+        )))
+        .unwrap();
+
+    let (_, body) = outbound_service.wait_pop_call(Duration::from_secs(10)).unwrap();
+    let envelope_body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
+    let bn_request: BaseNodeProto::BaseNodeServiceRequest = envelope_body
+        .decode_part::<BaseNodeProto::BaseNodeServiceRequest>(1)
+        .unwrap()
+        .unwrap();
+    match bn_request.request {
+        Some(BaseNodeRequestProto::FetchBlocks(heights)) => assert_eq!(heights.heights, vec![1]),
+        _ => panic!("Expected the scan to be rewound and block 1 to be fetched again"),
+    }
+}
+
+#[test]
+fn one_sided_payment_send_and_scan_memory_db() {
+    one_sided_payment_send_and_scan(OutputManagerMemoryDatabase::new(), OutputManagerMemoryDatabase::new());
+}
+
+#[test]
+fn one_sided_payment_send_and_scan_sqlite_db() {
+    let db_tempdir = TempDir::new(random_string(8).as_str()).unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let sender_connection =
+        run_migration_and_create_sqlite_connection(&format!("{}/{}.sqlite3", db_folder, random_string(8))).unwrap();
+    let receiver_connection =
+        run_migration_and_create_sqlite_connection(&format!("{}/{}.sqlite3", db_folder, random_string(8))).unwrap();
+
+    one_sided_payment_send_and_scan(
+        OutputManagerSqliteDatabase::new(sender_connection),
+        OutputManagerSqliteDatabase::new(receiver_connection),
+    );
+}
+
+fn coin_control<T: OutputManagerBackend + 'static>(backend: T) {
+    let factories = CryptoFactories::default();
+
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut oms, _, _shutdown, _) = setup_output_manager_service(&mut runtime, backend);
+
+    let (ti1, uo1) = make_input(&mut OsRng.clone(), MicroTari::from(1000), &factories.commitment);
+    let (ti2, uo2) = make_input(&mut OsRng.clone(), MicroTari::from(2000), &factories.commitment);
+    let (ti3, uo3) = make_input(&mut OsRng.clone(), MicroTari::from(5000), &factories.commitment);
+    runtime.block_on(oms.add_output(uo1.clone())).unwrap();
+    runtime.block_on(oms.add_output(uo2.clone())).unwrap();
+    runtime.block_on(oms.add_output(uo3.clone())).unwrap();
+
+    runtime.block_on(oms.freeze_output(ti3.commitment.clone())).unwrap();
+    runtime
+        .block_on(oms.set_output_label(ti2.commitment.clone(), Some("Treasury".to_string())))
+        .unwrap();
+
+    let annotated = runtime.block_on(oms.get_annotated_unspent_outputs()).unwrap();
+    assert_eq!(annotated.len(), 3);
+    let annotated1 = annotated.iter().find(|a| a.commitment == ti1.commitment).unwrap();
+    assert_eq!(annotated1.output, uo1);
+    assert_eq!(annotated1.label, None);
+    assert!(!annotated1.frozen);
+    let annotated2 = annotated.iter().find(|a| a.commitment == ti2.commitment).unwrap();
+    assert_eq!(annotated2.label, Some("Treasury".to_string()));
+    assert!(!annotated2.frozen);
+    assert!(
+        annotated
+            .iter()
+            .find(|a| a.commitment == ti3.commitment)
+            .unwrap()
+            .frozen
+    );
+
+    // The frozen output cannot be selected automatically
+    match runtime.block_on(oms.prepare_transaction_to_send(
+        MicroTari::from(4000),
+        MicroTari::from(20),
+        None,
+        "".to_string(),
+    )) {
+        Err(OutputManagerError::NotEnoughFunds) => assert!(true),
+        _ => assert!(false),
+    }
+
+    let (ti4, _) = make_input(&mut OsRng.clone(), MicroTari::from(5000), &factories.commitment);
+    match runtime.block_on(oms.prepare_transaction_to_send_with_inputs(
+        MicroTari::from(4000),
+        MicroTari::from(20),
+        None,
+        "".to_string(),
+        vec![ti4.commitment],
+    )) {
+        Err(OutputManagerError::UnspentOutputNotFound) => assert!(true),
+        _ => assert!(false),
+    }
+
+    // But it can be spent explicitly
+    let stp = runtime
+        .block_on(oms.prepare_transaction_to_send_with_inputs(
+            MicroTari::from(4000),
+            MicroTari::from(20),
+            None,
+            "".to_string(),
+            vec![ti3.commitment.clone()],
+        ))
+        .unwrap();
+    let pending_txs = runtime.block_on(oms.get_pending_transactions()).unwrap();
+    let pending_tx = pending_txs.get(&stp.get_tx_id().unwrap()).unwrap();
+    assert_eq!(pending_tx.outputs_to_be_spent, vec![uo3]);
+    assert_eq!(pending_tx.outputs_to_be_received.len(), 1);
+
+    runtime.block_on(oms.unfreeze_output(ti1.commitment.clone())).unwrap();
+    runtime
+        .block_on(oms.set_output_label(ti2.commitment.clone(), None))
+        .unwrap();
+    let annotated = runtime.block_on(oms.get_annotated_unspent_outputs()).unwrap();
+    assert_eq!(annotated.len(), 2);
+    assert!(annotated.iter().all(|a| a.label.is_none() && !a.frozen));
+
+    // The excess over the fee is too small to pay for a change output, so it goes to the fee instead
+    let fee_without_change = Fee::calculate(MicroTari::from(20), 1, 1, 1);
+    let fee_with_change = Fee::calculate(MicroTari::from(20), 1, 1, 2);
+    let amount = MicroTari::from(1000) - fee_without_change - MicroTari::from(1);
+    assert!(MicroTari::from(1000) < amount + fee_with_change);
+    let stp = runtime
+        .block_on(
+            oms.prepare_transaction_to_send_with_inputs(amount, MicroTari::from(20), None, "".to_string(), vec![ti1
+                .commitment
+                .clone()]),
+        )
+        .unwrap();
+    let pending_txs = runtime.block_on(oms.get_pending_transactions()).unwrap();
+    let pending_tx = pending_txs.get(&stp.get_tx_id().unwrap()).unwrap();
+    assert_eq!(pending_tx.outputs_to_be_spent, vec![uo1]);
+    assert!(pending_tx.outputs_to_be_received.is_empty());
+
+    runtime.block_on(oms.freeze_output(ti2.commitment.clone())).unwrap();
+    runtime
+        .block_on(oms.set_output_label(ti2.commitment.clone(), Some("Treasury".to_string())))
+        .unwrap();
+    runtime
+        .block_on(oms.clear_output_annotation(ti2.commitment.clone()))
+        .unwrap();
+    let annotated = runtime.block_on(oms.get_annotated_unspent_outputs()).unwrap();
+    assert_eq!(annotated.len(), 1);
+    assert!(annotated.iter().all(|a| a.label.is_none() && !a.frozen));
+    // Clearing an output without an annotation does nothing
+    runtime
+        .block_on(oms.clear_output_annotation(ti2.commitment.clone()))
+        .unwrap();
+}
+
+#[test]
+fn coin_control_memory_db() {
+    coin_control(OutputManagerMemoryDatabase::new());
+}
+
+#[test]
+fn coin_control_sqlite_db() {
+    let db_name = format!("{}.sqlite3", random_string(8).as_str());
+    let db_tempdir = TempDir::new(random_string(8).as_str()).unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+
+    coin_control(OutputManagerSqliteDatabase::new(connection));
+}
+
+fn immature_outputs_are_not_selected<T: OutputManagerBackend + 'static>(backend: T) {
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut oms, _, _shutdown, _) = setup_output_manager_service(&mut runtime, backend);
+
+    let mature = UnblindedOutput::new(MicroTari::from(2000), PrivateKey::random(&mut OsRng), None);
+    let immature = UnblindedOutput::new(
+        MicroTari::from(5000),
+        PrivateKey::random(&mut OsRng),
+        Some(OutputFeatures::with_maturity(10)),
+    );
+    runtime.block_on(oms.add_output(mature)).unwrap();
+    runtime.block_on(oms.add_output(immature.clone())).unwrap();
+
+    runtime.block_on(oms.set_chain_tip_height(5)).unwrap();
+    match runtime.block_on(oms.prepare_transaction_to_send(
+        MicroTari::from(3000),
+        MicroTari::from(20),
+        None,
+        "".to_string(),
+    )) {
+        Err(OutputManagerError::NotEnoughFunds) => assert!(true),
+        _ => assert!(false),
+    }
+
+    runtime.block_on(oms.set_chain_tip_height(10)).unwrap();
+    let stp = runtime
+        .block_on(oms.prepare_transaction_to_send(MicroTari::from(3000), MicroTari::from(20), None, "".to_string()))
+        .unwrap();
+    let pending_txs = runtime.block_on(oms.get_pending_transactions()).unwrap();
+    let pending_tx = pending_txs.get(&stp.get_tx_id().unwrap()).unwrap();
+    assert_eq!(pending_tx.outputs_to_be_spent, vec![immature]);
+}
+
+#[test]
+fn immature_outputs_are_not_selected_memory_db() {
+    immature_outputs_are_not_selected(OutputManagerMemoryDatabase::new());
+}
+
+#[test]
+fn immature_outputs_are_not_selected_sqlite_db() {
+    let db_name = format!("{}.sqlite3", random_string(8).as_str());
+    let db_tempdir = TempDir::new(random_string(8).as_str()).unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+
+    immature_outputs_are_not_selected(OutputManagerSqliteDatabase::new(connection));
+}
+
+fn time_locked_balance<T: OutputManagerBackend + 'static>(backend: T) {
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut oms, _, _shutdown, _) = setup_output_manager_service(&mut runtime, backend);
+
+    let mature = UnblindedOutput::new(MicroTari::from(2000), PrivateKey::random(&mut OsRng), None);
+    let immature = UnblindedOutput::new(
+        MicroTari::from(5000),
+        PrivateKey::random(&mut OsRng),
+        Some(OutputFeatures::with_maturity(10)),
+    );
+    runtime.block_on(oms.add_output(mature)).unwrap();
+    runtime.block_on(oms.add_output(immature)).unwrap();
+
+    let balance = runtime.block_on(oms.get_balance()).unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(7000));
+    assert_eq!(balance.time_locked_balance, None);
+
+    runtime.block_on(oms.set_chain_tip_height(5)).unwrap();
+    let balance = runtime.block_on(oms.get_balance()).unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(7000));
+    assert_eq!(balance.time_locked_balance, Some(MicroTari::from(5000)));
+
+    runtime.block_on(oms.set_chain_tip_height(10)).unwrap();
+    let balance = runtime.block_on(oms.get_balance()).unwrap();
+    assert_eq!(balance.time_locked_balance, Some(MicroTari::from(0)));
+}
+
+#[test]
+fn time_locked_balance_memory_db() {
+    time_locked_balance(OutputManagerMemoryDatabase::new());
+}
+
+#[test]
+fn time_locked_balance_sqlite_db() {
+    let db_name = format!("{}.sqlite3", random_string(8).as_str());
+    let db_tempdir = TempDir::new(random_string(8).as_str()).unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+
+    time_locked_balance(OutputManagerSqliteDatabase::new(connection));
+}
+
+fn time_locked_send_and_receive<T: OutputManagerBackend + 'static>(backend: T) {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut oms, _, _shutdown, _) = setup_output_manager_service(&mut runtime, backend);
+
+    let (_ti, uo) = make_input(&mut OsRng.clone(), MicroTari::from(10_000), &factories.commitment);
+    runtime.block_on(oms.add_output(uo)).unwrap();
+
+    let mut stp = runtime
+        .block_on(oms.prepare_time_locked_transaction_to_send(
+            MicroTari::from(1000),
+            MicroTari::from(20),
+            Some(7),
+            20,
+            "".to_string(),
+            None,
+        ))
+        .unwrap();
+    let msg = stp.build_single_round_message().unwrap();
+    assert_eq!(msg.metadata.lock_height, 7);
+    assert_eq!(msg.maturity, 20);
+
+    let value = MicroTari::from(2500);
+    runtime
+        .block_on(oms.get_recipient_spending_key_with_maturity(1, value, 20))
+        .unwrap();
+    let pending_txs = runtime.block_on(oms.get_pending_transactions()).unwrap();
+    let received = &pending_txs.get(&1).unwrap().outputs_to_be_received;
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].value, value);
+    assert_eq!(received[0].features.maturity, 20);
+}
+
+#[test]
+fn time_locked_send_and_receive_memory_db() {
+    time_locked_send_and_receive(OutputManagerMemoryDatabase::new());
+}
+
+#[test]
+fn time_locked_send_and_receive_sqlite_db() {
+    let db_name = format!("{}.sqlite3", random_string(8).as_str());
+    let db_tempdir = TempDir::new(random_string(8).as_str()).unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+
+    time_locked_send_and_receive(OutputManagerSqliteDatabase::new(connection));
+}
+
+fn coin_consolidation<T: Clone + OutputManagerBackend + 'static>(backend: T) {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+    let (mut oms, _, _, _) = setup_output_manager_service(&mut runtime, backend.clone());
+
+    let (_ti, uo1) = make_input(&mut OsRng.clone(), 100 * uT, &factories.commitment);
+    assert!(runtime.block_on(oms.add_output(uo1)).is_ok());
+
+    let fee_per_gram = MicroTari::from(5);
+    match runtime.block_on(oms.create_coin_consolidation(10, fee_per_gram, None)) {
+        Err(OutputManagerError::NothingToConsolidate) => assert!(true),
+        _ => assert!(false),
+    }
+
+    let (_ti, uo2) = make_input(&mut OsRng.clone(), 200 * uT, &factories.commitment);
+    let (_ti, uo3) = make_input(&mut OsRng.clone(), 300 * uT, &factories.commitment);
+    let (_ti, uo4) = make_input(&mut OsRng.clone(), 10_000 * uT, &factories.commitment);
+    assert!(runtime.block_on(oms.add_output(uo2)).is_ok());
+    assert!(runtime.block_on(oms.add_output(uo3)).is_ok());
+    assert!(runtime.block_on(oms.add_output(uo4)).is_ok());
+    // Worth no more than the fee of spending it, so it is never consolidated
+    let (_ti, dust) = make_input(&mut OsRng.clone(), 5 * uT, &factories.commitment);
+    assert!(runtime.block_on(oms.add_output(dust)).is_ok());
+
+    let candidates = runtime
+        .block_on(oms.get_consolidation_candidates(fee_per_gram))
+        .unwrap();
+    assert_eq!(candidates.iter().map(|o| o.value).collect::<Vec<_>>(), vec![
+        100 * uT,
+        200 * uT,
+        300 * uT,
+        10_000 * uT
+    ]);
+
+    let (_tx_id, consolidation_tx, fee, amount) = runtime
+        .block_on(oms.create_coin_consolidation(3, fee_per_gram, None))
+        .unwrap();
+    assert_eq!(consolidation_tx.body.inputs().len(), 3);
+    assert_eq!(consolidation_tx.body.outputs().len(), 1);
+    assert_eq!(fee, Fee::calculate(fee_per_gram, 1, 3, 1));
+    assert_eq!(amount, 600 * uT);
+
+    let balance = runtime.block_on(oms.get_balance()).unwrap();
+    assert_eq!(balance.available_balance, 10_005 * uT);
+    assert_eq!(balance.pending_incoming_balance, 600 * uT - fee);
+}
+
+#[test]
+fn coin_consolidation_memory_db() {
+    coin_consolidation(OutputManagerMemoryDatabase::new());
+}
+
+#[test]
+fn coin_consolidation_sqlite_db() {
+    let db_name = format!("{}.sqlite3", random_string(8).as_str());
+    let db_tempdir = TempDir::new(random_string(8).as_str()).unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+
+    coin_consolidation(OutputManagerSqliteDatabase::new(connection));
+}
+
+fn sub_accounts<T: OutputManagerBackend + 'static>(backend: T) {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+    let (mut oms, _, _shutdown, _) = setup_output_manager_service(&mut runtime, backend);
+
+    let (_ti, uo) = make_input(&mut OsRng.clone(), MicroTari::from(10_000), &factories.commitment);
+    runtime.block_on(oms.add_output(uo)).unwrap();
+
+    runtime.block_on(oms.create_account("hot".to_string())).unwrap();
+    match runtime.block_on(oms.create_account("hot".to_string())) {
+        Err(OutputManagerError::AccountAlreadyExists) => assert!(true),
+        _ => assert!(false),
+    }
+    match runtime.block_on(oms.create_account(DEFAULT_ACCOUNT.to_string())) {
+        Err(OutputManagerError::InvalidAccountName) => assert!(true),
+        _ => assert!(false),
+    }
+    match runtime.block_on(oms.set_receiving_account("cold".to_string())) {
+        Err(OutputManagerError::AccountNotFound) => assert!(true),
+        _ => assert!(false),
+    }
+    runtime.block_on(oms.set_receiving_account("hot".to_string())).unwrap();
+    let accounts = runtime.block_on(oms.get_accounts()).unwrap();
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0].name, "hot".to_string());
+    assert!(accounts[0].receiving);
+
+    // Received funds are assigned to the receiving account
+    let value = MicroTari::from(5000);
+    let recv_key = runtime.block_on(oms.get_recipient_spending_key(1, value)).unwrap();
+    let commitment = factories.commitment.commit(&recv_key, &value.into());
+    let rr = factories.range_proof.construct_proof(&recv_key, value.into()).unwrap();
+    let output = TransactionOutput::new(
+        OutputFeatures::default(),
+        commitment,
+        RangeProof::from_bytes(&rr).unwrap(),
+    );
+    runtime
+        .block_on(oms.confirm_transaction(1, vec![], vec![output]))
+        .unwrap();
+
+    let hot_balance = runtime.block_on(oms.get_account_balance("hot".to_string())).unwrap();
+    assert_eq!(hot_balance.available_balance, value);
+    let default_balance = runtime
+        .block_on(oms.get_account_balance(DEFAULT_ACCOUNT.to_string()))
+        .unwrap();
+    assert_eq!(default_balance.available_balance, MicroTari::from(10_000));
+    let balance = runtime.block_on(oms.get_balance()).unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(15_000));
+
+    // Sends only spend the outputs of the source account and return the change to it
+    match runtime.block_on(oms.prepare_transaction_to_send_from_account(
+        "hot".to_string(),
+        MicroTari::from(8000),
+        MicroTari::from(20),
+        None,
+        0,
+        "".to_string(),
+        None,
+    )) {
+        Err(OutputManagerError::NotEnoughFunds) => assert!(true),
+        _ => assert!(false),
+    }
+    let stp = runtime
+        .block_on(oms.prepare_transaction_to_send_from_account(
+            "hot".to_string(),
+            MicroTari::from(1000),
+            MicroTari::from(20),
+            None,
+            0,
+            "".to_string(),
+            None,
+        ))
+        .unwrap();
+    let tx_id = stp.get_tx_id().unwrap();
+
+    let hot_balance = runtime.block_on(oms.get_account_balance("hot".to_string())).unwrap();
+    assert_eq!(hot_balance.available_balance, MicroTari::from(0));
+    assert_eq!(hot_balance.pending_outgoing_balance, value);
+    assert_eq!(hot_balance.pending_incoming_balance, stp.get_amount_to_self().unwrap());
+    let default_balance = runtime
+        .block_on(oms.get_account_balance(DEFAULT_ACCOUNT.to_string()))
+        .unwrap();
+    assert_eq!(default_balance.available_balance, MicroTari::from(10_000));
+    assert_eq!(default_balance.pending_outgoing_balance, MicroTari::from(0));
+
+    let transaction_accounts = runtime.block_on(oms.get_transaction_accounts()).unwrap();
+    assert_eq!(transaction_accounts.get(&1), Some(&"hot".to_string()));
+    assert_eq!(transaction_accounts.get(&tx_id), Some(&"hot".to_string()));
+}
+
+#[test]
+fn sub_accounts_memory_db() {
+    sub_accounts(OutputManagerMemoryDatabase::new());
+}
+
+#[test]
+fn sub_accounts_sqlite_db() {
+    let db_name = format!("{}.sqlite3", random_string(8).as_str());
+    let db_tempdir = TempDir::new(random_string(8).as_str()).unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+
+    sub_accounts(OutputManagerSqliteDatabase::new(connection));
+}
+
+fn watch_only_wallet<T: OutputManagerBackend + 'static>(backend: T) {
+    let factories = CryptoFactories::default();
+
+    let mut runtime = Runtime::new().unwrap();
+
+    let node_identity = Arc::new(
+        NodeIdentity::random(
+            &mut OsRng,
+            "/ip4/127.0.0.1/tcp/58221".parse().unwrap(),
+            PeerFeatures::COMMUNICATION_NODE,
+        )
+        .unwrap(),
+    );
+    let (mut oms, outbound_service, _shutdown, mut base_node_response_sender) =
+        setup_output_manager_service_with_config(&mut runtime, backend, node_identity, OutputManagerServiceConfig {
+            base_node_query_timeout: Duration::from_secs(30),
+            dust_threshold: MicroTari::from(0),
+            watch_only: true,
+            ..Default::default()
+        });
+
+    // Anything that needs spending keys is refused
+    let (ti1, uo1) = make_input(&mut OsRng.clone(), MicroTari::from(1000), &factories.commitment);
+    match runtime.block_on(oms.add_output(uo1.clone())) {
+        Err(OutputManagerError::WatchOnlyWallet) => assert!(true),
+        _ => assert!(false),
+    }
+    match runtime.block_on(oms.prepare_transaction_to_send(
+        MicroTari::from(500),
+        MicroTari::from(20),
+        None,
+        "".to_string(),
+    )) {
+        Err(OutputManagerError::WatchOnlyWallet) => assert!(true),
+        _ => assert!(false),
+    }
+    match runtime.block_on(oms.get_recipient_spending_key(1, MicroTari::from(500))) {
+        Err(OutputManagerError::WatchOnlyWallet) => assert!(true),
+        _ => assert!(false),
+    }
+    match runtime.block_on(oms.get_seed_words()) {
+        Err(OutputManagerError::WatchOnlyWallet) => assert!(true),
+        _ => assert!(false),
+    }
+
+    // Outputs are watched using public data only
+    let (ti2, uo2) = make_input(&mut OsRng.clone(), MicroTari::from(2000), &factories.commitment);
+    runtime
+        .block_on(oms.add_watched_output(
+            ti1.commitment.clone(),
+            ti1.features.clone(),
+            uo1.value,
+            Some("Cold storage".to_string()),
+        ))
+        .unwrap();
+    runtime
+        .block_on(oms.add_watched_output(ti2.commitment.clone(), ti2.features.clone(), uo2.value, None))
+        .unwrap();
+    match runtime.block_on(oms.add_watched_output(ti2.commitment.clone(), ti2.features.clone(), uo2.value, None)) {
+        Err(OutputManagerError::DuplicateOutput) => assert!(true),
+        _ => assert!(false),
+    }
+
+    let balance = runtime.block_on(oms.get_balance()).unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(0));
+    assert_eq!(balance.pending_incoming_balance, MicroTari::from(3000));
+
+    // The base node reports the first output as unspent
+    let base_node_identity = NodeIdentity::random(
+        &mut OsRng,
+        "/ip4/127.0.0.1/tcp/58222".parse().unwrap(),
+        PeerFeatures::COMMUNICATION_NODE,
+    )
+    .unwrap();
+    runtime
+        .block_on(oms.set_base_node_public_key(base_node_identity.public_key().clone()))
+        .unwrap();
+    let (_, body) = outbound_service.wait_pop_call(Duration::from_secs(10)).unwrap();
+    let envelope_body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
+    let bn_request: BaseNodeProto::BaseNodeServiceRequest = envelope_body
+        .decode_part::<BaseNodeProto::BaseNodeServiceRequest>(1)
+        .unwrap()
+        .unwrap();
+    match bn_request.request {
+        Some(BaseNodeRequestProto::FetchUtxos(hash_outputs)) => assert_eq!(hash_outputs.outputs.len(), 2),
+        _ => assert!(false),
+    }
+
+    let mut event_stream = oms.get_event_stream_fused();
+    let base_node_response = BaseNodeProto::BaseNodeServiceResponse {
+        request_key: bn_request.request_key,
+        response: Some(BaseNodeResponseProto::TransactionOutputs(
+            BaseNodeProto::TransactionOutputs {
+                outputs: vec![uo1.as_transaction_output(&factories).unwrap().into()].into(),
+            },
+        )),
+    };
+    runtime
+        .block_on(base_node_response_sender.send(create_dummy_message(
+            base_node_response,
+            base_node_identity.public_key(),
+        )))
+        .unwrap();
+    runtime.block_on(async {
+        let mut delay = delay_for(Duration::from_secs(30)).fuse();
+        loop {
+            futures::select! {
+                event = event_stream.select_next_some() => {
+                    if let OutputManagerEvent::ReceiveBaseNodeResponse(_) = (*event).clone() {
+                        break;
+                    }
+                },
+                () = delay => panic!("Did not receive the base node response event"),
+            }
+        }
+    });
+
+    let watched = runtime.block_on(oms.get_watched_outputs()).unwrap();
+    let watched1 = watched.iter().find(|o| o.commitment == ti1.commitment).unwrap();
+    assert_eq!(watched1.status, WatchedOutputStatus::Unspent);
+    assert_eq!(watched1.label, Some("Cold storage".to_string()));
+    let watched2 = watched.iter().find(|o| o.commitment == ti2.commitment).unwrap();
+    assert_eq!(watched2.status, WatchedOutputStatus::Unconfirmed);
+
+    let balance = runtime.block_on(oms.get_balance()).unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(1000));
+    assert_eq!(balance.pending_incoming_balance, MicroTari::from(2000));
+
+    // When it is no longer reported it has been spent
+    runtime.block_on(oms.sync_with_base_node()).unwrap();
+    let (_, body) = outbound_service.wait_pop_call(Duration::from_secs(10)).unwrap();
+    let envelope_body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
+    let bn_request: BaseNodeProto::BaseNodeServiceRequest = envelope_body
+        .decode_part::<BaseNodeProto::BaseNodeServiceRequest>(1)
+        .unwrap()
+        .unwrap();
+    let mut event_stream = oms.get_event_stream_fused();
+    let base_node_response = BaseNodeProto::BaseNodeServiceResponse {
+        request_key: bn_request.request_key,
+        response: Some(BaseNodeResponseProto::TransactionOutputs(
+            BaseNodeProto::TransactionOutputs { outputs: vec![].into() },
+        )),
+    };
+    runtime
+        .block_on(base_node_response_sender.send(create_dummy_message(
+            base_node_response,
+            base_node_identity.public_key(),
+        )))
+        .unwrap();
+    runtime.block_on(async {
+        let mut delay = delay_for(Duration::from_secs(30)).fuse();
+        loop {
+            futures::select! {
+                event = event_stream.select_next_some() => {
+                    if let OutputManagerEvent::ReceiveBaseNodeResponse(_) = (*event).clone() {
+                        break;
+                    }
+                },
+                () = delay => panic!("Did not receive the base node response event"),
+            }
+        }
+    });
+
+    let watched = runtime.block_on(oms.get_watched_outputs()).unwrap();
+    let watched1 = watched.iter().find(|o| o.commitment == ti1.commitment).unwrap();
+    assert_eq!(watched1.status, WatchedOutputStatus::Spent);
+    let balance = runtime.block_on(oms.get_balance()).unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(0));
+    assert_eq!(balance.pending_incoming_balance, MicroTari::from(2000));
+
+    runtime
+        .block_on(oms.remove_watched_output(ti2.commitment.clone()))
+        .unwrap();
+    match runtime.block_on(oms.remove_watched_output(ti2.commitment.clone())) {
+        Err(OutputManagerError::WatchedOutputNotFound) => assert!(true),
+        _ => assert!(false),
+    }
+    assert_eq!(runtime.block_on(oms.get_watched_outputs()).unwrap().len(), 1);
+}
+
+#[test]
+fn watch_only_wallet_memory_db() {
+    watch_only_wallet(OutputManagerMemoryDatabase::new());
+}
+
+#[test]
+fn watch_only_wallet_sqlite_db() {
+    let db_name = format!("{}.sqlite3", random_string(8).as_str());
+    let db_tempdir = TempDir::new(random_string(8).as_str()).unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+
+    watch_only_wallet(OutputManagerSqliteDatabase::new(connection));
+}
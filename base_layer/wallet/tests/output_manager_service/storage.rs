@@ -124,11 +124,12 @@ pub fn test_db_backend<T: OutputManagerBackend + 'static>(backend: T) {
             .fold(MicroTari::from(0), |acc, x| acc + x.value);
     }
 
-    let balance = runtime.block_on(db.get_balance()).unwrap();
+    let balance = runtime.block_on(db.get_balance(None)).unwrap();
     assert_eq!(balance, Balance {
         available_balance,
         pending_incoming_balance,
-        pending_outgoing_balance
+        pending_outgoing_balance,
+        time_locked_balance: None
     });
 
     runtime
@@ -150,11 +151,12 @@ pub fn test_db_backend<T: OutputManagerBackend + 'static>(backend: T) {
         .iter()
         .fold(MicroTari::from(0), |acc, x| acc + x.value);
 
-    let balance = runtime.block_on(db.get_balance()).unwrap();
+    let balance = runtime.block_on(db.get_balance(None)).unwrap();
     assert_eq!(balance, Balance {
         available_balance,
         pending_incoming_balance,
-        pending_outgoing_balance
+        pending_outgoing_balance,
+        time_locked_balance: None
     });
 
     let spent_outputs = runtime.block_on(db.fetch_spent_outputs()).unwrap();
@@ -184,11 +186,12 @@ pub fn test_db_backend<T: OutputManagerBackend + 'static>(backend: T) {
     pending_incoming_balance += uo_change.clone().value;
     pending_outgoing_balance += total_encumbered;
 
-    let balance = runtime.block_on(db.get_balance()).unwrap();
+    let balance = runtime.block_on(db.get_balance(None)).unwrap();
     assert_eq!(balance, Balance {
         available_balance,
         pending_incoming_balance,
-        pending_outgoing_balance
+        pending_outgoing_balance,
+        time_locked_balance: None
     });
 
     let (_ti, uo_incoming) = make_input(
@@ -207,11 +210,12 @@ pub fn test_db_backend<T: OutputManagerBackend + 'static>(backend: T) {
 
     pending_incoming_balance += uo_incoming.clone().value;
 
-    let balance = runtime.block_on(db.get_balance()).unwrap();
+    let balance = runtime.block_on(db.get_balance(None)).unwrap();
     assert_eq!(balance, Balance {
         available_balance,
         pending_incoming_balance,
-        pending_outgoing_balance
+        pending_outgoing_balance,
+        time_locked_balance: None
     });
 
     runtime
@@ -234,11 +238,12 @@ pub fn test_db_backend<T: OutputManagerBackend + 'static>(backend: T) {
     pending_incoming_balance -= cancelled_incoming;
     pending_outgoing_balance -= cancelled_outgoing;
 
-    let balance = runtime.block_on(db.get_balance()).unwrap();
+    let balance = runtime.block_on(db.get_balance(None)).unwrap();
     assert_eq!(balance, Balance {
         available_balance,
         pending_incoming_balance,
-        pending_outgoing_balance
+        pending_outgoing_balance,
+        time_locked_balance: None
     });
 
     let remaining_p_tx = runtime.block_on(db.fetch_all_pending_transaction_outputs()).unwrap();
@@ -379,12 +384,12 @@ pub async fn test_short_term_encumberance<T: OutputManagerBackend + 'static>(bac
     .await
     .unwrap();
 
-    let balance = db.get_balance().await.unwrap();
+    let balance = db.get_balance(None).await.unwrap();
     assert_eq!(balance.available_balance, MicroTari(0));
 
     db.clear_short_term_encumberances().await.unwrap();
 
-    let balance = db.get_balance().await.unwrap();
+    let balance = db.get_balance(None).await.unwrap();
     assert_eq!(available_balance, balance.available_balance);
 
     pending_tx.outputs_to_be_received.clear();
@@ -400,7 +405,7 @@ pub async fn test_short_term_encumberance<T: OutputManagerBackend + 'static>(bac
     db.confirm_encumbered_outputs(pending_tx.tx_id).await.unwrap();
     db.clear_short_term_encumberances().await.unwrap();
 
-    let balance = db.get_balance().await.unwrap();
+    let balance = db.get_balance(None).await.unwrap();
     assert_eq!(balance.available_balance, MicroTari(0));
 
     pending_tx.outputs_to_be_received.clear();
@@ -417,7 +422,7 @@ pub async fn test_short_term_encumberance<T: OutputManagerBackend + 'static>(bac
 
     db.confirm_pending_transaction_outputs(pending_tx.tx_id).await.unwrap();
 
-    let balance = db.get_balance().await.unwrap();
+    let balance = db.get_balance(None).await.unwrap();
     assert_eq!(balance.available_balance, pending_tx.outputs_to_be_received[0].value);
 }
 
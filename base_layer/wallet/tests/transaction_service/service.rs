@@ -45,17 +45,24 @@ use tari_comms::{
     peer_manager::{NodeIdentity, PeerFeatures},
     CommsNode,
 };
-use tari_comms_dht::outbound::mock::{create_outbound_service_mock, OutboundServiceMockState};
+use tari_comms_dht::{
+    domain_message::MessageHeader,
+    outbound::mock::{create_outbound_service_mock, OutboundServiceMockState},
+};
 use tari_core::{
     base_node::proto::{
         base_node as BaseNodeProto,
-        base_node::base_node_service_response::Response as BaseNodeResponseProto,
+        base_node::{
+            base_node_service_request::Request as BaseNodeRequestProto,
+            base_node_service_response::Response as BaseNodeResponseProto,
+        },
     },
     mempool::{
         proto::mempool as MempoolProto,
         service::{MempoolRequest, MempoolResponse, MempoolServiceRequest},
         TxStorageResponse,
     },
+    proto::core as CoreProto,
     transactions::{
         proto::types::TransactionOutput as TransactionOutputProto,
         tari_amount::*,
@@ -87,6 +94,7 @@ use tari_p2p::{
             PingPongEvent,
         },
     },
+    tari_message::TariMessageType,
 };
 use tari_service_framework::{reply_channel, StackBuilder};
 use tari_test_utils::{collect_stream, paths::with_temp_dir};
@@ -101,7 +109,16 @@ use tari_wallet::{
     storage::connection_manager::run_migration_and_create_sqlite_connection,
     transaction_service::{
         config::TransactionServiceConfig,
+        error::TransactionServiceError,
         handle::{TransactionEvent, TransactionServiceHandle},
+        history::{TransactionDirection, TransactionHistoryFilter},
+        offline::{
+            OfflineFinalizedTransaction,
+            OfflineRecipientReply,
+            OfflineSenderMessage,
+            OfflineTransactionFile,
+            UnsignedTransactionRequest,
+        },
         service::TransactionService,
         storage::{
             database::{
@@ -163,11 +180,13 @@ pub fn setup_transaction_service<T: TransactionBackend + Clone + 'static>(
             subscription_factory.clone(),
             OutputManagerMemoryDatabase::new(),
             factories.clone(),
+            comms.node_identity(),
         ))
         .add_initializer(TransactionServiceInitializer::new(
             TransactionServiceConfig {
                 mempool_broadcast_timeout: Duration::from_secs(5),
                 base_node_mined_timeout: Duration::from_secs(5),
+                num_confirmations_required: 0,
                 ..Default::default()
             },
             subscription_factory.clone(),
@@ -211,6 +230,66 @@ pub fn setup_transaction_service_no_comms<T: TransactionBackend + Clone + 'stati
     Sender<DomainMessage<proto::TransactionSenderMessage>>,
     Sender<DomainMessage<proto::RecipientSignedMessage>>,
     Sender<DomainMessage<proto::TransactionFinalizedMessage>>,
+    Sender<DomainMessage<proto::TransactionCancelledMessage>>,
+    Sender<DomainMessage<MempoolProto::MempoolServiceResponse>>,
+    Sender<DomainMessage<BaseNodeProto::BaseNodeServiceResponse>>,
+    LivenessHandle,
+    LivenessMockState,
+    LivenessEventSender,
+)
+{
+    setup_transaction_service_no_comms_and_config(runtime, factories, backend, TransactionServiceConfig {
+        mempool_broadcast_timeout: Duration::from_secs(5),
+        base_node_mined_timeout: mined_request_timeout.unwrap_or(Duration::from_secs(5)),
+        num_confirmations_required: 0,
+        ..Default::default()
+    })
+}
+
+/// This utility function creates a Transaction service with the provided config without using the Service Framework
+/// Stack and exposes all the streams for testing purposes.
+pub fn setup_transaction_service_no_comms_and_config<T: TransactionBackend + Clone + 'static>(
+    runtime: &mut Runtime,
+    factories: CryptoFactories,
+    backend: T,
+    config: TransactionServiceConfig,
+) -> (
+    TransactionServiceHandle,
+    OutputManagerHandle,
+    OutboundServiceMockState,
+    Sender<DomainMessage<proto::TransactionSenderMessage>>,
+    Sender<DomainMessage<proto::RecipientSignedMessage>>,
+    Sender<DomainMessage<proto::TransactionFinalizedMessage>>,
+    Sender<DomainMessage<proto::TransactionCancelledMessage>>,
+    Sender<DomainMessage<MempoolProto::MempoolServiceResponse>>,
+    Sender<DomainMessage<BaseNodeProto::BaseNodeServiceResponse>>,
+    LivenessHandle,
+    LivenessMockState,
+    LivenessEventSender,
+)
+{
+    let node_identity = Arc::new(
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap(),
+    );
+    setup_transaction_service_no_comms_with_node_identity(runtime, factories, backend, config, node_identity)
+}
+
+/// This utility function creates a Transaction service with the provided config and node identity without using the
+/// Service Framework Stack and exposes all the streams for testing purposes.
+pub fn setup_transaction_service_no_comms_with_node_identity<T: TransactionBackend + Clone + 'static>(
+    runtime: &mut Runtime,
+    factories: CryptoFactories,
+    backend: T,
+    config: TransactionServiceConfig,
+    node_identity: Arc<NodeIdentity>,
+) -> (
+    TransactionServiceHandle,
+    OutputManagerHandle,
+    OutboundServiceMockState,
+    Sender<DomainMessage<proto::TransactionSenderMessage>>,
+    Sender<DomainMessage<proto::RecipientSignedMessage>>,
+    Sender<DomainMessage<proto::TransactionFinalizedMessage>>,
+    Sender<DomainMessage<proto::TransactionCancelledMessage>>,
     Sender<DomainMessage<MempoolProto::MempoolServiceResponse>>,
     Sender<DomainMessage<BaseNodeProto::BaseNodeServiceResponse>>,
     LivenessHandle,
@@ -232,6 +311,7 @@ pub fn setup_transaction_service_no_comms<T: TransactionBackend + Clone + 'stati
             OutputManagerDatabase::new(OutputManagerMemoryDatabase::new()),
             oms_event_publisher,
             factories.clone(),
+            node_identity.clone(),
         ))
         .unwrap();
 
@@ -247,6 +327,7 @@ pub fn setup_transaction_service_no_comms<T: TransactionBackend + Clone + 'stati
     let (tx_sender, tx_receiver) = mpsc::channel(20);
     let (tx_ack_sender, tx_ack_receiver) = mpsc::channel(20);
     let (tx_finalized_sender, tx_finalized_receiver) = mpsc::channel(20);
+    let (tx_cancelled_sender, tx_cancelled_receiver) = mpsc::channel(20);
     let (mempool_response_sender, mempool_response_receiver) = mpsc::channel(20);
     let (base_node_response_sender, base_node_response_receiver) = mpsc::channel(20);
 
@@ -254,25 +335,20 @@ pub fn setup_transaction_service_no_comms<T: TransactionBackend + Clone + 'stati
     runtime.spawn(mock_outbound_service.run());
 
     let ts_service = TransactionService::new(
-        TransactionServiceConfig {
-            mempool_broadcast_timeout: Duration::from_secs(5),
-            base_node_mined_timeout: mined_request_timeout.unwrap_or(Duration::from_secs(5)),
-            ..Default::default()
-        },
+        config,
         TransactionDatabase::new(backend),
         ts_request_receiver,
         tx_receiver,
         tx_ack_receiver,
         tx_finalized_receiver,
+        tx_cancelled_receiver,
         mempool_response_receiver,
         base_node_response_receiver,
         output_manager_service_handle.clone(),
         outbound_message_requester.clone(),
         liveness_handle.clone(),
         event_publisher,
-        Arc::new(
-            NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap(),
-        ),
+        node_identity,
         factories.clone(),
     );
     runtime.spawn(async move { output_manager_service.start().await.unwrap() });
@@ -284,6 +360,7 @@ pub fn setup_transaction_service_no_comms<T: TransactionBackend + Clone + 'stati
         tx_sender,
         tx_ack_sender,
         tx_finalized_sender,
+        tx_cancelled_sender,
         mempool_response_sender,
         base_node_response_sender,
         liveness_handle,
@@ -768,6 +845,7 @@ fn test_accepting_unknown_tx_id_and_malformed_reply<T: TransactionBackend + Clon
         _,
         _,
         _,
+        _,
     ) = setup_transaction_service_no_comms(&mut runtime, factories.clone(), alice_backend, None);
 
     let mut alice_event_stream = alice_ts.get_event_stream_fused();
@@ -878,13 +956,26 @@ fn finalize_tx_with_incorrect_pubkey<T: TransactionBackend + Clone + 'static>(al
         _,
         _,
         _,
+        _,
     ) = setup_transaction_service_no_comms(&mut runtime, factories.clone(), alice_backend, None);
     let alice_event_stream = alice_ts.get_event_stream_fused();
 
     let bob_node_identity =
         NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap();
-    let (_bob_ts, mut bob_output_manager, _bob_outbound_service, _bob_tx_sender, _bob_tx_ack_sender, _, _, _, _, _, _) =
-        setup_transaction_service_no_comms(&mut runtime, factories.clone(), bob_backend, None);
+    let (
+        _bob_ts,
+        mut bob_output_manager,
+        _bob_outbound_service,
+        _bob_tx_sender,
+        _bob_tx_ack_sender,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+    ) = setup_transaction_service_no_comms(&mut runtime, factories.clone(), bob_backend, None);
 
     let (_utxo, uo) = make_input(&mut OsRng, MicroTari(250000), &factories.commitment);
 
@@ -986,13 +1077,26 @@ fn finalize_tx_with_missing_output<T: TransactionBackend + Clone + 'static>(alic
         _,
         _,
         _,
+        _,
     ) = setup_transaction_service_no_comms(&mut runtime, factories.clone(), alice_backend, None);
     let alice_event_stream = alice_ts.get_event_stream_fused();
 
     let bob_node_identity =
         NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap();
-    let (_bob_ts, mut bob_output_manager, _bob_outbound_service, _bob_tx_sender, _bob_tx_ack_sender, _, _, _, _, _, _) =
-        setup_transaction_service_no_comms(&mut runtime, factories.clone(), bob_backend, None);
+    let (
+        _bob_ts,
+        mut bob_output_manager,
+        _bob_outbound_service,
+        _bob_tx_sender,
+        _bob_tx_ack_sender,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+    ) = setup_transaction_service_no_comms(&mut runtime, factories.clone(), bob_backend, None);
 
     let (_utxo, uo) = make_input(&mut OsRng, MicroTari(250000), &factories.commitment);
 
@@ -1077,6 +1181,183 @@ fn finalize_tx_with_missing_output_sqlite_db() {
     });
 }
 
+fn offline_transaction_file_exchange<T: TransactionBackend + Clone + 'static>(
+    alice_backend: T,
+    bob_backend: T,
+    carol_backend: T,
+)
+{
+    let mut runtime = create_runtime();
+    let factories = CryptoFactories::default();
+    let temp_dir = TempDir::new(random_string(8).as_str()).unwrap();
+    let file_path = |name: &str| temp_dir.path().join(name);
+
+    let config = TransactionServiceConfig {
+        mempool_broadcast_timeout: Duration::from_secs(5),
+        base_node_mined_timeout: Duration::from_secs(5),
+        num_confirmations_required: 0,
+        ..Default::default()
+    };
+    let alice_node_identity = Arc::new(
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap(),
+    );
+    let bob_node_identity = Arc::new(
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap(),
+    );
+
+    // Alice holds the spending keys offline, Bob is the recipient and Carol is Alice's online wallet
+    let (mut alice_ts, mut alice_output_manager, alice_outbound_service, _, _, _, _, _, _, _, _, _) =
+        setup_transaction_service_no_comms_with_node_identity(
+            &mut runtime,
+            factories.clone(),
+            alice_backend,
+            config.clone(),
+            alice_node_identity.clone(),
+        );
+    let (mut bob_ts, _, bob_outbound_service, _, _, _, _, _, _, _, _, _) =
+        setup_transaction_service_no_comms_with_node_identity(
+            &mut runtime,
+            factories.clone(),
+            bob_backend,
+            config,
+            bob_node_identity.clone(),
+        );
+    let (mut carol_ts, _, _, _, _, _, _, _, _, _, _, _) =
+        setup_transaction_service_no_comms(&mut runtime, factories.clone(), carol_backend, None);
+
+    let (_utxo, uo) = make_input(&mut OsRng, MicroTari(250000), &factories.commitment);
+    runtime.block_on(alice_output_manager.add_output(uo)).unwrap();
+
+    let amount = MicroTari::from(5000);
+    UnsignedTransactionRequest::new(
+        bob_node_identity.public_key().clone(),
+        amount,
+        MicroTari::from(20),
+        "Cold storage".to_string(),
+    )
+    .to_file(file_path("request.json"))
+    .unwrap();
+
+    let request = UnsignedTransactionRequest::from_file(file_path("request.json")).unwrap();
+    runtime
+        .block_on(alice_ts.sign_offline_transaction(request))
+        .unwrap()
+        .to_file(file_path("sender_message.json"))
+        .unwrap();
+    let pending_outbound = runtime.block_on(alice_ts.get_pending_outbound_transactions()).unwrap();
+    assert_eq!(pending_outbound.len(), 1);
+    let tx_id = *pending_outbound.keys().next().unwrap();
+
+    let sender_message = OfflineSenderMessage::from_file(file_path("sender_message.json")).unwrap();
+    runtime
+        .block_on(bob_ts.reply_to_offline_transaction(sender_message.clone()))
+        .unwrap()
+        .to_file(file_path("reply.json"))
+        .unwrap();
+    let pending_inbound = runtime.block_on(bob_ts.get_pending_inbound_transactions()).unwrap();
+    assert!(pending_inbound.get(&tx_id).is_some());
+
+    match runtime.block_on(bob_ts.reply_to_offline_transaction(sender_message)) {
+        Err(TransactionServiceError::RepeatedMessageError) => assert!(true),
+        _ => assert!(false),
+    }
+
+    let reply = OfflineRecipientReply::from_file(file_path("reply.json")).unwrap();
+    let mut wrong_reply = reply.clone();
+    wrong_reply.recipient_pubkey = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+    match runtime.block_on(alice_ts.finalize_offline_transaction(wrong_reply)) {
+        Err(TransactionServiceError::InvalidSourcePublicKey) => assert!(true),
+        _ => assert!(false),
+    }
+
+    runtime
+        .block_on(alice_ts.finalize_offline_transaction(reply))
+        .unwrap()
+        .to_file(file_path("transaction.json"))
+        .unwrap();
+    let alice_completed = runtime.block_on(alice_ts.get_completed_transactions()).unwrap();
+    assert_eq!(
+        alice_completed.get(&tx_id).unwrap().status,
+        TransactionStatus::Completed
+    );
+
+    let finalized_transaction = OfflineFinalizedTransaction::from_file(file_path("transaction.json")).unwrap();
+    assert_eq!(finalized_transaction.tx_id, tx_id);
+    assert_eq!(finalized_transaction.amount, amount);
+
+    // A transaction that does not finalize a pending inbound transaction is checked before it is imported
+    let mut wrong_fee = finalized_transaction.clone();
+    wrong_fee.fee += MicroTari::from(1);
+    match runtime.block_on(carol_ts.import_offline_transaction(wrong_fee)) {
+        Err(TransactionServiceError::InvalidOfflineTransaction(_)) => assert!(true),
+        _ => assert!(false),
+    }
+    let mut inconsistent = finalized_transaction.clone();
+    inconsistent.transaction.offset = PrivateKey::random(&mut OsRng);
+    match runtime.block_on(carol_ts.import_offline_transaction(inconsistent)) {
+        Err(TransactionServiceError::TransactionError(_)) => assert!(true),
+        _ => assert!(false),
+    }
+    assert!(runtime
+        .block_on(carol_ts.get_completed_transactions())
+        .unwrap()
+        .get(&tx_id)
+        .is_none());
+
+    let imported_tx_id = runtime
+        .block_on(carol_ts.import_offline_transaction(finalized_transaction.clone()))
+        .unwrap();
+    assert_eq!(imported_tx_id, tx_id);
+    let carol_completed = runtime.block_on(carol_ts.get_completed_transactions()).unwrap();
+    let carol_tx = carol_completed.get(&tx_id).unwrap();
+    assert_eq!(&carol_tx.source_public_key, alice_node_identity.public_key());
+    assert_eq!(&carol_tx.destination_public_key, bob_node_identity.public_key());
+    assert_eq!(carol_tx.amount, amount);
+
+    runtime
+        .block_on(bob_ts.import_offline_transaction(finalized_transaction))
+        .unwrap();
+    let bob_completed = runtime.block_on(bob_ts.get_completed_transactions()).unwrap();
+    assert_eq!(bob_completed.get(&tx_id).unwrap().status, TransactionStatus::Completed);
+    assert!(runtime
+        .block_on(bob_ts.get_pending_inbound_transactions())
+        .unwrap()
+        .get(&tx_id)
+        .is_none());
+
+    // Nothing was exchanged over comms
+    assert_eq!(alice_outbound_service.call_count(), 0);
+    assert_eq!(bob_outbound_service.call_count(), 0);
+}
+
+#[test]
+fn offline_transaction_file_exchange_memory_db() {
+    offline_transaction_file_exchange(
+        TransactionMemoryDatabase::new(),
+        TransactionMemoryDatabase::new(),
+        TransactionMemoryDatabase::new(),
+    );
+}
+
+#[test]
+fn offline_transaction_file_exchange_sqlite_db() {
+    with_temp_dir(|dir_path| {
+        let path_string = dir_path.to_str().unwrap().to_string();
+        let connections = (0..3)
+            .map(|_| {
+                let db_path = format!("{}/{}.sqlite3", path_string, random_string(8).as_str());
+                run_migration_and_create_sqlite_connection(&db_path).unwrap()
+            })
+            .collect::<Vec<_>>();
+        let mut connections = connections.into_iter();
+        offline_transaction_file_exchange(
+            TransactionServiceSqliteDatabase::new(connections.next().unwrap()),
+            TransactionServiceSqliteDatabase::new(connections.next().unwrap()),
+            TransactionServiceSqliteDatabase::new(connections.next().unwrap()),
+        );
+    });
+}
+
 #[test]
 fn discovery_async_return_test() {
     let db_tempdir = TempDir::new(random_string(8).as_str()).unwrap();
@@ -1295,6 +1576,7 @@ fn test_coinbase<T: TransactionBackend + Clone + 'static>(backend: T) {
         _,
         _,
         _,
+        _,
     ) = setup_transaction_service_no_comms(&mut runtime, factories.clone(), backend, None);
 
     let balance = runtime.block_on(alice_output_manager.get_balance()).unwrap();
@@ -1420,6 +1702,7 @@ fn transaction_mempool_broadcast() {
         mut _alice_tx_sender,
         mut alice_tx_ack_sender,
         _,
+        _,
         mut alice_mempool_response_sender,
         mut alice_base_node_response_sender,
         _,
@@ -1432,7 +1715,7 @@ fn transaction_mempool_broadcast() {
         .block_on(alice_ts.set_base_node_public_key(base_node_identity.public_key().clone()))
         .unwrap();
 
-    let (_bob_ts, _bob_output_manager, bob_outbound_service, mut bob_tx_sender, _, _, _, _, _, _, _) =
+    let (_bob_ts, _bob_output_manager, bob_outbound_service, mut bob_tx_sender, _, _, _, _, _, _, _, _) =
         setup_transaction_service_no_comms(&mut runtime, factories.clone(), TransactionMemoryDatabase::new(), None);
 
     let (_utxo, uo) = make_input(&mut OsRng, MicroTari(250000), &factories.commitment);
@@ -1787,6 +2070,18 @@ fn try_decode_sender_message(bytes: Vec<u8>) -> Option<TransactionSenderMessage>
     }
 }
 
+fn try_decode_transaction_cancelled_message(bytes: Vec<u8>) -> Option<proto::TransactionCancelledMessage> {
+    let envelope_body = EnvelopeBody::decode(&mut bytes.as_slice()).unwrap();
+    match envelope_body.decode_part::<MessageHeader>(0) {
+        Ok(Some(header)) if header.message_type == TariMessageType::TransactionCancelled as i32 => (),
+        _ => return None,
+    }
+    envelope_body
+        .decode_part::<proto::TransactionCancelledMessage>(1)
+        .ok()
+        .and_then(|m| m)
+}
+
 fn try_decode_base_node_request(bytes: Vec<u8>) -> Option<BaseNodeProto::BaseNodeServiceRequest> {
     let envelope_body = EnvelopeBody::decode(&mut bytes.as_slice()).unwrap();
     match envelope_body.decode_part::<BaseNodeProto::BaseNodeServiceRequest>(1) {
@@ -1823,6 +2118,9 @@ fn broadcast_all_completed_transactions_on_startup() {
         status: TransactionStatus::Completed,
         message: "Yo!".to_string(),
         timestamp: Utc::now().naive_utc(),
+        mined_height: None,
+        mined_block_hash: None,
+        confirmations: None,
     };
 
     let completed_tx2 = CompletedTransaction {
@@ -1855,7 +2153,7 @@ fn broadcast_all_completed_transactions_on_startup() {
     )))
     .unwrap();
 
-    let (mut alice_ts, _, _, _, _, _, _, _, _, _, _) =
+    let (mut alice_ts, _, _, _, _, _, _, _, _, _, _, _) =
         setup_transaction_service_no_comms(&mut runtime, factories.clone(), db, None);
 
     runtime
@@ -1914,6 +2212,7 @@ fn transaction_base_node_monitoring() {
         mut _alice_tx_sender,
         mut alice_tx_ack_sender,
         _,
+        _,
         mut alice_mempool_response_sender,
         mut alice_base_node_response_sender,
         _,
@@ -1923,7 +2222,7 @@ fn transaction_base_node_monitoring() {
 
     let mut alice_event_stream = alice_ts.get_event_stream_fused();
 
-    let (_, _, bob_outbound_service, mut bob_tx_sender, _, _, _, _, _, _, _) =
+    let (_, _, bob_outbound_service, mut bob_tx_sender, _, _, _, _, _, _, _, _) =
         setup_transaction_service_no_comms(&mut runtime, factories.clone(), TransactionMemoryDatabase::new(), None);
 
     let mut alice_total_available = 250000 * uT;
@@ -2333,6 +2632,9 @@ fn query_all_completed_transactions_on_startup() {
         status: TransactionStatus::Broadcast,
         message: "Yo!".to_string(),
         timestamp: Utc::now().naive_utc(),
+        mined_height: None,
+        mined_block_hash: None,
+        confirmations: None,
     };
 
     let completed_tx2 = CompletedTransaction {
@@ -2365,7 +2667,7 @@ fn query_all_completed_transactions_on_startup() {
     )))
     .unwrap();
 
-    let (mut alice_ts, _, _, _, _, _, _, _, _, _, _) =
+    let (mut alice_ts, _, _, _, _, _, _, _, _, _, _, _) =
         setup_transaction_service_no_comms(&mut runtime, factories.clone(), db, None);
     let mut alice_event_stream = alice_ts.get_event_stream_fused();
 
@@ -2402,85 +2704,457 @@ fn query_all_completed_transactions_on_startup() {
 }
 
 #[test]
-#[ignore]
-fn test_failed_tx_send_timeout() {
-    let temp_dir = TempDir::new(random_string(8).as_str()).unwrap();
-    let mut runtime = create_runtime();
-
+fn transaction_confirmations_and_reorg() {
+    let mut runtime = Runtime::new().unwrap();
     let factories = CryptoFactories::default();
-    // Alice's parameters
-    let alice_node_identity = Arc::new(
-        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap(),
-    );
 
-    // Bob's parameters
-    let bob_node_identity = Arc::new(
-        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap(),
-    );
+    let db = TransactionMemoryDatabase::new();
 
-    let base_node_identity = Arc::new(
-        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap(),
-    );
+    let kernel = KernelBuilder::new()
+        .with_excess(&factories.commitment.zero())
+        .with_signature(&Signature::default())
+        .build()
+        .unwrap();
 
-    log::info!(
-        "manage_single_transaction: Alice: '{}', Bob: '{}', Base: '{}'",
-        alice_node_identity.node_id().short_str(),
-        bob_node_identity.node_id().short_str(),
-        base_node_identity.node_id().short_str()
-    );
+    let tx = Transaction::new(vec![], vec![], vec![kernel], PrivateKey::random(&mut OsRng));
+
+    let completed_tx = CompletedTransaction {
+        tx_id: 1,
+        source_public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        destination_public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        amount: 5000 * uT,
+        fee: MicroTari::from(100),
+        transaction: tx.clone(),
+        status: TransactionStatus::Mined,
+        message: "Yo!".to_string(),
+        timestamp: Utc::now().naive_utc(),
+        mined_height: None,
+        mined_block_hash: None,
+        confirmations: None,
+    };
+
+    db.write(WriteOperation::Insert(DbKeyValuePair::CompletedTransaction(
+        completed_tx.tx_id,
+        Box::new(completed_tx.clone()),
+    )))
+    .unwrap();
+
+    let (mut alice_ts, _, alice_outbound_service, _, _, _, _, _, mut alice_base_node_response_sender, _, _, _) =
+        setup_transaction_service_no_comms_and_config(&mut runtime, factories.clone(), db, TransactionServiceConfig {
+            base_node_mined_timeout: Duration::from_secs(2),
+            num_confirmations_required: 2,
+            ..Default::default()
+        });
+    let mut alice_event_stream = alice_ts.get_event_stream_fused();
+
+    let base_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap();
 
-    let (mut alice_ts, mut alice_oms, _alice_comms) = setup_transaction_service(
-        &mut runtime,
-        alice_node_identity.clone(),
-        vec![bob_node_identity.clone()],
-        factories.clone(),
-        TransactionMemoryDatabase::new(),
-        temp_dir.path().to_str().unwrap().to_string().clone(),
-        Duration::from_secs(0),
-    );
     runtime
         .block_on(alice_ts.set_base_node_public_key(base_node_identity.public_key().clone()))
         .unwrap();
 
-    let (mut bob_ts, _bob_oms, bob_comms) = setup_transaction_service(
-        &mut runtime,
-        bob_node_identity.clone(),
-        vec![alice_node_identity.clone()],
-        factories.clone(),
-        TransactionMemoryDatabase::new(),
-        temp_dir.path().to_str().unwrap().to_string(),
-        Duration::from_secs(0),
-    );
-    runtime
-        .block_on(bob_ts.set_base_node_public_key(base_node_identity.public_key().clone()))
+    // The Mined transaction should be located in the chain, starting with a chain metadata request
+    alice_outbound_service
+        .wait_call_count(1, Duration::from_secs(60))
         .unwrap();
+    let (_, body) = alice_outbound_service.pop_call().unwrap();
+    let request = try_decode_base_node_request(body.to_vec()).unwrap();
+    match request.request {
+        Some(BaseNodeRequestProto::GetChainMetadata(_)) => {},
+        _ => panic!("Expected a GetChainMetadata request"),
+    }
+    let request_key = request.request_key;
 
-    let _ = runtime.block_on(
-        bob_comms
-            .connection_manager()
-            .dial_peer(alice_node_identity.node_id().clone()),
-    );
-
-    runtime.block_on(bob_comms.shutdown());
-
-    let balance = 2500 * uT;
-    let value_sent = MicroTari::from(1000);
-    let (_utxo, uo1) = make_input(&mut OsRng, balance, &factories.commitment);
-
-    runtime.block_on(alice_oms.add_output(uo1)).unwrap();
-    let message = "TAKE MAH MONEYS!".to_string();
     runtime
-        .block_on(alice_ts.send_transaction(
-            bob_node_identity.public_key().clone(),
-            value_sent,
-            MicroTari::from(20),
-            message.clone(),
-        ))
+        .block_on(alice_base_node_response_sender.send(create_dummy_message(
+            BaseNodeProto::BaseNodeServiceResponse {
+                request_key,
+                response: Some(BaseNodeResponseProto::ChainMetadata(BaseNodeProto::ChainMetadata {
+                    height_of_longest_chain: Some(10),
+                    best_block: Some(vec![10u8; 32]),
+                    pruning_horizon: 0,
+                    accumulated_difficulty: None,
+                })),
+            },
+            base_node_identity.public_key(),
+        )))
         .unwrap();
 
-    let mut alice_event_stream = alice_ts.get_event_stream_fused();
+    alice_outbound_service
+        .wait_call_count(1, Duration::from_secs(60))
+        .unwrap();
+    let (_, body) = alice_outbound_service.pop_call().unwrap();
+    match try_decode_base_node_request(body.to_vec()).unwrap().request {
+        Some(BaseNodeRequestProto::FetchBlocks(heights)) => assert_eq!(heights.heights, (1..=10).collect::<Vec<u64>>()),
+        _ => panic!("Expected a FetchBlocks request"),
+    }
 
-    runtime.block_on(async {
+    runtime
+        .block_on(alice_base_node_response_sender.send(create_dummy_message(
+            BaseNodeProto::BaseNodeServiceResponse {
+                request_key,
+                response: Some(BaseNodeResponseProto::HistoricalBlocks(
+                    BaseNodeProto::HistoricalBlocks {
+                        blocks: vec![CoreProto::HistoricalBlock {
+                            confirmations: 1,
+                            spent_commitments: vec![],
+                            block: Some(CoreProto::Block {
+                                header: Some(CoreProto::BlockHeader {
+                                    height: 10,
+                                    prev_hash: vec![9u8; 32],
+                                    ..Default::default()
+                                }),
+                                body: Some(tx.body.clone().into()),
+                            }),
+                        }],
+                    },
+                )),
+            },
+            base_node_identity.public_key(),
+        )))
+        .unwrap();
+
+    // With only 1 of the 2 required confirmations the protocol will check the chain again in the next round
+    alice_outbound_service
+        .wait_call_count(1, Duration::from_secs(60))
+        .unwrap();
+    let (_, body) = alice_outbound_service.pop_call().unwrap();
+    match try_decode_base_node_request(body.to_vec()).unwrap().request {
+        Some(BaseNodeRequestProto::GetChainMetadata(_)) => {},
+        _ => panic!("Expected a GetChainMetadata request"),
+    }
+
+    let completed_txs = runtime.block_on(alice_ts.get_completed_transactions()).unwrap();
+    let tx1 = completed_txs.get(&1).unwrap();
+    assert_eq!(tx1.status, TransactionStatus::Mined);
+    assert_eq!(tx1.mined_height, Some(10));
+    assert_eq!(tx1.mined_block_hash, Some(vec![10u8; 32]));
+    assert_eq!(tx1.confirmations, Some(1));
+
+    runtime
+        .block_on(alice_base_node_response_sender.send(create_dummy_message(
+            BaseNodeProto::BaseNodeServiceResponse {
+                request_key,
+                response: Some(BaseNodeResponseProto::ChainMetadata(BaseNodeProto::ChainMetadata {
+                    height_of_longest_chain: Some(11),
+                    best_block: Some(vec![11u8; 32]),
+                    pruning_horizon: 0,
+                    accumulated_difficulty: None,
+                })),
+            },
+            base_node_identity.public_key(),
+        )))
+        .unwrap();
+
+    alice_outbound_service
+        .wait_call_count(1, Duration::from_secs(60))
+        .unwrap();
+    let (_, body) = alice_outbound_service.pop_call().unwrap();
+    match try_decode_base_node_request(body.to_vec()).unwrap().request {
+        Some(BaseNodeRequestProto::FetchBlocks(heights)) => assert_eq!(heights.heights, vec![10, 11]),
+        _ => panic!("Expected a FetchBlocks request"),
+    }
+
+    // Block 10 has been replaced by a block that does not contain the transaction
+    runtime
+        .block_on(alice_base_node_response_sender.send(create_dummy_message(
+            BaseNodeProto::BaseNodeServiceResponse {
+                request_key,
+                response: Some(BaseNodeResponseProto::HistoricalBlocks(
+                    BaseNodeProto::HistoricalBlocks {
+                        blocks: vec![
+                        CoreProto::HistoricalBlock {
+                            confirmations: 2,
+                            spent_commitments: vec![],
+                            block: Some(CoreProto::Block {
+                                header: Some(CoreProto::BlockHeader {
+                                    height: 10,
+                                    prev_hash: vec![9u8; 32],
+                                    ..Default::default()
+                                }),
+                                body: Some(Transaction::new(vec![], vec![], vec![], PrivateKey::default()).body.into()),
+                            }),
+                        },
+                        CoreProto::HistoricalBlock {
+                            confirmations: 1,
+                            spent_commitments: vec![],
+                            block: Some(CoreProto::Block {
+                                header: Some(CoreProto::BlockHeader {
+                                    height: 11,
+                                    prev_hash: vec![12u8; 32],
+                                    ..Default::default()
+                                }),
+                                body: Some(Transaction::new(vec![], vec![], vec![], PrivateKey::default()).body.into()),
+                            }),
+                        },
+                    ],
+                    },
+                )),
+            },
+            base_node_identity.public_key(),
+        )))
+        .unwrap();
+
+    runtime.block_on(async {
+        let mut delay = delay_for(Duration::from_secs(60)).fuse();
+        let mut reorged = false;
+        loop {
+            futures::select! {
+                event = alice_event_stream.select_next_some() => {
+                    if let TransactionEvent::TransactionReorged(tx_id) = &*event.unwrap() {
+                        assert_eq!(*tx_id, 1);
+                        reorged = true;
+                        break;
+                    }
+                },
+                () = delay => {
+                    break;
+                },
+            }
+        }
+        assert!(reorged);
+    });
+
+    // The reorged transaction is resubmitted to the mempool
+    let resubmitted =
+        alice_outbound_service
+            .take_calls()
+            .iter()
+            .any(|(_, body)| match try_decode_mempool_request(body.to_vec()) {
+                Some(MempoolServiceRequest {
+                    request: MempoolRequest::SubmitTransaction(t),
+                    ..
+                }) => t == tx,
+                _ => false,
+            });
+    assert!(resubmitted);
+
+    let completed_txs = runtime.block_on(alice_ts.get_completed_transactions()).unwrap();
+    let tx1 = completed_txs.get(&1).unwrap();
+    assert_eq!(tx1.status, TransactionStatus::Broadcast);
+    assert_eq!(tx1.mined_height, None);
+    assert_eq!(tx1.mined_block_hash, None);
+    assert_eq!(tx1.confirmations, None);
+}
+
+#[test]
+fn third_party_transaction_confirmed() {
+    let mut runtime = Runtime::new().unwrap();
+    let factories = CryptoFactories::default();
+
+    let db = TransactionMemoryDatabase::new();
+
+    let kernel = KernelBuilder::new()
+        .with_excess(&factories.commitment.zero())
+        .with_signature(&Signature::default())
+        .build()
+        .unwrap();
+
+    let tx = Transaction::new(vec![], vec![], vec![kernel], PrivateKey::random(&mut OsRng));
+
+    // An offline transaction between two other wallets that was imported only to be broadcast, so the Output Manager
+    // holds no pending outputs for it
+    let completed_tx = CompletedTransaction {
+        tx_id: 1,
+        source_public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        destination_public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        amount: 5000 * uT,
+        fee: MicroTari::from(100),
+        transaction: tx.clone(),
+        status: TransactionStatus::Mined,
+        message: "Yo!".to_string(),
+        timestamp: Utc::now().naive_utc(),
+        mined_height: None,
+        mined_block_hash: None,
+        confirmations: None,
+    };
+
+    db.write(WriteOperation::Insert(DbKeyValuePair::CompletedTransaction(
+        completed_tx.tx_id,
+        Box::new(completed_tx),
+    )))
+    .unwrap();
+
+    let (mut alice_ts, _, alice_outbound_service, _, _, _, _, _, mut alice_base_node_response_sender, _, _, _) =
+        setup_transaction_service_no_comms_and_config(&mut runtime, factories.clone(), db, TransactionServiceConfig {
+            base_node_mined_timeout: Duration::from_secs(2),
+            num_confirmations_required: 1,
+            ..Default::default()
+        });
+    let mut alice_event_stream = alice_ts.get_event_stream_fused();
+
+    let base_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap();
+
+    runtime
+        .block_on(alice_ts.set_base_node_public_key(base_node_identity.public_key().clone()))
+        .unwrap();
+
+    alice_outbound_service
+        .wait_call_count(1, Duration::from_secs(60))
+        .unwrap();
+    let (_, body) = alice_outbound_service.pop_call().unwrap();
+    let request = try_decode_base_node_request(body.to_vec()).unwrap();
+    match request.request {
+        Some(BaseNodeRequestProto::GetChainMetadata(_)) => {},
+        _ => panic!("Expected a GetChainMetadata request"),
+    }
+    let request_key = request.request_key;
+
+    runtime
+        .block_on(alice_base_node_response_sender.send(create_dummy_message(
+            BaseNodeProto::BaseNodeServiceResponse {
+                request_key,
+                response: Some(BaseNodeResponseProto::ChainMetadata(BaseNodeProto::ChainMetadata {
+                    height_of_longest_chain: Some(10),
+                    best_block: Some(vec![10u8; 32]),
+                    pruning_horizon: 0,
+                    accumulated_difficulty: None,
+                })),
+            },
+            base_node_identity.public_key(),
+        )))
+        .unwrap();
+
+    alice_outbound_service
+        .wait_call_count(1, Duration::from_secs(60))
+        .unwrap();
+    let (_, body) = alice_outbound_service.pop_call().unwrap();
+    match try_decode_base_node_request(body.to_vec()).unwrap().request {
+        Some(BaseNodeRequestProto::FetchBlocks(_)) => {},
+        _ => panic!("Expected a FetchBlocks request"),
+    }
+
+    runtime
+        .block_on(alice_base_node_response_sender.send(create_dummy_message(
+            BaseNodeProto::BaseNodeServiceResponse {
+                request_key,
+                response: Some(BaseNodeResponseProto::HistoricalBlocks(
+                    BaseNodeProto::HistoricalBlocks {
+                        blocks: vec![CoreProto::HistoricalBlock {
+                            confirmations: 1,
+                            spent_commitments: vec![],
+                            block: Some(CoreProto::Block {
+                                header: Some(CoreProto::BlockHeader {
+                                    height: 10,
+                                    prev_hash: vec![9u8; 32],
+                                    ..Default::default()
+                                }),
+                                body: Some(tx.body.clone().into()),
+                            }),
+                        }],
+                    },
+                )),
+            },
+            base_node_identity.public_key(),
+        )))
+        .unwrap();
+
+    runtime.block_on(async {
+        let mut delay = delay_for(Duration::from_secs(60)).fuse();
+        let mut confirmed = false;
+        loop {
+            futures::select! {
+                event = alice_event_stream.select_next_some() => {
+                    if let TransactionEvent::TransactionConfirmed(tx_id) = &*event.unwrap() {
+                        assert_eq!(*tx_id, 1);
+                        confirmed = true;
+                        break;
+                    }
+                },
+                () = delay => {
+                    break;
+                },
+            }
+        }
+        assert!(confirmed);
+    });
+
+    let completed_txs = runtime.block_on(alice_ts.get_completed_transactions()).unwrap();
+    assert_eq!(completed_txs.get(&1).unwrap().confirmations, Some(1));
+}
+
+#[test]
+#[ignore]
+fn test_failed_tx_send_timeout() {
+    let temp_dir = TempDir::new(random_string(8).as_str()).unwrap();
+    let mut runtime = create_runtime();
+
+    let factories = CryptoFactories::default();
+    // Alice's parameters
+    let alice_node_identity = Arc::new(
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap(),
+    );
+
+    // Bob's parameters
+    let bob_node_identity = Arc::new(
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap(),
+    );
+
+    let base_node_identity = Arc::new(
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap(),
+    );
+
+    log::info!(
+        "manage_single_transaction: Alice: '{}', Bob: '{}', Base: '{}'",
+        alice_node_identity.node_id().short_str(),
+        bob_node_identity.node_id().short_str(),
+        base_node_identity.node_id().short_str()
+    );
+
+    let (mut alice_ts, mut alice_oms, _alice_comms) = setup_transaction_service(
+        &mut runtime,
+        alice_node_identity.clone(),
+        vec![bob_node_identity.clone()],
+        factories.clone(),
+        TransactionMemoryDatabase::new(),
+        temp_dir.path().to_str().unwrap().to_string().clone(),
+        Duration::from_secs(0),
+    );
+    runtime
+        .block_on(alice_ts.set_base_node_public_key(base_node_identity.public_key().clone()))
+        .unwrap();
+
+    let (mut bob_ts, _bob_oms, bob_comms) = setup_transaction_service(
+        &mut runtime,
+        bob_node_identity.clone(),
+        vec![alice_node_identity.clone()],
+        factories.clone(),
+        TransactionMemoryDatabase::new(),
+        temp_dir.path().to_str().unwrap().to_string(),
+        Duration::from_secs(0),
+    );
+    runtime
+        .block_on(bob_ts.set_base_node_public_key(base_node_identity.public_key().clone()))
+        .unwrap();
+
+    let _ = runtime.block_on(
+        bob_comms
+            .connection_manager()
+            .dial_peer(alice_node_identity.node_id().clone()),
+    );
+
+    runtime.block_on(bob_comms.shutdown());
+
+    let balance = 2500 * uT;
+    let value_sent = MicroTari::from(1000);
+    let (_utxo, uo1) = make_input(&mut OsRng, balance, &factories.commitment);
+
+    runtime.block_on(alice_oms.add_output(uo1)).unwrap();
+    let message = "TAKE MAH MONEYS!".to_string();
+    runtime
+        .block_on(alice_ts.send_transaction(
+            bob_node_identity.public_key().clone(),
+            value_sent,
+            MicroTari::from(20),
+            message.clone(),
+        ))
+        .unwrap();
+
+    let mut alice_event_stream = alice_ts.get_event_stream_fused();
+
+    runtime.block_on(async {
         let mut delay = delay_for(Duration::from_secs(180)).fuse();
         let mut returned = false;
         let mut result = true;
@@ -2527,6 +3201,7 @@ fn transaction_cancellation_when_not_in_mempool() {
         mut _alice_tx_sender,
         mut alice_tx_ack_sender,
         _,
+        _,
         mut alice_mempool_response_sender,
         mut alice_base_node_response_sender,
         _,
@@ -2539,7 +3214,7 @@ fn transaction_cancellation_when_not_in_mempool() {
         Some(Duration::from_secs(5)),
     );
     let mut alice_event_stream = alice_ts.get_event_stream_fused();
-    let (mut bob_ts, _, bob_outbound_service, mut bob_tx_sender, _, _, _, _, _, _, _) =
+    let (mut bob_ts, _, bob_outbound_service, mut bob_tx_sender, _, _, _, _, _, _, _, _) =
         setup_transaction_service_no_comms(
             &mut runtime,
             factories.clone(),
@@ -2780,7 +3455,7 @@ fn test_transaction_cancellation<T: TransactionBackend + Clone + 'static>(backen
     let bob_node_identity =
         NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap();
 
-    let (mut alice_ts, mut alice_output_manager, _alice_outbound_service, mut alice_tx_sender, _, _, _, _, _, _, _) =
+    let (mut alice_ts, mut alice_output_manager, _alice_outbound_service, mut alice_tx_sender, _, _, _, _, _, _, _, _) =
         setup_transaction_service_no_comms(&mut runtime, factories.clone(), backend, Some(Duration::from_secs(20)));
     let mut alice_event_stream = alice_ts.get_event_stream_fused();
 
@@ -2933,12 +3608,13 @@ fn test_resend_of_tx_on_pong_event<T: TransactionBackend + Clone + 'static>(back
         _,
         _,
         _,
+        _,
         _alice_liveness_handle,
         liveness_mock_state,
         liveness_event_sender,
     ) = setup_transaction_service_no_comms(&mut runtime, factories.clone(), backend, Some(Duration::from_secs(5)));
 
-    let (mut bob_ts, _, bob_outbound_service, mut bob_tx_sender, _, _, _, _, _, _, _) =
+    let (mut bob_ts, _, bob_outbound_service, mut bob_tx_sender, _, _, _, _, _, _, _, _) =
         setup_transaction_service_no_comms(
             &mut runtime,
             factories.clone(),
@@ -3086,3 +3762,277 @@ fn test_resend_of_tx_on_pong_event_sqlite_db() {
 
     test_resend_of_tx_on_pong_event(TransactionServiceSqliteDatabase::new(connection));
 }
+
+fn test_resend_and_expiry_of_pending_transaction<T: TransactionBackend + Clone + 'static>(backend: T) {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap();
+
+    let (mut alice_ts, mut alice_output_manager, alice_outbound_service, _, _, _, _, _, _, _, _, _) =
+        setup_transaction_service_no_comms_and_config(
+            &mut runtime,
+            factories.clone(),
+            backend,
+            TransactionServiceConfig {
+                transaction_resend_period: Duration::from_secs(2),
+                max_transaction_resend_period: Duration::from_secs(4),
+                pending_transaction_cancellation_timeout: Duration::from_secs(15),
+                ..Default::default()
+            },
+        );
+    let mut alice_event_stream = alice_ts.get_event_stream_fused();
+
+    let alice_total_available = 250000 * uT;
+    let (_utxo, uo) = make_input(&mut OsRng, alice_total_available, &factories.commitment);
+    runtime.block_on(alice_output_manager.add_output(uo)).unwrap();
+
+    let tx_id = runtime
+        .block_on(alice_ts.send_transaction(
+            bob_node_identity.public_key().clone(),
+            10000 * uT,
+            100 * uT,
+            "Testing Message".to_string(),
+        ))
+        .unwrap();
+    alice_outbound_service
+        .wait_call_count(2, Duration::from_secs(60))
+        .unwrap();
+    let _ = alice_outbound_service.take_calls();
+
+    // No reply arrives so the sender message is resent directly and via SAF
+    alice_outbound_service
+        .wait_call_count(2, Duration::from_secs(60))
+        .unwrap();
+    let (_, body) = alice_outbound_service.pop_call().unwrap();
+    match try_decode_sender_message(body.to_vec()) {
+        Some(TransactionSenderMessage::Single(data)) => assert_eq!(data.tx_id, tx_id),
+        _ => assert!(false, "Resent message is not a single round sender message"),
+    }
+    assert_eq!(
+        runtime
+            .block_on(alice_output_manager.get_balance())
+            .unwrap()
+            .pending_outgoing_balance,
+        alice_total_available
+    );
+
+    runtime.block_on(async {
+        let mut delay = delay_for(Duration::from_secs(60)).fuse();
+        let mut cancelled = false;
+        loop {
+            futures::select! {
+                event = alice_event_stream.select_next_some() => {
+                    if let TransactionEvent::TransactionCancelled(id) = &*event.unwrap() {
+                        if *id == tx_id {
+                            cancelled = true;
+                            break;
+                        }
+                    }
+                },
+                () = delay => {
+                    break;
+                },
+            }
+        }
+        assert!(cancelled, "Pending transaction was not cancelled after it expired");
+    });
+
+    let balance = runtime.block_on(alice_output_manager.get_balance()).unwrap();
+    assert_eq!(balance.available_balance, alice_total_available);
+    assert_eq!(balance.pending_outgoing_balance, MicroTari::from(0));
+
+    // Bob is notified of the cancellation
+    let mut cancellations = Vec::new();
+    for _ in 0..60 {
+        cancellations.extend(
+            alice_outbound_service
+                .take_calls()
+                .into_iter()
+                .filter_map(|(_, body)| try_decode_transaction_cancelled_message(body.to_vec())),
+        );
+        if !cancellations.is_empty() {
+            break;
+        }
+        runtime.block_on(delay_for(Duration::from_secs(1)));
+    }
+    assert!(!cancellations.is_empty());
+    assert!(cancellations.iter().all(|m| m.tx_id == tx_id));
+}
+
+#[test]
+fn test_resend_and_expiry_of_pending_transaction_memory_db() {
+    test_resend_and_expiry_of_pending_transaction(TransactionMemoryDatabase::new());
+}
+
+#[test]
+fn test_resend_and_expiry_of_pending_transaction_sqlite_db() {
+    let db_name = format!("{}.sqlite3", random_string(8).as_str());
+    let temp_dir = TempDir::new(random_string(8).as_str()).unwrap();
+    let db_folder = temp_dir.path().to_str().unwrap().to_string();
+    let connection = run_migration_and_create_sqlite_connection(&format!("{}/{}", db_folder, db_name)).unwrap();
+
+    test_resend_and_expiry_of_pending_transaction(TransactionServiceSqliteDatabase::new(connection));
+}
+
+#[test]
+fn test_transaction_cancelled_by_sender() {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+
+    let alice_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap();
+
+    let (_, mut alice_output_manager, _, _, _, _, _, _, _, _, _, _) =
+        setup_transaction_service_no_comms(&mut runtime, factories.clone(), TransactionMemoryDatabase::new(), None);
+    let (
+        bob_ts,
+        mut bob_output_manager,
+        bob_outbound_service,
+        mut bob_tx_sender,
+        _,
+        _,
+        mut bob_tx_cancelled_sender,
+        _,
+        _,
+        _,
+        _,
+        _,
+    ) = setup_transaction_service_no_comms(&mut runtime, factories.clone(), TransactionMemoryDatabase::new(), None);
+    let mut bob_event_stream = bob_ts.get_event_stream_fused();
+
+    let (_utxo, uo) = make_input(&mut OsRng, MicroTari(250000), &factories.commitment);
+    runtime.block_on(alice_output_manager.add_output(uo)).unwrap();
+    let amount = MicroTari::from(5000);
+    let mut stp = runtime
+        .block_on(alice_output_manager.prepare_transaction_to_send(amount, MicroTari::from(25), None, "".to_string()))
+        .unwrap();
+    let msg = stp.build_single_round_message().unwrap();
+    let tx_id = msg.tx_id;
+    runtime
+        .block_on(bob_tx_sender.send(create_dummy_message(
+            TransactionSenderMessage::Single(Box::new(msg)).into(),
+            alice_node_identity.public_key(),
+        )))
+        .unwrap();
+    bob_outbound_service
+        .wait_call_count(2, Duration::from_secs(60))
+        .unwrap();
+    assert_eq!(
+        runtime
+            .block_on(bob_output_manager.get_balance())
+            .unwrap()
+            .pending_incoming_balance,
+        amount
+    );
+
+    // Only the sender of the transaction can cancel it
+    runtime
+        .block_on(bob_tx_cancelled_sender.send(create_dummy_message(
+            proto::TransactionCancelledMessage { tx_id },
+            &PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        )))
+        .unwrap();
+    runtime
+        .block_on(bob_tx_cancelled_sender.send(create_dummy_message(
+            proto::TransactionCancelledMessage { tx_id },
+            alice_node_identity.public_key(),
+        )))
+        .unwrap();
+
+    runtime.block_on(async {
+        let mut delay = delay_for(Duration::from_secs(60)).fuse();
+        let mut cancelled = 0;
+        loop {
+            futures::select! {
+                event = bob_event_stream.select_next_some() => {
+                    if let TransactionEvent::TransactionCancelled(id) = &*event.unwrap() {
+                        assert_eq!(*id, tx_id);
+                        cancelled += 1;
+                    }
+                },
+                () = delay => {
+                    break;
+                },
+            }
+            if cancelled > 1 {
+                break;
+            }
+        }
+        assert_eq!(cancelled, 1);
+    });
+
+    assert_eq!(
+        runtime
+            .block_on(bob_output_manager.get_balance())
+            .unwrap()
+            .pending_incoming_balance,
+        MicroTari::from(0)
+    );
+}
+
+#[test]
+fn test_transaction_history() {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut alice_ts, mut alice_output_manager, alice_outbound_service, _, _, _, _, _, _, _, _, _) =
+        setup_transaction_service_no_comms(&mut runtime, factories.clone(), TransactionMemoryDatabase::new(), None);
+
+    let (_, bob_public_key) = PublicKey::random_keypair(&mut OsRng);
+    let (_, carol_public_key) = PublicKey::random_keypair(&mut OsRng);
+
+    let (_utxo, uo) = make_input(&mut OsRng, MicroTari(250000), &factories.commitment);
+    runtime.block_on(alice_output_manager.add_output(uo)).unwrap();
+    let imported_tx_id = runtime
+        .block_on(alice_ts.import_utxo(
+            MicroTari::from(250000),
+            carol_public_key.clone(),
+            "Imported".to_string(),
+        ))
+        .unwrap();
+    let sent_tx_id = runtime
+        .block_on(alice_ts.send_transaction(
+            bob_public_key.clone(),
+            MicroTari::from(10000),
+            MicroTari::from(20),
+            "Rent".to_string(),
+        ))
+        .unwrap();
+    alice_outbound_service
+        .wait_call_count(2, Duration::from_secs(60))
+        .unwrap();
+
+    let records = runtime
+        .block_on(alice_ts.get_transaction_history(TransactionHistoryFilter::default()))
+        .unwrap();
+    assert_eq!(records.len(), 2);
+    let imported = records.iter().find(|r| r.tx_id == imported_tx_id).unwrap();
+    assert_eq!(imported.direction, TransactionDirection::Inbound);
+    assert_eq!(imported.counterparty_public_key, carol_public_key);
+    assert_eq!(imported.status, TransactionStatus::Imported);
+    let sent = records.iter().find(|r| r.tx_id == sent_tx_id).unwrap();
+    assert_eq!(sent.direction, TransactionDirection::Outbound);
+    assert_eq!(sent.counterparty_public_key, bob_public_key);
+    assert_eq!(sent.amount, MicroTari::from(10000));
+    assert_eq!(sent.message, "Rent".to_string());
+    assert_eq!(sent.status, TransactionStatus::Pending);
+
+    let records = runtime
+        .block_on(alice_ts.get_transaction_history(TransactionHistoryFilter {
+            statuses: vec![TransactionStatus::Pending],
+            ..Default::default()
+        }))
+        .unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].tx_id, sent_tx_id);
+
+    let records = runtime
+        .block_on(alice_ts.get_transaction_history(TransactionHistoryFilter {
+            from: Some(Utc::now().naive_utc() + chrono::Duration::hours(1)),
+            ..Default::default()
+        }))
+        .unwrap();
+    assert!(records.is_empty());
+}
@@ -229,6 +229,9 @@ pub fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
             },
             message: messages[i].clone(),
             timestamp: Utc::now().naive_utc(),
+            mined_height: None,
+            mined_block_hash: None,
+            confirmations: None,
         });
         runtime
             .block_on(db.complete_outbound_transaction(outbound_txs[i].tx_id, completed_txs[i].clone()))
@@ -305,6 +308,30 @@ pub fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
             retrieved_completed_txs.get(&completed_txs[0].tx_id).unwrap().status,
             TransactionStatus::Mined
         );
+
+        runtime
+            .block_on(db.set_completed_transaction_mined_height(completed_txs[0].tx_id, 42, vec![1u8; 32]))
+            .unwrap();
+        runtime
+            .block_on(db.set_completed_transaction_confirmations(completed_txs[0].tx_id, 3))
+            .unwrap();
+        let retrieved_tx = runtime
+            .block_on(db.get_completed_transaction(completed_txs[0].tx_id))
+            .unwrap();
+        assert_eq!(retrieved_tx.mined_height, Some(42));
+        assert_eq!(retrieved_tx.mined_block_hash, Some(vec![1u8; 32]));
+        assert_eq!(retrieved_tx.confirmations, Some(3));
+
+        runtime
+            .block_on(db.revert_mined_completed_transaction(completed_txs[0].tx_id))
+            .unwrap();
+        let retrieved_tx = runtime
+            .block_on(db.get_completed_transaction(completed_txs[0].tx_id))
+            .unwrap();
+        assert_eq!(retrieved_tx.status, TransactionStatus::Broadcast);
+        assert_eq!(retrieved_tx.mined_height, None);
+        assert_eq!(retrieved_tx.mined_block_hash, None);
+        assert_eq!(retrieved_tx.confirmations, None);
     }
 
     let completed_txs = runtime.block_on(db.get_completed_transactions()).unwrap();
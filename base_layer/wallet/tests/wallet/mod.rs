@@ -88,6 +88,8 @@ fn create_wallet(
         comms_config,
         factories,
         transaction_service_config: None,
+        output_manager_service_config: None,
+        base_node_service_config: None,
     };
     let runtime_node = Runtime::new().unwrap();
     let wallet = Wallet::new(
@@ -334,6 +336,8 @@ fn test_import_utxo() {
         comms_config,
         factories: factories.clone(),
         transaction_service_config: None,
+        output_manager_service_config: None,
+        base_node_service_config: None,
     };
     let runtime_node = Runtime::new().unwrap();
     let mut alice_wallet = Wallet::new(
@@ -405,6 +409,8 @@ fn test_data_generation() {
         comms_config,
         factories,
         transaction_service_config: None,
+        output_manager_service_config: None,
+        base_node_service_config: None,
     };
 
     let transaction_backend = TransactionMemoryDatabase::new();
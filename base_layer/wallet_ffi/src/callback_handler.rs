@@ -193,6 +193,9 @@ where TBackend: TransactionBackend + 'static
                                 TransactionEvent::TransactionMined(tx_id) => {
                                     self.receive_transaction_mined_event(tx_id).await;
                                 },
+                                TransactionEvent::ReceivedOneSidedTransaction(tx_id) => {
+                                    self.receive_transaction_mined_event(tx_id).await;
+                                },
                                 /// Only the above variants are mapped to callbacks
                                 _ => (),
                             }
@@ -30,10 +30,12 @@ use tari_crypto::{
     tari_utilities::{hex::HexError, ByteArrayError},
 };
 use tari_wallet::{
+    base_node_service::error::BaseNodeServiceError,
     contacts_service::error::{ContactsServiceError, ContactsServiceStorageError},
     error::WalletError,
     output_manager_service::error::{OutputManagerError, OutputManagerStorageError},
     transaction_service::error::{TransactionServiceError, TransactionStorageError},
+    util::payment_request::PaymentRequestError,
 };
 
 const LOG_TARGET: &str = "wallet_ffi::error";
@@ -55,6 +57,9 @@ pub enum InterfaceError {
     DeserializationError(String),
     /// Emoji ID is invalid
     InvalidEmojiId,
+    /// An argument is outside the range of values accepted by the function
+    #[error(msg_embedded, non_std, no_from)]
+    InvalidArgument(String),
 }
 
 /// This struct is meant to hold an error for use by FFI client applications. The error has an integer code and string
@@ -93,6 +98,10 @@ impl From<InterfaceError> for LibWalletError {
                 code: 6,
                 message: format!("{:?}", v),
             },
+            InterfaceError::InvalidArgument(_) => Self {
+                code: 7,
+                message: format!("{:?}", v),
+            },
         }
     }
 }
@@ -174,6 +183,30 @@ impl From<WalletError> for LibWalletError {
                 code: 113,
                 message: format!("{:?}", w),
             },
+            WalletError::OutputManagerError(OutputManagerError::UnspentOutputNotFound) => Self {
+                code: 114,
+                message: format!("{:?}", w),
+            },
+            WalletError::TransactionServiceError(TransactionServiceError::OutputManagerError(
+                OutputManagerError::UnspentOutputNotFound,
+            )) => Self {
+                code: 115,
+                message: format!("{:?}", w),
+            },
+            WalletError::OutputManagerError(OutputManagerError::WatchOnlyWallet) => Self {
+                code: 116,
+                message: format!("{:?}", w),
+            },
+            WalletError::TransactionServiceError(TransactionServiceError::OutputManagerError(
+                OutputManagerError::WatchOnlyWallet,
+            )) => Self {
+                code: 116,
+                message: format!("{:?}", w),
+            },
+            WalletError::OutputManagerError(OutputManagerError::WatchedOutputNotFound) => Self {
+                code: 117,
+                message: format!("{:?}", w),
+            },
             // Transaction Service Errors
             WalletError::TransactionServiceError(TransactionServiceError::InvalidStateError) => Self {
                 code: 201,
@@ -212,6 +245,10 @@ impl From<WalletError> for LibWalletError {
                 code: 302,
                 message: format!("{:?}", w),
             },
+            WalletError::BaseNodeServiceError(BaseNodeServiceError::BaseNodeNotFound) => Self {
+                code: 303,
+                message: format!("{:?}", w),
+            },
             WalletError::ContactsServiceError(ContactsServiceError::ContactNotFound) => Self {
                 code: 401,
                 message: format!("{:?}", w),
@@ -297,6 +334,10 @@ impl From<NodeIdentityError> for LibWalletError {
                 code: 704,
                 message: format!("{:?}", n),
             },
+            NodeIdentityError::UnattainableProofOfWorkDifficulty => Self {
+                code: 705,
+                message: format!("{:?}", n),
+            },
         }
     }
 }
@@ -337,6 +378,54 @@ impl From<multiaddr::Error> for LibWalletError {
     }
 }
 
+impl From<PaymentRequestError> for LibWalletError {
+    fn from(err: PaymentRequestError) -> Self {
+        error!(target: LOG_TARGET, "{}", format!("{:?}", err));
+        match err {
+            PaymentRequestError::InvalidScheme => Self {
+                code: 1001,
+                message: format!("{:?}", err),
+            },
+            PaymentRequestError::InvalidDestination => Self {
+                code: 1002,
+                message: format!("{:?}", err),
+            },
+            PaymentRequestError::InvalidAmount => Self {
+                code: 1003,
+                message: format!("{:?}", err),
+            },
+            PaymentRequestError::InvalidExpiry => Self {
+                code: 1004,
+                message: format!("{:?}", err),
+            },
+            PaymentRequestError::InvalidBaseNodeHint => Self {
+                code: 1005,
+                message: format!("{:?}", err),
+            },
+            PaymentRequestError::InvalidEncoding => Self {
+                code: 1006,
+                message: format!("{:?}", err),
+            },
+            PaymentRequestError::DuplicateParameter(_) => Self {
+                code: 1007,
+                message: format!("{:?}", err),
+            },
+            PaymentRequestError::MissingChecksum => Self {
+                code: 1008,
+                message: format!("{:?}", err),
+            },
+            PaymentRequestError::InvalidChecksum => Self {
+                code: 1009,
+                message: format!("{:?}", err),
+            },
+            PaymentRequestError::Expired => Self {
+                code: 1010,
+                message: format!("{:?}", err),
+            },
+        }
+    }
+}
+
 impl From<SchnorrSignatureError> for LibWalletError {
     fn from(err: SchnorrSignatureError) -> Self {
         error!(target: LOG_TARGET, "{}", format!("{:?}", err));
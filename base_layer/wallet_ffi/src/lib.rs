@@ -118,6 +118,7 @@ mod callback_handler;
 mod error;
 
 use crate::{callback_handler::CallbackHandler, error::InterfaceError};
+use chrono::NaiveDateTime;
 use core::ptr;
 use error::LibWalletError;
 use libc::{c_char, c_int, c_longlong, c_uchar, c_uint, c_ulonglong, c_ushort};
@@ -130,6 +131,7 @@ use log4rs::{
 use rand::rngs::OsRng;
 use std::{
     boxed::Box,
+    convert::TryFrom,
     ffi::{CStr, CString},
     path::PathBuf,
     slice,
@@ -143,7 +145,11 @@ use tari_comms::{
     tor,
 };
 use tari_comms_dht::{DbConnectionUrl, DhtConfig};
-use tari_core::transactions::{tari_amount::MicroTari, types::CryptoFactories};
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction::OutputFeatures,
+    types::{Commitment, CryptoFactories},
+};
 use tari_crypto::{
     keys::{PublicKey, SecretKey},
     tari_utilities::ByteArray,
@@ -153,7 +159,11 @@ use tari_utilities::{hex, hex::Hex, message_format::MessageFormat};
 use tari_wallet::{
     contacts_service::storage::{database::Contact, sqlite_db::ContactsServiceSqliteDatabase},
     error::WalletError,
-    output_manager_service::storage::sqlite_db::OutputManagerSqliteDatabase,
+    output_manager_service::{
+        config::OutputManagerServiceConfig,
+        error::OutputManagerError,
+        storage::sqlite_db::OutputManagerSqliteDatabase,
+    },
     storage::{connection_manager::run_migration_and_create_sqlite_connection, sqlite_db::WalletSqliteDatabase},
     testnet_utils::{
         broadcast_transaction,
@@ -164,11 +174,14 @@ use tari_wallet::{
         mine_transaction,
         receive_test_transaction,
     },
-    transaction_service::storage::{
-        database::{InboundTransaction, OutboundTransaction, TransactionDatabase, TransactionStatus},
-        sqlite_db::TransactionServiceSqliteDatabase,
+    transaction_service::{
+        history::{AccountingPeriod, TransactionHistoryFilter, TransactionHistoryFormat},
+        storage::{
+            database::{InboundTransaction, OutboundTransaction, TransactionDatabase, TransactionStatus},
+            sqlite_db::TransactionServiceSqliteDatabase,
+        },
     },
-    util::emoji::EmojiId,
+    util::{emoji::EmojiId, payment_request::PaymentRequestError},
     wallet::WalletConfig,
 };
 use tokio::runtime::Runtime;
@@ -191,6 +204,7 @@ pub struct TariContacts(Vec<TariContact>);
 
 pub type TariContact = tari_wallet::contacts_service::storage::database::Contact;
 pub type TariCompletedTransaction = tari_wallet::transaction_service::storage::database::CompletedTransaction;
+pub type TariPaymentRequest = tari_wallet::util::payment_request::PaymentRequest;
 
 pub struct TariCompletedTransactions(Vec<TariCompletedTransaction>);
 
@@ -698,6 +712,286 @@ pub unsafe extern "C" fn private_key_from_hex(key: *const c_char, error_out: *mu
 
 /// -------------------------------------------------------------------------------------------- ///
 
+/// ----------------------------------- PaymentRequest ------------------------------------------///
+
+/// Creates a TariPaymentRequest asking for `amount` to be paid to `public_key`
+///
+/// ## Arguments
+/// `public_key` - The TariPublicKey pointer of the recipient
+/// `amount` - The amount to be paid
+/// `message` - The pointer to a char array holding the message for the payer, may be null
+/// `expiry` - The unix timestamp after which the request should no longer be paid, 0 if it does not expire
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariPaymentRequest` - Returns a pointer to a TariPaymentRequest. Note that it returns ptr::null_mut()
+/// if public_key is null, message is not valid UTF-8 or expiry is not a representable unix timestamp
+///
+/// # Safety
+/// The ```payment_request_destroy``` method must be called when finished with a TariPaymentRequest to prevent a memory
+/// leak
+#[no_mangle]
+pub unsafe extern "C" fn payment_request_create(
+    public_key: *mut TariPublicKey,
+    amount: c_ulonglong,
+    message: *const c_char,
+    expiry: c_ulonglong,
+    error_out: *mut c_int,
+) -> *mut TariPaymentRequest
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if public_key.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("public_key".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    let mut request = TariPaymentRequest::new((*public_key).clone(), MicroTari::from(amount));
+    if !message.is_null() {
+        match CStr::from_ptr(message).to_str() {
+            Ok(message) => request = request.with_message(message.to_owned()),
+            Err(_) => {
+                error = LibWalletError::from(PaymentRequestError::InvalidEncoding).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                return ptr::null_mut();
+            },
+        }
+    }
+    if expiry > 0 {
+        let expiry = i64::try_from(expiry)
+            .ok()
+            .and_then(|t| NaiveDateTime::from_timestamp_opt(t, 0));
+        match expiry {
+            Some(expiry) => request = request.with_expiry(expiry),
+            None => {
+                error = LibWalletError::from(PaymentRequestError::InvalidExpiry).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                return ptr::null_mut();
+            },
+        }
+    }
+    Box::into_raw(Box::new(request))
+}
+
+/// Parses a `tari:` payment request URI, validating its checksum
+///
+/// ## Arguments
+/// `uri` - The pointer to a char array holding the URI
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariPaymentRequest` - Returns a pointer to a TariPaymentRequest. Note that it returns ptr::null_mut()
+/// if uri is null or is not a valid payment request
+///
+/// # Safety
+/// The ```payment_request_destroy``` method must be called when finished with a TariPaymentRequest to prevent a memory
+/// leak
+#[no_mangle]
+pub unsafe extern "C" fn payment_request_from_uri(
+    uri: *const c_char,
+    error_out: *mut c_int,
+) -> *mut TariPaymentRequest
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if uri.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("uri".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    let uri = match CStr::from_ptr(uri).to_str() {
+        Ok(uri) => uri,
+        Err(_) => {
+            error = LibWalletError::from(PaymentRequestError::InvalidEncoding).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    };
+    match TariPaymentRequest::from_uri(uri) {
+        Ok(request) => Box::into_raw(Box::new(request)),
+        Err(e) => {
+            error = LibWalletError::from(e).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Encodes a TariPaymentRequest as a `tari:` URI that can be shared or shown as a QR code
+///
+/// ## Arguments
+/// `request` - The pointer to a TariPaymentRequest
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if request is null
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn payment_request_to_uri(
+    request: *mut TariPaymentRequest,
+    error_out: *mut c_int,
+) -> *mut c_char
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    let mut result = CString::new("").unwrap();
+    if request.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("request".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+    } else {
+        result = CString::new((*request).to_uri()).unwrap();
+    }
+    CString::into_raw(result)
+}
+
+/// Gets the TariPublicKey of the recipient of a TariPaymentRequest
+///
+/// ## Arguments
+/// `request` - The pointer to a TariPaymentRequest
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariPublicKey` - Returns a pointer to a TariPublicKey. Note that it returns ptr::null_mut() if request is null
+///
+/// # Safety
+/// The ```public_key_destroy``` method must be called when finished with a TariPublicKey to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn payment_request_get_public_key(
+    request: *mut TariPaymentRequest,
+    error_out: *mut c_int,
+) -> *mut TariPublicKey
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if request.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("request".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    Box::into_raw(Box::new((*request).destination.clone()))
+}
+
+/// Gets the amount requested by a TariPaymentRequest
+///
+/// ## Arguments
+/// `request` - The pointer to a TariPaymentRequest
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_ulonglong` - Returns the amount, note that it will be zero if request is null
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn payment_request_get_amount(
+    request: *mut TariPaymentRequest,
+    error_out: *mut c_int,
+) -> c_ulonglong
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if request.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("request".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    c_ulonglong::from((*request).amount)
+}
+
+/// Gets the message of a TariPaymentRequest
+///
+/// ## Arguments
+/// `request` - The pointer to a TariPaymentRequest
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if request is null or
+/// the message contains a NUL character
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn payment_request_get_message(
+    request: *mut TariPaymentRequest,
+    error_out: *mut c_int,
+) -> *mut c_char
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    let mut result = CString::new("").unwrap();
+    if request.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("request".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+    } else {
+        match CString::new((*request).message.clone()) {
+            Ok(message) => result = message,
+            Err(_) => {
+                error = LibWalletError::from(PaymentRequestError::InvalidEncoding).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+            },
+        }
+    }
+    CString::into_raw(result)
+}
+
+/// Gets the expiry of a TariPaymentRequest
+///
+/// ## Arguments
+/// `request` - The pointer to a TariPaymentRequest
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_ulonglong` - Returns the unix timestamp after which the request should not be paid, note that it will be zero if
+/// the request does not expire or request is null
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn payment_request_get_expiry(
+    request: *mut TariPaymentRequest,
+    error_out: *mut c_int,
+) -> c_ulonglong
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if request.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("request".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    (*request).expiry.map(|e| e.timestamp() as c_ulonglong).unwrap_or(0)
+}
+
+/// Frees memory for a TariPaymentRequest
+///
+/// ## Arguments
+/// `request` - The pointer to a TariPaymentRequest
+///
+/// ## Returns
+/// `()` - Does not return a value, equivalent to void in C
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn payment_request_destroy(request: *mut TariPaymentRequest) {
+    if !request.is_null() {
+        Box::from_raw(request);
+    }
+}
+
+/// -------------------------------------------------------------------------------------------- ///
+
 /// ----------------------------------- Contact -------------------------------------------------///
 
 /// Creates a TariContact
@@ -1300,6 +1594,35 @@ pub unsafe extern "C" fn completed_transaction_get_status(
     status as c_int
 }
 
+/// Gets the number of confirmations of a TariCompletedTransaction
+///
+/// ## Arguments
+/// `transaction` - The pointer to a TariCompletedTransaction
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_ulonglong` - Returns the number of blocks built on top of the transaction, including the block it was mined in.
+/// Note that it will be zero if the transaction is null or has not been mined
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn completed_transaction_get_confirmations(
+    transaction: *mut TariCompletedTransaction,
+    error_out: *mut c_int,
+) -> c_ulonglong
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if transaction.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("transaction".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    (*transaction).confirmations.unwrap_or(0) as c_ulonglong
+}
+
 /// Gets the amount of a TariCompletedTransaction
 ///
 /// ## Arguments
@@ -2301,46 +2624,128 @@ pub unsafe extern "C" fn wallet_create(
     error_out: *mut c_int,
 ) -> *mut TariWallet
 {
-    let mut error = 0;
-    ptr::swap(error_out, &mut error as *mut c_int);
-    if config.is_null() {
-        error = LibWalletError::from(InterfaceError::NullError("config".to_string())).code;
-        ptr::swap(error_out, &mut error as *mut c_int);
-        return ptr::null_mut();
-    }
-
-    if !log_path.is_null() {
-        let path = CStr::from_ptr(log_path).to_str().unwrap().to_owned();
-        let logfile = FileAppender::builder()
-            .encoder(Box::new(PatternEncoder::new(
-                "{d(%Y-%m-%d %H:%M:%S.%f)} [{t}] {l:5} {m}{n}",
-            )))
-            .append(false)
-            .build(path.as_str())
-            .unwrap();
-
-        let lconfig = Config::builder()
-            .appender(Appender::builder().build("logfile", Box::new(logfile)))
-            .build(Root::builder().appender("logfile").build(LevelFilter::Debug))
-            .unwrap();
-
-        log4rs::init_config(lconfig).expect("Should be able to start logging");
-        debug!(target: LOG_TARGET, "Logging started");
-    }
-
-    let runtime = Runtime::new();
-    let factories = CryptoFactories::default();
-    let w;
+    create_wallet(
+        config,
+        log_path,
+        callback_received_transaction,
+        callback_received_transaction_reply,
+        callback_received_finalized_transaction,
+        callback_transaction_broadcast,
+        callback_transaction_mined,
+        callback_direct_send_result,
+        callback_store_and_forward_send_result,
+        callback_transaction_cancellation,
+        callback_base_node_sync_complete,
+        false,
+        error_out,
+    )
+}
 
-    match runtime {
-        Ok(runtime) => {
-            let sql_database_path = (*config)
-                .datastore_path
-                .join((*config).peer_database_name.clone())
-                .with_extension("sqlite3");
-            let connection = run_migration_and_create_sqlite_connection(&sql_database_path)
-                .map_err(|e| {
-                    error!(
+/// Creates a watch-only TariWallet. A watch-only wallet holds no spending keys, it tracks the outputs added with
+/// `wallet_add_watched_output` through the base node and reports their value as its balance. Any function that needs
+/// to spend or receive funds fails with an error. Only public data is ever needed, Mimblewimble outputs have no view
+/// keys so the outputs to watch must be supplied by their commitments.
+///
+/// ## Arguments
+/// The arguments are the same as for `wallet_create`. The datastore path in the config should not be shared with a
+/// wallet that holds spending keys.
+///
+/// ## Returns
+/// `*mut TariWallet` - Returns a pointer to a TariWallet, note that it returns ptr::null_mut()
+/// if config is null, a wallet error was encountered or if the runtime could not be created
+///
+/// # Safety
+/// The ```wallet_destroy``` method must be called when finished with a TariWallet to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_create_watch_only(
+    config: *mut TariCommsConfig,
+    log_path: *const c_char,
+    callback_received_transaction: unsafe extern "C" fn(*mut TariPendingInboundTransaction),
+    callback_received_transaction_reply: unsafe extern "C" fn(*mut TariCompletedTransaction),
+    callback_received_finalized_transaction: unsafe extern "C" fn(*mut TariCompletedTransaction),
+    callback_transaction_broadcast: unsafe extern "C" fn(*mut TariCompletedTransaction),
+    callback_transaction_mined: unsafe extern "C" fn(*mut TariCompletedTransaction),
+    callback_direct_send_result: unsafe extern "C" fn(c_ulonglong, bool),
+    callback_store_and_forward_send_result: unsafe extern "C" fn(c_ulonglong, bool),
+    callback_transaction_cancellation: unsafe extern "C" fn(c_ulonglong),
+    callback_base_node_sync_complete: unsafe extern "C" fn(u64, bool),
+    error_out: *mut c_int,
+) -> *mut TariWallet
+{
+    create_wallet(
+        config,
+        log_path,
+        callback_received_transaction,
+        callback_received_transaction_reply,
+        callback_received_finalized_transaction,
+        callback_transaction_broadcast,
+        callback_transaction_mined,
+        callback_direct_send_result,
+        callback_store_and_forward_send_result,
+        callback_transaction_cancellation,
+        callback_base_node_sync_complete,
+        true,
+        error_out,
+    )
+}
+
+/// Creates a TariWallet, shared by `wallet_create` and `wallet_create_watch_only`
+unsafe fn create_wallet(
+    config: *mut TariCommsConfig,
+    log_path: *const c_char,
+    callback_received_transaction: unsafe extern "C" fn(*mut TariPendingInboundTransaction),
+    callback_received_transaction_reply: unsafe extern "C" fn(*mut TariCompletedTransaction),
+    callback_received_finalized_transaction: unsafe extern "C" fn(*mut TariCompletedTransaction),
+    callback_transaction_broadcast: unsafe extern "C" fn(*mut TariCompletedTransaction),
+    callback_transaction_mined: unsafe extern "C" fn(*mut TariCompletedTransaction),
+    callback_direct_send_result: unsafe extern "C" fn(c_ulonglong, bool),
+    callback_store_and_forward_send_result: unsafe extern "C" fn(c_ulonglong, bool),
+    callback_transaction_cancellation: unsafe extern "C" fn(c_ulonglong),
+    callback_base_node_sync_complete: unsafe extern "C" fn(u64, bool),
+    watch_only: bool,
+    error_out: *mut c_int,
+) -> *mut TariWallet
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if config.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("config".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    if !log_path.is_null() {
+        let path = CStr::from_ptr(log_path).to_str().unwrap().to_owned();
+        let logfile = FileAppender::builder()
+            .encoder(Box::new(PatternEncoder::new(
+                "{d(%Y-%m-%d %H:%M:%S.%f)} [{t}] {l:5} {m}{n}",
+            )))
+            .append(false)
+            .build(path.as_str())
+            .unwrap();
+
+        let lconfig = Config::builder()
+            .appender(Appender::builder().build("logfile", Box::new(logfile)))
+            .build(Root::builder().appender("logfile").build(LevelFilter::Debug))
+            .unwrap();
+
+        log4rs::init_config(lconfig).expect("Should be able to start logging");
+        debug!(target: LOG_TARGET, "Logging started");
+    }
+
+    let runtime = Runtime::new();
+    let factories = CryptoFactories::default();
+    let w;
+
+    match runtime {
+        Ok(runtime) => {
+            let sql_database_path = (*config)
+                .datastore_path
+                .join((*config).peer_database_name.clone())
+                .with_extension("sqlite3");
+            let connection = run_migration_and_create_sqlite_connection(&sql_database_path)
+                .map_err(|e| {
+                    error!(
                         target: LOG_TARGET,
                         "Error creating Sqlite Connection in Wallet: {:?}", e
                     );
@@ -2358,6 +2763,11 @@ pub unsafe extern "C" fn wallet_create(
                     comms_config: (*config).clone(),
                     factories,
                     transaction_service_config: None,
+                    output_manager_service_config: Some(OutputManagerServiceConfig {
+                        watch_only,
+                        ..Default::default()
+                    }),
+                    base_node_service_config: None,
                 },
                 runtime,
                 wallet_backend,
@@ -2828,7 +3238,8 @@ pub unsafe extern "C" fn wallet_test_mine_transaction(
     }
 }
 
-/// Adds a base node peer to the TariWallet
+/// Adds a base node peer to the end of the TariWallet's list of base nodes. The wallet uses the first healthy base node
+/// in the list and will fail over to the next one if it goes offline or falls behind the chain.
 ///
 /// ## Arguments
 /// `wallet` - The TariWallet pointer
@@ -2873,7 +3284,51 @@ pub unsafe extern "C" fn wallet_add_base_node_peer(
         return false;
     }
 
-    match (*wallet).set_base_node_peer((*public_key).clone(), address_string) {
+    match (*wallet).add_base_node_peer((*public_key).clone(), address_string) {
+        Ok(_) => true,
+        Err(e) => {
+            error = LibWalletError::from(e).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            false
+        },
+    }
+}
+
+/// Removes a base node peer from the TariWallet's list of base nodes
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `public_key` - The TariPublicKey pointer
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Returns if successful or not
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_remove_base_node_peer(
+    wallet: *mut TariWallet,
+    public_key: *mut TariPublicKey,
+    error_out: *mut c_int,
+) -> bool
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+
+    if public_key.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("public_key".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+
+    match (*wallet).remove_base_node_peer((*public_key).clone()) {
         Ok(_) => true,
         Err(e) => {
             error = LibWalletError::from(e).code;
@@ -2923,30 +3378,651 @@ pub unsafe extern "C" fn wallet_upsert_contact(
     {
         Ok(_) => true,
         Err(e) => {
-            error = LibWalletError::from(WalletError::ContactsServiceError(e)).code;
+            error = LibWalletError::from(WalletError::ContactsServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            false
+        },
+    }
+}
+
+/// Removes a TariContact from the TariWallet
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `tx` - The TariPendingInboundTransaction pointer
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Returns if successful or not
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_remove_contact(
+    wallet: *mut TariWallet,
+    contact: *mut TariContact,
+    error_out: *mut c_int,
+) -> bool
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+    if contact.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("contact".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).contacts_service.remove_contact((*contact).public_key.clone()))
+    {
+        Ok(_) => true,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::ContactsServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            false
+        },
+    }
+}
+
+/// Gets the available balance from a TariWallet. This is the balance the user can spend.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_ulonglong` - The available balance, 0 if wallet is null
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_available_balance(wallet: *mut TariWallet, error_out: *mut c_int) -> c_ulonglong {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).output_manager_service.get_balance())
+    {
+        Ok(b) => c_ulonglong::from(b.available_balance),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            0
+        },
+    }
+}
+
+/// Gets the incoming balance from a `TariWallet`. This is the uncleared balance of Tari that is
+/// expected to come into the `TariWallet` but is not yet spendable.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_ulonglong` - The incoming balance, 0 if wallet is null
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_pending_incoming_balance(
+    wallet: *mut TariWallet,
+    error_out: *mut c_int,
+) -> c_ulonglong
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).output_manager_service.get_balance())
+    {
+        Ok(b) => c_ulonglong::from(b.pending_incoming_balance),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            0
+        },
+    }
+}
+
+/// Gets the outgoing balance from a `TariWallet`. This is the uncleared balance of Tari that has
+/// been spent
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_ulonglong` - The outgoing balance, 0 if wallet is null
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_pending_outgoing_balance(
+    wallet: *mut TariWallet,
+    error_out: *mut c_int,
+) -> c_ulonglong
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).output_manager_service.get_balance())
+    {
+        Ok(b) => c_ulonglong::from(b.pending_outgoing_balance),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            0
+        },
+    }
+}
+
+/// Gets the time-locked balance from a `TariWallet`. This is the part of the available balance held in outputs that
+/// only become spendable at a block height above the current chain tip.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_ulonglong` - The time-locked balance, 0 if wallet is null or the chain tip height is not yet known
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_time_locked_balance(wallet: *mut TariWallet, error_out: *mut c_int) -> c_ulonglong {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).output_manager_service.get_balance())
+    {
+        Ok(b) => c_ulonglong::from(b.time_locked_balance.unwrap_or_default()),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            0
+        },
+    }
+}
+
+/// Sends a TariPendingOutboundTransaction
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `dest_public_key` - The TariPublicKey pointer of the peer
+/// `amount` - The amount
+/// `fee_per_gram` - The transaction fee
+/// `message` - The pointer to a char array
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `unsigned long long` - Returns 0 if unsuccessful or the TxId of the sent transaction if successful
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_send_transaction(
+    wallet: *mut TariWallet,
+    dest_public_key: *mut TariPublicKey,
+    amount: c_ulonglong,
+    fee_per_gram: c_ulonglong,
+    message: *const c_char,
+    error_out: *mut c_int,
+) -> c_ulonglong
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
+    if dest_public_key.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("dest_public_key".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
+    let message_string = if !message.is_null() {
+        CStr::from_ptr(message).to_str().unwrap().to_owned()
+    } else {
+        error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        CString::new("").unwrap().to_str().unwrap().to_owned()
+    };
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).transaction_service.send_transaction(
+            (*dest_public_key).clone(),
+            MicroTari::from(amount),
+            MicroTari::from(fee_per_gram),
+            message_string,
+        )) {
+        Ok(tx_id) => tx_id,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            0
+        },
+    }
+}
+
+/// Sends a time-locked TariPendingOutboundTransaction. The transaction cannot be mined before `lock_height` and the
+/// recipient can only spend the output it receives from the `maturity` block height.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `dest_public_key` - The TariPublicKey pointer of the peer
+/// `amount` - The amount
+/// `fee_per_gram` - The transaction fee
+/// `lock_height` - The block height before which the transaction cannot be mined
+/// `maturity` - The block height from which the recipient's output can be spent
+/// `message` - The pointer to a char array
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `unsigned long long` - Returns 0 if unsuccessful or the TxId of the sent transaction if successful
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_send_time_locked_transaction(
+    wallet: *mut TariWallet,
+    dest_public_key: *mut TariPublicKey,
+    amount: c_ulonglong,
+    fee_per_gram: c_ulonglong,
+    lock_height: c_ulonglong,
+    maturity: c_ulonglong,
+    message: *const c_char,
+    error_out: *mut c_int,
+) -> c_ulonglong
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
+    if dest_public_key.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("dest_public_key".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
+    let message_string = if !message.is_null() {
+        CStr::from_ptr(message).to_str().unwrap().to_owned()
+    } else {
+        error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        CString::new("").unwrap().to_str().unwrap().to_owned()
+    };
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).transaction_service.send_time_locked_transaction(
+            (*dest_public_key).clone(),
+            MicroTari::from(amount),
+            MicroTari::from(fee_per_gram),
+            Some(lock_height),
+            maturity,
+            message_string,
+        )) {
+        Ok(tx_id) => tx_id,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            0
+        },
+    }
+}
+
+/// Pays a TariPaymentRequest. The amount and message of the request are used for the transaction; expired requests
+/// are rejected.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `request` - The TariPaymentRequest pointer
+/// `fee_per_gram` - The transaction fee
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `unsigned long long` - Returns 0 if unsuccessful or the TxId of the sent transaction if successful
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_send_payment_request(
+    wallet: *mut TariWallet,
+    request: *mut TariPaymentRequest,
+    fee_per_gram: c_ulonglong,
+    error_out: *mut c_int,
+) -> c_ulonglong
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
+    if request.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("request".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
+    if (*request).is_expired() {
+        error = LibWalletError::from(PaymentRequestError::Expired).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).transaction_service.send_transaction(
+            (*request).destination.clone(),
+            (*request).amount,
+            MicroTari::from(fee_per_gram),
+            (*request).message.clone(),
+        )) {
+        Ok(tx_id) => tx_id,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            0
+        },
+    }
+}
+
+/// Sends a one-sided transaction. The recipient does not need to be online, the transaction is completed and broadcast
+/// immediately and the recipient will detect the payment when it scans the blockchain.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `dest_public_key` - The TariPublicKey pointer of the peer
+/// `amount` - The amount
+/// `fee_per_gram` - The transaction fee
+/// `message` - The pointer to a char array
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `unsigned long long` - Returns 0 if unsuccessful or the TxId of the sent transaction if successful
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_send_one_sided_transaction(
+    wallet: *mut TariWallet,
+    dest_public_key: *mut TariPublicKey,
+    amount: c_ulonglong,
+    fee_per_gram: c_ulonglong,
+    message: *const c_char,
+    error_out: *mut c_int,
+) -> c_ulonglong
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
+    if dest_public_key.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("dest_public_key".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
+    let message_string = if !message.is_null() {
+        CStr::from_ptr(message).to_str().unwrap().to_owned()
+    } else {
+        error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        CString::new("").unwrap().to_str().unwrap().to_owned()
+    };
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).transaction_service.send_one_sided_transaction(
+            (*dest_public_key).clone(),
+            MicroTari::from(amount),
+            MicroTari::from(fee_per_gram),
+            message_string,
+        )) {
+        Ok(tx_id) => tx_id,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            0
+        },
+    }
+}
+
+/// Parses a comma separated list of hex encoded commitments passed in by the client. If the pointer is null or a
+/// commitment is invalid `error_out` is set and `None` is returned.
+unsafe fn commitments_from_hex(
+    commitments: *const c_char,
+    name: &str,
+    error_out: *mut c_int,
+) -> Option<Vec<Commitment>>
+{
+    let mut error = 0;
+    if commitments.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError(name.to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return None;
+    }
+
+    let commitments_str = CStr::from_ptr(commitments).to_str().unwrap().to_owned();
+    match commitments_str
+        .split(',')
+        .map(|c| Commitment::from_hex(c.trim()))
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(c) => Some(c),
+        Err(e) => {
+            error = LibWalletError::from(e).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            None
+        },
+    }
+}
+
+/// Sends a TariPendingOutboundTransaction that spends exactly the specified unspent outputs, including frozen ones
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `dest_public_key` - The TariPublicKey pointer of the peer
+/// `amount` - The amount
+/// `fee_per_gram` - The transaction fee
+/// `message` - The pointer to a char array
+/// `inputs` - The pointer to a char array containing the comma separated hex commitments of the outputs to spend
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `unsigned long long` - Returns 0 if unsuccessful or the TxId of the sent transaction if successful
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_send_transaction_with_inputs(
+    wallet: *mut TariWallet,
+    dest_public_key: *mut TariPublicKey,
+    amount: c_ulonglong,
+    fee_per_gram: c_ulonglong,
+    message: *const c_char,
+    inputs: *const c_char,
+    error_out: *mut c_int,
+) -> c_ulonglong
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
+    if dest_public_key.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("dest_public_key".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
+    let inputs = match commitments_from_hex(inputs, "inputs", error_out) {
+        Some(inputs) => inputs,
+        None => return 0,
+    };
+
+    let message_string = if !message.is_null() {
+        CStr::from_ptr(message).to_str().unwrap().to_owned()
+    } else {
+        error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        CString::new("").unwrap().to_str().unwrap().to_owned()
+    };
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).transaction_service.send_transaction_with_inputs(
+            (*dest_public_key).clone(),
+            MicroTari::from(amount),
+            MicroTari::from(fee_per_gram),
+            message_string,
+            inputs,
+        )) {
+        Ok(tx_id) => tx_id,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            0
+        },
+    }
+}
+
+/// Freezes or unfreezes an unspent output. Frozen outputs are never selected automatically to fund a transaction.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `commitment` - The pointer to a char array containing the hex commitment of the output
+/// `frozen` - Whether the output should be frozen
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Returns if successful or not
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_set_output_frozen(
+    wallet: *mut TariWallet,
+    commitment: *const c_char,
+    frozen: bool,
+    error_out: *mut c_int,
+) -> bool
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+
+    let commitment = match commitments_from_hex(commitment, "commitment", error_out) {
+        Some(mut c) if c.len() == 1 => c.remove(0),
+        Some(_) => {
+            error = LibWalletError::from(InterfaceError::PositionInvalidError).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return false;
+        },
+        None => return false,
+    };
+
+    let mut output_manager = (*wallet).output_manager_service.clone();
+    let result = (*wallet).runtime.block_on(async move {
+        if frozen {
+            output_manager.freeze_output(commitment).await
+        } else {
+            output_manager.unfreeze_output(commitment).await
+        }
+    });
+    match result {
+        Ok(_) => true,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
             ptr::swap(error_out, &mut error as *mut c_int);
             false
         },
     }
 }
 
-/// Removes a TariContact from the TariWallet
+/// Checks whether an unspent output is frozen
 ///
 /// ## Arguments
 /// `wallet` - The TariWallet pointer
-/// `tx` - The TariPendingInboundTransaction pointer
+/// `commitment` - The pointer to a char array containing the hex commitment of the output
 /// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
 /// as an out parameter.
 ///
 /// ## Returns
-/// `bool` - Returns if successful or not
+/// `bool` - Returns true if the output is frozen, false if it is not or if an error occurred
 ///
 /// # Safety
 /// None
 #[no_mangle]
-pub unsafe extern "C" fn wallet_remove_contact(
+pub unsafe extern "C" fn wallet_is_output_frozen(
     wallet: *mut TariWallet,
-    contact: *mut TariContact,
+    commitment: *const c_char,
     error_out: *mut c_int,
 ) -> bool
 {
@@ -2957,201 +4033,281 @@ pub unsafe extern "C" fn wallet_remove_contact(
         ptr::swap(error_out, &mut error as *mut c_int);
         return false;
     }
-    if contact.is_null() {
-        error = LibWalletError::from(InterfaceError::NullError("contact".to_string())).code;
-        ptr::swap(error_out, &mut error as *mut c_int);
-        return false;
-    }
+
+    let commitment = match commitments_from_hex(commitment, "commitment", error_out) {
+        Some(mut c) if c.len() == 1 => c.remove(0),
+        Some(_) => {
+            error = LibWalletError::from(InterfaceError::PositionInvalidError).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return false;
+        },
+        None => return false,
+    };
 
     match (*wallet)
         .runtime
-        .block_on((*wallet).contacts_service.remove_contact((*contact).public_key.clone()))
+        .block_on((*wallet).output_manager_service.get_annotated_unspent_outputs())
     {
-        Ok(_) => true,
+        Ok(outputs) => match outputs.into_iter().find(|o| o.commitment == commitment) {
+            Some(o) => o.frozen,
+            None => {
+                error = LibWalletError::from(WalletError::OutputManagerError(
+                    OutputManagerError::UnspentOutputNotFound,
+                ))
+                .code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                false
+            },
+        },
         Err(e) => {
-            error = LibWalletError::from(WalletError::ContactsServiceError(e)).code;
+            error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
             ptr::swap(error_out, &mut error as *mut c_int);
             false
         },
     }
 }
 
-/// Gets the available balance from a TariWallet. This is the balance the user can spend.
+/// Sets the label of an unspent output
 ///
 /// ## Arguments
 /// `wallet` - The TariWallet pointer
+/// `commitment` - The pointer to a char array containing the hex commitment of the output
+/// `label` - The pointer to a char array containing the label, a null pointer clears the label
 /// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
 /// as an out parameter.
 ///
 /// ## Returns
-/// `c_ulonglong` - The available balance, 0 if wallet is null
+/// `bool` - Returns if successful or not
 ///
 /// # Safety
 /// None
 #[no_mangle]
-pub unsafe extern "C" fn wallet_get_available_balance(wallet: *mut TariWallet, error_out: *mut c_int) -> c_ulonglong {
+pub unsafe extern "C" fn wallet_set_output_label(
+    wallet: *mut TariWallet,
+    commitment: *const c_char,
+    label: *const c_char,
+    error_out: *mut c_int,
+) -> bool
+{
     let mut error = 0;
     ptr::swap(error_out, &mut error as *mut c_int);
     if wallet.is_null() {
         error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
         ptr::swap(error_out, &mut error as *mut c_int);
-        return 0;
+        return false;
     }
 
+    let commitment = match commitments_from_hex(commitment, "commitment", error_out) {
+        Some(mut c) if c.len() == 1 => c.remove(0),
+        Some(_) => {
+            error = LibWalletError::from(InterfaceError::PositionInvalidError).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return false;
+        },
+        None => return false,
+    };
+
+    let label = if label.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(label).to_str().unwrap().to_owned())
+    };
+
     match (*wallet)
         .runtime
-        .block_on((*wallet).output_manager_service.get_balance())
+        .block_on((*wallet).output_manager_service.set_output_label(commitment, label))
     {
-        Ok(b) => c_ulonglong::from(b.available_balance),
+        Ok(_) => true,
         Err(e) => {
             error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
             ptr::swap(error_out, &mut error as *mut c_int);
-            0
+            false
         },
     }
 }
 
-/// Gets the incoming balance from a `TariWallet`. This is the uncleared balance of Tari that is
-/// expected to come into the `TariWallet` but is not yet spendable.
+/// Gets the label of an unspent output
 ///
 /// ## Arguments
 /// `wallet` - The TariWallet pointer
+/// `commitment` - The pointer to a char array containing the hex commitment of the output
 /// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
 /// as an out parameter.
 ///
 /// ## Returns
-/// `c_ulonglong` - The incoming balance, 0 if wallet is null
+/// `*mut c_char` - Returns a pointer to a char array. Note that it returns empty if the output has no label or if an
+/// error occurred
 ///
 /// # Safety
-/// None
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
 #[no_mangle]
-pub unsafe extern "C" fn wallet_get_pending_incoming_balance(
+pub unsafe extern "C" fn wallet_get_output_label(
     wallet: *mut TariWallet,
+    commitment: *const c_char,
     error_out: *mut c_int,
-) -> c_ulonglong
+) -> *mut c_char
 {
     let mut error = 0;
+    let mut result = CString::new("").unwrap();
     ptr::swap(error_out, &mut error as *mut c_int);
     if wallet.is_null() {
         error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
         ptr::swap(error_out, &mut error as *mut c_int);
-        return 0;
+        return result.into_raw();
     }
 
+    let commitment = match commitments_from_hex(commitment, "commitment", error_out) {
+        Some(mut c) if c.len() == 1 => c.remove(0),
+        Some(_) => {
+            error = LibWalletError::from(InterfaceError::PositionInvalidError).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return result.into_raw();
+        },
+        None => return result.into_raw(),
+    };
+
     match (*wallet)
         .runtime
-        .block_on((*wallet).output_manager_service.get_balance())
+        .block_on((*wallet).output_manager_service.get_annotated_unspent_outputs())
     {
-        Ok(b) => c_ulonglong::from(b.pending_incoming_balance),
+        Ok(outputs) => match outputs.into_iter().find(|o| o.commitment == commitment) {
+            Some(o) => {
+                if let Some(label) = o.label {
+                    result = CString::new(label).unwrap_or_else(|_| CString::new("").unwrap());
+                }
+            },
+            None => {
+                error = LibWalletError::from(WalletError::OutputManagerError(
+                    OutputManagerError::UnspentOutputNotFound,
+                ))
+                .code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+            },
+        },
         Err(e) => {
             error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
             ptr::swap(error_out, &mut error as *mut c_int);
-            0
         },
     }
+
+    result.into_raw()
 }
 
-/// Gets the outgoing balance from a `TariWallet`. This is the uncleared balance of Tari that has
-/// been spent
+/// Starts tracking an output of another wallet, e.g. cold storage, in a watch-only wallet. The status of the output is
+/// updated by the next base node sync.
 ///
 /// ## Arguments
 /// `wallet` - The TariWallet pointer
+/// `commitment` - The pointer to a char array containing the hex commitment of the output
+/// `amount` - The value of the output in MicroTari, it cannot be recovered from the commitment
+/// `maturity` - The block height from which the output can be spent, 0 if it has none
+/// `label` - The pointer to a char array containing a label for the output, may be null
 /// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
 /// as an out parameter.
 ///
 /// ## Returns
-/// `c_ulonglong` - The outgoing balance, 0 if wallet is null
+/// `bool` - Returns if successful or not
 ///
 /// # Safety
 /// None
 #[no_mangle]
-pub unsafe extern "C" fn wallet_get_pending_outgoing_balance(
+pub unsafe extern "C" fn wallet_add_watched_output(
     wallet: *mut TariWallet,
+    commitment: *const c_char,
+    amount: c_ulonglong,
+    maturity: c_ulonglong,
+    label: *const c_char,
     error_out: *mut c_int,
-) -> c_ulonglong
+) -> bool
 {
     let mut error = 0;
     ptr::swap(error_out, &mut error as *mut c_int);
     if wallet.is_null() {
         error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
         ptr::swap(error_out, &mut error as *mut c_int);
-        return 0;
+        return false;
     }
 
+    let commitment = match commitments_from_hex(commitment, "commitment", error_out) {
+        Some(mut c) if c.len() == 1 => c.remove(0),
+        Some(_) => {
+            error = LibWalletError::from(InterfaceError::PositionInvalidError).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return false;
+        },
+        None => return false,
+    };
+
+    let label = if label.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(label).to_str().unwrap().to_owned())
+    };
+
     match (*wallet)
         .runtime
-        .block_on((*wallet).output_manager_service.get_balance())
-    {
-        Ok(b) => c_ulonglong::from(b.pending_outgoing_balance),
+        .block_on((*wallet).output_manager_service.add_watched_output(
+            commitment,
+            OutputFeatures::with_maturity(maturity),
+            MicroTari::from(amount),
+            label,
+        )) {
+        Ok(_) => true,
         Err(e) => {
             error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
             ptr::swap(error_out, &mut error as *mut c_int);
-            0
+            false
         },
     }
 }
 
-/// Sends a TariPendingOutboundTransaction
+/// Stops tracking a watched output
 ///
 /// ## Arguments
 /// `wallet` - The TariWallet pointer
-/// `dest_public_key` - The TariPublicKey pointer of the peer
-/// `amount` - The amount
-/// `fee_per_gram` - The transaction fee
-/// `message` - The pointer to a char array
+/// `commitment` - The pointer to a char array containing the hex commitment of the output
 /// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
 /// as an out parameter.
 ///
 /// ## Returns
-/// `unsigned long long` - Returns 0 if unsuccessful or the TxId of the sent transaction if successful
+/// `bool` - Returns if successful or not
 ///
 /// # Safety
 /// None
 #[no_mangle]
-pub unsafe extern "C" fn wallet_send_transaction(
+pub unsafe extern "C" fn wallet_remove_watched_output(
     wallet: *mut TariWallet,
-    dest_public_key: *mut TariPublicKey,
-    amount: c_ulonglong,
-    fee_per_gram: c_ulonglong,
-    message: *const c_char,
+    commitment: *const c_char,
     error_out: *mut c_int,
-) -> c_ulonglong
+) -> bool
 {
     let mut error = 0;
     ptr::swap(error_out, &mut error as *mut c_int);
     if wallet.is_null() {
         error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
         ptr::swap(error_out, &mut error as *mut c_int);
-        return 0;
-    }
-
-    if dest_public_key.is_null() {
-        error = LibWalletError::from(InterfaceError::NullError("dest_public_key".to_string())).code;
-        ptr::swap(error_out, &mut error as *mut c_int);
-        return 0;
+        return false;
     }
 
-    let message_string = if !message.is_null() {
-        CStr::from_ptr(message).to_str().unwrap().to_owned()
-    } else {
-        error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
-        ptr::swap(error_out, &mut error as *mut c_int);
-        CString::new("").unwrap().to_str().unwrap().to_owned()
+    let commitment = match commitments_from_hex(commitment, "commitment", error_out) {
+        Some(mut c) if c.len() == 1 => c.remove(0),
+        Some(_) => {
+            error = LibWalletError::from(InterfaceError::PositionInvalidError).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return false;
+        },
+        None => return false,
     };
 
     match (*wallet)
         .runtime
-        .block_on((*wallet).transaction_service.send_transaction(
-            (*dest_public_key).clone(),
-            MicroTari::from(amount),
-            MicroTari::from(fee_per_gram),
-            message_string,
-        )) {
-        Ok(tx_id) => tx_id,
+        .block_on((*wallet).output_manager_service.remove_watched_output(commitment))
+    {
+        Ok(_) => true,
         Err(e) => {
-            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            error = LibWalletError::from(WalletError::OutputManagerError(e)).code;
             ptr::swap(error_out, &mut error as *mut c_int);
-            0
+            false
         },
     }
 }
@@ -3720,6 +4876,183 @@ pub unsafe extern "C" fn wallet_cancel_pending_transaction(
     }
 }
 
+/// Builds the filter for the transaction history functions. Timestamps of 0 and a negative status do not restrict the
+/// selection.
+fn transaction_history_filter(
+    from_timestamp: c_ulonglong,
+    to_timestamp: c_ulonglong,
+    status: c_int,
+) -> Result<TransactionHistoryFilter, InterfaceError>
+{
+    let mut filter = TransactionHistoryFilter::default();
+    if from_timestamp > 0 {
+        filter.from = Some(
+            history_timestamp(from_timestamp)
+                .ok_or_else(|| InterfaceError::InvalidArgument("from_timestamp".to_string()))?,
+        );
+    }
+    if to_timestamp > 0 {
+        filter.to = Some(
+            history_timestamp(to_timestamp)
+                .ok_or_else(|| InterfaceError::InvalidArgument("to_timestamp".to_string()))?,
+        );
+    }
+    if status >= 0 {
+        let status =
+            TransactionStatus::try_from(status).map_err(|_| InterfaceError::InvalidArgument("status".to_string()))?;
+        filter.statuses.push(status);
+    }
+    Ok(filter)
+}
+
+/// Converts a Unix timestamp to a date and time, or None if it is out of range
+fn history_timestamp(timestamp: c_ulonglong) -> Option<NaiveDateTime> {
+    i64::try_from(timestamp)
+        .ok()
+        .and_then(|t| NaiveDateTime::from_timestamp_opt(t, 0))
+}
+
+fn transaction_history_format(format: c_uint) -> Result<TransactionHistoryFormat, InterfaceError> {
+    match format {
+        0 => Ok(TransactionHistoryFormat::Csv),
+        1 => Ok(TransactionHistoryFormat::Json),
+        _ => Err(InterfaceError::InvalidArgument("format".to_string())),
+    }
+}
+
+/// Exports the wallet's pending, completed and cancelled transactions
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `format` - The format of the export, 0 for CSV and 1 for JSON
+/// `from_timestamp` - Only include transactions at or after this Unix timestamp, 0 to not restrict the start
+/// `to_timestamp` - Only include transactions before this Unix timestamp, 0 to not restrict the end
+/// `status` - Only include transactions with this status, -1 for all statuses. The status values are:
+/// |   Value | Interpretation |
+/// |---|---|
+/// |  0 | Completed  |
+/// |  1 | Broadcast  |
+/// |  2 | Mined      |
+/// |  3 | Imported   |
+/// |  4 | Pending    |
+/// |  5 | Cancelled  |
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array holding the export. Note that it returns an empty char array if
+/// there was an error
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_export_transaction_history(
+    wallet: *mut TariWallet,
+    format: c_uint,
+    from_timestamp: c_ulonglong,
+    to_timestamp: c_ulonglong,
+    status: c_int,
+    error_out: *mut c_int,
+) -> *mut c_char
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    let mut result = CString::new("").unwrap();
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return result.into_raw();
+    }
+
+    let (filter, format) = match transaction_history_filter(from_timestamp, to_timestamp, status)
+        .and_then(|filter| transaction_history_format(format).map(|format| (filter, format)))
+    {
+        Ok(v) => v,
+        Err(e) => {
+            error = LibWalletError::from(e).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return result.into_raw();
+        },
+    };
+
+    match (*wallet).export_transaction_history(filter, format) {
+        Ok(export) => result = CString::new(export).unwrap_or_default(),
+        Err(e) => {
+            error = LibWalletError::from(e).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+        },
+    }
+    result.into_raw()
+}
+
+/// Reports the totals received, sent and paid in fees by the wallet per accounting period. Pending and cancelled
+/// transactions are not included in the totals.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `period` - The accounting period, 0 for daily, 1 for monthly and 2 for yearly totals
+/// `format` - The format of the report, 0 for CSV and 1 for JSON
+/// `from_timestamp` - Only include transactions at or after this Unix timestamp, 0 to not restrict the start
+/// `to_timestamp` - Only include transactions before this Unix timestamp, 0 to not restrict the end
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array holding the report. Note that it returns an empty char array if
+/// there was an error
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_transaction_history_report(
+    wallet: *mut TariWallet,
+    period: c_uint,
+    format: c_uint,
+    from_timestamp: c_ulonglong,
+    to_timestamp: c_ulonglong,
+    error_out: *mut c_int,
+) -> *mut c_char
+{
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    let mut result = CString::new("").unwrap();
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return result.into_raw();
+    }
+
+    let period = match period {
+        0 => AccountingPeriod::Day,
+        1 => AccountingPeriod::Month,
+        2 => AccountingPeriod::Year,
+        _ => {
+            error = LibWalletError::from(InterfaceError::InvalidArgument("period".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return result.into_raw();
+        },
+    };
+    let (filter, format) = match transaction_history_filter(from_timestamp, to_timestamp, -1)
+        .and_then(|filter| transaction_history_format(format).map(|format| (filter, format)))
+    {
+        Ok(v) => v,
+        Err(e) => {
+            error = LibWalletError::from(e).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return result.into_raw();
+        },
+    };
+
+    match (*wallet).export_transaction_history_report(filter, period, format) {
+        Ok(report) => result = CString::new(report).unwrap_or_default(),
+        Err(e) => {
+            error = LibWalletError::from(e).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+        },
+    }
+    result.into_raw()
+}
+
 /// This function will tell the wallet to query the set base node to confirm the status of wallet data. For example this
 /// will check that Unspent Outputs stored in the wallet are still available as UTXO's on the blockchain. This will also
 /// trigger a request for outstanding SAF messages to you neighbours
@@ -4228,6 +5561,60 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_payment_request() {
+        unsafe {
+            let mut error = 0;
+            let error_ptr = &mut error as *mut c_int;
+            let private_key = private_key_generate();
+            let public_key = public_key_from_private_key(private_key, error_ptr);
+            let message = CString::into_raw(CString::new("Dinner").unwrap()) as *const c_char;
+            let request = payment_request_create(public_key, 5000, message, 1_900_000_000, error_ptr);
+            assert_eq!(error, 0);
+            let uri = payment_request_to_uri(request, error_ptr) as *const c_char;
+            assert!(CStr::from_ptr(uri).to_str().unwrap().starts_with("tari:"));
+
+            let parsed = payment_request_from_uri(uri, error_ptr);
+            assert_eq!(error, 0);
+            assert_eq!(payment_request_get_amount(parsed, error_ptr), 5000);
+            assert_eq!(payment_request_get_expiry(parsed, error_ptr), 1_900_000_000);
+            let parsed_message = payment_request_get_message(parsed, error_ptr);
+            assert_eq!(CStr::from_ptr(parsed_message).to_str().unwrap(), "Dinner");
+            let parsed_key = payment_request_get_public_key(parsed, error_ptr);
+            assert_eq!(*parsed_key, *public_key);
+
+            let bad_uri = CString::into_raw(CString::new("tari:nobody?amount=1&checksum=00").unwrap()) as *const c_char;
+            let bad_request = payment_request_from_uri(bad_uri, error_ptr);
+            assert!(bad_request.is_null());
+            assert_eq!(error, LibWalletError::from(PaymentRequestError::InvalidChecksum).code);
+
+            let far_future_request = payment_request_create(public_key, 5000, message, u64::MAX, error_ptr);
+            assert!(far_future_request.is_null());
+            assert_eq!(error, LibWalletError::from(PaymentRequestError::InvalidExpiry).code);
+
+            payment_request_destroy(request);
+            payment_request_destroy(parsed);
+            public_key_destroy(public_key);
+            public_key_destroy(parsed_key);
+            private_key_destroy(private_key);
+            string_destroy(message as *mut c_char);
+            string_destroy(uri as *mut c_char);
+            string_destroy(parsed_message);
+            string_destroy(bad_uri as *mut c_char);
+        }
+    }
+
+    #[test]
+    fn test_transaction_history_filter() {
+        let filter = transaction_history_filter(1_584_273_600, 0, 2).unwrap();
+        assert_eq!(filter.from, Some(NaiveDateTime::from_timestamp(1_584_273_600, 0)));
+        assert!(filter.to.is_none());
+        assert_eq!(filter.statuses, vec![TransactionStatus::Mined]);
+
+        assert!(transaction_history_filter(u64::MAX, 0, -1).is_err());
+        assert!(transaction_history_filter(0, u64::MAX, -1).is_err());
+    }
+
     #[test]
     fn test_wallet_ffi() {
         unsafe {
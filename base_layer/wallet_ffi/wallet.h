@@ -57,6 +57,8 @@ struct TariContacts;
 
 struct TariContact;
 
+struct TariPaymentRequest;
+
 struct TariCompletedTransactions;
 
 struct TariCompletedTransaction;
@@ -157,6 +159,32 @@ struct TariPrivateKey *private_key_from_hex(const char *hex,int* error_out);
 // Frees memory for a TariPrivateKey
 void private_key_destroy(struct TariPrivateKey *pk);
 
+/// -------------------------------- PaymentRequest ------------------------------------------------ ///
+
+// Creates a TariPaymentRequest, expiry is a unix timestamp or 0 if the request does not expire
+struct TariPaymentRequest *payment_request_create(struct TariPublicKey *public_key, unsigned long long amount, const char *message, unsigned long long expiry,int* error_out);
+
+// Parses a tari: payment request URI and validates its checksum
+struct TariPaymentRequest *payment_request_from_uri(const char *uri,int* error_out);
+
+// Encodes a TariPaymentRequest as a tari: URI
+char *payment_request_to_uri(struct TariPaymentRequest *request,int* error_out);
+
+// Gets the TariPublicKey of the recipient of the TariPaymentRequest
+struct TariPublicKey *payment_request_get_public_key(struct TariPaymentRequest *request,int* error_out);
+
+// Gets the amount of the TariPaymentRequest
+unsigned long long payment_request_get_amount(struct TariPaymentRequest *request,int* error_out);
+
+// Gets the message of the TariPaymentRequest
+char *payment_request_get_message(struct TariPaymentRequest *request,int* error_out);
+
+// Gets the expiry of the TariPaymentRequest as a unix timestamp, 0 if it does not expire
+unsigned long long payment_request_get_expiry(struct TariPaymentRequest *request,int* error_out);
+
+// Frees memory for a TariPaymentRequest
+void payment_request_destroy(struct TariPaymentRequest *request);
+
 /// -------------------------------- Contact ------------------------------------------------------ ///
 
 // Creates a TariContact
@@ -190,6 +218,9 @@ struct TariPublicKey *completed_transaction_get_destination_public_key(struct Ta
 // Gets the source TariPublicKey of a TariCompletedTransaction
 struct TariPublicKey *completed_transaction_get_source_public_key(struct TariCompletedTransaction *transaction,int* error_out);
 
+// Gets the number of confirmations of a TariCompletedTransaction
+unsigned long long completed_transaction_get_confirmations(struct TariCompletedTransaction *transaction,int* error_out);
+
 // Gets the amount of a TariCompletedTransaction
 unsigned long long completed_transaction_get_amount(struct TariCompletedTransaction *transaction,int* error_out);
 
@@ -346,6 +377,20 @@ struct TariWallet *wallet_create(struct TariWalletConfig *config,
                                     void (*callback_base_node_sync_complete)(unsigned long long, bool),
                                     int* error_out);
 
+// Creates a watch-only TariWallet that holds no spending keys and refuses to spend
+struct TariWallet *wallet_create_watch_only(struct TariWalletConfig *config,
+                                               const char *log_path,
+                                               void (*callback_received_transaction)(struct TariPendingInboundTransaction*),
+                                               void (*callback_received_transaction_reply)(struct TariCompletedTransaction*),
+                                               void (*callback_received_finalized_transaction)(struct TariCompletedTransaction*),
+                                               void (*callback_transaction_broadcast)(struct TariCompletedTransaction*),
+                                               void (*callback_transaction_mined)(struct TariCompletedTransaction*),
+                                               void (*callback_direct_send_result)(unsigned long long, bool),
+                                               void (*callback_store_and_forward_send_result)(unsigned long long, bool),
+                                               void (*callback_transaction_cancellation)(unsigned long long),
+                                               void (*callback_base_node_sync_complete)(unsigned long long, bool),
+                                               int* error_out);
+
 // Signs a message
 char* wallet_sign_message(struct TariWallet *wallet, const char* msg, int* error_out);
 
@@ -355,9 +400,12 @@ bool wallet_verify_message_signature(struct TariWallet *wallet, struct TariPubli
 /// Generates test data
 bool wallet_test_generate_data(struct TariWallet *wallet, const char *datastore_path,int* error_out);
 
-// Adds a base node peer to the TariWallet
+// Adds a base node peer to the end of the TariWallet's list of base nodes
 bool wallet_add_base_node_peer(struct TariWallet *wallet, struct TariPublicKey *public_key, const char *address,int* error_out);
 
+// Removes a base node peer from the TariWallet's list of base nodes
+bool wallet_remove_base_node_peer(struct TariWallet *wallet, struct TariPublicKey *public_key,int* error_out);
+
 // Upserts a TariContact to the TariWallet, if the contact does not exist it is inserted and if it does the alias is updated
 bool wallet_upsert_contact(struct TariWallet *wallet, struct TariContact *contact,int* error_out);
 
@@ -373,9 +421,42 @@ unsigned long long wallet_get_pending_incoming_balance(struct TariWallet *wallet
 // Gets the outgoing balance from a TariWallet
 unsigned long long wallet_get_pending_outgoing_balance(struct TariWallet *wallet,int* error_out);
 
+// Gets the part of the available balance that is time-locked at the current chain tip from a TariWallet
+unsigned long long wallet_get_time_locked_balance(struct TariWallet *wallet,int* error_out);
+
 // Sends a TariPendingOutboundTransaction
 unsigned long long wallet_send_transaction(struct TariWallet *wallet, struct TariPublicKey *destination, unsigned long long amount, unsigned long long fee_per_gram,const char *message,int* error_out);
 
+// Sends a TariPendingOutboundTransaction with a lock height and a maturity for the recipient's output
+unsigned long long wallet_send_time_locked_transaction(struct TariWallet *wallet, struct TariPublicKey *destination, unsigned long long amount, unsigned long long fee_per_gram, unsigned long long lock_height, unsigned long long maturity,const char *message,int* error_out);
+
+// Pays a TariPaymentRequest, expired requests are rejected
+unsigned long long wallet_send_payment_request(struct TariWallet *wallet, struct TariPaymentRequest *request, unsigned long long fee_per_gram,int* error_out);
+
+// Sends a one-sided transaction that does not require the recipient to be online
+unsigned long long wallet_send_one_sided_transaction(struct TariWallet *wallet, struct TariPublicKey *destination, unsigned long long amount, unsigned long long fee_per_gram,const char *message,int* error_out);
+
+// Sends a TariPendingOutboundTransaction that spends exactly the UTXOs given as comma separated hex commitments
+unsigned long long wallet_send_transaction_with_inputs(struct TariWallet *wallet, struct TariPublicKey *destination, unsigned long long amount, unsigned long long fee_per_gram,const char *message,const char *inputs,int* error_out);
+
+// Freezes or unfreezes the UTXO with the hex commitment, frozen UTXOs are never selected automatically
+bool wallet_set_output_frozen(struct TariWallet *wallet, const char *commitment, bool frozen, int* error_out);
+
+// Returns whether the UTXO with the hex commitment is frozen
+bool wallet_is_output_frozen(struct TariWallet *wallet, const char *commitment, int* error_out);
+
+// Sets the label of the UTXO with the hex commitment, a null label clears it
+bool wallet_set_output_label(struct TariWallet *wallet, const char *commitment, const char *label, int* error_out);
+
+// Gets the label of the UTXO with the hex commitment, empty if it has none
+char *wallet_get_output_label(struct TariWallet *wallet, const char *commitment, int* error_out);
+
+// Starts tracking the UTXO with the hex commitment and the given value and maturity in a watch-only wallet
+bool wallet_add_watched_output(struct TariWallet *wallet, const char *commitment, unsigned long long amount, unsigned long long maturity, const char *label, int* error_out);
+
+// Stops tracking the watched UTXO with the hex commitment
+bool wallet_remove_watched_output(struct TariWallet *wallet, const char *commitment, int* error_out);
+
 // Get the TariContacts from a TariWallet
 struct TariContacts *wallet_get_contacts(struct TariWallet *wallet,int* error_out);
 
@@ -411,6 +492,14 @@ bool wallet_is_completed_transaction_outbound(struct TariWallet *wallet, struct
 // event.
 unsigned long long wallet_import_utxo(struct TariWallet *wallet, unsigned long long amount, struct TariPrivateKey *spending_key, struct TariPublicKey *source_public_key, const char *message, int* error_out);
 
+// Exports the wallet's transactions as CSV (format 0) or JSON (format 1). Timestamps of 0 and a status of -1 do not
+// restrict the selection.
+char *wallet_export_transaction_history(struct TariWallet *wallet, unsigned int format, unsigned long long from_timestamp, unsigned long long to_timestamp, int status, int* error_out);
+
+// Reports the totals in, out and fees per day (period 0), month (period 1) or year (period 2) as CSV (format 0) or
+// JSON (format 1)
+char *wallet_get_transaction_history_report(struct TariWallet *wallet, unsigned int period, unsigned int format, unsigned long long from_timestamp, unsigned long long to_timestamp, int* error_out);
+
 // This function will tell the wallet to query the set base node to confirm the status of wallet data.
 unsigned long long wallet_sync_with_base_node(struct TariWallet *wallet, int* error_out);
 
@@ -42,6 +42,7 @@ pub struct GlobalConfig {
     pub comms_transport: CommsTransport,
     pub listnener_liveness_max_sessions: usize,
     pub listener_liveness_whitelist_cidrs: Vec<String>,
+    pub dht_pow_difficulty: u32,
     pub data_dir: PathBuf,
     pub db_type: DatabaseType,
     pub orphan_storage_capacity: usize,
@@ -52,6 +53,8 @@ pub struct GlobalConfig {
     pub public_address: Multiaddr,
     pub grpc_enabled: bool,
     pub grpc_address: SocketAddr,
+    pub metrics_enabled: bool,
+    pub metrics_address: SocketAddr,
     pub peer_seeds: Vec<String>,
     pub peer_db_path: PathBuf,
     pub block_sync_strategy: String,
@@ -62,6 +65,11 @@ pub struct GlobalConfig {
     pub wallet_identity_file: PathBuf,
     pub wallet_tor_identity_file: PathBuf,
     pub wallet_peer_db_path: PathBuf,
+    pub console_wallet_db_file: PathBuf,
+    pub console_wallet_identity_file: PathBuf,
+    pub console_wallet_tor_identity_file: PathBuf,
+    pub console_wallet_peer_db_path: PathBuf,
+    pub console_wallet_base_nodes: Vec<String>,
 }
 
 impl GlobalConfig {
@@ -144,6 +152,19 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
         .map_err(|e| ConfigurationError::new(&key, &e.to_string()))?
         .into();
 
+    // Console wallet identity paths
+    let key = config_string(&net_str, "console_wallet_identity_file");
+    let console_wallet_identity_file = cfg
+        .get_str(&key)
+        .map_err(|e| ConfigurationError::new(&key, &e.to_string()))?
+        .into();
+
+    let key = config_string(&net_str, "console_wallet_tor_identity_file");
+    let console_wallet_tor_identity_file = cfg
+        .get_str(&key)
+        .map_err(|e| ConfigurationError::new(&key, &e.to_string()))?
+        .into();
+
     // Tor private key persistence
     let key = config_string(&net_str, "tor_identity_file");
     let tor_identity_file = cfg
@@ -179,6 +200,21 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
                 .map_err(|e| ConfigurationError::new(&key, &e.to_string()))
         })?;
 
+    // Metrics
+    let key = config_string(&net_str, "metrics_enabled");
+    let metrics_enabled = cfg
+        .get_bool(&key)
+        .map_err(|e| ConfigurationError::new(&key, &e.to_string()))? as bool;
+
+    let key = config_string(&net_str, "metrics_address");
+    let metrics_address = cfg
+        .get_str(&key)
+        .map_err(|e| ConfigurationError::new(&key, &e.to_string()))
+        .and_then(|addr| {
+            addr.parse::<SocketAddr>()
+                .map_err(|e| ConfigurationError::new(&key, &e.to_string()))
+        })?;
+
     // Peer seeds
     let key = config_string(&net_str, "peer_seeds");
     let peer_seeds = cfg
@@ -189,6 +225,7 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
     // Peer DB path
     let peer_db_path = data_dir.join("peer_db");
     let wallet_peer_db_path = data_dir.join("wallet_peer_db");
+    let console_wallet_peer_db_path = data_dir.join("console_wallet_peer_db");
 
     let key = config_string(&net_str, "block_sync_strategy");
     let block_sync_strategy = cfg
@@ -213,6 +250,19 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
         .map_err(|e| ConfigurationError::new(&key, &e.to_string()))?
         .into();
 
+    // set console wallet_file
+    let key = "wallet.console_wallet_file".to_string();
+    let console_wallet_db_file = cfg
+        .get_str(&key)
+        .map_err(|e| ConfigurationError::new(&key, &e.to_string()))?
+        .into();
+
+    let key = "wallet.base_nodes";
+    let console_wallet_base_nodes = cfg
+        .get_array(key)
+        .map(|values| values.into_iter().filter_map(|v| v.into_str().ok()).collect())
+        .unwrap_or_default();
+
     let key = "common.liveness_max_sessions";
     let liveness_max_sessions = cfg
         .get_int(key)
@@ -226,11 +276,19 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
         .map(|values| values.iter().map(ToString::to_string).collect())
         .unwrap_or_else(|_| vec!["127.0.0.1/32".to_string()]);
 
+    let key = "common.dht_pow_difficulty";
+    let dht_pow_difficulty = cfg
+        .get_int(key)
+        .map_err(|e| ConfigurationError::new(key, &e.to_string()))?
+        .try_into()
+        .map_err(|e: TryFromIntError| ConfigurationError::new(&key, &e.to_string()))?;
+
     Ok(GlobalConfig {
         network,
         comms_transport,
         listnener_liveness_max_sessions: liveness_max_sessions,
         listener_liveness_whitelist_cidrs: liveness_whitelist_cidrs,
+        dht_pow_difficulty,
         data_dir,
         db_type,
         orphan_storage_capacity,
@@ -241,6 +299,8 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
         public_address,
         grpc_enabled,
         grpc_address,
+        metrics_enabled,
+        metrics_address,
         peer_seeds,
         peer_db_path,
         block_sync_strategy,
@@ -251,6 +311,11 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
         wallet_db_file,
         wallet_tor_identity_file,
         wallet_peer_db_path,
+        console_wallet_db_file,
+        console_wallet_identity_file,
+        console_wallet_tor_identity_file,
+        console_wallet_peer_db_path,
+        console_wallet_base_nodes,
     })
 }
 
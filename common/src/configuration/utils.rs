@@ -52,6 +52,7 @@ pub fn default_config(bootstrap: &ConfigBootstrap) -> Config {
     cfg.set_default("common.message_cache_ttl", 1440).unwrap();
     cfg.set_default("common.peer_whitelist", Vec::<String>::new()).unwrap();
     cfg.set_default("common.liveness_max_sessions", 0).unwrap();
+    cfg.set_default("common.dht_pow_difficulty", 0).unwrap();
     cfg.set_default(
         "common.peer_database ",
         default_subdir("peers", Some(&bootstrap.base_path)),
@@ -67,6 +68,12 @@ pub fn default_config(bootstrap: &ConfigBootstrap) -> Config {
         default_subdir("wallet/wallet.dat", Some(&bootstrap.base_path)),
     )
     .unwrap();
+    cfg.set_default(
+        "wallet.console_wallet_file",
+        default_subdir("console_wallet/console_wallet.dat", Some(&bootstrap.base_path)),
+    )
+    .unwrap();
+    cfg.set_default("wallet.base_nodes", Vec::<String>::new()).unwrap();
 
     //---------------------------------- Mainnet Defaults --------------------------------------------//
 
@@ -108,6 +115,16 @@ pub fn default_config(bootstrap: &ConfigBootstrap) -> Config {
         default_subdir("mainnet/wallet-tor.json", Some(&bootstrap.base_path)),
     )
     .unwrap();
+    cfg.set_default(
+        "base_node.mainnet.console_wallet_identity_file",
+        default_subdir("mainnet/console-wallet-identity.json", Some(&bootstrap.base_path)),
+    )
+    .unwrap();
+    cfg.set_default(
+        "base_node.mainnet.console_wallet_tor_identity_file",
+        default_subdir("mainnet/console-wallet-tor.json", Some(&bootstrap.base_path)),
+    )
+    .unwrap();
     cfg.set_default(
         "base_node.mainnet.public_address",
         format!("{}/tcp/18041", local_ip_addr),
@@ -116,6 +133,9 @@ pub fn default_config(bootstrap: &ConfigBootstrap) -> Config {
     cfg.set_default("base_node.mainnet.grpc_enabled", false).unwrap();
     cfg.set_default("base_node.mainnet.grpc_address", "127.0.0.1:18042")
         .unwrap();
+    cfg.set_default("base_node.mainnet.metrics_enabled", false).unwrap();
+    cfg.set_default("base_node.mainnet.metrics_address", "127.0.0.1:18043")
+        .unwrap();
     cfg.set_default("base_node.mainnet.enable_mining", false).unwrap();
     cfg.set_default("base_node.mainnet.num_mining_threads", 1).unwrap();
 
@@ -151,6 +171,16 @@ pub fn default_config(bootstrap: &ConfigBootstrap) -> Config {
         default_subdir("rincewind/wallet-tor.json", Some(&bootstrap.base_path)),
     )
     .unwrap();
+    cfg.set_default(
+        "base_node.rincewind.console_wallet_identity_file",
+        default_subdir("rincewind/console-wallet-identity.json", Some(&bootstrap.base_path)),
+    )
+    .unwrap();
+    cfg.set_default(
+        "base_node.rincewind.console_wallet_tor_identity_file",
+        default_subdir("rincewind/console-wallet-tor.json", Some(&bootstrap.base_path)),
+    )
+    .unwrap();
     cfg.set_default(
         "base_node.rincewind.identity_file",
         default_subdir("rincewind/node_id.json", Some(&bootstrap.base_path)),
@@ -165,6 +195,9 @@ pub fn default_config(bootstrap: &ConfigBootstrap) -> Config {
     cfg.set_default("base_node.rincewind.grpc_enabled", false).unwrap();
     cfg.set_default("base_node.rincewind.grpc_address", "127.0.0.1:18142")
         .unwrap();
+    cfg.set_default("base_node.rincewind.metrics_enabled", false).unwrap();
+    cfg.set_default("base_node.rincewind.metrics_address", "127.0.0.1:18144")
+        .unwrap();
     cfg.set_default("base_node.rincewind.enable_mining", false).unwrap();
     cfg.set_default("base_node.rincewind.num_mining_threads", 1).unwrap();
 
@@ -0,0 +1,119 @@
+// Copyright 2019. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::rngs::OsRng;
+use std::{sync::Arc, time::Duration};
+use tari_comms::{
+    net_address::MultiaddressesWithStats,
+    peer_manager::{NodeId, Peer, PeerFeatures, PeerFlags, PeerId, PeerQuery, PeerQuerySortBy, PeerStorage},
+};
+use tari_crypto::{keys::PublicKey, ristretto::RistrettoPublicKey};
+use tari_storage::{HashmapDatabase, IterationResult};
+
+const NUM_CLOSEST: usize = 8;
+
+fn create_peer_storage(n: usize) -> PeerStorage<HashmapDatabase<PeerId, Peer>> {
+    let mut storage = PeerStorage::new_indexed(HashmapDatabase::new()).unwrap();
+    for _ in 0..n {
+        let (_sk, pk) = RistrettoPublicKey::random_keypair(&mut OsRng);
+        let node_id = NodeId::from_key(&pk).unwrap();
+        let peer = Peer::new(
+            pk,
+            node_id,
+            MultiaddressesWithStats::from(
+                "/ip4/1.2.3.4/tcp/8000"
+                    .parse::<tari_comms::multiaddr::Multiaddr>()
+                    .unwrap(),
+            ),
+            PeerFlags::empty(),
+            PeerFeatures::COMMUNICATION_NODE,
+            &[],
+        );
+        storage.add_peer(peer).unwrap();
+    }
+    storage
+}
+
+fn random_node_id() -> NodeId {
+    let (_sk, pk) = RistrettoPublicKey::random_keypair(&mut OsRng);
+    NodeId::from_key(&pk).unwrap()
+}
+
+/// Selects the closest peers by loading and sorting every peer, as peer selection did before the routing table
+fn closest_full_scan(storage: &PeerStorage<HashmapDatabase<PeerId, Peer>>, node_id: &NodeId) -> Vec<Peer> {
+    let mut peers = Vec::new();
+    storage
+        .for_each(|peer| {
+            if !peer.is_banned() {
+                peers.push((node_id.distance(&peer.node_id), peer));
+            }
+            IterationResult::Continue
+        })
+        .unwrap();
+    peers.sort_by(|(a, _), (b, _)| a.cmp(b));
+    peers.into_iter().take(NUM_CLOSEST).map(|(_, peer)| peer).collect()
+}
+
+fn closest_routing_table(storage: &PeerStorage<HashmapDatabase<PeerId, Peer>>, node_id: &NodeId) -> Vec<Peer> {
+    let query = PeerQuery::new()
+        .select_where(|peer| !peer.is_banned())
+        .sort_by(PeerQuerySortBy::DistanceFrom(node_id))
+        .limit(NUM_CLOSEST);
+    storage.perform_query(query).unwrap()
+}
+
+fn closest_peers(c: &mut Criterion) {
+    for &n in &[1_000, 10_000, 50_000] {
+        let storage = create_peer_storage(n);
+        let node_id = random_node_id();
+        assert_eq!(
+            closest_full_scan(&storage, &node_id),
+            closest_routing_table(&storage, &node_id)
+        );
+
+        let storage = Arc::new(storage);
+        c.bench_function(&format!("closest {} of {} peers (full scan)", NUM_CLOSEST, n), {
+            let storage = storage.clone();
+            move |b| {
+                let node_id = random_node_id();
+                b.iter(|| closest_full_scan(&storage, &node_id))
+            }
+        });
+        c.bench_function(
+            &format!("closest {} of {} peers (routing table)", NUM_CLOSEST, n),
+            move |b| {
+                let node_id = random_node_id();
+                b.iter(|| closest_routing_table(&storage, &node_id))
+            },
+        );
+    }
+}
+
+criterion_group!(
+    name = routing_table;
+    config = Criterion::default().warm_up_time(Duration::from_millis(500)).sample_size(10);
+    targets = closest_peers
+);
+
+criterion_main!(routing_table);
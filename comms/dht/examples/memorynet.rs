@@ -548,6 +548,14 @@ fn connection_manager_logger(
                     node_name, err
                 );
             },
+            PeerEvicted(node_id, direction) => {
+                println!(
+                    "'{}' evicted {} connection to '{}'",
+                    node_name,
+                    direction,
+                    get_name(node_id),
+                );
+            },
             Listening(_) | ListenFailed(_) => unreachable!(),
             NewInboundSubstream(node_id, protocol, _) => {
                 println!(
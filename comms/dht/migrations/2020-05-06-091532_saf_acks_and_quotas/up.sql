@@ -0,0 +1,6 @@
+ALTER TABLE stored_messages ADD COLUMN body_hash TEXT NOT NULL DEFAULT '';
+ALTER TABLE stored_messages ADD COLUMN source_pubkey TEXT NOT NULL DEFAULT '';
+
+CREATE INDEX idx_stored_messages_body_hash ON stored_messages (body_hash);
+CREATE INDEX idx_stored_messages_origin_pubkey ON stored_messages (origin_pubkey);
+CREATE INDEX idx_stored_messages_source_pubkey ON stored_messages (source_pubkey);
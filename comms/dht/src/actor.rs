@@ -22,14 +22,15 @@
 
 //! Actor for DHT functionality.
 //!
-//! The DhtActor is responsible for sending a join request on startup
-//! and furnishing [DhtRequest]s.
+//! The DhtActor is responsible for sending a join request on startup,
+//! furnishing [DhtRequest]s and periodically maintaining this node's neighbourhood.
 //!
 //! [DhtRequest]: ./enum.DhtRequest.html
 
 use crate::{
     broadcast_strategy::BroadcastStrategy,
-    discovery::DhtDiscoveryError,
+    consts::MAX_FAILED_ATTEMPTS_MARK_PEER_OFFLINE,
+    discovery::{DhtDiscoveryError, DhtDiscoveryRequester},
     outbound::{OutboundMessageRequester, SendMessageParams},
     proto::{dht::JoinMessage, envelope::DhtMessageType},
     storage::{DbConnection, DhtDatabase, DhtMetadataKey, StorageError},
@@ -46,8 +47,17 @@ use futures::{
     StreamExt,
 };
 use log::*;
-use std::{fmt, fmt::Display, sync::Arc};
+use rand::{rngs::OsRng, RngCore};
+use std::{
+    fmt,
+    fmt::Display,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 use tari_comms::{
+    connection_manager::{ConnectionManagerError, ConnectionManagerRequester},
     peer_manager::{
         node_id::NodeDistance,
         NodeId,
@@ -62,8 +72,11 @@ use tari_comms::{
     types::CommsPublicKey,
 };
 use tari_shutdown::ShutdownSignal;
-use tari_utilities::message_format::{MessageFormat, MessageFormatError};
-use tokio::task;
+use tari_utilities::{
+    message_format::{MessageFormat, MessageFormatError},
+    ByteArray,
+};
+use tokio::{task, time};
 use ttl_cache::TtlCache;
 
 const LOG_TARGET: &str = "comms::dht::actor";
@@ -77,6 +90,7 @@ pub enum DhtActorError {
     /// Reply sender canceled the request
     ReplyCanceled,
     PeerManagerError(PeerManagerError),
+    ConnectionManagerError(ConnectionManagerError),
     #[error(msg_embedded, no_from, non_std)]
     SendFailed(String),
     DiscoveryError(DhtDiscoveryError),
@@ -180,11 +194,14 @@ pub struct DhtActor<'a> {
     peer_manager: Arc<PeerManager>,
     database: DhtDatabase,
     outbound_requester: OutboundMessageRequester,
+    connection_manager: ConnectionManagerRequester,
+    discovery: DhtDiscoveryRequester,
     config: DhtConfig,
     shutdown_signal: Option<ShutdownSignal>,
     request_rx: Fuse<mpsc::Receiver<DhtRequest>>,
     msg_hash_cache: TtlCache<Vec<u8>, ()>,
     pending_jobs: FuturesUnordered<BoxFuture<'a, Result<(), DhtActorError>>>,
+    is_maintenance_running: Arc<AtomicBool>,
 }
 
 impl DhtActor<'static> {
@@ -195,12 +212,15 @@ impl DhtActor<'static> {
 }
 
 impl<'a> DhtActor<'a> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         config: DhtConfig,
         conn: DbConnection,
         node_identity: Arc<NodeIdentity>,
         peer_manager: Arc<PeerManager>,
         outbound_requester: OutboundMessageRequester,
+        connection_manager: ConnectionManagerRequester,
+        discovery: DhtDiscoveryRequester,
         request_rx: mpsc::Receiver<DhtRequest>,
         shutdown_signal: ShutdownSignal,
     ) -> Self
@@ -210,11 +230,14 @@ impl<'a> DhtActor<'a> {
             config,
             database: DhtDatabase::new(conn),
             outbound_requester,
+            connection_manager,
+            discovery,
             peer_manager,
             node_identity,
             shutdown_signal: Some(shutdown_signal),
             request_rx: request_rx.fuse(),
             pending_jobs: FuturesUnordered::new(),
+            is_maintenance_running: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -238,6 +261,11 @@ impl<'a> DhtActor<'a> {
             .take()
             .expect("DhtActor initialized without shutdown_signal");
 
+        // The first maintenance round is delayed by one interval to give the node a chance to connect and join
+        let maintenance_interval = self.config.neighbourhood_maintenance_interval;
+        let mut maintenance_ticker =
+            time::interval_at(time::Instant::now() + maintenance_interval, maintenance_interval).fuse();
+
         loop {
             futures::select! {
                 request = self.request_rx.select_next_some() => {
@@ -246,6 +274,12 @@ impl<'a> DhtActor<'a> {
                     self.pending_jobs.push(handler);
                 },
 
+                _ = maintenance_ticker.select_next_some() => {
+                    if let Some(job) = self.neighbourhood_maintenance() {
+                        self.pending_jobs.push(job);
+                    }
+                },
+
                 result = self.pending_jobs.select_next_some() => {
                     match result {
                         Ok(_) => {
@@ -282,9 +316,11 @@ impl<'a> DhtActor<'a> {
             SendJoin => {
                 let node_identity = Arc::clone(&self.node_identity);
                 let outbound_requester = self.outbound_requester.clone();
+                let db = self.database.clone();
                 Box::pin(Self::send_join(
                     node_identity,
                     outbound_requester,
+                    db,
                     self.config.num_neighbouring_nodes,
                 ))
             },
@@ -336,9 +372,37 @@ impl<'a> DhtActor<'a> {
         }
     }
 
+    /// Returns the neighbourhood maintenance job, or None if the previous maintenance round has not completed yet.
+    fn neighbourhood_maintenance(&mut self) -> Option<BoxFuture<'a, Result<(), DhtActorError>>> {
+        if self.is_maintenance_running.swap(true, Ordering::SeqCst) {
+            debug!(
+                target: LOG_TARGET,
+                "Skipping neighbourhood maintenance because the previous round is still running"
+            );
+            return None;
+        }
+
+        let mut maintenance = NeighbourhoodMaintenance {
+            config: self.config.clone(),
+            node_identity: Arc::clone(&self.node_identity),
+            peer_manager: Arc::clone(&self.peer_manager),
+            database: self.database.clone(),
+            outbound_requester: self.outbound_requester.clone(),
+            connection_manager: self.connection_manager.clone(),
+            discovery: self.discovery.clone(),
+        };
+        let is_maintenance_running = Arc::clone(&self.is_maintenance_running);
+        Some(Box::pin(async move {
+            let result = maintenance.run().await;
+            is_maintenance_running.store(false, Ordering::SeqCst);
+            result
+        }))
+    }
+
     async fn send_join(
         node_identity: Arc<NodeIdentity>,
         mut outbound_requester: OutboundMessageRequester,
+        db: DhtDatabase,
         num_neighbouring_nodes: usize,
     ) -> Result<(), DhtActorError>
     {
@@ -361,6 +425,9 @@ impl<'a> DhtActor<'a> {
             .await
             .map_err(|err| DhtActorError::SendFailed(format!("Failed to send join message: {}", err)))?;
 
+        db.set_metadata_value(DhtMetadataKey::JoinAddress, node_identity.public_address().to_string())
+            .await?;
+
         Ok(())
     }
 
@@ -408,7 +475,6 @@ impl<'a> DhtActor<'a> {
                 // Send to a random set of peers of size n that are Communication Nodes
                 peer_manager.random_peers(n, excluded).await.map_err(Into::into)
             },
-            // TODO: This is a common and expensive search - values here should be cached
             Neighbours(exclude, include_all_communication_clients) => {
                 // Send to a random set of peers of size n that are Communication Nodes
                 let mut candidates = Self::select_closest_peers_for_propagation(
@@ -497,7 +563,8 @@ impl<'a> DhtActor<'a> {
         features: PeerFeatures,
     ) -> Result<Vec<Peer>, DhtActorError>
     {
-        // TODO: This query is expensive. We can probably cache a list of neighbouring peers which are online
+        // Distance sorted queries are served by the peer manager's routing table, so only peers in the buckets closest
+        // to node_id are loaded and checked.
         // Fetch to all n nearest neighbour Communication Nodes
         // which are eligible for connection.
         // Currently that means:
@@ -582,17 +649,159 @@ impl<'a> DhtActor<'a> {
     }
 }
 
+/// A single round of neighbourhood maintenance, run periodically by the DhtActor.
+struct NeighbourhoodMaintenance {
+    config: DhtConfig,
+    node_identity: Arc<NodeIdentity>,
+    peer_manager: Arc<PeerManager>,
+    database: DhtDatabase,
+    outbound_requester: OutboundMessageRequester,
+    connection_manager: ConnectionManagerRequester,
+    discovery: DhtDiscoveryRequester,
+}
+
+impl NeighbourhoodMaintenance {
+    async fn run(&mut self) -> Result<(), DhtActorError> {
+        self.connect_to_neighbours().await?;
+        self.refresh_random_buckets().await;
+        self.rejoin_if_address_changed().await?;
+        Ok(())
+    }
+
+    /// Dial the closest `num_neighbouring_nodes` communication nodes which this node is not connected to
+    async fn connect_to_neighbours(&mut self) -> Result<(), DhtActorError> {
+        let neighbours = self
+            .peer_manager
+            .closest_peers(
+                self.node_identity.node_id(),
+                self.config.num_neighbouring_nodes,
+                &[],
+                Some(PeerFeatures::COMMUNICATION_NODE),
+            )
+            .await?;
+
+        for peer in neighbours {
+            if self
+                .connection_manager
+                .get_active_connection(peer.node_id.clone())
+                .await?
+                .is_some()
+            {
+                continue;
+            }
+
+            debug!(
+                target: LOG_TARGET,
+                "Dialing neighbouring peer '{}' because this node is not connected to it",
+                peer.node_id.short_str()
+            );
+            if let Err(err) = self.connection_manager.dial_peer(peer.node_id.clone()).await {
+                debug!(
+                    target: LOG_TARGET,
+                    "Failed to connect to neighbouring peer '{}': {:?}",
+                    peer.node_id.short_str(),
+                    err
+                );
+                self.mark_offline_if_unreachable(&peer.node_id).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Mark the peer as offline if this node has failed to connect to it too many times. Peers are only marked offline
+    /// if this node has at least one active connection, otherwise this node may be the one that is offline.
+    async fn mark_offline_if_unreachable(&mut self, node_id: &NodeId) -> Result<(), DhtActorError> {
+        let peer = self.peer_manager.find_by_node_id(node_id).await?;
+        if peer.connection_stats.failed_attempts() <= MAX_FAILED_ATTEMPTS_MARK_PEER_OFFLINE {
+            return Ok(());
+        }
+
+        if self.connection_manager.get_num_active_connections().await? == 0 {
+            info!(
+                target: LOG_TARGET,
+                "Not marking peer '{}' as offline because this node has no active connections",
+                node_id.short_str()
+            );
+            return Ok(());
+        }
+
+        debug!(
+            target: LOG_TARGET,
+            "Marking peer '{}' as offline because this node failed to connect to them {} times",
+            node_id.short_str(),
+            peer.connection_stats.failed_attempts()
+        );
+        self.peer_manager.set_offline(&peer.public_key, true).await?;
+
+        Ok(())
+    }
+
+    /// Look up random node ids to discover peers in parts of the network this node does not know much about
+    async fn refresh_random_buckets(&mut self) {
+        let node_id_len = self.node_identity.node_id().as_bytes().len();
+        for _ in 0..self.config.num_random_refresh_lookups {
+            let mut bytes = vec![0u8; node_id_len];
+            OsRng.fill_bytes(&mut bytes);
+            let node_id = NodeId::from_bytes(&bytes).expect("random bytes are the size of a NodeId");
+            debug!(target: LOG_TARGET, "Refreshing bucket for random node id '{}'", node_id);
+            // It is expected that the random node id does not exist, peers discovered during the lookup are added
+            // to the peer manager once they have been dialed and authenticated
+            match self.discovery.lookup_node(node_id).await {
+                Ok(peer) => {
+                    trace!(target: LOG_TARGET, "Random lookup found peer '{}'", peer.node_id);
+                },
+                Err(DhtDiscoveryError::LookupPeerNotFound) => {},
+                Err(err) => {
+                    debug!(target: LOG_TARGET, "Random lookup failed: {:?}", err);
+                },
+            }
+        }
+    }
+
+    /// Re-send a join message if this node's public address differs from the address in the last join message. If
+    /// this node has not sent a join message yet, nothing is sent.
+    async fn rejoin_if_address_changed(&mut self) -> Result<(), DhtActorError> {
+        let current_address = self.node_identity.public_address().to_string();
+        let join_address = self
+            .database
+            .get_metadata_value::<String>(DhtMetadataKey::JoinAddress)
+            .await?;
+
+        match join_address {
+            Some(join_address) if join_address != current_address => {
+                info!(
+                    target: LOG_TARGET,
+                    "Public address changed from '{}' to '{}'. Re-sending join message.", join_address, current_address
+                );
+                DhtActor::send_join(
+                    Arc::clone(&self.node_identity),
+                    self.outbound_requester.clone(),
+                    self.database.clone(),
+                    self.config.num_neighbouring_nodes,
+                )
+                .await
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::{
         broadcast_strategy::BroadcastClosestRequest,
-        test_utils::{make_node_identity, make_peer_manager},
+        outbound::mock::create_outbound_service_mock,
+        test_utils::{create_dht_discovery_mock, make_node_identity, make_peer_manager, DhtDiscoveryMockState},
     };
     use chrono::{DateTime, Utc};
+    use std::time::Duration;
     use tari_comms::{
+        multiaddr::Multiaddr,
         net_address::MultiaddressesWithStats,
         peer_manager::{PeerFeatures, PeerFlags},
+        test_utils::mocks::{create_connection_manager_mock, create_peer_connection_mock_pair},
     };
     use tari_shutdown::Shutdown;
     use tari_test_utils::random;
@@ -611,6 +820,8 @@ mod test {
         let (actor_tx, actor_rx) = mpsc::channel(1);
         let mut requester = DhtRequester::new(actor_tx);
         let outbound_requester = OutboundMessageRequester::new(out_tx);
+        let (connection_manager, _) = create_connection_manager_mock(1);
+        let (discovery, _) = create_dht_discovery_mock(1, Duration::from_secs(10));
         let shutdown = Shutdown::new();
         let actor = DhtActor::new(
             Default::default(),
@@ -618,6 +829,8 @@ mod test {
             node_identity,
             peer_manager,
             outbound_requester,
+            connection_manager,
+            discovery,
             actor_rx,
             shutdown.to_signal(),
         );
@@ -637,6 +850,8 @@ mod test {
         let (actor_tx, actor_rx) = mpsc::channel(1);
         let mut requester = DhtRequester::new(actor_tx);
         let outbound_requester = OutboundMessageRequester::new(out_tx);
+        let (connection_manager, _) = create_connection_manager_mock(1);
+        let (discovery, _) = create_dht_discovery_mock(1, Duration::from_secs(10));
         let shutdown = Shutdown::new();
         let actor = DhtActor::new(
             Default::default(),
@@ -644,6 +859,8 @@ mod test {
             node_identity,
             peer_manager,
             outbound_requester,
+            connection_manager,
+            discovery,
             actor_rx,
             shutdown.to_signal(),
         );
@@ -693,6 +910,8 @@ mod test {
         let (actor_tx, actor_rx) = mpsc::channel(1);
         let mut requester = DhtRequester::new(actor_tx);
         let outbound_requester = OutboundMessageRequester::new(out_tx);
+        let (connection_manager, _) = create_connection_manager_mock(1);
+        let (discovery, _) = create_dht_discovery_mock(1, Duration::from_secs(10));
         let shutdown = Shutdown::new();
         let actor = DhtActor::new(
             Default::default(),
@@ -700,6 +919,8 @@ mod test {
             Arc::clone(&node_identity),
             peer_manager,
             outbound_requester,
+            connection_manager,
+            discovery,
             actor_rx,
             shutdown.to_signal(),
         );
@@ -749,6 +970,8 @@ mod test {
         let (actor_tx, actor_rx) = mpsc::channel(1);
         let mut requester = DhtRequester::new(actor_tx);
         let outbound_requester = OutboundMessageRequester::new(out_tx);
+        let (connection_manager, _) = create_connection_manager_mock(1);
+        let (discovery, _) = create_dht_discovery_mock(1, Duration::from_secs(10));
         let mut shutdown = Shutdown::new();
         let actor = DhtActor::new(
             Default::default(),
@@ -756,6 +979,8 @@ mod test {
             node_identity,
             peer_manager,
             outbound_requester,
+            connection_manager,
+            discovery,
             actor_rx,
             shutdown.to_signal(),
         );
@@ -796,4 +1021,120 @@ mod test {
 
         shutdown.trigger().unwrap();
     }
+
+    #[tokio_macros::test_basic]
+    async fn neighbourhood_maintenance_connects_to_neighbours() {
+        let node_identity = make_node_identity();
+        let peer_manager = make_peer_manager();
+        let connected = make_node_identity();
+        let unreachable = make_node_identity();
+        for neighbour in &[&connected, &unreachable] {
+            peer_manager
+                .add_peer(Peer::new(
+                    neighbour.public_key().clone(),
+                    neighbour.node_id().clone(),
+                    MultiaddressesWithStats::new(vec![]),
+                    PeerFlags::empty(),
+                    PeerFeatures::COMMUNICATION_NODE,
+                    &[],
+                ))
+                .await
+                .unwrap();
+        }
+        for _ in 0..=MAX_FAILED_ATTEMPTS_MARK_PEER_OFFLINE {
+            peer_manager
+                .set_last_connect_failed(unreachable.node_id())
+                .await
+                .unwrap();
+        }
+
+        let (connection_manager, mock) = create_connection_manager_mock(10);
+        let conn_man_state = mock.get_shared_state();
+        task::spawn(mock.run());
+        let (conn, _, _, _) =
+            create_peer_connection_mock_pair(1, node_identity.node_id().clone(), connected.node_id().clone()).await;
+        conn_man_state
+            .add_active_connection(connected.node_id().clone(), conn)
+            .await;
+
+        let (discovery, mut discovery_mock) = create_dht_discovery_mock(10, Duration::from_secs(10));
+        let discovery_state = DhtDiscoveryMockState::new();
+        discovery_mock.set_shared_state(discovery_state.clone());
+        task::spawn(discovery_mock.run());
+
+        let (outbound_requester, _) = create_outbound_service_mock(1);
+        let mut maintenance = NeighbourhoodMaintenance {
+            config: Default::default(),
+            node_identity,
+            peer_manager: Arc::clone(&peer_manager),
+            database: DhtDatabase::new(db_connection().await),
+            outbound_requester,
+            connection_manager,
+            discovery,
+        };
+
+        maintenance.run().await.unwrap();
+
+        let dial_calls = conn_man_state
+            .take_calls()
+            .await
+            .into_iter()
+            .filter(|call| call.starts_with("DialPeer"))
+            .collect::<Vec<_>>();
+        assert_eq!(dial_calls.len(), 1);
+        assert!(dial_calls[0].contains(&format!("{:?}", unreachable.node_id())));
+
+        let peer = peer_manager.find_by_node_id(unreachable.node_id()).await.unwrap();
+        assert!(peer.is_offline());
+        let peer = peer_manager.find_by_node_id(connected.node_id()).await.unwrap();
+        assert!(!peer.is_offline());
+
+        assert_eq!(discovery_state.call_count(), 1);
+    }
+
+    #[tokio_macros::test_basic]
+    async fn neighbourhood_maintenance_rejoins_after_address_change() {
+        let node_identity = make_node_identity();
+        let (connection_manager, mock) = create_connection_manager_mock(1);
+        task::spawn(mock.run());
+        let (discovery, discovery_mock) = create_dht_discovery_mock(1, Duration::from_secs(10));
+        task::spawn(discovery_mock.run());
+        let (outbound_requester, outbound_mock) = create_outbound_service_mock(1);
+        let oms_state = outbound_mock.get_state();
+        task::spawn(outbound_mock.run());
+        let database = DhtDatabase::new(db_connection().await);
+
+        let mut maintenance = NeighbourhoodMaintenance {
+            config: Default::default(),
+            node_identity: Arc::clone(&node_identity),
+            peer_manager: make_peer_manager(),
+            database: database.clone(),
+            outbound_requester,
+            connection_manager,
+            discovery,
+        };
+
+        // No join has been sent yet
+        maintenance.run().await.unwrap();
+        assert_eq!(oms_state.call_count(), 0);
+
+        database
+            .set_metadata_value(DhtMetadataKey::JoinAddress, node_identity.public_address().to_string())
+            .await
+            .unwrap();
+        maintenance.run().await.unwrap();
+        assert_eq!(oms_state.call_count(), 0);
+
+        let new_address = "/ip4/127.0.0.1/tcp/9001".parse::<Multiaddr>().unwrap();
+        node_identity.set_public_address(new_address.clone()).unwrap();
+        maintenance.run().await.unwrap();
+        let (params, _) = oms_state.pop_call().unwrap();
+        assert_eq!(params.dht_message_type, DhtMessageType::Join);
+        let join_address = database
+            .get_metadata_value::<String>(DhtMetadataKey::JoinAddress)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(join_address, new_address.to_string());
+    }
 }
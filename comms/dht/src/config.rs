@@ -61,6 +61,19 @@ pub struct DhtConfig {
     pub saf_max_message_size: usize,
     /// When true, store and forward messages are requested from peers on connect (Default: true)
     pub saf_auto_request: bool,
+    /// The number of store and forward nodes closest to the destination of a message that should store the message.
+    /// Messages for destinations that this node cannot deliver to directly are forwarded to the closest
+    /// `saf_replication_factor` store and forward nodes, which store them if they are not aware of any closer nodes.
+    /// Default: 3
+    pub saf_replication_factor: usize,
+    /// The maximum number of messages that can be stored for a single origin
+    /// Default: 1_000
+    pub saf_max_messages_per_origin: usize,
+    /// The maximum number of messages without a known origin, e.g. encrypted messages, that can be stored from a
+    /// single peer. Such messages are usually forwarded by a neighbour on behalf of many origins, so the limit is
+    /// larger than `saf_max_messages_per_origin`.
+    /// Default: 5_000
+    pub saf_max_unknown_origin_messages_per_source: usize,
     /// The max capacity of the message hash cache
     /// Default: 10000
     pub msg_hash_cache_capacity: usize,
@@ -79,6 +92,27 @@ pub struct DhtConfig {
     /// The duration to wait for a peer discovery to complete before giving up.
     /// Default: 2 minutes
     pub discovery_request_timeout: Duration,
+    /// The number of peers that are queried concurrently in each round of an iterative node lookup.
+    /// Default: 3
+    pub lookup_concurrency: usize,
+    /// The maximum number of rounds of an iterative node lookup before giving up.
+    /// Default: 10
+    pub lookup_max_rounds: usize,
+    /// The duration to wait for responses in a round of an iterative node lookup before moving onto the next round.
+    /// Default: 10 seconds
+    pub lookup_round_timeout: Duration,
+    /// The interval at which the DHT actor performs neighbourhood maintenance. Each round ensures that this node is
+    /// connected to its `num_neighbouring_nodes` closest communication nodes, looks up random node ids to discover
+    /// new peers and re-sends a join message if this node's public address has changed.
+    /// Default: 5 minutes
+    pub neighbourhood_maintenance_interval: Duration,
+    /// The number of random node lookups performed in each neighbourhood maintenance round.
+    /// Default: 1
+    pub num_random_refresh_lookups: usize,
+    /// The minimum proof-of-work difficulty, in leading zero bits of the hash of a peer's public key and nonce, that a
+    /// peer must meet in its join and discovery messages before it is added to the peer list. 0 disables the check.
+    /// Default: 0
+    pub pow_difficulty: u32,
     /// The active Network. Default: TestNet
     pub network: Network,
 }
@@ -117,12 +151,21 @@ impl Default for DhtConfig {
             saf_high_priority_msg_storage_ttl: SAF_HIGH_PRIORITY_MSG_STORAGE_TTL,
             saf_auto_request: true,
             saf_max_message_size: 512 * 1024, // 500 KiB
+            saf_replication_factor: 3,
+            saf_max_messages_per_origin: 1_000,
+            saf_max_unknown_origin_messages_per_source: 5_000,
             msg_hash_cache_capacity: 10_000,
             msg_hash_cache_ttl: Duration::from_secs(5 * 60),
             broadcast_cooldown_max_attempts: 3,
             database_url: DbConnectionUrl::Memory,
             broadcast_cooldown_period: Duration::from_secs(60 * 30),
             discovery_request_timeout: Duration::from_secs(2 * 60),
+            lookup_concurrency: 3,
+            lookup_max_rounds: 10,
+            lookup_round_timeout: Duration::from_secs(10),
+            neighbourhood_maintenance_interval: Duration::from_secs(5 * 60),
+            num_random_refresh_lookups: 1,
+            pow_difficulty: 0,
             network: Network::TestNet,
         }
     }
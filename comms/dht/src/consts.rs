@@ -22,3 +22,7 @@
 
 /// Version for DHT envelope
 pub const DHT_ENVELOPE_HEADER_VERSION: u32 = 0;
+
+/// The number of consecutive times that attempts to connect should
+/// fail before marking the peer as offline
+pub const MAX_FAILED_ATTEMPTS_MARK_PEER_OFFLINE: usize = 10;
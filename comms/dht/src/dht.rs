@@ -140,6 +140,8 @@ impl Dht {
             Arc::clone(&self.node_identity),
             Arc::clone(&self.peer_manager),
             self.outbound_requester(),
+            self.connection_manager.clone(),
+            self.discovery_service_requester(),
             request_receiver,
             shutdown_signal,
         )
@@ -225,17 +227,24 @@ impl Dht {
         //        release mode, related to the amount of layers. (issue #1416)
         ServiceBuilder::new()
             .layer(inbound::DeserializeLayer)
-            .layer(inbound::ValidateLayer::new(self.config.network))
+            .layer(inbound::ValidateLayer::new(
+                self.config.network,
+                Arc::clone(&self.peer_manager),
+            ))
             .layer(DedupLayer::new(self.dht_requester()))
             .layer(tower_filter::FilterLayer::new(self.unsupported_saf_messages_filter()))
             .layer(MessageLoggingLayer::new(format!(
                 "Inbound [{}]",
                 self.node_identity.node_id().short_str()
             )))
-            .layer(inbound::DecryptionLayer::new(Arc::clone(&self.node_identity)))
+            .layer(inbound::DecryptionLayer::new(
+                Arc::clone(&self.node_identity),
+                Arc::clone(&self.peer_manager),
+            ))
             .layer(store_forward::ForwardLayer::new(
                 Arc::clone(&self.peer_manager),
                 self.outbound_requester(),
+                self.config.saf_replication_factor,
                 self.node_identity.features().contains(PeerFeatures::DHT_STORE_FORWARD),
             ))
             .layer(store_forward::StoreLayer::new(
@@ -309,7 +318,7 @@ impl Dht {
             }
 
             match msg.dht_header.message_type {
-                DhtMessageType::SafRequestMessages => {
+                DhtMessageType::SafRequestMessages | DhtMessageType::SafAcknowledgeMessages => {
                     // TODO: #banheuristic This is an indication of node misbehaviour
                     warn!(
                         "Received store and forward message from PublicKey={}. Store and forward feature is not \
@@ -34,6 +34,10 @@ pub enum DhtDiscoveryError {
     DiscoveredPeerMismatch,
     /// Received an invalid `NodeId`
     InvalidNodeId,
+    /// Received an invalid public key
+    InvalidPublicKey,
+    /// The peer's proof-of-work does not meet the required difficulty
+    InsufficientProofOfWork,
     /// MPSC channel is disconnected
     ChannelDisconnected,
     /// MPSC sender was unable to send because the channel buffer is full
@@ -42,6 +46,8 @@ pub enum DhtDiscoveryError {
     DiscoveryTimeout,
     /// Failed to send discovery message
     DiscoverySendFailed,
+    /// The node lookup converged without finding the target peer
+    LookupPeerNotFound,
     PeerManagerError(PeerManagerError),
     #[error(msg_embedded, non_std, no_from)]
     InvalidPeerMultiaddr(String),
@@ -0,0 +1,172 @@
+// Copyright 2020, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    time::{Duration, Instant},
+};
+use tari_comms::peer_manager::{node_id::NodeDistance, NodeId, Peer};
+
+/// The state of an iterative lookup for the peer with a particular `NodeId`.
+///
+/// Each round, the `concurrency` closest peers which have not been queried yet are asked for the peers they know of
+/// that are closest to the target. Returned peers are added to the candidates for the next round, so that each round
+/// gets closer to the target. The lookup converges when all of the `num_closest` closest known peers have been
+/// queried.
+pub struct NodeLookup {
+    target: NodeId,
+    concurrency: usize,
+    num_closest: usize,
+    round: usize,
+    round_deadline: Option<Instant>,
+    candidates: BTreeMap<NodeDistance, Peer>,
+    queried: HashSet<NodeId>,
+    inflight: HashSet<NodeId>,
+}
+
+impl NodeLookup {
+    pub fn new(target: NodeId, concurrency: usize, num_closest: usize) -> Self {
+        Self {
+            target,
+            concurrency,
+            num_closest,
+            round: 0,
+            round_deadline: None,
+            candidates: BTreeMap::new(),
+            queried: HashSet::new(),
+            inflight: HashSet::new(),
+        }
+    }
+
+    pub fn target(&self) -> &NodeId {
+        &self.target
+    }
+
+    /// The number of rounds started so far
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    /// Add a peer to the lookup candidates. Peers which have already been queried are ignored.
+    pub fn add_candidate(&mut self, peer: Peer) {
+        if self.queried.contains(&peer.node_id) {
+            return;
+        }
+        self.candidates.insert(self.target.distance(&peer.node_id), peer);
+    }
+
+    /// Start the next round, returning the peers to query. An empty result indicates that the lookup has converged.
+    pub fn next_round(&mut self, round_timeout: Duration) -> Vec<Peer> {
+        let queried = &self.queried;
+        let peers = self
+            .candidates
+            .values()
+            .take(self.num_closest)
+            .filter(|peer| !queried.contains(&peer.node_id))
+            .take(self.concurrency)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for peer in &peers {
+            self.queried.insert(peer.node_id.clone());
+            self.inflight.insert(peer.node_id.clone());
+        }
+
+        if !peers.is_empty() {
+            self.round += 1;
+            self.round_deadline = Some(Instant::now() + round_timeout);
+        }
+
+        peers
+    }
+
+    /// Record that a response was received from the given peer. Returns false if a response from this peer was not
+    /// expected.
+    pub fn mark_responded(&mut self, node_id: &NodeId) -> bool {
+        self.inflight.remove(node_id)
+    }
+
+    /// Returns true if all queried peers in the current round have responded or the round has timed out
+    pub fn is_round_complete(&self) -> bool {
+        self.inflight.is_empty() || self.round_deadline.map(|d| Instant::now() >= d).unwrap_or(true)
+    }
+
+    /// Give up waiting for outstanding responses in the current round
+    pub fn expire_round(&mut self) {
+        self.inflight.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::make_peer;
+
+    #[test]
+    fn next_round() {
+        let target = make_peer().node_id;
+        let mut lookup = NodeLookup::new(target.clone(), 2, 3);
+        let mut peers = (0..5).map(|_| make_peer()).collect::<Vec<_>>();
+        peers.sort_by(|a, b| target.distance(&a.node_id).cmp(&target.distance(&b.node_id)));
+        peers.iter().cloned().for_each(|p| lookup.add_candidate(p));
+
+        let round = lookup.next_round(Duration::from_secs(10));
+        assert_eq!(lookup.round(), 1);
+        assert_eq!(round, peers[..2].to_vec());
+        assert!(!lookup.is_round_complete());
+        assert!(lookup.mark_responded(&peers[0].node_id));
+        assert!(!lookup.mark_responded(&peers[0].node_id));
+        assert!(!lookup.is_round_complete());
+        assert!(lookup.mark_responded(&peers[1].node_id));
+        assert!(lookup.is_round_complete());
+
+        // Only the 3 closest are queried
+        let round = lookup.next_round(Duration::from_secs(10));
+        assert_eq!(round, peers[2..3].to_vec());
+        lookup.expire_round();
+        assert!(lookup.is_round_complete());
+
+        // A closer peer is learned
+        let mut closer = make_peer();
+        while target.distance(&closer.node_id) > target.distance(&peers[0].node_id) {
+            closer = make_peer();
+        }
+        lookup.add_candidate(closer.clone());
+        let round = lookup.next_round(Duration::from_secs(10));
+        assert_eq!(round, vec![closer]);
+
+        // Converged
+        assert!(lookup.next_round(Duration::from_secs(10)).is_empty());
+        assert_eq!(lookup.round(), 3);
+    }
+
+    #[test]
+    fn queried_peers_are_not_candidates() {
+        let target = make_peer().node_id;
+        let mut lookup = NodeLookup::new(target, 3, 3);
+        let peer = make_peer();
+        lookup.add_candidate(peer.clone());
+        assert_eq!(lookup.next_round(Duration::from_secs(10)), vec![peer.clone()]);
+        lookup.add_candidate(peer);
+        assert!(lookup.next_round(Duration::from_secs(10)).is_empty());
+    }
+}
@@ -20,7 +20,11 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::{discovery::DhtDiscoveryError, envelope::NodeDestination, proto::dht::DiscoveryResponseMessage};
+use crate::{
+    discovery::DhtDiscoveryError,
+    envelope::NodeDestination,
+    proto::dht::{DiscoveryResponseMessage, FindNodeResponse},
+};
 use futures::{
     channel::{mpsc, oneshot},
     SinkExt,
@@ -29,7 +33,10 @@ use std::{
     fmt::{Display, Error, Formatter},
     time::Duration,
 };
-use tari_comms::{peer_manager::Peer, types::CommsPublicKey};
+use tari_comms::{
+    peer_manager::{NodeId, Peer},
+    types::CommsPublicKey,
+};
 use tokio::time;
 
 #[derive(Debug)]
@@ -40,6 +47,8 @@ pub enum DhtDiscoveryRequest {
         oneshot::Sender<Result<Peer, DhtDiscoveryError>>,
     ),
     NotifyDiscoveryResponseReceived(Box<DiscoveryResponseMessage>),
+    LookupNode(Box<NodeId>, oneshot::Sender<Result<Peer, DhtDiscoveryError>>),
+    NotifyFindNodeResponseReceived(Box<CommsPublicKey>, Box<FindNodeResponse>),
 }
 
 impl Display for DhtDiscoveryRequest {
@@ -50,6 +59,13 @@ impl Display for DhtDiscoveryRequest {
             NotifyDiscoveryResponseReceived(discovery_resp) => {
                 write!(f, "NotifyDiscoveryResponseReceived({:#?})", discovery_resp)
             },
+            LookupNode(node_id, _) => write!(f, "LookupNode({})", node_id),
+            NotifyFindNodeResponseReceived(source, response) => write!(
+                f,
+                "NotifyFindNodeResponseReceived({}, {} peer(s))",
+                source,
+                response.peers.len()
+            ),
         }
     }
 }
@@ -109,6 +125,38 @@ impl DhtDiscoveryRequester {
             .map_err(|_| DhtDiscoveryError::ReplyCanceled)?
     }
 
+    /// Look up the peer with the given `NodeId` by iteratively asking the closest known peers for the peers closest
+    /// to the target. Unlike `discover_peer`, this does not require the target to be online, as the peer record is
+    /// returned as soon as any queried peer knows of the target.
+    pub async fn lookup_node(&mut self, node_id: NodeId) -> Result<Peer, DhtDiscoveryError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        self.sender
+            .send(DhtDiscoveryRequest::LookupNode(Box::new(node_id), reply_tx))
+            .await?;
+
+        time::timeout(self.discovery_timeout, reply_rx)
+            .await
+            .map_err(|_| DhtDiscoveryError::DiscoveryTimeout)?
+            .map_err(|_| DhtDiscoveryError::ReplyCanceled)?
+    }
+
+    pub async fn notify_find_node_response_received(
+        &mut self,
+        source_public_key: CommsPublicKey,
+        response: FindNodeResponse,
+    ) -> Result<(), DhtDiscoveryError>
+    {
+        self.sender
+            .send(DhtDiscoveryRequest::NotifyFindNodeResponseReceived(
+                Box::new(source_public_key),
+                Box::new(response),
+            ))
+            .await?;
+
+        Ok(())
+    }
+
     pub async fn notify_discovery_response_received(
         &mut self,
         response: DiscoveryResponseMessage,
@@ -21,10 +21,12 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    discovery::{requester::DhtDiscoveryRequest, DhtDiscoveryError},
+    consts::MAX_FAILED_ATTEMPTS_MARK_PEER_OFFLINE,
+    discovery::{lookup::NodeLookup, requester::DhtDiscoveryRequest, DhtDiscoveryError},
     envelope::{DhtMessageType, NodeDestination},
+    metrics,
     outbound::{OutboundEncryption, OutboundMessageRequester, SendMessageParams},
-    proto::dht::{DiscoveryMessage, DiscoveryResponseMessage},
+    proto::dht::{DiscoveryMessage, DiscoveryResponseMessage, FindNodeRequest, FindNodeResponse, PeerRecord},
     DhtConfig,
 };
 use futures::{
@@ -46,6 +48,7 @@ use tari_comms::{
     multiaddr::Multiaddr,
     peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerFlags, PeerManager},
     types::CommsPublicKey,
+    utils::proof_of_work,
     validate_peer_addresses,
     ConnectionManagerEvent,
 };
@@ -55,9 +58,8 @@ use tokio::{sync::broadcast, task, time};
 
 const LOG_TARGET: &str = "comms::dht::discovery_service";
 
-/// The number of consecutive times that attempts to connect should
-/// fail before marking the peer as offline
-const MAX_FAILED_ATTEMPTS_MARK_PEER_OFFLINE: usize = 10;
+/// The interval at which inflight node lookups are checked for rounds that have timed out
+const LOOKUP_TICK_INTERVAL: Duration = Duration::from_secs(1);
 
 struct DiscoveryRequestState {
     reply_tx: oneshot::Sender<Result<Peer, DhtDiscoveryError>>,
@@ -75,6 +77,16 @@ impl DiscoveryRequestState {
     }
 }
 
+struct NodeLookupState {
+    lookup: NodeLookup,
+    reply_tx: oneshot::Sender<Result<Peer, DhtDiscoveryError>>,
+    start_ts: Instant,
+    /// Set while the target is being dialed using a record returned by another peer
+    target_dial_deadline: Option<Instant>,
+    /// The verified proof-of-work nonce from the target's peer record
+    target_pow_nonce: Option<u64>,
+}
+
 pub struct DhtDiscoveryService {
     config: DhtConfig,
     node_identity: Arc<NodeIdentity>,
@@ -84,6 +96,7 @@ pub struct DhtDiscoveryService {
     request_rx: Option<mpsc::Receiver<DhtDiscoveryRequest>>,
     shutdown_signal: Option<ShutdownSignal>,
     inflight_discoveries: HashMap<u64, DiscoveryRequestState>,
+    inflight_lookups: HashMap<u64, NodeLookupState>,
 }
 
 impl DhtDiscoveryService {
@@ -106,6 +119,7 @@ impl DhtDiscoveryService {
             shutdown_signal: Some(shutdown_signal),
             request_rx: Some(request_rx),
             inflight_discoveries: HashMap::new(),
+            inflight_lookups: HashMap::new(),
         }
     }
 
@@ -129,6 +143,8 @@ impl DhtDiscoveryService {
             .expect("DiscoveryService initialized without request_rx")
             .fuse();
 
+        let mut lookup_ticker = time::interval(LOOKUP_TICK_INTERVAL).fuse();
+
         loop {
             futures::select! {
                 request = request_rx.select_next_some() => {
@@ -145,6 +161,10 @@ impl DhtDiscoveryService {
                     }
                 },
 
+                _ = lookup_ticker.select_next_some() => {
+                    self.check_inflight_lookups().await;
+                },
+
                 _ = shutdown_signal => {
                     info!(target: LOG_TARGET, "Discovery service is shutting down because the shutdown signal was received");
                     break;
@@ -165,6 +185,22 @@ impl DhtDiscoveryService {
             },
 
             NotifyDiscoveryResponseReceived(discovery_msg) => self.handle_discovery_response(discovery_msg).await,
+
+            LookupNode(node_id, reply_tx) => {
+                log_if_error!(
+                    target: LOG_TARGET,
+                    self.initiate_node_lookup(*node_id, reply_tx).await,
+                    "Failed to initiate a node lookup because '{error}'",
+                );
+            },
+
+            NotifyFindNodeResponseReceived(source_public_key, response) => {
+                log_if_error!(
+                    target: LOG_TARGET,
+                    self.handle_find_node_response(*source_public_key, *response).await,
+                    "Failed to handle find node response because '{error}'",
+                );
+            },
         }
     }
 
@@ -174,8 +210,11 @@ impl DhtDiscoveryService {
     ) -> Result<(), DhtDiscoveryError>
     {
         use ConnectionManagerEvent::*;
-        // The connection manager could not dial the peer on any address
         match event {
+            PeerConnected(conn) => {
+                self.complete_lookups_for_connected_peer(conn.peer_node_id()).await?;
+            },
+            // The connection manager could not dial the peer on any address
             PeerConnectFailed(node_id, ConnectionManagerError::ConnectFailedMaximumAttemptsReached) => {
                 if self.connection_manager.get_num_active_connections().await? == 0 {
                     info!(
@@ -186,7 +225,12 @@ impl DhtDiscoveryService {
                     );
                     return Ok(());
                 }
-                let peer = self.peer_manager.find_by_node_id(node_id).await?;
+                let peer = match self.peer_manager.find_by_node_id(node_id).await {
+                    Ok(peer) => peer,
+                    // Lookup candidates are dialed before they are in the peer manager
+                    Err(err) if err.is_peer_not_found() => return Ok(()),
+                    Err(err) => return Err(err.into()),
+                };
                 if peer.connection_stats.failed_attempts() > MAX_FAILED_ATTEMPTS_MARK_PEER_OFFLINE {
                     debug!(
                         target: LOG_TARGET,
@@ -285,6 +329,7 @@ impl DhtDiscoveryService {
                 } = request;
 
                 let result = self.validate_then_add_peer(&public_key, discovery_msg).await;
+                metrics::observe_discovery_latency(start_ts.elapsed());
 
                 // Resolve any other pending discover requests if the peer was found
                 match &result {
@@ -340,6 +385,10 @@ impl DhtDiscoveryService {
     {
         let node_id = self.validate_raw_node_id(&public_key, &discovery_msg.node_id)?;
 
+        if !proof_of_work::verify(&public_key, discovery_msg.pow_nonce, self.config.pow_difficulty) {
+            return Err(DhtDiscoveryError::InsufficientProofOfWork);
+        }
+
         let addresses = discovery_msg
             .addresses
             .into_iter()
@@ -355,6 +404,7 @@ impl DhtDiscoveryService {
                 node_id,
                 addresses,
                 PeerFeatures::from_bits_truncate(discovery_msg.peer_features),
+                discovery_msg.pow_nonce,
             )
             .await?;
 
@@ -386,6 +436,7 @@ impl DhtDiscoveryService {
         node_id: NodeId,
         net_addresses: Vec<Multiaddr>,
         peer_features: PeerFeatures,
+        pow_nonce: u64,
     ) -> Result<Peer, DhtDiscoveryError>
     {
         let peer_manager = &self.peer_manager;
@@ -418,6 +469,7 @@ impl DhtDiscoveryService {
                 ))
                 .await?;
         }
+        peer_manager.set_proof_of_work_nonce(pubkey, pow_nonce).await?;
 
         let peer = peer_manager.find_by_public_key(&pubkey).await?;
 
@@ -474,6 +526,7 @@ impl DhtDiscoveryService {
             addresses: vec![self.node_identity.public_address().to_string()],
             peer_features: self.node_identity.features().bits(),
             nonce,
+            pow_nonce: self.node_identity.proof_of_work_nonce().unwrap_or(0),
         };
         info!(
             target: LOG_TARGET,
@@ -524,6 +577,371 @@ impl DhtDiscoveryService {
 
         Ok(())
     }
+
+    async fn initiate_node_lookup(
+        &mut self,
+        target: NodeId,
+        reply_tx: oneshot::Sender<Result<Peer, DhtDiscoveryError>>,
+    ) -> Result<(), DhtDiscoveryError>
+    {
+        let mut lookup = NodeLookup::new(
+            target.clone(),
+            self.config.lookup_concurrency,
+            self.config.num_neighbouring_nodes,
+        );
+        let peers = self
+            .peer_manager
+            .closest_peers(
+                &target,
+                self.config.num_neighbouring_nodes,
+                &[],
+                Some(PeerFeatures::COMMUNICATION_NODE),
+            )
+            .await?;
+        peers.into_iter().for_each(|peer| lookup.add_candidate(peer));
+
+        let nonce = OsRng.next_u64();
+        debug!(
+            target: LOG_TARGET,
+            "Starting lookup for node '{}' (nonce = {})",
+            target.short_str(),
+            nonce
+        );
+        self.inflight_lookups.insert(nonce, NodeLookupState {
+            lookup,
+            reply_tx,
+            start_ts: Instant::now(),
+            target_dial_deadline: None,
+            target_pow_nonce: None,
+        });
+        self.progress_lookup(nonce).await;
+
+        Ok(())
+    }
+
+    /// Starts the next round of the lookup if the current round is complete, or completes the lookup if it has
+    /// converged or reached the maximum number of rounds.
+    async fn progress_lookup(&mut self, nonce: u64) {
+        let max_rounds = self.config.lookup_max_rounds;
+        let round_timeout = self.config.lookup_round_timeout;
+        let (target, peers, is_dialing_target) = match self.inflight_lookups.get_mut(&nonce) {
+            Some(state) => {
+                if !state.lookup.is_round_complete() {
+                    return;
+                }
+                state.lookup.expire_round();
+                let peers = if state.lookup.round() < max_rounds {
+                    state.lookup.next_round(round_timeout)
+                } else {
+                    Vec::new()
+                };
+                let is_dialing_target = state
+                    .target_dial_deadline
+                    .map(|deadline| Instant::now() < deadline)
+                    .unwrap_or(false);
+                (state.lookup.target().clone(), peers, is_dialing_target)
+            },
+            None => return,
+        };
+
+        if peers.is_empty() {
+            if is_dialing_target {
+                return;
+            }
+            debug!(
+                target: LOG_TARGET,
+                "Lookup for node '{}' did not find the node",
+                target.short_str()
+            );
+            self.complete_lookup(nonce, Err(DhtDiscoveryError::LookupPeerNotFound));
+            return;
+        }
+
+        for peer in peers {
+            if !self.peer_manager.exists(&peer.public_key).await {
+                self.spawn_unverified_find_node_request(nonce, &target, peer);
+                continue;
+            }
+            let request = self.find_node_request(nonce, &target);
+            if let Err(err) =
+                send_find_node_request(&mut self.outbound_requester, request, peer.public_key.clone()).await
+            {
+                debug!(
+                    target: LOG_TARGET,
+                    "Failed to send find node request to peer '{}': {:?}",
+                    peer.node_id.short_str(),
+                    err
+                );
+                // Don't wait for a response that will never arrive
+                if let Some(state) = self.inflight_lookups.get_mut(&nonce) {
+                    state.lookup.mark_responded(&peer.node_id);
+                }
+            }
+        }
+    }
+
+    async fn check_inflight_lookups(&mut self) {
+        // Lookups which the caller is no longer waiting for (e.g. the request timed out) are discarded
+        let inflight_lookups = self
+            .inflight_lookups
+            .drain()
+            .filter(|(_, state)| !state.reply_tx.is_canceled())
+            .collect();
+        self.inflight_lookups = inflight_lookups;
+
+        let nonces = self
+            .inflight_lookups
+            .iter()
+            .filter(|(_, state)| state.lookup.is_round_complete())
+            .map(|(nonce, _)| *nonce)
+            .collect::<Vec<_>>();
+
+        for nonce in nonces {
+            self.progress_lookup(nonce).await;
+        }
+    }
+
+    fn complete_lookup(&mut self, nonce: u64, result: Result<Peer, DhtDiscoveryError>) {
+        if let Some(state) = self.inflight_lookups.remove(&nonce) {
+            if result.is_ok() {
+                metrics::observe_discovery_latency(state.start_ts.elapsed());
+                info!(
+                    target: LOG_TARGET,
+                    "Lookup for node '{}' completed in {}s after {} round(s)",
+                    state.lookup.target().short_str(),
+                    state.start_ts.elapsed().as_secs_f32(),
+                    state.lookup.round()
+                );
+            }
+            let _ = state.reply_tx.send(result);
+        }
+    }
+
+    async fn handle_find_node_response(
+        &mut self,
+        source_public_key: CommsPublicKey,
+        response: FindNodeResponse,
+    ) -> Result<(), DhtDiscoveryError>
+    {
+        let source_node_id = NodeId::from_key(&source_public_key).map_err(|_| DhtDiscoveryError::InvalidNodeId)?;
+        let nonce = response.nonce;
+        let target = self.inflight_lookups.get_mut(&nonce).and_then(|state| {
+            if state.lookup.mark_responded(&source_node_id) {
+                Some(state.lookup.target().clone())
+            } else {
+                None
+            }
+        });
+        let target = match target {
+            Some(target) => target,
+            None => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Received a find node response from peer '{}' that this node did not expect",
+                    source_node_id.short_str()
+                );
+                return Ok(());
+            },
+        };
+
+        // The target responded to the request itself
+        if source_node_id == target {
+            let peer = self.peer_manager.find_by_node_id(&target).await?;
+            self.complete_lookup(nonce, Ok(peer));
+            return Ok(());
+        }
+
+        let mut candidates = Vec::with_capacity(response.peers.len());
+        for record in response.peers {
+            let peer = match self.validate_peer_record(record) {
+                Ok(peer) => peer,
+                Err(err) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Peer '{}' returned an invalid peer record: {:?}",
+                        source_node_id.short_str(),
+                        err
+                    );
+                    continue;
+                },
+            };
+
+            if &peer.node_id == self.node_identity.node_id() {
+                continue;
+            }
+
+            if peer.node_id == target {
+                // The lookup completes once the target has been connected to and authenticated
+                if let Some(state) = self.inflight_lookups.get_mut(&nonce) {
+                    state.target_dial_deadline = Some(Instant::now() + self.config.lookup_round_timeout);
+                    state.target_pow_nonce = peer.proof_of_work_nonce;
+                }
+                self.spawn_dial_lookup_target(peer);
+                continue;
+            }
+
+            let peer = self.known_peer_or_record(peer).await?;
+            if peer.features == PeerFeatures::COMMUNICATION_NODE && !peer.is_banned() {
+                candidates.push(peer);
+            }
+        }
+
+        if let Some(state) = self.inflight_lookups.get_mut(&nonce) {
+            candidates.into_iter().for_each(|peer| state.lookup.add_candidate(peer));
+        }
+        self.progress_lookup(nonce).await;
+
+        Ok(())
+    }
+
+    fn validate_peer_record(&self, record: PeerRecord) -> Result<Peer, DhtDiscoveryError> {
+        let public_key =
+            CommsPublicKey::from_bytes(&record.public_key).map_err(|_| DhtDiscoveryError::InvalidPublicKey)?;
+        let node_id = NodeId::from_key(&public_key).map_err(|_| DhtDiscoveryError::InvalidNodeId)?;
+        if !proof_of_work::verify(&public_key, record.pow_nonce, self.config.pow_difficulty) {
+            return Err(DhtDiscoveryError::InsufficientProofOfWork);
+        }
+
+        let addresses = record
+            .addresses
+            .into_iter()
+            .filter_map(|addr| addr.parse().ok())
+            .collect::<Vec<Multiaddr>>();
+        if addresses.is_empty() {
+            return Err(DhtDiscoveryError::InvalidPeerMultiaddr(
+                "No valid addresses in peer record".to_string(),
+            ));
+        }
+        validate_peer_addresses(&addresses, self.config.network.is_localtest())
+            .map_err(|err| DhtDiscoveryError::InvalidPeerMultiaddr(err.to_string()))?;
+
+        let mut peer = Peer::new(
+            public_key,
+            node_id,
+            addresses.into(),
+            PeerFlags::default(),
+            PeerFeatures::from_bits_truncate(record.peer_features),
+            &[],
+        );
+        peer.proof_of_work_nonce = Some(record.pow_nonce);
+        Ok(peer)
+    }
+
+    /// Peer records returned by other peers are not authenticated. Known peers are used as they are stored in the peer
+    /// manager and the records of unknown peers are only kept in the lookup state, never in the peer manager.
+    async fn known_peer_or_record(&self, peer: Peer) -> Result<Peer, DhtDiscoveryError> {
+        match self.peer_manager.find_by_public_key(&peer.public_key).await {
+            Ok(existing) => Ok(existing),
+            Err(err) if err.is_peer_not_found() => Ok(peer),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Dial the lookup target using the record returned by another peer. The record is not trusted: the connection
+    /// authenticates the target, which is then added to or updated in the peer manager from the identity it sends, and
+    /// the `PeerConnected` event completes the lookup.
+    fn spawn_dial_lookup_target(&self, peer: Peer) {
+        let mut connection_manager = self.connection_manager.clone();
+        task::spawn(async move {
+            let node_id = peer.node_id.clone();
+            if let Err(err) = connection_manager.dial_unverified_peer(peer).await {
+                debug!(
+                    target: LOG_TARGET,
+                    "Failed to connect to lookup target '{}': {:?}",
+                    node_id.short_str(),
+                    err
+                );
+            }
+        });
+    }
+
+    /// Dial a lookup candidate that is not in the peer manager and send it the find node request once the connection
+    /// has authenticated it. If either fails the candidate is treated as not having responded.
+    fn spawn_unverified_find_node_request(&self, nonce: u64, target: &NodeId, peer: Peer) {
+        let mut connection_manager = self.connection_manager.clone();
+        let mut outbound_requester = self.outbound_requester.clone();
+        let request = self.find_node_request(nonce, target);
+        task::spawn(async move {
+            let public_key = peer.public_key.clone();
+            let node_id = peer.node_id.clone();
+            if let Err(err) = connection_manager.dial_unverified_peer(peer).await {
+                debug!(
+                    target: LOG_TARGET,
+                    "Failed to connect to lookup candidate '{}': {:?}",
+                    node_id.short_str(),
+                    err
+                );
+                return;
+            }
+            if let Err(err) = send_find_node_request(&mut outbound_requester, request, public_key).await {
+                debug!(
+                    target: LOG_TARGET,
+                    "Failed to send find node request to peer '{}': {:?}",
+                    node_id.short_str(),
+                    err
+                );
+            }
+        });
+    }
+
+    /// Complete any lookups for a peer that this node has connected to
+    async fn complete_lookups_for_connected_peer(&mut self, node_id: &NodeId) -> Result<(), DhtDiscoveryError> {
+        let nonces = self
+            .inflight_lookups
+            .iter()
+            .filter(|(_, state)| state.lookup.target() == node_id)
+            .map(|(nonce, _)| *nonce)
+            .collect::<Vec<_>>();
+        if nonces.is_empty() {
+            return Ok(());
+        }
+
+        let mut peer = self.peer_manager.find_by_node_id(node_id).await?;
+        // The peer was added from its authenticated identity, which does not include the proof-of-work nonce
+        let pow_nonce = nonces
+            .iter()
+            .filter_map(|nonce| self.inflight_lookups.get(nonce))
+            .find_map(|state| state.target_pow_nonce);
+        if let Some(pow_nonce) = pow_nonce {
+            self.peer_manager
+                .set_proof_of_work_nonce(&peer.public_key, pow_nonce)
+                .await?;
+            peer.proof_of_work_nonce = Some(pow_nonce);
+        }
+        for nonce in nonces {
+            self.complete_lookup(nonce, Ok(peer.clone()));
+        }
+        Ok(())
+    }
+
+    fn find_node_request(&self, nonce: u64, target: &NodeId) -> FindNodeRequest {
+        FindNodeRequest {
+            target: target.to_vec(),
+            num_peers: self.config.num_neighbouring_nodes as u32,
+            nonce,
+        }
+    }
+}
+
+async fn send_find_node_request(
+    outbound_requester: &mut OutboundMessageRequester,
+    request: FindNodeRequest,
+    dest_public_key: CommsPublicKey,
+) -> Result<(), DhtDiscoveryError>
+{
+    trace!(target: LOG_TARGET, "Sending find node request to {}", dest_public_key);
+    outbound_requester
+        .send_message_no_header(
+            SendMessageParams::new()
+                .direct_public_key(dest_public_key.clone())
+                .with_destination(NodeDestination::PublicKey(Box::new(dest_public_key)))
+                .with_dht_message_type(DhtMessageType::FindNodeRequest)
+                .finish(),
+            request,
+        )
+        .await?;
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -532,10 +950,14 @@ mod test {
     use crate::{
         discovery::DhtDiscoveryRequester,
         outbound::mock::create_outbound_service_mock,
-        test_utils::{make_node_identity, make_peer_manager},
+        test_utils::{make_node_identity, make_peer, make_peer_manager},
     };
+    use prost::Message;
     use std::time::Duration;
-    use tari_comms::test_utils::mocks::create_connection_manager_mock;
+    use tari_comms::{
+        message::EnvelopeBody,
+        test_utils::mocks::{create_connection_manager_mock, create_peer_connection_mock_pair},
+    };
     use tari_shutdown::Shutdown;
 
     #[tokio_macros::test_basic]
@@ -578,4 +1000,116 @@ mod test {
         assert_eq!(params.dht_message_type, DhtMessageType::Discovery);
         assert_eq!(params.encryption, OutboundEncryption::EncryptFor(dest_public_key));
     }
+
+    #[tokio_macros::test_basic]
+    async fn validate_peer_record_proof_of_work() {
+        let (outbound_requester, _) = create_outbound_service_mock(1);
+        let (connection_manager, _) = create_connection_manager_mock(1);
+        let (_, receiver) = mpsc::channel(1);
+        let shutdown = Shutdown::new();
+        let service = DhtDiscoveryService::new(
+            DhtConfig {
+                pow_difficulty: 8,
+                ..DhtConfig::default_local_test()
+            },
+            make_node_identity(),
+            make_peer_manager(),
+            outbound_requester,
+            connection_manager,
+            receiver,
+            shutdown.to_signal(),
+        );
+
+        let mut peer = make_peer();
+        let nonce = proof_of_work::find_nonce(&peer.public_key, 8, 0).unwrap();
+        peer.proof_of_work_nonce = Some(nonce);
+        let validated = service.validate_peer_record(PeerRecord::from(&peer)).unwrap();
+        assert_eq!(validated.proof_of_work_nonce, Some(nonce));
+
+        let public_key = peer.public_key.clone();
+        peer.proof_of_work_nonce = (0..).find(|nonce| !proof_of_work::verify(&public_key, *nonce, 8));
+        let err = service.validate_peer_record(PeerRecord::from(&peer)).unwrap_err();
+        match err {
+            DhtDiscoveryError::InsufficientProofOfWork => {},
+            err => panic!("Unexpected error {:?}", err),
+        }
+    }
+
+    #[tokio_macros::test]
+    async fn lookup_node() {
+        let node_identity = make_node_identity();
+        let peer_manager = make_peer_manager();
+        let peer = make_peer();
+        peer_manager.add_peer(peer.clone()).await.unwrap();
+        let target = make_peer();
+
+        let (outbound_requester, outbound_mock) = create_outbound_service_mock(10);
+        let oms_mock_state = outbound_mock.get_state();
+        task::spawn(outbound_mock.run());
+
+        let (connection_manager, mock) = create_connection_manager_mock(10);
+        let mut conn_man_state = mock.get_shared_state();
+        task::spawn(mock.run());
+        let (sender, receiver) = mpsc::channel(10);
+        let requester = DhtDiscoveryRequester::new(sender, Duration::from_secs(10));
+        let shutdown = Shutdown::new();
+
+        DhtDiscoveryService::new(
+            DhtConfig::default_local_test(),
+            node_identity.clone(),
+            peer_manager.clone(),
+            outbound_requester,
+            connection_manager,
+            receiver,
+            shutdown.to_signal(),
+        )
+        .spawn();
+
+        let lookup = task::spawn({
+            let mut requester = requester.clone();
+            let target_node_id = target.node_id.clone();
+            async move { requester.lookup_node(target_node_id).await }
+        });
+
+        // The only known peer is asked for the peers closest to the target
+        oms_mock_state.wait_call_count(1, Duration::from_secs(5)).unwrap();
+        let (params, body) = oms_mock_state.pop_call().unwrap();
+        assert_eq!(params.dht_message_type, DhtMessageType::FindNodeRequest);
+        let request = EnvelopeBody::decode(body)
+            .unwrap()
+            .decode_part::<FindNodeRequest>(0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(request.target, target.node_id.to_vec());
+
+        // The peer knows the target
+        let mut requester = requester;
+        requester
+            .notify_find_node_response_received(peer.public_key.clone(), FindNodeResponse {
+                peers: vec![PeerRecord::from(&target)],
+                nonce: request.nonce,
+            })
+            .await
+            .unwrap();
+
+        // The unverified record is only used to dial the target, it is not added to the peer manager
+        for _ in 0..50 {
+            if conn_man_state.call_count() > 0 {
+                break;
+            }
+            time::delay_for(Duration::from_millis(100)).await;
+        }
+        let calls = conn_man_state.take_calls().await;
+        assert!(calls.iter().any(|call| call.starts_with("DialUnverifiedPeer")));
+        assert!(!peer_manager.exists(&target.public_key).await);
+
+        // The connection authenticates the target, which is added from the identity it sends, and completes the lookup
+        peer_manager.add_peer(target.clone()).await.unwrap();
+        let (conn, _, _, _) =
+            create_peer_connection_mock_pair(1, target.node_id.clone(), node_identity.node_id().clone()).await;
+        conn_man_state.publish_event(ConnectionManagerEvent::PeerConnected(conn));
+
+        let found = lookup.await.unwrap().unwrap();
+        assert_eq!(found.node_id, target.node_id);
+    }
 }
@@ -79,7 +79,7 @@ impl DhtMessageFlags {
 
 impl DhtMessageType {
     pub fn is_dht_message(self) -> bool {
-        self.is_dht_discovery() || self.is_dht_join()
+        self.is_dht_discovery() || self.is_dht_join() || self.is_dht_find_node()
     }
 
     pub fn is_dht_discovery(self) -> bool {
@@ -96,10 +96,18 @@ impl DhtMessageType {
         }
     }
 
+    pub fn is_dht_find_node(self) -> bool {
+        use DhtMessageType::*;
+        match self {
+            FindNodeRequest | FindNodeResponse => true,
+            _ => false,
+        }
+    }
+
     pub fn is_saf_message(self) -> bool {
         use DhtMessageType::*;
         match self {
-            SafRequestMessages | SafStoredMessages => true,
+            SafRequestMessages | SafStoredMessages | SafAcknowledgeMessages => true,
             _ => false,
         }
     }
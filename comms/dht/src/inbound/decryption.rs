@@ -23,7 +23,10 @@
 use crate::{
     crypt,
     envelope::{DhtMessageFlags, DhtMessageHeader},
-    inbound::message::{DecryptedDhtMessage, DhtInboundMessage},
+    inbound::{
+        message::{DecryptedDhtMessage, DhtInboundMessage},
+        report_offence,
+    },
     proto::envelope::OriginMac,
 };
 use derive_error::Error;
@@ -33,7 +36,7 @@ use prost::Message;
 use std::{sync::Arc, task::Poll};
 use tari_comms::{
     message::EnvelopeBody,
-    peer_manager::NodeIdentity,
+    peer_manager::{NodeIdentity, Offence, PeerManager},
     pipeline::PipelineError,
     types::CommsPublicKey,
     utils::signature,
@@ -62,11 +65,15 @@ enum DecryptionError {
 /// This layer is responsible for attempting to decrypt inbound messages.
 pub struct DecryptionLayer {
     node_identity: Arc<NodeIdentity>,
+    peer_manager: Arc<PeerManager>,
 }
 
 impl DecryptionLayer {
-    pub fn new(node_identity: Arc<NodeIdentity>) -> Self {
-        Self { node_identity }
+    pub fn new(node_identity: Arc<NodeIdentity>, peer_manager: Arc<PeerManager>) -> Self {
+        Self {
+            node_identity,
+            peer_manager,
+        }
     }
 }
 
@@ -74,7 +81,7 @@ impl<S> Layer<S> for DecryptionLayer {
     type Service = DecryptionService<S>;
 
     fn layer(&self, service: S) -> Self::Service {
-        DecryptionService::new(service, Arc::clone(&self.node_identity))
+        DecryptionService::new(service, Arc::clone(&self.node_identity), Arc::clone(&self.peer_manager))
     }
 }
 
@@ -82,14 +89,16 @@ impl<S> Layer<S> for DecryptionLayer {
 #[derive(Clone)]
 pub struct DecryptionService<S> {
     node_identity: Arc<NodeIdentity>,
+    peer_manager: Arc<PeerManager>,
     inner: S,
 }
 
 impl<S> DecryptionService<S> {
-    pub fn new(service: S, node_identity: Arc<NodeIdentity>) -> Self {
+    pub fn new(service: S, node_identity: Arc<NodeIdentity>, peer_manager: Arc<PeerManager>) -> Self {
         Self {
             inner: service,
             node_identity,
+            peer_manager,
         }
     }
 }
@@ -107,7 +116,12 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError> + Cl
     }
 
     fn call(&mut self, msg: DhtInboundMessage) -> Self::Future {
-        Self::handle_message(self.inner.clone(), Arc::clone(&self.node_identity), msg)
+        Self::handle_message(
+            self.inner.clone(),
+            Arc::clone(&self.node_identity),
+            Arc::clone(&self.peer_manager),
+            msg,
+        )
     }
 }
 
@@ -117,20 +131,23 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
     async fn handle_message(
         next_service: S,
         node_identity: Arc<NodeIdentity>,
+        peer_manager: Arc<PeerManager>,
         message: DhtInboundMessage,
     ) -> Result<(), PipelineError>
     {
         let dht_header = &message.dht_header;
 
         if !dht_header.flags.contains(DhtMessageFlags::ENCRYPTED) {
-            return Self::success_not_encrypted(next_service, message).await;
+            return Self::success_not_encrypted(next_service, &peer_manager, message).await;
         }
 
-        let e_pk = dht_header
-            .ephemeral_public_key
-            .as_ref()
-            // TODO: #banheuristic - encrypted message sent without ephemeral public key
-            .ok_or("Ephemeral public key not provided for encrypted message")?;
+        let e_pk = match dht_header.ephemeral_public_key.as_ref() {
+            Some(e_pk) => e_pk,
+            None => {
+                report_offence(&peer_manager, &message.source_peer, Offence::InvalidMessage).await;
+                return Err("Ephemeral public key not provided for encrypted message".into());
+            },
+        };
 
         let shared_secret = crypt::generate_ecdh_secret(node_identity.secret_key(), e_pk);
 
@@ -138,9 +155,11 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         let authenticated_origin = match Self::attempt_decrypt_origin_mac(&shared_secret, dht_header) {
             Ok((public_key, signature)) => {
                 // If this fails, discard the message because we decrypted and deserialized the message with our shared
-                // ECDH secret but the message could not be authenticated
-                Self::authenticate_origin_mac(&public_key, &signature, &message.body)
-                    .map_err(PipelineError::from_debug)?;
+                // ECDH secret but the message could not be authenticated. The peer that sent us the message may have
+                // only relayed it and cannot check the encrypted origin MAC, so this is not reported as an offence.
+                if let Err(err) = Self::authenticate_origin_mac(&public_key, &signature, &message.body) {
+                    return Err(PipelineError::from_debug(err));
+                }
                 public_key
             },
             Err(err) => {
@@ -234,17 +253,22 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
             .map_err(|_| DecryptionError::MessageBodyDecryptionFailed)
     }
 
-    async fn success_not_encrypted(next_service: S, message: DhtInboundMessage) -> Result<(), PipelineError> {
+    async fn success_not_encrypted(
+        next_service: S,
+        peer_manager: &PeerManager,
+        message: DhtInboundMessage,
+    ) -> Result<(), PipelineError>
+    {
         let authenticated_pk = if message.dht_header.origin_mac.is_empty() {
             None
         } else {
-            let origin_mac = OriginMac::decode(message.dht_header.origin_mac.as_slice())
-                .map_err(|_| PipelineError::from_debug(DecryptionError::OriginMacClearTextDecodeFailed))?;
-            let public_key = CommsPublicKey::from_bytes(&origin_mac.public_key)
-                .map_err(|_| PipelineError::from_debug(DecryptionError::OriginMacInvalidPublicKey))?;
-            Self::authenticate_origin_mac(&public_key, &origin_mac.signature, &message.body)
-                .map_err(PipelineError::from_debug)?;
-            Some(public_key)
+            match Self::authenticate_clear_text_origin_mac(&message) {
+                Ok(public_key) => Some(public_key),
+                Err(err) => {
+                    report_offence(peer_manager, &message.source_peer, Offence::InvalidMessage).await;
+                    return Err(PipelineError::from_debug(err));
+                },
+            }
         };
 
         match EnvelopeBody::decode(message.body.as_slice()) {
@@ -258,7 +282,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
             },
             Err(err) => {
                 // Message was not encrypted but failed to deserialize - immediately discard
-                // TODO: Bad node behaviour?
+                report_offence(peer_manager, &message.source_peer, Offence::InvalidMessage).await;
                 debug!(
                     target: LOG_TARGET,
                     "Unable to deserialize message: {}. Message will be discarded.", err
@@ -268,6 +292,15 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         }
     }
 
+    fn authenticate_clear_text_origin_mac(message: &DhtInboundMessage) -> Result<CommsPublicKey, DecryptionError> {
+        let origin_mac = OriginMac::decode(message.dht_header.origin_mac.as_slice())
+            .map_err(|_| DecryptionError::OriginMacClearTextDecodeFailed)?;
+        let public_key = CommsPublicKey::from_bytes(&origin_mac.public_key)
+            .map_err(|_| DecryptionError::OriginMacInvalidPublicKey)?;
+        Self::authenticate_origin_mac(&public_key, &origin_mac.signature, &message.body)?;
+        Ok(public_key)
+    }
+
     async fn decryption_failed(
         next_service: S,
         node_identity: &NodeIdentity,
@@ -277,7 +310,8 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         if message.dht_header.destination == node_identity.node_id() ||
             message.dht_header.destination == node_identity.public_key()
         {
-            // TODO: #banheuristic - the origin of this message sent this node a message we could not decrypt
+            // This is not reported as an offence against the source peer, because it may have only relayed a message
+            // that it cannot decrypt
             warn!(
                 target: LOG_TARGET,
                 "Received message from peer '{}' that is destined for this node that could not be decrypted. \
@@ -298,18 +332,18 @@ mod test {
     use super::*;
     use crate::{
         envelope::DhtMessageFlags,
-        test_utils::{make_dht_inbound_message, make_node_identity, service_fn},
+        test_utils::{make_dht_inbound_message, make_node_identity, make_peer_manager, service_fn},
     };
     use futures::{executor::block_on, future};
     use std::sync::Mutex;
-    use tari_comms::{message::MessageExt, wrap_in_envelope_body};
+    use tari_comms::{message::MessageExt, peer_manager::ReputationConfig, wrap_in_envelope_body};
     use tari_test_utils::counter_context;
 
     #[test]
     fn poll_ready() {
         let inner = service_fn(|_: DecryptedDhtMessage| future::ready(Result::<(), PipelineError>::Ok(())));
         let node_identity = make_node_identity();
-        let mut service = DecryptionService::new(inner, node_identity);
+        let mut service = DecryptionService::new(inner, node_identity, make_peer_manager());
 
         counter_context!(cx, counter);
 
@@ -326,7 +360,7 @@ mod test {
             future::ready(Result::<(), PipelineError>::Ok(()))
         });
         let node_identity = make_node_identity();
-        let mut service = DecryptionService::new(inner, Arc::clone(&node_identity));
+        let mut service = DecryptionService::new(inner, Arc::clone(&node_identity), make_peer_manager());
 
         let plain_text_msg = wrap_in_envelope_body!(b"Secret plans".to_vec());
         let inbound_msg = make_dht_inbound_message(
@@ -350,7 +384,7 @@ mod test {
             future::ready(Result::<(), PipelineError>::Ok(()))
         });
         let node_identity = make_node_identity();
-        let mut service = DecryptionService::new(inner, Arc::clone(&node_identity));
+        let mut service = DecryptionService::new(inner, Arc::clone(&node_identity), make_peer_manager());
 
         let some_secret = "Super secret message".as_bytes().to_vec();
         let some_other_node_identity = make_node_identity();
@@ -372,15 +406,22 @@ mod test {
             future::ready(Result::<(), PipelineError>::Ok(()))
         });
         let node_identity = make_node_identity();
-        let mut service = DecryptionService::new(inner, Arc::clone(&node_identity));
+        let peer_manager = make_peer_manager();
+        let mut service = DecryptionService::new(inner, Arc::clone(&node_identity), Arc::clone(&peer_manager));
 
         let nonsense = "Cannot Decrypt this".as_bytes().to_vec();
         let mut inbound_msg =
             make_dht_inbound_message(&node_identity, nonsense.clone(), DhtMessageFlags::ENCRYPTED, true);
         inbound_msg.dht_header.destination = node_identity.public_key().clone().into();
+        let source_peer = (*inbound_msg.source_peer).clone();
+        block_on(peer_manager.add_peer(source_peer.clone())).unwrap();
 
         let err = block_on(service.call(inbound_msg)).unwrap_err();
         assert!(err.to_string().starts_with("Message rejected"),);
         assert!(result.lock().unwrap().is_none());
+
+        // The relaying source peer cannot check the ciphertext, so no offence is recorded against it
+        let peer = block_on(peer_manager.find_by_public_key(&source_peer.public_key)).unwrap();
+        assert_eq!(peer.reputation.score(&ReputationConfig::default()), 0.0);
     }
 }
@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::{inbound::DhtInboundMessage, proto::envelope::DhtEnvelope};
+use crate::{inbound::DhtInboundMessage, metrics, proto::envelope::DhtEnvelope};
 use futures::{task::Context, Future};
 use log::*;
 use prost::Message;
@@ -89,6 +89,7 @@ where S: Service<DhtInboundMessage, Response = (), Error = PipelineError> + Clon
                         source_peer,
                         dht_envelope.body,
                     );
+                    metrics::inc_inbound_messages(inbound_msg.dht_header.message_type);
 
                     next_service.oneshot(inbound_msg).await
                 },
@@ -27,18 +27,27 @@ use crate::{
     inbound::{error::DhtInboundError, message::DecryptedDhtMessage},
     outbound::{OutboundMessageRequester, SendMessageParams},
     proto::{
-        dht::{DiscoveryMessage, DiscoveryResponseMessage, JoinMessage, RejectMessage},
+        dht::{
+            DiscoveryMessage,
+            DiscoveryResponseMessage,
+            FindNodeRequest,
+            FindNodeResponse,
+            JoinMessage,
+            PeerRecord,
+            RejectMessage,
+        },
         envelope::DhtMessageType,
     },
 };
 use log::*;
-use std::sync::Arc;
+use std::{cmp, sync::Arc};
 use tari_comms::{
     message::MessageExt,
     multiaddr::Multiaddr,
     peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerFlags, PeerManager},
     pipeline::PipelineError,
     types::CommsPublicKey,
+    utils::proof_of_work,
 };
 use tari_utilities::{hex::Hex, ByteArray};
 use tower::{Service, ServiceExt};
@@ -105,6 +114,14 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
                 .handle_message_reject(message)
                 .await
                 .map_err(PipelineError::from_debug)?,
+            DhtMessageType::FindNodeRequest => self
+                .handle_find_node_request(message)
+                .await
+                .map_err(PipelineError::from_debug)?,
+            DhtMessageType::FindNodeResponse => self
+                .handle_find_node_response(message)
+                .await
+                .map_err(PipelineError::from_debug)?,
             // Not a DHT message, call downstream middleware
             _ => {
                 trace!(target: LOG_TARGET, "Passing message onto next service");
@@ -121,6 +138,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         node_id: NodeId,
         net_addresses: Vec<Multiaddr>,
         peer_features: PeerFeatures,
+        pow_nonce: u64,
     ) -> Result<Peer, DhtInboundError>
     {
         let peer_manager = &self.peer_manager;
@@ -151,6 +169,8 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
                 ))
                 .await?;
         }
+        // The nonce has been verified and is included in the peer records sent to other peers
+        peer_manager.set_proof_of_work_nonce(pubkey, pow_nonce).await?;
 
         let peer = peer_manager.find_by_public_key(&pubkey).await?;
 
@@ -171,6 +191,20 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         }
     }
 
+    fn validate_proof_of_work(&self, public_key: &CommsPublicKey, nonce: u64) -> Result<(), DhtInboundError> {
+        if proof_of_work::verify(public_key, nonce, self.config.pow_difficulty) {
+            Ok(())
+        } else {
+            warn!(
+                target: LOG_TARGET,
+                "Peer '{}' did not meet the required proof-of-work difficulty of {}",
+                public_key,
+                self.config.pow_difficulty
+            );
+            Err(DhtInboundError::InsufficientProofOfWork)
+        }
+    }
+
     async fn handle_join(&mut self, message: DecryptedDhtMessage) -> Result<(), DhtInboundError> {
         let DecryptedDhtMessage {
             decryption_result,
@@ -211,6 +245,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         }
 
         let node_id = self.validate_raw_node_id(&authenticated_pk, &join_msg.node_id)?;
+        self.validate_proof_of_work(&authenticated_pk, join_msg.pow_nonce)?;
 
         let origin_peer = self
             .add_or_update_peer(
@@ -218,6 +253,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
                 node_id,
                 addresses,
                 PeerFeatures::from_bits_truncate(join_msg.peer_features),
+                join_msg.pow_nonce,
             )
             .await?;
 
@@ -369,12 +405,14 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         }
 
         let node_id = self.validate_raw_node_id(&authenticated_pk, &discover_msg.node_id)?;
+        self.validate_proof_of_work(&authenticated_pk, discover_msg.pow_nonce)?;
         let origin_peer = self
             .add_or_update_peer(
                 &authenticated_pk,
                 node_id,
                 addresses,
                 PeerFeatures::from_bits_truncate(discover_msg.peer_features),
+                discover_msg.pow_nonce,
             )
             .await?;
 
@@ -394,6 +432,92 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         Ok(())
     }
 
+    async fn handle_find_node_request(&mut self, message: DecryptedDhtMessage) -> Result<(), DhtInboundError> {
+        let msg = message
+            .success()
+            .expect("already checked that this message decrypted successfully");
+
+        let request = msg
+            .decode_part::<FindNodeRequest>(0)?
+            .ok_or_else(|| DhtInboundError::InvalidMessageBody)?;
+
+        let target = NodeId::from_bytes(&request.target).map_err(|_| DhtInboundError::InvalidNodeId)?;
+        let source_peer = message.source_peer;
+        trace!(
+            target: LOG_TARGET,
+            "Received find node request for '{}' from '{}'",
+            target.short_str(),
+            source_peer.node_id.short_str()
+        );
+
+        let num_peers = cmp::min(request.num_peers as usize, self.config.num_neighbouring_nodes);
+        let mut peers = Vec::with_capacity(num_peers);
+        let mut excluded_peers = vec![source_peer.public_key.clone()];
+        // The target is always returned if it is known, even if it is currently offline, so that the requester at least
+        // learns its addresses
+        match self.peer_manager.find_by_node_id(&target).await {
+            Ok(peer) => {
+                excluded_peers.push(peer.public_key.clone());
+                if !peer.is_banned() && peer.public_key != source_peer.public_key {
+                    peers.push(peer);
+                }
+            },
+            Err(err) if err.is_peer_not_found() => {},
+            Err(err) => return Err(err.into()),
+        }
+
+        let closest = self
+            .peer_manager
+            .closest_peers(&target, num_peers.saturating_sub(peers.len()), &excluded_peers, None)
+            .await?;
+        peers.extend(closest);
+        // Peers without a known proof-of-work nonce that meets the difficulty would be rejected by the requester
+        let pow_difficulty = self.config.pow_difficulty;
+        peers.retain(|peer| {
+            proof_of_work::verify(&peer.public_key, peer.proof_of_work_nonce.unwrap_or(0), pow_difficulty)
+        });
+
+        let response = FindNodeResponse {
+            peers: peers.iter().map(PeerRecord::from).collect(),
+            nonce: request.nonce,
+        };
+
+        trace!(
+            target: LOG_TARGET,
+            "Sending find node response containing {} peer(s) to {}",
+            response.peers.len(),
+            source_peer.public_key
+        );
+        self.outbound_service
+            .send_message_no_header(
+                SendMessageParams::new()
+                    .direct_public_key(source_peer.public_key.clone())
+                    .with_destination(NodeDestination::Unknown)
+                    .with_dht_message_type(DhtMessageType::FindNodeResponse)
+                    .finish(),
+                response,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn handle_find_node_response(&mut self, message: DecryptedDhtMessage) -> Result<(), DhtInboundError> {
+        let msg = message
+            .success()
+            .expect("already checked that this message decrypted successfully");
+
+        let response = msg
+            .decode_part::<FindNodeResponse>(0)?
+            .ok_or_else(|| DhtInboundError::InvalidMessageBody)?;
+
+        self.discovery_requester
+            .notify_find_node_response_received(message.source_peer.public_key.clone(), response)
+            .await?;
+
+        Ok(())
+    }
+
     /// Send a network join update request directly to a specific known peer
     async fn send_join_direct(&mut self, dest_public_key: CommsPublicKey) -> Result<(), DhtInboundError> {
         let join_msg = JoinMessage::from(&self.node_identity);
@@ -427,6 +551,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
             addresses: vec![self.node_identity.public_address().to_string()],
             peer_features: self.node_identity.features().bits(),
             nonce,
+            pow_nonce: self.node_identity.proof_of_work_nonce().unwrap_or(0),
         };
 
         trace!(target: LOG_TARGET, "Sending discovery response to {}", dest_public_key);
@@ -38,6 +38,8 @@ pub enum DhtInboundError {
     InvalidNodeId,
     /// All given addresses were invalid
     InvalidAddresses,
+    /// The peer's proof-of-work does not meet the required difficulty
+    InsufficientProofOfWork,
     DhtDiscoveryError(DhtDiscoveryError),
     #[error(msg_embedded, no_from, non_std)]
     OriginRequired(String),
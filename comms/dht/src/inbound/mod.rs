@@ -34,3 +34,33 @@ pub use self::{
     message::{DecryptedDhtMessage, DhtInboundMessage},
     validate::ValidateLayer,
 };
+
+use log::*;
+use tari_comms::peer_manager::{Offence, Peer, PeerManager};
+
+const LOG_TARGET: &str = "comms::dht::inbound";
+
+/// Report an offence committed by the peer that sent us the message. Errors are logged and otherwise ignored, because
+/// failing to record the offence should not affect message handling.
+async fn report_offence(peer_manager: &PeerManager, source_peer: &Peer, offence: Offence) {
+    match peer_manager.report_offence(&source_peer.public_key, offence).await {
+        Ok(true) => {
+            warn!(
+                target: LOG_TARGET,
+                "Peer '{}' has been banned because of repeated offences ({})",
+                source_peer.node_id.short_str(),
+                offence
+            );
+        },
+        Ok(false) => {},
+        Err(err) => {
+            debug!(
+                target: LOG_TARGET,
+                "Failed to report offence '{}' for peer '{}': {:?}",
+                offence,
+                source_peer.node_id.short_str(),
+                err
+            );
+        },
+    }
+}
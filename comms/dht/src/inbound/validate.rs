@@ -20,11 +20,17 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::{inbound::DhtInboundMessage, proto::envelope::Network};
+use crate::{
+    inbound::{report_offence, DhtInboundMessage},
+    proto::envelope::Network,
+};
 use futures::{task::Context, Future};
 use log::*;
-use std::task::Poll;
-use tari_comms::pipeline::PipelineError;
+use std::{sync::Arc, task::Poll};
+use tari_comms::{
+    peer_manager::{Offence, PeerManager},
+    pipeline::PipelineError,
+};
 use tower::{layer::Layer, Service, ServiceExt};
 
 const LOG_TARGET: &str = "comms::dht::validate";
@@ -32,18 +38,21 @@ const LOG_TARGET: &str = "comms::dht::validate";
 /// # DHT validation middleware
 ///
 /// Takes in a `DhtInboundMessage` and checks the message header for any invalid fields
-/// If an invalid message is detected a rejection message is sent to the sending peer.
+/// If an invalid message is detected a rejection message is sent to the sending peer. Messages with an invalid header
+/// are reported as an offence against the sending peer.
 #[derive(Clone)]
 pub struct ValidateMiddleware<S> {
     next_service: S,
     target_network: Network,
+    peer_manager: Arc<PeerManager>,
 }
 
 impl<S> ValidateMiddleware<S> {
-    pub fn new(service: S, target_network: Network) -> Self {
+    pub fn new(service: S, target_network: Network, peer_manager: Arc<PeerManager>) -> Self {
         Self {
             next_service: service,
             target_network,
+            peer_manager,
         }
     }
 }
@@ -63,11 +72,15 @@ where S: Service<DhtInboundMessage, Response = (), Error = PipelineError> + Clon
     fn call(&mut self, message: DhtInboundMessage) -> Self::Future {
         let next_service = self.next_service.clone();
         let target_network = self.target_network;
+        let peer_manager = Arc::clone(&self.peer_manager);
         async move {
             if message.dht_header.network == target_network && message.dht_header.is_valid() {
                 debug!(target: LOG_TARGET, "Passing message {} to next service", message.tag);
                 next_service.oneshot(message).await?;
             } else {
+                if !message.dht_header.is_valid() {
+                    report_offence(&peer_manager, &message.source_peer, Offence::InvalidMessage).await;
+                }
                 warn!(
                     target: LOG_TARGET,
                     "Message is for another network (want = {:?} got = {:?}) or message header is invalid. Discarding \
@@ -84,11 +97,15 @@ where S: Service<DhtInboundMessage, Response = (), Error = PipelineError> + Clon
 
 pub struct ValidateLayer {
     target_network: Network,
+    peer_manager: Arc<PeerManager>,
 }
 
 impl ValidateLayer {
-    pub fn new(target_network: Network) -> Self {
-        Self { target_network }
+    pub fn new(target_network: Network, peer_manager: Arc<PeerManager>) -> Self {
+        Self {
+            target_network,
+            peer_manager,
+        }
     }
 }
 
@@ -96,7 +113,7 @@ impl<S> Layer<S> for ValidateLayer {
     type Service = ValidateMiddleware<S>;
 
     fn layer(&self, service: S) -> Self::Service {
-        ValidateMiddleware::new(service, self.target_network)
+        ValidateMiddleware::new(service, self.target_network, Arc::clone(&self.peer_manager))
     }
 }
 
@@ -105,7 +122,7 @@ mod test {
     use super::*;
     use crate::{
         envelope::DhtMessageFlags,
-        test_utils::{make_dht_inbound_message, make_node_identity, service_spy},
+        test_utils::{make_dht_inbound_message, make_node_identity, make_peer_manager, service_spy},
     };
     use tari_test_utils::panic_context;
     use tokio::runtime::Runtime;
@@ -115,7 +132,8 @@ mod test {
         let mut rt = Runtime::new().unwrap();
         let spy = service_spy();
 
-        let mut validate = ValidateLayer::new(Network::LocalTest).layer(spy.to_service::<PipelineError>());
+        let mut validate =
+            ValidateLayer::new(Network::LocalTest, make_peer_manager()).layer(spy.to_service::<PipelineError>());
 
         panic_context!(cx);
 
@@ -111,6 +111,8 @@
 extern crate diesel;
 #[macro_use]
 extern crate diesel_migrations;
+#[macro_use]
+extern crate lazy_static;
 
 #[macro_use]
 mod macros;
@@ -144,6 +146,7 @@ mod dedup;
 pub use dedup::DedupLayer;
 
 mod logging_middleware;
+mod metrics;
 mod proto;
 mod tower_filter;
 mod utils;
@@ -0,0 +1,63 @@
+// Copyright 2020, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::envelope::DhtMessageType;
+use std::time::Duration;
+use tari_metrics::{Histogram, IntCounterVec, IntGauge};
+
+lazy_static! {
+    static ref INBOUND_MESSAGES: IntCounterVec = tari_metrics::register_int_counter_vec(
+        "comms_dht_inbound_messages_total",
+        "Number of inbound DHT messages by DHT message type",
+        &["message_type"]
+    );
+    static ref OUTBOUND_MESSAGES: IntCounterVec = tari_metrics::register_int_counter_vec(
+        "comms_dht_outbound_messages_total",
+        "Number of outbound DHT messages by DHT message type",
+        &["message_type"]
+    );
+    static ref SAF_STORED_MESSAGES: IntGauge = tari_metrics::register_int_gauge(
+        "comms_dht_saf_stored_messages",
+        "Number of messages held in the store and forward database"
+    );
+    static ref DISCOVERY_LATENCY: Histogram = tari_metrics::register_histogram(
+        "comms_dht_discovery_latency_seconds",
+        "Time taken for a peer discovery to complete",
+        tari_metrics::DURATION_BUCKETS
+    );
+}
+
+pub fn inc_inbound_messages(message_type: DhtMessageType) {
+    INBOUND_MESSAGES.with_label_values(&[&message_type.to_string()]).inc();
+}
+
+pub fn inc_outbound_messages(message_type: DhtMessageType) {
+    OUTBOUND_MESSAGES.with_label_values(&[&message_type.to_string()]).inc();
+}
+
+pub fn set_saf_stored_messages(num_messages: usize) {
+    SAF_STORED_MESSAGES.set(num_messages as i64);
+}
+
+pub fn observe_discovery_latency(latency: Duration) {
+    DISCOVERY_LATENCY.observe(latency.as_secs_f64());
+}
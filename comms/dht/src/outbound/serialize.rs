@@ -22,8 +22,9 @@
 
 use crate::{
     consts::DHT_ENVELOPE_HEADER_VERSION,
+    metrics,
     outbound::message::DhtOutboundMessage,
-    proto::envelope::{DhtEnvelope, DhtHeader},
+    proto::envelope::{DhtEnvelope, DhtHeader, DhtMessageType},
 };
 use futures::{task::Context, Future};
 use log::*;
@@ -90,6 +91,9 @@ where S: Service<OutboundMessage, Response = (), Error = PipelineError> + Clone
                 flags: dht_flags.bits(),
                 destination: Some(destination.into()),
             });
+            metrics::inc_outbound_messages(
+                DhtMessageType::from_i32(dht_header.message_type).unwrap_or(DhtMessageType::None),
+            );
             let envelope = DhtEnvelope::new(dht_header, body);
 
             let body = Bytes::from(envelope.to_encoded_bytes());
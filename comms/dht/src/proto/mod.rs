@@ -20,10 +20,13 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::proto::{dht::JoinMessage, envelope::Network};
+use crate::proto::{
+    dht::{JoinMessage, PeerRecord},
+    envelope::Network,
+};
 use rand::{rngs::OsRng, RngCore};
 use std::fmt;
-use tari_comms::NodeIdentity;
+use tari_comms::{peer_manager::Peer, NodeIdentity};
 use tari_utilities::{hex::Hex, ByteArray};
 
 #[path = "tari.dht.envelope.rs"]
@@ -81,6 +84,7 @@ impl<T: AsRef<NodeIdentity>> From<T> for JoinMessage {
             addresses: vec![node_identity.public_address().to_string()],
             peer_features: node_identity.features().bits(),
             nonce: OsRng.next_u64(),
+            pow_nonce: node_identity.proof_of_work_nonce().unwrap_or(0),
         }
     }
 }
@@ -96,3 +100,42 @@ impl fmt::Display for dht::JoinMessage {
         )
     }
 }
+
+//---------------------------------- PeerRecord --------------------------------------------//
+
+impl From<&Peer> for PeerRecord {
+    fn from(peer: &Peer) -> Self {
+        Self {
+            public_key: peer.public_key.to_vec(),
+            addresses: peer.addresses.address_iter().map(ToString::to_string).collect(),
+            peer_features: peer.features.bits(),
+            pow_nonce: peer.proof_of_work_nonce.unwrap_or(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Arc;
+    use tari_comms::peer_manager::PeerFeatures;
+
+    #[test]
+    fn join_message_nonces() {
+        let node_identity = Arc::new(
+            NodeIdentity::random_with_proof_of_work(
+                &mut OsRng,
+                "/ip4/127.0.0.1/tcp/9000".parse().unwrap(),
+                PeerFeatures::COMMUNICATION_NODE,
+                4,
+            )
+            .unwrap(),
+        );
+        let join_msg1 = JoinMessage::from(&node_identity);
+        let join_msg2 = JoinMessage::from(&node_identity);
+        // Each join is unique so that repeated joins are not discarded as duplicates
+        assert_ne!(join_msg1.nonce, join_msg2.nonce);
+        assert_eq!(join_msg1.pow_nonce, node_identity.proof_of_work_nonce().unwrap());
+        assert_eq!(join_msg2.pow_nonce, node_identity.proof_of_work_nonce().unwrap());
+    }
+}
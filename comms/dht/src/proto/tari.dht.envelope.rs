@@ -64,10 +64,16 @@ pub enum DhtMessageType {
     DiscoveryResponse = 3,
     /// Message was rejected
     RejectMsg = 4,
+    /// Request for the closest peers to a node id
+    FindNodeRequest = 5,
+    /// Response to a find node request
+    FindNodeResponse = 6,
     /// Request stored messages from a node
     SafRequestMessages = 20,
     /// Stored messages response
     SafStoredMessages = 21,
+    /// Acknowledgement of received stored messages
+    SafAcknowledgeMessages = 22,
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
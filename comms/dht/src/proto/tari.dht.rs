@@ -14,6 +14,9 @@ pub struct JoinMessage {
     pub peer_features: u64,
     #[prost(uint64, tag = "4")]
     pub nonce: u64,
+    /// The proof-of-work nonce for the node's public key
+    #[prost(uint64, tag = "5")]
+    pub pow_nonce: u64,
 }
 /// The DiscoverMessage stores the information required for a network discover request.
 ///
@@ -30,6 +33,9 @@ pub struct DiscoveryMessage {
     pub peer_features: u64,
     #[prost(uint64, tag = "4")]
     pub nonce: u64,
+    /// The proof-of-work nonce for the node's public key
+    #[prost(uint64, tag = "5")]
+    pub pow_nonce: u64,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DiscoveryResponseMessage {
@@ -41,6 +47,44 @@ pub struct DiscoveryResponseMessage {
     pub peer_features: u64,
     #[prost(uint64, tag = "4")]
     pub nonce: u64,
+    /// The proof-of-work nonce for the node's public key
+    #[prost(uint64, tag = "5")]
+    pub pow_nonce: u64,
+}
+/// Request for the peers closest to the target node id that are known by the receiving node. This is sent directly
+/// to peers during an iterative node lookup.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FindNodeRequest {
+    /// The node id of the peer being looked up
+    #[prost(bytes, tag = "1")]
+    pub target: std::vec::Vec<u8>,
+    /// The maximum number of peers to return
+    #[prost(uint32, tag = "2")]
+    pub num_peers: u32,
+    #[prost(uint64, tag = "3")]
+    pub nonce: u64,
+}
+/// Response to a FindNodeRequest containing the peers closest to the target node id known by the responding node
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FindNodeResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub peers: ::std::vec::Vec<PeerRecord>,
+    /// The nonce of the FindNodeRequest
+    #[prost(uint64, tag = "2")]
+    pub nonce: u64,
+}
+/// Contact information for a peer
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PeerRecord {
+    #[prost(bytes, tag = "1")]
+    pub public_key: std::vec::Vec<u8>,
+    #[prost(string, repeated, tag = "2")]
+    pub addresses: ::std::vec::Vec<std::string::String>,
+    #[prost(uint64, tag = "3")]
+    pub peer_features: u64,
+    /// The proof-of-work nonce for the peer's public key
+    #[prost(uint64, tag = "4")]
+    pub pow_nonce: u64,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RejectMessage {
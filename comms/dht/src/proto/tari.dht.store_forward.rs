@@ -48,3 +48,10 @@ pub mod stored_messages_response {
         InRegion = 4,
     }
 }
+/// Acknowledges that the stored messages with the given body hashes were received. The storing node may then delete
+/// these messages.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StoredMessagesAck {
+    #[prost(bytes, repeated, tag = "1")]
+    pub message_hashes: ::std::vec::Vec<std::vec::Vec<u8>>,
+}
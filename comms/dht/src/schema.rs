@@ -19,6 +19,8 @@ table! {
         is_encrypted -> Bool,
         priority -> Integer,
         stored_at -> Timestamp,
+        body_hash -> Text,
+        source_pubkey -> Text,
     }
 }
 
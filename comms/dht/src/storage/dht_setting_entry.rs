@@ -27,6 +27,8 @@ use std::fmt;
 pub enum DhtMetadataKey {
     /// Timestamp each time the DHT is shut down
     OfflineTimestamp,
+    /// The public address of this node at the time the last join message was sent
+    JoinAddress,
 }
 
 impl fmt::Display for DhtMetadataKey {
@@ -57,6 +57,39 @@ impl StoreAndForwardDatabase {
             .await
     }
 
+    /// Returns the total number of stored messages
+    pub async fn count_messages(&self) -> Result<usize, StorageError> {
+        self.connection
+            .with_connection_async(|conn| {
+                let count = stored_messages::table.count().get_result::<i64>(conn)?;
+                Ok(count as usize)
+            })
+            .await
+    }
+
+    /// Returns the number of stored messages from the given origin. If the origin is not known (e.g. the message could
+    /// not be decrypted), messages are counted against the peer that sent the message to this node.
+    pub async fn count_messages_from_origin(
+        &self,
+        origin_pubkey: Option<String>,
+        source_pubkey: String,
+    ) -> Result<usize, StorageError>
+    {
+        self.connection
+            .with_connection_async(move |conn| {
+                let query = stored_messages::table.count().into_boxed();
+                let query = match origin_pubkey {
+                    Some(origin_pubkey) => query.filter(stored_messages::origin_pubkey.eq(origin_pubkey)),
+                    None => query
+                        .filter(stored_messages::origin_pubkey.is_null())
+                        .filter(stored_messages::source_pubkey.eq(source_pubkey)),
+                };
+                let count = query.get_result::<i64>(conn)?;
+                Ok(count as usize)
+            })
+            .await
+    }
+
     pub async fn find_messages_for_peer(
         &self,
         public_key: &CommsPublicKey,
@@ -242,6 +275,32 @@ impl StoreAndForwardDatabase {
             .await
     }
 
+    /// Deletes the messages with the given body hashes that are destined for the given public key or node id. Returns
+    /// the number of messages that were deleted.
+    pub(crate) async fn delete_acknowledged_messages(
+        &self,
+        public_key: &CommsPublicKey,
+        node_id: &NodeId,
+        body_hashes: Vec<String>,
+    ) -> Result<usize, StorageError>
+    {
+        let pk_hex = public_key.to_hex();
+        let node_id_hex = node_id.to_hex();
+        self.connection
+            .with_connection_async(move |conn| {
+                diesel::delete(stored_messages::table)
+                    .filter(
+                        stored_messages::destination_pubkey
+                            .eq(pk_hex)
+                            .or(stored_messages::destination_node_id.eq(node_id_hex)),
+                    )
+                    .filter(stored_messages::body_hash.eq_any(body_hashes))
+                    .execute(conn)
+                    .map_err(Into::into)
+            })
+            .await
+    }
+
     pub(crate) async fn delete_messages_with_priority_older_than(
         &self,
         priority: StoredMessagePriority,
@@ -274,5 +333,69 @@ mod test {
         db.insert_message(Default::default()).await.unwrap();
         let messages = db.get_all_messages().await.unwrap();
         assert_eq!(messages.len(), 1);
+        assert_eq!(db.count_messages().await.unwrap(), 1);
+    }
+
+    #[tokio_macros::test_basic]
+    async fn count_messages_from_origin() {
+        let conn = DbConnection::connect_memory(random::string(8)).await.unwrap();
+        conn.migrate().await.unwrap();
+        let db = StoreAndForwardDatabase::new(conn);
+        db.insert_message(NewStoredMessage {
+            origin_pubkey: Some("origin".to_string()),
+            source_pubkey: "source".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        db.insert_message(NewStoredMessage {
+            source_pubkey: "source".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let count = db
+            .count_messages_from_origin(Some("origin".to_string()), "other".to_string())
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+        let count = db.count_messages_from_origin(None, "source".to_string()).await.unwrap();
+        assert_eq!(count, 1);
+        let count = db.count_messages_from_origin(None, "other".to_string()).await.unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[tokio_macros::test_basic]
+    async fn delete_acknowledged_messages() {
+        let conn = DbConnection::connect_memory(random::string(8)).await.unwrap();
+        conn.migrate().await.unwrap();
+        let db = StoreAndForwardDatabase::new(conn);
+        let public_key = CommsPublicKey::default();
+        let node_id = NodeId::default();
+        db.insert_message(NewStoredMessage {
+            destination_pubkey: Some(public_key.to_hex()),
+            body_hash: "hash1".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        // Not destined for the acknowledging peer
+        db.insert_message(NewStoredMessage {
+            destination_pubkey: Some("other".to_string()),
+            body_hash: "hash2".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let num_deleted = db
+            .delete_acknowledged_messages(&public_key, &node_id, vec!["hash1".to_string(), "hash2".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(num_deleted, 1);
+        let messages = db.get_all_messages().await.unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].body_hash, "hash2");
     }
 }
@@ -24,7 +24,7 @@ use crate::{
     inbound::DecryptedDhtMessage,
     proto::envelope::DhtHeader,
     schema::stored_messages,
-    store_forward::message::StoredMessagePriority,
+    store_forward::message::{message_body_hash, StoredMessagePriority},
 };
 use chrono::NaiveDateTime;
 use std::convert::TryInto;
@@ -43,6 +43,8 @@ pub struct NewStoredMessage {
     pub body: Vec<u8>,
     pub is_encrypted: bool,
     pub priority: i32,
+    pub body_hash: String,
+    pub source_pubkey: String,
 }
 
 impl NewStoredMessage {
@@ -52,6 +54,7 @@ impl NewStoredMessage {
             authenticated_origin,
             decryption_result,
             dht_header,
+            source_peer,
             ..
         } = message;
 
@@ -72,6 +75,8 @@ impl NewStoredMessage {
                 let dht_header: DhtHeader = dht_header.into();
                 dht_header.to_encoded_bytes()
             },
+            body_hash: message_body_hash(&body).to_hex(),
+            source_pubkey: source_peer.public_key.to_hex(),
             body,
         })
     }
@@ -90,4 +95,6 @@ pub struct StoredMessage {
     pub is_encrypted: bool,
     pub priority: i32,
     pub stored_at: NaiveDateTime,
+    pub body_hash: String,
+    pub source_pubkey: String,
 }
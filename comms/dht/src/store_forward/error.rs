@@ -78,4 +78,9 @@ pub enum StoreAndForwardError {
     /// Failed to send request for store and forward messages
     #[error(no_from)]
     RequestMessagesFailed(DhtOutboundError),
+    /// The store and forward storage capacity has been reached
+    StorageCapacityReached,
+    /// The origin of the message, or the peer that sent it if the origin is not known, has reached its store and
+    /// forward storage quota
+    OriginQuotaExceeded,
 }
@@ -31,7 +31,7 @@ use futures::{task::Context, Future};
 use log::*;
 use std::{sync::Arc, task::Poll};
 use tari_comms::{
-    peer_manager::{Peer, PeerManager},
+    peer_manager::{NodeId, Peer, PeerFeatures, PeerManager},
     pipeline::PipelineError,
     types::CommsPublicKey,
 };
@@ -43,14 +43,22 @@ const LOG_TARGET: &str = "comms::dht::storeforward::forward";
 pub struct ForwardLayer {
     peer_manager: Arc<PeerManager>,
     outbound_service: OutboundMessageRequester,
+    replication_factor: usize,
     is_enabled: bool,
 }
 
 impl ForwardLayer {
-    pub fn new(peer_manager: Arc<PeerManager>, outbound_service: OutboundMessageRequester, is_enabled: bool) -> Self {
+    pub fn new(
+        peer_manager: Arc<PeerManager>,
+        outbound_service: OutboundMessageRequester,
+        replication_factor: usize,
+        is_enabled: bool,
+    ) -> Self
+    {
         Self {
             peer_manager,
             outbound_service,
+            replication_factor,
             is_enabled,
         }
     }
@@ -65,6 +73,7 @@ impl<S> Layer<S> for ForwardLayer {
             // Pass in just the config item needed by the middleware for almost free copies
             Arc::clone(&self.peer_manager),
             self.outbound_service.clone(),
+            self.replication_factor,
             self.is_enabled,
         )
     }
@@ -78,6 +87,7 @@ pub struct ForwardMiddleware<S> {
     next_service: S,
     peer_manager: Arc<PeerManager>,
     outbound_service: OutboundMessageRequester,
+    replication_factor: usize,
     is_enabled: bool,
 }
 
@@ -86,6 +96,7 @@ impl<S> ForwardMiddleware<S> {
         service: S,
         peer_manager: Arc<PeerManager>,
         outbound_service: OutboundMessageRequester,
+        replication_factor: usize,
         is_enabled: bool,
     ) -> Self
     {
@@ -93,6 +104,7 @@ impl<S> ForwardMiddleware<S> {
             next_service: service,
             peer_manager,
             outbound_service,
+            replication_factor,
             is_enabled,
         }
     }
@@ -114,6 +126,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError> + Cl
         let next_service = self.next_service.clone();
         let peer_manager = Arc::clone(&self.peer_manager);
         let outbound_service = self.outbound_service.clone();
+        let replication_factor = self.replication_factor;
         let is_enabled = self.is_enabled;
         async move {
             if !is_enabled {
@@ -121,7 +134,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError> + Cl
                 return next_service.oneshot(message).await;
             }
 
-            let forwarder = Forwarder::new(next_service, peer_manager, outbound_service);
+            let forwarder = Forwarder::new(next_service, peer_manager, outbound_service, replication_factor);
             forwarder.handle(message).await
         }
     }
@@ -133,14 +146,22 @@ struct Forwarder<S> {
     peer_manager: Arc<PeerManager>,
     next_service: S,
     outbound_service: OutboundMessageRequester,
+    replication_factor: usize,
 }
 
 impl<S> Forwarder<S> {
-    pub fn new(service: S, peer_manager: Arc<PeerManager>, outbound_service: OutboundMessageRequester) -> Self {
+    pub fn new(
+        service: S,
+        peer_manager: Arc<PeerManager>,
+        outbound_service: OutboundMessageRequester,
+        replication_factor: usize,
+    ) -> Self
+    {
         Self {
             peer_manager,
             next_service: service,
             outbound_service,
+            replication_factor,
         }
     }
 }
@@ -232,7 +253,14 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
                     if is_discovery {
                         params.neighbours_include_clients(excluded_peers);
                     } else {
-                        params.neighbours(excluded_peers);
+                        match NodeId::from_key(&*dest_public_key) {
+                            Ok(dest_node_id) => {
+                                self.replicate_to_closest(&mut params, dest_node_id, excluded_peers);
+                            },
+                            Err(_) => {
+                                params.neighbours(excluded_peers);
+                            },
+                        }
                     }
                 }
             },
@@ -247,7 +275,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
                         if is_discovery {
                             params.neighbours_include_clients(excluded_peers);
                         } else {
-                            params.neighbours(excluded_peers);
+                            self.replicate_to_closest(&mut params, *dest_node_id, excluded_peers);
                         }
                     },
                 }
@@ -257,6 +285,23 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         Ok(params)
     }
 
+    /// Send to the `replication_factor` store and forward nodes closest to the destination, so that the message is
+    /// stored by the nodes that the destination will request stored messages from
+    fn replicate_to_closest(
+        &self,
+        params: &mut SendMessageParams,
+        dest_node_id: NodeId,
+        excluded_peers: Vec<CommsPublicKey>,
+    )
+    {
+        params.closest(
+            dest_node_id,
+            self.replication_factor,
+            excluded_peers,
+            PeerFeatures::DHT_STORE_FORWARD,
+        );
+    }
+
     fn destination_matches_source(&self, destination: &NodeDestination, source: &Peer) -> bool {
         if let Some(pk) = destination.public_key() {
             return pk == &source.public_key;
@@ -288,7 +333,7 @@ mod test {
         let peer_manager = make_peer_manager();
         let (oms_tx, mut oms_rx) = mpsc::channel(1);
         let oms = OutboundMessageRequester::new(oms_tx);
-        let mut service = ForwardLayer::new(peer_manager, oms, true).layer(spy.to_service::<PipelineError>());
+        let mut service = ForwardLayer::new(peer_manager, oms, 3, true).layer(spy.to_service::<PipelineError>());
 
         let node_identity = make_node_identity();
         let inbound_msg = make_dht_inbound_message(&node_identity, b"".to_vec(), DhtMessageFlags::empty(), false);
@@ -311,7 +356,8 @@ mod test {
         let oms_mock_state = oms_mock.get_state();
         rt.spawn(oms_mock.run());
 
-        let mut service = ForwardLayer::new(peer_manager, oms_requester, true).layer(spy.to_service::<PipelineError>());
+        let mut service =
+            ForwardLayer::new(peer_manager, oms_requester, 3, true).layer(spy.to_service::<PipelineError>());
 
         let sample_body = b"Lorem ipsum";
         let inbound_msg = make_dht_inbound_message(
@@ -28,6 +28,7 @@ use crate::{
     store_forward::{database, StoreAndForwardError},
 };
 use chrono::{DateTime, NaiveDateTime, Utc};
+use digest::Digest;
 use prost::Message;
 use prost_types::Timestamp;
 use rand::{rngs::OsRng, RngCore};
@@ -35,6 +36,7 @@ use std::{
     cmp,
     convert::{TryFrom, TryInto},
 };
+use tari_comms::types::Challenge;
 
 /// Utility function that converts a `chrono::DateTime<Utc>` to a `prost::Timestamp`
 pub(crate) fn datetime_to_timestamp(datetime: DateTime<Utc>) -> Timestamp {
@@ -50,6 +52,11 @@ pub(crate) fn timestamp_to_datetime(timestamp: Timestamp) -> DateTime<Utc> {
     DateTime::from_utc(naive, Utc)
 }
 
+/// Utility function that returns the hash used to identify a stored message body when acknowledging it
+pub(crate) fn message_body_hash(body: &[u8]) -> Vec<u8> {
+    Challenge::new().chain(body).result().to_vec()
+}
+
 impl StoredMessagesRequest {
     pub fn new() -> Self {
         Self {
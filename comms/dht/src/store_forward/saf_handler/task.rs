@@ -32,13 +32,14 @@ use crate::{
         store_forward::{
             stored_messages_response::SafResponseType,
             StoredMessage as ProtoStoredMessage,
+            StoredMessagesAck,
             StoredMessagesRequest,
             StoredMessagesResponse,
         },
     },
     store_forward::{
         error::StoreAndForwardError,
-        message::timestamp_to_datetime,
+        message::{message_body_hash, timestamp_to_datetime},
         service::FetchStoredMessageQuery,
         StoreAndForwardRequester,
     },
@@ -136,6 +137,21 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
                 .handle_stored_messages(message)
                 .await
                 .map_err(PipelineError::from_debug)?,
+
+            DhtMessageType::SafAcknowledgeMessages => {
+                if self.node_identity.has_peer_features(PeerFeatures::DHT_STORE_FORWARD) {
+                    self.handle_stored_messages_ack(message)
+                        .await
+                        .map_err(PipelineError::from_debug)?
+                } else {
+                    info!(
+                        target: LOG_TARGET,
+                        "Received store and forward acknowledgement from peer '{}' however, this node is not a store \
+                         and forward node. Acknowledgement ignored.",
+                        message.source_peer.node_id.short_str()
+                    );
+                }
+            },
             // Not a SAF message, call downstream middleware
             _ => {
                 trace!(target: LOG_TARGET, "Passing message onto next service");
@@ -244,6 +260,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
             .decode_part::<StoredMessagesResponse>(0)?
             .ok_or_else(|| StoreAndForwardError::InvalidEnvelopeBody)?;
         let source_peer = Arc::new(message.source_peer);
+        let mut outbound_service = self.outbound_service.clone();
 
         info!(
             target: LOG_TARGET,
@@ -255,14 +272,31 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
                 .unwrap_or("<Invalid>".to_string()),
         );
 
+        let message_hashes = response
+            .messages
+            .iter()
+            .map(|msg| message_body_hash(&msg.body))
+            .collect::<Vec<_>>();
+
         let tasks = response
             .messages
             .into_iter()
             // Map to futures which process the stored message
             .map(|msg| self.process_incoming_stored_message(Arc::clone(&source_peer), msg));
 
-        let successful_msgs_iter = future::join_all(tasks)
-            .await
+        let results = future::join_all(tasks).await;
+
+        // Acknowledge messages which this node has now received so that the storing node can remove them
+        let ack_hashes = results
+            .iter()
+            .zip(message_hashes)
+            .filter_map(|(result, hash)| match result {
+                Ok(_) | Err(StoreAndForwardError::DuplicateMessage) => Some(hash),
+                Err(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        let successful_msgs_iter = results
             .into_iter()
             .map(|result| {
                 match &result {
@@ -328,6 +362,61 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
             })
             .await;
 
+        if !ack_hashes.is_empty() {
+            debug!(
+                target: LOG_TARGET,
+                "Acknowledging {} stored message(s) received from peer '{}'",
+                ack_hashes.len(),
+                source_peer.node_id.short_str()
+            );
+            let send_result = outbound_service
+                .send_message_no_header(
+                    SendMessageParams::new()
+                        .direct_public_key(source_peer.public_key.clone())
+                        .with_dht_message_type(DhtMessageType::SafAcknowledgeMessages)
+                        .finish(),
+                    StoredMessagesAck {
+                        message_hashes: ack_hashes,
+                    },
+                )
+                .await;
+            if let Err(err) = send_result {
+                warn!(
+                    target: LOG_TARGET,
+                    "Failed to acknowledge stored messages received from peer '{}': {}",
+                    source_peer.node_id.short_str(),
+                    err
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_stored_messages_ack(&mut self, message: DecryptedDhtMessage) -> Result<(), StoreAndForwardError> {
+        let msg = message
+            .success()
+            .expect("already checked that this message decrypted successfully");
+        let ack = msg
+            .decode_part::<StoredMessagesAck>(0)?
+            .ok_or_else(|| StoreAndForwardError::InvalidEnvelopeBody)?;
+
+        debug!(
+            target: LOG_TARGET,
+            "Peer '{}' acknowledged {} stored message(s)",
+            message.source_peer.node_id.short_str(),
+            ack.message_hashes.len()
+        );
+
+        // Only messages destined for the acknowledging peer are removed
+        self.saf_requester
+            .remove_acknowledged_messages(
+                message.source_peer.public_key.clone(),
+                message.source_peer.node_id.clone(),
+                ack.message_hashes,
+            )
+            .await?;
+
         Ok(())
     }
 
@@ -499,6 +588,7 @@ mod test {
     use super::*;
     use crate::{
         envelope::DhtMessageFlags,
+        outbound::mock::create_outbound_service_mock,
         proto::envelope::DhtHeader,
         store_forward::{message::StoredMessagePriority, StoredMessage},
         test_utils::{
@@ -516,7 +606,9 @@ mod test {
     use chrono::Utc;
     use futures::channel::mpsc;
     use prost::Message;
+    use std::time::Duration;
     use tari_comms::{message::MessageExt, wrap_in_envelope_body};
+    use tari_test_utils::async_assert_eventually;
     use tari_utilities::hex::Hex;
     use tokio::runtime::Handle;
 
@@ -535,6 +627,8 @@ mod test {
             is_encrypted: false,
             priority: StoredMessagePriority::High as i32,
             stored_at: Utc::now().naive_utc(),
+            body_hash: message_body_hash(b"A").to_hex(),
+            source_pubkey: node_identity.public_key().to_hex(),
         }
     }
 
@@ -607,7 +701,9 @@ mod test {
         let (requester, _) = create_store_and_forward_mock();
 
         let peer_manager = make_peer_manager();
-        let (oms_tx, _) = mpsc::channel(1);
+        let (oms_requester, oms_mock) = create_outbound_service_mock(1);
+        let oms_mock_state = oms_mock.get_state();
+        rt_handle.spawn(oms_mock.run());
 
         let node_identity = make_node_identity();
 
@@ -662,7 +758,7 @@ mod test {
             requester,
             dht_requester,
             peer_manager,
-            OutboundMessageRequester::new(oms_tx),
+            oms_requester,
             node_identity,
             message,
         );
@@ -679,5 +775,59 @@ mod test {
         assert!(msgs.contains(&b"A".to_vec()));
         assert!(msgs.contains(&b"B".to_vec()));
         assert!(msgs.contains(&b"Clear".to_vec()));
+
+        // All received messages are acknowledged
+        assert_eq!(oms_mock_state.call_count(), 1);
+        let (params, body) = oms_mock_state.pop_call().unwrap();
+        assert_eq!(params.dht_message_type, DhtMessageType::SafAcknowledgeMessages);
+        let body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
+        let ack = body.decode_part::<StoredMessagesAck>(0).unwrap().unwrap();
+        assert_eq!(ack.message_hashes.len(), 3);
+        assert!(ack.message_hashes.contains(&message_body_hash(&msg1.body)));
+    }
+
+    #[tokio_macros::test_basic]
+    async fn receive_stored_messages_ack() {
+        let spy = service_spy();
+        let (requester, mock_state) = create_store_and_forward_mock();
+        let (oms_tx, _) = mpsc::channel(1);
+        let (dht_requester, _) = create_dht_actor_mock(1);
+        let node_identity = make_node_identity();
+
+        let hash = message_body_hash(b"A");
+        let mut message = DecryptedDhtMessage::succeeded(
+            wrap_in_envelope_body!(StoredMessagesAck {
+                message_hashes: vec![hash.clone()],
+            }),
+            None,
+            make_dht_inbound_message(&make_node_identity(), Vec::new(), DhtMessageFlags::ENCRYPTED, true),
+        );
+        message.dht_header.message_type = DhtMessageType::SafAcknowledgeMessages;
+        let source_public_key = message.source_peer.public_key.clone();
+
+        let task = MessageHandlerTask::new(
+            Default::default(),
+            spy.to_service::<PipelineError>(),
+            requester,
+            dht_requester,
+            make_peer_manager(),
+            OutboundMessageRequester::new(oms_tx),
+            node_identity,
+            message,
+        );
+
+        task.run().await.unwrap();
+        assert!(!spy.is_called());
+
+        async_assert_eventually!(
+            mock_state.call_count(),
+            expect = 1,
+            max_attempts = 10,
+            interval = Duration::from_millis(10),
+        );
+        let calls = mock_state.take_calls().await;
+        assert!(calls[0].starts_with("RemoveAcknowledgedMessages"));
+        assert!(calls[0].contains(&format!("{:?}", source_public_key)));
+        assert!(calls[0].contains(&format!("{:?}", hash)));
     }
 }
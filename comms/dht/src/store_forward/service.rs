@@ -28,6 +28,7 @@ use super::{
 };
 use crate::{
     envelope::DhtMessageType,
+    metrics,
     outbound::{OutboundMessageRequester, SendMessageParams},
     proto::store_forward::{stored_messages_response::SafResponseType, StoredMessagesRequest},
     storage::{DbConnection, DhtMetadataKey},
@@ -52,7 +53,7 @@ use tari_comms::{
     PeerManager,
 };
 use tari_shutdown::ShutdownSignal;
-use tari_utilities::ByteArray;
+use tari_utilities::{hex::Hex, ByteArray};
 use tokio::{sync::broadcast, task, time};
 
 const LOG_TARGET: &str = "comms::dht::storeforward::actor";
@@ -100,6 +101,7 @@ impl FetchStoredMessageQuery {
 pub enum StoreAndForwardRequest {
     FetchMessages(FetchStoredMessageQuery, oneshot::Sender<SafResult<Vec<StoredMessage>>>),
     InsertMessage(NewStoredMessage),
+    RemoveAcknowledgedMessages(Box<CommsPublicKey>, Box<NodeId>, Vec<Vec<u8>>),
     SendStoreForwardRequestToPeer(Box<NodeId>),
     SendStoreForwardRequestNeighbours,
 }
@@ -131,6 +133,25 @@ impl StoreAndForwardRequester {
         Ok(())
     }
 
+    /// Remove the stored messages with the given body hashes which were acknowledged by the peer they were stored for
+    pub async fn remove_acknowledged_messages(
+        &mut self,
+        public_key: CommsPublicKey,
+        node_id: NodeId,
+        message_hashes: Vec<Vec<u8>>,
+    ) -> SafResult<()>
+    {
+        self.sender
+            .send(StoreAndForwardRequest::RemoveAcknowledgedMessages(
+                Box::new(public_key),
+                Box::new(node_id),
+                message_hashes,
+            ))
+            .await
+            .map_err(|_| StoreAndForwardError::RequesterChannelClosed)?;
+        Ok(())
+    }
+
     pub async fn request_saf_messages_from_peer(&mut self, node_id: NodeId) -> SafResult<()> {
         self.sender
             .send(StoreAndForwardRequest::SendStoreForwardRequestToPeer(Box::new(node_id)))
@@ -247,20 +268,51 @@ impl StoreAndForwardService {
             InsertMessage(msg) => {
                 let public_key = msg.destination_pubkey.clone();
                 let node_id = msg.destination_node_id.clone();
-                match self.database.insert_message(msg).await {
-                    Ok(_) => info!(
-                        target: LOG_TARGET,
-                        "Stored message for {}",
-                        public_key
-                            .map(|p| format!("public key '{}'", p))
-                            .or_else(|| node_id.map(|n| format!("node id '{}'", n)))
-                            .unwrap_or_else(|| "<Anonymous>".to_string())
-                    ),
+                match self.insert_message(msg).await {
+                    Ok(_) => {
+                        info!(
+                            target: LOG_TARGET,
+                            "Stored message for {}",
+                            public_key
+                                .map(|p| format!("public key '{}'", p))
+                                .or_else(|| node_id.map(|n| format!("node id '{}'", n)))
+                                .unwrap_or_else(|| "<Anonymous>".to_string())
+                        );
+                        self.update_store_size_metric().await;
+                    },
+                    Err(err @ StoreAndForwardError::StorageCapacityReached) |
+                    Err(err @ StoreAndForwardError::OriginQuotaExceeded) => {
+                        warn!(target: LOG_TARGET, "Message not stored because '{}'", err);
+                    },
                     Err(err) => {
                         error!(target: LOG_TARGET, "InsertMessage failed because '{:?}'", err);
                     },
                 }
             },
+            RemoveAcknowledgedMessages(public_key, node_id, message_hashes) => {
+                let message_hashes = message_hashes.iter().map(|hash| hash.to_hex()).collect();
+                match self
+                    .database
+                    .delete_acknowledged_messages(&public_key, &node_id, message_hashes)
+                    .await
+                {
+                    Ok(num_removed) => {
+                        info!(
+                            target: LOG_TARGET,
+                            "Removed {} stored message(s) acknowledged by peer '{}'",
+                            num_removed,
+                            node_id.short_str()
+                        );
+                        self.update_store_size_metric().await;
+                    },
+                    Err(err) => {
+                        error!(
+                            target: LOG_TARGET,
+                            "RemoveAcknowledgedMessages failed because '{:?}'", err
+                        );
+                    },
+                }
+            },
             SendStoreForwardRequestToPeer(node_id) => {
                 if let Err(err) = self.request_stored_messages_from_peer(&node_id).await {
                     error!(target: LOG_TARGET, "Error sending store and forward request: {:?}", err);
@@ -277,6 +329,31 @@ impl StoreAndForwardService {
         }
     }
 
+    /// Insert the message if the storage capacity and the storage quota for the message origin have not been reached.
+    /// Messages without a known origin are counted against the separate, larger quota of the peer that sent them, as
+    /// a neighbour forwards such messages on behalf of many origins.
+    async fn insert_message(&self, message: NewStoredMessage) -> SafResult<()> {
+        if self.database.count_messages().await? >= self.config.saf_msg_cache_storage_capacity {
+            return Err(StoreAndForwardError::StorageCapacityReached);
+        }
+
+        let quota = if message.origin_pubkey.is_some() {
+            self.config.saf_max_messages_per_origin
+        } else {
+            self.config.saf_max_unknown_origin_messages_per_source
+        };
+        let num_from_origin = self
+            .database
+            .count_messages_from_origin(message.origin_pubkey.clone(), message.source_pubkey.clone())
+            .await?;
+        if num_from_origin >= quota {
+            return Err(StoreAndForwardError::OriginQuotaExceeded);
+        }
+
+        self.database.insert_message(message).await?;
+        Ok(())
+    }
+
     async fn handle_connection_manager_event(&mut self, event: &ConnectionManagerEvent) -> SafResult<()> {
         use ConnectionManagerEvent::*;
         if !self.config.saf_auto_request {
@@ -416,8 +493,21 @@ impl StoreAndForwardService {
             )
             .await?;
         info!(target: LOG_TARGET, "Cleaned {} old high priority messages", num_removed);
+        self.update_store_size_metric().await;
         Ok(())
     }
+
+    async fn update_store_size_metric(&self) {
+        match self.database.count_messages().await {
+            Ok(num_messages) => metrics::set_saf_stored_messages(num_messages),
+            Err(err) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Unable to count stored messages because '{:?}'", err
+                );
+            },
+        }
+    }
 }
 
 fn since(period: Duration) -> NaiveDateTime {
@@ -34,9 +34,9 @@ use crate::{
 };
 use futures::{task::Context, Future};
 use log::*;
-use std::{sync::Arc, task::Poll};
+use std::{cmp, sync::Arc, task::Poll};
 use tari_comms::{
-    peer_manager::{NodeIdentity, PeerFeatures, PeerManager},
+    peer_manager::{NodeId, NodeIdentity, PeerFeatures, PeerManager},
     pipeline::PipelineError,
 };
 use tower::{layer::Layer, Service, ServiceExt};
@@ -344,6 +344,21 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
             return Ok(None);
         }
 
+        let dest_node_id = match &message.dht_header.destination {
+            NodeDestination::Unknown => None,
+            NodeDestination::PublicKey(dest_public_key) => NodeId::from_key(&**dest_public_key).ok(),
+            NodeDestination::NodeId(dest_node_id) => Some((**dest_node_id).clone()),
+        };
+        if let Some(dest_node_id) = dest_node_id {
+            if !self.is_replica_node(&dest_node_id).await? {
+                log_not_eligible(&format!(
+                    "this node is not one of the {} closest store and forward nodes to the destination",
+                    self.config.saf_replication_factor
+                ));
+                return Ok(None);
+            }
+        }
+
         use NodeDestination::*;
         match &message.dht_header.destination {
             Unknown => {
@@ -396,6 +411,20 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         }
     }
 
+    /// Returns true if this node is one of the `saf_replication_factor` closest store and forward nodes to
+    /// `dest_node_id` that this node knows about
+    async fn is_replica_node(&self, dest_node_id: &NodeId) -> SafResult<bool> {
+        let threshold = self
+            .peer_manager
+            .calc_region_threshold(
+                dest_node_id,
+                cmp::max(1, self.config.saf_replication_factor),
+                PeerFeatures::COMMUNICATION_NODE,
+            )
+            .await?;
+        Ok(self.node_identity.node_id().distance(dest_node_id) <= threshold)
+    }
+
     async fn store(&mut self, priority: StoredMessagePriority, message: DecryptedDhtMessage) -> SafResult<()> {
         debug!(
             target: LOG_TARGET,
@@ -553,4 +582,36 @@ mod test {
         let duration = Utc::now().naive_utc().signed_duration_since(message.stored_at);
         assert!(duration.num_seconds() <= 5);
     }
+
+    #[tokio_macros::test_basic]
+    async fn decryption_failed_not_replica_node_no_store() {
+        let (requester, mock_state) = create_store_and_forward_mock();
+        let spy = service_spy();
+        let peer_manager = make_peer_manager();
+        let origin_node_identity = make_node_identity();
+        // The destination is the closest known node to itself, so with a replication factor of 1 this node should not
+        // store the message
+        peer_manager.add_peer(origin_node_identity.to_peer()).await.unwrap();
+        let node_identity = make_node_identity();
+        let config = DhtConfig {
+            saf_replication_factor: 1,
+            ..Default::default()
+        };
+        let mut service =
+            StoreLayer::new(config, peer_manager, node_identity, requester).layer(spy.to_service::<PipelineError>());
+
+        let mut inbound_msg = make_dht_inbound_message(
+            &origin_node_identity,
+            b"Will you keep this for me?".to_vec(),
+            DhtMessageFlags::ENCRYPTED,
+            true,
+        );
+        inbound_msg.dht_header.destination =
+            NodeDestination::PublicKey(Box::new(origin_node_identity.public_key().clone()));
+        let msg = DecryptedDhtMessage::failed(inbound_msg);
+        service.call(msg).await.unwrap();
+        assert!(spy.is_called());
+
+        assert_eq!(mock_state.call_count(), 0);
+    }
 }
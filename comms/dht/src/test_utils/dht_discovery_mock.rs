@@ -106,7 +106,11 @@ impl DhtDiscoveryMock {
                 let lock = self.state.discover_peer.read().unwrap();
                 reply_tx.send(Ok(lock.clone())).unwrap();
             },
-            NotifyDiscoveryResponseReceived(_) => {},
+            LookupNode(_, reply_tx) => {
+                let lock = self.state.discover_peer.read().unwrap();
+                reply_tx.send(Ok(lock.clone())).unwrap();
+            },
+            NotifyDiscoveryResponseReceived(_) | NotifyFindNodeResponseReceived(_, _) => {},
         }
     }
 }
@@ -128,7 +128,10 @@ impl StoreAndForwardMock {
                 is_encrypted: msg.is_encrypted,
                 priority: msg.priority,
                 stored_at: Utc::now().naive_utc(),
+                body_hash: msg.body_hash,
+                source_pubkey: msg.source_pubkey,
             }),
+            RemoveAcknowledgedMessages(_, _, _) => {},
             SendStoreForwardRequestToPeer(_) => {},
             SendStoreForwardRequestNeighbours => {},
         }
@@ -39,7 +39,7 @@ use log::*;
 use std::{fmt, sync::Arc, time::Duration};
 use tari_shutdown::{Shutdown, ShutdownSignal};
 use tokio::{sync::broadcast, time};
-use tower::Service;
+use tower::{layer::Layer, Service};
 
 const LOG_TARGET: &str = "comms::node";
 
@@ -170,7 +170,10 @@ where
 
         // Spawn inbound pipeline
         let bounded_executor = BoundedExecutor::new(executor.clone(), messaging_pipeline.max_concurrent_inbound_tasks);
-        let inbound = pipeline::Inbound::new(bounded_executor, inbound_message_rx, messaging_pipeline.inbound);
+        let inbound_service =
+            pipeline::RateLimitLayer::new(messaging_pipeline.inbound_rate_limit, Arc::clone(&peer_manager))
+                .layer(messaging_pipeline.inbound);
+        let inbound = pipeline::Inbound::new(bounded_executor, inbound_message_rx, inbound_service);
         executor.spawn(inbound.run());
 
         // Spawn outbound pipeline
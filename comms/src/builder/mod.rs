@@ -53,7 +53,7 @@ use crate::{
     message::InboundMessage,
     multiaddr::Multiaddr,
     noise::NoiseConfig,
-    peer_manager::{NodeIdentity, PeerManager},
+    peer_manager::{NodeIdentity, PeerManager, ReputationConfig},
     protocol::{messaging, messaging::MessagingProtocol, ProtocolNotification, Protocols},
     tor,
     transports::{SocksTransport, TcpWithTorTransport, Transport},
@@ -77,6 +77,7 @@ pub struct CommsBuilder<TTransport> {
     dial_backoff: Option<BoxedBackoff>,
     hidden_service: Option<tor::HiddenService>,
     connection_manager_config: ConnectionManagerConfig,
+    reputation_config: ReputationConfig,
     shutdown: Shutdown,
 }
 
@@ -104,6 +105,7 @@ impl Default for CommsBuilder<TcpWithTorTransport> {
             protocols: None,
             hidden_service: None,
             connection_manager_config: ConnectionManagerConfig::default(),
+            reputation_config: ReputationConfig::default(),
             shutdown: Shutdown::new(),
         }
     }
@@ -162,12 +164,26 @@ where
         self
     }
 
+    /// The maximum number of active inbound and outbound connections. Once either limit is exceeded, the least
+    /// valuable connection is evicted.
+    pub fn with_max_connections(mut self, max_inbound_connections: usize, max_outbound_connections: usize) -> Self {
+        self.connection_manager_config.max_inbound_connections = max_inbound_connections;
+        self.connection_manager_config.max_outbound_connections = max_outbound_connections;
+        self
+    }
+
     /// The number of dial attempts to make before giving up.
     pub fn with_max_dial_attempts(mut self, max_dial_attempts: usize) -> Self {
         self.connection_manager_config.max_dial_attempts = max_dial_attempts;
         self
     }
 
+    /// Set the configuration used to score peer offences and ban misbehaving peers.
+    pub fn with_reputation_config(mut self, reputation_config: ReputationConfig) -> Self {
+        self.reputation_config = reputation_config;
+        self
+    }
+
     /// Set the peer storage database to use.
     pub fn with_peer_storage(mut self, peer_storage: CommsDatabase) -> Self {
         self.peer_storage = Some(peer_storage);
@@ -190,6 +206,7 @@ where
             protocols: self.protocols,
             dial_backoff: self.dial_backoff,
             connection_manager_config: self.connection_manager_config,
+            reputation_config: self.reputation_config,
             shutdown: self.shutdown,
         }
     }
@@ -216,11 +233,12 @@ where
             protocols: self.protocols,
             dial_backoff: self.dial_backoff,
             connection_manager_config: self.connection_manager_config,
+            reputation_config: self.reputation_config,
             shutdown: self.shutdown,
         }
     }
 
-    pub fn with_protocols(mut self, protocols: Protocols<yamux::Stream>) -> Self {
+    pub fn with_protocols(mut self, protocols: Protocols<CommsSubstream>) -> Self {
         self.protocols = Some(protocols);
         self
     }
@@ -266,7 +284,9 @@ where
     fn make_peer_manager(&mut self) -> Result<Arc<PeerManager>, CommsBuilderError> {
         match self.peer_storage.take() {
             Some(storage) => {
-                let peer_manager = PeerManager::new(storage).map_err(CommsBuilderError::PeerManagerError)?;
+                let peer_manager = PeerManager::new(storage)
+                    .map_err(CommsBuilderError::PeerManagerError)?
+                    .with_reputation_config(self.reputation_config.clone());
                 Ok(Arc::new(peer_manager))
             },
             None => Err(CommsBuilderError::PeerStorageNotProvided),
@@ -0,0 +1,147 @@
+// Copyright 2020, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Eviction policy for active peer connections.
+//!
+//! When the number of connections in a direction exceeds the configured maximum, one connection is selected for
+//! eviction. The policy aims to keep a diverse and useful set of connections:
+//!
+//! 1. The closest peers to this node (DHT neighbours) are never evicted,
+//! 1. The longest-lived connections are never evicted, as these peers have proven to be stable,
+//! 1. Of the remaining connections, the peer is selected from the most represented class of peers (base nodes vs
+//!    clients) so that both remain represented,
+//! 1. Within that class, the most recently established connection is evicted.
+
+use crate::peer_manager::{node_id::NodeDistance, NodeId, PeerFeatures};
+use std::time::Duration;
+
+/// Information about an active connection used to select a connection to evict
+#[derive(Debug, Clone)]
+pub struct EvictionCandidate {
+    pub node_id: NodeId,
+    /// The distance between this node and the peer
+    pub distance: NodeDistance,
+    /// The length of time the connection has been active
+    pub connected_since: Duration,
+    pub features: PeerFeatures,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EvictionPolicy {
+    /// The number of closest peers that are protected from eviction
+    pub protected_neighbours: usize,
+    /// The number of longest-lived connections that are protected from eviction
+    pub protected_long_lived: usize,
+}
+
+impl EvictionPolicy {
+    /// Select a connection to evict from the given candidates. None is returned if all candidates are protected.
+    pub fn select(&self, mut candidates: Vec<EvictionCandidate>) -> Option<NodeId> {
+        // Protect DHT neighbours
+        candidates.sort_by(|a, b| a.distance.cmp(&b.distance));
+        let mut candidates = candidates.split_off(self.protected_neighbours.min(candidates.len()));
+
+        // Protect long-lived connections
+        candidates.sort_by(|a, b| b.connected_since.cmp(&a.connected_since));
+        let candidates = candidates.split_off(self.protected_long_lived.min(candidates.len()));
+
+        // Evict from the most represented class of peer
+        let (nodes, clients) = candidates
+            .into_iter()
+            .partition::<Vec<_>, _>(|c| c.features.contains(PeerFeatures::COMMUNICATION_NODE));
+        let selected_class = if nodes.len() >= clients.len() { nodes } else { clients };
+
+        // Evict the most recent connection
+        selected_class
+            .into_iter()
+            .min_by_key(|c| c.connected_since)
+            .map(|c| c.node_id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::node_id;
+
+    fn make_candidate(our_node_id: &NodeId, connected_since_secs: u64, features: PeerFeatures) -> EvictionCandidate {
+        let node_id = node_id::random();
+        EvictionCandidate {
+            distance: our_node_id.distance(&node_id),
+            node_id,
+            connected_since: Duration::from_secs(connected_since_secs),
+            features,
+        }
+    }
+
+    #[test]
+    fn select_evicts_most_recent_connection() {
+        let our_node_id = node_id::random();
+        let policy = EvictionPolicy {
+            protected_neighbours: 0,
+            protected_long_lived: 0,
+        };
+        let candidates = (1..=5)
+            .map(|i| make_candidate(&our_node_id, i * 10, PeerFeatures::COMMUNICATION_NODE))
+            .collect::<Vec<_>>();
+        let selected = policy.select(candidates.clone()).unwrap();
+        assert_eq!(selected, candidates[0].node_id);
+    }
+
+    #[test]
+    fn select_protects_neighbours_and_long_lived_connections() {
+        let our_node_id = node_id::random();
+        let policy = EvictionPolicy {
+            protected_neighbours: 1,
+            protected_long_lived: 1,
+        };
+        let mut candidates = (1..=3)
+            .map(|i| make_candidate(&our_node_id, i * 10, PeerFeatures::COMMUNICATION_NODE))
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| a.distance.cmp(&b.distance));
+        // The closest neighbour is the youngest connection
+        candidates[0].connected_since = Duration::from_secs(1);
+        candidates[1].connected_since = Duration::from_secs(100);
+        candidates[2].connected_since = Duration::from_secs(50);
+
+        let selected = policy.select(candidates.clone()).unwrap();
+        assert_eq!(selected, candidates[2].node_id);
+
+        assert!(policy.select(candidates[..2].to_vec()).is_none());
+    }
+
+    #[test]
+    fn select_evicts_from_most_represented_class() {
+        let our_node_id = node_id::random();
+        let policy = EvictionPolicy {
+            protected_neighbours: 0,
+            protected_long_lived: 0,
+        };
+        let candidates = vec![
+            make_candidate(&our_node_id, 1, PeerFeatures::COMMUNICATION_CLIENT),
+            make_candidate(&our_node_id, 10, PeerFeatures::COMMUNICATION_NODE),
+            make_candidate(&our_node_id, 20, PeerFeatures::COMMUNICATION_NODE),
+        ];
+        let selected = policy.select(candidates.clone()).unwrap();
+        assert_eq!(selected, candidates[1].node_id);
+    }
+}
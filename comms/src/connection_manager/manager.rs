@@ -23,7 +23,9 @@
 use super::{
     dialer::{Dialer, DialerRequest},
     error::ConnectionManagerError,
+    eviction::{EvictionCandidate, EvictionPolicy},
     listener::PeerListener,
+    metrics,
     peer_connection::{ConnId, PeerConnection},
     requester::ConnectionManagerRequest,
     types::ConnectionDirection,
@@ -35,7 +37,7 @@ use crate::{
     protocol::{ProtocolEvent, ProtocolId, Protocols},
     runtime,
     transports::Transport,
-    types::DEFAULT_LISTENER_ADDRESS,
+    types::{CommsSubstream, DEFAULT_LISTENER_ADDRESS},
     PeerManager,
 };
 use futures::{
@@ -72,7 +74,10 @@ pub enum ConnectionManagerEvent {
     ListenFailed(ConnectionManagerError),
 
     // Substreams
-    NewInboundSubstream(Box<NodeId>, ProtocolId, yamux::Stream),
+    NewInboundSubstream(Box<NodeId>, ProtocolId, CommsSubstream),
+
+    // Connection limits
+    PeerEvicted(Box<NodeId>, ConnectionDirection),
 }
 
 impl fmt::Display for ConnectionManagerEvent {
@@ -98,6 +103,7 @@ impl fmt::Display for ConnectionManagerEvent {
                 node_id.short_str(),
                 String::from_utf8_lossy(protocol)
             ),
+            PeerEvicted(node_id, direction) => write!(f, "PeerEvicted({}, {})", node_id.short_str(), direction),
         }
     }
 }
@@ -123,6 +129,16 @@ pub struct ConnectionManagerConfig {
     pub liveness_max_sessions: usize,
     /// CIDR blocks that whitelist liveness checks. Default: Localhost only (127.0.0.1/32)
     pub liveness_cidr_whitelist: Vec<cidr::AnyIpCidr>,
+    /// The maximum number of active inbound connections. Once this limit is exceeded, a connection is selected for
+    /// eviction. Default: 100
+    pub max_inbound_connections: usize,
+    /// The maximum number of active outbound connections. Once this limit is exceeded, a connection is selected for
+    /// eviction. Default: 50
+    pub max_outbound_connections: usize,
+    /// The number of closest peers (DHT neighbours) that are never evicted. Default: 8
+    pub eviction_protected_neighbours: usize,
+    /// The number of longest-lived connections that are never evicted. Default: 8
+    pub eviction_protected_long_lived: usize,
 }
 
 impl Default for ConnectionManagerConfig {
@@ -142,6 +158,10 @@ impl Default for ConnectionManagerConfig {
             liveness_max_sessions: 0,
             time_to_first_byte: Duration::from_secs(7),
             liveness_cidr_whitelist: vec![cidr::AnyIpCidr::V4("127.0.0.1/32".parse().unwrap())],
+            max_inbound_connections: 100,
+            max_outbound_connections: 50,
+            eviction_protected_neighbours: 8,
+            eviction_protected_long_lived: 8,
         }
     }
 }
@@ -157,7 +177,7 @@ pub struct ConnectionManager<TTransport, TBackoff> {
     node_identity: Arc<NodeIdentity>,
     active_connections: HashMap<NodeId, PeerConnection>,
     shutdown_signal: Option<ShutdownSignal>,
-    protocols: Protocols<yamux::Stream>,
+    protocols: Protocols<CommsSubstream>,
     listener_address: Option<Multiaddr>,
     listening_notifiers: Vec<oneshot::Sender<Multiaddr>>,
     connection_manager_events_tx: broadcast::Sender<Arc<ConnectionManagerEvent>>,
@@ -179,7 +199,7 @@ where
         request_rx: mpsc::Receiver<ConnectionManagerRequest>,
         node_identity: Arc<NodeIdentity>,
         peer_manager: Arc<PeerManager>,
-        protocols: Protocols<yamux::Stream>,
+        protocols: Protocols<CommsSubstream>,
         connection_manager_events_tx: broadcast::Sender<Arc<ConnectionManagerEvent>>,
         shutdown_signal: ShutdownSignal,
     ) -> Self
@@ -336,6 +356,26 @@ where
                     self.dial_peer(node_id, reply_tx).await
                 },
             },
+            DialUnverifiedPeer(peer, reply_tx) => match self.get_active_connection(&peer.node_id) {
+                Some(conn) => {
+                    log_if_error_fmt!(
+                        target: LOG_TARGET,
+                        reply_tx.send(Ok(conn.clone())),
+                        "Failed to send reply for dial request for peer '{}'",
+                        peer.node_id.short_str()
+                    );
+                },
+                None => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Dialing peer '{}' using an unverified peer record",
+                        peer.node_id.short_str()
+                    );
+                    if let Err(err) = self.dialer_tx.send(DialerRequest::Dial(peer, reply_tx)).await {
+                        error!(target: LOG_TARGET, "Failed to send request to dialer because '{}'", err);
+                    }
+                },
+            },
             NotifyListening(reply_tx) => match self.listener_address.as_ref() {
                 Some(addr) => {
                     let _ = reply_tx.send(addr.clone());
@@ -366,6 +406,7 @@ where
             },
             DisconnectPeer(node_id, reply_tx) => match self.active_connections.remove(&node_id) {
                 Some(mut conn) => {
+                    self.update_connection_metrics();
                     let _ = reply_tx.send(conn.disconnect().await.map_err(Into::into));
                 },
                 None => {
@@ -452,11 +493,13 @@ where
                             // Replace existing connection with new one
                             let existing_conn = self
                                 .active_connections
-                                .insert(node_id, new_conn.clone())
+                                .insert(node_id.clone(), new_conn.clone())
                                 .expect("Already checked");
 
                             self.delayed_disconnect(existing_conn);
+                            let direction = new_conn.direction();
                             self.publish_event(PeerConnected(new_conn));
+                            self.evict_if_required(direction, &node_id).await;
                         } else {
                             debug!(
                                 target: LOG_TARGET,
@@ -476,8 +519,10 @@ where
                             new_conn.direction(),
                             new_conn.peer_node_id().short_str()
                         );
-                        self.active_connections.insert(node_id, new_conn.clone());
+                        self.active_connections.insert(node_id.clone(), new_conn.clone());
+                        let direction = new_conn.direction();
                         self.publish_event(PeerConnected(new_conn));
+                        self.evict_if_required(direction, &node_id).await;
                     },
                 }
             },
@@ -487,9 +532,14 @@ where
                 }
             },
             PeerConnectFailed(node_id, err) => {
-                if let Err(err) = self.peer_manager.set_last_connect_failed(&node_id).await {
-                    error!(target: LOG_TARGET, "set_peer_connect_failed failed because '{:?}'", err);
+                match self.peer_manager.set_last_connect_failed(&node_id).await {
+                    // A peer dialed using an unverified peer record is not in the peer manager
+                    Err(err) if !err.is_peer_not_found() => {
+                        error!(target: LOG_TARGET, "set_peer_connect_failed failed because '{:?}'", err);
+                    },
+                    _ => {},
                 }
+                metrics::inc_dial_failures();
                 self.publish_event(PeerConnectFailed(node_id, err));
             },
             event => {
@@ -497,6 +547,8 @@ where
             },
         }
 
+        self.update_connection_metrics();
+
         trace!(
             target: LOG_TARGET,
             "[ThisNode={}] {} active connection(s)",
@@ -505,6 +557,19 @@ where
         );
     }
 
+    fn update_connection_metrics(&self) {
+        let num_inbound = self
+            .active_connections
+            .values()
+            .filter(|conn| conn.direction().is_inbound())
+            .count();
+        metrics::set_connections(ConnectionDirection::Inbound, num_inbound);
+        metrics::set_connections(
+            ConnectionDirection::Outbound,
+            self.active_connections.len() - num_inbound,
+        );
+    }
+
     #[inline]
     async fn send_dialer_request(&mut self, req: DialerRequest) {
         if let Err(err) = self.dialer_tx.send(req).await {
@@ -535,6 +600,87 @@ where
         }
     }
 
+    /// Evict a connection if the number of active connections in the given direction exceeds the configured maximum.
+    /// The newly connected peer is not considered for eviction if the connection is outbound, because this node
+    /// requested the connection.
+    async fn evict_if_required(&mut self, direction: ConnectionDirection, new_peer: &NodeId) {
+        let max_connections = match direction {
+            ConnectionDirection::Inbound => self.config.max_inbound_connections,
+            ConnectionDirection::Outbound => self.config.max_outbound_connections,
+        };
+
+        let connections = self
+            .active_connections
+            .values()
+            .filter(|conn| conn.direction() == direction && conn.is_connected())
+            .cloned()
+            .collect::<Vec<_>>();
+        if connections.len() <= max_connections {
+            return;
+        }
+        let connections = connections
+            .into_iter()
+            .filter(|conn| direction.is_inbound() || conn.peer_node_id() != new_peer);
+
+        let our_node_id = self.node_identity.node_id();
+        let mut candidates = Vec::new();
+        for conn in connections {
+            let features = match self.peer_manager.get_peer_features(conn.peer_node_id()).await {
+                Ok(features) => features,
+                Err(err) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Unable to fetch features for peer '{}' because '{:?}'",
+                        conn.peer_node_id().short_str(),
+                        err
+                    );
+                    continue;
+                },
+            };
+            candidates.push(EvictionCandidate {
+                node_id: conn.peer_node_id().clone(),
+                distance: our_node_id.distance(conn.peer_node_id()),
+                connected_since: conn.connected_since(),
+                features,
+            });
+        }
+
+        let policy = EvictionPolicy {
+            protected_neighbours: self.config.eviction_protected_neighbours,
+            protected_long_lived: self.config.eviction_protected_long_lived,
+        };
+
+        let node_id = match policy.select(candidates) {
+            Some(node_id) => node_id,
+            None => {
+                debug!(
+                    target: LOG_TARGET,
+                    "{} connection limit ({}) exceeded but all connections are protected from eviction",
+                    direction,
+                    max_connections
+                );
+                return;
+            },
+        };
+
+        if let Some(conn) = self.active_connections.remove(&node_id) {
+            debug!(
+                target: LOG_TARGET,
+                "Evicting {} connection to peer '{}' because the connection limit ({}) was exceeded",
+                direction,
+                node_id.short_str(),
+                max_connections
+            );
+            self.publish_event(ConnectionManagerEvent::PeerConnectWillClose(
+                conn.id(),
+                Box::new(node_id.clone()),
+                direction,
+            ));
+            self.delayed_disconnect(conn);
+            self.publish_event(ConnectionManagerEvent::PeerEvicted(Box::new(node_id), direction));
+        }
+    }
+
     /// A 'gentle' disconnect starts by firing a `PeerConnectWillClose` event, waiting (lingering) for a period of time
     /// and then disconnecting. This gives other components time to conclude their work before the connection is
     /// closed.
@@ -0,0 +1,60 @@
+// Copyright 2020, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use super::ConnectionDirection;
+use crate::protocol::ProtocolId;
+use tari_metrics::{IntCounter, IntCounterVec, IntGaugeVec};
+
+lazy_static! {
+    static ref CONNECTIONS: IntGaugeVec =
+        tari_metrics::register_int_gauge_vec("comms_connections", "Number of active peer connections", &["direction"]);
+    static ref DIAL_FAILURES: IntCounter =
+        tari_metrics::register_int_counter("comms_dial_failures_total", "Number of failed outbound dials");
+    static ref SUBSTREAMS: IntCounterVec = tari_metrics::register_int_counter_vec(
+        "comms_substreams_total",
+        "Number of negotiated substreams by protocol",
+        &["protocol", "direction"]
+    );
+}
+
+pub fn set_connections(direction: ConnectionDirection, num_connections: usize) {
+    CONNECTIONS
+        .with_label_values(&[direction_label(direction)])
+        .set(num_connections as i64);
+}
+
+pub fn inc_dial_failures() {
+    DIAL_FAILURES.inc();
+}
+
+pub fn inc_substreams(protocol: &ProtocolId, direction: ConnectionDirection) {
+    SUBSTREAMS
+        .with_label_values(&[&String::from_utf8_lossy(protocol), direction_label(direction)])
+        .inc();
+}
+
+fn direction_label(direction: ConnectionDirection) -> &'static str {
+    match direction {
+        ConnectionDirection::Inbound => "inbound",
+        ConnectionDirection::Outbound => "outbound",
+    }
+}
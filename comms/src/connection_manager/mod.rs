@@ -42,7 +42,12 @@ pub use error::{ConnectionManagerError, PeerConnectionError};
 mod peer_connection;
 pub use peer_connection::{NegotiatedSubstream, PeerConnection, PeerConnectionRequest};
 
+mod traffic;
+pub use traffic::{CountingSocket, PeerTraffic, TrafficCounters, TrafficStats};
+
+mod eviction;
 mod liveness;
+mod metrics;
 mod wire_mode;
 
 #[cfg(test)]
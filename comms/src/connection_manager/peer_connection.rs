@@ -23,6 +23,8 @@
 use super::{
     error::{ConnectionManagerError, PeerConnectionError},
     manager::ConnectionManagerEvent,
+    metrics,
+    traffic::{CountingSocket, PeerTraffic},
     types::ConnectionDirection,
 };
 use crate::{
@@ -72,12 +74,21 @@ pub fn create(
     );
     let (peer_tx, peer_rx) = mpsc::channel(PEER_REQUEST_BUFFER_SIZE);
     let id = ID_COUNTER.fetch_add(1, Ordering::Relaxed); // Monotonic
-    let peer_conn = PeerConnection::new(id, peer_tx, peer_node_id.clone(), peer_addr, direction);
+    let traffic = Arc::new(PeerTraffic::new(connection.traffic_counters()));
+    let peer_conn = PeerConnection::new(
+        id,
+        peer_tx,
+        peer_node_id.clone(),
+        peer_addr,
+        direction,
+        Arc::clone(&traffic),
+    );
     let peer_actor = PeerConnectionActor::new(
         id,
         peer_node_id,
         direction,
         connection,
+        traffic,
         peer_rx,
         event_notifier,
         our_supported_protocols,
@@ -109,6 +120,7 @@ pub struct PeerConnection {
     address: Multiaddr,
     direction: ConnectionDirection,
     started_at: Instant,
+    traffic: Arc<PeerTraffic>,
 }
 
 impl PeerConnection {
@@ -118,6 +130,7 @@ impl PeerConnection {
         peer_node_id: NodeId,
         address: Multiaddr,
         direction: ConnectionDirection,
+        traffic: Arc<PeerTraffic>,
     ) -> Self
     {
         Self {
@@ -127,6 +140,7 @@ impl PeerConnection {
             address,
             direction,
             started_at: Instant::now(),
+            traffic,
         }
     }
 
@@ -154,6 +168,11 @@ impl PeerConnection {
         self.started_at.elapsed()
     }
 
+    /// Traffic counters for this connection and each protocol spoken on it
+    pub fn traffic(&self) -> &PeerTraffic {
+        &self.traffic
+    }
+
     pub fn reference_count(&self) -> usize {
         Arc::strong_count(&self.peer_node_id)
     }
@@ -217,15 +236,18 @@ pub struct PeerConnectionActor {
     control: yamux::Control,
     event_notifier: mpsc::Sender<ConnectionManagerEvent>,
     supported_protocols: Vec<ProtocolId>,
+    traffic: Arc<PeerTraffic>,
     shutdown: bool,
 }
 
 impl PeerConnectionActor {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         id: ConnId,
         peer_node_id: NodeId,
         direction: ConnectionDirection,
         connection: Yamux,
+        traffic: Arc<PeerTraffic>,
         request_rx: mpsc::Receiver<PeerConnectionRequest>,
         event_notifier: mpsc::Sender<ConnectionManagerEvent>,
         supported_protocols: Vec<ProtocolId>,
@@ -242,6 +264,7 @@ impl PeerConnectionActor {
             event_notifier,
             shutdown: false,
             supported_protocols,
+            traffic,
         }
     }
 
@@ -307,11 +330,14 @@ impl PeerConnectionActor {
         let selected_protocol = ProtocolNegotiation::new(&mut stream)
             .negotiate_protocol_inbound(&self.supported_protocols)
             .await?;
+        let counters = self.traffic.protocol(&selected_protocol);
+        counters.record_substream();
+        metrics::inc_substreams(&selected_protocol, ConnectionDirection::Inbound);
 
         self.notify_event(ConnectionManagerEvent::NewInboundSubstream(
             Box::new(self.peer_node_id.clone()),
             selected_protocol,
-            stream,
+            CountingSocket::new(stream, counters),
         ))
         .await;
 
@@ -339,8 +365,14 @@ impl PeerConnectionActor {
         } else {
             negotiation.negotiate_protocol_outbound(&[protocol]).await?
         };
+        let counters = self.traffic.protocol(&selected_protocol);
+        counters.record_substream();
+        metrics::inc_substreams(&selected_protocol, ConnectionDirection::Outbound);
 
-        Ok(NegotiatedSubstream::new(selected_protocol, stream))
+        Ok(NegotiatedSubstream::new(
+            selected_protocol,
+            CountingSocket::new(stream, counters),
+        ))
     }
 
     async fn notify_event(&mut self, event: ConnectionManagerEvent) {
@@ -428,6 +460,7 @@ mod test {
             Default::default(),
             Multiaddr::empty(),
             ConnectionDirection::Outbound,
+            Default::default(),
         );
 
         assert_eq!(conn.reference_count(), 1);
@@ -21,7 +21,11 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use super::{error::ConnectionManagerError, peer_connection::PeerConnection};
-use crate::{connection_manager::manager::ConnectionManagerEvent, multiaddr::Multiaddr, peer_manager::NodeId};
+use crate::{
+    connection_manager::manager::ConnectionManagerEvent,
+    multiaddr::Multiaddr,
+    peer_manager::{NodeId, Peer},
+};
 use futures::{
     channel::{mpsc, oneshot},
     SinkExt,
@@ -36,6 +40,13 @@ pub enum ConnectionManagerRequest {
     /// Parameters:
     /// 1. Node Id to dial
     DialPeer(NodeId, oneshot::Sender<Result<PeerConnection, ConnectionManagerError>>),
+    /// Dial a peer using the addresses in a peer record that has not been verified, for instance one returned by
+    /// another peer. The record is not added to the peer manager; the peer is only added, using the identity it sends,
+    /// once the connection has been authenticated.
+    DialUnverifiedPeer(
+        Box<Peer>,
+        oneshot::Sender<Result<PeerConnection, ConnectionManagerError>>,
+    ),
     /// Register a oneshot to get triggered when the node is listening, or has failed to listen
     NotifyListening(oneshot::Sender<Multiaddr>),
     /// Retrieve an active connection for a given node id if one exists.
@@ -120,6 +131,19 @@ impl ConnectionManagerRequester {
             .map_err(|_| ConnectionManagerError::ActorRequestCanceled)?
     }
 
+    /// Attempt to connect to a remote peer using an unverified peer record. See
+    /// [ConnectionManagerRequest::DialUnverifiedPeer].
+    pub async fn dial_unverified_peer(&mut self, peer: Peer) -> Result<PeerConnection, ConnectionManagerError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.sender
+            .send(ConnectionManagerRequest::DialUnverifiedPeer(Box::new(peer), reply_tx))
+            .await
+            .map_err(|_| ConnectionManagerError::SendToActorFailed)?;
+        reply_rx
+            .await
+            .map_err(|_| ConnectionManagerError::ActorRequestCanceled)?
+    }
+
     /// Return the listening address of this node's listener. This will asynchronously block until the listener has
     /// initialized and a listening address has been established.
     ///
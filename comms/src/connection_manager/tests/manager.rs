@@ -170,6 +170,10 @@ async fn dial_success() {
     let mut buf = [0u8; MSG.len()];
     substream_in.read_exact(&mut buf).await.unwrap();
     assert_eq!(buf, MSG);
+
+    // Bytes sent and received on the substreams are recorded against the protocol
+    assert_eq!(substream_out.stream.counters().snapshot().bytes_out, MSG.len() as u64);
+    assert_eq!(substream_in.counters().snapshot().bytes_in, MSG.len() as u64);
 }
 
 fn count_string_occurrences<T, U>(events: &[T], expected: &[&str]) -> usize
@@ -0,0 +1,254 @@
+// Copyright 2020, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Traffic accounting for peer connections.
+//!
+//! Every peer connection keeps a set of [TrafficCounters](self::TrafficCounters) for the underlying socket (all bytes
+//! sent and received, including yamux framing and substream negotiation) and a set of counters per negotiated
+//! protocol. Every negotiated substream is wrapped in a [CountingSocket](self::CountingSocket) which records the bytes
+//! sent and received on it against the counters for its protocol. Protocol implementations may additionally record the
+//! number of messages they send and receive using [CountingSocket::counters](self::CountingSocket::counters).
+
+use crate::protocol::ProtocolId;
+use futures::{
+    io::{AsyncRead, AsyncWrite},
+    task::Context,
+};
+use std::{
+    collections::HashMap,
+    fmt,
+    io,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+        Mutex,
+    },
+    task::Poll,
+};
+
+/// Byte and message counters. These counters are cheap to update and may be shared between tasks.
+#[derive(Debug, Default)]
+pub struct TrafficCounters {
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    messages_in: AtomicU64,
+    messages_out: AtomicU64,
+    substreams: AtomicU64,
+}
+
+impl TrafficCounters {
+    pub fn record_bytes_in(&self, n: usize) {
+        self.bytes_in.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_bytes_out(&self, n: usize) {
+        self.bytes_out.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    /// Record an inbound message. The bytes of the message are recorded by the socket it was received on.
+    pub fn record_message_in(&self) {
+        self.messages_in.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record an outbound message. The bytes of the message are recorded by the socket it was sent on.
+    pub fn record_message_out(&self) {
+        self.messages_out.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_substream(&self) {
+        self.substreams.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns a point-in-time copy of these counters
+    pub fn snapshot(&self) -> TrafficStats {
+        TrafficStats {
+            bytes_in: self.bytes_in.load(Ordering::Relaxed),
+            bytes_out: self.bytes_out.load(Ordering::Relaxed),
+            messages_in: self.messages_in.load(Ordering::Relaxed),
+            messages_out: self.messages_out.load(Ordering::Relaxed),
+            substreams: self.substreams.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A point-in-time copy of [TrafficCounters](self::TrafficCounters)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrafficStats {
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub messages_in: u64,
+    pub messages_out: u64,
+    pub substreams: u64,
+}
+
+impl fmt::Display for TrafficStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "in: {} bytes/{} msgs, out: {} bytes/{} msgs, substreams: {}",
+            self.bytes_in, self.messages_in, self.bytes_out, self.messages_out, self.substreams
+        )
+    }
+}
+
+/// Traffic counters for a single peer connection
+#[derive(Debug, Default)]
+pub struct PeerTraffic {
+    connection: Arc<TrafficCounters>,
+    protocols: Mutex<HashMap<ProtocolId, Arc<TrafficCounters>>>,
+}
+
+impl PeerTraffic {
+    pub fn new(connection: Arc<TrafficCounters>) -> Self {
+        Self {
+            connection,
+            protocols: Default::default(),
+        }
+    }
+
+    /// Counters for all traffic on the underlying socket
+    pub fn connection(&self) -> &TrafficCounters {
+        &self.connection
+    }
+
+    /// Returns the counters for the given protocol, creating them if necessary
+    pub fn protocol(&self, protocol: &ProtocolId) -> Arc<TrafficCounters> {
+        let mut protocols = acquire_lock!(self.protocols);
+        match protocols.get(protocol) {
+            Some(counters) => Arc::clone(counters),
+            None => {
+                let counters = Arc::new(TrafficCounters::default());
+                protocols.insert(protocol.clone(), Arc::clone(&counters));
+                counters
+            },
+        }
+    }
+
+    /// Returns a snapshot of the counters for every protocol that has been used on this connection
+    pub fn protocol_stats(&self) -> Vec<(ProtocolId, TrafficStats)> {
+        let protocols = acquire_lock!(self.protocols);
+        let mut stats = protocols
+            .iter()
+            .map(|(protocol, counters)| (protocol.clone(), counters.snapshot()))
+            .collect::<Vec<_>>();
+        stats.sort_by(|(a, _), (b, _)| a.cmp(b));
+        stats
+    }
+}
+
+/// Wraps a socket and records the number of bytes read from and written to it
+#[derive(Debug)]
+pub struct CountingSocket<TSocket> {
+    inner: TSocket,
+    counters: Arc<TrafficCounters>,
+}
+
+impl<TSocket> CountingSocket<TSocket> {
+    pub fn new(inner: TSocket, counters: Arc<TrafficCounters>) -> Self {
+        Self { inner, counters }
+    }
+
+    /// The counters that this socket records to
+    pub fn counters(&self) -> Arc<TrafficCounters> {
+        Arc::clone(&self.counters)
+    }
+}
+
+impl<TSocket> AsyncRead for CountingSocket<TSocket>
+where TSocket: AsyncRead + Unpin
+{
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let result = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = result {
+            self.counters.record_bytes_in(n);
+        }
+        result
+    }
+}
+
+impl<TSocket> AsyncWrite for CountingSocket<TSocket>
+where TSocket: AsyncWrite + Unpin
+{
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let result = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = result {
+            self.counters.record_bytes_out(n);
+        }
+        result
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn protocol_counters() {
+        let traffic = PeerTraffic::default();
+        let proto_a = ProtocolId::from_static(b"/tari/a");
+        let proto_b = ProtocolId::from_static(b"/tari/b");
+
+        traffic.protocol(&proto_a).record_bytes_in(10);
+        traffic.protocol(&proto_a).record_message_in();
+        traffic.protocol(&proto_a).record_bytes_out(5);
+        traffic.protocol(&proto_a).record_message_out();
+        traffic.protocol(&proto_b).record_substream();
+
+        let stats = traffic.protocol_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].0, proto_a);
+        assert_eq!(stats[0].1, TrafficStats {
+            bytes_in: 10,
+            bytes_out: 5,
+            messages_in: 1,
+            messages_out: 1,
+            substreams: 0,
+        });
+        assert_eq!(stats[1].0, proto_b);
+        assert_eq!(stats[1].1.substreams, 1);
+    }
+
+    #[tokio_macros::test_basic]
+    async fn counting_socket() {
+        let counters = Arc::new(TrafficCounters::default());
+        let mut socket = CountingSocket::new(futures::io::Cursor::new(vec![0u8; 8]), Arc::clone(&counters));
+
+        let mut buf = [0u8; 5];
+        socket.read_exact(&mut buf).await.unwrap();
+        socket.write_all(&[1, 2, 3]).await.unwrap();
+
+        let stats = counters.snapshot();
+        assert_eq!(stats.bytes_in, 5);
+        assert_eq!(stats.bytes_out, 3);
+        assert_eq!(stats.messages_in, 0);
+    }
+}
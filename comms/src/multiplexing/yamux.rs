@@ -20,7 +20,10 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::{connection_manager::ConnectionDirection, runtime};
+use crate::{
+    connection_manager::{ConnectionDirection, CountingSocket, TrafficCounters},
+    runtime,
+};
 use futures::{
     channel::mpsc,
     future,
@@ -32,7 +35,7 @@ use futures::{
     StreamExt,
 };
 use log::*;
-use std::{io, pin::Pin, task::Poll};
+use std::{io, pin::Pin, sync::Arc, task::Poll};
 use tari_shutdown::{Shutdown, ShutdownSignal};
 use yamux::Mode;
 
@@ -46,6 +49,7 @@ const LOG_TARGET: &str = "comms::multiplexing::yamux";
 pub struct Yamux {
     control: Control,
     incoming: IncomingSubstreams,
+    traffic: Arc<TrafficCounters>,
 }
 
 const MAX_BUFFER_SIZE: u32 = 8 * 1024 * 1024; // 8MB
@@ -71,12 +75,18 @@ impl Yamux {
         config.set_max_buffer_size(MAX_BUFFER_SIZE as usize);
         config.set_receive_window(RECEIVE_WINDOW);
 
+        let traffic = Arc::new(TrafficCounters::default());
+        let socket = CountingSocket::new(socket, Arc::clone(&traffic));
         let connection = yamux::Connection::new(socket, config, mode);
         let control = connection.control();
 
         let incoming = Self::spawn_incoming_stream_worker(connection);
 
-        Ok(Self { control, incoming })
+        Ok(Self {
+            control,
+            incoming,
+            traffic,
+        })
     }
 
     // yamux@0.4 requires the incoming substream stream be polled in order to make progress on requests from it's
@@ -96,6 +106,11 @@ impl Yamux {
         self.control.clone()
     }
 
+    /// Returns the counters for all bytes sent and received on the underlying socket
+    pub fn traffic_counters(&self) -> Arc<TrafficCounters> {
+        Arc::clone(&self.traffic)
+    }
+
     /// Returns a mutable reference to a `Stream` that emits substreams initiated by the remote
     pub fn incoming_mut(&mut self) -> &mut IncomingSubstreams {
         &mut self.incoming
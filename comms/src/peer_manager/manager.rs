@@ -23,10 +23,12 @@
 use crate::{
     peer_manager::{
         connection_stats::PeerConnectionStats,
+        migrations,
         node_id::{NodeDistance, NodeId},
         peer::{Peer, PeerFlags},
         peer_id::PeerId,
         peer_storage::{PeerStorage, RegionStats},
+        reputation::{Offence, ReputationConfig},
         PeerFeatures,
         PeerManagerError,
         PeerQuery,
@@ -36,7 +38,7 @@ use crate::{
 };
 use multiaddr::Multiaddr;
 use std::time::Duration;
-use tari_storage::IterationResult;
+use tari_storage::{lmdb_store::LMDBDatabase, IterationResult, KeyValStoreError};
 use tokio::sync::RwLock;
 
 /// The PeerManager consist of a routing table of previously discovered peers.
@@ -44,6 +46,7 @@ use tokio::sync::RwLock;
 /// routing table based on the selected Broadcast strategy.
 pub struct PeerManager {
     peer_storage: RwLock<PeerStorage<CommsDatabase>>,
+    reputation_config: ReputationConfig,
 }
 
 impl PeerManager {
@@ -51,9 +54,23 @@ impl PeerManager {
     pub fn new(database: CommsDatabase) -> Result<PeerManager, PeerManagerError> {
         Ok(Self {
             peer_storage: RwLock::new(PeerStorage::new_indexed(database)?),
+            reputation_config: Default::default(),
         })
     }
 
+    /// Migrate the given LMDB peer database to the latest version. This must be called before the database is used to
+    /// construct a PeerManager, otherwise peers stored in an older format will not be loaded.
+    pub fn migrate_lmdb(database: &LMDBDatabase) -> Result<(), PeerManagerError> {
+        migrations::migrate(database)
+            .map_err(|err| PeerManagerError::DatabaseError(KeyValStoreError::DatabaseError(format!("{:?}", err))))
+    }
+
+    /// Set the [ReputationConfig](crate::peer_manager::ReputationConfig) used to score and ban misbehaving peers
+    pub fn with_reputation_config(mut self, reputation_config: ReputationConfig) -> Self {
+        self.reputation_config = reputation_config;
+        self
+    }
+
     /// Adds a peer to the routing table of the PeerManager if the peer does not already exist. When a peer already
     /// exist, the stored version will be replaced with the newly provided peer.
     pub async fn add_peer(&self, peer: Peer) -> Result<PeerId, PeerManagerError> {
@@ -249,9 +266,29 @@ impl PeerManager {
         self.peer_storage.write().await.unban(public_key)
     }
 
-    /// Ban the peer for a length of time specified by the duration
-    pub async fn ban_for(&self, public_key: &CommsPublicKey, duration: Duration) -> Result<NodeId, PeerManagerError> {
-        self.peer_storage.write().await.ban_for(public_key, duration)
+    /// Ban the peer for a length of time specified by the duration. The reason is persisted with the peer.
+    pub async fn ban_for(
+        &self,
+        public_key: &CommsPublicKey,
+        duration: Duration,
+        reason: String,
+    ) -> Result<NodeId, PeerManagerError>
+    {
+        self.peer_storage.write().await.ban_for(public_key, duration, reason)
+    }
+
+    /// Report an offence committed by the peer. Once the peer's reputation score reaches the ban threshold, the peer is
+    /// banned. Returns true if the peer was banned as a result of this offence, otherwise false.
+    pub async fn report_offence(
+        &self,
+        public_key: &CommsPublicKey,
+        offence: Offence,
+    ) -> Result<bool, PeerManagerError>
+    {
+        self.peer_storage
+            .write()
+            .await
+            .report_offence(public_key, offence, &self.reputation_config)
     }
 
     /// Changes the offline flag bit of the peer
@@ -259,6 +296,19 @@ impl PeerManager {
         self.peer_storage.write().await.set_offline(public_key, is_offline)
     }
 
+    /// Sets the proof-of-work nonce of the peer. The caller is responsible for verifying the nonce.
+    pub async fn set_proof_of_work_nonce(
+        &self,
+        public_key: &CommsPublicKey,
+        nonce: u64,
+    ) -> Result<(), PeerManagerError>
+    {
+        self.peer_storage
+            .write()
+            .await
+            .set_proof_of_work_nonce(public_key, nonce)
+    }
+
     /// Adds a new net address to the peer if it doesn't yet exist
     pub async fn add_net_address(&self, node_id: &NodeId, net_address: &Multiaddr) -> Result<(), PeerManagerError> {
         self.peer_storage.write().await.add_net_address(node_id, net_address)
@@ -325,7 +375,7 @@ mod test {
         let net_addresses = MultiaddressesWithStats::from("/ip4/1.2.3.4/tcp/8000".parse::<Multiaddr>().unwrap());
         let mut peer = Peer::new(pk, node_id, net_addresses, PeerFlags::default(), features, &[]);
         if ban_flag {
-            peer.ban_for(Duration::from_secs(1000));
+            peer.ban_for(Duration::from_secs(1000), "Test".to_string());
         }
         peer
     }
@@ -544,4 +594,41 @@ mod test {
                 .all(|p| network_region_node_id.distance(&p.node_id) <= node_threshold));
         }
     }
+
+    #[tokio_macros::test_basic]
+    async fn report_offence() {
+        let peer_manager = PeerManager::new(HashmapDatabase::new())
+            .unwrap()
+            .with_reputation_config(ReputationConfig {
+                ban_threshold: 50.0,
+                ..Default::default()
+            });
+        let peer = create_test_peer(false, PeerFeatures::COMMUNICATION_NODE);
+        peer_manager.add_peer(peer.clone()).await.unwrap();
+
+        let is_banned = peer_manager
+            .report_offence(&peer.public_key, Offence::InvalidTransaction)
+            .await
+            .unwrap();
+        assert_eq!(is_banned, false);
+        assert!(!peer_manager
+            .find_by_public_key(&peer.public_key)
+            .await
+            .unwrap()
+            .is_banned());
+
+        let is_banned = peer_manager
+            .report_offence(&peer.public_key, Offence::InvalidTransaction)
+            .await
+            .unwrap();
+        assert_eq!(is_banned, true);
+        let peer = peer_manager.find_by_public_key(&peer.public_key).await.unwrap();
+        assert!(peer.is_banned());
+        assert!(peer.banned_reason.contains("Invalid transaction"));
+
+        peer_manager.unban(&peer.public_key).await.unwrap();
+        let peer = peer_manager.find_by_public_key(&peer.public_key).await.unwrap();
+        assert!(!peer.is_banned());
+        assert!(peer.banned_reason.is_empty());
+    }
 }
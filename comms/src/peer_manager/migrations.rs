@@ -0,0 +1,324 @@
+//  Copyright 2020 The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Migrations for the LMDB peer database.
+//!
+//! Peers are stored using bincode, which does not support adding or removing struct fields. Every change to the
+//! stored [Peer](super::Peer) layout therefore requires a migration that rewrites the existing records. The current
+//! version of the peer database is stored under [MIGRATION_VERSION_KEY], a key that is never used for a peer.
+
+use super::{
+    connection_stats::PeerConnectionStats,
+    node_id::{deserialize_node_id_from_hex, NodeId},
+    peer::{Peer, PeerFlags},
+    peer_id::PeerId,
+    reputation::PeerReputation,
+    PeerFeatures,
+};
+use crate::{net_address::MultiaddressesWithStats, protocol::ProtocolId, types::CommsPublicKey};
+use chrono::NaiveDateTime;
+use log::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tari_crypto::tari_utilities::hex::serialize_to_hex;
+use tari_storage::{
+    lmdb_store::{LMDBDatabase, LMDBError},
+    IterationResult,
+};
+
+const LOG_TARGET: &str = "comms::peer_manager::migrations";
+
+/// The key under which the peer database version is stored
+const MIGRATION_VERSION_KEY: PeerId = u64::MAX;
+/// The current version of the peer database
+const LATEST_VERSION: u32 = 2;
+
+/// Migrate the peer database to the latest version. A database without a stored version is either empty or was
+/// created before versioning was introduced (version 0).
+pub fn migrate(database: &LMDBDatabase) -> Result<(), LMDBError> {
+    if database.len()? == 0 {
+        return database.insert(&MIGRATION_VERSION_KEY, &LATEST_VERSION);
+    }
+
+    let mut version = database.get::<_, u32>(&MIGRATION_VERSION_KEY)?.unwrap_or(0);
+    while version < LATEST_VERSION {
+        match version {
+            0 => migrate_records::<PeerV0, PeerV1>(database, 1)?,
+            1 => migrate_records::<PeerV1, Peer>(database, 2)?,
+            _ => unreachable!("all peer database versions below LATEST_VERSION have a migration"),
+        }
+        version += 1;
+        info!(target: LOG_TARGET, "Migrated peer database to version {}", version);
+    }
+
+    Ok(())
+}
+
+/// The layout of a peer before `banned_reason` and `reputation` were added
+#[derive(Deserialize, Serialize)]
+struct PeerV0 {
+    id: Option<PeerId>,
+    public_key: CommsPublicKey,
+    #[serde(serialize_with = "serialize_to_hex")]
+    #[serde(deserialize_with = "deserialize_node_id_from_hex")]
+    node_id: NodeId,
+    addresses: MultiaddressesWithStats,
+    flags: PeerFlags,
+    banned_until: Option<NaiveDateTime>,
+    offline_at: Option<NaiveDateTime>,
+    features: PeerFeatures,
+    connection_stats: PeerConnectionStats,
+    supported_protocols: Vec<ProtocolId>,
+    added_at: NaiveDateTime,
+}
+
+/// The layout of a peer before `proof_of_work_nonce` was added
+#[derive(Deserialize, Serialize)]
+struct PeerV1 {
+    id: Option<PeerId>,
+    public_key: CommsPublicKey,
+    #[serde(serialize_with = "serialize_to_hex")]
+    #[serde(deserialize_with = "deserialize_node_id_from_hex")]
+    node_id: NodeId,
+    addresses: MultiaddressesWithStats,
+    flags: PeerFlags,
+    banned_until: Option<NaiveDateTime>,
+    banned_reason: String,
+    offline_at: Option<NaiveDateTime>,
+    features: PeerFeatures,
+    connection_stats: PeerConnectionStats,
+    supported_protocols: Vec<ProtocolId>,
+    added_at: NaiveDateTime,
+    reputation: PeerReputation,
+}
+
+impl From<PeerV0> for PeerV1 {
+    fn from(old: PeerV0) -> Self {
+        Self {
+            id: old.id,
+            public_key: old.public_key,
+            node_id: old.node_id,
+            addresses: old.addresses,
+            flags: old.flags,
+            banned_until: old.banned_until,
+            banned_reason: String::new(),
+            offline_at: old.offline_at,
+            features: old.features,
+            connection_stats: old.connection_stats,
+            supported_protocols: old.supported_protocols,
+            added_at: old.added_at,
+            reputation: Default::default(),
+        }
+    }
+}
+
+impl From<PeerV1> for Peer {
+    fn from(old: PeerV1) -> Self {
+        let mut peer = Peer::new(
+            old.public_key,
+            old.node_id,
+            old.addresses,
+            old.flags,
+            old.features,
+            &old.supported_protocols,
+        );
+        if let Some(id) = old.id {
+            peer.set_id(id);
+        }
+        peer.banned_until = old.banned_until;
+        peer.banned_reason = old.banned_reason;
+        peer.offline_at = old.offline_at;
+        peer.connection_stats = old.connection_stats;
+        peer.added_at = old.added_at;
+        peer.reputation = old.reputation;
+        peer
+    }
+}
+
+/// Rewrite every peer record from the `Old` layout to the `New` layout and set the database version to `to_version`.
+/// Records that cannot be read as `Old` cannot be migrated and are removed.
+fn migrate_records<Old, New>(database: &LMDBDatabase, to_version: u32) -> Result<(), LMDBError>
+where
+    Old: DeserializeOwned,
+    New: From<Old> + Serialize,
+{
+    // Values are not read here (bincode ignores the trailing bytes), so every key is returned even if its record is
+    // corrupt
+    let mut keys = Vec::new();
+    database.for_each::<PeerId, (), _>(|result| {
+        match result {
+            Ok((key, _)) => keys.push(key),
+            Err(err) => warn!(target: LOG_TARGET, "Unable to read peer record key: {:?}", err),
+        }
+        IterationResult::Continue
+    })?;
+
+    let mut peers = Vec::new();
+    let mut invalid_keys = Vec::new();
+    for key in keys.into_iter().filter(|key| *key != MIGRATION_VERSION_KEY) {
+        match database.get::<_, Old>(&key) {
+            Ok(Some(peer)) => peers.push((key, New::from(peer))),
+            Ok(None) => {},
+            Err(err) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Removing peer record {} because it could not be migrated: {:?}", key, err
+                );
+                invalid_keys.push(key);
+            },
+        }
+    }
+
+    let num_peers = peers.len();
+    let num_removed = invalid_keys.len();
+    database.with_write_transaction(|mut txn| {
+        for key in invalid_keys {
+            txn.delete(&key)?;
+        }
+        for (key, peer) in peers {
+            txn.insert(&key, &peer)?;
+        }
+        txn.insert(&MIGRATION_VERSION_KEY, &to_version)
+    })?;
+    debug!(
+        target: LOG_TARGET,
+        "Migrated {} peer(s) to version {} and removed {} invalid peer record(s)", num_peers, to_version, num_removed
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::peer_manager::PeerStorage;
+    use rand::rngs::OsRng;
+    use std::sync::Arc;
+    use tari_crypto::keys::PublicKey;
+    use tari_storage::{lmdb_store::LMDBBuilder, LMDBWrapper};
+    use tempdir::TempDir;
+
+    fn make_v0_peer(id: PeerId) -> PeerV0 {
+        let (_, public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        let node_id = NodeId::from_key(&public_key).unwrap();
+        PeerV0 {
+            id: Some(id),
+            public_key,
+            node_id,
+            addresses: "/ip4/127.0.0.1/tcp/8000"
+                .parse::<multiaddr::Multiaddr>()
+                .unwrap()
+                .into(),
+            flags: PeerFlags::default(),
+            banned_until: None,
+            offline_at: None,
+            features: PeerFeatures::COMMUNICATION_NODE,
+            connection_stats: Default::default(),
+            supported_protocols: Vec::new(),
+            added_at: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    #[test]
+    fn migrate_v0_peers() {
+        let temp_dir = TempDir::new("peer_db_migration").unwrap();
+        let datastore = LMDBBuilder::new()
+            .set_path(temp_dir.path().to_str().unwrap())
+            .set_environment_size(10)
+            .set_max_number_of_databases(1)
+            .add_database("peers", lmdb_zero::db::CREATE)
+            .build()
+            .unwrap();
+        let database = datastore.get_handle("peers").unwrap();
+        let peer1 = make_v0_peer(1);
+        let peer2 = make_v0_peer(2);
+        database.insert(&1u64, &peer1).unwrap();
+        database.insert(&2u64, &peer2).unwrap();
+        database.insert(&3u64, &"not a peer".to_string()).unwrap();
+
+        migrate(&database).unwrap();
+        assert_eq!(
+            database.get::<_, u32>(&MIGRATION_VERSION_KEY).unwrap(),
+            Some(LATEST_VERSION)
+        );
+        // Running the migrations again does nothing
+        migrate(&database).unwrap();
+        // The record that could not be migrated was removed
+        assert!(!database.contains_key(&3u64).unwrap());
+
+        let peer_storage = PeerStorage::new_indexed(LMDBWrapper::<PeerId, Peer>::new(Arc::new(database))).unwrap();
+        let peer = peer_storage.find_by_public_key(&peer1.public_key).unwrap();
+        assert_eq!(peer.node_id, peer1.node_id);
+        assert!(peer.banned_reason.is_empty());
+        let peer = peer_storage.find_by_public_key(&peer2.public_key).unwrap();
+        assert_eq!(peer.added_at, peer2.added_at);
+    }
+
+    #[test]
+    fn migrate_v1_peers() {
+        let temp_dir = TempDir::new("peer_db_migration_v1").unwrap();
+        let datastore = LMDBBuilder::new()
+            .set_path(temp_dir.path().to_str().unwrap())
+            .set_environment_size(10)
+            .set_max_number_of_databases(1)
+            .add_database("peers", lmdb_zero::db::CREATE)
+            .build()
+            .unwrap();
+        let database = datastore.get_handle("peers").unwrap();
+        let mut peer1 = PeerV1::from(make_v0_peer(1));
+        peer1.banned_reason = "Spamming".to_string();
+        let public_key = peer1.public_key.clone();
+        database.insert(&1u64, &peer1).unwrap();
+        database.insert(&MIGRATION_VERSION_KEY, &1u32).unwrap();
+
+        migrate(&database).unwrap();
+        assert_eq!(
+            database.get::<_, u32>(&MIGRATION_VERSION_KEY).unwrap(),
+            Some(LATEST_VERSION)
+        );
+
+        let peer_storage = PeerStorage::new_indexed(LMDBWrapper::<PeerId, Peer>::new(Arc::new(database))).unwrap();
+        let peer = peer_storage.find_by_public_key(&public_key).unwrap();
+        assert_eq!(peer.banned_reason, "Spamming");
+        assert!(peer.proof_of_work_nonce.is_none());
+    }
+
+    #[test]
+    fn migrate_empty_database() {
+        let temp_dir = TempDir::new("peer_db_migration_empty").unwrap();
+        let datastore = LMDBBuilder::new()
+            .set_path(temp_dir.path().to_str().unwrap())
+            .set_environment_size(10)
+            .set_max_number_of_databases(1)
+            .add_database("peers", lmdb_zero::db::CREATE)
+            .build()
+            .unwrap();
+        let database = datastore.get_handle("peers").unwrap();
+
+        migrate(&database).unwrap();
+        assert_eq!(
+            database.get::<_, u32>(&MIGRATION_VERSION_KEY).unwrap(),
+            Some(LATEST_VERSION)
+        );
+        let peer_storage = PeerStorage::new_indexed(LMDBWrapper::<PeerId, Peer>::new(Arc::new(database))).unwrap();
+        assert!(peer_storage.find_by_public_key(&make_v0_peer(1).public_key).is_err());
+    }
+}
@@ -84,8 +84,16 @@ pub use peer_id::PeerId;
 mod manager;
 pub use manager::PeerManager;
 
+mod migrations;
+
 mod peer_query;
 pub use peer_query::{PeerQuery, PeerQuerySortBy};
 
 mod peer_storage;
 pub use peer_storage::PeerStorage;
+
+mod reputation;
+pub use reputation::{Offence, PeerReputation, ReputationConfig};
+
+mod routing_table;
+pub use routing_table::{ClosestEntries, RoutingEntry, RoutingTable};
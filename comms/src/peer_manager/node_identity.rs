@@ -29,6 +29,7 @@ use crate::{
         PeerFlags,
     },
     types::{CommsPublicKey, CommsSecretKey},
+    utils::proof_of_work,
 };
 use derive_error::Error;
 use multiaddr::Multiaddr;
@@ -45,6 +46,8 @@ pub enum NodeIdentityError {
     NodeIdError(NodeIdError),
     /// The Thread Safety has been breached and the data access has become poisoned
     PoisonedAccess,
+    /// The requested proof-of-work difficulty cannot be met
+    UnattainableProofOfWorkDifficulty,
 }
 
 /// The public and private identity of this node on the network
@@ -57,6 +60,10 @@ pub struct NodeIdentity {
     features: PeerFeatures,
     secret_key: CommsSecretKey,
     public_address: RwLock<Multiaddr>,
+    /// The nonce which, together with the public key, meets a proof-of-work difficulty. None if no proof-of-work has
+    /// been generated for this identity.
+    #[serde(default)]
+    proof_of_work_nonce: Option<u64>,
 }
 
 impl NodeIdentity {
@@ -76,6 +83,7 @@ impl NodeIdentity {
             features,
             secret_key,
             public_address: RwLock::new(public_address),
+            proof_of_work_nonce: None,
         })
     }
 
@@ -98,9 +106,38 @@ impl NodeIdentity {
             features,
             secret_key,
             public_address: RwLock::new(public_address),
+            proof_of_work_nonce: None,
         })
     }
 
+    /// Generates a new random NodeIdentity whose public key and proof-of-work nonce meet the given difficulty
+    pub fn random_with_proof_of_work<R>(
+        rng: &mut R,
+        public_address: Multiaddr,
+        features: PeerFeatures,
+        difficulty: u32,
+    ) -> Result<Self, NodeIdentityError>
+    where
+        R: CryptoRng + Rng,
+    {
+        let mut node_identity = Self::random(rng, public_address, features)?;
+        node_identity.generate_proof_of_work(rng.next_u64(), difficulty)?;
+        Ok(node_identity)
+    }
+
+    /// Search for a proof-of-work nonce for this identity's public key, starting at `start_nonce`, which meets the
+    /// given difficulty. A difficulty of zero clears the nonce.
+    pub fn generate_proof_of_work(&mut self, start_nonce: u64, difficulty: u32) -> Result<(), NodeIdentityError> {
+        if difficulty == 0 {
+            self.proof_of_work_nonce = None;
+            return Ok(());
+        }
+        let nonce = proof_of_work::find_nonce(&self.public_key, difficulty, start_nonce)
+            .ok_or(NodeIdentityError::UnattainableProofOfWorkDifficulty)?;
+        self.proof_of_work_nonce = Some(nonce);
+        Ok(())
+    }
+
     /// Retrieve the publicly accessible address that peers must connect to establish a connection
     pub fn public_address(&self) -> Multiaddr {
         acquire_read_lock!(self.public_address).clone()
@@ -147,6 +184,16 @@ impl NodeIdentity {
         self.features
     }
 
+    #[inline]
+    pub fn proof_of_work_nonce(&self) -> Option<u64> {
+        self.proof_of_work_nonce
+    }
+
+    /// Returns true if this identity's proof-of-work nonce meets the given difficulty
+    pub fn meets_proof_of_work_difficulty(&self, difficulty: u32) -> bool {
+        proof_of_work::verify(&self.public_key, self.proof_of_work_nonce.unwrap_or(0), difficulty)
+    }
+
     #[inline]
     pub fn has_peer_features(&self, peer_features: PeerFeatures) -> bool {
         self.features().contains(peer_features)
@@ -155,14 +202,16 @@ impl NodeIdentity {
     /// Returns a Peer with the same public key, node id, public address and features as represented in this
     /// NodeIdentity. _NOTE: PeerFlags and supported_protocols are empty._
     pub fn to_peer(&self) -> Peer {
-        Peer::new(
+        let mut peer = Peer::new(
             self.public_key().clone(),
             self.node_id().clone(),
             self.public_address().into(),
             PeerFlags::empty(),
             self.features(),
             &[],
-        )
+        );
+        peer.proof_of_work_nonce = self.proof_of_work_nonce;
+        peer
     }
 }
 
@@ -174,6 +223,7 @@ impl Clone for NodeIdentity {
             features: self.features,
             secret_key: self.secret_key.clone(),
             public_address: RwLock::new(self.public_address()),
+            proof_of_work_nonce: self.proof_of_work_nonce,
         }
     }
 }
@@ -184,6 +234,14 @@ impl fmt::Display for NodeIdentity {
         writeln!(f, "Node ID: {}", self.node_id)?;
         writeln!(f, "Public Address: {}", acquire_read_lock!(self.public_address))?;
         writeln!(f, "Features: {:?}", self.features)?;
+        if let Some(nonce) = self.proof_of_work_nonce {
+            writeln!(
+                f,
+                "Proof-of-work: {} (difficulty {})",
+                nonce,
+                proof_of_work::difficulty(&self.public_key, nonce)
+            )?;
+        }
 
         Ok(())
     }
@@ -24,6 +24,7 @@ use super::{
     connection_stats::PeerConnectionStats,
     node_id::{deserialize_node_id_from_hex, NodeId},
     peer_id::PeerId,
+    reputation::PeerReputation,
     PeerFeatures,
 };
 use crate::{
@@ -71,6 +72,9 @@ pub struct Peer {
     /// Flags for the peer.
     pub flags: PeerFlags,
     pub banned_until: Option<NaiveDateTime>,
+    /// The reason the peer was banned. Empty if the peer has never been banned.
+    #[serde(default)]
+    pub banned_reason: String,
     pub offline_at: Option<NaiveDateTime>,
     /// Features supported by the peer
     pub features: PeerFeatures,
@@ -81,6 +85,13 @@ pub struct Peer {
     pub supported_protocols: Vec<ProtocolId>,
     /// Timestamp of when the peer was added to this nodes peer list
     pub added_at: NaiveDateTime,
+    /// Reputation score of the peer, used to ban misbehaving peers
+    #[serde(default)]
+    pub reputation: PeerReputation,
+    /// The proof-of-work nonce for the peer's public key, if the peer has sent one that met this node's required
+    /// difficulty
+    #[serde(default)]
+    pub proof_of_work_nonce: Option<u64>,
 }
 
 impl Peer {
@@ -102,10 +113,13 @@ impl Peer {
             flags,
             features,
             banned_until: None,
+            banned_reason: String::new(),
             offline_at: None,
             connection_stats: Default::default(),
             added_at: Utc::now().naive_utc(),
             supported_protocols: supported_protocols.into_iter().cloned().collect(),
+            reputation: Default::default(),
+            proof_of_work_nonce: None,
         }
     }
 
@@ -213,15 +227,17 @@ impl Peer {
         self.banned_until().is_some()
     }
 
-    /// Bans the peer for a specified duration
-    pub fn ban_for(&mut self, duration: Duration) {
+    /// Bans the peer for a specified duration, recording the reason for the ban
+    pub fn ban_for(&mut self, duration: Duration, reason: String) {
         let dt = safe_future_datetime_from_duration(duration);
         self.banned_until = Some(dt.naive_utc());
+        self.banned_reason = reason;
     }
 
     /// Unban the peer
     pub fn unban(&mut self) {
         self.banned_until = None;
+        self.banned_reason = String::new();
     }
 
     pub fn banned_until(&self) -> Option<&NaiveDateTime> {
@@ -254,7 +270,11 @@ impl Display for Peer {
             }
 
             if let Some(dt) = self.banned_until() {
-                s.push(format!("BANNED until {}", dt));
+                if self.banned_reason.is_empty() {
+                    s.push(format!("BANNED until {}", dt));
+                } else {
+                    s.push(format!("BANNED until {} ({})", dt, self.banned_reason));
+                }
             }
             s.join(", ")
         };
@@ -299,9 +319,10 @@ mod test {
         let addresses = MultiaddressesWithStats::from("/ip4/123.0.0.123/tcp/8000".parse::<Multiaddr>().unwrap());
         let mut peer: Peer = Peer::new(pk, node_id, addresses, PeerFlags::default(), PeerFeatures::empty(), &[]);
         assert_eq!(peer.is_banned(), false);
-        peer.ban_for(Duration::from_millis(std::u64::MAX));
+        peer.ban_for(Duration::from_millis(std::u64::MAX), "Very bad".to_string());
+        assert_eq!(peer.banned_reason, "Very bad");
         assert_eq!(peer.is_banned(), true);
-        peer.ban_for(Duration::from_millis(0));
+        peer.ban_for(Duration::from_millis(0), String::new());
         assert_eq!(peer.is_banned(), false);
     }
 
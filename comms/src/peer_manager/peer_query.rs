@@ -20,8 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::peer_manager::{peer_id::PeerId, NodeId, Peer, PeerManagerError};
-use std::cmp::min;
+use crate::peer_manager::{peer_id::PeerId, NodeId, Peer, PeerManagerError, RoutingTable};
 use tari_storage::{IterationResult, KeyValueStore};
 
 type Predicate<'a, A> = Box<dyn FnMut(&A) -> bool + Send + 'a>;
@@ -83,9 +82,15 @@ impl<'a> PeerQuery<'a> {
     }
 
     /// Returns a `PeerQueryExecutor` with this `PeerQuery`
-    pub(super) fn executor<DS>(self, store: &DS) -> PeerQueryExecutor<'a, '_, DS>
-    where DS: KeyValueStore<PeerId, Peer> {
-        PeerQueryExecutor::new(self, store)
+    pub(super) fn executor<'b, DS>(
+        self,
+        store: &'b DS,
+        routing_table: &'b RoutingTable,
+    ) -> PeerQueryExecutor<'a, 'b, DS>
+    where
+        DS: KeyValueStore<PeerId, Peer>,
+    {
+        PeerQueryExecutor::new(self, store, routing_table)
     }
 
     /// Returns true if the given limit is within the specified limit. If the limit
@@ -116,13 +121,18 @@ impl<'a> PeerQuery<'a> {
 pub(super) struct PeerQueryExecutor<'a, 'b, DS> {
     query: PeerQuery<'a>,
     store: &'b DS,
+    routing_table: &'b RoutingTable,
 }
 
 impl<'a, 'b, DS> PeerQueryExecutor<'a, 'b, DS>
 where DS: KeyValueStore<PeerId, Peer>
 {
-    pub fn new(query: PeerQuery<'a>, store: &'b DS) -> Self {
-        Self { query, store }
+    pub fn new(query: PeerQuery<'a>, store: &'b DS, routing_table: &'b RoutingTable) -> Self {
+        Self {
+            query,
+            store,
+            routing_table,
+        }
     }
 
     pub fn get_results(&mut self) -> Result<Vec<Peer>, PeerManagerError> {
@@ -132,45 +142,29 @@ where DS: KeyValueStore<PeerId, Peer>
         }
     }
 
+    /// Selects peers in order of increasing distance from `node_id`. The routing table is used to visit peers in
+    /// distance order so that only the peers needed to satisfy the query are loaded from the store.
     pub fn get_distance_sorted_results(&mut self, node_id: &NodeId) -> Result<Vec<Peer>, PeerManagerError> {
-        let mut peer_keys = Vec::new();
-        let mut distances = Vec::new();
-        self.store
-            .for_each_ok(|(peer_key, peer)| {
-                if self.query.is_selected(&peer) {
-                    peer_keys.push(peer_key);
-                    distances.push(node_id.distance(&peer.node_id));
-                }
-
-                IterationResult::Continue
-            })
-            .map_err(PeerManagerError::DatabaseError)?;
-
-        // Use all available peers up to a maximum of N
-        let max_available = self
-            .query
-            .limit
-            .map(|limit| min(peer_keys.len(), limit))
-            .unwrap_or_else(|| peer_keys.len());
-        if max_available == 0 {
-            return Ok(Vec::new());
-        }
+        let mut selected_peers = match self.query.limit {
+            Some(n) => Vec::with_capacity(n),
+            None => Vec::new(),
+        };
 
-        // Perform partial sort of elements only up to N elements
-        let mut selected_peers = Vec::with_capacity(max_available);
-        for i in 0..max_available {
-            for j in (i + 1)..peer_keys.len() {
-                if distances[i] > distances[j] {
-                    distances.swap(i, j);
-                    peer_keys.swap(i, j);
-                }
+        for entry in self.routing_table.closest(node_id) {
+            if !self.query.within_limit(selected_peers.len()) {
+                break;
             }
+
             let peer = self
                 .store
-                .get(&peer_keys[i])
+                .get(&entry.peer_key())
                 .map_err(PeerManagerError::DatabaseError)?
                 .ok_or(PeerManagerError::PeerNotFoundError)?;
 
+            if !self.query.is_selected(&peer) {
+                continue;
+            }
+
             selected_peers.push(peer);
 
             if self.query.should_stop(&selected_peers) {
@@ -235,7 +229,7 @@ mod test {
             &[],
         );
         if ban_flag {
-            peer.ban_for(Duration::from_secs(1000));
+            peer.ban_for(Duration::from_secs(1000), "Test".to_string());
         }
         peer
     }
@@ -254,7 +248,11 @@ mod test {
             id_counter += 1;
         });
 
-        let peers = PeerQuery::new().limit(4).executor(&db).get_results().unwrap();
+        let peers = PeerQuery::new()
+            .limit(4)
+            .executor(&db, &RoutingTable::new())
+            .get_results()
+            .unwrap();
 
         assert_eq!(peers.len(), 4);
     }
@@ -281,7 +279,7 @@ mod test {
 
         let peers = PeerQuery::new()
             .select_where(|peer| !peer.is_banned())
-            .executor(&db)
+            .executor(&db, &RoutingTable::new())
             .get_results()
             .unwrap();
 
@@ -312,7 +310,7 @@ mod test {
         let peers = PeerQuery::new()
             .select_where(|peer| peer.is_banned())
             .limit(2)
-            .executor(&db)
+            .executor(&db, &RoutingTable::new())
             .get_results()
             .unwrap();
 
@@ -322,7 +320,7 @@ mod test {
         let peers = PeerQuery::new()
             .select_where(|peer| !peer.is_banned())
             .limit(100)
-            .executor(&db)
+            .executor(&db, &RoutingTable::new())
             .get_results()
             .unwrap();
 
@@ -353,7 +351,7 @@ mod test {
         let peers = PeerQuery::new()
             .select_where(|peer| !peer.is_banned())
             .until(|peers| peers.len() == 2)
-            .executor(&db)
+            .executor(&db, &RoutingTable::new())
             .get_results()
             .unwrap();
 
@@ -362,7 +360,7 @@ mod test {
 
         let peers = PeerQuery::new()
             .until(|peers| peers.len() == 100)
-            .executor(&db)
+            .executor(&db, &RoutingTable::new())
             .get_results()
             .unwrap();
 
@@ -377,14 +375,17 @@ mod test {
         let _rng = rand::rngs::OsRng;
         sample_peers.push(create_test_peer(true));
         let db = HashmapDatabase::new();
+        let mut routing_table = RoutingTable::new();
         let mut id_counter = 0;
 
         repeat_with(|| create_test_peer(true)).take(3).for_each(|peer| {
+            routing_table.insert(peer.node_id.clone(), id_counter, peer.banned_until);
             db.insert(id_counter, peer).unwrap();
             id_counter += 1;
         });
 
         repeat_with(|| create_test_peer(false)).take(5).for_each(|peer| {
+            routing_table.insert(peer.node_id.clone(), id_counter, peer.banned_until);
             db.insert(id_counter, peer).unwrap();
             id_counter += 1;
         });
@@ -394,7 +395,7 @@ mod test {
         let peers = PeerQuery::new()
             .sort_by(PeerQuerySortBy::DistanceFrom(&node_id))
             .limit(2)
-            .executor(&db)
+            .executor(&db, &routing_table)
             .get_results()
             .unwrap();
 
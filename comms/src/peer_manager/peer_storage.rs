@@ -27,6 +27,8 @@ use crate::{
         node_id::{NodeDistance, NodeId},
         peer::{Peer, PeerFlags},
         peer_id::{generate_peer_key, PeerId},
+        reputation::{Offence, ReputationConfig},
+        routing_table::RoutingTable,
         PeerFeatures,
         PeerManagerError,
         PeerQuery,
@@ -34,6 +36,7 @@ use crate::{
     protocol::ProtocolId,
     types::{CommsDatabase, CommsPublicKey},
 };
+use chrono::NaiveDateTime;
 use log::*;
 use multiaddr::Multiaddr;
 use rand::{rngs::OsRng, Rng};
@@ -43,11 +46,13 @@ use tari_storage::{IterationResult, KeyValueStore};
 const LOG_TARGET: &str = "comms::peer_manager::peer_storage";
 
 /// PeerStorage provides a mechanism to keep a datastore and a local copy of all peers in sync and allow fast searches
-/// using the node_id, public key or net_address of a peer.
+/// using the node_id, public key or net_address of a peer. A [RoutingTable](crate::peer_manager::RoutingTable) is
+/// kept up to date with the datastore so that distance queries do not need to visit every peer.
 pub struct PeerStorage<DS> {
     pub(crate) peer_db: DS,
     public_key_index: HashMap<CommsPublicKey, PeerId>,
     node_id_index: HashMap<NodeId, PeerId>,
+    routing_table: RoutingTable,
 }
 
 impl<DS> PeerStorage<DS>
@@ -58,11 +63,13 @@ where DS: KeyValueStore<PeerId, Peer>
         // Restore peers and hashmap links from database
         let mut public_key_index = HashMap::new();
         let mut node_id_index = HashMap::new();
+        let mut routing_table = RoutingTable::new();
         let mut total_entries = 0;
         database
             .for_each_ok(|(peer_key, peer)| {
                 total_entries += 1;
                 public_key_index.insert(peer.public_key, peer_key);
+                routing_table.insert(peer.node_id.clone(), peer_key, peer.banned_until);
                 node_id_index.insert(peer.node_id, peer_key);
                 IterationResult::Continue
             })
@@ -78,6 +85,7 @@ where DS: KeyValueStore<PeerId, Peer>
             peer_db: database,
             public_key_index,
             node_id_index,
+            routing_table,
         })
     }
 
@@ -90,11 +98,12 @@ where DS: KeyValueStore<PeerId, Peer>
                 trace!(target: LOG_TARGET, "Replacing peer that has NodeId '{}'", peer.node_id);
                 // Replace existing entry
                 peer.set_id(peer_key);
+                let banned_until = peer.banned_until;
                 self.peer_db
                     .insert(peer_key, peer)
                     .map_err(PeerManagerError::DatabaseError)?;
                 self.remove_index_links(peer_key);
-                self.add_index_links(peer_key, public_key, node_id);
+                self.add_index_links(peer_key, public_key, node_id, banned_until);
                 Ok(peer_key)
             },
             None => {
@@ -103,10 +112,11 @@ where DS: KeyValueStore<PeerId, Peer>
                 // Generate new random peer key
                 let peer_key = generate_peer_key();
                 peer.set_id(peer_key);
+                let banned_until = peer.banned_until;
                 self.peer_db
                     .insert(peer_key, peer)
                     .map_err(PeerManagerError::DatabaseError)?;
-                self.add_index_links(peer_key, public_key, node_id);
+                self.add_index_links(peer_key, public_key, node_id, banned_until);
                 Ok(peer_key)
             },
         }
@@ -160,6 +170,7 @@ where DS: KeyValueStore<PeerId, Peer>
 
                 let public_key = stored_peer.public_key.clone();
                 let node_id = stored_peer.node_id.clone();
+                let banned_until = stored_peer.banned_until;
 
                 self.peer_db
                     .insert(peer_key, stored_peer)
@@ -168,7 +179,9 @@ where DS: KeyValueStore<PeerId, Peer>
                 if must_update_node_id {
                     trace!(target: LOG_TARGET, "Must update node id for peer '{}'", node_id);
                     self.remove_index_links(peer_key);
-                    self.add_index_links(peer_key, public_key, node_id);
+                    self.add_index_links(peer_key, public_key, node_id, banned_until);
+                } else {
+                    self.routing_table.set_banned_until(&node_id, banned_until);
                 }
 
                 Ok(())
@@ -198,20 +211,40 @@ where DS: KeyValueStore<PeerId, Peer>
         Ok(())
     }
 
-    /// Add key pairs to the search hashmaps for a newly added or moved peer
-    fn add_index_links(&mut self, peer_key: PeerId, public_key: CommsPublicKey, node_id: NodeId) {
+    /// Add key pairs to the search hashmaps and the routing table for a newly added or moved peer
+    fn add_index_links(
+        &mut self,
+        peer_key: PeerId,
+        public_key: CommsPublicKey,
+        node_id: NodeId,
+        banned_until: Option<NaiveDateTime>,
+    )
+    {
+        self.routing_table.insert(node_id.clone(), peer_key, banned_until);
         self.node_id_index.insert(node_id, peer_key);
         self.public_key_index.insert(public_key, peer_key);
     }
 
-    /// Remove the peer specified by a given index from the database and remove hashmap keys
+    /// Remove the peer specified by a given index from the database and remove hashmap keys and routing table entry
     fn remove_index_links(&mut self, peer_key: PeerId) {
         let initial_size_pk = self.public_key_index.len();
         let initial_size_node_id = self.node_id_index.len();
+        let routing_table = &mut self.routing_table;
         self.public_key_index = self.public_key_index.drain().filter(|(_, k)| k != &peer_key).collect();
-        self.node_id_index = self.node_id_index.drain().filter(|(_, k)| k != &peer_key).collect();
+        self.node_id_index = self
+            .node_id_index
+            .drain()
+            .filter(|(node_id, k)| {
+                if k == &peer_key {
+                    routing_table.remove(node_id);
+                    return false;
+                }
+                true
+            })
+            .collect();
         debug_assert_eq!(initial_size_pk - 1, self.public_key_index.len());
         debug_assert_eq!(initial_size_node_id - 1, self.node_id_index.len());
+        debug_assert_eq!(self.node_id_index.len(), self.routing_table.len());
     }
 
     /// Find the peer with the provided NodeID
@@ -273,7 +306,7 @@ where DS: KeyValueStore<PeerId, Peer>
 
     /// Perform an ad-hoc query on the peer database.
     pub fn perform_query(&self, query: PeerQuery) -> Result<Vec<Peer>, PeerManagerError> {
-        query.executor(&self.peer_db).get_results()
+        query.executor(&self.peer_db, &self.routing_table).get_results()
     }
 
     /// Return all peers
@@ -299,7 +332,7 @@ where DS: KeyValueStore<PeerId, Peer>
         self.peer_db.for_each_ok(|(_, peer)| f(peer)).map_err(Into::into)
     }
 
-    /// Compile a list of peers
+    /// Compile a list of the `n` peers closest to `node_id` that are not banned, offline or excluded
     pub fn closest_peers(
         &self,
         node_id: &NodeId,
@@ -308,42 +341,25 @@ where DS: KeyValueStore<PeerId, Peer>
         features: Option<PeerFeatures>,
     ) -> Result<Vec<Peer>, PeerManagerError>
     {
-        let mut peer_keys = Vec::new();
-        let mut dists = Vec::new();
-        self.peer_db
-            .for_each_ok(|(peer_key, peer)| {
-                if features.map(|f| peer.features == f).unwrap_or(true) &&
-                    !peer.is_banned() &&
-                    !peer.is_offline() &&
-                    !excluded_peers.contains(&peer.public_key)
-                {
-                    peer_keys.push(peer_key);
-                    dists.push(node_id.distance(&peer.node_id));
-                }
-                IterationResult::Continue
-            })
-            .map_err(PeerManagerError::DatabaseError)?;
-        // Use all available peers up to a maximum of N
-        let max_available = cmp::min(peer_keys.len(), n);
-        if max_available == 0 {
-            return Ok(Vec::new());
-        }
-
-        // Perform partial sort of elements only up to N elements
-        let mut nearest_identities = Vec::with_capacity(max_available);
-        for i in 0..max_available {
-            for j in (i + 1)..peer_keys.len() {
-                if dists[i] > dists[j] {
-                    dists.swap(i, j);
-                    peer_keys.swap(i, j);
-                }
+        let mut nearest_identities = Vec::with_capacity(cmp::min(n, self.routing_table.len()));
+        for entry in self.routing_table.closest(node_id) {
+            if nearest_identities.len() >= n {
+                break;
+            }
+            if entry.is_banned() {
+                continue;
             }
             let peer = self
                 .peer_db
-                .get(&peer_keys[i])
+                .get(&entry.peer_key())
                 .map_err(PeerManagerError::DatabaseError)?
                 .ok_or_else(|| PeerManagerError::PeerNotFoundError)?;
-            nearest_identities.push(peer);
+            if features.map(|f| peer.features == f).unwrap_or(true) &&
+                !peer.is_offline() &&
+                !excluded_peers.contains(&peer.public_key)
+            {
+                nearest_identities.push(peer);
+            }
         }
 
         Ok(nearest_identities)
@@ -437,12 +453,19 @@ where DS: KeyValueStore<PeerId, Peer>
             self.peer_db
                 .insert(peer_key, peer)
                 .map_err(PeerManagerError::DatabaseError)?;
+            self.routing_table.set_banned_until(&node_id, None);
         }
         Ok(node_id)
     }
 
     /// Ban the peer for the given duration
-    pub fn ban_for(&mut self, public_key: &CommsPublicKey, duration: Duration) -> Result<NodeId, PeerManagerError> {
+    pub fn ban_for(
+        &mut self,
+        public_key: &CommsPublicKey,
+        duration: Duration,
+        reason: String,
+    ) -> Result<NodeId, PeerManagerError>
+    {
         let peer_key = *self
             .public_key_index
             .get(&public_key)
@@ -452,14 +475,58 @@ where DS: KeyValueStore<PeerId, Peer>
             .get(&peer_key)
             .map_err(PeerManagerError::DatabaseError)?
             .ok_or_else(|| PeerManagerError::PeerNotFoundError)?;
-        peer.ban_for(duration);
+        peer.ban_for(duration, reason);
         let node_id = peer.node_id.clone();
+        let banned_until = peer.banned_until;
         self.peer_db
             .insert(peer_key, peer)
             .map_err(PeerManagerError::DatabaseError)?;
+        self.routing_table.set_banned_until(&node_id, banned_until);
         Ok(node_id)
     }
 
+    /// Add the score of the given offence to the peer's reputation. If the peer's score reaches the ban threshold, the
+    /// peer is banned for the configured ban duration and its score is reset. Returns true if the peer was banned,
+    /// otherwise false.
+    pub fn report_offence(
+        &mut self,
+        public_key: &CommsPublicKey,
+        offence: Offence,
+        config: &ReputationConfig,
+    ) -> Result<bool, PeerManagerError>
+    {
+        let peer_key = *self
+            .public_key_index
+            .get(&public_key)
+            .ok_or_else(|| PeerManagerError::PeerNotFoundError)?;
+        let mut peer: Peer = self
+            .peer_db
+            .get(&peer_key)
+            .map_err(PeerManagerError::DatabaseError)?
+            .ok_or_else(|| PeerManagerError::PeerNotFoundError)?;
+
+        let must_ban = peer.reputation.add_offence(offence, config);
+        if must_ban {
+            debug!(
+                target: LOG_TARGET,
+                "Peer '{}' reached the reputation ban threshold ({}). Banning for {:.0?}",
+                peer.node_id.short_str(),
+                offence,
+                config.ban_duration
+            );
+            peer.reputation.reset();
+            peer.ban_for(
+                config.ban_duration,
+                format!("Reputation threshold reached ({})", offence),
+            );
+            self.routing_table.set_banned_until(&peer.node_id, peer.banned_until);
+        }
+        self.peer_db
+            .insert(peer_key, peer)
+            .map_err(PeerManagerError::DatabaseError)?;
+        Ok(must_ban)
+    }
+
     /// Changes the OFFLINE flag bit of the peer
     pub fn set_offline(&mut self, public_key: &CommsPublicKey, ban_flag: bool) -> Result<NodeId, PeerManagerError> {
         let peer_key = *self
@@ -479,6 +546,23 @@ where DS: KeyValueStore<PeerId, Peer>
         Ok(node_id)
     }
 
+    /// Sets the proof-of-work nonce of the peer
+    pub fn set_proof_of_work_nonce(&mut self, public_key: &CommsPublicKey, nonce: u64) -> Result<(), PeerManagerError> {
+        let peer_key = *self
+            .public_key_index
+            .get(&public_key)
+            .ok_or_else(|| PeerManagerError::PeerNotFoundError)?;
+        let mut peer: Peer = self
+            .peer_db
+            .get(&peer_key)
+            .map_err(PeerManagerError::DatabaseError)?
+            .ok_or_else(|| PeerManagerError::PeerNotFoundError)?;
+        peer.proof_of_work_nonce = Some(nonce);
+        self.peer_db
+            .insert(peer_key, peer)
+            .map_err(PeerManagerError::DatabaseError)
+    }
+
     /// Enables Thread safe access - Adds a new net address to the peer if it doesn't yet exist
     pub fn add_net_address(&mut self, node_id: &NodeId, net_address: &Multiaddr) -> Result<(), PeerManagerError> {
         let peer_key = *self
@@ -825,4 +909,58 @@ mod test {
         assert!(peer_storage.find_by_public_key(&peer2.public_key).is_err());
         assert!(peer_storage.find_by_public_key(&peer3.public_key).is_ok());
     }
+
+    fn create_test_peer() -> Peer {
+        let (_sk, pk) = RistrettoPublicKey::random_keypair(&mut rand::rngs::OsRng);
+        let node_id = NodeId::from_key(&pk).unwrap();
+        let net_addresses = MultiaddressesWithStats::from("/ip4/1.2.3.4/tcp/8000".parse::<Multiaddr>().unwrap());
+        Peer::new(
+            pk,
+            node_id,
+            net_addresses,
+            PeerFlags::default(),
+            PeerFeatures::COMMUNICATION_NODE,
+            &[],
+        )
+    }
+
+    #[test]
+    fn closest_peers_uses_routing_table() {
+        let mut peer_storage = PeerStorage::new_indexed(HashmapDatabase::new()).unwrap();
+        let mut peers = (0..50).map(|_| create_test_peer()).collect::<Vec<_>>();
+        for peer in &peers {
+            peer_storage.add_peer(peer.clone()).unwrap();
+        }
+        assert_eq!(peer_storage.routing_table.len(), 50);
+
+        let target = NodeId::default();
+        peers.sort_by(|a, b| target.distance(&a.node_id).cmp(&target.distance(&b.node_id)));
+
+        let closest = peer_storage.closest_peers(&target, 5, &[], None).unwrap();
+        assert_eq!(
+            closest.iter().map(|p| &p.node_id).collect::<Vec<_>>(),
+            peers.iter().take(5).map(|p| &p.node_id).collect::<Vec<_>>()
+        );
+
+        // Banned and deleted peers are excluded
+        peer_storage
+            .ban_for(&peers[0].public_key, Duration::from_secs(100), "Test".to_string())
+            .unwrap();
+        peer_storage.delete_peer(&peers[1].node_id).unwrap();
+        assert_eq!(peer_storage.routing_table.len(), 49);
+        let closest = peer_storage.closest_peers(&target, 5, &[], None).unwrap();
+        assert_eq!(
+            closest.iter().map(|p| &p.node_id).collect::<Vec<_>>(),
+            peers.iter().skip(2).take(5).map(|p| &p.node_id).collect::<Vec<_>>()
+        );
+
+        // Unbanned peers are included again
+        peer_storage.unban(&peers[0].public_key).unwrap();
+        let closest = peer_storage.closest_peers(&target, 1, &[], None).unwrap();
+        assert_eq!(closest[0].node_id, peers[0].node_id);
+
+        // Restoring from the database rebuilds the routing table
+        let peer_storage = PeerStorage::new_indexed(peer_storage.peer_db).unwrap();
+        assert_eq!(peer_storage.routing_table.len(), 49);
+    }
 }
@@ -0,0 +1,176 @@
+//  Copyright 2020, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Peer reputation tracking.
+//!
+//! Components that detect misbehaviour (invalid messages, invalid blocks or transactions, protocol violations) report
+//! an [Offence](self::Offence) against the offending peer. Each offence carries a score that is added to the peer's
+//! reputation score. Scores decay exponentially over time, so that occasional offences are forgiven, and once a peer's
+//! score reaches the configured ban threshold the peer is banned.
+
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{fmt, time::Duration};
+
+/// Misbehaviour that can be reported against a peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Offence {
+    /// A message failed validation (invalid header, signature etc.)
+    InvalidMessage,
+    /// The peer sent an invalid block
+    InvalidBlock,
+    /// The peer sent an invalid transaction
+    InvalidTransaction,
+    /// The peer did not follow a protocol correctly
+    ProtocolViolation,
+    /// The peer sent messages faster than the inbound rate limit allows
+    RateLimitExceeded,
+}
+
+impl Offence {
+    /// The reputation score added to a peer for this offence
+    pub fn score(self) -> f64 {
+        use Offence::*;
+        match self {
+            RateLimitExceeded => 5.0,
+            InvalidMessage => 10.0,
+            InvalidTransaction => 25.0,
+            ProtocolViolation => 25.0,
+            InvalidBlock => 50.0,
+        }
+    }
+}
+
+impl fmt::Display for Offence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Offence::*;
+        match self {
+            InvalidMessage => write!(f, "Invalid message"),
+            InvalidBlock => write!(f, "Invalid block"),
+            InvalidTransaction => write!(f, "Invalid transaction"),
+            ProtocolViolation => write!(f, "Protocol violation"),
+            RateLimitExceeded => write!(f, "Rate limit exceeded"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReputationConfig {
+    /// Peers are banned once their reputation score reaches this value. Default: 100
+    pub ban_threshold: f64,
+    /// The time taken for a peer's reputation score to decay to half its value. Default: 1 hour
+    pub decay_half_life: Duration,
+    /// The length of time a peer is banned for once it reaches the ban threshold. Default: 6 hours
+    pub ban_duration: Duration,
+}
+
+impl Default for ReputationConfig {
+    fn default() -> Self {
+        Self {
+            ban_threshold: 100.0,
+            decay_half_life: Duration::from_secs(60 * 60),
+            ban_duration: Duration::from_secs(6 * 60 * 60),
+        }
+    }
+}
+
+/// The current reputation score of a peer. A score of zero indicates no recent offences.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct PeerReputation {
+    score: f64,
+    last_updated: Option<NaiveDateTime>,
+}
+
+impl PeerReputation {
+    /// Returns the score, decayed up to the current time
+    pub fn score(&self, config: &ReputationConfig) -> f64 {
+        self.decayed_score(config, Utc::now().naive_utc())
+    }
+
+    /// Add the score for the given offence. Returns true if the resulting score has reached the ban threshold.
+    pub fn add_offence(&mut self, offence: Offence, config: &ReputationConfig) -> bool {
+        self.add_offence_at(offence, config, Utc::now().naive_utc())
+    }
+
+    /// Reset the reputation score to zero
+    pub fn reset(&mut self) {
+        self.score = 0.0;
+        self.last_updated = None;
+    }
+
+    fn add_offence_at(&mut self, offence: Offence, config: &ReputationConfig, now: NaiveDateTime) -> bool {
+        self.score = self.decayed_score(config, now) + offence.score();
+        self.last_updated = Some(now);
+        self.score >= config.ban_threshold
+    }
+
+    fn decayed_score(&self, config: &ReputationConfig, now: NaiveDateTime) -> f64 {
+        match self.last_updated {
+            Some(last_updated) => {
+                let elapsed = now
+                    .signed_duration_since(last_updated)
+                    .to_std()
+                    .unwrap_or_else(|_| Duration::from_secs(0));
+                let half_life = config.decay_half_life.as_secs_f64();
+                if half_life <= 0.0 {
+                    return 0.0;
+                }
+                self.score * 0.5f64.powf(elapsed.as_secs_f64() / half_life)
+            },
+            None => self.score,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn add_offence() {
+        let config = ReputationConfig::default();
+        let mut reputation = PeerReputation::default();
+        assert_eq!(reputation.score(&config), 0.0);
+
+        assert_eq!(reputation.add_offence(Offence::InvalidBlock, &config), false);
+        assert!(reputation.score(&config) > 49.0);
+        assert_eq!(reputation.add_offence(Offence::InvalidBlock, &config), true);
+
+        reputation.reset();
+        assert_eq!(reputation.score(&config), 0.0);
+    }
+
+    #[test]
+    fn score_decays() {
+        let config = ReputationConfig::default();
+        let mut reputation = PeerReputation::default();
+        let then = Utc::now().naive_utc() - chrono::Duration::hours(1);
+        reputation.add_offence_at(Offence::InvalidBlock, &config, then);
+        let score = reputation.decayed_score(&config, then + chrono::Duration::hours(1));
+        assert!((score - 25.0).abs() < 0.001);
+
+        // Decayed score is taken into account when adding an offence
+        let banned = reputation.add_offence_at(Offence::InvalidBlock, &config, then + chrono::Duration::hours(1));
+        assert_eq!(banned, false);
+        assert!((reputation.score - 75.0).abs() < 0.001);
+    }
+}
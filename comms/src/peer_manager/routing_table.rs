@@ -0,0 +1,238 @@
+// Copyright 2019, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::peer_manager::{node_id::NodeId, peer_id::PeerId};
+use chrono::{NaiveDateTime, Utc};
+use tari_crypto::tari_utilities::ByteArray;
+
+const NUM_BUCKETS: usize = 256;
+
+/// A routing table entry, referencing a peer in the peer database by its `PeerId`
+#[derive(Debug, Clone)]
+pub struct RoutingEntry {
+    node_id: NodeId,
+    peer_key: PeerId,
+    banned_until: Option<NaiveDateTime>,
+}
+
+impl RoutingEntry {
+    pub fn node_id(&self) -> &NodeId {
+        &self.node_id
+    }
+
+    pub fn peer_key(&self) -> PeerId {
+        self.peer_key
+    }
+
+    /// Returns true if the peer was banned at the time this entry was last updated and the ban has not expired
+    pub fn is_banned(&self) -> bool {
+        self.banned_until
+            .as_ref()
+            .filter(|dt| *dt > &Utc::now().naive_utc())
+            .is_some()
+    }
+}
+
+/// A bucketed index of peer `NodeId`s used to answer "closest peers to X" queries without visiting every peer.
+///
+/// Peers are placed in one of 256 buckets by the first byte of their `NodeId`. All peers in a bucket share the first
+/// byte of their XOR distance to any given node id, so buckets can be visited in order of increasing distance from the
+/// query node id and only the buckets needed to satisfy a query have to be sorted.
+#[derive(Debug, Clone)]
+pub struct RoutingTable {
+    buckets: Vec<Vec<RoutingEntry>>,
+    len: usize,
+}
+
+impl RoutingTable {
+    pub fn new() -> Self {
+        Self {
+            buckets: vec![Vec::new(); NUM_BUCKETS],
+            len: 0,
+        }
+    }
+
+    /// Insert a peer into the routing table. If an entry for the `NodeId` exists, it is replaced.
+    pub fn insert(&mut self, node_id: NodeId, peer_key: PeerId, banned_until: Option<NaiveDateTime>) {
+        let entry = RoutingEntry {
+            node_id,
+            peer_key,
+            banned_until,
+        };
+        let bucket = &mut self.buckets[Self::bucket_index(&entry.node_id)];
+        match bucket.iter_mut().find(|e| e.node_id == entry.node_id) {
+            Some(existing) => {
+                *existing = entry;
+            },
+            None => {
+                bucket.push(entry);
+                self.len += 1;
+            },
+        }
+    }
+
+    /// Remove the entry for the given `NodeId`, returning it if it existed
+    pub fn remove(&mut self, node_id: &NodeId) -> Option<RoutingEntry> {
+        let bucket = &mut self.buckets[Self::bucket_index(node_id)];
+        let pos = bucket.iter().position(|e| &e.node_id == node_id)?;
+        self.len -= 1;
+        Some(bucket.swap_remove(pos))
+    }
+
+    /// Update the ban state of the entry for the given `NodeId`. Returns false if the entry does not exist.
+    pub fn set_banned_until(&mut self, node_id: &NodeId, banned_until: Option<NaiveDateTime>) -> bool {
+        match self.buckets[Self::bucket_index(node_id)]
+            .iter_mut()
+            .find(|e| &e.node_id == node_id)
+        {
+            Some(entry) => {
+                entry.banned_until = banned_until;
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn contains(&self, node_id: &NodeId) -> bool {
+        self.buckets[Self::bucket_index(node_id)]
+            .iter()
+            .any(|e| &e.node_id == node_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an iterator over all entries in order of increasing distance from the given `NodeId`. Buckets are
+    /// sorted lazily, so taking the first few entries only sorts the buckets closest to `node_id`.
+    pub fn closest<'a>(&'a self, node_id: &'a NodeId) -> ClosestEntries<'a> {
+        ClosestEntries {
+            table: self,
+            node_id,
+            next_distance_prefix: 0,
+            current: Vec::new(),
+        }
+    }
+
+    fn bucket_index(node_id: &NodeId) -> usize {
+        node_id.as_bytes()[0] as usize
+    }
+}
+
+impl Default for RoutingTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Iterator returned by [RoutingTable::closest](self::RoutingTable::closest)
+pub struct ClosestEntries<'a> {
+    table: &'a RoutingTable,
+    node_id: &'a NodeId,
+    next_distance_prefix: usize,
+    /// Entries of the current bucket, sorted by _descending_ distance so that the closest entry can be popped
+    current: Vec<&'a RoutingEntry>,
+}
+
+impl<'a> Iterator for ClosestEntries<'a> {
+    type Item = &'a RoutingEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current.is_empty() {
+            if self.next_distance_prefix >= NUM_BUCKETS {
+                return None;
+            }
+            // The bucket containing peers whose distance from node_id starts with next_distance_prefix
+            let index = RoutingTable::bucket_index(self.node_id) ^ self.next_distance_prefix;
+            self.next_distance_prefix += 1;
+
+            let node_id = self.node_id;
+            let mut entries = self.table.buckets[index].iter().collect::<Vec<_>>();
+            entries.sort_by(|a, b| node_id.distance(&b.node_id).cmp(&node_id.distance(&a.node_id)));
+            self.current = entries;
+        }
+
+        self.current.pop()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::node_id;
+    use chrono::Duration;
+
+    fn make_table(n: usize) -> (RoutingTable, Vec<NodeId>) {
+        let mut table = RoutingTable::new();
+        let node_ids = (0..n).map(|_| node_id::random()).collect::<Vec<_>>();
+        for (i, node_id) in node_ids.iter().enumerate() {
+            table.insert(node_id.clone(), i as PeerId, None);
+        }
+        (table, node_ids)
+    }
+
+    #[test]
+    fn insert_remove() {
+        let (mut table, node_ids) = make_table(100);
+        assert_eq!(table.len(), 100);
+        assert!(table.contains(&node_ids[10]));
+
+        // Replacing an entry does not change the size
+        table.insert(node_ids[10].clone(), 1000, None);
+        assert_eq!(table.len(), 100);
+
+        let entry = table.remove(&node_ids[10]).unwrap();
+        assert_eq!(entry.peer_key(), 1000);
+        assert_eq!(table.len(), 99);
+        assert!(!table.contains(&node_ids[10]));
+        assert!(table.remove(&node_ids[10]).is_none());
+    }
+
+    #[test]
+    fn closest() {
+        let (table, mut node_ids) = make_table(1000);
+        let target = node_id::random();
+
+        let closest = table.closest(&target).map(|e| e.node_id().clone()).collect::<Vec<_>>();
+        assert_eq!(closest.len(), 1000);
+
+        node_ids.sort_by(|a, b| target.distance(a).cmp(&target.distance(b)));
+        assert_eq!(closest, node_ids);
+    }
+
+    #[test]
+    fn set_banned_until() {
+        let (mut table, node_ids) = make_table(10);
+        let banned_until = Utc::now().naive_utc() + Duration::minutes(10);
+        assert!(table.set_banned_until(&node_ids[0], Some(banned_until)));
+        assert!(table.closest(&node_ids[0]).next().unwrap().is_banned());
+
+        assert!(table.set_banned_until(&node_ids[0], None));
+        assert!(!table.closest(&node_ids[0]).next().unwrap().is_banned());
+
+        assert!(!table.set_banned_until(&node_id::random(), None));
+    }
+}
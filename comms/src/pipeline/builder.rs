@@ -22,7 +22,7 @@
 
 use crate::{
     message::{InboundMessage, OutboundMessage},
-    pipeline::SinkService,
+    pipeline::{RateLimitConfig, SinkService},
 };
 use derive_error::Error;
 use futures::channel::mpsc;
@@ -37,6 +37,7 @@ type OutboundMessageSinkService = SinkService<mpsc::Sender<OutboundMessage>>;
 pub struct Builder<TInSvc, TOutSvc, TOutReq> {
     max_concurrent_inbound_tasks: usize,
     outbound_buffer_size: usize,
+    inbound_rate_limit: RateLimitConfig,
     inbound: Option<TInSvc>,
     outbound_rx: Option<mpsc::Receiver<TOutReq>>,
     outbound_pipeline_factory: Option<Box<dyn FnOnce(OutboundMessageSinkService) -> TOutSvc>>,
@@ -47,6 +48,7 @@ impl Builder<(), (), ()> {
         Self {
             max_concurrent_inbound_tasks: DEFAULT_MAX_CONCURRENT_TASKS,
             outbound_buffer_size: DEFAULT_OUTBOUND_BUFFER_SIZE,
+            inbound_rate_limit: Default::default(),
             inbound: None,
             outbound_rx: None,
             outbound_pipeline_factory: None,
@@ -65,6 +67,12 @@ impl<TInSvc, TOutSvc, TOutReq> Builder<TInSvc, TOutSvc, TOutReq> {
         self
     }
 
+    /// Set the global and per-peer rate limits for inbound messages
+    pub fn inbound_rate_limit(mut self, config: RateLimitConfig) -> Self {
+        self.inbound_rate_limit = config;
+        self
+    }
+
     pub fn with_outbound_pipeline<F, S, R>(self, receiver: mpsc::Receiver<R>, factory: F) -> Builder<TInSvc, S, R>
     where
         // Factory function takes in a SinkService and returns a new composed service
@@ -77,6 +85,7 @@ impl<TInSvc, TOutSvc, TOutReq> Builder<TInSvc, TOutSvc, TOutReq> {
 
             max_concurrent_inbound_tasks: self.max_concurrent_inbound_tasks,
             inbound: self.inbound,
+            inbound_rate_limit: self.inbound_rate_limit,
             outbound_buffer_size: self.outbound_buffer_size,
         }
     }
@@ -87,6 +96,7 @@ impl<TInSvc, TOutSvc, TOutReq> Builder<TInSvc, TOutSvc, TOutReq> {
             inbound: Some(inbound),
 
             max_concurrent_inbound_tasks: self.max_concurrent_inbound_tasks,
+            inbound_rate_limit: self.inbound_rate_limit,
             outbound_rx: self.outbound_rx,
             outbound_pipeline_factory: self.outbound_pipeline_factory,
             outbound_buffer_size: self.outbound_buffer_size,
@@ -130,6 +140,7 @@ where
 
         Ok(Config {
             max_concurrent_inbound_tasks: self.max_concurrent_inbound_tasks,
+            inbound_rate_limit: self.inbound_rate_limit,
             inbound,
             outbound,
         })
@@ -151,6 +162,7 @@ pub struct OutboundPipelineConfig<TInStream, TPipeline> {
 
 pub struct Config<TInSvc, TOutSvc, TOutReq> {
     pub max_concurrent_inbound_tasks: usize,
+    pub inbound_rate_limit: RateLimitConfig,
     pub inbound: TInSvc,
     pub outbound: OutboundPipelineConfig<mpsc::Receiver<TOutReq>, TOutSvc>,
 }
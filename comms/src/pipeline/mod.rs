@@ -47,5 +47,8 @@ pub(crate) use inbound::Inbound;
 mod outbound;
 pub(crate) use outbound::Outbound;
 
+mod rate_limit;
+pub use rate_limit::{RateLimitConfig, RateLimitLayer, RateLimitService, TokenBucket};
+
 mod translate_sink;
 pub use translate_sink::TranslateSink;
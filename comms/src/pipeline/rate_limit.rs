@@ -0,0 +1,414 @@
+// Copyright 2020, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Inbound message rate limiting.
+//!
+//! Inbound messages are limited by two token buckets: one shared by all peers and one for each peer. A message is
+//! only passed on to the inbound pipeline if a token is available in both buckets. Messages that exceed a peer's
+//! limit are dropped and reported as an [Offence](crate::peer_manager::Offence) against that peer. Messages that
+//! exceed the global limit are dropped without penalising the sending peer.
+
+use crate::{
+    message::InboundMessage,
+    peer_manager::{NodeId, Offence, PeerManager},
+};
+use futures::{task::Context, Future};
+use log::*;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    task::Poll,
+    time::{Duration, Instant},
+};
+use tower::{layer::Layer, Service, ServiceExt};
+
+const LOG_TARGET: &str = "comms::pipeline::rate_limit";
+
+/// The maximum number of per-peer buckets that are kept. When a new peer arrives and this limit has been reached, the
+/// bucket of the least recently seen peer is discarded.
+const MAX_TRACKED_PEERS: usize = 1000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitConfig {
+    /// The sustained number of messages per second accepted from all peers
+    /// Default: 1000
+    pub global_rate: u32,
+    /// The maximum number of messages from all peers that can be accepted in a burst
+    /// Default: 2500
+    pub global_burst: u32,
+    /// The sustained number of messages per second accepted from a single peer
+    /// Default: 100
+    pub peer_rate: u32,
+    /// The maximum number of messages from a single peer that can be accepted in a burst
+    /// Default: 500
+    pub peer_burst: u32,
+    /// The minimum time between offence reports for a peer that continues to exceed its rate limit
+    /// Default: 10 seconds
+    pub offence_report_interval: Duration,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            global_rate: 1000,
+            global_burst: 2500,
+            peer_rate: 100,
+            peer_burst: 500,
+            offence_report_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+/// A token bucket which holds up to `capacity` tokens and is refilled at `rate` tokens per second
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Create a new, full, token bucket
+    pub fn new(rate: u32, capacity: u32) -> Self {
+        Self::new_at(rate, capacity, Instant::now())
+    }
+
+    fn new_at(rate: u32, capacity: u32, now: Instant) -> Self {
+        Self {
+            capacity: f64::from(capacity),
+            rate: f64::from(rate),
+            tokens: f64::from(capacity),
+            last_refill: now,
+        }
+    }
+
+    /// Take a token from the bucket. Returns false if the bucket is empty.
+    pub fn try_take(&mut self) -> bool {
+        self.try_take_at(Instant::now())
+    }
+
+    fn try_take_at(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Put a previously taken token back into the bucket
+    fn restore(&mut self) {
+        self.tokens = (self.tokens + 1.0).min(self.capacity);
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.rate).min(self.capacity);
+        self.last_refill = now;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RateLimitResult {
+    Allowed,
+    /// The peer exceeded its limit. `should_report` is true if an offence should be reported against the peer.
+    PeerLimitExceeded {
+        should_report: bool,
+    },
+    GlobalLimitExceeded,
+}
+
+struct PeerBucket {
+    bucket: TokenBucket,
+    last_reported: Option<Instant>,
+    last_seen: Instant,
+}
+
+struct RateLimiterState {
+    global: TokenBucket,
+    peers: HashMap<NodeId, PeerBucket>,
+}
+
+/// Shared global and per-peer token buckets
+struct RateLimiter {
+    config: RateLimitConfig,
+    state: Mutex<RateLimiterState>,
+}
+
+impl RateLimiter {
+    fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(RateLimiterState {
+                global: TokenBucket::new(config.global_rate, config.global_burst),
+                peers: HashMap::new(),
+            }),
+        }
+    }
+
+    fn check(&self, node_id: &NodeId) -> RateLimitResult {
+        self.check_at(node_id, Instant::now())
+    }
+
+    fn check_at(&self, node_id: &NodeId, now: Instant) -> RateLimitResult {
+        let config = &self.config;
+        let mut state = acquire_lock!(self.state);
+
+        if state.peers.len() >= MAX_TRACKED_PEERS && !state.peers.contains_key(node_id) {
+            let least_recent = state
+                .peers
+                .iter()
+                .min_by_key(|(_, peer)| peer.last_seen)
+                .map(|(node_id, _)| node_id.clone());
+            if let Some(node_id) = least_recent {
+                state.peers.remove(&node_id);
+            }
+        }
+
+        let RateLimiterState { global, peers } = &mut *state;
+        let peer = peers.entry(node_id.clone()).or_insert_with(|| PeerBucket {
+            bucket: TokenBucket::new_at(config.peer_rate, config.peer_burst, now),
+            last_reported: None,
+            last_seen: now,
+        });
+        peer.last_seen = now;
+
+        // The peer limit is checked first so that a single flooding peer cannot use up the global allowance
+        if !peer.bucket.try_take_at(now) {
+            let should_report = peer
+                .last_reported
+                .map(|t| now.saturating_duration_since(t) >= config.offence_report_interval)
+                .unwrap_or(true);
+            if should_report {
+                peer.last_reported = Some(now);
+            }
+            return RateLimitResult::PeerLimitExceeded { should_report };
+        }
+
+        if !global.try_take_at(now) {
+            // The peer is not at fault, so it keeps its token
+            peer.bucket.restore();
+            return RateLimitResult::GlobalLimitExceeded;
+        }
+
+        RateLimitResult::Allowed
+    }
+}
+
+/// Layer which drops inbound messages that exceed the global or per-peer rate limits
+pub struct RateLimitLayer {
+    limiter: Arc<RateLimiter>,
+    peer_manager: Arc<PeerManager>,
+}
+
+impl RateLimitLayer {
+    pub fn new(config: RateLimitConfig, peer_manager: Arc<PeerManager>) -> Self {
+        Self {
+            limiter: Arc::new(RateLimiter::new(config)),
+            peer_manager,
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        RateLimitService {
+            next_service: service,
+            limiter: Arc::clone(&self.limiter),
+            peer_manager: Arc::clone(&self.peer_manager),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimitService<S> {
+    next_service: S,
+    limiter: Arc<RateLimiter>,
+    peer_manager: Arc<PeerManager>,
+}
+
+impl<S> Service<InboundMessage> for RateLimitService<S>
+where S: Service<InboundMessage, Response = ()> + Clone + 'static
+{
+    type Error = S::Error;
+    type Response = ();
+
+    type Future = impl Future<Output = Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, message: InboundMessage) -> Self::Future {
+        let next_service = self.next_service.clone();
+        let peer_manager = Arc::clone(&self.peer_manager);
+        let result = self.limiter.check(&message.source_peer.node_id);
+        async move {
+            match result {
+                RateLimitResult::Allowed => next_service.oneshot(message).await,
+                RateLimitResult::PeerLimitExceeded { should_report } => {
+                    let peer = &message.source_peer;
+                    debug!(
+                        target: LOG_TARGET,
+                        "Peer '{}' exceeded the inbound message rate limit. Discarding message {}",
+                        peer.node_id.short_str(),
+                        message.tag
+                    );
+                    if should_report {
+                        warn!(
+                            target: LOG_TARGET,
+                            "Peer '{}' is sending messages faster than the inbound rate limit",
+                            peer.node_id.short_str()
+                        );
+                        if let Err(err) = peer_manager
+                            .report_offence(&peer.public_key, Offence::RateLimitExceeded)
+                            .await
+                        {
+                            debug!(
+                                target: LOG_TARGET,
+                                "Failed to report offence for peer '{}': {:?}",
+                                peer.node_id.short_str(),
+                                err
+                            );
+                        }
+                    }
+                    Ok(())
+                },
+                RateLimitResult::GlobalLimitExceeded => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Global inbound message rate limit exceeded. Discarding message {} from peer '{}'",
+                        message.tag,
+                        message.source_peer.node_id.short_str()
+                    );
+                    Ok(())
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::node_id;
+
+    fn config() -> RateLimitConfig {
+        RateLimitConfig {
+            global_rate: 10,
+            global_burst: 4,
+            peer_rate: 1,
+            peer_burst: 2,
+            offence_report_interval: Duration::from_secs(10),
+        }
+    }
+
+    #[test]
+    fn token_bucket_refill() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new_at(2, 2, now);
+        assert!(bucket.try_take_at(now));
+        assert!(bucket.try_take_at(now));
+        assert!(!bucket.try_take_at(now));
+
+        assert!(bucket.try_take_at(now + Duration::from_millis(500)));
+        assert!(!bucket.try_take_at(now + Duration::from_millis(500)));
+
+        // Tokens do not accumulate past the capacity
+        bucket.refill(now + Duration::from_secs(60));
+        assert!((bucket.tokens - bucket.capacity).abs() < f64::EPSILON);
+        assert!(bucket.try_take_at(now + Duration::from_secs(60)));
+        assert!(bucket.try_take_at(now + Duration::from_secs(60)));
+        assert!(!bucket.try_take_at(now + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn peer_limit() {
+        let limiter = RateLimiter::new(config());
+        let now = Instant::now();
+        let peer1 = node_id::random();
+        let peer2 = node_id::random();
+
+        assert_eq!(limiter.check_at(&peer1, now), RateLimitResult::Allowed);
+        assert_eq!(limiter.check_at(&peer1, now), RateLimitResult::Allowed);
+        assert_eq!(limiter.check_at(&peer1, now), RateLimitResult::PeerLimitExceeded {
+            should_report: true
+        });
+        // Reports are throttled
+        assert_eq!(limiter.check_at(&peer1, now), RateLimitResult::PeerLimitExceeded {
+            should_report: false
+        });
+        // Other peers are unaffected
+        assert_eq!(limiter.check_at(&peer2, now), RateLimitResult::Allowed);
+
+        let later = now + Duration::from_secs(10);
+        assert_eq!(limiter.check_at(&peer1, later), RateLimitResult::Allowed);
+        assert_eq!(limiter.check_at(&peer1, later), RateLimitResult::Allowed);
+        assert_eq!(limiter.check_at(&peer1, later), RateLimitResult::PeerLimitExceeded {
+            should_report: true
+        });
+    }
+
+    #[test]
+    fn global_limit() {
+        let limiter = RateLimiter::new(config());
+        let now = Instant::now();
+        let peers = (0..3).map(|_| node_id::random()).collect::<Vec<_>>();
+
+        assert_eq!(limiter.check_at(&peers[0], now), RateLimitResult::Allowed);
+        assert_eq!(limiter.check_at(&peers[0], now), RateLimitResult::Allowed);
+        assert_eq!(limiter.check_at(&peers[1], now), RateLimitResult::Allowed);
+        assert_eq!(limiter.check_at(&peers[1], now), RateLimitResult::Allowed);
+        assert_eq!(limiter.check_at(&peers[2], now), RateLimitResult::GlobalLimitExceeded);
+
+        // The global bucket refills faster than the peer bucket, and peer 2 kept its tokens
+        let later = now + Duration::from_millis(200);
+        assert_eq!(limiter.check_at(&peers[2], later), RateLimitResult::Allowed);
+        assert_eq!(limiter.check_at(&peers[2], later), RateLimitResult::Allowed);
+    }
+
+    #[test]
+    fn least_recently_seen_peer_evicted() {
+        let limiter = RateLimiter::new(config());
+        let now = Instant::now();
+        let peers = (0..MAX_TRACKED_PEERS).map(|_| node_id::random()).collect::<Vec<_>>();
+
+        for (i, peer) in peers.iter().enumerate() {
+            limiter.check_at(peer, now + Duration::from_millis(i as u64));
+        }
+        // Peer 0 is seen again, so peer 1 is now the least recently seen
+        limiter.check_at(&peers[0], now + Duration::from_secs(10));
+        assert_eq!(acquire_lock!(limiter.state).peers.len(), MAX_TRACKED_PEERS);
+
+        let new_peer = node_id::random();
+        limiter.check_at(&new_peer, now + Duration::from_secs(10));
+        let state = acquire_lock!(limiter.state);
+        assert_eq!(state.peers.len(), MAX_TRACKED_PEERS);
+        assert!(state.peers.contains_key(&peers[0]));
+        assert!(!state.peers.contains_key(&peers[1]));
+        assert!(state.peers.contains_key(&new_peer));
+    }
+}
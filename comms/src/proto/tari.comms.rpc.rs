@@ -0,0 +1,36 @@
+/// Message type for all RPC requests
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RpcRequest {
+    /// An identifier that is unique per request within a session. Requests and responses alternate, so this is only
+    /// needed to discard late responses and to match cancellations to the request in progress.
+    #[prost(uint32, tag = "1")]
+    pub request_id: u32,
+    /// The method identifier. Each service defines the method that a given value refers to.
+    #[prost(uint32, tag = "2")]
+    pub method: u32,
+    /// Message flags. The FIN flag cancels the request in progress.
+    #[prost(uint32, tag = "3")]
+    pub flags: u32,
+    /// The length of time in milliseconds that the client will wait for a response. 0 means no deadline.
+    #[prost(uint64, tag = "4")]
+    pub deadline: u64,
+    /// The encoded request message
+    #[prost(bytes, tag = "10")]
+    pub payload: std::vec::Vec<u8>,
+}
+/// Message type for all RPC responses
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RpcResponse {
+    /// The request_id of the request that this response is for
+    #[prost(uint32, tag = "1")]
+    pub request_id: u32,
+    /// The status code of the response. 0 is success, other values are errors.
+    #[prost(uint32, tag = "2")]
+    pub status: u32,
+    /// Message flags. The FIN flag marks the last response for a request.
+    #[prost(uint32, tag = "3")]
+    pub flags: u32,
+    /// The encoded response message, or error details if the status is not 0
+    #[prost(bytes, tag = "10")]
+    pub payload: std::vec::Vec<u8>,
+}
@@ -123,6 +123,7 @@ impl OutboundMessaging {
     }
 
     async fn start_forwarding_messages(mut self, substream: CommsSubstream) -> Result<(), MessagingProtocolError> {
+        let traffic = substream.counters();
         let mut framed = MessagingProtocol::framed(substream);
         while let Some(mut out_msg) = self.request_rx.next().await {
             trace!(
@@ -133,6 +134,7 @@ impl OutboundMessaging {
             );
             match framed.send(out_msg.body.clone()).await {
                 Ok(_) => {
+                    traffic.record_message_out();
                     out_msg.reply_success();
                     let _ = self
                         .messaging_events_tx
@@ -25,7 +25,7 @@ use crate::{
     compat::IoCompat,
     connection_manager::{ConnectionManagerEvent, ConnectionManagerRequester},
     message::{InboundMessage, MessageTag, OutboundMessage},
-    peer_manager::{NodeId, NodeIdentity, Peer, PeerManagerError},
+    peer_manager::{NodeId, NodeIdentity, Offence, Peer, PeerManagerError},
     protocol::{messaging::outbound::OutboundMessaging, ProtocolEvent, ProtocolNotification},
     runtime::current_executor,
     types::CommsSubstream,
@@ -38,6 +38,7 @@ use log::*;
 use std::{
     collections::{hash_map::Entry, HashMap},
     fmt,
+    io,
     sync::Arc,
 };
 use tari_shutdown::{Shutdown, ShutdownSignal};
@@ -353,8 +354,10 @@ impl MessagingProtocol {
     }
 
     async fn spawn_inbound_handler(&mut self, peer: Arc<Peer>, substream: CommsSubstream) {
+        let traffic = substream.counters();
         let messaging_events_tx = self.messaging_events_tx.clone();
         let mut inbound_message_tx = self.inbound_message_tx.clone();
+        let peer_manager = Arc::clone(&self.peer_manager);
         let mut framed_substream = Self::framed(substream);
 
         self.executor.spawn(async move {
@@ -367,6 +370,7 @@ impl MessagingProtocol {
                             peer.node_id.short_str(),
                             raw_msg.len()
                         );
+                        traffic.record_message_in();
 
                         let inbound_msg = InboundMessage::new(Arc::clone(&peer), raw_msg.freeze());
 
@@ -406,6 +410,20 @@ impl MessagingProtocol {
                             peer.node_id.short_str(),
                             err
                         );
+                        // The peer sent an invalid frame (e.g. exceeding the maximum frame length)
+                        if err.kind() == io::ErrorKind::InvalidData {
+                            if let Err(err) = peer_manager
+                                .report_offence(&peer.public_key, Offence::ProtocolViolation)
+                                .await
+                            {
+                                debug!(
+                                    target: LOG_TARGET,
+                                    "Failed to report protocol violation for peer '{}': {:?}",
+                                    peer.node_id.short_str(),
+                                    err
+                                );
+                            }
+                        }
                         break;
                     },
                 }
@@ -28,6 +28,7 @@ use super::protocol::{
     MESSAGING_PROTOCOL,
 };
 use crate::{
+    connection_manager::CountingSocket,
     message::{InboundMessage, MessageTag, OutboundMessage},
     net_address::MultiaddressesWithStats,
     peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerFlags, PeerManager},
@@ -134,7 +135,10 @@ async fn new_inbound_substream_handling() {
     proto_tx
         .send(ProtocolNotification::new(
             MESSAGING_PROTOCOL.clone(),
-            ProtocolEvent::NewInboundSubstream(Box::new(expected_node_id.clone()), stream_ours),
+            ProtocolEvent::NewInboundSubstream(
+                Box::new(expected_node_id.clone()),
+                CountingSocket::new(stream_ours, Default::default()),
+            ),
         ))
         .await
         .unwrap();
@@ -33,6 +33,7 @@ mod protocols;
 pub use protocols::{ProtocolEvent, ProtocolNotification, Protocols};
 
 pub mod messaging;
+pub mod rpc;
 
 /// Represents a protocol id string (e.g. /tari/transactions/1.0.0).
 /// This is atomically reference counted, so clones are shallow and cheap
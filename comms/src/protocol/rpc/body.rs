@@ -0,0 +1,69 @@
+// Copyright 2020, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use super::RpcStatus;
+use bytes::Bytes;
+use futures::{channel::mpsc, stream::BoxStream, Stream, StreamExt};
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The encoded response of a service method
+pub enum Body {
+    /// The single response of a unary method
+    Single(Bytes),
+    /// The responses of a server-streaming method
+    Streaming(BoxStream<'static, Result<Bytes, RpcStatus>>),
+}
+
+/// The responses returned by a server-streaming method. The channel capacity bounds the number of responses that are
+/// produced ahead of those sent to the client.
+pub struct Streaming<T> {
+    inner: mpsc::Receiver<Result<T, RpcStatus>>,
+}
+
+impl<T> Streaming<T> {
+    pub fn new(inner: mpsc::Receiver<Result<T, RpcStatus>>) -> Self {
+        Self { inner }
+    }
+
+    /// A stream that ends without any responses
+    pub fn empty() -> Self {
+        let (_, inner) = mpsc::channel(0);
+        Self { inner }
+    }
+}
+
+impl<T> Stream for Streaming<T> {
+    type Item = Result<T, RpcStatus>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+impl<T> From<mpsc::Receiver<Result<T, RpcStatus>>> for Streaming<T> {
+    fn from(inner: mpsc::Receiver<Result<T, RpcStatus>>) -> Self {
+        Self::new(inner)
+    }
+}
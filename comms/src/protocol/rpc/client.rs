@@ -0,0 +1,409 @@
+// Copyright 2020, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use super::{
+    framing::{self, CanonicalFraming},
+    message::RpcMessageFlags,
+    RpcError,
+    DEFAULT_MAX_FRAME_SIZE,
+};
+use crate::{
+    connection_manager::PeerConnection,
+    message::MessageExt,
+    proto::rpc::{RpcRequest, RpcResponse},
+    protocol::ProtocolId,
+};
+use bytes::Bytes;
+use futures::{
+    channel::{mpsc, oneshot},
+    AsyncRead,
+    AsyncWrite,
+    SinkExt,
+    Stream,
+    StreamExt,
+};
+use log::*;
+use prost::Message;
+use std::{
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{task, time};
+
+const LOG_TARGET: &str = "comms::protocol::rpc::client";
+/// The number of responses that are read from the substream ahead of the caller
+const RESPONSE_CHANNEL_SIZE: usize = 5;
+
+#[derive(Debug, Clone)]
+pub struct RpcClientConfig {
+    /// The length of time the server has to respond to a request, and for a server-streaming request the length of
+    /// time between responses. None means requests never time out.
+    pub deadline: Option<Duration>,
+    /// Extra time the client waits beyond the deadline to allow for the round trip to the server
+    pub deadline_grace_period: Duration,
+    /// The maximum size of a request or response frame
+    pub max_frame_size: usize,
+}
+
+impl Default for RpcClientConfig {
+    fn default() -> Self {
+        Self {
+            deadline: Some(Duration::from_secs(30)),
+            deadline_grace_period: Duration::from_secs(1),
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+        }
+    }
+}
+
+/// A client session with an RPC service. Requests are sent one at a time over the session's substream; clones of the
+/// client share the session.
+#[derive(Clone)]
+pub struct RpcClient {
+    request_tx: mpsc::Sender<ClientRequest>,
+}
+
+impl RpcClient {
+    /// Open a substream for the protocol on the peer connection and start a session with the service
+    pub async fn connect_peer(
+        connection: &mut PeerConnection,
+        protocol: &ProtocolId,
+        config: RpcClientConfig,
+    ) -> Result<Self, RpcError>
+    {
+        let substream = connection.open_substream(protocol).await?;
+        Ok(Self::connect(config, substream.stream))
+    }
+
+    /// Start a session over a substream on which the service protocol has been negotiated
+    pub fn connect<TSubstream>(config: RpcClientConfig, substream: TSubstream) -> Self
+    where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + 'static {
+        let (request_tx, request_rx) = mpsc::channel(1);
+        let framed = framing::canonical(substream, config.max_frame_size);
+        task::spawn(RpcClientWorker::new(config, request_rx, framed).run());
+        Self { request_tx }
+    }
+
+    /// Make a request to a unary method
+    pub async fn request_response<T, R>(&mut self, request: T, method: u32) -> Result<R, RpcError>
+    where
+        T: prost::Message,
+        R: prost::Message + Default,
+    {
+        let mut responses = self.call(method, request).await?;
+        let response = responses.next().await.ok_or(RpcError::ServerClosedRequest)??;
+        let payload = response.into_result()?;
+        Ok(R::decode(payload)?)
+    }
+
+    /// Make a request to a server-streaming method. Dropping the returned stream before it ends cancels the request.
+    pub async fn server_streaming<T, R>(&mut self, request: T, method: u32) -> Result<ClientStreaming<R>, RpcError>
+    where
+        T: prost::Message,
+        R: prost::Message + Default,
+    {
+        let responses = self.call(method, request).await?;
+        Ok(ClientStreaming::new(responses))
+    }
+
+    /// Close the session. Requests that are in progress are completed.
+    pub fn close(&mut self) {
+        self.request_tx.close_channel();
+    }
+
+    pub fn is_connected(&self) -> bool {
+        !self.request_tx.is_closed()
+    }
+
+    async fn call<T: prost::Message>(
+        &mut self,
+        method: u32,
+        request: T,
+    ) -> Result<mpsc::Receiver<Result<RpcResponse, RpcError>>, RpcError>
+    {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.request_tx
+            .send(ClientRequest::SendRequest {
+                method,
+                payload: request.to_encoded_bytes().into(),
+                reply_tx,
+            })
+            .await
+            .map_err(|_| RpcError::ClientClosed)?;
+        reply_rx.await.map_err(|_| RpcError::ClientClosed)
+    }
+}
+
+/// The responses of a server-streaming request
+pub struct ClientStreaming<T> {
+    inner: mpsc::Receiver<Result<RpcResponse, RpcError>>,
+    is_complete: bool,
+    _message: PhantomData<T>,
+}
+
+impl<T> ClientStreaming<T> {
+    fn new(inner: mpsc::Receiver<Result<RpcResponse, RpcError>>) -> Self {
+        Self {
+            inner,
+            is_complete: false,
+            _message: PhantomData,
+        }
+    }
+}
+
+impl<T: prost::Message + Default> Stream for ClientStreaming<T> {
+    type Item = Result<T, RpcError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.is_complete {
+            return Poll::Ready(None);
+        }
+
+        match futures::ready!(self.inner.poll_next_unpin(cx)) {
+            Some(Ok(response)) => {
+                if response.is_fin() {
+                    // The last response does not carry a message, but may carry an error status
+                    self.is_complete = true;
+                    return Poll::Ready(response.into_result().err().map(|status| Err(status.into())));
+                }
+                let item = response
+                    .into_result()
+                    .map_err(RpcError::from)
+                    .and_then(|payload| T::decode(payload).map_err(Into::into));
+                Poll::Ready(Some(item))
+            },
+            Some(Err(err)) => {
+                self.is_complete = true;
+                Poll::Ready(Some(Err(err)))
+            },
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+impl<T> Unpin for ClientStreaming<T> {}
+
+enum ClientRequest {
+    SendRequest {
+        method: u32,
+        payload: Bytes,
+        reply_tx: oneshot::Sender<mpsc::Receiver<Result<RpcResponse, RpcError>>>,
+    },
+}
+
+struct RpcClientWorker<TSubstream> {
+    config: RpcClientConfig,
+    request_rx: mpsc::Receiver<ClientRequest>,
+    framed: CanonicalFraming<TSubstream>,
+    next_request_id: u32,
+}
+
+impl<TSubstream> RpcClientWorker<TSubstream>
+where TSubstream: AsyncRead + AsyncWrite + Unpin
+{
+    fn new(
+        config: RpcClientConfig,
+        request_rx: mpsc::Receiver<ClientRequest>,
+        framed: CanonicalFraming<TSubstream>,
+    ) -> Self
+    {
+        Self {
+            config,
+            request_rx,
+            framed,
+            next_request_id: 0,
+        }
+    }
+
+    async fn run(mut self) {
+        while let Some(request) = self.request_rx.next().await {
+            match request {
+                ClientRequest::SendRequest {
+                    method,
+                    payload,
+                    reply_tx,
+                } => {
+                    let (response_tx, response_rx) = mpsc::channel(RESPONSE_CHANNEL_SIZE);
+                    if reply_tx.send(response_rx).is_err() {
+                        continue;
+                    }
+                    if let Err(err) = self.do_request(method, payload, response_tx).await {
+                        debug!(target: LOG_TARGET, "RPC client session ended because '{}'", err);
+                        break;
+                    }
+                },
+            }
+        }
+        self.request_rx.close();
+        let _ = self.framed.close().await;
+    }
+
+    /// Send the request and forward the responses to the caller. An error is returned if the session can no longer be
+    /// used.
+    async fn do_request(
+        &mut self,
+        method: u32,
+        payload: Bytes,
+        mut response_tx: mpsc::Sender<Result<RpcResponse, RpcError>>,
+    ) -> Result<(), RpcError>
+    {
+        let request_id = self.next_request_id;
+        self.next_request_id = self.next_request_id.wrapping_add(1);
+        let request = RpcRequest {
+            request_id,
+            method,
+            flags: 0,
+            deadline: self.config.deadline.map(|d| d.as_millis() as u64).unwrap_or(0),
+            payload: payload.to_vec(),
+        };
+        self.framed.send(request.to_encoded_bytes().into()).await?;
+
+        loop {
+            match self.read_response(request_id).await {
+                Ok(response) => {
+                    let is_fin = response.is_fin();
+                    // Waiting for the caller to take the response stops reading from the substream, so the server
+                    // cannot get ahead of the caller
+                    if response_tx.send(Ok(response)).await.is_err() {
+                        if !is_fin {
+                            debug!(target: LOG_TARGET, "Request {} cancelled by the caller", request_id);
+                            self.cancel_request(request_id).await?;
+                        }
+                        return Ok(());
+                    }
+                    if is_fin {
+                        return Ok(());
+                    }
+                },
+                Err(RpcError::RequestTimedOut) => {
+                    debug!(target: LOG_TARGET, "Request {} timed out", request_id);
+                    self.cancel_request(request_id).await?;
+                    let _ = response_tx.send(Err(RpcError::RequestTimedOut)).await;
+                    return Ok(());
+                },
+                Err(err) => {
+                    let _ = response_tx.send(Err(RpcError::ServerClosedRequest)).await;
+                    return Err(err);
+                },
+            }
+        }
+    }
+
+    async fn cancel_request(&mut self, request_id: u32) -> Result<(), RpcError> {
+        let cancel = RpcRequest {
+            request_id,
+            flags: RpcMessageFlags::FIN.bits(),
+            ..Default::default()
+        };
+        self.framed.send(cancel.to_encoded_bytes().into()).await?;
+        Ok(())
+    }
+
+    /// Read the next response for the request, discarding late responses to earlier requests that timed out or were
+    /// cancelled
+    async fn read_response(&mut self, request_id: u32) -> Result<RpcResponse, RpcError> {
+        let timeout = self.config.deadline.map(|d| d + self.config.deadline_grace_period);
+        loop {
+            let next = match timeout {
+                Some(timeout) => time::timeout(timeout, self.framed.next())
+                    .await
+                    .map_err(|_| RpcError::RequestTimedOut)?,
+                None => self.framed.next().await,
+            };
+            let frame = next.ok_or(RpcError::ServerClosedRequest)??;
+            let response = RpcResponse::decode(frame)?;
+            if response.request_id == request_id {
+                return Ok(response);
+            }
+            trace!(
+                target: LOG_TARGET,
+                "Discarding response for request {} while waiting for request {}",
+                response.request_id,
+                request_id
+            );
+        }
+    }
+}
+
+/// Defines a typed client for an RPC service. Each method is declared with its method id, whether it is `unary` or
+/// `streaming` and its request and response messages.
+///
+/// ```ignore
+/// rpc_client! {
+///     pub struct BlockSyncClient for b"/tari/block-sync/1.0.0" {
+///         1 => unary fn get_chain_metadata(GetChainMetadataRequest) -> ChainMetadata;
+///         2 => streaming fn sync_blocks(SyncBlocksRequest) -> Block;
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! rpc_client {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident for $protocol:literal {
+            $($method_id:literal => $kind:ident fn $method:ident($request:ty) -> $response:ty;)*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone)]
+        $vis struct $name {
+            inner: $crate::protocol::rpc::RpcClient,
+        }
+
+        impl $name {
+            pub fn protocol_id() -> $crate::protocol::ProtocolId {
+                $crate::protocol::ProtocolId::from_static($protocol)
+            }
+
+            pub async fn connect_peer(
+                connection: &mut $crate::PeerConnection,
+                config: $crate::protocol::rpc::RpcClientConfig,
+            ) -> Result<Self, $crate::protocol::rpc::RpcError>
+            {
+                let inner =
+                    $crate::protocol::rpc::RpcClient::connect_peer(connection, &Self::protocol_id(), config).await?;
+                Ok(Self { inner })
+            }
+
+            pub fn close(&mut self) {
+                self.inner.close();
+            }
+
+            $($crate::rpc_client!(@method $kind $method_id $method $request, $response);)*
+        }
+    };
+    (@method unary $method_id:literal $method:ident $request:ty, $response:ty) => {
+        pub async fn $method(&mut self, request: $request) -> Result<$response, $crate::protocol::rpc::RpcError> {
+            self.inner.request_response(request, $method_id).await
+        }
+    };
+    (@method streaming $method_id:literal $method:ident $request:ty, $response:ty) => {
+        pub async fn $method(
+            &mut self,
+            request: $request,
+        ) -> Result<$crate::protocol::rpc::ClientStreaming<$response>, $crate::protocol::rpc::RpcError>
+        {
+            self.inner.server_streaming(request, $method_id).await
+        }
+    };
+}
@@ -0,0 +1,52 @@
+// Copyright 2020, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use super::RpcStatus;
+use crate::{connection_manager::PeerConnectionError, protocol::ProtocolError};
+use derive_error::Error;
+use std::io;
+
+#[derive(Debug, Error)]
+pub enum RpcError {
+    DecodeError(prost::DecodeError),
+    IoError(io::Error),
+    ProtocolError(ProtocolError),
+    PeerConnectionError(PeerConnectionError),
+    /// The server responded to the request with an error status
+    #[error(no_from, non_std)]
+    RequestFailed(RpcStatus),
+    /// The client connection has been closed
+    ClientClosed,
+    /// The server closed the session before the response was received
+    ServerClosedRequest,
+    /// The server did not respond before the deadline of the request was reached
+    RequestTimedOut,
+    /// The peer sent a message that does not follow the RPC protocol
+    #[error(msg_embedded, no_from, non_std)]
+    ProtocolViolation(String),
+}
+
+impl From<RpcStatus> for RpcError {
+    fn from(status: RpcStatus) -> Self {
+        RpcError::RequestFailed(status)
+    }
+}
@@ -0,0 +1,165 @@
+// Copyright 2020, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use super::RpcStatus;
+use crate::proto::rpc::{RpcRequest, RpcResponse};
+use bitflags::bitflags;
+use bytes::Bytes;
+use std::time::Duration;
+
+bitflags! {
+    pub struct RpcMessageFlags: u32 {
+        /// Set on the last response for a request. When set on a request, the client cancels the request in progress.
+        const FIN = 0x01;
+    }
+}
+
+/// An RPC request as received by a service method
+#[derive(Debug)]
+pub struct Request<T> {
+    method: u32,
+    deadline: Option<Duration>,
+    message: T,
+}
+
+impl<T> Request<T> {
+    pub fn new(method: u32, message: T) -> Self {
+        Self {
+            method,
+            deadline: None,
+            message,
+        }
+    }
+
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn method(&self) -> u32 {
+        self.method
+    }
+
+    /// The length of time that the client will wait for a response
+    pub fn deadline(&self) -> Option<Duration> {
+        self.deadline
+    }
+
+    pub fn message(&self) -> &T {
+        &self.message
+    }
+
+    pub fn into_message(self) -> T {
+        self.message
+    }
+
+    pub fn map<F, U>(self, f: F) -> Request<U>
+    where F: FnOnce(T) -> U {
+        Request {
+            method: self.method,
+            deadline: self.deadline,
+            message: f(self.message),
+        }
+    }
+}
+
+impl Request<Bytes> {
+    pub fn decode<T: prost::Message + Default>(self) -> Result<Request<T>, RpcStatus> {
+        let message = T::decode(self.message.clone()).map_err(|err| RpcStatus::bad_request(err.to_string()))?;
+        Ok(self.map(|_| message))
+    }
+}
+
+impl From<RpcRequest> for Request<Bytes> {
+    fn from(request: RpcRequest) -> Self {
+        let request_deadline = request.deadline;
+        let req = Request::new(request.method, Bytes::from(request.payload));
+        if request_deadline > 0 {
+            req.with_deadline(Duration::from_millis(request_deadline))
+        } else {
+            req
+        }
+    }
+}
+
+/// An RPC response message returned by a unary service method
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response<T> {
+    pub message: T,
+}
+
+impl<T> Response<T> {
+    pub fn new(message: T) -> Self {
+        Self { message }
+    }
+
+    pub fn into_message(self) -> T {
+        self.message
+    }
+}
+
+impl RpcRequest {
+    pub(super) fn flags(&self) -> RpcMessageFlags {
+        RpcMessageFlags::from_bits_truncate(self.flags)
+    }
+
+    pub(super) fn is_cancel(&self) -> bool {
+        self.flags().contains(RpcMessageFlags::FIN)
+    }
+}
+
+impl RpcResponse {
+    pub(super) fn ok(request_id: u32, payload: Vec<u8>, flags: RpcMessageFlags) -> Self {
+        Self {
+            request_id,
+            status: RpcStatus::ok().as_code(),
+            flags: flags.bits(),
+            payload,
+        }
+    }
+
+    pub(super) fn error(request_id: u32, status: &RpcStatus) -> Self {
+        Self {
+            request_id,
+            status: status.as_code(),
+            flags: RpcMessageFlags::FIN.bits(),
+            payload: status.details_for_client().as_bytes().to_vec(),
+        }
+    }
+
+    pub(super) fn flags(&self) -> RpcMessageFlags {
+        RpcMessageFlags::from_bits_truncate(self.flags)
+    }
+
+    pub(super) fn is_fin(&self) -> bool {
+        self.flags().contains(RpcMessageFlags::FIN)
+    }
+
+    /// Convert the response into the payload, or the status if the request failed
+    pub(super) fn into_result(self) -> Result<Bytes, RpcStatus> {
+        if self.status == RpcStatus::ok().as_code() {
+            Ok(Bytes::from(self.payload))
+        } else {
+            Err(RpcStatus::from_response(self.status, &self.payload))
+        }
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright 2020, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! # RPC protocol
+//!
+//! Typed request/response RPC over comms substreams. Each service is identified by its protocol id, which is
+//! negotiated when the client opens a substream to the peer. The substream is a session in which the client makes
+//! requests one after the other. A method either replies with a single response (unary) or with a stream of responses
+//! (server-streaming).
+//!
+//! - Deadlines: the client sends its deadline with each request. The server stops waiting for the method when the
+//!   deadline is reached and the client gives up after the deadline and a grace period.
+//! - Cancellation: a client cancels a streaming request by dropping the response stream, and a request that timed out
+//!   is cancelled too.
+//! - Flow control: responses are only read from the substream as fast as the caller takes them, so a streaming method
+//!   cannot get more than a few responses ahead of the client.
+//! - Errors: failed requests are answered with an `RpcStatus` code.
+//!
+//! A server registers its services' protocols with the comms `Protocols` and serves the substreams they are notified
+//! of:
+//!
+//! ```ignore
+//! let server = RpcServer::new().add_service(RpcService::new(PROTOCOL).unary(1, get_header));
+//! let (notif_tx, notif_rx) = mpsc::channel(10);
+//! let protocols = Protocols::new().add(server.protocols(), notif_tx);
+//! task::spawn(server.serve(notif_rx));
+//! ```
+//!
+//! Clients are defined with the `rpc_client!` macro or use `RpcClient` directly.
+
+mod body;
+pub use body::{Body, Streaming};
+
+mod client;
+pub use client::{ClientStreaming, RpcClient, RpcClientConfig};
+
+mod error;
+pub use error::RpcError;
+
+mod framing;
+
+mod message;
+pub use message::{Request, Response, RpcMessageFlags};
+
+mod server;
+pub use server::{RpcServer, RpcServerConfig, RpcService};
+
+mod status;
+pub use status::{RpcStatus, RpcStatusCode};
+
+#[cfg(test)]
+mod test;
+
+/// The default maximum size of a request or response frame
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 4 * 1024 * 1024;
@@ -0,0 +1,423 @@
+// Copyright 2020, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use super::{
+    body::{Body, Streaming},
+    framing::{self, CanonicalFraming},
+    message::{Request, Response, RpcMessageFlags},
+    RpcError,
+    RpcStatus,
+    DEFAULT_MAX_FRAME_SIZE,
+};
+use crate::{
+    message::MessageExt,
+    peer_manager::NodeId,
+    proto::rpc::{RpcRequest, RpcResponse},
+    protocol::{ProtocolEvent, ProtocolId, ProtocolNotification},
+};
+use bytes::Bytes;
+use futures::{
+    channel::mpsc,
+    future::{self, BoxFuture},
+    stream::{BoxStream, FuturesUnordered},
+    AsyncRead,
+    AsyncWrite,
+    Future,
+    FutureExt,
+    SinkExt,
+    StreamExt,
+};
+use log::*;
+use prost::Message;
+use std::{cmp, collections::HashMap, sync::Arc, time::Duration};
+use tokio::{task, time};
+
+const LOG_TARGET: &str = "comms::protocol::rpc::server";
+
+type MethodHandler = Box<dyn Fn(Request<Bytes>) -> BoxFuture<'static, Result<Body, RpcStatus>> + Send + Sync>;
+
+/// An RPC service, identified by its protocol id, made up of typed unary and server-streaming methods.
+///
+/// ```ignore
+/// let service = RpcService::new(ProtocolId::from_static(b"/tari/example/1.0.0"))
+///     .unary(1, |req: Request<PingRequest>| async move { Ok(Response::new(PingResponse::from(req.into_message()))) });
+/// ```
+pub struct RpcService {
+    protocol: ProtocolId,
+    methods: HashMap<u32, MethodHandler>,
+}
+
+impl RpcService {
+    pub fn new(protocol: ProtocolId) -> Self {
+        Self {
+            protocol,
+            methods: HashMap::new(),
+        }
+    }
+
+    pub fn protocol(&self) -> &ProtocolId {
+        &self.protocol
+    }
+
+    /// Add a method that replies to a request with a single response
+    pub fn unary<TReq, TResp, F, Fut>(mut self, method: u32, handler: F) -> Self
+    where
+        TReq: prost::Message + Default + Send + 'static,
+        TResp: prost::Message + 'static,
+        F: Fn(Request<TReq>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Response<TResp>, RpcStatus>> + Send + 'static,
+    {
+        self.methods.insert(
+            method,
+            Box::new(move |request: Request<Bytes>| match request.decode::<TReq>() {
+                Ok(request) => handler(request)
+                    .map(|result| result.map(|response| Body::Single(response.message.to_encoded_bytes().into())))
+                    .boxed(),
+                Err(status) => future::ready(Err(status)).boxed(),
+            }),
+        );
+        self
+    }
+
+    /// Add a method that replies to a request with a stream of responses
+    pub fn server_streaming<TReq, TResp, F, Fut>(mut self, method: u32, handler: F) -> Self
+    where
+        TReq: prost::Message + Default + Send + 'static,
+        TResp: prost::Message + Send + 'static,
+        F: Fn(Request<TReq>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Streaming<TResp>, RpcStatus>> + Send + 'static,
+    {
+        self.methods.insert(
+            method,
+            Box::new(move |request: Request<Bytes>| match request.decode::<TReq>() {
+                Ok(request) => handler(request)
+                    .map(|result| {
+                        result.map(|stream| {
+                            Body::Streaming(
+                                stream
+                                    .map(|item| item.map(|msg| Bytes::from(msg.to_encoded_bytes())))
+                                    .boxed(),
+                            )
+                        })
+                    })
+                    .boxed(),
+                Err(status) => future::ready(Err(status)).boxed(),
+            }),
+        );
+        self
+    }
+
+    fn call(&self, request: Request<Bytes>) -> BoxFuture<'static, Result<Body, RpcStatus>> {
+        match self.methods.get(&request.method()) {
+            Some(handler) => handler(request),
+            None => future::ready(Err(RpcStatus::unsupported_method(format!(
+                "Method {} is not supported by {}",
+                request.method(),
+                String::from_utf8_lossy(&self.protocol)
+            ))))
+            .boxed(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RpcServerConfig {
+    /// The maximum size of a request or response frame
+    pub max_frame_size: usize,
+    /// The maximum number of sessions a single peer can have open at once. Substreams beyond this are closed.
+    pub max_sessions_per_peer: usize,
+    /// A session is closed if the client does not make a request within this time
+    pub session_idle_timeout: Duration,
+    /// The longest time a method is given to respond. Deadlines sent by clients are clamped to this and requests
+    /// without a deadline are given this deadline.
+    pub max_deadline: Duration,
+}
+
+impl Default for RpcServerConfig {
+    fn default() -> Self {
+        Self {
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            max_sessions_per_peer: 10,
+            session_idle_timeout: Duration::from_secs(120),
+            max_deadline: Duration::from_secs(120),
+        }
+    }
+}
+
+/// Serves the registered RPC services. Each inbound substream for a service protocol is an RPC session in which the
+/// client makes requests one after the other.
+pub struct RpcServer {
+    config: RpcServerConfig,
+    services: HashMap<ProtocolId, Arc<RpcService>>,
+}
+
+impl RpcServer {
+    pub fn new() -> Self {
+        Self::with_config(Default::default())
+    }
+
+    pub fn with_config(config: RpcServerConfig) -> Self {
+        Self {
+            config,
+            services: HashMap::new(),
+        }
+    }
+
+    pub fn add_service(mut self, service: RpcService) -> Self {
+        self.services.insert(service.protocol.clone(), Arc::new(service));
+        self
+    }
+
+    /// The protocols of the registered services, which should be added to the comms `Protocols` with the sender of
+    /// the notification channel passed to `serve`
+    pub fn protocols(&self) -> Vec<ProtocolId> {
+        self.services.keys().cloned().collect()
+    }
+
+    /// Start a session for each inbound substream until the notification channel is closed
+    pub async fn serve<TSubstream>(self, notifications: mpsc::Receiver<ProtocolNotification<TSubstream>>)
+    where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + 'static {
+        let mut notifications = notifications.fuse();
+        let mut sessions_per_peer = HashMap::<NodeId, usize>::new();
+        let mut active_sessions = FuturesUnordered::new();
+        loop {
+            futures::select! {
+                notification = notifications.select_next_some() => {
+                    let ProtocolNotification { protocol, event } = notification;
+                    match event {
+                        ProtocolEvent::NewInboundSubstream(node_id, substream) => {
+                            let service = match self.services.get(&protocol) {
+                                Some(service) => Arc::clone(service),
+                                None => {
+                                    warn!(
+                                        target: LOG_TARGET,
+                                        "Received substream for unregistered RPC protocol '{}'",
+                                        String::from_utf8_lossy(&protocol)
+                                    );
+                                    continue;
+                                },
+                            };
+                            let mut framed = framing::canonical(substream, self.config.max_frame_size);
+                            let num_sessions = sessions_per_peer.entry((*node_id).clone()).or_insert(0);
+                            if *num_sessions >= self.config.max_sessions_per_peer {
+                                warn!(
+                                    target: LOG_TARGET,
+                                    "Closing RPC substream from peer '{}' because it already has the maximum of {} sessions",
+                                    node_id.short_str(),
+                                    self.config.max_sessions_per_peer
+                                );
+                                let _ = framed.close().await;
+                                continue;
+                            }
+                            *num_sessions += 1;
+                            let session = ActiveSession::new(*node_id, service, framed, &self.config);
+                            let node_id = session.node_id.clone();
+                            active_sessions.push(task::spawn(session.run()).map(move |_| node_id));
+                        },
+                    }
+                },
+                node_id = active_sessions.select_next_some() => {
+                    if let Some(num_sessions) = sessions_per_peer.get_mut(&node_id) {
+                        *num_sessions -= 1;
+                        if *num_sessions == 0 {
+                            sessions_per_peer.remove(&node_id);
+                        }
+                    }
+                },
+                complete => break,
+            }
+        }
+        debug!(
+            target: LOG_TARGET,
+            "RPC server stopped because the notification channel closed"
+        );
+    }
+}
+
+impl Default for RpcServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct ActiveSession<TSubstream> {
+    node_id: NodeId,
+    service: Arc<RpcService>,
+    framed: CanonicalFraming<TSubstream>,
+    idle_timeout: Duration,
+    max_deadline: Duration,
+}
+
+impl<TSubstream> ActiveSession<TSubstream>
+where TSubstream: AsyncRead + AsyncWrite + Unpin
+{
+    fn new(
+        node_id: NodeId,
+        service: Arc<RpcService>,
+        framed: CanonicalFraming<TSubstream>,
+        config: &RpcServerConfig,
+    ) -> Self
+    {
+        Self {
+            node_id,
+            service,
+            framed,
+            idle_timeout: config.session_idle_timeout,
+            max_deadline: config.max_deadline,
+        }
+    }
+
+    async fn run(mut self) {
+        debug!(
+            target: LOG_TARGET,
+            "RPC session started for peer '{}' on protocol '{}'",
+            self.node_id.short_str(),
+            String::from_utf8_lossy(&self.service.protocol)
+        );
+        if let Err(err) = self.handle_requests().await {
+            debug!(
+                target: LOG_TARGET,
+                "RPC session for peer '{}' ended with an error: {}",
+                self.node_id.short_str(),
+                err
+            );
+        }
+        let _ = self.framed.close().await;
+    }
+
+    async fn handle_requests(&mut self) -> Result<(), RpcError> {
+        loop {
+            let frame = match time::timeout(self.idle_timeout, self.framed.next()).await {
+                Ok(Some(frame)) => frame,
+                Ok(None) => break,
+                Err(_) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Closing RPC session for peer '{}' because no request was made within {:.0?}",
+                        self.node_id.short_str(),
+                        self.idle_timeout
+                    );
+                    break;
+                },
+            };
+            let request = RpcRequest::decode(frame?)?;
+            if request.is_cancel() {
+                // The request that is being cancelled has already completed
+                trace!(
+                    target: LOG_TARGET,
+                    "Ignoring cancellation of completed request {}",
+                    request.request_id
+                );
+                continue;
+            }
+            self.handle_request(request).await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_request(&mut self, request: RpcRequest) -> Result<(), RpcError> {
+        let request_id = request.request_id;
+        let request = Request::from(request);
+        trace!(
+            target: LOG_TARGET,
+            "Request {} for method {} from peer '{}'",
+            request_id,
+            request.method(),
+            self.node_id.short_str()
+        );
+        let deadline = request
+            .deadline()
+            .map(|deadline| cmp::min(deadline, self.max_deadline))
+            .unwrap_or(self.max_deadline);
+        let call = self.service.call(request);
+        let result = match time::timeout(deadline, call).await {
+            Ok(result) => result,
+            Err(_) => Err(RpcStatus::timed_out("Deadline was reached before the method returned")),
+        };
+
+        match result {
+            Ok(Body::Single(payload)) => {
+                self.send_response(RpcResponse::ok(request_id, payload.to_vec(), RpcMessageFlags::FIN))
+                    .await
+            },
+            Ok(Body::Streaming(stream)) => self.stream_responses(request_id, stream).await,
+            Err(status) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Request {} from peer '{}' failed: {}",
+                    request_id,
+                    self.node_id.short_str(),
+                    status
+                );
+                self.send_response(RpcResponse::error(request_id, &status)).await
+            },
+        }
+    }
+
+    /// Send each response as it is produced, stopping early if the client cancels the request. The session does not
+    /// read ahead of the responses that the client has received, which provides flow control.
+    async fn stream_responses(
+        &mut self,
+        request_id: u32,
+        mut stream: BoxStream<'static, Result<Bytes, RpcStatus>>,
+    ) -> Result<(), RpcError>
+    {
+        loop {
+            futures::select! {
+                item = stream.next().fuse() => match item {
+                    Some(Ok(payload)) => {
+                        self.send_response(RpcResponse::ok(request_id, payload.to_vec(), RpcMessageFlags::empty())).await?;
+                    },
+                    Some(Err(status)) => {
+                        return self.send_response(RpcResponse::error(request_id, &status)).await;
+                    },
+                    None => {
+                        return self.send_response(RpcResponse::ok(request_id, Vec::new(), RpcMessageFlags::FIN)).await;
+                    },
+                },
+                frame = self.framed.next().fuse() => match frame {
+                    Some(frame) => {
+                        let request = RpcRequest::decode(frame?)?;
+                        if request.is_cancel() && request.request_id == request_id {
+                            debug!(
+                                target: LOG_TARGET,
+                                "Request {} cancelled by peer '{}'",
+                                request_id,
+                                self.node_id.short_str()
+                            );
+                            return self.send_response(RpcResponse::error(request_id, &RpcStatus::cancelled("Request cancelled by client"))).await;
+                        }
+                        let status = RpcStatus::protocol_error("Request sent while a streaming response is in progress");
+                        self.send_response(RpcResponse::error(request.request_id, &status)).await?;
+                        return Err(RpcError::ProtocolViolation(status.details().to_string()));
+                    },
+                    None => return Err(RpcError::ClientClosed),
+                },
+            }
+        }
+    }
+
+    async fn send_response(&mut self, response: RpcResponse) -> Result<(), RpcError> {
+        self.framed.send(response.to_encoded_bytes().into()).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+// Copyright 2020, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fmt;
+
+/// The status of an RPC response, sent to the client when a request fails
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcStatus {
+    code: RpcStatusCode,
+    details: String,
+}
+
+impl RpcStatus {
+    pub fn ok() -> Self {
+        Self {
+            code: RpcStatusCode::Ok,
+            details: Default::default(),
+        }
+    }
+
+    pub fn unsupported_method<T: ToString>(details: T) -> Self {
+        Self {
+            code: RpcStatusCode::UnsupportedMethod,
+            details: details.to_string(),
+        }
+    }
+
+    pub fn not_implemented<T: ToString>(details: T) -> Self {
+        Self {
+            code: RpcStatusCode::NotImplemented,
+            details: details.to_string(),
+        }
+    }
+
+    pub fn bad_request<T: ToString>(details: T) -> Self {
+        Self {
+            code: RpcStatusCode::BadRequest,
+            details: details.to_string(),
+        }
+    }
+
+    pub fn not_found<T: ToString>(details: T) -> Self {
+        Self {
+            code: RpcStatusCode::NotFound,
+            details: details.to_string(),
+        }
+    }
+
+    /// Used to return a general error to the client. The details are logged on the server and not sent to the client
+    /// as they may contain internal information.
+    pub fn general<T: ToString>(details: T) -> Self {
+        Self {
+            code: RpcStatusCode::General,
+            details: details.to_string(),
+        }
+    }
+
+    pub fn timed_out<T: ToString>(details: T) -> Self {
+        Self {
+            code: RpcStatusCode::Timeout,
+            details: details.to_string(),
+        }
+    }
+
+    pub fn cancelled<T: ToString>(details: T) -> Self {
+        Self {
+            code: RpcStatusCode::Cancelled,
+            details: details.to_string(),
+        }
+    }
+
+    pub(super) fn protocol_error<T: ToString>(details: T) -> Self {
+        Self {
+            code: RpcStatusCode::ProtocolError,
+            details: details.to_string(),
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.code.is_ok()
+    }
+
+    pub fn status_code(&self) -> RpcStatusCode {
+        self.code
+    }
+
+    pub fn as_code(&self) -> u32 {
+        self.code as u32
+    }
+
+    pub fn details(&self) -> &str {
+        &self.details
+    }
+
+    /// The details that may be sent to the client
+    pub(super) fn details_for_client(&self) -> &str {
+        match self.code {
+            RpcStatusCode::General => "",
+            _ => &self.details,
+        }
+    }
+
+    pub(super) fn from_response(code: u32, payload: &[u8]) -> Self {
+        Self {
+            code: code.into(),
+            details: String::from_utf8_lossy(payload).to_string(),
+        }
+    }
+}
+
+impl fmt::Display for RpcStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.code, self.details)
+    }
+}
+
+impl std::error::Error for RpcStatus {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RpcStatusCode {
+    /// Request succeeded
+    Ok = 0,
+    /// Request is incorrect
+    BadRequest = 1,
+    /// The method is not recognised by the service
+    UnsupportedMethod = 2,
+    /// Method is not implemented
+    NotImplemented = 3,
+    /// The deadline of the request was reached before a response was sent
+    Timeout = 4,
+    /// The request was cancelled by the client
+    Cancelled = 5,
+    /// Entity not found
+    NotFound = 6,
+    /// The client or server did not follow the RPC protocol
+    ProtocolError = 7,
+    /// General server error
+    General = 8,
+    /// An unrecognised status code was received
+    Unrecognised = 9,
+}
+
+impl RpcStatusCode {
+    pub fn is_ok(self) -> bool {
+        self == RpcStatusCode::Ok
+    }
+}
+
+impl From<u32> for RpcStatusCode {
+    fn from(code: u32) -> Self {
+        use RpcStatusCode::*;
+        match code {
+            0 => Ok,
+            1 => BadRequest,
+            2 => UnsupportedMethod,
+            3 => NotImplemented,
+            4 => Timeout,
+            5 => Cancelled,
+            6 => NotFound,
+            7 => ProtocolError,
+            8 => General,
+            _ => Unrecognised,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rpc_status_code_conversions() {
+        use RpcStatusCode::*;
+        for code in &[
+            Ok,
+            BadRequest,
+            UnsupportedMethod,
+            NotImplemented,
+            Timeout,
+            Cancelled,
+            NotFound,
+            ProtocolError,
+            General,
+        ] {
+            assert_eq!(RpcStatusCode::from(*code as u32), *code);
+        }
+        assert_eq!(RpcStatusCode::from(123), Unrecognised);
+    }
+
+    #[test]
+    fn general_details_are_not_sent_to_client() {
+        let status = RpcStatus::general("database is locked");
+        assert_eq!(status.details_for_client(), "");
+        let status = RpcStatus::not_found("block 123");
+        assert_eq!(status.details_for_client(), "block 123");
+    }
+}
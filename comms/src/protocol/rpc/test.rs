@@ -0,0 +1,293 @@
+// Copyright 2020, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use super::{
+    ClientStreaming,
+    Request,
+    Response,
+    RpcClient,
+    RpcClientConfig,
+    RpcError,
+    RpcServer,
+    RpcServerConfig,
+    RpcService,
+    RpcStatus,
+    RpcStatusCode,
+    Streaming,
+};
+use crate::{
+    memsocket::MemorySocket,
+    peer_manager::NodeId,
+    protocol::{ProtocolEvent, ProtocolId, ProtocolNotification},
+    test_utils::node_id,
+};
+use futures::{channel::mpsc, SinkExt, StreamExt};
+use std::time::Duration;
+use tari_test_utils::unpack_enum;
+use tokio::{task, time};
+
+const GREETING_PROTOCOL: ProtocolId = ProtocolId::from_static(b"/tari/test/greeting/1.0.0");
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SayHelloRequest {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(uint32, tag = "2")]
+    pub count: u32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SayHelloResponse {
+    #[prost(string, tag = "1")]
+    pub greeting: String,
+}
+
+crate::rpc_client! {
+    struct GreetingClient for b"/tari/test/greeting/1.0.0" {
+        1 => unary fn say_hello(SayHelloRequest) -> SayHelloResponse;
+        2 => streaming fn say_hello_many(SayHelloRequest) -> SayHelloResponse;
+    }
+}
+
+fn greeting_service() -> RpcService {
+    RpcService::new(GREETING_PROTOCOL)
+        .unary(1, |req: Request<SayHelloRequest>| {
+            async move {
+                let req = req.into_message();
+                if req.name.is_empty() {
+                    return Err(RpcStatus::not_found("Nobody to greet"));
+                }
+                if req.count > 0 {
+                    // Used to exceed the deadline
+                    time::delay_for(Duration::from_millis(u64::from(req.count))).await;
+                }
+                Ok(Response::new(SayHelloResponse {
+                    greeting: format!("Hello {}", req.name),
+                }))
+            }
+        })
+        .server_streaming(2, |req: Request<SayHelloRequest>| {
+            async move {
+                let req = req.into_message();
+                let (mut tx, rx) = mpsc::channel(1);
+                task::spawn(async move {
+                    // A count of 0 streams until the client cancels the request
+                    let mut i = 0;
+                    while req.count == 0 || i < req.count {
+                        let greeting = format!("Hello {} #{}", req.name, i);
+                        if tx.send(Ok(SayHelloResponse { greeting })).await.is_err() {
+                            break;
+                        }
+                        i += 1;
+                    }
+                });
+                Ok(Streaming::new(rx))
+            }
+        })
+}
+
+fn serve(config: RpcServerConfig) -> mpsc::Sender<ProtocolNotification<MemorySocket>> {
+    let server = RpcServer::with_config(config).add_service(greeting_service());
+    assert_eq!(server.protocols(), vec![GreetingClient::protocol_id()]);
+    let (notif_tx, notif_rx) = mpsc::channel(1);
+    task::spawn(server.serve(notif_rx));
+    notif_tx
+}
+
+async fn connect(
+    notif_tx: &mut mpsc::Sender<ProtocolNotification<MemorySocket>>,
+    node_id: NodeId,
+    config: RpcClientConfig,
+) -> GreetingClient
+{
+    let (client_socket, server_socket) = MemorySocket::new_pair();
+    notif_tx
+        .send(ProtocolNotification::new(
+            GREETING_PROTOCOL,
+            ProtocolEvent::NewInboundSubstream(Box::new(node_id), server_socket),
+        ))
+        .await
+        .unwrap();
+
+    GreetingClient {
+        inner: RpcClient::connect(config, client_socket),
+    }
+}
+
+async fn setup(config: RpcClientConfig) -> GreetingClient {
+    let mut notif_tx = serve(Default::default());
+    connect(&mut notif_tx, node_id::random(), config).await
+}
+
+fn request(name: &str, count: u32) -> SayHelloRequest {
+    SayHelloRequest {
+        name: name.to_string(),
+        count,
+    }
+}
+
+#[tokio_macros::test_basic]
+async fn request_response() {
+    let mut client = setup(Default::default()).await;
+    let response = client.say_hello(request("Tari", 0)).await.unwrap();
+    assert_eq!(response.greeting, "Hello Tari");
+    let response = client.say_hello(request("Comms", 0)).await.unwrap();
+    assert_eq!(response.greeting, "Hello Comms");
+}
+
+#[tokio_macros::test_basic]
+async fn request_failed_with_status() {
+    let mut client = setup(Default::default()).await;
+    let err = client.say_hello(request("", 0)).await.unwrap_err();
+    unpack_enum!(RpcError::RequestFailed(status) = err);
+    assert_eq!(status.status_code(), RpcStatusCode::NotFound);
+    assert_eq!(status.details(), "Nobody to greet");
+}
+
+#[tokio_macros::test_basic]
+async fn unsupported_method() {
+    let mut client = setup(Default::default()).await;
+    let err = client
+        .inner
+        .request_response::<_, SayHelloResponse>(request("Tari", 0), 123)
+        .await
+        .unwrap_err();
+    unpack_enum!(RpcError::RequestFailed(status) = err);
+    assert_eq!(status.status_code(), RpcStatusCode::UnsupportedMethod);
+}
+
+#[tokio_macros::test_basic]
+async fn server_streaming() {
+    let mut client = setup(Default::default()).await;
+    let responses = client
+        .say_hello_many(request("Tari", 3))
+        .await
+        .unwrap()
+        .map(|r| r.unwrap().greeting)
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(responses, vec!["Hello Tari #0", "Hello Tari #1", "Hello Tari #2"]);
+}
+
+#[tokio_macros::test_basic]
+async fn server_streaming_cancelled_by_client() {
+    let mut client = setup(Default::default()).await;
+    let mut responses: ClientStreaming<SayHelloResponse> = client.say_hello_many(request("Tari", 0)).await.unwrap();
+    let first = responses.next().await.unwrap().unwrap();
+    assert_eq!(first.greeting, "Hello Tari #0");
+    drop(responses);
+
+    // The session can be used for further requests once the stream is cancelled
+    let response = client.say_hello(request("again", 0)).await.unwrap();
+    assert_eq!(response.greeting, "Hello again");
+}
+
+#[tokio_macros::test_basic]
+async fn deadline_reached() {
+    let mut client = setup(RpcClientConfig {
+        deadline: Some(Duration::from_millis(50)),
+        deadline_grace_period: Duration::from_millis(500),
+        ..Default::default()
+    })
+    .await;
+    let err = client.say_hello(request("Tari", 1000)).await.unwrap_err();
+    unpack_enum!(RpcError::RequestFailed(status) = err);
+    assert_eq!(status.status_code(), RpcStatusCode::Timeout);
+
+    let response = client.say_hello(request("Tari", 0)).await.unwrap();
+    assert_eq!(response.greeting, "Hello Tari");
+}
+
+#[tokio_macros::test_basic]
+async fn client_closed() {
+    let mut client = setup(Default::default()).await;
+    client.close();
+    let err = client.say_hello(request("Tari", 0)).await.unwrap_err();
+    unpack_enum!(RpcError::ClientClosed = err);
+}
+
+#[tokio_macros::test_basic]
+async fn max_deadline() {
+    let mut notif_tx = serve(RpcServerConfig {
+        max_deadline: Duration::from_millis(50),
+        ..Default::default()
+    });
+    let mut client = connect(&mut notif_tx, node_id::random(), RpcClientConfig {
+        deadline: None,
+        ..Default::default()
+    })
+    .await;
+    // A request without a deadline is given the server's maximum deadline
+    let err = client.say_hello(request("Tari", 1000)).await.unwrap_err();
+    unpack_enum!(RpcError::RequestFailed(status) = err);
+    assert_eq!(status.status_code(), RpcStatusCode::Timeout);
+
+    let mut client = connect(&mut notif_tx, node_id::random(), RpcClientConfig {
+        deadline: Some(Duration::from_secs(60)),
+        ..Default::default()
+    })
+    .await;
+    // A longer deadline is clamped to the server's maximum deadline
+    let err = client.say_hello(request("Tari", 1000)).await.unwrap_err();
+    unpack_enum!(RpcError::RequestFailed(status) = err);
+    assert_eq!(status.status_code(), RpcStatusCode::Timeout);
+}
+
+#[tokio_macros::test_basic]
+async fn session_idle_timeout() {
+    let mut notif_tx = serve(RpcServerConfig {
+        session_idle_timeout: Duration::from_millis(50),
+        ..Default::default()
+    });
+    let mut client = connect(&mut notif_tx, node_id::random(), Default::default()).await;
+    let response = client.say_hello(request("Tari", 0)).await.unwrap();
+    assert_eq!(response.greeting, "Hello Tari");
+
+    time::delay_for(Duration::from_millis(200)).await;
+    assert!(client.say_hello(request("Tari", 0)).await.is_err());
+}
+
+#[tokio_macros::test_basic]
+async fn max_sessions_per_peer() {
+    let mut notif_tx = serve(RpcServerConfig {
+        max_sessions_per_peer: 1,
+        ..Default::default()
+    });
+    let node_id = node_id::random();
+    let mut client1 = connect(&mut notif_tx, node_id.clone(), Default::default()).await;
+    let mut client2 = connect(&mut notif_tx, node_id.clone(), Default::default()).await;
+    let response = client1.say_hello(request("Tari", 0)).await.unwrap();
+    assert_eq!(response.greeting, "Hello Tari");
+    assert!(client2.say_hello(request("Tari", 0)).await.is_err());
+
+    // Other peers have their own limit
+    let mut client3 = connect(&mut notif_tx, node_id::random(), Default::default()).await;
+    let response = client3.say_hello(request("Comms", 0)).await.unwrap();
+    assert_eq!(response.greeting, "Hello Comms");
+
+    // Once the session is closed the peer can start another one
+    client1.close();
+    time::delay_for(Duration::from_millis(50)).await;
+    let mut client4 = connect(&mut notif_tx, node_id, Default::default()).await;
+    let response = client4.say_hello(request("again", 0)).await.unwrap();
+    assert_eq!(response.greeting, "Hello again");
+}
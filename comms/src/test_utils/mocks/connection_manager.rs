@@ -141,6 +141,19 @@ impl ConnectionManagerMock {
                     )
                     .unwrap();
             },
+            DialUnverifiedPeer(peer, reply_tx) => {
+                reply_tx
+                    .send(
+                        self.state
+                            .active_conns
+                            .lock()
+                            .await
+                            .get(&peer.node_id)
+                            .map(Clone::clone)
+                            .ok_or_else(|| ConnectionManagerError::DialConnectFailedAllAddresses),
+                    )
+                    .unwrap();
+            },
             NotifyListening(_reply_tx) => {},
             GetActiveConnection(node_id, reply_tx) => {
                 reply_tx
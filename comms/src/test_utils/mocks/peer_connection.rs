@@ -23,6 +23,7 @@
 use crate::{
     connection_manager::{
         ConnectionDirection,
+        CountingSocket,
         NegotiatedSubstream,
         PeerConnection,
         PeerConnectionError,
@@ -32,6 +33,7 @@ use crate::{
     multiplexing::{IncomingSubstreams, Yamux},
     peer_manager::NodeId,
     test_utils::transport,
+    types::CommsSubstream,
 };
 use futures::{channel::mpsc, lock::Mutex, stream::Fuse, StreamExt};
 use std::sync::{
@@ -65,9 +67,23 @@ pub async fn create_peer_connection_mock_pair(
     rt_handle.spawn(mock.run());
 
     (
-        PeerConnection::new(1, tx1, node_id_in, listen_addr.clone(), ConnectionDirection::Inbound),
+        PeerConnection::new(
+            1,
+            tx1,
+            node_id_in,
+            listen_addr.clone(),
+            ConnectionDirection::Inbound,
+            Default::default(),
+        ),
         mock_state_in,
-        PeerConnection::new(2, tx2, node_id_out, listen_addr, ConnectionDirection::Outbound),
+        PeerConnection::new(
+            2,
+            tx2,
+            node_id_out,
+            listen_addr,
+            ConnectionDirection::Outbound,
+            Default::default(),
+        ),
         mock_state_out,
     )
 }
@@ -96,12 +112,14 @@ impl PeerConnectionMockState {
         self.call_count.load(Ordering::SeqCst)
     }
 
-    pub async fn open_substream(&self) -> Result<yamux::Stream, PeerConnectionError> {
-        self.mux_control.lock().await.open_stream().await.map_err(Into::into)
+    pub async fn open_substream(&self) -> Result<CommsSubstream, PeerConnectionError> {
+        let stream = self.mux_control.lock().await.open_stream().await?;
+        Ok(CountingSocket::new(stream, Default::default()))
     }
 
-    pub async fn next_incoming_substream(&self) -> Option<yamux::Stream> {
-        self.mux_incoming.lock().await.next().await
+    pub async fn next_incoming_substream(&self) -> Option<CommsSubstream> {
+        let stream = self.mux_incoming.lock().await.next().await?;
+        Some(CountingSocket::new(stream, Default::default()))
     }
 
     pub async fn disconnect(&self) {
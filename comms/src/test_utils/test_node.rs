@@ -28,6 +28,7 @@ use crate::{
     protocol::Protocols,
     runtime,
     transports::MemoryTransport,
+    types::CommsSubstream,
 };
 use futures::channel::mpsc;
 use rand::rngs::OsRng;
@@ -70,7 +71,7 @@ impl Default for TestNodeConfig {
 pub fn build_connection_manager(
     config: TestNodeConfig,
     peer_manager: Arc<PeerManager>,
-    protocols: Protocols<yamux::Stream>,
+    protocols: Protocols<CommsSubstream>,
     shutdown: ShutdownSignal,
 ) -> ConnectionManagerRequester
 {
@@ -20,7 +20,10 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::peer_manager::{Peer, PeerId};
+use crate::{
+    connection_manager::CountingSocket,
+    peer_manager::{Peer, PeerId},
+};
 use tari_crypto::{common::Blake256, keys::PublicKey, ristretto::RistrettoPublicKey};
 use tari_storage::lmdb_store::LMDBStore;
 #[cfg(test)]
@@ -50,4 +53,5 @@ pub type CommsDatabase = LMDBWrapper<PeerId, Peer>;
 #[cfg(test)]
 pub type CommsDatabase = HashmapDatabase<PeerId, Peer>;
 
-pub type CommsSubstream = yamux::Stream;
+/// A negotiated substream. Bytes sent and received on the substream are recorded against its protocol.
+pub type CommsSubstream = CountingSocket<yamux::Stream>;
@@ -23,4 +23,5 @@
 pub mod cidr;
 pub mod datetime;
 pub mod multiaddr;
+pub mod proof_of_work;
 pub mod signature;
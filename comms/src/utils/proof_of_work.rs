@@ -0,0 +1,94 @@
+// Copyright 2020, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Proof-of-work bound to a node's public key.
+//!
+//! The work is the number of leading zero bits of `Challenge(public_key || nonce)`. Requiring a minimum difficulty
+//! makes generating many node identities (and therefore many node ids) expensive, which raises the cost of a sybil
+//! attack on the DHT.
+
+use crate::types::{Challenge, CommsPublicKey};
+use digest::Digest;
+use tari_crypto::tari_utilities::ByteArray;
+
+/// Returns the proof-of-work difficulty, as the number of leading zero bits, of the given public key and nonce.
+pub fn difficulty(public_key: &CommsPublicKey, nonce: u64) -> u32 {
+    let hash = Challenge::new()
+        .chain(public_key.as_bytes())
+        .chain(nonce.to_le_bytes())
+        .result();
+
+    let mut leading_zeros = 0;
+    for byte in hash.iter() {
+        leading_zeros += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    leading_zeros
+}
+
+/// Returns true if the public key and nonce meet the required difficulty. A required difficulty of zero is always met.
+pub fn verify(public_key: &CommsPublicKey, nonce: u64, required_difficulty: u32) -> bool {
+    required_difficulty == 0 || difficulty(public_key, nonce) >= required_difficulty
+}
+
+/// Searches for a nonce, starting at `start_nonce`, which meets the required difficulty for the given public key.
+/// Returns None if the difficulty cannot be met by any nonce.
+pub fn find_nonce(public_key: &CommsPublicKey, required_difficulty: u32, start_nonce: u64) -> Option<u64> {
+    if required_difficulty as usize > Challenge::output_size() * 8 {
+        return None;
+    }
+
+    let mut nonce = start_nonce;
+    loop {
+        if verify(public_key, nonce, required_difficulty) {
+            return Some(nonce);
+        }
+        nonce = nonce.wrapping_add(1);
+        if nonce == start_nonce {
+            return None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::OsRng;
+    use tari_crypto::keys::PublicKey;
+
+    #[test]
+    fn find_nonce_and_verify() {
+        let (_, public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        let nonce = find_nonce(&public_key, 8, 0).unwrap();
+        assert!(difficulty(&public_key, nonce) >= 8);
+        assert!(verify(&public_key, nonce, 8));
+        assert!(verify(&public_key, nonce.wrapping_add(1), 0));
+    }
+
+    #[test]
+    fn find_nonce_impossible_difficulty() {
+        let (_, public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        assert!(find_nonce(&public_key, 257, 0).is_none());
+    }
+}
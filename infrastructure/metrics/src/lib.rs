@@ -0,0 +1,129 @@
+// Copyright 2020, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! # Tari metrics
+//!
+//! A thin facade over the `prometheus` crate used by the comms, DHT, core and wallet crates. Every metric is registered
+//! in a single process-wide registry and is prefixed with the `tari` namespace. The registered metrics can be rendered
+//! in the Prometheus text exposition format using [encode_text](self::encode_text).
+//!
+//! Metric definitions are static, so an invalid definition (e.g. an invalid name) is a programming error and will
+//! panic. A metric that cannot be registered (e.g. a duplicate name) is still returned so that it can be updated, but
+//! it will not be exported.
+
+use log::*;
+use prometheus::{core::Collector, Encoder, HistogramOpts, Opts, TextEncoder};
+
+pub use prometheus::{Histogram, HistogramTimer, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec};
+
+const LOG_TARGET: &str = "metrics";
+
+/// The namespace prepended to all metric names
+pub const NAMESPACE: &str = "tari";
+
+/// The content type of the text returned by [encode_text](self::encode_text)
+pub const TEXT_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Buckets (in seconds) suitable for timing operations that usually take between a millisecond and a minute
+pub const DURATION_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+];
+
+pub fn register_int_counter(name: &str, help: &str) -> IntCounter {
+    register(IntCounter::with_opts(opts(name, help)))
+}
+
+pub fn register_int_counter_vec(name: &str, help: &str, labels: &[&str]) -> IntCounterVec {
+    register(IntCounterVec::new(opts(name, help), labels))
+}
+
+pub fn register_int_gauge(name: &str, help: &str) -> IntGauge {
+    register(IntGauge::with_opts(opts(name, help)))
+}
+
+pub fn register_int_gauge_vec(name: &str, help: &str, labels: &[&str]) -> IntGaugeVec {
+    register(IntGaugeVec::new(opts(name, help), labels))
+}
+
+pub fn register_histogram(name: &str, help: &str, buckets: &[f64]) -> Histogram {
+    register(Histogram::with_opts(histogram_opts(name, help, buckets)))
+}
+
+pub fn register_histogram_vec(name: &str, help: &str, buckets: &[f64], labels: &[&str]) -> HistogramVec {
+    register(HistogramVec::new(histogram_opts(name, help, buckets), labels))
+}
+
+/// Render all registered metrics in the Prometheus text exposition format
+pub fn encode_text() -> String {
+    let metric_families = prometheus::gather();
+    let mut buf = Vec::new();
+    if let Err(err) = TextEncoder::new().encode(&metric_families, &mut buf) {
+        error!(target: LOG_TARGET, "Failed to encode metrics because '{}'", err);
+    }
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+fn opts(name: &str, help: &str) -> Opts {
+    Opts::new(name, help).namespace(NAMESPACE)
+}
+
+fn histogram_opts(name: &str, help: &str, buckets: &[f64]) -> HistogramOpts {
+    HistogramOpts::new(name, help)
+        .namespace(NAMESPACE)
+        .buckets(buckets.to_vec())
+}
+
+fn register<T>(metric: prometheus::Result<T>) -> T
+where T: Collector + Clone + 'static {
+    let metric = metric.expect("Invalid metric definition");
+    if let Err(err) = prometheus::register(Box::new(metric.clone())) {
+        warn!(target: LOG_TARGET, "Metric was not registered because '{}'", err);
+    }
+    metric
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn encode() {
+        let counter = register_int_counter("test_counter_total", "A test counter");
+        counter.inc_by(3);
+        let gauge = register_int_gauge_vec("test_gauge", "A test gauge", &["kind"]);
+        gauge.with_label_values(&["a"]).set(5);
+
+        let text = encode_text();
+        assert!(text.contains("tari_test_counter_total 3"));
+        assert!(text.contains("tari_test_gauge{kind=\"a\"} 5"));
+    }
+
+    #[test]
+    fn duplicate_registration() {
+        let counter1 = register_int_counter("test_duplicate_total", "A test counter");
+        let counter2 = register_int_counter("test_duplicate_total", "A test counter");
+        counter1.inc();
+        counter2.inc_by(2);
+
+        assert!(encode_text().contains("tari_test_duplicate_total 1"));
+    }
+}